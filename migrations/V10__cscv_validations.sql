@@ -0,0 +1,19 @@
+-- CSCV overfitting analyses (1:N with sweeps)
+CREATE TABLE IF NOT EXISTS cscv_validations (
+    id                      TEXT PRIMARY KEY,
+    sweep_id                TEXT NOT NULL REFERENCES sweeps(id) ON DELETE CASCADE,
+    n_blocks                INTEGER NOT NULL,
+    n_splits                INTEGER NOT NULL,
+    n_strategies            INTEGER NOT NULL,
+    n_observations          INTEGER NOT NULL,
+    pbo                     REAL NOT NULL,
+    degradation_slope       REAL NOT NULL,
+    degradation_intercept   REAL NOT NULL,
+    degradation_r2          REAL NOT NULL,
+    prob_oos_loss           REAL NOT NULL,
+    median_logit            REAL NOT NULL,
+    pbo_threshold           REAL NOT NULL,
+    rejected                INTEGER NOT NULL,
+    created_at              TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_cscv_sweep ON cscv_validations(sweep_id);
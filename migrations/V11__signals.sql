@@ -0,0 +1,12 @@
+-- Saved signal library: named, versioned `SignalSpec` definitions that
+-- strategies reference with `signal "name"` and specs with `{"type": "Saved"}`.
+-- Every save appends a new version; the highest version is the current one.
+
+CREATE TABLE IF NOT EXISTS signals (
+    name         TEXT NOT NULL COLLATE NOCASE,
+    version      INTEGER NOT NULL,
+    spec         TEXT NOT NULL CHECK(json_valid(spec)),
+    description  TEXT,
+    created_at   TEXT NOT NULL,
+    PRIMARY KEY (name, version)
+);
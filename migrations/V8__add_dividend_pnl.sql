@@ -0,0 +1,2 @@
+-- Add dividend cash flows received (long) or paid (short) on a trade's stock.
+ALTER TABLE trades ADD COLUMN dividend_pnl REAL;
@@ -0,0 +1,27 @@
+-- Background task queue (backtests, sweeps, pipelines, workflows).
+-- Persists task parameters, progress and results so the queue survives
+-- server restarts and `/tasks` can list history.
+
+CREATE TABLE IF NOT EXISTS tasks (
+    id                TEXT PRIMARY KEY,
+    kind              TEXT NOT NULL,                   -- single, sweep, walk_forward, pipeline, workflow
+    strategy          TEXT NOT NULL,
+    symbol            TEXT NOT NULL,
+    thread_id         TEXT,
+    params            TEXT NOT NULL CHECK(json_valid(params)),
+    -- Original submit request, replayed to resume the task after a restart
+    request           TEXT CHECK(request IS NULL OR json_valid(request)),
+    status            TEXT NOT NULL DEFAULT 'queued',  -- queued, running, completed, failed, cancelled, interrupted
+    progress_current  INTEGER NOT NULL DEFAULT 0,
+    progress_total    INTEGER NOT NULL DEFAULT 0,
+    stage_label       TEXT NOT NULL DEFAULT '',
+    created_at        TEXT NOT NULL,
+    started_at        TEXT,
+    completed_at      TEXT,
+    result            TEXT CHECK(result IS NULL OR json_valid(result)),
+    result_id         TEXT,
+    error             TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
+CREATE INDEX IF NOT EXISTS idx_tasks_created ON tasks(created_at);
@@ -43,8 +43,11 @@ pub async fn execute_script_with_progress(
 ) -> Result<ExecuteResult> {
     let start = std::time::Instant::now();
 
-    let (resolved_id, source) =
-        crate::tools::run_script::resolve_script_source(&params, server.strategy_store.as_deref())?;
+    let (resolved_id, source) = crate::tools::run_script::resolve_script_source(
+        &params,
+        server.strategy_store.as_deref(),
+        server.signal_store.as_deref(),
+    )?;
 
     let script_meta = resolved_id
         .as_deref()
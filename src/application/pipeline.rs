@@ -3,17 +3,23 @@
 use anyhow::Result;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::application::sweeps;
+use crate::data::traits::RunStore;
+use crate::engine::sweep::SweepResultCallback;
 use crate::scripting::engine::{CancelCallback, ProgressCallback};
 use crate::server::OptopsyServer;
 use crate::tools::pipeline::StageCallback;
+use crate::tools::response_types::overfitting::CscvResponse;
 use crate::tools::response_types::pipeline::PipelineResponse;
 
 pub struct PipelineRequest {
     pub strategy: String,
     pub mode: String,
     pub objective: String,
+    pub objectives: Vec<String>,
+    pub seed: Option<u64>,
     pub params: HashMap<String, Value>,
     pub sweep_params: Vec<sweeps::SweepParamDef>,
     pub max_evaluations: usize,
@@ -27,6 +33,8 @@ impl Clone for PipelineRequest {
             strategy: self.strategy.clone(),
             mode: self.mode.clone(),
             objective: self.objective.clone(),
+            objectives: self.objectives.clone(),
+            seed: self.seed,
             params: self.params.clone(),
             sweep_params: self.sweep_params.clone(),
             max_evaluations: self.max_evaluations,
@@ -42,7 +50,7 @@ pub async fn execute(
     request: &PipelineRequest,
     source: &str,
 ) -> Result<PipelineResponse> {
-    execute_with_stage(server, request, source, &None, None, None).await
+    execute_with_stage(server, request, source, &None, None, None, None).await
 }
 
 /// Execute the full pipeline with an optional stage progress callback.
@@ -54,6 +62,7 @@ pub async fn execute_with_stage(
     on_stage: &StageCallback,
     progress: Option<ProgressCallback>,
     is_cancelled: Option<&CancelCallback>,
+    on_result: Option<SweepResultCallback>,
 ) -> Result<PipelineResponse> {
     let run_store = server.require_run_store()?;
 
@@ -61,6 +70,8 @@ pub async fn execute_with_stage(
         strategy: request.strategy.clone(),
         mode: request.mode.clone(),
         objective: request.objective.clone(),
+        objectives: request.objectives.clone(),
+        seed: request.seed,
         params: request.params.clone(),
         sweep_params: request.sweep_params.clone(),
         max_evaluations: request.max_evaluations,
@@ -79,6 +90,7 @@ pub async fn execute_with_stage(
         request.thread_id.as_deref(),
         progress,
         is_cancelled,
+        on_result,
     )
     .await?;
 
@@ -96,6 +108,10 @@ pub async fn execute_with_stage(
     )
     .await?;
 
+    if let Some(cscv) = &response.cscv {
+        persist_cscv(run_store, &sweep_result.sweep_id, cscv).await?;
+    }
+
     // Persist pipeline stages to sweeps.analysis for tracking
     let stages_json = serde_json::to_string(&response.stages)?;
     let sweep_id = sweep_result.sweep_id;
@@ -105,3 +121,34 @@ pub async fn execute_with_stage(
 
     Ok(response)
 }
+
+/// Persist a CSCV overfitting analysis alongside its sweep.
+pub async fn persist_cscv(
+    run_store: &Arc<dyn RunStore>,
+    sweep_id: &str,
+    cscv: &CscvResponse,
+) -> Result<()> {
+    let store = run_store.clone();
+    let sweep_id = sweep_id.to_string();
+    let cscv = cscv.clone();
+    tokio::task::spawn_blocking(move || {
+        store.insert_cscv_validation(
+            &uuid::Uuid::new_v4().to_string(),
+            &sweep_id,
+            cscv.n_blocks as i64,
+            cscv.n_splits as i64,
+            cscv.n_strategies as i64,
+            cscv.n_observations as i64,
+            cscv.pbo,
+            cscv.degradation_slope,
+            cscv.degradation_intercept,
+            cscv.degradation_r2,
+            cscv.prob_oos_loss,
+            cscv.median_logit,
+            cscv.pbo_threshold,
+            cscv.rejected,
+        )
+    })
+    .await??;
+    Ok(())
+}
@@ -8,11 +8,19 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::application::error::{ApplicationError, ApplicationResult};
+use crate::data::signal_store::SqliteSignalStore;
 use crate::data::traits::{RunStore, StrategyStore, TradeRow};
-use crate::engine::bayesian::{run_bayesian, BayesianConfig};
+use crate::engine::bayesian::{
+    run_bayesian_with_results, BayesianConfig, CategoricalParam, ParamCondition,
+};
+use crate::engine::pareto::{
+    parse_objectives, run_pareto_sweep_with_results, ParetoConfig, DEFAULT_PARETO_SEED,
+};
 use crate::engine::permutation::apply_permutation_gate;
-use crate::engine::sweep::{run_grid_sweep, GridSweepConfig};
+use crate::engine::reality_check::apply_reality_check_gate;
+use crate::engine::sweep::{run_grid_sweep_with_results, GridSweepConfig, SweepResultCallback};
 use crate::scripting::engine::{CachingDataLoader, CancelCallback, DataLoader, ProgressCallback};
+use crate::scripting::stdlib::ExternParam;
 use crate::server::sanitize::{sanitize, trade_row_from_record};
 use crate::server::OptopsyServer;
 use crate::tools::response_types::sweep::SweepResponse;
@@ -38,6 +46,12 @@ pub struct CreateSweepRequest {
     pub mode: String,
     #[serde(default = "default_objective")]
     pub objective: String,
+    /// Objectives for `mode = "pareto"`, e.g. `["cagr", "max_drawdown", "cvar_95"]`.
+    #[serde(default)]
+    pub objectives: Vec<String>,
+    /// Seed for the NSGA-II search in `mode = "pareto"`. Default 42.
+    #[serde(default)]
+    pub seed: Option<u64>,
     pub params: HashMap<String, Value>,
     pub sweep_params: Vec<SweepParamDef>,
     #[serde(default = "default_max_evaluations")]
@@ -49,11 +63,40 @@ pub struct CreateSweepRequest {
 #[derive(Debug, Deserialize, Clone, Serialize, schemars::JsonSchema)]
 pub struct SweepParamDef {
     pub name: String,
+    /// `"int"`, `"float"` or `"categorical"` (sweeps `choices` instead of a range).
     #[serde(default = "default_param_type")]
     pub param_type: String,
+    #[serde(default)]
     pub start: f64,
+    #[serde(default)]
     pub stop: f64,
     pub step: Option<f64>,
+    /// Values for a categorical param, e.g. `["mid", "spread"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<Value>,
+    /// Only sweep this param when a parent categorical param takes one of the
+    /// listed values. Honoured by bayesian mode; grid and pareto sweep the full product.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<ParamCondition>,
+}
+
+impl SweepParamDef {
+    pub fn is_categorical(&self) -> bool {
+        self.param_type == "categorical" || !self.choices.is_empty()
+    }
+
+    /// Values to sweep for a categorical param: its `choices`, or when none are
+    /// given, the options declared by the script's matching `extern`.
+    pub fn categorical_choices(&self, externs: &[ExternParam]) -> Vec<Value> {
+        if !self.choices.is_empty() {
+            return self.choices.clone();
+        }
+        externs
+            .iter()
+            .find(|ep| ep.name == self.name)
+            .map(|ep| ep.options.clone())
+            .unwrap_or_default()
+    }
 }
 
 pub struct ExecuteSweepResult {
@@ -76,9 +119,27 @@ struct SweepExecutionContext {
 }
 
 /// Build a Cartesian grid from sweep param definitions.
-pub fn build_grid(sweep_params: &[SweepParamDef]) -> Result<HashMap<String, Vec<Value>>, String> {
+///
+/// Categorical params without `choices` sweep the options of the script's
+/// matching `extern` in `externs`.
+pub fn build_grid(
+    sweep_params: &[SweepParamDef],
+    externs: &[ExternParam],
+) -> Result<HashMap<String, Vec<Value>>, String> {
     let mut grid: HashMap<String, Vec<Value>> = HashMap::new();
     for sp in sweep_params {
+        if sp.is_categorical() {
+            let choices = sp.categorical_choices(externs);
+            if choices.is_empty() {
+                return Err(format!(
+                    "Invalid sweep param '{}': categorical params need at least one choice \
+                     (none given, and the script declares no options for it)",
+                    sp.name
+                ));
+            }
+            grid.insert(sp.name.clone(), choices);
+            continue;
+        }
         let is_int = sp.param_type == "int";
         let step = sp.step.unwrap_or(if is_int { 1.0 } else { 0.01 });
         if sp.stop < sp.start {
@@ -112,6 +173,7 @@ pub fn build_grid(sweep_params: &[SweepParamDef]) -> Result<HashMap<String, Vec<
 /// Resolve strategy source from a strategy store.
 pub fn resolve_strategy_source_from_store(
     store: &dyn StrategyStore,
+    signal_store: Option<&SqliteSignalStore>,
     name_or_id: &str,
 ) -> ApplicationResult<(String, String)> {
     let (id, raw) = match store.get_source(name_or_id) {
@@ -136,7 +198,7 @@ pub fn resolve_strategy_source_from_store(
         }
     };
 
-    let source = crate::tools::run_script::maybe_transpile(raw)
+    let source = crate::tools::run_script::maybe_transpile(raw, signal_store)
         .map_err(|e| ApplicationError::invalid_input(e.to_string()))?;
     Ok((id, source))
 }
@@ -158,6 +220,8 @@ pub fn persist_sweep_to_store(
     let sweep_config = serde_json::json!({
         "mode": req.mode,
         "objective": req.objective,
+        "objectives": req.objectives,
+        "seed": req.seed,
         "sweep_params": req.sweep_params,
         "params": req.params,
         "num_permutations": req.num_permutations,
@@ -295,9 +359,14 @@ fn resolve_execution_context(
 ) -> Result<SweepExecutionContext> {
     let strategy_store = server.require_strategy_store()?;
 
-    let (strategy_key, script_source) =
-        resolve_strategy_source_from_store(strategy_store.as_ref(), &req.strategy)?;
-    let script_meta = crate::scripting::stdlib::parse_script_meta(&strategy_key, &script_source);
+    let (strategy_key, script_source) = resolve_strategy_source_from_store(
+        strategy_store.as_ref(),
+        server.signal_store.as_deref(),
+        &req.strategy,
+    )?;
+    let mut script_meta =
+        crate::scripting::stdlib::parse_script_meta(&strategy_key, &script_source);
+    script_meta.params = crate::scripting::stdlib::extract_extern_params(&script_source);
 
     Ok(SweepExecutionContext {
         strategy_key,
@@ -314,9 +383,12 @@ async fn run_sweep_mode(
     context: &SweepExecutionContext,
     progress: Option<ProgressCallback>,
     is_cancelled: Option<&CancelCallback>,
+    on_result: Option<SweepResultCallback>,
 ) -> Result<SweepResponse> {
     let noop_progress: ProgressCallback = Box::new(|_, _| {});
     let progress_ref = progress.as_ref().unwrap_or(&noop_progress);
+    let noop_result: SweepResultCallback = Box::new(|_| {});
+    let result_ref = on_result.as_ref().unwrap_or(&noop_result);
     let noop_cancel: CancelCallback = Box::new(|| false);
     let cancel_ref = is_cancelled.unwrap_or(&noop_cancel);
 
@@ -325,43 +397,83 @@ async fn run_sweep_mode(
             let config = GridSweepConfig {
                 script_source: context.script_source.clone(),
                 base_params: req.params.clone(),
-                param_grid: build_grid(&req.sweep_params).map_err(anyhow::Error::msg)?,
+                param_grid: build_grid(&req.sweep_params, &context.script_meta.params)
+                    .map_err(anyhow::Error::msg)?,
                 objective: req.objective.clone(),
             };
-            run_grid_sweep(
+            run_grid_sweep_with_results(
                 &config,
                 Arc::clone(&context.loader),
                 cancel_ref,
                 progress_ref,
+                result_ref,
             )
             .await
         }
         "bayesian" => {
-            let continuous_params: Vec<(String, f64, f64, bool, Option<f64>)> = req
-                .sweep_params
-                .iter()
-                .map(|sp| {
-                    (
+            let mut continuous_params = Vec::new();
+            let mut categorical_params = Vec::new();
+            let mut conditions = HashMap::new();
+            for sp in &req.sweep_params {
+                if sp.is_categorical() {
+                    categorical_params.push(CategoricalParam {
+                        name: sp.name.clone(),
+                        choices: sp.categorical_choices(&context.script_meta.params),
+                    });
+                } else {
+                    continuous_params.push((
                         sp.name.clone(),
                         sp.start,
                         sp.stop,
                         sp.param_type == "int",
                         sp.step,
-                    )
-                })
-                .collect();
+                    ));
+                }
+                if let Some(cond) = &sp.when {
+                    conditions.insert(sp.name.clone(), cond.clone());
+                }
+            }
             let config = BayesianConfig {
                 script_source: context.script_source.clone(),
                 base_params: req.params.clone(),
                 continuous_params,
+                categorical_params,
+                conditions,
                 max_evaluations: req.max_evaluations,
                 initial_samples: (req.max_evaluations / 3).max(2),
                 objective: req.objective.clone(),
             };
-            run_bayesian(&config, context.loader.as_ref(), cancel_ref, progress_ref).await
+            run_bayesian_with_results(
+                &config,
+                context.loader.as_ref(),
+                cancel_ref,
+                progress_ref,
+                result_ref,
+            )
+            .await
+        }
+        "pareto" => {
+            let config = ParetoConfig {
+                script_source: context.script_source.clone(),
+                base_params: req.params.clone(),
+                param_grid: build_grid(&req.sweep_params, &context.script_meta.params)
+                    .map_err(anyhow::Error::msg)?,
+                objectives: parse_objectives(&req.objectives)?,
+                seed: req.seed.unwrap_or(DEFAULT_PARETO_SEED),
+                max_evaluations: req.max_evaluations,
+                objective: req.objective.clone(),
+            };
+            run_pareto_sweep_with_results(
+                &config,
+                Arc::clone(&context.loader),
+                cancel_ref,
+                progress_ref,
+                result_ref,
+            )
+            .await
         }
         other => {
-            anyhow::bail!("Invalid mode '{other}', expected 'grid' or 'bayesian'");
+            anyhow::bail!("Invalid mode '{other}', expected 'grid', 'bayesian' or 'pareto'");
         }
     }
 }
@@ -374,7 +486,9 @@ async fn apply_permutation_if_needed(
     let num_permutations = req.num_permutations;
     if num_permutations > 0 {
         Ok(tokio::task::spawn_blocking(move || {
-            apply_permutation_gate(sweep_response, num_permutations, &objective, Some(42))
+            let response =
+                apply_permutation_gate(sweep_response, num_permutations, &objective, Some(42));
+            apply_reality_check_gate(response, num_permutations, Some(42))
         })
         .await?)
     } else {
@@ -398,9 +512,10 @@ pub async fn execute_sweep(
     thread_id: Option<&str>,
     progress: Option<ProgressCallback>,
     is_cancelled: Option<&CancelCallback>,
+    on_result: Option<SweepResultCallback>,
 ) -> Result<ExecuteSweepResult> {
     let context = resolve_execution_context(server, req)?;
-    let sweep_response = run_sweep_mode(req, &context, progress, is_cancelled).await?;
+    let sweep_response = run_sweep_mode(req, &context, progress, is_cancelled, on_result).await?;
     let sweep_response = apply_permutation_if_needed(req, sweep_response).await?;
     let sweep_id = persist_sweep_to_store(
         run_store,
@@ -425,3 +540,42 @@ pub async fn execute_sweep(
         objective: req.objective.clone(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categorical(name: &str, choices: Vec<Value>) -> SweepParamDef {
+        SweepParamDef {
+            name: name.to_string(),
+            param_type: "categorical".to_string(),
+            start: 0.0,
+            stop: 0.0,
+            step: None,
+            choices,
+            when: None,
+        }
+    }
+
+    #[test]
+    fn build_grid_defaults_empty_choices_to_extern_options() {
+        let externs = vec![ExternParam {
+            name: "SLIPPAGE".to_string(),
+            default: Some(serde_json::json!("mid")),
+            description: "Slippage model".to_string(),
+            param_type: "string".to_string(),
+            options: vec![serde_json::json!("mid"), serde_json::json!("spread")],
+            role: String::new(),
+        }];
+
+        let grid = build_grid(&[categorical("SLIPPAGE", Vec::new())], &externs).unwrap();
+        assert_eq!(grid["SLIPPAGE"], externs[0].options);
+
+        // Explicit choices win over the extern's options
+        let explicit = vec![serde_json::json!("spread")];
+        let grid = build_grid(&[categorical("SLIPPAGE", explicit.clone())], &externs).unwrap();
+        assert_eq!(grid["SLIPPAGE"], explicit);
+
+        assert!(build_grid(&[categorical("OTHER", Vec::new())], &externs).is_err());
+    }
+}
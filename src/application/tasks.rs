@@ -1,11 +1,11 @@
 //! Shared queued-task orchestration helpers.
 
 use std::future::Future;
-use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use serde_json::Value;
 
+use crate::engine::sweep::SweepResultCallback;
 use crate::server::task_manager::{TaskInfo, TaskManager};
 
 pub struct TaskCompletion {
@@ -13,16 +13,22 @@ pub struct TaskCompletion {
     pub result_id: String,
 }
 
-/// Create a progress callback that writes into a task's atomic counters.
+/// Create a progress callback that writes into a task's atomic counters and
+/// publishes throttled `progress` events.
 pub fn progress_callback(task: &Arc<TaskInfo>) -> crate::scripting::engine::ProgressCallback {
     let task_for_progress = Arc::clone(task);
-    Box::new(move |current, total| {
-        task_for_progress
-            .progress_current
-            .store(current, Ordering::Relaxed);
-        task_for_progress
-            .progress_total
-            .store(total, Ordering::Relaxed);
+    Box::new(move |current, total| task_for_progress.report_progress(current, total))
+}
+
+/// Create a callback that publishes each completed sweep combination as a
+/// `sweep_result` event.
+pub fn sweep_result_callback(task: &Arc<TaskInfo>) -> SweepResultCallback {
+    let task_for_results = Arc::clone(task);
+    Box::new(move |result| {
+        task_for_results.emit(
+            "sweep_result",
+            serde_json::to_value(result).unwrap_or(Value::Null),
+        );
     })
 }
 
@@ -9,6 +9,7 @@ use serde_json::Value;
 use crate::application::pipeline::{self, PipelineRequest};
 use crate::application::sweeps;
 use crate::server::OptopsyServer;
+use crate::tools::response_types::overfitting::OverfittingCheckResponse;
 use crate::tools::response_types::walk_forward::WalkForwardResponse;
 use crate::tools::response_types::workflow::{
     StrategyEvaluationResponse, WalkForwardRobustnessCheck, WorkflowKind, WorkflowResponse,
@@ -34,7 +35,7 @@ pub async fn execute(
     request: &WorkflowRequest,
     source: &str,
 ) -> Result<WorkflowResponse> {
-    execute_with_stage(server, request, source, &None, None, None).await
+    execute_with_stage(server, request, source, &None, None, None, None).await
 }
 
 pub async fn execute_with_stage(
@@ -44,6 +45,7 @@ pub async fn execute_with_stage(
     on_stage: &crate::tools::pipeline::StageCallback,
     progress: Option<crate::scripting::engine::ProgressCallback>,
     is_cancelled: Option<&crate::scripting::engine::CancelCallback>,
+    on_result: Option<crate::engine::sweep::SweepResultCallback>,
 ) -> Result<WorkflowResponse> {
     match request.kind {
         WorkflowKind::BaselineValidation => Ok(WorkflowResponse::BaselineValidation(
@@ -54,6 +56,7 @@ pub async fn execute_with_stage(
                 on_stage,
                 progress,
                 is_cancelled,
+                on_result,
             )
             .await?,
         )),
@@ -65,10 +68,120 @@ pub async fn execute_with_stage(
                 on_stage,
                 progress,
                 is_cancelled,
+                on_result,
             )
             .await?,
         )),
+        WorkflowKind::OverfittingCheck => Ok(WorkflowResponse::OverfittingCheck(
+            execute_overfitting_check(
+                server,
+                &request.pipeline,
+                source,
+                on_stage,
+                progress,
+                is_cancelled,
+                on_result,
+            )
+            .await?,
+        )),
+    }
+}
+
+async fn execute_overfitting_check(
+    server: &OptopsyServer,
+    request: &PipelineRequest,
+    source: &str,
+    on_stage: &crate::tools::pipeline::StageCallback,
+    progress: Option<crate::scripting::engine::ProgressCallback>,
+    is_cancelled: Option<&crate::scripting::engine::CancelCallback>,
+    on_result: Option<crate::engine::sweep::SweepResultCallback>,
+) -> Result<OverfittingCheckResponse> {
+    let started_at = Instant::now();
+    let run_store = server.require_run_store()?;
+    let sweep_req = sweeps::CreateSweepRequest {
+        strategy: request.strategy.clone(),
+        mode: request.mode.clone(),
+        objective: request.objective.clone(),
+        objectives: request.objectives.clone(),
+        seed: request.seed,
+        params: request.params.clone(),
+        sweep_params: request.sweep_params.clone(),
+        max_evaluations: request.max_evaluations,
+        num_permutations: request.num_permutations,
+    };
+
+    if let Some(cb) = on_stage {
+        cb("Sweep");
+    }
+    let sweep_result = sweeps::execute_sweep(
+        server,
+        run_store.as_ref(),
+        &sweep_req,
+        source,
+        request.thread_id.as_deref(),
+        progress,
+        is_cancelled,
+        on_result,
+    )
+    .await?;
+
+    if let Some(cb) = on_stage {
+        cb("Overfitting Check");
     }
+    let sweep = sweep_result.response;
+    let cscv = crate::tools::pipeline::compute_cscv(&sweep).await?;
+    pipeline::persist_cscv(run_store, &sweep_result.sweep_id, &cscv).await?;
+
+    let verdict = if cscv.rejected {
+        "REJECT: the in-sample winner is more likely than not to underperform the median combo out of sample"
+    } else {
+        "PASS: the in-sample winner usually holds its rank out of sample"
+    };
+    let summary = format!(
+        "Overfitting check on {} combos over {} splits: PBO={:.1}% (threshold {:.0}%). {verdict}.",
+        cscv.n_strategies,
+        cscv.n_splits,
+        cscv.pbo * 100.0,
+        cscv.pbo_threshold * 100.0,
+    );
+    let key_findings = vec![
+        format!(
+            "Probability of backtest overfitting: {:.1}% across {} blocks",
+            cscv.pbo * 100.0,
+            cscv.n_blocks
+        ),
+        format!(
+            "Performance degradation: OOS Sharpe ≈ {:.2} × IS Sharpe {:+.3} (R²={:.2})",
+            cscv.degradation_slope, cscv.degradation_intercept, cscv.degradation_r2
+        ),
+        format!(
+            "Probability the IS winner loses money OOS: {:.1}%",
+            cscv.prob_oos_loss * 100.0
+        ),
+    ];
+    let suggested_next_steps = if cscv.rejected {
+        vec![
+            "[NEXT] Narrow the sweep to fewer, economically motivated parameter values and re-run"
+                .to_string(),
+            "[TIP] Do not paper trade the sweep winner — its ranking is not stable out of sample"
+                .to_string(),
+        ]
+    } else {
+        vec![
+            "[NEXT] Run the strategy_evaluation workflow for walk-forward and Monte Carlo validation"
+                .to_string(),
+        ]
+    };
+
+    Ok(OverfittingCheckResponse {
+        summary,
+        sweep_id: sweep_result.sweep_id,
+        sweep,
+        cscv,
+        key_findings,
+        suggested_next_steps,
+        total_duration_ms: started_at.elapsed().as_millis() as u64,
+    })
 }
 
 async fn execute_strategy_evaluation(
@@ -78,6 +191,7 @@ async fn execute_strategy_evaluation(
     on_stage: &crate::tools::pipeline::StageCallback,
     progress: Option<crate::scripting::engine::ProgressCallback>,
     is_cancelled: Option<&crate::scripting::engine::CancelCallback>,
+    on_result: Option<crate::engine::sweep::SweepResultCallback>,
 ) -> Result<StrategyEvaluationResponse> {
     let started_at = Instant::now();
     let mut eval_request = request.clone();
@@ -92,6 +206,7 @@ async fn execute_strategy_evaluation(
         on_stage,
         progress,
         is_cancelled,
+        on_result,
     )
     .await?;
     let robustness_checks = if pipeline.walk_forward.is_some() {
@@ -149,6 +264,7 @@ async fn execute_strategy_evaluation(
     })
 }
 
+#[allow(clippy::too_many_lines)]
 async fn run_walk_forward_robustness(
     server: &OptopsyServer,
     request: &PipelineRequest,
@@ -161,8 +277,11 @@ async fn run_walk_forward_robustness(
 
     let params_grid = crate::tools::pipeline::build_wf_params_grid(&top_combos);
     let strategy_store = server.require_strategy_store()?;
-    let (strategy_key, script_source) =
-        sweeps::resolve_strategy_source_from_store(strategy_store.as_ref(), &request.strategy)?;
+    let (strategy_key, script_source) = sweeps::resolve_strategy_source_from_store(
+        strategy_store.as_ref(),
+        server.signal_store.as_deref(),
+        &request.strategy,
+    )?;
 
     let symbol = request
         .params
@@ -288,6 +407,7 @@ async fn execute_walk_forward_variant(
         None,
         Some(script_source.to_string()),
         Some(base_params.clone()),
+        None,
     )
     .await
 }
@@ -8,6 +8,7 @@ use anyhow::{Context, Result};
 use crate::data::adjustment_store::SqliteAdjustmentStore;
 use crate::data::database::Database;
 use crate::data::forward_test_store::SqliteForwardTestStore;
+use crate::data::signal_store::SqliteSignalStore;
 use crate::data::traits::{self, ChatStore, RunStore, StrategyStore};
 use crate::server::state::AppState;
 use crate::server::task_manager::TaskManager;
@@ -21,7 +22,11 @@ pub struct AppServices {
     pub chat_store: Arc<dyn ChatStore>,
     pub adjustment_store: Arc<SqliteAdjustmentStore>,
     pub forward_test_store: Arc<SqliteForwardTestStore>,
+    pub signal_store: Arc<SqliteSignalStore>,
     pub task_manager: Arc<TaskManager>,
+    /// Re-queue tasks that were running at shutdown instead of marking them
+    /// interrupted (`REQUEUE_INTERRUPTED_TASKS`).
+    pub requeue_interrupted_tasks: bool,
 }
 
 impl AppServices {
@@ -37,6 +42,7 @@ impl AppServices {
         let chat_store: Arc<dyn ChatStore> = Arc::new(db.chat());
         let adjustment_store = Arc::new(db.adjustments());
         let forward_test_store = Arc::new(db.forward_tests());
+        let signal_store = Arc::new(db.signals());
 
         let seeded = traits::seed_strategies_if_empty(
             strategy_store.as_ref(),
@@ -50,7 +56,9 @@ impl AppServices {
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(1usize);
-        let task_manager = Arc::new(TaskManager::new(max_concurrent_tasks));
+        let task_manager = Arc::new(TaskManager::new(max_concurrent_tasks).with_store(db.tasks()));
+        let requeue_interrupted_tasks = std::env::var("REQUEUE_INTERRUPTED_TASKS")
+            .is_ok_and(|v| matches!(v.as_str(), "1" | "true" | "yes"));
 
         Ok(Self {
             strategy_store,
@@ -58,7 +66,9 @@ impl AppServices {
             chat_store,
             adjustment_store,
             forward_test_store,
+            signal_store,
             task_manager,
+            requeue_interrupted_tasks,
         })
     }
 
@@ -71,6 +81,7 @@ impl AppServices {
             Arc::clone(&self.adjustment_store),
         )
         .with_forward_test_store(Arc::clone(&self.forward_test_store))
+        .with_signal_store(Arc::clone(&self.signal_store))
     }
 
     /// Construct the shared HTTP application state for REST handlers.
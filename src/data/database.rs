@@ -79,6 +79,18 @@ impl Database {
         super::forward_test_store::SqliteForwardTestStore::new(self.conn.clone())
     }
 
+    /// Create a [`SqliteTaskStore`](super::task_store::SqliteTaskStore)
+    /// backed by this database's connection.
+    pub fn tasks(&self) -> super::task_store::SqliteTaskStore {
+        super::task_store::SqliteTaskStore::new(self.conn.clone())
+    }
+
+    /// Create a [`SqliteSignalStore`](super::signal_store::SqliteSignalStore)
+    /// backed by this database's connection.
+    pub fn signals(&self) -> super::signal_store::SqliteSignalStore {
+        super::signal_store::SqliteSignalStore::new(self.conn.clone())
+    }
+
     /// Return the shared database connection handle.
     pub fn connection(&self) -> DbConnection {
         self.conn.clone()
@@ -126,6 +138,9 @@ mod tests {
         assert!(tables.contains(&"results".to_string()));
         assert!(tables.contains(&"splits".to_string()));
         assert!(tables.contains(&"dividends".to_string()));
+        assert!(tables.contains(&"cscv_validations".to_string()));
+        assert!(tables.contains(&"tasks".to_string()));
+        assert!(tables.contains(&"signals".to_string()));
     }
 
     #[test]
@@ -10,7 +10,9 @@ pub mod database;
 pub mod forward_test_store;
 pub mod parquet;
 pub mod run_store;
+pub mod signal_store;
 pub mod strategy_store;
+pub mod task_store;
 pub mod traits;
 
 use anyhow::Result;
@@ -9,8 +9,8 @@ use serde_json::Value;
 
 use super::database::DbConnection;
 use super::traits::{
-    RunDetail, RunRow, RunStore, RunSummary, RunsListResponse, RunsOverview, SweepDetail,
-    SweepParamRange, TradeRow, WalkForwardValidation,
+    CscvValidation, RunDetail, RunRow, RunStore, RunSummary, RunsListResponse, RunsOverview,
+    SweepDetail, SweepParamRange, TradeRow, WalkForwardValidation,
 };
 use crate::server::sanitize::sanitize_opt;
 
@@ -148,8 +148,8 @@ impl RunStore for SqliteRunStore {
                     (run_id, trade_id, entry_datetime, exit_datetime, entry_cost,
                      exit_proceeds, entry_amount, entry_label, exit_amount, exit_label,
                      pnl, days_held, exit_type, legs, computed_quantity, entry_equity,
-                     stock_entry_price, stock_exit_price, stock_pnl, [group])
-                 VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20)",
+                     stock_entry_price, stock_exit_price, stock_pnl, [group], dividend_pnl)
+                 VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,?21)",
                 rusqlite::params![
                     run_id,
                     trade.trade_id,
@@ -171,6 +171,7 @@ impl RunStore for SqliteRunStore {
                     trade.stock_exit_price,
                     trade.stock_pnl,
                     trade.group,
+                    trade.dividend_pnl,
                 ],
             )
             .context("Failed to insert trade")?;
@@ -552,7 +553,8 @@ impl RunStore for SqliteRunStore {
                         entry_amount, entry_label, exit_amount, exit_label,
                         pnl, days_held, exit_type, legs,
                         computed_quantity, entry_equity,
-                        stock_entry_price, stock_exit_price, stock_pnl, [group]
+                        stock_entry_price, stock_exit_price, stock_pnl, [group],
+                        dividend_pnl
                  FROM trades
                  WHERE run_id = ?1
                  ORDER BY trade_id ASC",
@@ -584,6 +586,7 @@ impl RunStore for SqliteRunStore {
                     stock_exit_price: row.get(16)?,
                     stock_pnl: row.get(17)?,
                     group: row.get(18)?,
+                    dividend_pnl: row.get(19)?,
                 })
             })
             .context("Failed to query trades")?
@@ -628,8 +631,9 @@ impl RunStore for SqliteRunStore {
                             .unwrap_or_else(|| "manual".to_string()),
                         thread_id: row.get(11)?,
                         created_at: row.get(12)?,
-                        runs: Vec::new(),        // filled below
-                        validations: Vec::new(), // filled below
+                        runs: Vec::new(),             // filled below
+                        validations: Vec::new(),      // filled below
+                        cscv_validations: Vec::new(), // filled below
                     })
                 },
             )
@@ -682,6 +686,9 @@ impl RunStore for SqliteRunStore {
                 .context("Failed to collect walk-forward validations")?;
         }
 
+        // Load CSCV overfitting analyses for this sweep
+        detail.cscv_validations = query_cscv_validations(&conn, id)?;
+
         // Load runs for this sweep
         let mut stmt = conn
             .prepare(
@@ -882,6 +889,102 @@ impl RunStore for SqliteRunStore {
             .context("Failed to delete walk-forward validation")?;
         Ok(n > 0)
     }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert_cscv_validation(
+        &self,
+        id: &str,
+        sweep_id: &str,
+        n_blocks: i64,
+        n_splits: i64,
+        n_strategies: i64,
+        n_observations: i64,
+        pbo: f64,
+        degradation_slope: f64,
+        degradation_intercept: f64,
+        degradation_r2: f64,
+        prob_oos_loss: f64,
+        median_logit: f64,
+        pbo_threshold: f64,
+        rejected: bool,
+    ) -> Result<String> {
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let conn = self.conn.lock().expect("mutex poisoned");
+        conn.execute(
+            "INSERT INTO cscv_validations
+                (id, sweep_id, n_blocks, n_splits, n_strategies, n_observations,
+                 pbo, degradation_slope, degradation_intercept, degradation_r2,
+                 prob_oos_loss, median_logit, pbo_threshold, rejected, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            rusqlite::params![
+                id,
+                sweep_id,
+                n_blocks,
+                n_splits,
+                n_strategies,
+                n_observations,
+                pbo,
+                degradation_slope,
+                degradation_intercept,
+                degradation_r2,
+                prob_oos_loss,
+                median_logit,
+                pbo_threshold,
+                rejected,
+                created_at,
+            ],
+        )
+        .context("Failed to insert CSCV validation")?;
+        Ok(created_at)
+    }
+
+    fn get_cscv_validations(&self, sweep_id: &str) -> Result<Vec<CscvValidation>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        query_cscv_validations(&conn, sweep_id)
+    }
+}
+
+/// Load the CSCV analyses for a sweep on an already-locked connection.
+fn query_cscv_validations(
+    conn: &rusqlite::Connection,
+    sweep_id: &str,
+) -> Result<Vec<CscvValidation>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, sweep_id, n_blocks, n_splits, n_strategies, n_observations,
+                    pbo, degradation_slope, degradation_intercept, degradation_r2,
+                    prob_oos_loss, median_logit, pbo_threshold, rejected, created_at
+             FROM cscv_validations
+             WHERE sweep_id = ?1
+             ORDER BY created_at DESC",
+        )
+        .context("Failed to prepare CSCV validations query")?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![sweep_id], |row| {
+            Ok(CscvValidation {
+                id: row.get(0)?,
+                sweep_id: row.get(1)?,
+                n_blocks: row.get(2)?,
+                n_splits: row.get(3)?,
+                n_strategies: row.get(4)?,
+                n_observations: row.get(5)?,
+                pbo: row.get(6)?,
+                degradation_slope: row.get(7)?,
+                degradation_intercept: row.get(8)?,
+                degradation_r2: row.get(9)?,
+                prob_oos_loss: row.get(10)?,
+                median_logit: row.get(11)?,
+                pbo_threshold: row.get(12)?,
+                rejected: row.get(13)?,
+                created_at: row.get(14)?,
+            })
+        })
+        .context("Failed to query CSCV validations")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect CSCV validations")?;
+
+    Ok(rows)
 }
 
 // ──────────────────────────────────────────────────────────────────────────────
@@ -920,6 +1023,7 @@ mod tests {
                 stock_exit_price: None,
                 stock_pnl: None,
                 group: None,
+                dividend_pnl: None,
             },
             TradeRow {
                 trade_id: 2,
@@ -941,6 +1045,7 @@ mod tests {
                 stock_exit_price: None,
                 stock_pnl: None,
                 group: Some("group-A".to_string()),
+                dividend_pnl: None,
             },
         ]
     }
@@ -1224,6 +1329,39 @@ mod tests {
         assert!(store.get_run(&run_id).unwrap().is_none());
     }
 
+    #[test]
+    fn test_cscv_validation_roundtrip() {
+        let store = make_store();
+        let params = serde_json::json!({});
+        let sweep_id = uuid::Uuid::new_v4().to_string();
+        store
+            .insert_sweep(
+                &sweep_id, None, "SPY", &params, "sharpe", "grid", 12, None, "manual", None,
+            )
+            .unwrap();
+
+        store
+            .insert_cscv_validation(
+                "cscv-1", &sweep_id, 16, 12_870, 12, 480, 0.62, 0.35, -0.01, 0.2, 0.55, -0.4, 0.5,
+                true,
+            )
+            .unwrap();
+
+        let validations = store.get_cscv_validations(&sweep_id).unwrap();
+        assert_eq!(validations.len(), 1);
+        assert_eq!(validations[0].n_splits, 12_870);
+        assert!((validations[0].pbo - 0.62).abs() < 1e-12);
+        assert!(validations[0].rejected);
+
+        let detail = store.get_sweep(&sweep_id).unwrap().unwrap();
+        assert_eq!(detail.cscv_validations.len(), 1);
+        assert_eq!(detail.cscv_validations[0].id, "cscv-1");
+
+        // Deleting the sweep cascades to its CSCV analyses
+        assert!(store.delete_sweep(&sweep_id).unwrap());
+        assert!(store.get_cscv_validations(&sweep_id).unwrap().is_empty());
+    }
+
     #[test]
     fn test_set_analysis_on_run() {
         let store = make_store();
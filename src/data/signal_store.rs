@@ -0,0 +1,409 @@
+//! SQLite-backed library of saved, versioned signals.
+//!
+//! A saved signal is a named [`SignalSpec`] that strategies reference instead
+//! of repeating the formula. Saving under an existing name appends a new
+//! version; references resolve to the latest version unless pinned with
+//! `name@version`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::params;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+
+use super::database::DbConnection;
+use crate::engine::types::SignalSpec;
+
+/// Maximum length of a saved signal name.
+pub const MAX_SIGNAL_NAME_LEN: usize = 64;
+
+/// Maximum nesting of saved-signal references followed during resolution.
+const MAX_RESOLVE_DEPTH: usize = 16;
+
+const SIGNAL_COLUMNS: &str = "name, version, spec, description, created_at";
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Types
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// One version of a saved signal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalRow {
+    pub name: String,
+    pub version: i64,
+    pub spec: SignalSpec,
+    pub description: Option<String>,
+    pub created_at: String,
+}
+
+fn signal_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SignalRow> {
+    let spec: String = row.get(2)?;
+    let spec = serde_json::from_str(&spec).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    Ok(SignalRow {
+        name: row.get(0)?,
+        version: row.get(1)?,
+        spec,
+        description: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// Check that `name` is usable as a saved signal name: 1–64 characters of
+/// letters, digits, `_`, `-` or `.` (`@` is reserved for version pins).
+pub fn validate_signal_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_SIGNAL_NAME_LEN {
+        return Err(format!(
+            "Signal name must be 1-{MAX_SIGNAL_NAME_LEN} characters, got {}",
+            name.len()
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!(
+            "Invalid signal name '{name}': only letters, digits, '_', '-' and '.' are allowed"
+        ));
+    }
+    Ok(())
+}
+
+/// Split a signal reference into its name and optional pinned version
+/// (`risk_on` → latest, `risk_on@2` → version 2).
+pub fn parse_signal_ref(reference: &str) -> Result<(&str, Option<i64>)> {
+    match reference.split_once('@') {
+        None => Ok((reference, None)),
+        Some((name, version)) => match version.parse::<i64>() {
+            Ok(v) if v > 0 => Ok((name, Some(v))),
+            _ => bail!("Invalid version '{version}' in signal reference '{reference}'"),
+        },
+    }
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Store
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// SQLite-backed saved signal store.
+#[derive(Clone)]
+pub struct SqliteSignalStore {
+    pub(crate) conn: DbConnection,
+}
+
+impl SqliteSignalStore {
+    pub fn new(conn: DbConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Save `spec` as the next version of `name` and return the stored row.
+    pub fn save(
+        &self,
+        name: &str,
+        spec: &SignalSpec,
+        description: Option<&str>,
+    ) -> Result<SignalRow> {
+        let spec_json = serde_json::to_string(spec).context("Failed to serialize signal spec")?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut conn = self.conn.lock().expect("mutex poisoned");
+        let tx = conn.transaction()?;
+        // Keep the casing of the first save so renamed-by-case saves stay one signal.
+        let existing: Option<(String, i64)> = tx
+            .query_row(
+                "SELECT name, MAX(version) FROM signals WHERE name = ?1 GROUP BY name",
+                params![name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (name, version) = match existing {
+            Some((stored, latest)) => (stored, latest + 1),
+            None => (name.to_string(), 1),
+        };
+        tx.execute(
+            &format!("INSERT INTO signals ({SIGNAL_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5)"),
+            params![name, version, spec_json, description, now],
+        )
+        .context("Failed to save signal")?;
+        tx.commit()?;
+        Ok(SignalRow {
+            name,
+            version,
+            spec: spec.clone(),
+            description: description.map(str::to_string),
+            created_at: now,
+        })
+    }
+
+    /// Get a signal by name — the given version, or the latest when `None`.
+    pub fn get(&self, name: &str, version: Option<i64>) -> Result<Option<SignalRow>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let row = match version {
+            Some(version) => conn
+                .query_row(
+                    &format!(
+                        "SELECT {SIGNAL_COLUMNS} FROM signals WHERE name = ?1 AND version = ?2"
+                    ),
+                    params![name, version],
+                    signal_from_row,
+                )
+                .optional()?,
+            None => conn
+                .query_row(
+                    &format!(
+                        "SELECT {SIGNAL_COLUMNS} FROM signals WHERE name = ?1
+                         ORDER BY version DESC LIMIT 1"
+                    ),
+                    params![name],
+                    signal_from_row,
+                )
+                .optional()?,
+        };
+        Ok(row)
+    }
+
+    /// List the latest version of every saved signal, ordered by name.
+    pub fn list(&self) -> Result<Vec<SignalRow>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SIGNAL_COLUMNS} FROM signals s
+             WHERE version = (SELECT MAX(version) FROM signals WHERE name = s.name)
+             ORDER BY name"
+        ))?;
+        let rows = stmt
+            .query_map([], signal_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// List every version of a signal, oldest first.
+    pub fn versions(&self, name: &str) -> Result<Vec<SignalRow>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SIGNAL_COLUMNS} FROM signals WHERE name = ?1 ORDER BY version"
+        ))?;
+        let rows = stmt
+            .query_map(params![name], signal_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Delete a signal and all its versions. Returns `true` if it existed.
+    pub fn delete(&self, name: &str) -> Result<bool> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let deleted = conn
+            .execute("DELETE FROM signals WHERE name = ?1", params![name])
+            .context("Failed to delete signal")?;
+        Ok(deleted > 0)
+    }
+
+    /// Replace every `Saved` reference in `spec` — including those nested in
+    /// `And` / `Or` — with the stored definition, recursively.
+    pub fn resolve(&self, spec: &SignalSpec) -> Result<SignalSpec> {
+        self.resolve_inner(spec, None, &mut Vec::new())
+    }
+
+    /// Resolve `spec` as the next version of `name`, before it is saved.
+    ///
+    /// Unpinned references to `name` anywhere in the graph see `spec` instead
+    /// of the stored definition, so a save that would close a cycle through
+    /// other signals is reported as circular.
+    pub fn resolve_as(&self, name: &str, spec: &SignalSpec) -> Result<SignalSpec> {
+        let mut chain = vec![name.to_lowercase()];
+        self.resolve_inner(spec, Some((name, spec)), &mut chain)
+    }
+
+    fn resolve_inner(
+        &self,
+        spec: &SignalSpec,
+        candidate: Option<(&str, &SignalSpec)>,
+        chain: &mut Vec<String>,
+    ) -> Result<SignalSpec> {
+        match spec {
+            SignalSpec::Formula { .. } => Ok(spec.clone()),
+            SignalSpec::Saved { name: reference } => {
+                let (name, version) = parse_signal_ref(reference)?;
+                let key = name.to_lowercase();
+                if chain.contains(&key) {
+                    bail!(
+                        "Circular saved signal reference: {} -> {name}",
+                        chain.join(" -> ")
+                    );
+                }
+                if chain.len() >= MAX_RESOLVE_DEPTH {
+                    bail!("Saved signal '{reference}' nests more than {MAX_RESOLVE_DEPTH} levels");
+                }
+                let stored;
+                let definition = match candidate {
+                    Some((next, next_spec))
+                        if version.is_none() && next.eq_ignore_ascii_case(name) =>
+                    {
+                        next_spec
+                    }
+                    _ => {
+                        stored = self
+                            .get(name, version)?
+                            .ok_or_else(|| anyhow!("Saved signal '{reference}' not found"))?;
+                        &stored.spec
+                    }
+                };
+                chain.push(key);
+                let resolved = self.resolve_inner(definition, candidate, chain);
+                chain.pop();
+                resolved
+            }
+            SignalSpec::And { left, right } => Ok(SignalSpec::And {
+                left: Box::new(self.resolve_inner(left, candidate, chain)?),
+                right: Box::new(self.resolve_inner(right, candidate, chain)?),
+            }),
+            SignalSpec::Or { left, right } => Ok(SignalSpec::Or {
+                left: Box::new(self.resolve_inner(left, candidate, chain)?),
+                right: Box::new(self.resolve_inner(right, candidate, chain)?),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> SqliteSignalStore {
+        crate::data::database::Database::open_in_memory()
+            .expect("open_in_memory")
+            .signals()
+    }
+
+    fn formula(f: &str) -> SignalSpec {
+        SignalSpec::Formula {
+            formula: f.to_string(),
+        }
+    }
+
+    fn saved(name: &str) -> SignalSpec {
+        SignalSpec::Saved {
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_appends_versions() {
+        let s = store();
+        let v1 = s
+            .save("risk_on", &formula("close > sma(close, 200)"), None)
+            .unwrap();
+        let v2 = s
+            .save(
+                "Risk_On",
+                &formula("close > sma(close, 100)"),
+                Some("faster"),
+            )
+            .unwrap();
+        assert_eq!(v1.version, 1);
+        assert_eq!(v2.version, 2);
+        assert_eq!(v2.name, "risk_on", "first-save casing is kept");
+
+        let latest = s.get("RISK_ON", None).unwrap().expect("latest");
+        assert_eq!(latest.version, 2);
+        assert_eq!(latest.description.as_deref(), Some("faster"));
+        let pinned = s.get("risk_on", Some(1)).unwrap().expect("v1");
+        assert!(
+            matches!(pinned.spec, SignalSpec::Formula { ref formula } if formula.contains("200"))
+        );
+        assert!(s.get("risk_on", Some(3)).unwrap().is_none());
+
+        s.save("calm", &formula("abs(pct_change(close, 1)) < 0.01"), None)
+            .unwrap();
+        let names: Vec<(String, i64)> = s
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.name, r.version))
+            .collect();
+        assert_eq!(names, vec![("calm".into(), 1), ("risk_on".into(), 2)]);
+        assert_eq!(s.versions("risk_on").unwrap().len(), 2);
+
+        assert!(s.delete("risk_on").unwrap());
+        assert!(!s.delete("risk_on").unwrap());
+        assert!(s.get("risk_on", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_resolve_expands_nested_references() {
+        let s = store();
+        s.save("trend", &formula("close > sma(close, 50)"), None)
+            .unwrap();
+        s.save("calm", &formula("std(close, 20) < 2"), None)
+            .unwrap();
+        s.save(
+            "risk_on",
+            &SignalSpec::And {
+                left: Box::new(saved("trend")),
+                right: Box::new(saved("calm")),
+            },
+            None,
+        )
+        .unwrap();
+
+        let spec = SignalSpec::Or {
+            left: Box::new(saved("risk_on")),
+            right: Box::new(formula("gap() > 0.02")),
+        };
+        let resolved = s.resolve(&spec).unwrap();
+        let SignalSpec::Or { left, .. } = resolved else {
+            panic!("expected Or");
+        };
+        let SignalSpec::And { left, right } = *left else {
+            panic!("expected And");
+        };
+        assert!(matches!(*left, SignalSpec::Formula { ref formula } if formula.contains("sma")));
+        assert!(matches!(*right, SignalSpec::Formula { ref formula } if formula.contains("std")));
+
+        // Pinned versions resolve to that version even after the signal changes
+        s.save("trend", &formula("close > sma(close, 20)"), None)
+            .unwrap();
+        let pinned = s.resolve(&saved("trend@1")).unwrap();
+        assert!(matches!(pinned, SignalSpec::Formula { ref formula } if formula.contains("50")));
+    }
+
+    #[test]
+    fn test_resolve_reports_missing_and_circular() {
+        let s = store();
+        let err = s.resolve(&saved("nope")).unwrap_err().to_string();
+        assert!(err.contains("not found"), "got: {err}");
+        assert!(s.resolve(&saved("nope@x")).is_err());
+
+        s.save("a", &formula("close > open"), None).unwrap();
+        s.save("b", &saved("a"), None).unwrap();
+        // A later version of `a` pointing back at `b` closes the loop
+        s.save("a", &saved("b"), None).unwrap();
+        let err = s.resolve(&saved("b")).unwrap_err().to_string();
+        assert!(err.contains("Circular"), "got: {err}");
+    }
+
+    #[test]
+    fn test_resolve_as_sees_the_candidate_definition() {
+        let s = store();
+        s.save("a", &formula("close > open"), None).unwrap();
+        s.save("b", &saved("a"), None).unwrap();
+
+        // Saving `a = b` would loop through the stored `b = a`
+        let err = s.resolve_as("a", &saved("b")).unwrap_err().to_string();
+        assert!(err.contains("Circular"), "got: {err}");
+
+        // A version pinned below the candidate still resolves from the store
+        s.save("c", &saved("a@1"), None).unwrap();
+        let resolved = s.resolve_as("b", &saved("c")).unwrap();
+        assert!(
+            matches!(resolved, SignalSpec::Formula { ref formula } if formula == "close > open")
+        );
+    }
+
+    #[test]
+    fn test_validate_signal_name() {
+        assert!(validate_signal_name("risk-on.v2_x").is_ok());
+        assert!(validate_signal_name("").is_err());
+        assert!(validate_signal_name("risk on").is_err());
+        assert!(validate_signal_name("risk_on@2").is_err());
+        assert!(validate_signal_name(&"x".repeat(65)).is_err());
+    }
+}
@@ -47,7 +47,8 @@ impl StrategyRow {
     pub fn into_script_meta(self) -> ScriptMeta {
         // Transpile DSL to Rhai for param/metadata extraction
         let rhai_source = if crate::scripting::dsl::is_trading_dsl(&self.source) {
-            crate::scripting::dsl::transpile(&self.source).unwrap_or_else(|_| self.source.clone())
+            crate::scripting::dsl::transpile_for_metadata(&self.source)
+                .unwrap_or_else(|_| self.source.clone())
         } else {
             self.source.clone()
         };
@@ -0,0 +1,384 @@
+//! SQLite-backed storage for the background task queue.
+//!
+//! Every task registered with the `TaskManager` is mirrored here so that
+//! queued work survives a restart and `/tasks` can list history after the
+//! in-memory entries have been evicted.
+
+use anyhow::{Context, Result};
+use rusqlite::params;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::database::DbConnection;
+
+/// Error recorded on tasks that were running (or unresumable) at shutdown.
+pub const INTERRUPTED_ERROR: &str = "Interrupted by server restart";
+
+const TASK_COLUMNS: &str = "id, kind, strategy, symbol, thread_id, params, request, status,
+    progress_current, progress_total, stage_label, created_at, started_at,
+    completed_at, result, result_id, error";
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Types
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// A persisted task row. `kind` and `status` use the snake-case names of
+/// `TaskKind` and `TaskStatus`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRow {
+    pub id: String,
+    pub kind: String,
+    pub strategy: String,
+    pub symbol: String,
+    pub thread_id: Option<String>,
+    pub params: Value,
+    /// Original submit request, replayed to resume the task. `None` for
+    /// tasks that cannot be resumed.
+    pub request: Option<Value>,
+    pub status: String,
+    pub progress_current: i64,
+    pub progress_total: i64,
+    pub stage_label: String,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub result: Option<Value>,
+    pub result_id: Option<String>,
+    pub error: Option<String>,
+}
+
+fn task_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TaskRow> {
+    let json = |idx: usize| -> rusqlite::Result<Option<Value>> {
+        Ok(row
+            .get::<_, Option<String>>(idx)?
+            .and_then(|s| serde_json::from_str(&s).ok()))
+    };
+    Ok(TaskRow {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        strategy: row.get(2)?,
+        symbol: row.get(3)?,
+        thread_id: row.get(4)?,
+        params: json(5)?.unwrap_or_default(),
+        request: json(6)?,
+        status: row.get(7)?,
+        progress_current: row.get(8)?,
+        progress_total: row.get(9)?,
+        stage_label: row.get(10)?,
+        created_at: row.get(11)?,
+        started_at: row.get(12)?,
+        completed_at: row.get(13)?,
+        result: json(14)?,
+        result_id: row.get(15)?,
+        error: row.get(16)?,
+    })
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Store
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// SQLite-backed task store.
+#[derive(Clone)]
+pub struct SqliteTaskStore {
+    pub(crate) conn: DbConnection,
+}
+
+impl SqliteTaskStore {
+    pub fn new(conn: DbConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Insert a newly registered task.
+    pub fn insert_task(&self, task: &TaskRow) -> Result<()> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        conn.execute(
+            &format!(
+                "INSERT INTO tasks ({TASK_COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)"
+            ),
+            params![
+                task.id,
+                task.kind,
+                task.strategy,
+                task.symbol,
+                task.thread_id,
+                serde_json::to_string(&task.params)?,
+                task.request.as_ref().map(Value::to_string),
+                task.status,
+                task.progress_current,
+                task.progress_total,
+                task.stage_label,
+                task.created_at,
+                task.started_at,
+                task.completed_at,
+                task.result.as_ref().map(Value::to_string),
+                task.result_id,
+                task.error,
+            ],
+        )
+        .context("Failed to insert task")?;
+        Ok(())
+    }
+
+    /// Mark a task as running.
+    pub fn mark_started(&self, id: &str, started_at: &str) -> Result<()> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        conn.execute(
+            "UPDATE tasks SET status = 'running', started_at = ?1 WHERE id = ?2",
+            params![started_at, id],
+        )
+        .context("Failed to mark task running")?;
+        Ok(())
+    }
+
+    /// Record the latest progress counters and pipeline stage of a task.
+    pub fn update_progress(
+        &self,
+        id: &str,
+        progress_current: i64,
+        progress_total: i64,
+        stage_label: &str,
+    ) -> Result<()> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        conn.execute(
+            "UPDATE tasks SET progress_current = ?1, progress_total = ?2, stage_label = ?3
+             WHERE id = ?4",
+            params![progress_current, progress_total, stage_label, id],
+        )
+        .context("Failed to update task progress")?;
+        Ok(())
+    }
+
+    /// Move a task to a terminal status with its final progress and outcome.
+    #[allow(clippy::too_many_arguments)]
+    pub fn finish_task(
+        &self,
+        id: &str,
+        status: &str,
+        completed_at: &str,
+        progress_current: i64,
+        progress_total: i64,
+        result: Option<&Value>,
+        result_id: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        conn.execute(
+            "UPDATE tasks
+             SET status = ?1, completed_at = ?2, progress_current = ?3, progress_total = ?4,
+                 result = ?5, result_id = ?6, error = ?7
+             WHERE id = ?8",
+            params![
+                status,
+                completed_at,
+                progress_current,
+                progress_total,
+                result.map(Value::to_string),
+                result_id,
+                error,
+                id,
+            ],
+        )
+        .context("Failed to finish task")?;
+        Ok(())
+    }
+
+    /// Get a task by ID.
+    pub fn get_task(&self, id: &str) -> Result<Option<TaskRow>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let mut stmt = conn.prepare(&format!("SELECT {TASK_COLUMNS} FROM tasks WHERE id = ?1"))?;
+        let row = stmt.query_row(params![id], task_from_row).optional()?;
+        Ok(row)
+    }
+
+    /// List the most recent tasks, newest first.
+    pub fn list_tasks(&self, limit: usize) -> Result<Vec<TaskRow>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {TASK_COLUMNS} FROM tasks ORDER BY created_at DESC LIMIT ?1"
+        ))?;
+        let rows = stmt
+            .query_map(
+                params![i64::try_from(limit).unwrap_or(i64::MAX)],
+                task_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// List tasks in the given status, oldest first (queue order).
+    pub fn list_by_status(&self, status: &str) -> Result<Vec<TaskRow>> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {TASK_COLUMNS} FROM tasks WHERE status = ?1 ORDER BY created_at ASC"
+        ))?;
+        let rows = stmt
+            .query_map(params![status], task_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Reconcile tasks left behind by a previous process.
+    ///
+    /// Tasks that were running are re-queued from scratch when
+    /// `requeue_running` is set and they have a stored request; all other
+    /// running tasks, and queued tasks without a request, are marked
+    /// `interrupted`. Returns the number of interrupted tasks.
+    pub fn recover_after_restart(&self, requeue_running: bool, now: &str) -> Result<usize> {
+        let conn = self.conn.lock().expect("mutex poisoned");
+        if requeue_running {
+            conn.execute(
+                "UPDATE tasks
+                 SET status = 'queued', started_at = NULL, progress_current = 0,
+                     progress_total = 0, stage_label = ''
+                 WHERE status = 'running' AND request IS NOT NULL",
+                [],
+            )
+            .context("Failed to re-queue running tasks")?;
+        }
+        let interrupted = conn
+            .execute(
+                "UPDATE tasks SET status = 'interrupted', completed_at = ?1, error = ?2
+                 WHERE status = 'running' OR (status = 'queued' AND request IS NULL)",
+                params![now, INTERRUPTED_ERROR],
+            )
+            .context("Failed to mark interrupted tasks")?;
+        Ok(interrupted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> SqliteTaskStore {
+        crate::data::database::Database::open_in_memory()
+            .expect("open_in_memory")
+            .tasks()
+    }
+
+    fn row(id: &str, status: &str, created_at: &str, request: Option<Value>) -> TaskRow {
+        TaskRow {
+            id: id.to_string(),
+            kind: "sweep".to_string(),
+            strategy: "wheel".to_string(),
+            symbol: "SPY".to_string(),
+            thread_id: None,
+            params: serde_json::json!({"DELTA": 0.3}),
+            request,
+            status: status.to_string(),
+            progress_current: 0,
+            progress_total: 0,
+            stage_label: String::new(),
+            created_at: created_at.to_string(),
+            started_at: None,
+            completed_at: None,
+            result: None,
+            result_id: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_task_lifecycle_round_trip() {
+        let s = store();
+        s.insert_task(&row("t1", "queued", "2024-01-01T00:00:00Z", None))
+            .unwrap();
+        s.mark_started("t1", "2024-01-01T00:00:01Z").unwrap();
+        s.update_progress("t1", 5, 10, "Sweep").unwrap();
+
+        let running = s.get_task("t1").unwrap().expect("should exist");
+        assert_eq!(running.status, "running");
+        assert_eq!(running.progress_current, 5);
+        assert_eq!(running.stage_label, "Sweep");
+        assert_eq!(running.params["DELTA"], 0.3);
+
+        s.finish_task(
+            "t1",
+            "completed",
+            "2024-01-01T00:00:02Z",
+            10,
+            10,
+            Some(&serde_json::json!({"ok": true})),
+            Some("sweep-1"),
+            None,
+        )
+        .unwrap();
+        let done = s.get_task("t1").unwrap().unwrap();
+        assert_eq!(done.status, "completed");
+        assert_eq!(done.result, Some(serde_json::json!({"ok": true})));
+        assert_eq!(done.result_id.as_deref(), Some("sweep-1"));
+        assert!(s.get_task("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_tasks_newest_first_and_by_status_oldest_first() {
+        let s = store();
+        s.insert_task(&row("a", "queued", "2024-01-01T00:00:00Z", None))
+            .unwrap();
+        s.insert_task(&row("b", "completed", "2024-01-02T00:00:00Z", None))
+            .unwrap();
+        s.insert_task(&row("c", "queued", "2024-01-03T00:00:00Z", None))
+            .unwrap();
+
+        let ids: Vec<String> = s.list_tasks(2).unwrap().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec!["c", "b"]);
+        let queued: Vec<String> = s
+            .list_by_status("queued")
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(queued, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_recover_marks_running_interrupted() {
+        let s = store();
+        let req = Some(serde_json::json!({"type": "backtest"}));
+        s.insert_task(&row("run", "running", "2024-01-01T00:00:00Z", req.clone()))
+            .unwrap();
+        s.insert_task(&row("q", "queued", "2024-01-02T00:00:00Z", req))
+            .unwrap();
+        s.insert_task(&row("orphan", "queued", "2024-01-03T00:00:00Z", None))
+            .unwrap();
+
+        let n = s
+            .recover_after_restart(false, "2024-01-04T00:00:00Z")
+            .unwrap();
+        assert_eq!(n, 2);
+        let run = s.get_task("run").unwrap().unwrap();
+        assert_eq!(run.status, "interrupted");
+        assert_eq!(run.error.as_deref(), Some(INTERRUPTED_ERROR));
+        assert_eq!(s.get_task("q").unwrap().unwrap().status, "queued");
+        assert_eq!(s.get_task("orphan").unwrap().unwrap().status, "interrupted");
+    }
+
+    #[test]
+    fn test_recover_requeues_running_with_request() {
+        let s = store();
+        let mut running = row(
+            "run",
+            "running",
+            "2024-01-01T00:00:00Z",
+            Some(serde_json::json!({"type": "backtest"})),
+        );
+        running.progress_current = 7;
+        running.started_at = Some("2024-01-01T00:00:01Z".to_string());
+        s.insert_task(&running).unwrap();
+        s.insert_task(&row("bare", "running", "2024-01-02T00:00:00Z", None))
+            .unwrap();
+
+        let n = s
+            .recover_after_restart(true, "2024-01-04T00:00:00Z")
+            .unwrap();
+        assert_eq!(n, 1);
+        let requeued = s.get_task("run").unwrap().unwrap();
+        assert_eq!(requeued.status, "queued");
+        assert_eq!(requeued.progress_current, 0);
+        assert!(requeued.started_at.is_none());
+        assert_eq!(s.get_task("bare").unwrap().unwrap().status, "interrupted");
+    }
+}
@@ -54,6 +54,8 @@ pub struct TradeRow {
     pub stock_pnl: Option<f64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dividend_pnl: Option<f64>,
 }
 
 /// Summary view of a run (no trades, no `result_json`).
@@ -144,7 +146,7 @@ pub enum RunRow {
         strategy_id: Option<String>,
         strategy_name: Option<String>,
         symbol: String,
-        /// Optimization mode: "grid" or "bayesian"
+        /// Optimization mode: "grid", "bayesian" or "pareto"
         mode: String,
         combinations: i64,
         best_return: Option<f64>,
@@ -219,6 +221,26 @@ pub struct WalkForwardValidation {
     pub created_at: String,
 }
 
+/// A CSCV overfitting analysis attached to a sweep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CscvValidation {
+    pub id: String,
+    pub sweep_id: String,
+    pub n_blocks: i64,
+    pub n_splits: i64,
+    pub n_strategies: i64,
+    pub n_observations: i64,
+    pub pbo: f64,
+    pub degradation_slope: f64,
+    pub degradation_intercept: f64,
+    pub degradation_r2: f64,
+    pub prob_oos_loss: f64,
+    pub median_logit: f64,
+    pub pbo_threshold: f64,
+    pub rejected: bool,
+    pub created_at: String,
+}
+
 /// Full sweep detail with its child runs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SweepDetail {
@@ -239,6 +261,8 @@ pub struct SweepDetail {
     pub created_at: String,
     pub runs: Vec<RunSummary>,
     pub validations: Vec<WalkForwardValidation>,
+    #[serde(default)]
+    pub cscv_validations: Vec<CscvValidation>,
 }
 
 // ──────────────────────────────────────────────────────────────────────────────
@@ -347,6 +371,29 @@ pub trait RunStore: Send + Sync {
 
     /// Delete a walk-forward validation.
     fn delete_walk_forward_validation(&self, id: &str) -> Result<bool>;
+
+    /// Insert a CSCV overfitting analysis. Returns `created_at` timestamp.
+    #[allow(clippy::too_many_arguments)]
+    fn insert_cscv_validation(
+        &self,
+        id: &str,
+        sweep_id: &str,
+        n_blocks: i64,
+        n_splits: i64,
+        n_strategies: i64,
+        n_observations: i64,
+        pbo: f64,
+        degradation_slope: f64,
+        degradation_intercept: f64,
+        degradation_r2: f64,
+        prob_oos_loss: f64,
+        median_logit: f64,
+        pbo_threshold: f64,
+        rejected: bool,
+    ) -> Result<String>;
+
+    /// Get all CSCV overfitting analyses for a sweep, newest first.
+    fn get_cscv_validations(&self, sweep_id: &str) -> Result<Vec<CscvValidation>>;
 }
 
 // ──────────────────────────────────────────────────────────────────────────────
@@ -514,7 +561,7 @@ pub fn seed_strategies_if_empty(store: &dyn StrategyStore, scripts_dir: &Path) -
         // For .trading files, transpile to Rhai to extract //! metadata,
         // but store the original DSL source (transpiled on demand at runtime).
         let meta_source = if filename.ends_with(".trading") {
-            match crate::scripting::dsl::transpile(&source) {
+            match crate::scripting::dsl::transpile_for_metadata(&source) {
                 Ok(rhai) => rhai,
                 Err(_) => continue,
             }
@@ -1,11 +1,18 @@
 //! Bayesian optimization using Gaussian Process with Expected Improvement.
-
-use std::collections::HashMap;
+//!
+//! The search space mixes continuous dimensions (one coordinate each) with
+//! categorical dimensions (one-hot encoded, decoded by argmax). Parameters can
+//! be made conditional on a parent categorical choice; inactive ones are left
+//! out of the backtest params and their coordinates are zeroed so the GP sees
+//! every inactive configuration as the same point.
+
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::{FRAC_1_SQRT_2, PI};
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::engine::sweep::{compute_sensitivity, extract_objective};
@@ -22,14 +29,184 @@ pub struct BayesianConfig {
     /// `step` controls rounding precision — e.g. 0.01 for delta, 1.0 for DTE.
     /// When `None`, defaults to 1.0 for ints, 0.01 for floats.
     pub continuous_params: Vec<(String, f64, f64, bool, Option<f64>)>,
+    /// Categorical dimensions, e.g. script externs declared with `choices`.
+    pub categorical_params: Vec<CategoricalParam>,
+    /// Activation conditions keyed by child parameter name.
+    pub conditions: HashMap<String, ParamCondition>,
     pub max_evaluations: usize,
     pub initial_samples: usize,
     pub objective: String,
 }
 
+/// A categorical search dimension.
+#[derive(Debug, Clone)]
+pub struct CategoricalParam {
+    pub name: String,
+    pub choices: Vec<Value>,
+}
+
+/// Makes a parameter apply only when a parent categorical parameter takes one
+/// of `values` (e.g. a spread-width param that only matters for `spread` slippage).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ParamCondition {
+    /// Name of the parent categorical parameter.
+    pub parent: String,
+    /// Parent choices under which the parameter is active.
+    pub values: Vec<Value>,
+}
+
+/// Mixed continuous/categorical search space encoded into `[0,1]^dim`.
+pub(crate) struct SearchSpace<'a> {
+    continuous: &'a [(String, f64, f64, bool, Option<f64>)],
+    categorical: &'a [CategoricalParam],
+    conditions: &'a HashMap<String, ParamCondition>,
+}
+
+impl<'a> SearchSpace<'a> {
+    /// Build and validate the search space described by `config`.
+    pub(crate) fn new(config: &'a BayesianConfig) -> Result<Self> {
+        let space = Self {
+            continuous: &config.continuous_params,
+            categorical: &config.categorical_params,
+            conditions: &config.conditions,
+        };
+        space.validate()?;
+        Ok(space)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for name in self
+            .continuous
+            .iter()
+            .map(|(n, ..)| n)
+            .chain(self.categorical.iter().map(|c| &c.name))
+        {
+            if !names.insert(name.as_str()) {
+                bail!("Sweep param '{name}' is declared more than once");
+            }
+        }
+        for cat in self.categorical {
+            if cat.choices.is_empty() {
+                bail!("Categorical sweep param '{}' has no choices", cat.name);
+            }
+        }
+        for (child, cond) in self.conditions {
+            if !names.contains(child.as_str()) {
+                bail!("Condition declared for unknown sweep param '{child}'");
+            }
+            let Some(parent) = self.categorical.iter().find(|c| c.name == cond.parent) else {
+                bail!(
+                    "Sweep param '{child}' depends on '{}', which is not a categorical sweep param",
+                    cond.parent
+                );
+            };
+            if cond.values.is_empty() {
+                bail!("Condition on sweep param '{child}' lists no parent values");
+            }
+            if let Some(v) = cond.values.iter().find(|v| !parent.choices.contains(v)) {
+                bail!(
+                    "Condition on sweep param '{child}' uses {v}, which is not a choice of '{}'",
+                    cond.parent
+                );
+            }
+            // Walk up the parent chain; revisiting a name means a cycle.
+            let mut seen = HashSet::from([child.as_str()]);
+            let mut current = cond.parent.as_str();
+            while let Some(next) = self.conditions.get(current) {
+                if !seen.insert(current) {
+                    bail!("Sweep param conditions form a cycle through '{current}'");
+                }
+                current = next.parent.as_str();
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of encoded coordinates.
+    pub(crate) fn dim(&self) -> usize {
+        self.continuous.len()
+            + self
+                .categorical
+                .iter()
+                .map(|c| c.choices.len())
+                .sum::<usize>()
+    }
+
+    /// Decode a point into parameter values, returning them together with the
+    /// canonical encoding of the point (exact one-hot blocks, inactive
+    /// coordinates zeroed) that the GP should be trained on.
+    pub(crate) fn decode(&self, x: &[f64]) -> (HashMap<String, Value>, Vec<f64>) {
+        let n = self.continuous.len();
+        let mut swept = decode_params(&x[..n], self.continuous);
+        let mut canonical = x.to_vec();
+
+        let mut blocks = Vec::with_capacity(self.categorical.len());
+        let mut offset = n;
+        for cat in self.categorical {
+            let end = offset + cat.choices.len();
+            let idx = argmax(&x[offset..end]);
+            for (j, c) in canonical[offset..end].iter_mut().enumerate() {
+                *c = if j == idx { 1.0 } else { 0.0 };
+            }
+            swept.insert(cat.name.clone(), cat.choices[idx].clone());
+            blocks.push(offset..end);
+            offset = end;
+        }
+
+        if !self.conditions.is_empty() {
+            let inactive: HashSet<String> = swept
+                .keys()
+                .filter(|name| !self.is_active(name, &swept))
+                .cloned()
+                .collect();
+            for (i, (name, ..)) in self.continuous.iter().enumerate() {
+                if inactive.contains(name) {
+                    canonical[i] = 0.0;
+                }
+            }
+            for (cat, block) in self.categorical.iter().zip(blocks) {
+                if inactive.contains(&cat.name) {
+                    canonical[block].fill(0.0);
+                }
+            }
+            swept.retain(|name, _| !inactive.contains(name));
+        }
+
+        (swept, canonical)
+    }
+
+    /// Whether `name` and every ancestor's condition is satisfied.
+    fn is_active(&self, name: &str, swept: &HashMap<String, Value>) -> bool {
+        let mut current = name;
+        while let Some(cond) = self.conditions.get(current) {
+            match swept.get(&cond.parent) {
+                Some(v) if cond.values.contains(v) => current = cond.parent.as_str(),
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Index of the largest value (first one on ties).
+fn argmax(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |(best_i, best_v), (i, &v)| {
+            if v > best_v {
+                (i, v)
+            } else {
+                (best_i, best_v)
+            }
+        })
+        .0
+}
+
 /// Build a deterministic cache key from decoded parameters.
 /// Single-allocation: sorts by borrowed key, writes directly into one String.
-fn cache_key(swept: &HashMap<String, Value>) -> String {
+pub(crate) fn cache_key(swept: &HashMap<String, Value>) -> String {
     use std::fmt::Write;
     let mut pairs: Vec<_> = swept.iter().collect();
     pairs.sort_by_key(|(k, _)| k.as_str());
@@ -44,15 +221,28 @@ fn cache_key(swept: &HashMap<String, Value>) -> String {
 }
 
 /// Run Bayesian optimization with GP-EI.
-#[allow(clippy::too_many_lines)]
 pub async fn run_bayesian(
     config: &BayesianConfig,
     data_loader: &dyn DataLoader,
     is_cancelled: &CancelCallback,
     on_progress: impl Fn(usize, usize),
+) -> Result<SweepResponse> {
+    run_bayesian_with_results(config, data_loader, is_cancelled, on_progress, |_| {}).await
+}
+
+/// Run Bayesian optimization, reporting each fresh evaluation to `on_result`
+/// as soon as it completes (cache hits are not repeated).
+#[allow(clippy::too_many_lines)]
+pub async fn run_bayesian_with_results(
+    config: &BayesianConfig,
+    data_loader: &dyn DataLoader,
+    is_cancelled: &CancelCallback,
+    on_progress: impl Fn(usize, usize),
+    on_result: impl Fn(&SweepResult),
 ) -> Result<SweepResponse> {
     let start = Instant::now();
-    let dim = config.continuous_params.len();
+    let space = SearchSpace::new(config)?;
+    let dim = space.dim();
     let mut xs: Vec<Vec<f64>> = Vec::new();
     let mut ys: Vec<f64> = Vec::new();
     let mut results: Vec<SweepResult> = Vec::new();
@@ -88,10 +278,10 @@ pub async fn run_bayesian(
             random_point(dim)
         } else {
             let gp = GaussianProcess::fit(&xs, &ys);
-            maximize_ei(&gp, best_so_far, dim)
+            maximize_ei(&gp, best_so_far, &space)
         };
 
-        let swept = decode_params(&x, &config.continuous_params);
+        let (swept, x) = space.decode(&x);
         let key = cache_key(&swept);
 
         if let Some((cached_result, cached_bt, cached_obj)) = eval_cache.get(&key) {
@@ -142,6 +332,7 @@ pub async fn run_bayesian(
                 }
             }
             convergence_trace.push(trace_val(best_so_far));
+            on_result(&result);
             results.push(result);
             full_results.push(bt);
 
@@ -218,6 +409,8 @@ pub async fn run_bayesian(
         convergence_trace: Some(convergence_trace),
         execution_time_ms: start.elapsed().as_millis() as u64,
         multiple_comparisons: None,
+        reality_check: None,
+        pareto: None,
         full_results,
     })
 }
@@ -449,13 +642,16 @@ fn expected_improvement(mean: f64, var: f64, best_y: f64) -> f64 {
 }
 
 /// Find the point that maximizes EI by random candidate sampling (1000 candidates).
-fn maximize_ei(gp: &GaussianProcess, best_y: f64, dim: usize) -> Vec<f64> {
+/// Candidates are canonicalized first so the GP is queried in the same
+/// encoding it was trained on.
+fn maximize_ei(gp: &GaussianProcess, best_y: f64, space: &SearchSpace) -> Vec<f64> {
     let n_candidates = 1000;
+    let dim = space.dim();
     let mut best_ei = f64::NEG_INFINITY;
     let mut best_x = random_point(dim);
 
     for _ in 0..n_candidates {
-        let x = random_point(dim);
+        let (_, x) = space.decode(&random_point(dim));
         let (mean, var) = gp.predict(&x);
         let ei = expected_improvement(mean, var, best_y);
         if ei > best_ei {
@@ -510,7 +706,7 @@ pub fn random_point(dim: usize) -> Vec<f64> {
 
 /// Evaluate a single parameter combination.
 /// Takes owned `swept_params` to avoid a clone — caller moves the `HashMap` in.
-async fn evaluate(
+pub(crate) async fn evaluate(
     script_source: &str,
     base_params: &HashMap<String, Value>,
     swept_params: HashMap<String, Value>,
@@ -810,6 +1006,120 @@ mod tests {
         assert!((b - 0.5).abs() < 0.01);
     }
 
+    // ── SearchSpace ──────────────────────────────────────────────────
+
+    fn mixed_config(conditions: HashMap<String, ParamCondition>) -> BayesianConfig {
+        BayesianConfig {
+            script_source: String::new(),
+            base_params: HashMap::new(),
+            continuous_params: vec![("WIDTH".to_string(), 1.0, 5.0, true, None)],
+            categorical_params: vec![
+                CategoricalParam {
+                    name: "SLIPPAGE".to_string(),
+                    choices: vec![json!("mid"), json!("spread"), json!("liquidity")],
+                },
+                CategoricalParam {
+                    name: "SIDE".to_string(),
+                    choices: vec![json!("put"), json!("call")],
+                },
+            ],
+            conditions,
+            max_evaluations: 10,
+            initial_samples: 3,
+            objective: "sharpe".to_string(),
+        }
+    }
+
+    fn width_when_spread() -> HashMap<String, ParamCondition> {
+        HashMap::from([(
+            "WIDTH".to_string(),
+            ParamCondition {
+                parent: "SLIPPAGE".to_string(),
+                values: vec![json!("spread")],
+            },
+        )])
+    }
+
+    #[test]
+    fn search_space_decodes_categorical_by_argmax() {
+        let config = mixed_config(HashMap::new());
+        let space = SearchSpace::new(&config).unwrap();
+        assert_eq!(space.dim(), 6);
+
+        let (params, canonical) = space.decode(&[0.5, 0.2, 0.9, 0.4, 0.7, 0.1]);
+        assert_eq!(params["WIDTH"], json!(3_i64));
+        assert_eq!(params["SLIPPAGE"], json!("spread"));
+        assert_eq!(params["SIDE"], json!("put"));
+        assert_eq!(canonical, vec![0.5, 0.0, 1.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn search_space_drops_inactive_conditional_params() {
+        let config = mixed_config(width_when_spread());
+        let space = SearchSpace::new(&config).unwrap();
+
+        let (params, canonical) = space.decode(&[0.5, 0.9, 0.2, 0.4, 0.7, 0.1]);
+        assert_eq!(params["SLIPPAGE"], json!("mid"));
+        assert!(!params.contains_key("WIDTH"));
+        assert!(canonical[0].abs() < f64::EPSILON);
+
+        let (params, canonical) = space.decode(&[0.5, 0.2, 0.9, 0.4, 0.7, 0.1]);
+        assert_eq!(params["WIDTH"], json!(3_i64));
+        assert!((canonical[0] - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn search_space_applies_nested_conditions() {
+        let mut conditions = width_when_spread();
+        conditions.insert(
+            "SLIPPAGE".to_string(),
+            ParamCondition {
+                parent: "SIDE".to_string(),
+                values: vec![json!("call")],
+            },
+        );
+        let config = mixed_config(conditions);
+        let space = SearchSpace::new(&config).unwrap();
+
+        // SIDE=put deactivates SLIPPAGE, and with it WIDTH.
+        let (params, canonical) = space.decode(&[0.5, 0.2, 0.9, 0.4, 0.7, 0.1]);
+        assert_eq!(params.len(), 1);
+        assert_eq!(params["SIDE"], json!("put"));
+        assert_eq!(canonical, vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn search_space_rejects_invalid_conditions() {
+        let mut conditions = width_when_spread();
+        conditions.get_mut("WIDTH").unwrap().parent = "WIDTH".to_string();
+        assert!(SearchSpace::new(&mixed_config(conditions)).is_err());
+
+        let mut conditions = width_when_spread();
+        conditions.get_mut("WIDTH").unwrap().values = vec![json!("fixed")];
+        assert!(SearchSpace::new(&mixed_config(conditions)).is_err());
+
+        let mut conditions = width_when_spread();
+        conditions.insert(
+            "SLIPPAGE".to_string(),
+            ParamCondition {
+                parent: "SIDE".to_string(),
+                values: vec![json!("call")],
+            },
+        );
+        conditions.insert(
+            "SIDE".to_string(),
+            ParamCondition {
+                parent: "SLIPPAGE".to_string(),
+                values: vec![json!("mid")],
+            },
+        );
+        let err = SearchSpace::new(&mixed_config(conditions))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("cycle"), "{err}");
+    }
+
     // ── GP edge cases ────────────────────────────────────────────────
 
     #[test]
@@ -0,0 +1,662 @@
+//! Combinatorial purged cross-validation (CPCV) for walk-forward analysis,
+//! after López de Prado (2018), ch. 12.
+//!
+//! The date range is cut into `N` contiguous groups. Every choice of `k`
+//! groups is a test set and the remaining groups are the training set, giving
+//! `C(N, k)` splits. Training observations that fall inside the holding period
+//! of a trade that overlaps a test group are purged, and an embargo of bars
+//! after each test group is dropped too — options positions held 30–45 days
+//! would otherwise leak test-period information into training.
+//!
+//! Each group is tested in `C(N-1, k-1)` splits, so the test groups can be
+//! assembled into that many complete out-of-sample backtest paths. The
+//! engine reports the distribution of path Sharpe ratios instead of a single
+//! stitched curve.
+//!
+//! Every combo is backtested once over the full range; train and test metrics
+//! are computed from its daily returns on the selected dates.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::constants::MAX_PROFIT_FACTOR;
+use crate::engine::types::{EquityPoint, TradeRecord};
+use crate::engine::walk_forward::{WfObjective, WfWindowResult};
+use crate::scripting::engine::{run_script_backtest, CancelCallback, DataLoader};
+
+/// Upper bound on generated splits (keeps `N` and `k` from exploding).
+const MAX_SPLITS: usize = 5_000;
+
+/// Minimum bars per group for meaningful group metrics.
+const MIN_BARS_PER_GROUP: usize = 2;
+
+/// Trading bars per year used to annualize daily-return metrics.
+const BARS_PER_YEAR: f64 = 252.0;
+
+/// Settings for `WfMode::Cpcv`. The number of groups is `n_windows`.
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+pub struct CpcvConfig {
+    /// Groups held out as the test set in each split (default: 2).
+    #[serde(default = "default_n_test_groups")]
+    pub n_test_groups: usize,
+    /// Bars dropped from training after each test group (default: 5).
+    #[serde(default = "default_embargo_bars")]
+    pub embargo_bars: usize,
+}
+
+impl Default for CpcvConfig {
+    fn default() -> Self {
+        Self {
+            n_test_groups: default_n_test_groups(),
+            embargo_bars: default_embargo_bars(),
+        }
+    }
+}
+
+fn default_n_test_groups() -> usize {
+    2
+}
+fn default_embargo_bars() -> usize {
+    5
+}
+
+/// One CPCV train/test split.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CpcvSplit {
+    pub split_idx: usize,
+    /// Indices of the groups used as the test set.
+    pub test_groups: Vec<usize>,
+    /// Training bars removed by purging and embargo for the selected combo.
+    pub purged_bars: usize,
+}
+
+/// One out-of-sample backtest path assembled from test groups.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CpcvPath {
+    pub path_idx: usize,
+    /// Annualized Sharpe ratio of the path's daily returns.
+    pub sharpe: f64,
+    pub total_return: f64,
+    pub max_drawdown: f64,
+}
+
+/// Distribution of out-of-sample results across CPCV paths.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CpcvSummary {
+    pub n_groups: usize,
+    pub n_test_groups: usize,
+    pub embargo_bars: usize,
+    pub n_splits: usize,
+    pub n_paths: usize,
+    pub splits: Vec<CpcvSplit>,
+    pub paths: Vec<CpcvPath>,
+    pub oos_sharpe_mean: f64,
+    pub oos_sharpe_std: f64,
+    pub oos_sharpe_median: f64,
+    pub oos_sharpe_p5: f64,
+    pub oos_sharpe_p95: f64,
+    /// Share of paths with a negative Sharpe ratio.
+    pub prob_negative_sharpe: f64,
+    /// Index of the median-Sharpe path, reported as the stitched equity.
+    pub representative_path: usize,
+}
+
+/// Output of a CPCV run, consumed by the walk-forward engine.
+pub(crate) struct CpcvRun {
+    pub windows: Vec<WfWindowResult>,
+    pub equity: Vec<EquityPoint>,
+    pub is_metrics_sum: f64,
+    pub oos_metrics_sum: f64,
+    pub summary: CpcvSummary,
+}
+
+/// Daily returns and trade holding spans of one combo's full-range backtest.
+struct ComboSeries {
+    returns: Vec<f64>,
+    holdings: Vec<(usize, usize)>,
+}
+
+/// Split `n_obs` bars into `n_groups` contiguous, near-equal ranges.
+pub fn cpcv_groups(n_obs: usize, n_groups: usize) -> Vec<Range<usize>> {
+    (0..n_groups)
+        .map(|g| (g * n_obs / n_groups)..((g + 1) * n_obs / n_groups))
+        .collect()
+}
+
+/// All `k`-subsets of `0..n_groups` in lexicographic order.
+pub fn cpcv_splits(n_groups: usize, k: usize) -> Vec<Vec<usize>> {
+    fn recurse(start: usize, n: usize, k: usize, cur: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        if cur.len() == k {
+            out.push(cur.clone());
+            return;
+        }
+        for g in start..n {
+            if n - g < k - cur.len() {
+                break;
+            }
+            cur.push(g);
+            recurse(g + 1, n, k, cur, out);
+            cur.pop();
+        }
+    }
+    let mut out = Vec::new();
+    recurse(0, n_groups, k, &mut Vec::with_capacity(k), &mut out);
+    out
+}
+
+/// Assign splits to backtest paths: `paths[p][g]` is the split whose test
+/// result fills group `g` on path `p`. The `j`-th split testing a group goes
+/// to path `j`, so every path covers every group exactly once.
+pub fn cpcv_paths(n_groups: usize, splits: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut per_group: Vec<Vec<usize>> = vec![Vec::new(); n_groups];
+    for (s, test) in splits.iter().enumerate() {
+        for &g in test {
+            per_group[g].push(s);
+        }
+    }
+    let n_paths = per_group.iter().map(Vec::len).min().unwrap_or(0);
+    (0..n_paths)
+        .map(|p| per_group.iter().map(|splits| splits[p]).collect())
+        .collect()
+}
+
+/// Training mask for one split: bars outside the test groups, minus bars
+/// covered by a trade whose holding span overlaps a test group (purge) and
+/// `embargo` bars after each test group.
+pub fn purged_train_mask(
+    n_obs: usize,
+    test_ranges: &[Range<usize>],
+    holdings: &[(usize, usize)],
+    embargo: usize,
+) -> Vec<bool> {
+    let mut mask = vec![true; n_obs];
+    for range in test_ranges {
+        mask[range.clone()].fill(false);
+        let embargo_end = (range.end + embargo).min(n_obs);
+        mask[range.end..embargo_end].fill(false);
+    }
+    for &(entry, exit) in holdings {
+        let overlaps = test_ranges.iter().any(|r| entry < r.end && exit >= r.start);
+        if overlaps {
+            mask[entry..=exit.min(n_obs - 1)].fill(false);
+        }
+    }
+    mask
+}
+
+/// Run CPCV: backtest every combo over the full range, then select and
+/// score combos split by split.
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+pub(crate) async fn run(
+    script_source: &str,
+    base_params: &HashMap<String, Value>,
+    combos: &[HashMap<String, Value>],
+    dates: &[NaiveDate],
+    capital: f64,
+    objective: &WfObjective,
+    n_groups: usize,
+    config: CpcvConfig,
+    data_loader: &dyn DataLoader,
+    is_cancelled: &CancelCallback,
+    on_progress: impl Fn(usize, usize),
+    on_window: impl Fn(&WfWindowResult),
+) -> Result<CpcvRun> {
+    let k = config.n_test_groups;
+    if k == 0 || k >= n_groups {
+        bail!("CPCV needs 1 <= n_test_groups < n_windows (got {k} of {n_groups} groups)");
+    }
+    let n_obs = dates.len();
+    if n_obs < n_groups * MIN_BARS_PER_GROUP {
+        bail!("Not enough data for {n_groups} CPCV groups ({n_obs} dates)");
+    }
+    let splits = cpcv_splits(n_groups, k);
+    if splits.len() > MAX_SPLITS {
+        bail!(
+            "CPCV with {n_groups} groups and {k} test groups yields {} splits (max {MAX_SPLITS})",
+            splits.len()
+        );
+    }
+    let groups = cpcv_groups(n_obs, n_groups);
+
+    // Full-range backtest per combo; reuse precomputed options across combos.
+    let mut precomputed: Option<crate::scripting::engine::PrecomputedOptionsData> = None;
+    let mut series: Vec<Option<ComboSeries>> = Vec::with_capacity(combos.len());
+    for (combo_idx, combo) in combos.iter().enumerate() {
+        if is_cancelled() {
+            bail!("Walk-forward cancelled");
+        }
+        let mut run_params = base_params.clone();
+        run_params.extend(combo.clone());
+        run_params.insert(
+            "START_DATE".to_string(),
+            serde_json::json!(dates[0].to_string()),
+        );
+        run_params.insert(
+            "END_DATE".to_string(),
+            serde_json::json!(dates[n_obs - 1].to_string()),
+        );
+        match run_script_backtest(
+            script_source,
+            &run_params,
+            data_loader,
+            None,
+            precomputed.as_ref(),
+            Some(is_cancelled),
+        )
+        .await
+        {
+            Err(e) => {
+                tracing::warn!(combo = combo_idx, "CPCV backtest run failed: {e:#}");
+                series.push(None);
+            }
+            Ok(result) => {
+                if precomputed.is_none() {
+                    precomputed = result.precomputed_options;
+                }
+                series.push(Some(ComboSeries {
+                    returns: daily_returns(&result.result.equity_curve, dates, capital),
+                    holdings: holding_spans(&result.result.trade_log, dates),
+                }));
+            }
+        }
+        on_progress(combo_idx + 1, combos.len());
+    }
+    if series.iter().all(Option::is_none) {
+        bail!("All CPCV backtest runs failed");
+    }
+
+    // Select the best combo on purged training data for each split.
+    let mut windows = Vec::with_capacity(splits.len());
+    let mut split_infos = Vec::with_capacity(splits.len());
+    let mut selected = Vec::with_capacity(splits.len());
+    let mut is_metrics_sum = 0.0;
+    let mut oos_metrics_sum = 0.0;
+    for (split_idx, test_groups) in splits.iter().enumerate() {
+        let test_ranges: Vec<Range<usize>> =
+            test_groups.iter().map(|&g| groups[g].clone()).collect();
+
+        let mut best: Option<(usize, f64, usize)> = None;
+        for (combo_idx, s) in series.iter().enumerate() {
+            let Some(s) = s else { continue };
+            let mask = purged_train_mask(n_obs, &test_ranges, &s.holdings, config.embargo_bars);
+            let train: Vec<f64> = s
+                .returns
+                .iter()
+                .zip(&mask)
+                .filter_map(|(r, &keep)| keep.then_some(*r))
+                .collect();
+            let metric = objective_metric(&train, objective);
+            if metric.is_finite() && best.is_none_or(|(_, m, _)| metric > m) {
+                let in_test: usize = test_ranges.iter().map(ExactSizeIterator::len).sum();
+                let purged = n_obs - in_test - train.len();
+                best = Some((combo_idx, metric, purged));
+            }
+        }
+        let (combo_idx, is_metric, purged_bars) =
+            best.unwrap_or((series.iter().position(Option::is_some).unwrap_or(0), 0.0, 0));
+
+        let test_returns: Vec<f64> = series[combo_idx]
+            .as_ref()
+            .map(|s| {
+                test_ranges
+                    .iter()
+                    .flat_map(|r| s.returns[r.clone()].iter().copied())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let oos_metric = objective_metric(&test_returns, objective);
+        is_metrics_sum += is_metric;
+        oos_metrics_sum += if oos_metric.is_finite() {
+            oos_metric
+        } else {
+            0.0
+        };
+
+        // Training span is not contiguous; report its outer bounds.
+        let train_idx: Vec<usize> = (0..n_obs)
+            .filter(|i| !test_ranges.iter().any(|r| r.contains(i)))
+            .collect();
+        let window = WfWindowResult {
+            window_idx: split_idx,
+            train_start: train_idx
+                .first()
+                .map_or_else(String::new, |&i| dates[i].to_string()),
+            train_end: train_idx
+                .last()
+                .map_or_else(String::new, |&i| dates[i].to_string()),
+            test_start: dates[test_ranges[0].start].to_string(),
+            test_end: dates[test_ranges[k - 1].end - 1].to_string(),
+            best_params: combos[combo_idx].clone(),
+            in_sample_metric: is_metric,
+            out_of_sample_metric: oos_metric,
+        };
+        on_window(&window);
+        windows.push(window);
+        split_infos.push(CpcvSplit {
+            split_idx,
+            test_groups: test_groups.clone(),
+            purged_bars,
+        });
+        selected.push(combo_idx);
+    }
+
+    // Assemble full out-of-sample paths from the test groups.
+    let path_assignments = cpcv_paths(n_groups, &splits);
+    let path_returns: Vec<Vec<f64>> = path_assignments
+        .iter()
+        .map(|assignment| {
+            assignment
+                .iter()
+                .enumerate()
+                .flat_map(|(g, &split)| {
+                    let returns = series[selected[split]]
+                        .as_ref()
+                        .map_or(&[][..], |s| &s.returns[groups[g].clone()]);
+                    returns.iter().copied()
+                })
+                .collect()
+        })
+        .collect();
+    let paths: Vec<CpcvPath> = path_returns
+        .iter()
+        .enumerate()
+        .map(|(path_idx, returns)| {
+            let equity = compound(returns);
+            CpcvPath {
+                path_idx,
+                sharpe: sharpe(returns),
+                total_return: equity.last().copied().unwrap_or(1.0) - 1.0,
+                max_drawdown: max_drawdown(&equity),
+            }
+        })
+        .collect();
+
+    let mut sharpes: Vec<f64> = paths.iter().map(|p| p.sharpe).collect();
+    sharpes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let n_paths = sharpes.len();
+    let mean = sharpes.iter().sum::<f64>() / n_paths.max(1) as f64;
+    let std = if n_paths > 1 {
+        (sharpes.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n_paths - 1) as f64).sqrt()
+    } else {
+        0.0
+    };
+    let median = percentile(&sharpes, 0.5);
+    let representative_path = paths
+        .iter()
+        .min_by(|a, b| {
+            (a.sharpe - median)
+                .abs()
+                .partial_cmp(&(b.sharpe - median).abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map_or(0, |p| p.path_idx);
+
+    let equity = path_returns
+        .get(representative_path)
+        .map(|returns| {
+            compound(returns)
+                .into_iter()
+                .zip(dates)
+                .map(|(growth, date)| EquityPoint {
+                    datetime: date.and_hms_opt(0, 0, 0).unwrap_or_default(),
+                    equity: capital * growth,
+                    unrealized: None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let summary = CpcvSummary {
+        n_groups,
+        n_test_groups: k,
+        embargo_bars: config.embargo_bars,
+        n_splits: splits.len(),
+        n_paths,
+        splits: split_infos,
+        paths,
+        oos_sharpe_mean: mean,
+        oos_sharpe_std: std,
+        oos_sharpe_median: median,
+        oos_sharpe_p5: percentile(&sharpes, 0.05),
+        oos_sharpe_p95: percentile(&sharpes, 0.95),
+        prob_negative_sharpe: sharpes.iter().filter(|&&s| s < 0.0).count() as f64
+            / n_paths.max(1) as f64,
+        representative_path,
+    };
+
+    Ok(CpcvRun {
+        windows,
+        equity,
+        is_metrics_sum,
+        oos_metrics_sum,
+        summary,
+    })
+}
+
+/// Daily returns on `dates` from an equity curve (last point per date,
+/// carried forward over dates without points).
+fn daily_returns(curve: &[EquityPoint], dates: &[NaiveDate], capital: f64) -> Vec<f64> {
+    let mut eod: Vec<Option<f64>> = vec![None; dates.len()];
+    for point in curve {
+        if let Ok(i) = dates.binary_search(&point.datetime.date()) {
+            eod[i] = Some(point.equity);
+        }
+    }
+    let mut prev = capital;
+    eod.into_iter()
+        .map(|equity| {
+            let equity = equity.unwrap_or(prev);
+            let r = if prev.abs() > f64::EPSILON {
+                equity / prev - 1.0
+            } else {
+                0.0
+            };
+            prev = equity;
+            r
+        })
+        .collect()
+}
+
+/// Bar index spans `[entry, exit]` covered by each trade.
+fn holding_spans(trades: &[TradeRecord], dates: &[NaiveDate]) -> Vec<(usize, usize)> {
+    let last = dates.len().saturating_sub(1);
+    trades
+        .iter()
+        .map(|t| {
+            let entry = dates.partition_point(|d| *d < t.entry_datetime.date());
+            let exit = dates
+                .partition_point(|d| *d <= t.exit_datetime.date())
+                .saturating_sub(1);
+            (entry.min(last), exit.max(entry).min(last))
+        })
+        .collect()
+}
+
+/// Objective metric on a daily return sample.
+///
+/// Profit factor is computed on daily returns rather than trades, since the
+/// selected bars do not map onto whole trades.
+fn objective_metric(returns: &[f64], objective: &WfObjective) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    match objective {
+        WfObjective::Sharpe => sharpe(returns),
+        WfObjective::Sortino => {
+            let n = returns.len() as f64;
+            let mean = returns.iter().sum::<f64>() / n;
+            let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+            if downside < 1e-12 {
+                0.0
+            } else {
+                mean / downside * BARS_PER_YEAR.sqrt()
+            }
+        }
+        WfObjective::ProfitFactor => {
+            let gains: f64 = returns.iter().filter(|r| **r > 0.0).sum();
+            let losses: f64 = -returns.iter().filter(|r| **r < 0.0).sum::<f64>();
+            if losses < 1e-12 {
+                if gains > 0.0 {
+                    MAX_PROFIT_FACTOR
+                } else {
+                    0.0
+                }
+            } else {
+                gains / losses
+            }
+        }
+        WfObjective::Cagr => {
+            let growth = compound(returns).last().copied().unwrap_or(1.0);
+            if growth <= 0.0 {
+                -1.0
+            } else {
+                growth.powf(BARS_PER_YEAR / returns.len() as f64) - 1.0
+            }
+        }
+    }
+}
+
+/// Annualized Sharpe ratio of daily returns.
+fn sharpe(returns: &[f64]) -> f64 {
+    let n = returns.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let nf = n as f64;
+    let mean = returns.iter().sum::<f64>() / nf;
+    let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (nf - 1.0)).sqrt();
+    if std < 1e-12 {
+        0.0
+    } else {
+        mean / std * BARS_PER_YEAR.sqrt()
+    }
+}
+
+/// Cumulative growth of 1 unit after each return.
+fn compound(returns: &[f64]) -> Vec<f64> {
+    returns
+        .iter()
+        .scan(1.0, |acc, r| {
+            *acc *= 1.0 + r;
+            Some(*acc)
+        })
+        .collect()
+}
+
+/// Maximum peak-to-trough decline of a growth curve, as a fraction.
+fn max_drawdown(growth: &[f64]) -> f64 {
+    let mut peak = 1.0_f64;
+    let mut max_dd = 0.0_f64;
+    for &g in growth {
+        peak = peak.max(g);
+        if peak > 0.0 {
+            max_dd = max_dd.max((peak - g) / peak);
+        }
+    }
+    max_dd
+}
+
+/// Linear-interpolated percentile of sorted values.
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = q * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_cover_all_bars() {
+        let groups = cpcv_groups(103, 6);
+        assert_eq!(groups.len(), 6);
+        assert_eq!(groups[0].start, 0);
+        assert_eq!(groups[5].end, 103);
+        for pair in groups.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+    }
+
+    #[test]
+    fn splits_and_paths_follow_combinatorics() {
+        // N=6, k=2: C(6,2)=15 splits, C(5,1)=5 paths.
+        let splits = cpcv_splits(6, 2);
+        assert_eq!(splits.len(), 15);
+        assert_eq!(splits[0], vec![0, 1]);
+        let paths = cpcv_paths(6, &splits);
+        assert_eq!(paths.len(), 5);
+        for path in &paths {
+            assert_eq!(path.len(), 6);
+            for (g, &split) in path.iter().enumerate() {
+                assert!(
+                    splits[split].contains(&g),
+                    "path uses a split that tests group {g}"
+                );
+            }
+        }
+        // Each split is used by exactly k path-group slots.
+        let mut uses = vec![0; splits.len()];
+        for path in &paths {
+            for &split in path {
+                uses[split] += 1;
+            }
+        }
+        assert!(uses.iter().all(|&u| u == 2));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn purge_removes_overlapping_holdings_and_embargo() {
+        // 30 bars, test group 10..20; one trade spans 6..12 (overlaps test),
+        // one spans 0..3 (does not).
+        let mask = purged_train_mask(30, &[10..20], &[(6, 12), (0, 3)], 3);
+        assert!(
+            mask[0..6].iter().all(|&m| m),
+            "non-overlapping trade is kept"
+        );
+        assert!(
+            mask[6..20].iter().all(|&m| !m),
+            "overlapping trade and test are removed"
+        );
+        assert!(mask[20..23].iter().all(|&m| !m), "embargo after test group");
+        assert!(mask[23..].iter().all(|&m| m));
+    }
+
+    #[test]
+    fn daily_returns_carry_forward_missing_dates() {
+        let dates: Vec<NaiveDate> = (1..=4)
+            .map(|d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap())
+            .collect();
+        let point = |d: usize, equity: f64| EquityPoint {
+            datetime: dates[d].and_hms_opt(16, 0, 0).unwrap(),
+            equity,
+            unrealized: None,
+        };
+        let curve = vec![point(0, 100.0), point(1, 110.0), point(3, 121.0)];
+        let returns = daily_returns(&curve, &dates, 100.0);
+        assert_eq!(returns.len(), 4);
+        assert!(returns[0].abs() < 1e-12);
+        assert!((returns[1] - 0.1).abs() < 1e-12);
+        assert!(returns[2].abs() < 1e-12);
+        assert!((returns[3] - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn percentile_interpolates() {
+        let v = [0.0, 1.0, 2.0, 3.0, 4.0];
+        assert!((percentile(&v, 0.5) - 2.0).abs() < 1e-12);
+        assert!((percentile(&v, 0.25) - 1.0).abs() < 1e-12);
+        assert!((percentile(&v, 0.1) - 0.4).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,301 @@
+//! Probability of Backtest Overfitting (PBO) via combinatorially symmetric
+//! cross-validation (CSCV), after Bailey, Borwein, López de Prado & Zhu (2015).
+//!
+//! The per-combo return series of a sweep are aligned into a `T × N` matrix
+//! and cut into `S` contiguous time blocks. Every way of picking `S/2` blocks
+//! as the in-sample set (the rest being out-of-sample) is evaluated: the
+//! in-sample Sharpe winner is located and its relative rank among all combos
+//! out-of-sample is recorded. PBO is the share of splits in which the winner
+//! lands at or below the out-of-sample median.
+
+use anyhow::{bail, Result};
+
+use crate::engine::equity::{align_daily_returns, daily_equity};
+use crate::engine::types::EquityPoint;
+use crate::tools::response_types::overfitting::CscvResponse;
+use crate::tools::response_types::sweep::SweepResponse;
+
+/// Default number of time blocks (`C(16, 8)` = 12,870 splits).
+pub const DEFAULT_CSCV_BLOCKS: usize = 16;
+
+/// PBO above which a sweep is rejected as overfit.
+pub const PBO_THRESHOLD: f64 = 0.5;
+
+/// Minimum return observations per block for a meaningful block Sharpe.
+const MIN_OBS_PER_BLOCK: usize = 4;
+
+/// Largest supported block count (keeps the split enumeration bounded).
+const MAX_BLOCKS: usize = 20;
+
+/// Extract aligned per-combo return series from a sweep's equity curves.
+///
+/// Combos whose backtest produced no equity curve are left out.
+pub fn sweep_returns(sweep: &SweepResponse) -> Vec<Vec<f64>> {
+    let curves: Vec<&[EquityPoint]> = sweep
+        .full_results
+        .iter()
+        .map(|r| r.result.equity_curve.as_slice())
+        .filter(|c| c.len() > 1)
+        .collect();
+    aligned_returns(&curves)
+}
+
+/// Convert equity curves into daily returns over their common trading days.
+pub fn aligned_returns(curves: &[&[EquityPoint]]) -> Vec<Vec<f64>> {
+    let daily: Vec<_> = curves.iter().map(|c| daily_equity(c)).collect();
+    align_daily_returns(&daily).1
+}
+
+/// Run CSCV on `returns` (one equally long return series per combo).
+///
+/// `n_blocks` is rounded down to an even number and reduced when the series
+/// are too short to give every block `MIN_OBS_PER_BLOCK` observations.
+#[allow(clippy::needless_range_loop)]
+pub fn run_cscv(returns: &[Vec<f64>], n_blocks: usize) -> Result<CscvResponse> {
+    let n_strategies = returns.len();
+    if n_strategies < 2 {
+        bail!("CSCV needs return series for at least 2 parameter combos, got {n_strategies}");
+    }
+    let n_obs = returns.iter().map(Vec::len).min().unwrap_or(0);
+
+    let max_fit = n_obs / MIN_OBS_PER_BLOCK;
+    let n_blocks = n_blocks.min(max_fit).min(MAX_BLOCKS) & !1;
+    if n_blocks < 2 {
+        bail!(
+            "CSCV needs at least {} aligned return observations, got {n_obs}",
+            2 * MIN_OBS_PER_BLOCK
+        );
+    }
+
+    // Per-block (count, sum, sum of squares) so each split combines in O(S).
+    let bounds: Vec<usize> = (0..=n_blocks).map(|b| b * n_obs / n_blocks).collect();
+    let block_stats: Vec<Vec<Moments>> = returns
+        .iter()
+        .map(|series| {
+            (0..n_blocks)
+                .map(|b| Moments::of(&series[bounds[b]..bounds[b + 1]]))
+                .collect()
+        })
+        .collect();
+
+    let half = n_blocks / 2;
+    let mut logits = Vec::new();
+    let mut is_best = Vec::new();
+    let mut oos_best = Vec::new();
+    let mut oos = vec![0.0; n_strategies];
+
+    for mask in 0u32..(1 << n_blocks) {
+        if mask.count_ones() as usize != half {
+            continue;
+        }
+        let mut best = (0, f64::NEG_INFINITY);
+        for n in 0..n_strategies {
+            let (train, test) = block_stats[n].iter().enumerate().fold(
+                (Moments::default(), Moments::default()),
+                |(train, test), (b, m)| {
+                    if mask & (1 << b) != 0 {
+                        (train.merge(m), test)
+                    } else {
+                        (train, test.merge(m))
+                    }
+                },
+            );
+            let is_sharpe = train.sharpe();
+            oos[n] = test.sharpe();
+            if is_sharpe > best.1 {
+                best = (n, is_sharpe);
+            }
+        }
+
+        let (winner, winner_is) = best;
+        let winner_oos = oos[winner];
+        // Relative OOS rank of the IS winner in (0, 1), ties counted half.
+        let below = oos.iter().filter(|&&v| v < winner_oos).count() as f64;
+        let above = oos.iter().filter(|&&v| v > winner_oos).count() as f64;
+        let ties = n_strategies as f64 - below - above;
+        let omega = (below + 0.5 * (ties - 1.0) + 1.0) / (n_strategies as f64 + 1.0);
+        logits.push((omega / (1.0 - omega)).ln());
+        is_best.push(winner_is);
+        oos_best.push(winner_oos);
+    }
+
+    let n_splits = logits.len();
+    let pbo = logits.iter().filter(|&&l| l <= 0.0).count() as f64 / n_splits as f64;
+    let prob_oos_loss = oos_best.iter().filter(|&&v| v < 0.0).count() as f64 / n_splits as f64;
+    let (slope, intercept, r2) = linear_fit(&is_best, &oos_best);
+
+    logits.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median_logit = if n_splits % 2 == 0 {
+        f64::midpoint(logits[n_splits / 2 - 1], logits[n_splits / 2])
+    } else {
+        logits[n_splits / 2]
+    };
+
+    Ok(CscvResponse {
+        n_blocks,
+        n_splits,
+        n_strategies,
+        n_observations: n_obs,
+        pbo,
+        degradation_slope: slope,
+        degradation_intercept: intercept,
+        degradation_r2: r2,
+        prob_oos_loss,
+        median_logit,
+        pbo_threshold: PBO_THRESHOLD,
+        rejected: pbo > PBO_THRESHOLD,
+    })
+}
+
+/// Running moments of a return series.
+#[derive(Debug, Clone, Copy, Default)]
+struct Moments {
+    n: usize,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Moments {
+    fn of(values: &[f64]) -> Self {
+        Self {
+            n: values.len(),
+            sum: values.iter().sum(),
+            sum_sq: values.iter().map(|v| v * v).sum(),
+        }
+    }
+
+    fn merge(self, other: &Self) -> Self {
+        Self {
+            n: self.n + other.n,
+            sum: self.sum + other.sum,
+            sum_sq: self.sum_sq + other.sum_sq,
+        }
+    }
+
+    /// Non-annualized Sharpe ratio (`mean / std`), 0 for flat series.
+    fn sharpe(&self) -> f64 {
+        if self.n < 2 {
+            return 0.0;
+        }
+        let n = self.n as f64;
+        let mean = self.sum / n;
+        let var = ((self.sum_sq - n * mean * mean) / (n - 1.0)).max(0.0);
+        let std = var.sqrt();
+        if std < 1e-12 {
+            0.0
+        } else {
+            mean / std
+        }
+    }
+}
+
+/// Ordinary least squares `y = slope·x + intercept`, returning `(slope, intercept, r²)`.
+fn linear_fit(x: &[f64], y: &[f64]) -> (f64, f64, f64) {
+    let n = x.len() as f64;
+    if x.len() < 2 {
+        return (0.0, y.first().copied().unwrap_or(0.0), 0.0);
+    }
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (xi, yi) in x.iter().zip(y) {
+        sxx += (xi - mean_x).powi(2);
+        sxy += (xi - mean_x) * (yi - mean_y);
+        syy += (yi - mean_y).powi(2);
+    }
+    if sxx < 1e-18 {
+        return (0.0, mean_y, 0.0);
+    }
+    let slope = sxy / sxx;
+    let r2 = if syy < 1e-18 {
+        0.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    (slope, mean_y - slope * mean_x, r2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    fn noise(seed: u64, n: usize, drift: f64) -> Vec<f64> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| drift + rng.random_range(-0.01..0.01))
+            .collect()
+    }
+
+    #[test]
+    fn pure_noise_sweep_has_high_pbo() {
+        // Identically distributed combos: the IS winner is a coin flip OOS.
+        let returns: Vec<Vec<f64>> = (0..20).map(|s| noise(s, 400, 0.0)).collect();
+        let result = run_cscv(&returns, 16).unwrap();
+        assert_eq!(result.n_blocks, 16);
+        assert_eq!(result.n_splits, 12_870);
+        assert!(
+            (0.25..=0.75).contains(&result.pbo),
+            "noise PBO should be near 0.5, got {}",
+            result.pbo
+        );
+    }
+
+    #[test]
+    fn persistent_edge_has_low_pbo() {
+        // One combo has a real drift the others lack.
+        let mut returns: Vec<Vec<f64>> = (0..10).map(|s| noise(s, 400, 0.0)).collect();
+        returns[3] = noise(99, 400, 0.004);
+        let result = run_cscv(&returns, 8).unwrap();
+        assert!(result.pbo < 0.1, "PBO should be low, got {}", result.pbo);
+        assert!(result.prob_oos_loss < 0.1);
+        assert!(!result.rejected);
+    }
+
+    #[test]
+    fn block_count_shrinks_to_fit_short_history() {
+        let returns: Vec<Vec<f64>> = (0..4).map(|s| noise(s, 25, 0.0)).collect();
+        let result = run_cscv(&returns, 16).unwrap();
+        assert_eq!(result.n_blocks, 6);
+        assert_eq!(result.n_splits, 20);
+    }
+
+    #[test]
+    fn rejects_insufficient_input() {
+        assert!(run_cscv(&[noise(1, 100, 0.0)], 16).is_err());
+        let short: Vec<Vec<f64>> = (0..3).map(|s| noise(s, 5, 0.0)).collect();
+        assert!(run_cscv(&short, 16).is_err());
+    }
+
+    #[test]
+    fn aligned_returns_uses_common_days() {
+        let day = |d: u32| {
+            chrono::NaiveDate::from_ymd_opt(2024, 1, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        let point = |d: u32, equity: f64| EquityPoint {
+            datetime: day(d),
+            equity,
+            unrealized: None,
+        };
+        let a = vec![point(1, 100.0), point(2, 110.0), point(3, 121.0)];
+        let b = vec![point(2, 50.0), point(3, 55.0), point(4, 60.0)];
+        let returns = aligned_returns(&[&a, &b]);
+        assert_eq!(returns.len(), 2);
+        assert_eq!(returns[0].len(), 1);
+        assert!((returns[0][0] - 0.1).abs() < 1e-12);
+        assert!((returns[1][0] - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn linear_fit_recovers_line() {
+        let x = [0.0, 1.0, 2.0, 3.0];
+        let y = [1.0, 0.5, 0.0, -0.5];
+        let (slope, intercept, r2) = linear_fit(&x, &y);
+        assert!((slope + 0.5).abs() < 1e-12);
+        assert!((intercept - 1.0).abs() < 1e-12);
+        assert!((r2 - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,138 @@
+//! Equity-curve helpers shared by the tools that analyse stored runs and sweep
+//! results: loading a run's curve and collapsing it to one value per day.
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+
+use crate::data::traits::RunDetail;
+use crate::engine::types::EquityPoint;
+
+/// Equity curve stored in a run's result JSON (empty when none was saved).
+pub fn stored_equity_curve(run: &RunDetail) -> Result<Vec<EquityPoint>> {
+    let curve = run
+        .result_json
+        .as_ref()
+        .and_then(|v| v.get("equity_curve"))
+        .map(|v| serde_json::from_value(v.clone()))
+        .transpose()
+        .with_context(|| format!("Run '{}' has a malformed equity curve", run.id))?
+        .unwrap_or_default();
+    Ok(curve)
+}
+
+/// Collapse a curve to one value per calendar day, keeping `value` of the
+/// day's last point.
+///
+/// `value` sees every point in order, so it may carry state across points.
+pub fn collapse_daily<T>(
+    curve: &[EquityPoint],
+    mut value: impl FnMut(&EquityPoint) -> T,
+) -> Vec<(NaiveDate, T)> {
+    let mut daily: Vec<(NaiveDate, T)> = Vec::new();
+    for point in curve {
+        let date = point.datetime.date();
+        let v = value(point);
+        match daily.last_mut() {
+            Some((last, current)) if *last == date => *current = v,
+            _ => daily.push((date, v)),
+        }
+    }
+    daily
+}
+
+/// End-of-day equity of a curve.
+pub fn daily_equity(curve: &[EquityPoint]) -> Vec<(NaiveDate, f64)> {
+    collapse_daily(curve, |p| p.equity)
+}
+
+/// Daily returns of a curve, skipping days that start from zero equity.
+pub fn daily_returns(curve: &[EquityPoint]) -> Vec<f64> {
+    daily_equity(curve)
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, curr) = (pair[0].1, pair[1].1);
+            (prev.abs() > f64::EPSILON).then(|| curr / prev - 1.0)
+        })
+        .collect()
+}
+
+/// Daily returns of each end-of-day series on the dates common to all of them.
+///
+/// Returns the common dates and one return series per input (one element
+/// shorter than the dates). A return from zero equity counts as zero.
+pub fn align_daily_returns(series: &[Vec<(NaiveDate, f64)>]) -> (Vec<NaiveDate>, Vec<Vec<f64>>) {
+    let Some(first) = series.first() else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut common: BTreeSet<NaiveDate> = first.iter().map(|(d, _)| *d).collect();
+    for s in &series[1..] {
+        let dates: BTreeSet<NaiveDate> = s.iter().map(|(d, _)| *d).collect();
+        common.retain(|d| dates.contains(d));
+    }
+
+    let aligned = series
+        .iter()
+        .map(|s| {
+            let equity: Vec<f64> = s
+                .iter()
+                .filter(|(d, _)| common.contains(d))
+                .map(|(_, e)| *e)
+                .collect();
+            equity
+                .windows(2)
+                .map(|pair| {
+                    if pair[0].abs() > f64::EPSILON {
+                        pair[1] / pair[0] - 1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    (common.into_iter().collect(), aligned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(d: u32, h: u32, equity: f64) -> EquityPoint {
+        EquityPoint {
+            datetime: NaiveDate::from_ymd_opt(2024, 1, d)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap(),
+            equity,
+            unrealized: None,
+        }
+    }
+
+    #[test]
+    fn daily_returns_collapse_intraday_points() {
+        let curve = vec![at(2, 10, 100.0), at(2, 15, 105.0), at(3, 15, 110.25)];
+        assert_eq!(
+            daily_equity(&curve),
+            vec![
+                (NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), 105.0),
+                (NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(), 110.25),
+            ]
+        );
+        let returns = daily_returns(&curve);
+        assert_eq!(returns.len(), 1);
+        assert!((returns[0] - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn collapse_daily_threads_state_through_every_point() {
+        let curve = vec![at(2, 10, 100.0), at(2, 15, 105.0), at(3, 15, 110.0)];
+        let mut seen = 0;
+        let counts = collapse_daily(&curve, |_| {
+            seen += 1;
+            seen
+        });
+        assert_eq!(counts.iter().map(|(_, c)| *c).collect::<Vec<_>>(), [2, 3]);
+    }
+}
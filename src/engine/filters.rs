@@ -1,7 +1,7 @@
 //! Polars-based filter pipeline for options chain data.
 //!
-//! Provides composable filters for DTE range, option type, delta targeting,
-//! bid/ask validity, expiration type, and leg-join preparation.
+//! Provides composable filters for DTE range, option type, delta/strike/premium
+//! targeting, bid/ask validity, expiration type, and leg-join preparation.
 
 use anyhow::Result;
 use chrono::Datelike;
@@ -91,6 +91,95 @@ pub fn select_closest_delta(df: DataFrame, target: &TargetRange) -> Result<DataF
     Ok(result)
 }
 
+/// Keep only the expiration whose DTE is closest to `target_dte`.
+/// Ties go to the nearer expiration.
+pub fn select_nearest_expiration(df: DataFrame, target_dte: i32) -> Result<DataFrame> {
+    let best = df
+        .column("dte")?
+        .cast(&DataType::Int32)?
+        .i32()?
+        .into_iter()
+        .flatten()
+        .min_by_key(|dte| ((dte - target_dte).abs(), *dte));
+    let Some(best) = best else {
+        return Ok(df.head(Some(0)));
+    };
+    let result = df
+        .lazy()
+        .filter(col("dte").cast(DataType::Int32).eq(lit(best)))
+        .collect()?;
+    Ok(result)
+}
+
+/// Select the row whose strike is closest to `target_strike`.
+/// Ties go to the lower strike. Expects a single expiration.
+pub fn select_closest_strike(df: DataFrame, target_strike: f64) -> Result<DataFrame> {
+    let result = df
+        .lazy()
+        .with_column(
+            (col("strike") - lit(target_strike))
+                .abs()
+                .alias("strike_dist"),
+        )
+        .sort(
+            ["strike_dist", "strike"],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .limit(1)
+        .collect()?;
+    Ok(result.drop("strike_dist")?)
+}
+
+/// Select the strike `offset` listed strikes above (positive) or below (negative)
+/// the strike closest to `spot`. Returns an empty frame when the offset runs off
+/// the listed chain. Expects a single expiration.
+pub fn select_strike_offset(df: DataFrame, spot: f64, offset: i64) -> Result<DataFrame> {
+    let mut strikes: Vec<f64> = df.column("strike")?.f64()?.into_iter().flatten().collect();
+    strikes.sort_by(f64::total_cmp);
+    strikes.dedup();
+
+    let Some(atm_idx) = (0..strikes.len()).min_by(|&a, &b| {
+        (strikes[a] - spot)
+            .abs()
+            .total_cmp(&(strikes[b] - spot).abs())
+    }) else {
+        return Ok(df.head(Some(0)));
+    };
+    let target = i64::try_from(atm_idx)
+        .ok()
+        .and_then(|i| i.checked_add(offset))
+        .and_then(|i| usize::try_from(i).ok())
+        .and_then(|i| strikes.get(i).copied());
+    let Some(target) = target else {
+        return Ok(df.head(Some(0)));
+    };
+    let result = df
+        .lazy()
+        .filter(col("strike").eq(lit(target)))
+        .limit(1)
+        .collect()?;
+    Ok(result)
+}
+
+/// Select the row whose bid/ask mid is closest to `target_premium`.
+/// Ties go to the lower strike. Expects a single expiration.
+pub fn select_closest_premium(df: DataFrame, target_premium: f64) -> Result<DataFrame> {
+    let result = df
+        .lazy()
+        .with_column(
+            (((col("bid") + col("ask")) / lit(2.0)) - lit(target_premium))
+                .abs()
+                .alias("premium_dist"),
+        )
+        .sort(
+            ["premium_dist", "strike"],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .limit(1)
+        .collect()?;
+    Ok(result.drop("premium_dist")?)
+}
+
 /// Select only the columns needed for leg joining, then rename per-leg columns
 /// with the leg index suffix to avoid conflicts when joining multiple legs.
 ///
@@ -700,4 +789,40 @@ mod tests {
             "Only 3rd Friday expiration should remain"
         );
     }
+
+    #[test]
+    fn select_nearest_expiration_prefers_nearer_on_tie() {
+        let df = df! {
+            "strike" => &[100.0f64, 100.0, 100.0],
+            "dte" => &[30i32, 40, 50],
+        }
+        .unwrap();
+        let result = select_nearest_expiration(df, 35).unwrap();
+        let dtes: Vec<i32> = result
+            .column("dte")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(dtes, vec![30]);
+    }
+
+    #[test]
+    fn select_strike_offset_counts_listed_strikes_from_atm() {
+        let df = df! {
+            "strike" => &[95.0f64, 100.0, 102.5, 105.0],
+        }
+        .unwrap();
+        let pick = |offset| {
+            let result = select_strike_offset(df.clone(), 101.0, offset).unwrap();
+            (result.height() > 0)
+                .then(|| result.column("strike").unwrap().f64().unwrap().get(0))
+                .flatten()
+        };
+        assert_eq!(pick(0), Some(100.0));
+        assert_eq!(pick(2), Some(105.0));
+        assert_eq!(pick(-1), Some(95.0));
+        assert_eq!(pick(3), None, "offset past the chain selects nothing");
+    }
 }
@@ -0,0 +1,895 @@
+//! Black-Scholes / Black-76 pricing, implied volatility, and option greeks.
+//!
+//! The options schema only carries `bid`, `ask`, and a vendor `delta`, so this
+//! module backs implied volatility out of the mid quote and derives gamma,
+//! theta, and vega from it. When the chain has no `underlying_price` column the
+//! spot is recovered from put-call parity at the strike where call and put mids
+//! are closest, which keeps the solver independent of split-adjusted OHLCV.
+//!
+//! It also derives a constant-maturity at-the-money implied volatility per
+//! quote date, which backs the `atm_iv`, `iv_rank`, and `iv_percentile`
+//! indicators, plus the per-date skew, term-structure slope, and put/call
+//! volume features behind the options-structure indicators and hypothesis
+//! scan.
+
+use std::collections::HashMap;
+use std::f64::consts::{PI, SQRT_2};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use ordered_float::OrderedFloat;
+use polars::prelude::*;
+use rayon::prelude::*;
+use statrs::function::erf::erfc;
+
+use super::types::{timestamp_to_naive_datetime, OptionType};
+use crate::constants::TRADING_DAYS_PER_YEAR;
+use crate::data::parquet::DATETIME_COL;
+use crate::stats::rolling_apply;
+
+/// Optional chain column holding the underlying spot price at quote time.
+pub const UNDERLYING_PRICE_COL: &str = "underlying_price";
+
+/// Lower bound on time to expiry (in years) so 0-DTE rows stay solvable.
+const MIN_TIME_TO_EXPIRY: f64 = 1.0 / 365.0;
+/// Volatility search bracket for the implied-volatility solver.
+const MIN_VOL: f64 = 1e-4;
+const MAX_VOL: f64 = 5.0;
+/// Absolute price tolerance for the implied-volatility solver.
+const IV_PRICE_TOLERANCE: f64 = 1e-8;
+const IV_MAX_ITERATIONS: usize = 100;
+/// Constant maturity (calendar days) of the ATM implied-volatility series.
+pub const ATM_IV_TARGET_DTE: i32 = 30;
+/// Far leg (calendar days) of the ATM term-structure slope.
+pub const TERM_SLOPE_FAR_DTE: i32 = 90;
+/// Absolute delta of the wings compared by the put–call skew.
+pub const SKEW_DELTA: f64 = 0.25;
+/// Window (bars) of the realized volatility compared against ATM IV.
+pub const REALIZED_VOL_WINDOW: usize = 20;
+
+/// Rate and carry inputs for greeks / implied volatility computation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GreeksParams {
+    /// Continuously-compounded annual risk-free rate (e.g. 0.05 = 5%).
+    pub risk_free_rate: f64,
+    /// Continuously-compounded annual dividend yield of the underlying.
+    pub dividend_yield: f64,
+}
+
+/// Implied volatility and model greeks for a single option contract.
+///
+/// `theta` is per calendar day and `vega` is per 1 volatility point (0.01),
+/// both per share — multiply by contract multiplier × quantity for dollars.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OptionGreeks {
+    pub iv: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
+}
+
+fn norm_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+fn norm_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+/// Convert whole days to expiry into a year fraction, floored at one day.
+pub fn year_fraction(dte: i32) -> f64 {
+    (f64::from(dte) / 365.0).max(MIN_TIME_TO_EXPIRY)
+}
+
+/// Black-76 price of a European option on a forward.
+///
+/// Degenerates to the discounted intrinsic value when `t` or `sigma` is non-positive.
+pub fn black76_price(
+    option_type: OptionType,
+    forward: f64,
+    strike: f64,
+    t: f64,
+    rate: f64,
+    sigma: f64,
+) -> f64 {
+    let df = (-rate * t).exp();
+    if t <= 0.0 || sigma <= 0.0 || forward <= 0.0 || strike <= 0.0 {
+        return df
+            * match option_type {
+                OptionType::Call => (forward - strike).max(0.0),
+                OptionType::Put => (strike - forward).max(0.0),
+            };
+    }
+    let sd = sigma * t.sqrt();
+    let d1 = ((forward / strike).ln() + 0.5 * sd * sd) / sd;
+    let d2 = d1 - sd;
+    df * match option_type {
+        OptionType::Call => forward * norm_cdf(d1) - strike * norm_cdf(d2),
+        OptionType::Put => strike * norm_cdf(-d2) - forward * norm_cdf(-d1),
+    }
+}
+
+/// Black-Scholes-Merton price of a European option on a dividend-paying spot.
+pub fn bs_price(
+    option_type: OptionType,
+    spot: f64,
+    strike: f64,
+    t: f64,
+    params: &GreeksParams,
+    sigma: f64,
+) -> f64 {
+    let forward = spot * ((params.risk_free_rate - params.dividend_yield) * t).exp();
+    black76_price(
+        option_type,
+        forward,
+        strike,
+        t,
+        params.risk_free_rate,
+        sigma,
+    )
+}
+
+/// Black-Scholes-Merton greeks at a given volatility.
+///
+/// Returns `None` for non-positive inputs where the greeks are undefined.
+pub fn bs_greeks(
+    option_type: OptionType,
+    spot: f64,
+    strike: f64,
+    t: f64,
+    params: &GreeksParams,
+    sigma: f64,
+) -> Option<OptionGreeks> {
+    if spot <= 0.0 || strike <= 0.0 || t <= 0.0 || sigma <= 0.0 {
+        return None;
+    }
+    let (r, q) = (params.risk_free_rate, params.dividend_yield);
+    let sqrt_t = t.sqrt();
+    let sd = sigma * sqrt_t;
+    let d1 = ((spot / strike).ln() + (r - q + 0.5 * sigma * sigma) * t) / sd;
+    let d2 = d1 - sd;
+    let div_df = (-q * t).exp();
+    let rate_df = (-r * t).exp();
+    let pdf_d1 = norm_pdf(d1);
+
+    let gamma = div_df * pdf_d1 / (spot * sd);
+    let vega = spot * div_df * pdf_d1 * sqrt_t;
+    let decay = -spot * div_df * pdf_d1 * sigma / (2.0 * sqrt_t);
+    let (delta, theta) = match option_type {
+        OptionType::Call => (
+            div_df * norm_cdf(d1),
+            decay - r * strike * rate_df * norm_cdf(d2) + q * spot * div_df * norm_cdf(d1),
+        ),
+        OptionType::Put => (
+            div_df * (norm_cdf(d1) - 1.0),
+            decay + r * strike * rate_df * norm_cdf(-d2) - q * spot * div_df * norm_cdf(-d1),
+        ),
+    };
+
+    Some(OptionGreeks {
+        iv: sigma,
+        delta,
+        gamma,
+        theta: theta / 365.0,
+        vega: vega / 100.0,
+    })
+}
+
+/// Solve for the Black-Scholes-Merton volatility that reproduces `price`.
+///
+/// Uses Newton-Raphson on vega, falling back to bisection whenever a Newton
+/// step leaves the current bracket. Returns `None` when the price violates
+/// no-arbitrage bounds (below intrinsic or above the spot/strike cap).
+pub fn implied_volatility(
+    option_type: OptionType,
+    price: f64,
+    spot: f64,
+    strike: f64,
+    t: f64,
+    params: &GreeksParams,
+) -> Option<f64> {
+    if !price.is_finite() || price <= 0.0 || spot <= 0.0 || strike <= 0.0 || t <= 0.0 {
+        return None;
+    }
+    let floor = bs_price(option_type, spot, strike, t, params, 0.0);
+    if price <= floor + IV_PRICE_TOLERANCE {
+        return None;
+    }
+    if bs_price(option_type, spot, strike, t, params, MAX_VOL) < price {
+        return None;
+    }
+
+    let forward = spot * ((params.risk_free_rate - params.dividend_yield) * t).exp();
+    let rate_df = (-params.risk_free_rate * t).exp();
+    let sqrt_t = t.sqrt();
+
+    let (mut lo, mut hi) = (MIN_VOL, MAX_VOL);
+    let mut sigma = 0.3;
+    for _ in 0..IV_MAX_ITERATIONS {
+        let diff = bs_price(option_type, spot, strike, t, params, sigma) - price;
+        if diff.abs() < IV_PRICE_TOLERANCE {
+            return Some(sigma);
+        }
+        if diff > 0.0 {
+            hi = sigma;
+        } else {
+            lo = sigma;
+        }
+        let sd = sigma * sqrt_t;
+        let d1 = ((forward / strike).ln() + 0.5 * sd * sd) / sd;
+        let vega = rate_df * forward * norm_pdf(d1) * sqrt_t;
+        let newton = sigma - diff / vega;
+        sigma = if vega > f64::EPSILON && newton > lo && newton < hi {
+            newton
+        } else {
+            0.5 * (lo + hi)
+        };
+    }
+    Some(sigma)
+}
+
+/// Spot implied by put-call parity: `F = K + (C - P)·e^{rT}`, `S = F·e^{-(r-q)T}`.
+pub fn implied_spot(
+    call_mid: f64,
+    put_mid: f64,
+    strike: f64,
+    t: f64,
+    params: &GreeksParams,
+) -> f64 {
+    let forward = strike + (call_mid - put_mid) * (params.risk_free_rate * t).exp();
+    forward * (-(params.risk_free_rate - params.dividend_yield) * t).exp()
+}
+
+/// Solve implied volatility from the bid/ask mid and derive the full greek set.
+pub fn greeks_from_quote(
+    option_type: OptionType,
+    bid: f64,
+    ask: f64,
+    spot: f64,
+    strike: f64,
+    t: f64,
+    params: &GreeksParams,
+) -> Option<OptionGreeks> {
+    if bid < 0.0 || ask <= 0.0 || ask < bid {
+        return None;
+    }
+    let mid = f64::midpoint(bid, ask);
+    let iv = implied_volatility(option_type, mid, spot, strike, t, params)?;
+    bs_greeks(option_type, spot, strike, t, params, iv)
+}
+
+/// Append `iv`, `gamma`, `theta`, and `vega` columns to an options chain.
+///
+/// Requires `datetime`, `strike`, `bid`, `ask`, `option_type`, and a
+/// pre-computed `dte` column. Spot comes from `underlying_price` when present,
+/// otherwise from put-call parity per (quote date, expiration). Rows whose
+/// quotes cannot be inverted get nulls.
+pub fn append_greeks_columns(df: &DataFrame, params: &GreeksParams) -> Result<DataFrame> {
+    let n = df.height();
+    let strike_col = df.column("strike")?.cast(&DataType::Float64)?;
+    let bid_col = df.column("bid")?.cast(&DataType::Float64)?;
+    let ask_col = df.column("ask")?.cast(&DataType::Float64)?;
+    let dte_col = df.column("dte")?.cast(&DataType::Int32)?;
+    let strikes = strike_col.f64()?;
+    let bids = bid_col.f64()?;
+    let asks = ask_col.f64()?;
+    let dtes = dte_col.i32()?;
+    let types = df.column("option_type")?.str()?;
+    let dt_ca = df.column(DATETIME_COL)?.datetime()?;
+    let tu = dt_ca.time_unit();
+    let spot_col = match df.column(UNDERLYING_PRICE_COL) {
+        Ok(c) => Some(c.cast(&DataType::Float64)?),
+        Err(_) => None,
+    };
+    let spots = spot_col.as_ref().map(|c| c.f64()).transpose()?;
+
+    let option_type_at = |i: usize| match types.get(i) {
+        Some("c") => Some(OptionType::Call),
+        Some("p") => Some(OptionType::Put),
+        _ => None,
+    };
+
+    // Group rows by (quote date, dte) — equivalent to (quote date, expiration)
+    let mut groups: HashMap<(NaiveDate, i32), Vec<usize>> = HashMap::new();
+    for i in 0..n {
+        let date = dt_ca
+            .phys
+            .get(i)
+            .and_then(|raw| timestamp_to_naive_datetime(raw, tu))
+            .map(|ndt| ndt.date());
+        if let (Some(date), Some(dte)) = (date, dtes.get(i)) {
+            groups.entry((date, dte)).or_default().push(i);
+        }
+    }
+
+    let solved: Vec<(usize, OptionGreeks)> = groups
+        .into_par_iter()
+        .flat_map_iter(|((_, dte), rows)| {
+            let t = year_fraction(dte);
+            let parity_spot = if spots.is_some() {
+                None
+            } else {
+                parity_spot(&rows, t, params, |i| {
+                    Some((
+                        option_type_at(i)?,
+                        strikes.get(i)?,
+                        bids.get(i)?,
+                        asks.get(i)?,
+                    ))
+                })
+            };
+            rows.into_iter().filter_map(move |i| {
+                let spot = match spots {
+                    Some(s) => s.get(i)?,
+                    None => parity_spot?,
+                };
+                let g = greeks_from_quote(
+                    option_type_at(i)?,
+                    bids.get(i)?,
+                    asks.get(i)?,
+                    spot,
+                    strikes.get(i)?,
+                    t,
+                    params,
+                )?;
+                Some((i, g))
+            })
+        })
+        .collect();
+
+    let mut iv: Vec<Option<f64>> = vec![None; n];
+    let mut gamma: Vec<Option<f64>> = vec![None; n];
+    let mut theta: Vec<Option<f64>> = vec![None; n];
+    let mut vega: Vec<Option<f64>> = vec![None; n];
+    for (i, g) in solved {
+        iv[i] = Some(g.iv);
+        gamma[i] = Some(g.gamma);
+        theta[i] = Some(g.theta);
+        vega[i] = Some(g.vega);
+    }
+
+    let mut out = df.clone();
+    out.with_column(Column::new("iv".into(), iv))?;
+    out.with_column(Column::new("gamma".into(), gamma))?;
+    out.with_column(Column::new("theta".into(), theta))?;
+    out.with_column(Column::new("vega".into(), vega))?;
+    Ok(out)
+}
+
+/// Spot implied by put-call parity at the strike where call and put mids are closest.
+fn parity_spot(
+    rows: &[usize],
+    t: f64,
+    params: &GreeksParams,
+    quote: impl Fn(usize) -> Option<(OptionType, f64, f64, f64)>,
+) -> Option<f64> {
+    let mut pairs: HashMap<OrderedFloat<f64>, (Option<f64>, Option<f64>)> = HashMap::new();
+    for &i in rows {
+        let Some((option_type, strike, bid, ask)) = quote(i) else {
+            continue;
+        };
+        if bid <= 0.0 || ask < bid {
+            continue;
+        }
+        let entry = pairs.entry(OrderedFloat(strike)).or_default();
+        let mid = f64::midpoint(bid, ask);
+        match option_type {
+            OptionType::Call => entry.0 = Some(mid),
+            OptionType::Put => entry.1 = Some(mid),
+        }
+    }
+    pairs
+        .into_iter()
+        .filter_map(|(k, (c, p))| Some((k.0, c?, p?)))
+        .min_by(|a, b| {
+            (a.1 - a.2)
+                .abs()
+                .total_cmp(&(b.1 - b.2).abs())
+                .then(a.0.total_cmp(&b.0))
+        })
+        .map(|(strike, c, p)| implied_spot(c, p, strike, t, params))
+        .filter(|s| s.is_finite() && *s > 0.0)
+}
+
+/// Constant-maturity at-the-money implied volatility for one quote date.
+///
+/// `chain` is a single-date slice carrying `dte`, `strike`, `option_type`,
+/// `bid`, `ask`, and a solved `iv` column. Each expiration's ATM volatility is
+/// the mean call/put IV at the strike where call and put mids are closest;
+/// expirations are then interpolated linearly in total variance (σ²T) to
+/// `target_dte`. Outside the listed range the nearest expiration is used.
+pub fn constant_maturity_atm_iv(chain: &DataFrame, target_dte: i32) -> Option<f64> {
+    interpolate_total_variance(&atm_term_structure(chain)?, target_dte)
+}
+
+/// ATM term-structure slope for one quote date: the `far_dte` minus the
+/// `near_dte` constant-maturity ATM IV. Positive in contango, negative when
+/// the front of the curve inverts. Needs at least two listed expirations.
+pub fn atm_term_slope(chain: &DataFrame, near_dte: i32, far_dte: i32) -> Option<f64> {
+    let term = atm_term_structure(chain)?;
+    if term.len() < 2 {
+        return None;
+    }
+    Some(interpolate_total_variance(&term, far_dte)? - interpolate_total_variance(&term, near_dte)?)
+}
+
+/// Put–call delta skew for one quote date: IV of the put whose vendor `delta`
+/// is nearest `-delta` minus IV of the call nearest `+delta`, taken on the
+/// expiration closest to `target_dte` that lists both.
+pub fn delta_skew(chain: &DataFrame, target_dte: i32, delta: f64) -> Option<f64> {
+    type Wing = Option<(f64, f64)>; // (distance from target delta, iv)
+
+    let dte_col = chain.column("dte").ok()?.cast(&DataType::Int32).ok()?;
+    let delta_col = chain.column("delta").ok()?.cast(&DataType::Float64).ok()?;
+    let dtes = dte_col.i32().ok()?;
+    let deltas = delta_col.f64().ok()?;
+    let ivs = chain.column("iv").ok()?.f64().ok()?;
+    let types = chain.column("option_type").ok()?.str().ok()?;
+
+    let mut by_expiration: HashMap<i32, (Wing, Wing)> = HashMap::new();
+    for i in 0..chain.height() {
+        let (Some(dte), Some(d), Some(iv)) = (dtes.get(i), deltas.get(i), ivs.get(i)) else {
+            continue;
+        };
+        if dte <= 0 || !iv.is_finite() {
+            continue;
+        }
+        let entry = by_expiration.entry(dte).or_default();
+        let (slot, distance) = match types.get(i) {
+            Some("p") => (&mut entry.0, (d + delta).abs()),
+            Some("c") => (&mut entry.1, (d - delta).abs()),
+            _ => continue,
+        };
+        if slot.is_none_or(|(best, _)| distance < best) {
+            *slot = Some((distance, iv));
+        }
+    }
+
+    by_expiration
+        .into_iter()
+        .filter_map(|(dte, (put, call))| Some((dte, put?.1, call?.1)))
+        .min_by_key(|&(dte, _, _)| ((dte - target_dte).abs(), dte))
+        .map(|(_, put_iv, call_iv)| put_iv - call_iv)
+}
+
+/// Put-to-call volume ratio for one quote date. `None` when the chain carries
+/// no `volume` column or no call volume traded.
+pub fn put_call_volume_ratio(chain: &DataFrame) -> Option<f64> {
+    let volume_col = chain.column("volume").ok()?.cast(&DataType::Float64).ok()?;
+    let volumes = volume_col.f64().ok()?;
+    let types = chain.column("option_type").ok()?.str().ok()?;
+    let (mut puts, mut calls) = (0.0, 0.0);
+    for i in 0..chain.height() {
+        let Some(v) = volumes.get(i).filter(|v| v.is_finite()) else {
+            continue;
+        };
+        match types.get(i) {
+            Some("p") => puts += v,
+            Some("c") => calls += v,
+            _ => {}
+        }
+    }
+    (calls > 0.0).then(|| puts / calls)
+}
+
+/// ATM term structure `(dte, iv)` sorted by dte: each expiration's mean
+/// call/put IV at the strike where call and put mids are closest.
+fn atm_term_structure(chain: &DataFrame) -> Option<Vec<(i32, f64)>> {
+    type Quote = Option<(f64, f64)>;
+
+    let strike_col = chain.column("strike").ok()?.cast(&DataType::Float64).ok()?;
+    let bid_col = chain.column("bid").ok()?.cast(&DataType::Float64).ok()?;
+    let ask_col = chain.column("ask").ok()?.cast(&DataType::Float64).ok()?;
+    let dte_col = chain.column("dte").ok()?.cast(&DataType::Int32).ok()?;
+    let strikes = strike_col.f64().ok()?;
+    let bids = bid_col.f64().ok()?;
+    let asks = ask_col.f64().ok()?;
+    let dtes = dte_col.i32().ok()?;
+    let ivs = chain.column("iv").ok()?.f64().ok()?;
+    let types = chain.column("option_type").ok()?.str().ok()?;
+
+    // (call, put) quotes as (mid, iv), keyed by expiration then strike
+    let mut by_expiration: HashMap<i32, HashMap<OrderedFloat<f64>, (Quote, Quote)>> =
+        HashMap::new();
+    for i in 0..chain.height() {
+        let (Some(dte), Some(strike), Some(bid), Some(ask), Some(iv)) = (
+            dtes.get(i),
+            strikes.get(i),
+            bids.get(i),
+            asks.get(i),
+            ivs.get(i),
+        ) else {
+            continue;
+        };
+        if dte <= 0 || ask < bid || !iv.is_finite() {
+            continue;
+        }
+        let entry = by_expiration
+            .entry(dte)
+            .or_default()
+            .entry(OrderedFloat(strike))
+            .or_default();
+        let quote = Some((f64::midpoint(bid, ask), iv));
+        match types.get(i) {
+            Some("c") => entry.0 = quote,
+            Some("p") => entry.1 = quote,
+            _ => {}
+        }
+    }
+
+    let mut term: Vec<(i32, f64)> = by_expiration
+        .into_iter()
+        .filter_map(|(dte, pairs)| {
+            pairs
+                .into_iter()
+                .filter_map(|(k, (c, p))| Some((k.0, c?, p?)))
+                .min_by(|a, b| {
+                    (a.1 .0 - a.2 .0)
+                        .abs()
+                        .total_cmp(&(b.1 .0 - b.2 .0).abs())
+                        .then(a.0.total_cmp(&b.0))
+                })
+                .map(|(_, c, p)| (dte, f64::midpoint(c.1, p.1)))
+        })
+        .collect();
+    term.sort_unstable_by_key(|&(dte, _)| dte);
+    Some(term)
+}
+
+/// Interpolate an ATM term structure `(dte, iv)` (sorted by dte) to `target_dte`.
+fn interpolate_total_variance(term: &[(i32, f64)], target_dte: i32) -> Option<f64> {
+    let (first, last) = (term.first()?, term.last()?);
+    if target_dte <= first.0 {
+        return Some(first.1);
+    }
+    if target_dte >= last.0 {
+        return Some(last.1);
+    }
+    let upper = term.iter().position(|&(dte, _)| dte >= target_dte)?;
+    let (d1, v1) = term[upper - 1];
+    let (d2, v2) = term[upper];
+    let (t1, t2, t) = (
+        year_fraction(d1),
+        year_fraction(d2),
+        year_fraction(target_dte),
+    );
+    let w1 = v1 * v1 * t1;
+    let w2 = v2 * v2 * t2;
+    let w = w1 + (w2 - w1) * (t - t1) / (t2 - t1);
+    (w > 0.0).then(|| (w / t).sqrt())
+}
+
+/// IV Rank: position of the latest IV within the window's min–max range (0-100).
+///
+/// NaN until the window holds `period` solved values; a flat window ranks 50.
+pub fn rolling_iv_rank(iv: &[f64], period: usize) -> Vec<f64> {
+    rolling_apply(iv, period, |w| {
+        if w.iter().any(|v| !v.is_finite()) {
+            return f64::NAN;
+        }
+        let cur = w[w.len() - 1];
+        let mn = w.iter().copied().fold(f64::INFINITY, f64::min);
+        let mx = w.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let r = mx - mn;
+        if r > f64::EPSILON {
+            (cur - mn) / r * 100.0
+        } else {
+            50.0
+        }
+    })
+}
+
+/// IV Percentile: share of prior days in the window with IV below the latest (0-100).
+///
+/// NaN until the window holds `period` solved values.
+pub fn rolling_iv_percentile(iv: &[f64], period: usize) -> Vec<f64> {
+    rolling_apply(iv, period, |w| {
+        if w.iter().any(|v| !v.is_finite()) {
+            return f64::NAN;
+        }
+        let cur = w[w.len() - 1];
+        let below = w.iter().filter(|&&v| v < cur).count();
+        below as f64 / (w.len() - 1).max(1) as f64 * 100.0
+    })
+}
+
+/// ATM IV minus the trailing [`REALIZED_VOL_WINDOW`]-bar annualized realized
+/// volatility of `closes`, both aligned to the bar index. Bar `i` only sees
+/// returns ending on or before it.
+pub fn iv_rv_spread(atm_iv: &[f64], closes: &[f64]) -> Vec<f64> {
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .map(|w| {
+            if w[0] > 0.0 {
+                w[1] / w[0] - 1.0
+            } else {
+                f64::NAN
+            }
+        })
+        .collect();
+    // returns[i - 1] ends on bar i
+    let realized = rolling_apply(&returns, REALIZED_VOL_WINDOW, crate::stats::std_dev);
+    atm_iv
+        .iter()
+        .enumerate()
+        .map(|(i, &iv)| {
+            let rv = if i > 0 {
+                realized.get(i - 1).copied().unwrap_or(f64::NAN)
+            } else {
+                f64::NAN
+            };
+            iv - rv * TRADING_DAYS_PER_YEAR.sqrt()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const PARAMS: GreeksParams = GreeksParams {
+        risk_free_rate: 0.05,
+        dividend_yield: 0.02,
+    };
+
+    #[test]
+    fn bs_price_matches_reference_value() {
+        // Hull: S=42, K=40, r=10%, sigma=20%, T=0.5 → call 4.76, put 0.81
+        let p = GreeksParams {
+            risk_free_rate: 0.10,
+            dividend_yield: 0.0,
+        };
+        let call = bs_price(OptionType::Call, 42.0, 40.0, 0.5, &p, 0.2);
+        let put = bs_price(OptionType::Put, 42.0, 40.0, 0.5, &p, 0.2);
+        assert!((call - 4.7594).abs() < 1e-3, "call = {call}");
+        assert!((put - 0.8086).abs() < 1e-3, "put = {put}");
+    }
+
+    #[test]
+    fn put_call_parity_holds() {
+        let (s, k, t) = (100.0, 105.0, 0.25);
+        let call = bs_price(OptionType::Call, s, k, t, &PARAMS, 0.3);
+        let put = bs_price(OptionType::Put, s, k, t, &PARAMS, 0.3);
+        let lhs = call - put;
+        let rhs = s * (-PARAMS.dividend_yield * t).exp() - k * (-PARAMS.risk_free_rate * t).exp();
+        assert!((lhs - rhs).abs() < 1e-10);
+    }
+
+    #[test]
+    fn black76_price_at_zero_vol_is_discounted_intrinsic() {
+        let p = black76_price(OptionType::Call, 110.0, 100.0, 1.0, 0.05, 0.0);
+        assert!((p - 10.0 * (-0.05f64).exp()).abs() < 1e-10);
+        let p = black76_price(OptionType::Put, 110.0, 100.0, 1.0, 0.05, 0.0);
+        assert!(p.abs() < 1e-10);
+    }
+
+    #[test]
+    fn implied_volatility_round_trips() {
+        for &(opt, k, sigma) in &[
+            (OptionType::Call, 90.0, 0.15),
+            (OptionType::Call, 110.0, 0.45),
+            (OptionType::Put, 95.0, 0.25),
+            (OptionType::Put, 120.0, 0.8),
+        ] {
+            let price = bs_price(opt, 100.0, k, 0.4, &PARAMS, sigma);
+            let iv = implied_volatility(opt, price, 100.0, k, 0.4, &PARAMS).unwrap();
+            assert!((iv - sigma).abs() < 1e-6, "{opt:?} K={k}: {iv} vs {sigma}");
+        }
+    }
+
+    #[test]
+    fn implied_volatility_rejects_arbitrage_prices() {
+        // Below intrinsic
+        assert!(implied_volatility(OptionType::Call, 5.0, 120.0, 100.0, 0.5, &PARAMS).is_none());
+        // Above the spot cap
+        assert!(implied_volatility(OptionType::Call, 150.0, 100.0, 100.0, 0.5, &PARAMS).is_none());
+        assert!(implied_volatility(OptionType::Put, 0.0, 100.0, 100.0, 0.5, &PARAMS).is_none());
+    }
+
+    #[test]
+    fn greeks_match_finite_differences() {
+        let (spot, strike, t, sigma) = (100.0, 100.0, 0.5, 0.25);
+        for opt in [OptionType::Call, OptionType::Put] {
+            let greeks = bs_greeks(opt, spot, strike, t, &PARAMS, sigma).unwrap();
+            let price = |s: f64, t: f64, v: f64| bs_price(opt, s, strike, t, &PARAMS, v);
+            let h = 0.01;
+            let fd_delta = (price(spot + h, t, sigma) - price(spot - h, t, sigma)) / (2.0 * h);
+            let fd_gamma = (price(spot + h, t, sigma) - 2.0 * price(spot, t, sigma)
+                + price(spot - h, t, sigma))
+                / (h * h);
+            let fd_vega =
+                (price(spot, t, sigma + 1e-4) - price(spot, t, sigma - 1e-4)) / 2e-4 / 100.0;
+            let fd_theta = price(spot, t - 1.0 / 365.0, sigma) - price(spot, t, sigma);
+            assert!((greeks.delta - fd_delta).abs() < 1e-5, "{opt:?} delta");
+            assert!((greeks.gamma - fd_gamma).abs() < 1e-4, "{opt:?} gamma");
+            assert!((greeks.vega - fd_vega).abs() < 1e-6, "{opt:?} vega");
+            assert!((greeks.theta - fd_theta).abs() < 1e-3, "{opt:?} theta");
+        }
+    }
+
+    #[test]
+    fn long_option_greeks_signs() {
+        let call = bs_greeks(OptionType::Call, 100.0, 100.0, 0.25, &PARAMS, 0.2).unwrap();
+        let put = bs_greeks(OptionType::Put, 100.0, 100.0, 0.25, &PARAMS, 0.2).unwrap();
+        assert!(call.delta > 0.0 && put.delta < 0.0);
+        assert!(call.gamma > 0.0 && put.gamma > 0.0);
+        assert!(call.vega > 0.0 && put.vega > 0.0);
+        assert!(call.theta < 0.0);
+        assert!((call.gamma - put.gamma).abs() < 1e-12);
+    }
+
+    #[test]
+    fn implied_spot_recovers_spot() {
+        let (s, k, t) = (100.0, 102.0, 0.3);
+        let call = bs_price(OptionType::Call, s, k, t, &PARAMS, 0.2);
+        let put = bs_price(OptionType::Put, s, k, t, &PARAMS, 0.2);
+        assert!((implied_spot(call, put, k, t, &PARAMS) - s).abs() < 1e-9);
+    }
+
+    fn chain_df(spot: f64, sigma: f64, with_underlying: bool) -> DataFrame {
+        let t = year_fraction(30);
+        let quote_dt = NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(15, 59, 0)
+            .unwrap();
+        let mut strikes = Vec::new();
+        let mut types = Vec::new();
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        for k in [95.0, 100.0, 105.0] {
+            for (opt, s) in [(OptionType::Call, "c"), (OptionType::Put, "p")] {
+                let mid = bs_price(opt, spot, k, t, &PARAMS, sigma);
+                strikes.push(k);
+                types.push(s);
+                bids.push(mid - 0.05);
+                asks.push(mid + 0.05);
+            }
+        }
+        let n = strikes.len();
+        let mut df = df! {
+            DATETIME_COL => vec![quote_dt; n],
+            "option_type" => types,
+            "strike" => strikes,
+            "bid" => bids,
+            "ask" => asks,
+            "dte" => vec![30i32; n],
+        }
+        .unwrap();
+        if with_underlying {
+            df.with_column(Column::new(UNDERLYING_PRICE_COL.into(), vec![spot; n]))
+                .unwrap();
+        }
+        df
+    }
+
+    #[test]
+    fn append_greeks_columns_from_parity() {
+        let df = chain_df(101.0, 0.22, false);
+        let out = append_greeks_columns(&df, &PARAMS).unwrap();
+        let iv = out.column("iv").unwrap().f64().unwrap();
+        for i in 0..out.height() {
+            let v = iv.get(i).unwrap();
+            assert!((v - 0.22).abs() < 1e-6, "row {i}: iv {v}");
+        }
+        for name in ["gamma", "theta", "vega"] {
+            assert_eq!(out.column(name).unwrap().null_count(), 0, "{name}");
+        }
+    }
+
+    #[test]
+    fn append_greeks_columns_uses_underlying_price() {
+        let df = chain_df(98.0, 0.3, true);
+        let out = append_greeks_columns(&df, &PARAMS).unwrap();
+        let iv = out.column("iv").unwrap().f64().unwrap();
+        assert!((iv.get(0).unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn append_greeks_columns_nulls_unsolvable_rows() {
+        let mut df = chain_df(100.0, 0.2, true);
+        // Zero out the ask on the first row — quote can't be inverted
+        let asks: Vec<f64> = (0..df.height())
+            .map(|i| if i == 0 { 0.0 } else { 1.0 })
+            .collect();
+        df.with_column(Column::new("ask".into(), asks)).unwrap();
+        let out = append_greeks_columns(&df, &PARAMS).unwrap();
+        assert!(out.column("iv").unwrap().f64().unwrap().get(0).is_none());
+    }
+
+    #[test]
+    fn constant_maturity_atm_iv_recovers_flat_surface() {
+        let df = chain_df(101.0, 0.22, false);
+        let out = append_greeks_columns(&df, &PARAMS).unwrap();
+        let iv = constant_maturity_atm_iv(&out, ATM_IV_TARGET_DTE).unwrap();
+        assert!((iv - 0.22).abs() < 1e-6, "atm iv {iv}");
+    }
+
+    #[test]
+    fn constant_maturity_atm_iv_requires_iv_column() {
+        let df = chain_df(100.0, 0.2, true);
+        assert!(constant_maturity_atm_iv(&df, ATM_IV_TARGET_DTE).is_none());
+    }
+
+    /// Two-expiration chain with solved `iv`, vendor `delta`, and `volume`.
+    fn structure_df() -> DataFrame {
+        // (dte, type, strike, delta, iv, volume)
+        let rows = [
+            (30, "c", 100.0, 0.50, 0.20, 100.0),
+            (30, "p", 100.0, -0.50, 0.20, 150.0),
+            (30, "c", 110.0, 0.24, 0.17, 50.0),
+            (30, "p", 90.0, -0.26, 0.26, 100.0),
+            (30, "p", 80.0, -0.10, 0.35, 50.0),
+            (90, "c", 100.0, 0.52, 0.25, 10.0),
+            (90, "p", 100.0, -0.48, 0.25, 10.0),
+            (90, "c", 120.0, 0.25, 0.22, 10.0),
+            (90, "p", 80.0, -0.25, 0.30, 10.0),
+        ];
+        df! {
+            "dte" => rows.iter().map(|r| r.0).collect::<Vec<i32>>(),
+            "option_type" => rows.iter().map(|r| r.1).collect::<Vec<&str>>(),
+            "strike" => rows.iter().map(|r| r.2).collect::<Vec<f64>>(),
+            "delta" => rows.iter().map(|r| r.3).collect::<Vec<f64>>(),
+            "iv" => rows.iter().map(|r| r.4).collect::<Vec<f64>>(),
+            "volume" => rows.iter().map(|r| r.5).collect::<Vec<f64>>(),
+            "bid" => vec![1.0; rows.len()],
+            "ask" => vec![1.2; rows.len()],
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn delta_skew_uses_nearest_expiration_and_wings() {
+        let df = structure_df();
+        // 30-DTE: 25Δ put ≈ the -0.26 put (0.26), 25Δ call ≈ the 0.24 call (0.17)
+        let skew = delta_skew(&df, ATM_IV_TARGET_DTE, SKEW_DELTA).unwrap();
+        assert!((skew - 0.09).abs() < 1e-10, "skew {skew}");
+        let far = delta_skew(&df, 120, SKEW_DELTA).unwrap();
+        assert!((far - 0.08).abs() < 1e-10, "skew {far}");
+        assert!(delta_skew(&df.drop("delta").unwrap(), 30, SKEW_DELTA).is_none());
+    }
+
+    #[test]
+    fn atm_term_slope_and_put_call_volume() {
+        let df = structure_df();
+        let slope = atm_term_slope(&df, ATM_IV_TARGET_DTE, TERM_SLOPE_FAR_DTE).unwrap();
+        assert!((slope - 0.05).abs() < 1e-10, "slope {slope}");
+        let single = df
+            .clone()
+            .lazy()
+            .filter(col("dte").eq(lit(30)))
+            .collect()
+            .unwrap();
+        assert!(atm_term_slope(&single, 30, 90).is_none());
+
+        let ratio = put_call_volume_ratio(&df).unwrap();
+        assert!((ratio - 320.0 / 170.0).abs() < 1e-10, "ratio {ratio}");
+        assert!(put_call_volume_ratio(&df.drop("volume").unwrap()).is_none());
+    }
+
+    #[test]
+    fn interpolate_total_variance_between_expirations() {
+        let term = [(20, 0.2), (40, 0.3)];
+        let iv = interpolate_total_variance(&term, 30).unwrap();
+        assert!((iv - (2.2f64 / 30.0).sqrt()).abs() < 1e-10);
+        // Flat extrapolation outside the listed expirations
+        assert!((interpolate_total_variance(&term, 7).unwrap() - 0.2).abs() < 1e-10);
+        assert!((interpolate_total_variance(&term, 60).unwrap() - 0.3).abs() < 1e-10);
+        assert!(interpolate_total_variance(&[], 30).is_none());
+    }
+
+    #[test]
+    fn rolling_iv_rank_and_percentile() {
+        let iv = [f64::NAN, 0.20, 0.30, 0.10, 0.25];
+        let rank = rolling_iv_rank(&iv, 3);
+        let pct = rolling_iv_percentile(&iv, 3);
+        // Windows touching the unsolved first day stay NaN
+        assert!(rank[..3].iter().all(|v| v.is_nan()) && pct[1].is_nan());
+        assert!((rank[3] - 0.0).abs() < 1e-10);
+        assert!((rank[4] - 75.0).abs() < 1e-10);
+        assert!((pct[3] - 0.0).abs() < 1e-10);
+        assert!((pct[4] - 50.0).abs() < 1e-10);
+        assert!((rolling_iv_rank(&[0.2, 0.2], 2)[1] - 50.0).abs() < 1e-10);
+    }
+}
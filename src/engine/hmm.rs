@@ -1,7 +1,20 @@
 //! Gaussian Hidden Markov Model with Baum-Welch EM and Viterbi decoding.
 //!
-//! Each hidden state emits observations from a univariate Gaussian distribution.
-//! Used by `regime_detect` to discover latent market regimes from return series.
+//! Each hidden state of [`GaussianHmm`] emits observations from a univariate
+//! Gaussian distribution. Used by `regime_detect` to discover latent market
+//! regimes from return series, and walk-forward (refit on a trailing window,
+//! forward-filtered) by the scripting `regime` indicator.
+//!
+//! [`MultivariateHmm`] emits feature vectors from full-covariance Gaussians,
+//! so a state can capture joint return/volatility behavior; [`select_by_bic`]
+//! picks its state count.
+//!
+//! [`MarkovSwitchingRegression`] (Hamilton, 1989) switches the coefficients
+//! and residual variance of a linear regression with the hidden state, fit by
+//! the same EM with weighted least squares in the M-step;
+//! [`select_markov_switching_by_bic`] picks its state count.
+
+use nalgebra::{Cholesky, DMatrix, DVector};
 
 /// Fitted Gaussian HMM parameters.
 #[derive(Debug, Clone)]
@@ -293,81 +306,138 @@ pub fn viterbi(hmm: &GaussianHmm, observations: &[f64]) -> Vec<usize> {
 ///
 /// `threshold` must be in (0.5, 1.0]. Values near 1.0 produce very stable (sticky)
 /// regime labels; values near 0.5 behave like raw argmax.
-#[allow(clippy::needless_range_loop)]
 pub fn forward_filter(hmm: &GaussianHmm, observations: &[f64], threshold: f64) -> Vec<usize> {
-    let t = observations.len();
-    let k = hmm.n_states;
-    if t == 0 {
-        return vec![];
-    }
+    let mut prev_state: Option<usize> = None;
+    filter_posteriors(hmm, observations)
+        .iter()
+        .map(|posterior| {
+            let argmax_state = posterior
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map_or(0, |(i, _)| i);
+            let state = match prev_state {
+                Some(prev) if argmax_state == prev || posterior[argmax_state] <= threshold => prev,
+                _ => argmax_state,
+            };
+            prev_state = Some(state);
+            state
+        })
+        .collect()
+}
 
-    let mut result = Vec::with_capacity(t);
+/// Filtered state probabilities `P(state_t | obs_0..=t)` for each observation.
+///
+/// Each row only conditions on observations up to and including its own, so
+/// the output is safe to consume bar by bar.
+#[allow(clippy::needless_range_loop)]
+pub fn filter_posteriors(hmm: &GaussianHmm, observations: &[f64]) -> Vec<Vec<f64>> {
+    let k = hmm.n_states;
+    let mut result = Vec::with_capacity(observations.len());
     let mut posterior = hmm.initial.clone();
     let mut log_post = vec![0.0_f64; k];
-    let mut norm_post = vec![0.0_f64; k];
-
-    // Classify first bar
-    for j in 0..k {
-        log_post[j] = posterior[j].max(1e-300).ln()
-            + log_gaussian(observations[0], hmm.means[j], hmm.variances[j]);
-    }
-    let max_lp = log_post.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-    let sum_exp: f64 = log_post.iter().map(|&lp| (lp - max_lp).exp()).sum();
-    let log_norm = max_lp + sum_exp.ln();
-    for j in 0..k {
-        norm_post[j] = (log_post[j] - log_norm).exp();
-    }
 
-    let initial_state = norm_post
-        .iter()
-        .enumerate()
-        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-        .map_or(0, |(i, _)| i);
-    result.push(initial_state);
-    posterior.copy_from_slice(&norm_post);
-    let mut prev_state = initial_state;
-
-    // Process remaining bars
-    for tt in 1..t {
-        let mut predicted = vec![0.0_f64; k];
-        for j in 0..k {
-            for i in 0..k {
-                predicted[j] += posterior[i] * hmm.transition[i][j];
-            }
-        }
+    for (tt, &obs) in observations.iter().enumerate() {
+        // Predict (the first bar uses the initial distribution as its prior)
+        let predicted: Vec<f64> = if tt == 0 {
+            posterior.clone()
+        } else {
+            (0..k)
+                .map(|j| (0..k).map(|i| posterior[i] * hmm.transition[i][j]).sum())
+                .collect()
+        };
 
         for j in 0..k {
-            log_post[j] = predicted[j].max(1e-300).ln()
-                + log_gaussian(observations[tt], hmm.means[j], hmm.variances[j]);
+            log_post[j] =
+                predicted[j].max(1e-300).ln() + log_gaussian(obs, hmm.means[j], hmm.variances[j]);
         }
-
         let max_lp = log_post.iter().copied().fold(f64::NEG_INFINITY, f64::max);
         let sum_exp: f64 = log_post.iter().map(|&lp| (lp - max_lp).exp()).sum();
         let log_norm = max_lp + sum_exp.ln();
         for j in 0..k {
-            norm_post[j] = (log_post[j] - log_norm).exp();
+            posterior[j] = (log_post[j] - log_norm).exp();
         }
+        result.push(posterior.clone());
+    }
 
-        let argmax_state = norm_post
-            .iter()
-            .enumerate()
-            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .map_or(0, |(i, _)| i);
+    result
+}
 
-        let state = if argmax_state != prev_state && norm_post[argmax_state] > threshold {
-            argmax_state
-        } else {
-            prev_state
-        };
+/// Walk-forward regime classification with no look-ahead.
+///
+/// Every `refit_every` observations the HMM is refit on the trailing `lookback`
+/// observations, then [`forward_filter`] runs from the start of that window up
+/// to the next refit. The entry at `t` therefore depends only on
+/// `observations[..=t]`. Entries before the first full window are `None`.
+///
+/// States are reported by volatility rank (0 = lowest emission variance), which
+/// keeps labels stable across refits; each entry is `(state, posterior)` where
+/// `posterior` is the filtered probability of that state.
+pub fn rolling_regimes(
+    observations: &[f64],
+    n_states: usize,
+    lookback: usize,
+    refit_every: usize,
+    threshold: f64,
+) -> Vec<Option<(usize, f64)>> {
+    let t = observations.len();
+    let mut result = vec![None; t];
+    if n_states < 2 || lookback < 2 * n_states || refit_every == 0 {
+        return result;
+    }
 
-        result.push(state);
-        posterior.copy_from_slice(&norm_post);
-        prev_state = state;
+    let mut refit_at = lookback;
+    while refit_at < t {
+        let window = &observations[refit_at - lookback..refit_at];
+        let segment_end = (refit_at + refit_every).min(t);
+        if window.iter().all(|x| x.is_finite()) {
+            let hmm = fit(window, n_states);
+            let rank = volatility_rank(&hmm);
+            let segment = &observations[refit_at - lookback..segment_end];
+            let states = forward_filter(&hmm, segment, threshold);
+            let posteriors = filter_posteriors(&hmm, segment);
+            // Skip the in-window warmup; only bars past the fit window are emitted
+            let emitted = states.iter().zip(&posteriors).skip(lookback);
+            for (slot, (&state, posterior)) in result[refit_at..segment_end].iter_mut().zip(emitted)
+            {
+                let posterior = posterior[state];
+                if posterior.is_finite() {
+                    *slot = Some((rank[state], posterior));
+                }
+            }
+        }
+        refit_at = segment_end;
     }
 
     result
 }
 
+/// Rank of each fitted state by emission variance (0 = calmest).
+fn volatility_rank(hmm: &GaussianHmm) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..hmm.n_states).collect();
+    order.sort_by(|&a, &b| hmm.variances[a].total_cmp(&hmm.variances[b]));
+    let mut rank = vec![0; hmm.n_states];
+    for (r, &state) in order.iter().enumerate() {
+        rank[state] = r;
+    }
+    rank
+}
+
+/// Label for a volatility-ranked state from [`rolling_regimes`]:
+/// `low_vol` / `high_vol` at the ends, `mid_vol` for the middle of three, and
+/// `vol_<rank>` for interior states of larger models.
+pub fn volatility_regime_label(state: usize, n_states: usize) -> String {
+    if state == 0 {
+        "low_vol".to_string()
+    } else if state + 1 == n_states {
+        "high_vol".to_string()
+    } else if n_states == 3 {
+        "mid_vol".to_string()
+    } else {
+        format!("vol_{state}")
+    }
+}
+
 /// Check if any pair of HMM states has overlapping emission distributions.
 ///
 /// Two states overlap if their means are within 1 standard deviation of each other
@@ -386,6 +456,699 @@ pub fn overlapping_emissions(hmm: &GaussianHmm) -> bool {
     false
 }
 
+// ---------------------------------------------------------------------------
+// Multivariate Gaussian HMM
+// ---------------------------------------------------------------------------
+
+/// Ridge added to every fitted covariance diagonal so it stays positive definite.
+const COVARIANCE_RIDGE: f64 = 1e-6;
+
+/// Lloyd iterations used to seed the multivariate EM.
+const KMEANS_ITER: usize = 10;
+
+/// Posteriors from one scaled forward-backward pass.
+struct Posteriors {
+    /// `gamma[t][j]` = P(state j at t | all observations).
+    gamma: Vec<Vec<f64>>,
+    /// Expected number of i → j transitions over the sample.
+    transitions: Vec<Vec<f64>>,
+    log_likelihood: f64,
+}
+
+/// Forward-backward over log emission densities `log_b[t][state]`, shifting
+/// each row by its maximum so the densities never underflow.
+#[allow(clippy::needless_range_loop)]
+fn forward_backward(log_b: &[Vec<f64>], initial: &[f64], transition: &[Vec<f64>]) -> Posteriors {
+    let t = log_b.len();
+    let n_states = initial.len();
+    let shift: Vec<f64> = log_b
+        .iter()
+        .map(|row| row.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+        .collect();
+    let b: Vec<Vec<f64>> = log_b
+        .iter()
+        .zip(&shift)
+        .map(|(row, &m)| row.iter().map(|&lp| (lp - m).exp()).collect())
+        .collect();
+
+    let mut alpha = vec![vec![0.0_f64; n_states]; t];
+    let mut scale = vec![0.0_f64; t];
+    for tt in 0..t {
+        for j in 0..n_states {
+            let prior = if tt == 0 {
+                initial[j]
+            } else {
+                (0..n_states)
+                    .map(|i| alpha[tt - 1][i] * transition[i][j])
+                    .sum()
+            };
+            alpha[tt][j] = prior * b[tt][j];
+        }
+        let total: f64 = alpha[tt].iter().sum();
+        scale[tt] = if total > 0.0 { total } else { 1e-300 };
+        for j in 0..n_states {
+            alpha[tt][j] /= scale[tt];
+        }
+    }
+    let log_likelihood = scale.iter().zip(&shift).map(|(&c, &m)| c.ln() + m).sum();
+
+    let mut beta = vec![vec![0.0_f64; n_states]; t];
+    beta[t - 1].fill(1.0);
+    for tt in (0..t - 1).rev() {
+        for i in 0..n_states {
+            beta[tt][i] = (0..n_states)
+                .map(|j| transition[i][j] * b[tt + 1][j] * beta[tt + 1][j])
+                .sum::<f64>()
+                / scale[tt + 1];
+        }
+    }
+    let gamma = (0..t)
+        .map(|tt| {
+            let denom: f64 = (0..n_states).map(|j| alpha[tt][j] * beta[tt][j]).sum();
+            (0..n_states)
+                .map(|j| {
+                    if denom > 0.0 {
+                        alpha[tt][j] * beta[tt][j] / denom
+                    } else {
+                        1.0 / n_states as f64
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut transitions = vec![vec![0.0_f64; n_states]; n_states];
+    for tt in 0..t - 1 {
+        for i in 0..n_states {
+            for j in 0..n_states {
+                transitions[i][j] +=
+                    alpha[tt][i] * transition[i][j] * b[tt + 1][j] * beta[tt + 1][j]
+                        / scale[tt + 1];
+            }
+        }
+    }
+
+    Posteriors {
+        gamma,
+        transitions,
+        log_likelihood,
+    }
+}
+
+/// Replace each transition row with the normalized expected transition counts.
+fn reestimate_transition(transition: &mut [Vec<f64>], expected: &[Vec<f64>]) {
+    for (row, next) in transition.iter_mut().zip(expected) {
+        let row_sum: f64 = next.iter().map(|v| v.max(1e-10)).sum();
+        for (val, n) in row.iter_mut().zip(next) {
+            *val = n.max(1e-10) / row_sum;
+        }
+    }
+}
+
+/// Most likely state path given log emission densities `log_b[t][state]`.
+#[allow(clippy::needless_range_loop)]
+fn viterbi_log(log_b: &[Vec<f64>], initial: &[f64], transition: &[Vec<f64>]) -> Vec<usize> {
+    let t = log_b.len();
+    let k = initial.len();
+    if t == 0 {
+        return vec![];
+    }
+    let log_a: Vec<Vec<f64>> = transition
+        .iter()
+        .map(|row| row.iter().map(|p| p.max(1e-300).ln()).collect())
+        .collect();
+
+    let mut v = vec![vec![f64::NEG_INFINITY; k]; t];
+    let mut bt = vec![vec![0usize; k]; t];
+    for j in 0..k {
+        v[0][j] = initial[j].max(1e-300).ln() + log_b[0][j];
+    }
+    for tt in 1..t {
+        for j in 0..k {
+            let (best_i, best_val) = (0..k)
+                .map(|i| (i, v[tt - 1][i] + log_a[i][j]))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((0, f64::NEG_INFINITY));
+            v[tt][j] = best_val + log_b[tt][j];
+            bt[tt][j] = best_i;
+        }
+    }
+
+    let mut path = vec![0usize; t];
+    path[t - 1] = v[t - 1]
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(i, _)| i);
+    for tt in (0..t - 1).rev() {
+        path[tt] = bt[tt + 1][path[tt + 1]];
+    }
+    path
+}
+
+/// Fitted Gaussian HMM with multivariate (full-covariance) emissions.
+///
+/// Each observation is a feature vector (e.g. return, realized vol, VIX level,
+/// term-structure slope); states are sorted by ascending mean of feature 0.
+#[derive(Debug, Clone)]
+pub struct MultivariateHmm {
+    pub n_states: usize,
+    pub n_features: usize,
+    /// Initial state probabilities (pi).
+    pub initial: Vec<f64>,
+    /// Transition matrix A\[i\]\[j\] = P(state j | state i).
+    pub transition: Vec<Vec<f64>>,
+    /// Emission mean vector per state.
+    pub means: Vec<Vec<f64>>,
+    /// Emission covariance per state (ridge-regularized, positive definite).
+    pub covariances: Vec<DMatrix<f64>>,
+    /// Log-likelihood of the training observations under the fitted model.
+    pub log_likelihood: f64,
+}
+
+impl MultivariateHmm {
+    /// Number of free parameters: initial, transition, means and covariances.
+    pub fn n_parameters(&self) -> usize {
+        let (k, d) = (self.n_states, self.n_features);
+        (k - 1) + k * (k - 1) + k * d + k * d * (d + 1) / 2
+    }
+
+    /// Bayesian information criterion `p·ln(T) − 2·ln L` (lower is better).
+    pub fn bic(&self, n_observations: usize) -> f64 {
+        self.n_parameters() as f64 * (n_observations as f64).ln() - 2.0 * self.log_likelihood
+    }
+}
+
+/// One state count tried by [`select_by_bic`] or
+/// [`select_markov_switching_by_bic`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BicCandidate {
+    pub n_states: usize,
+    pub log_likelihood: f64,
+    pub bic: f64,
+}
+
+/// Cholesky factor and log-determinant of one state's covariance.
+struct EmissionDensity {
+    chol: DMatrix<f64>,
+    log_norm: f64,
+}
+
+impl EmissionDensity {
+    fn new(covariance: &DMatrix<f64>) -> Self {
+        let d = covariance.nrows();
+        let mut cov = covariance.clone();
+        let mut ridge = COVARIANCE_RIDGE;
+        let chol = loop {
+            if let Some(chol) = Cholesky::new(cov.clone()) {
+                break chol.l();
+            }
+            cov += DMatrix::identity(d, d) * ridge;
+            ridge *= 10.0;
+        };
+        let log_det = 2.0 * chol.diagonal().iter().map(|v| v.ln()).sum::<f64>();
+        Self {
+            chol,
+            log_norm: -0.5 * (d as f64 * std::f64::consts::TAU.ln() + log_det),
+        }
+    }
+
+    fn log_pdf(&self, x: &[f64], mean: &[f64]) -> f64 {
+        let diff = DVector::from_iterator(x.len(), x.iter().zip(mean).map(|(a, b)| a - b));
+        let z = self
+            .chol
+            .solve_lower_triangular(&diff)
+            .unwrap_or_else(|| DVector::zeros(x.len()));
+        self.log_norm - 0.5 * z.norm_squared()
+    }
+}
+
+/// Log emission density of every observation under every state, `[t][state]`.
+fn log_emissions(
+    observations: &[Vec<f64>],
+    means: &[Vec<f64>],
+    covariances: &[DMatrix<f64>],
+) -> Vec<Vec<f64>> {
+    let densities: Vec<EmissionDensity> = covariances.iter().map(EmissionDensity::new).collect();
+    observations
+        .iter()
+        .map(|x| {
+            densities
+                .iter()
+                .zip(means)
+                .map(|(density, mean)| density.log_pdf(x, mean))
+                .collect()
+        })
+        .collect()
+}
+
+/// Weighted mean and covariance of `observations` under `weights`.
+fn weighted_moments(observations: &[Vec<f64>], weights: &[f64]) -> (Vec<f64>, DMatrix<f64>) {
+    let d = observations[0].len();
+    let total: f64 = weights.iter().sum::<f64>().max(1e-300);
+    let mut mean = vec![0.0; d];
+    for (x, &w) in observations.iter().zip(weights) {
+        for (m, v) in mean.iter_mut().zip(x) {
+            *m += w * v;
+        }
+    }
+    for m in &mut mean {
+        *m /= total;
+    }
+    let mut cov = DMatrix::zeros(d, d);
+    for (x, &w) in observations.iter().zip(weights) {
+        let diff = DVector::from_iterator(d, x.iter().zip(&mean).map(|(a, b)| a - b));
+        cov += &diff * diff.transpose() * w;
+    }
+    cov /= total;
+    cov += DMatrix::identity(d, d) * COVARIANCE_RIDGE;
+    (mean, cov)
+}
+
+/// Deterministic k-means seeding: farthest-point centers, then Lloyd iterations.
+fn kmeans_assign(observations: &[Vec<f64>], k: usize) -> Vec<usize> {
+    let dist =
+        |a: &[f64], b: &[f64]| -> f64 { a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum() };
+    let (global_mean, _) = weighted_moments(observations, &vec![1.0; observations.len()]);
+
+    let farthest_from = |centers: &[Vec<f64>]| -> Vec<f64> {
+        observations
+            .iter()
+            .max_by(|a, b| {
+                let da = centers
+                    .iter()
+                    .map(|c| dist(a, c))
+                    .fold(f64::INFINITY, f64::min);
+                let db = centers
+                    .iter()
+                    .map(|c| dist(b, c))
+                    .fold(f64::INFINITY, f64::min);
+                da.total_cmp(&db)
+            })
+            .cloned()
+            .unwrap_or_else(|| global_mean.clone())
+    };
+    let mut centers = vec![farthest_from(std::slice::from_ref(&global_mean))];
+    while centers.len() < k {
+        let next = farthest_from(&centers);
+        centers.push(next);
+    }
+
+    let mut labels = vec![0; observations.len()];
+    for _ in 0..KMEANS_ITER {
+        for (label, x) in labels.iter_mut().zip(observations) {
+            *label = (0..k)
+                .min_by(|&a, &b| dist(x, &centers[a]).total_cmp(&dist(x, &centers[b])))
+                .unwrap_or(0);
+        }
+        for (c, center) in centers.iter_mut().enumerate() {
+            let members: Vec<&Vec<f64>> = observations
+                .iter()
+                .zip(&labels)
+                .filter(|(_, &l)| l == c)
+                .map(|(x, _)| x)
+                .collect();
+            if !members.is_empty() {
+                for (f, value) in center.iter_mut().enumerate() {
+                    *value = members.iter().map(|x| x[f]).sum::<f64>() / members.len() as f64;
+                }
+            }
+        }
+    }
+    labels
+}
+
+/// Fit a multivariate Gaussian HMM with full covariances using Baum-Welch (EM).
+///
+/// `observations` are rows of equal-length feature vectors; features should be
+/// on comparable scales (standardize first). States are sorted by ascending
+/// mean of feature 0, so with returns first, state 0 is the lowest-return regime.
+pub fn fit_multivariate(observations: &[Vec<f64>], n_states: usize) -> MultivariateHmm {
+    fit_multivariate_with_params(observations, n_states, DEFAULT_MAX_ITER, DEFAULT_TOL)
+}
+
+/// Multivariate fit with explicit iteration and tolerance parameters.
+#[allow(clippy::too_many_lines, clippy::needless_range_loop)]
+pub fn fit_multivariate_with_params(
+    observations: &[Vec<f64>],
+    n_states: usize,
+    max_iter: usize,
+    tol: f64,
+) -> MultivariateHmm {
+    let t = observations.len();
+    assert!(t >= 2, "need at least 2 observations");
+    assert!(n_states >= 2, "need at least 2 states");
+    let d = observations[0].len();
+    assert!(
+        d > 0 && observations.iter().all(|x| x.len() == d),
+        "observations must share a non-zero feature count"
+    );
+
+    // ── Initialization: k-means clusters → per-cluster moments ──
+    let labels = kmeans_assign(observations, n_states);
+    let mut means = Vec::with_capacity(n_states);
+    let mut covariances = Vec::with_capacity(n_states);
+    for s in 0..n_states {
+        let mut weights: Vec<f64> = labels
+            .iter()
+            .map(|&l| if l == s { 1.0 } else { 0.0 })
+            .collect();
+        if weights.iter().sum::<f64>() < d as f64 + 1.0 {
+            // Degenerate cluster: fall back to the pooled moments
+            weights = vec![1.0; t];
+        }
+        let (mean, cov) = weighted_moments(observations, &weights);
+        means.push(mean);
+        covariances.push(cov);
+    }
+
+    let mut initial = vec![1.0 / n_states as f64; n_states];
+    let off_diag = 0.3 / (n_states - 1).max(1) as f64;
+    let mut transition: Vec<Vec<f64>> = (0..n_states)
+        .map(|i| {
+            (0..n_states)
+                .map(|j| if i == j { 0.7 } else { off_diag })
+                .collect()
+        })
+        .collect();
+
+    let mut prev_ll = f64::NEG_INFINITY;
+    let mut ll = f64::NEG_INFINITY;
+
+    for _iter in 0..max_iter {
+        let log_b = log_emissions(observations, &means, &covariances);
+        let posteriors = forward_backward(&log_b, &initial, &transition);
+        ll = posteriors.log_likelihood;
+
+        // ── M-step ──
+        initial.clone_from(&posteriors.gamma[0]);
+        reestimate_transition(&mut transition, &posteriors.transitions);
+        for j in 0..n_states {
+            let weights: Vec<f64> = posteriors.gamma.iter().map(|g| g[j]).collect();
+            if weights.iter().sum::<f64>() > 1e-9 {
+                let (mean, cov) = weighted_moments(observations, &weights);
+                means[j] = mean;
+                covariances[j] = cov;
+            }
+        }
+
+        if (ll - prev_ll).abs() < tol {
+            break;
+        }
+        prev_ll = ll;
+    }
+
+    let mut order: Vec<usize> = (0..n_states).collect();
+    order.sort_by(|&a, &b| means[a][0].total_cmp(&means[b][0]));
+    MultivariateHmm {
+        n_states,
+        n_features: d,
+        initial: order.iter().map(|&i| initial[i]).collect(),
+        transition: order
+            .iter()
+            .map(|&i| order.iter().map(|&j| transition[i][j]).collect())
+            .collect(),
+        means: order.iter().map(|&i| means[i].clone()).collect(),
+        covariances: order.iter().map(|&i| covariances[i].clone()).collect(),
+        log_likelihood: ll,
+    }
+}
+
+/// Viterbi decoding for a multivariate HMM (log-space).
+pub fn viterbi_multivariate(hmm: &MultivariateHmm, observations: &[Vec<f64>]) -> Vec<usize> {
+    let log_b = log_emissions(observations, &hmm.means, &hmm.covariances);
+    viterbi_log(&log_b, &hmm.initial, &hmm.transition)
+}
+
+/// Fit a multivariate HMM for each state count in `state_counts` and keep the
+/// lowest-BIC model.
+///
+/// State counts with no more observations than free parameters are skipped;
+/// returns `None` when none can be fit.
+pub fn select_by_bic(
+    observations: &[Vec<f64>],
+    state_counts: impl IntoIterator<Item = usize>,
+) -> Option<(MultivariateHmm, Vec<BicCandidate>)> {
+    let t = observations.len();
+    let mut best: Option<(MultivariateHmm, f64)> = None;
+    let mut candidates = Vec::new();
+    for n_states in state_counts {
+        if n_states < 2 || t < 2 {
+            continue;
+        }
+        let hmm = fit_multivariate(observations, n_states);
+        if hmm.n_parameters() >= t || !hmm.log_likelihood.is_finite() {
+            continue;
+        }
+        let bic = hmm.bic(t);
+        candidates.push(BicCandidate {
+            n_states,
+            log_likelihood: hmm.log_likelihood,
+            bic,
+        });
+        if best.as_ref().is_none_or(|(_, b)| bic < *b) {
+            best = Some((hmm, bic));
+        }
+    }
+    best.map(|(hmm, _)| (hmm, candidates))
+}
+
+// ---------------------------------------------------------------------------
+// Markov-switching regression
+// ---------------------------------------------------------------------------
+
+/// Ridge added to the normal equations so a state's weighted least squares
+/// stays solvable when it holds few observations.
+const REGRESSION_RIDGE: f64 = 1e-8;
+
+/// Fitted Markov-switching regression `y_t = x_t·β_s + ε_t`,
+/// `ε_t ~ N(0, σ_s²)`, where the state `s` follows a Markov chain.
+///
+/// States are sorted by ascending first coefficient, so with an intercept
+/// first, state 0 is the lowest-drift regime.
+#[derive(Debug, Clone)]
+pub struct MarkovSwitchingRegression {
+    pub n_states: usize,
+    pub n_regressors: usize,
+    /// Initial state probabilities (pi).
+    pub initial: Vec<f64>,
+    /// Transition matrix A\[i\]\[j\] = P(state j | state i).
+    pub transition: Vec<Vec<f64>>,
+    /// Regression coefficients per state, one per regressor.
+    pub coefficients: Vec<Vec<f64>>,
+    /// Residual variance per state.
+    pub variances: Vec<f64>,
+    /// Log-likelihood of the training observations under the fitted model.
+    pub log_likelihood: f64,
+}
+
+impl MarkovSwitchingRegression {
+    /// Number of free parameters: initial, transition, coefficients and variances.
+    pub fn n_parameters(&self) -> usize {
+        let (k, p) = (self.n_states, self.n_regressors);
+        (k - 1) + k * (k - 1) + k * p + k
+    }
+
+    /// Bayesian information criterion `p·ln(T) − 2·ln L` (lower is better).
+    pub fn bic(&self, n_observations: usize) -> f64 {
+        self.n_parameters() as f64 * (n_observations as f64).ln() - 2.0 * self.log_likelihood
+    }
+}
+
+/// Weighted least squares of `y` on `x`: coefficients and weighted residual variance.
+fn weighted_least_squares(y: &[f64], x: &[Vec<f64>], weights: &[f64]) -> (Vec<f64>, f64) {
+    let p = x[0].len();
+    let mut xtx = DMatrix::identity(p, p) * REGRESSION_RIDGE;
+    let mut xty = DVector::zeros(p);
+    for ((row, &target), &w) in x.iter().zip(y).zip(weights) {
+        let row = DVector::from_column_slice(row);
+        xtx += &row * row.transpose() * w;
+        xty += row * (w * target);
+    }
+    let beta = Cholesky::new(xtx).map_or_else(|| DVector::zeros(p), |chol| chol.solve(&xty));
+    let total: f64 = weights.iter().sum::<f64>().max(1e-300);
+    let sse: f64 = x
+        .iter()
+        .zip(y)
+        .zip(weights)
+        .map(|((row, &target), &w)| {
+            let fitted: f64 = row.iter().zip(beta.iter()).map(|(a, b)| a * b).sum();
+            w * (target - fitted).powi(2)
+        })
+        .sum();
+    (
+        beta.iter().copied().collect(),
+        (sse / total).max(VARIANCE_FLOOR),
+    )
+}
+
+/// Log density of every observation under every state's regression, `[t][state]`.
+fn regression_log_emissions(
+    y: &[f64],
+    x: &[Vec<f64>],
+    coefficients: &[Vec<f64>],
+    variances: &[f64],
+) -> Vec<Vec<f64>> {
+    x.iter()
+        .zip(y)
+        .map(|(row, &target)| {
+            coefficients
+                .iter()
+                .zip(variances)
+                .map(|(beta, &var)| {
+                    let fitted: f64 = row.iter().zip(beta).map(|(a, b)| a * b).sum();
+                    log_gaussian(target, fitted, var)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Fit a Markov-switching regression of `y` on the regressor rows `x` (include
+/// a constant column for a switching intercept) using EM.
+pub fn fit_markov_switching(
+    y: &[f64],
+    x: &[Vec<f64>],
+    n_states: usize,
+) -> MarkovSwitchingRegression {
+    fit_markov_switching_with_params(y, x, n_states, DEFAULT_MAX_ITER, DEFAULT_TOL)
+}
+
+/// Markov-switching fit with explicit iteration and tolerance parameters.
+pub fn fit_markov_switching_with_params(
+    y: &[f64],
+    x: &[Vec<f64>],
+    n_states: usize,
+    max_iter: usize,
+    tol: f64,
+) -> MarkovSwitchingRegression {
+    let t = y.len();
+    assert!(t >= 2, "need at least 2 observations");
+    assert!(n_states >= 2, "need at least 2 states");
+    assert_eq!(x.len(), t, "need one regressor row per observation");
+    let p = x[0].len();
+    assert!(
+        p > 0 && x.iter().all(|row| row.len() == p),
+        "regressor rows must share a non-zero width"
+    );
+
+    // ── Initialization: pooled OLS, then states by residual-size quantile ──
+    let (pooled, _) = weighted_least_squares(y, x, &vec![1.0; t]);
+    let mut by_residual: Vec<usize> = (0..t).collect();
+    let residual = |i: usize| {
+        let fitted: f64 = x[i].iter().zip(&pooled).map(|(a, b)| a * b).sum();
+        (y[i] - fitted).abs()
+    };
+    by_residual.sort_by(|&a, &b| residual(a).total_cmp(&residual(b)));
+    let mut coefficients = Vec::with_capacity(n_states);
+    let mut variances = Vec::with_capacity(n_states);
+    for s in 0..n_states {
+        let mut weights = vec![0.0; t];
+        for &i in &by_residual[s * t / n_states..(s + 1) * t / n_states] {
+            weights[i] = 1.0;
+        }
+        if weights.iter().sum::<f64>() < p as f64 + 1.0 {
+            // Degenerate group: fall back to the pooled fit
+            weights = vec![1.0; t];
+        }
+        let (beta, var) = weighted_least_squares(y, x, &weights);
+        coefficients.push(beta);
+        variances.push(var);
+    }
+
+    let mut initial = vec![1.0 / n_states as f64; n_states];
+    let off_diag = 0.3 / (n_states - 1).max(1) as f64;
+    let mut transition: Vec<Vec<f64>> = (0..n_states)
+        .map(|i| {
+            (0..n_states)
+                .map(|j| if i == j { 0.7 } else { off_diag })
+                .collect()
+        })
+        .collect();
+
+    let mut prev_ll = f64::NEG_INFINITY;
+    let mut ll = f64::NEG_INFINITY;
+    for _iter in 0..max_iter {
+        let log_b = regression_log_emissions(y, x, &coefficients, &variances);
+        let posteriors = forward_backward(&log_b, &initial, &transition);
+        ll = posteriors.log_likelihood;
+
+        // ── M-step: weighted least squares per state ──
+        initial.clone_from(&posteriors.gamma[0]);
+        reestimate_transition(&mut transition, &posteriors.transitions);
+        for (j, (beta, var)) in coefficients.iter_mut().zip(&mut variances).enumerate() {
+            let weights: Vec<f64> = posteriors.gamma.iter().map(|g| g[j]).collect();
+            if weights.iter().sum::<f64>() > 1e-9 {
+                (*beta, *var) = weighted_least_squares(y, x, &weights);
+            }
+        }
+
+        if (ll - prev_ll).abs() < tol {
+            break;
+        }
+        prev_ll = ll;
+    }
+
+    let mut order: Vec<usize> = (0..n_states).collect();
+    order.sort_by(|&a, &b| coefficients[a][0].total_cmp(&coefficients[b][0]));
+    MarkovSwitchingRegression {
+        n_states,
+        n_regressors: p,
+        initial: order.iter().map(|&i| initial[i]).collect(),
+        transition: order
+            .iter()
+            .map(|&i| order.iter().map(|&j| transition[i][j]).collect())
+            .collect(),
+        coefficients: order.iter().map(|&i| coefficients[i].clone()).collect(),
+        variances: order.iter().map(|&i| variances[i]).collect(),
+        log_likelihood: ll,
+    }
+}
+
+/// Viterbi decoding for a Markov-switching regression (log-space).
+pub fn viterbi_markov_switching(
+    model: &MarkovSwitchingRegression,
+    y: &[f64],
+    x: &[Vec<f64>],
+) -> Vec<usize> {
+    let log_b = regression_log_emissions(y, x, &model.coefficients, &model.variances);
+    viterbi_log(&log_b, &model.initial, &model.transition)
+}
+
+/// Fit a Markov-switching regression for each state count in `state_counts`
+/// and keep the lowest-BIC model.
+///
+/// State counts with no more observations than free parameters are skipped;
+/// returns `None` when none can be fit.
+pub fn select_markov_switching_by_bic(
+    y: &[f64],
+    x: &[Vec<f64>],
+    state_counts: impl IntoIterator<Item = usize>,
+) -> Option<(MarkovSwitchingRegression, Vec<BicCandidate>)> {
+    let t = y.len();
+    let mut best: Option<(MarkovSwitchingRegression, f64)> = None;
+    let mut candidates = Vec::new();
+    for n_states in state_counts {
+        if n_states < 2 || t < 2 {
+            continue;
+        }
+        let model = fit_markov_switching(y, x, n_states);
+        if model.n_parameters() >= t || !model.log_likelihood.is_finite() {
+            continue;
+        }
+        let bic = model.bic(t);
+        candidates.push(BicCandidate {
+            n_states,
+            log_likelihood: model.log_likelihood,
+            bic,
+        });
+        if best.as_ref().is_none_or(|(_, b)| bic < *b) {
+            best = Some((model, bic));
+        }
+    }
+    best.map(|(model, _)| (model, candidates))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -625,6 +1388,81 @@ mod tests {
         assert_eq!(switches, 0, "threshold=1.0 should produce zero switches");
     }
 
+    /// Alternating 60-bar calm / volatile blocks with zero mean.
+    fn vol_block_data(n: usize) -> Vec<f64> {
+        let mut seed: u64 = 7;
+        (0..n)
+            .map(|i| {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                let u = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+                let scale = if (i / 60).is_multiple_of(2) {
+                    0.01
+                } else {
+                    0.06
+                };
+                u * scale
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_posteriors_are_distributions() {
+        let data = two_state_data(200);
+        let hmm = fit(&data, 2);
+        let posteriors = filter_posteriors(&hmm, &data);
+        assert_eq!(posteriors.len(), data.len());
+        for row in &posteriors {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_rolling_regimes_has_no_look_ahead() {
+        let data = vol_block_data(480);
+        let full = rolling_regimes(&data, 2, 180, 20, 0.6);
+        assert!(
+            full[..180].iter().all(Option::is_none),
+            "warmup must be empty"
+        );
+        // Truncating the future never changes past labels
+        for cut in [200, 273, 399] {
+            let prefix = rolling_regimes(&data[..cut], 2, 180, 20, 0.6);
+            assert_eq!(prefix[..], full[..cut], "labels changed when cut at {cut}");
+        }
+    }
+
+    #[test]
+    fn test_rolling_regimes_ranks_states_by_volatility() {
+        let data = vol_block_data(480);
+        let regimes = rolling_regimes(&data, 2, 180, 20, 0.6);
+        // Bars 370..420 are the tail of a calm block, 430..480 of a volatile one
+        let share_high = |range: std::ops::Range<usize>| {
+            let n = range.len() as f64;
+            regimes[range]
+                .iter()
+                .filter(|r| matches!(r, Some((1, _))))
+                .count() as f64
+                / n
+        };
+        assert!(share_high(370..420) < 0.3, "calm block labeled high_vol");
+        assert!(
+            share_high(430..480) > 0.7,
+            "volatile block not labeled high_vol"
+        );
+        for (state, posterior) in regimes.iter().flatten() {
+            assert!(*state < 2 && (0.0..=1.0).contains(posterior));
+        }
+    }
+
+    #[test]
+    fn test_volatility_regime_labels() {
+        assert_eq!(volatility_regime_label(0, 2), "low_vol");
+        assert_eq!(volatility_regime_label(1, 2), "high_vol");
+        assert_eq!(volatility_regime_label(1, 3), "mid_vol");
+        assert_eq!(volatility_regime_label(2, 4), "vol_2");
+        assert_eq!(volatility_regime_label(3, 4), "high_vol");
+    }
+
     #[test]
     fn test_forward_filter_mostly_agrees_with_viterbi() {
         let data = two_state_data(400);
@@ -662,4 +1500,141 @@ mod tests {
         };
         assert!(overlapping_emissions(&hmm));
     }
+
+    /// Three regimes that share a return mean pairwise but differ jointly:
+    /// (calm, up), (volatile, up), (volatile, down) in 50-bar blocks.
+    fn three_regime_features(n: usize) -> (Vec<Vec<f64>>, Vec<usize>) {
+        let mut seed: u64 = 11;
+        let mut noise = || -> f64 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            ((seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.6
+        };
+        let centers = [[0.5, -1.0], [0.5, 1.0], [-1.0, 1.0]];
+        let truth: Vec<usize> = (0..n).map(|i| (i / 50) % 3).collect();
+        let obs = truth
+            .iter()
+            .map(|&s| vec![centers[s][0] + noise(), centers[s][1] + noise()])
+            .collect();
+        (obs, truth)
+    }
+
+    #[test]
+    fn test_multivariate_fit_recovers_joint_regimes() {
+        let (obs, truth) = three_regime_features(600);
+        let hmm = fit_multivariate(&obs, 3);
+        assert_eq!(hmm.n_features, 2);
+        assert!(hmm.means[0][0] < hmm.means[1][0] && hmm.means[1][0] <= hmm.means[2][0]);
+        for row in &hmm.transition {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+
+        // Decoded states must map one-to-one onto the true regimes
+        let path = viterbi_multivariate(&hmm, &obs);
+        let mut matches = 0usize;
+        let mut mapping = [usize::MAX; 3];
+        for (&state, &true_state) in path.iter().zip(&truth) {
+            if mapping[true_state] == usize::MAX {
+                mapping[true_state] = state;
+            }
+            if mapping[true_state] == state {
+                matches += 1;
+            }
+        }
+        assert!(
+            matches as f64 / obs.len() as f64 > 0.95,
+            "only {matches}/{} bars decoded consistently",
+            obs.len()
+        );
+        let mut distinct = mapping.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 3, "regimes collapsed: {mapping:?}");
+    }
+
+    #[test]
+    fn test_select_by_bic_picks_true_state_count() {
+        let (obs, _) = three_regime_features(600);
+        let (best, candidates) = select_by_bic(&obs, 2..=4).expect("some model fits");
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(best.n_states, 3, "BIC table: {candidates:?}");
+        let best_bic = candidates
+            .iter()
+            .map(|c| c.bic)
+            .fold(f64::INFINITY, f64::min);
+        assert!((best.bic(obs.len()) - best_bic).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_select_by_bic_skips_underdetermined_models() {
+        let (obs, _) = three_regime_features(12);
+        // 2 states × 2 features already needs 13 parameters
+        assert!(select_by_bic(&obs, 2..=3).is_none());
+    }
+
+    #[test]
+    fn test_multivariate_parameter_count() {
+        let (obs, _) = three_regime_features(300);
+        let hmm = fit_multivariate(&obs, 2);
+        // 1 initial + 2 transition + 4 means + 6 covariance terms
+        assert_eq!(hmm.n_parameters(), 13);
+    }
+
+    /// Two regression regimes in 100-bar blocks: `y = -1 + 2x` (quiet) and
+    /// `y = 1 − x` (noisy), with `x` uniform on [−1, 1].
+    fn switching_regression_data(n: usize) -> (Vec<f64>, Vec<Vec<f64>>, Vec<usize>) {
+        let mut seed: u64 = 7;
+        let mut uniform = || -> f64 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let truth: Vec<usize> = (0..n).map(|i| (i / 100) % 2).collect();
+        let mut y = Vec::with_capacity(n);
+        let mut x = Vec::with_capacity(n);
+        for &state in &truth {
+            let regressor = uniform() * 2.0;
+            let target = if state == 0 {
+                -1.0 + 2.0 * regressor + uniform() * 0.1
+            } else {
+                1.0 - regressor + uniform() * 0.6
+            };
+            x.push(vec![1.0, regressor]);
+            y.push(target);
+        }
+        (y, x, truth)
+    }
+
+    #[test]
+    fn test_markov_switching_recovers_coefficients() {
+        let (y, x, truth) = switching_regression_data(600);
+        let model = fit_markov_switching(&y, &x, 2);
+        assert_eq!(model.n_regressors, 2);
+        assert!((model.coefficients[0][0] + 1.0).abs() < 0.05, "{model:?}");
+        assert!((model.coefficients[0][1] - 2.0).abs() < 0.05, "{model:?}");
+        assert!((model.coefficients[1][0] - 1.0).abs() < 0.1, "{model:?}");
+        assert!((model.coefficients[1][1] + 1.0).abs() < 0.1, "{model:?}");
+        assert!(model.variances[0] < model.variances[1]);
+        for row in &model.transition {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+
+        let path = viterbi_markov_switching(&model, &y, &x);
+        let correct = path.iter().zip(&truth).filter(|(a, b)| a == b).count();
+        assert!(
+            correct as f64 / y.len() as f64 > 0.95,
+            "only {correct}/{} bars decoded correctly",
+            y.len()
+        );
+    }
+
+    #[test]
+    fn test_select_markov_switching_by_bic() {
+        let (y, x, _) = switching_regression_data(600);
+        let (best, candidates) =
+            select_markov_switching_by_bic(&y, &x, 2..=3).expect("some model fits");
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(best.n_states, 2, "BIC table: {candidates:?}");
+        // 1 initial + 2 transition + 4 coefficients + 2 variances
+        assert_eq!(best.n_parameters(), 9);
+        assert!(select_markov_switching_by_bic(&y[..8], &x[..8], 2..=3).is_none());
+    }
 }
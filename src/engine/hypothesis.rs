@@ -8,6 +8,7 @@ use crate::constants::TRADING_DAYS_PER_YEAR;
 use statrs::distribution::{ContinuousCDF, Normal};
 use std::collections::{HashMap, HashSet};
 
+use crate::engine::greeks;
 use crate::engine::multiple_comparisons::benjamini_hochberg;
 use crate::engine::types::SignalSpec;
 use crate::engine::types::{HypothesisDimension, PriceBar, StructuralBasis};
@@ -41,6 +42,17 @@ pub struct ScoredHypothesis {
     pub composite_score: f64,
 }
 
+/// Daily options-structure features aligned 1:1 with the scanned prices.
+#[derive(Debug, Clone, Default)]
+pub struct OptionsStructureSeries {
+    /// 25Δ put IV minus 25Δ call IV on the ~30-DTE expiration.
+    pub skew: Vec<f64>,
+    /// 90-day minus 30-day constant-maturity ATM IV.
+    pub term_slope: Vec<f64>,
+    /// Put-to-call volume ratio; `None` when the chain carries no volume.
+    pub put_call_volume: Option<Vec<f64>>,
+}
+
 /// Parameters controlling hypothesis generation.
 pub struct HypothesisConfig {
     pub forward_horizons: Vec<usize>,
@@ -53,6 +65,11 @@ pub struct HypothesisConfig {
 /// Main entry point: scan dimensions, filter by significance, compute DSR,
 /// deduplicate, score, and rank patterns.
 ///
+/// `atm_iv` is the 30-day ATM implied volatility aligned to `prices`; when
+/// present the volatility-regime scan adds IV rank / IV percentile patterns.
+/// `options_structure` feeds the options-structure scan (skew, term slope,
+/// IV-vs-realized spread, put/call volume); without it that dimension is empty.
+///
 /// Returns `(total_trials, patterns_tested, patterns_significant_pre_dedup, scored_hypotheses)`.
 #[allow(clippy::implicit_hasher, clippy::too_many_lines)]
 pub fn generate_hypotheses(
@@ -60,6 +77,8 @@ pub fn generate_hypotheses(
     config: &HypothesisConfig,
     dimensions: &[HypothesisDimension],
     regime_labels: Option<&[usize]>,
+    atm_iv: Option<&[f64]>,
+    options_structure: Option<&OptionsStructureSeries>,
     cross_asset_prices: &HashMap<String, Vec<PriceBar>>,
 ) -> (usize, usize, usize, Vec<ScoredHypothesis>) {
     if prices.len() < 60 {
@@ -77,7 +96,15 @@ pub fn generate_hypotheses(
             }
             HypothesisDimension::Volume => scan_volume(prices, &config.forward_horizons),
             HypothesisDimension::VolatilityRegime => {
-                scan_volatility_regime(prices, &config.forward_horizons)
+                let mut patterns = scan_volatility_regime(prices, &config.forward_horizons);
+                if let Some(iv) = atm_iv {
+                    patterns.extend(scan_implied_volatility(
+                        prices,
+                        iv,
+                        &config.forward_horizons,
+                    ));
+                }
+                patterns
             }
             HypothesisDimension::CrossAsset => scan_cross_asset(
                 prices,
@@ -91,10 +118,9 @@ pub fn generate_hypotheses(
             HypothesisDimension::Autocorrelation => {
                 scan_autocorrelation(prices, &config.forward_horizons)
             }
-            HypothesisDimension::OptionsStructure => {
-                // Options scanner requires options DataFrame — skip if not available
-                vec![]
-            }
+            HypothesisDimension::OptionsStructure => options_structure
+                .map(|os| scan_options_structure(prices, os, atm_iv, &config.forward_horizons))
+                .unwrap_or_default(),
         };
         all_patterns.extend(patterns);
     }
@@ -449,7 +475,13 @@ fn tag_structural_basis(dimension: HypothesisDimension, pattern_type: &str) -> S
         HypothesisDimension::CrossAsset => StructuralBasis::MacroTransmission,
         HypothesisDimension::Microstructure => StructuralBasis::OvernightRiskPremium,
         HypothesisDimension::Autocorrelation => StructuralBasis::EmpiricalOnly,
-        HypothesisDimension::OptionsStructure => StructuralBasis::HedgingDemand,
+        HypothesisDimension::OptionsStructure => {
+            if pattern_type.contains("iv_rv") || pattern_type.contains("term") {
+                StructuralBasis::VarianceRiskPremium
+            } else {
+                StructuralBasis::HedgingDemand
+            }
+        }
     }
 }
 
@@ -945,6 +977,137 @@ fn scan_volatility_regime(prices: &[PriceBar], horizons: &[usize]) -> Vec<RawPat
     patterns
 }
 
+/// Implied volatility: IV rank / IV percentile extremes of the 30-day ATM IV.
+fn scan_implied_volatility(
+    prices: &[PriceBar],
+    atm_iv: &[f64],
+    horizons: &[usize],
+) -> Vec<RawPattern> {
+    let mut patterns = Vec::new();
+    if atm_iv.len() != prices.len() {
+        return patterns;
+    }
+
+    let lookback = TRADING_DAYS_PER_YEAR as usize;
+    let iv_rank = greeks::rolling_iv_rank(atm_iv, lookback);
+    let iv_percentile = greeks::rolling_iv_percentile(atm_iv, lookback);
+
+    for &h in horizons {
+        for (name, series, low, high) in [
+            ("rank", &iv_rank, 20.0, 50.0),
+            ("percentile", &iv_percentile, 20.0, 80.0),
+        ] {
+            for above in [true, false] {
+                let (level, op, threshold) = if above {
+                    ("high", ">", high)
+                } else {
+                    ("low", "<", low)
+                };
+                if let Some(pat) = scan_condition(
+                    prices,
+                    |i, _p| {
+                        let v = series[i];
+                        v.is_finite() && if above { v > threshold } else { v < threshold }
+                    },
+                    h,
+                    &format!(
+                        "IV {name} {op} {threshold} (30-day ATM IV, {lookback}-day lookback) → {h}-day forward return"
+                    ),
+                    HypothesisDimension::VolatilityRegime,
+                    &format!("{level}_iv_{name}"),
+                    SignalSpec::Formula {
+                        formula: format!("iv_{name}(iv, {lookback}) {op} {threshold}"),
+                    },
+                ) {
+                    patterns.push(pat);
+                }
+            }
+        }
+    }
+
+    patterns
+}
+
+/// Options structure: put/call skew, ATM term-structure slope, IV-vs-realized
+/// spread, and put/call volume imbalance. Each feature is ranked against its
+/// own trailing year (percentile of the window, so no look-ahead) and the
+/// top/bottom quintiles are tested as predictors of forward returns. Signals
+/// rank the matching options-structure indicator (`rank(skew_25d, 252) > 80`).
+fn scan_options_structure(
+    prices: &[PriceBar],
+    structure: &OptionsStructureSeries,
+    atm_iv: Option<&[f64]>,
+    horizons: &[usize],
+) -> Vec<RawPattern> {
+    let mut patterns = Vec::new();
+    let lookback = TRADING_DAYS_PER_YEAR as usize;
+
+    let iv_rv = atm_iv.filter(|iv| iv.len() == prices.len()).map(|iv| {
+        let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
+        greeks::iv_rv_spread(iv, &closes)
+    });
+
+    let features: Vec<(&str, &str, &str, &[f64])> = [
+        (
+            "skew",
+            "25Δ put–call IV skew",
+            "skew_25d",
+            Some(structure.skew.as_slice()),
+        ),
+        (
+            "term_slope",
+            "ATM IV term slope (90d − 30d)",
+            "iv_term_slope",
+            Some(structure.term_slope.as_slice()),
+        ),
+        (
+            "iv_rv_spread",
+            "IV − 20-day realized vol",
+            "iv_rv_spread",
+            iv_rv.as_deref(),
+        ),
+        (
+            "put_call_volume",
+            "Put/call volume ratio",
+            "put_call_volume",
+            structure.put_call_volume.as_deref(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(key, label, series, values)| Some((key, label, series, values?)))
+    .filter(|(_, _, _, values)| values.len() == prices.len())
+    .collect();
+
+    for (key, label, series, values) in features {
+        let percentile = greeks::rolling_iv_percentile(values, lookback);
+        for &h in horizons {
+            for (level, op, threshold) in [("high", ">", 80.0), ("low", "<", 20.0)] {
+                let above = op == ">";
+                if let Some(pat) = scan_condition(
+                    prices,
+                    |i, _p| {
+                        let v = percentile[i];
+                        v.is_finite() && if above { v > threshold } else { v < threshold }
+                    },
+                    h,
+                    &format!(
+                        "{label} {op} {threshold} percentile ({lookback}-day lookback) → {h}-day forward return"
+                    ),
+                    HypothesisDimension::OptionsStructure,
+                    &format!("{level}_{key}"),
+                    SignalSpec::Formula {
+                        formula: format!("rank({series}, {lookback}) {op} {threshold}"),
+                    },
+                ) {
+                    patterns.push(pat);
+                }
+            }
+        }
+    }
+
+    patterns
+}
+
 /// Cross-asset: lead/lag relationships with other symbols.
 fn scan_cross_asset(
     prices: &[PriceBar],
@@ -1410,7 +1573,7 @@ mod tests {
             HypothesisDimension::PriceAction,
         ];
         let (total_trials, patterns_tested, _patterns_sig, _hypotheses) =
-            generate_hypotheses(&prices, &config, &dims, None, &HashMap::new());
+            generate_hypotheses(&prices, &config, &dims, None, None, None, &HashMap::new());
         assert!(
             patterns_tested <= total_trials,
             "patterns_tested should be <= total_trials"
@@ -1418,4 +1581,98 @@ mod tests {
         assert!(total_trials > 0, "Should generate some trials");
         // We don't assert hypotheses > 0 since synthetic data may not have significant patterns
     }
+
+    #[test]
+    fn test_scan_implied_volatility_patterns() {
+        let prices = synthetic_prices(600);
+        // Slow IV cycle so both rank extremes occur after the 252-day warmup
+        let atm_iv: Vec<f64> = (0..prices.len())
+            .map(|i| 0.2 + 0.1 * (i as f64 / 40.0).sin())
+            .collect();
+        let patterns = scan_implied_volatility(&prices, &atm_iv, &[5]);
+        assert!(!patterns.is_empty(), "IV extremes should yield patterns");
+        for pat in &patterns {
+            assert_eq!(pat.dimension, HypothesisDimension::VolatilityRegime);
+            assert_eq!(pat.structural_basis, StructuralBasis::VarianceRiskPremium);
+            assert!(matches!(
+                &pat.signal_spec,
+                SignalSpec::Formula { formula } if formula.starts_with("iv_")
+            ));
+        }
+        // Misaligned series is ignored
+        assert!(scan_implied_volatility(&prices, &atm_iv[1..], &[5]).is_empty());
+    }
+
+    #[test]
+    fn test_scan_options_structure_patterns() {
+        let prices = synthetic_prices(600);
+        let n = prices.len();
+        let cycle = |period: f64| -> Vec<f64> {
+            (0..n)
+                .map(|i| 0.05 + 0.03 * (i as f64 / period).sin())
+                .collect()
+        };
+        let structure = OptionsStructureSeries {
+            skew: cycle(40.0),
+            term_slope: cycle(55.0),
+            put_call_volume: None,
+        };
+        let atm_iv: Vec<f64> = cycle(35.0).iter().map(|v| v + 0.15).collect();
+
+        let patterns = scan_options_structure(&prices, &structure, Some(&atm_iv), &[5]);
+        assert!(
+            !patterns.is_empty(),
+            "feature extremes should yield patterns"
+        );
+        let formulas: Vec<&str> = patterns
+            .iter()
+            .map(|p| match &p.signal_spec {
+                SignalSpec::Formula { formula } => formula.as_str(),
+                _ => panic!("expected a formula signal"),
+            })
+            .collect();
+        assert!(formulas
+            .iter()
+            .any(|f| f.starts_with("rank(skew_25d, 252)")));
+        assert!(formulas
+            .iter()
+            .any(|f| f.starts_with("rank(iv_rv_spread, 252)")));
+        // No volume column → no put/call volume patterns
+        assert!(!formulas.iter().any(|f| f.contains("put_call_volume")));
+        // Every signal translates into a DSL condition
+        for formula in &formulas {
+            let expr = crate::scripting::dsl::signal::formula_to_expr(formula)
+                .unwrap_or_else(|e| panic!("{formula}: {e}"));
+            assert!(expr.needs_options, "{formula}");
+        }
+        for pat in &patterns {
+            assert_eq!(pat.dimension, HypothesisDimension::OptionsStructure);
+            let expected = if pat.description.contains("skew") {
+                StructuralBasis::HedgingDemand
+            } else {
+                StructuralBasis::VarianceRiskPremium
+            };
+            assert_eq!(pat.structural_basis, expected, "{}", pat.description);
+        }
+
+        // Without IV only the chain features are scanned
+        let chain_only = scan_options_structure(&prices, &structure, None, &[5]);
+        assert!(chain_only
+            .iter()
+            .all(|p| !p.description.contains("realized")));
+    }
+
+    #[test]
+    fn test_options_structure_dimension_needs_features() {
+        let prices = synthetic_prices(300);
+        let config = HypothesisConfig {
+            forward_horizons: vec![5],
+            significance: 0.10,
+            dedup_threshold: 0.5,
+        };
+        let dims = [HypothesisDimension::OptionsStructure];
+        let (total_trials, ..) =
+            generate_hypotheses(&prices, &config, &dims, None, None, None, &HashMap::new());
+        assert_eq!(total_trials, 0);
+    }
 }
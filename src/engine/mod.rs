@@ -5,16 +5,22 @@
 
 pub mod adjustments;
 pub mod bayesian;
+pub mod cpcv;
+pub mod cscv;
+pub mod equity;
 pub mod filters;
+pub mod greeks;
 pub mod hmm;
 pub mod hypothesis;
 pub mod metrics;
 pub mod multiple_comparisons;
 pub mod ohlcv;
+pub mod pareto;
 pub mod permutation;
 pub mod positions;
 pub mod price_table;
 pub mod pricing;
+pub mod reality_check;
 pub mod sim_types;
 pub mod sweep;
 pub mod types;
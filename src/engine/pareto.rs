@@ -0,0 +1,754 @@
+//! Multi-objective sweep optimization with Pareto-front output.
+//!
+//! Instead of ranking combos on one metric, combos are sorted into Pareto
+//! fronts (NSGA-II fast non-dominated sort) across several objectives, e.g.
+//! maximize CAGR while minimizing max drawdown and tail `CVaR`. Within a front,
+//! crowding distance measures how isolated a combo is, and the knee point —
+//! the front member closest to the ideal point once each objective is
+//! normalized to the front's range — is recommended as `best_result`.
+//!
+//! Grids no larger than `max_evaluations` are run exhaustively via the grid
+//! sweep. Larger grids are searched with an NSGA-II genetic algorithm over
+//! grid indices, spending at most `max_evaluations` backtests.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+
+use crate::engine::bayesian::{cache_key, evaluate};
+use crate::engine::sweep::{compute_sensitivity, run_grid_sweep_with_results, GridSweepConfig};
+use crate::scripting::engine::{
+    CancelCallback, DataLoader, PrecomputedOptionsData, ScriptBacktestResult,
+};
+use crate::tools::response_types::sweep::{
+    ObjectiveDirection, ParetoObjective, ParetoPoint, ParetoSummary, SweepResponse, SweepResult,
+};
+
+/// Metrics that can be used as Pareto objectives.
+pub const PARETO_METRICS: [&str; 9] = [
+    "sharpe",
+    "sortino",
+    "calmar",
+    "profit_factor",
+    "cagr",
+    "pnl",
+    "win_rate",
+    "max_drawdown",
+    "cvar_95",
+];
+
+/// NSGA-II seed when the request does not set one, matching the permutation
+/// and reality-check gates.
+pub const DEFAULT_PARETO_SEED: u64 = 42;
+
+/// Input configuration for a multi-objective sweep.
+pub struct ParetoConfig {
+    pub script_source: String,
+    pub base_params: HashMap<String, Value>,
+    pub param_grid: HashMap<String, Vec<Value>>,
+    pub objectives: Vec<ParetoObjective>,
+    /// Backtest budget; grids larger than this are searched with NSGA-II.
+    pub max_evaluations: usize,
+    /// Single objective used for dimension sensitivity.
+    pub objective: String,
+    /// Seed for the NSGA-II search, so a rerun evaluates the same combos.
+    pub seed: u64,
+}
+
+/// Parse objective specs such as `"cagr"`, `"max_drawdown"` or
+/// `"min:cvar_95"`. Without a `max:`/`min:` prefix, `max_drawdown` and
+/// `cvar_95` are minimized and every other metric is maximized.
+pub fn parse_objectives(specs: &[String]) -> Result<Vec<ParetoObjective>> {
+    let mut objectives: Vec<ParetoObjective> = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let spec = spec.trim();
+        let (direction, metric) = match spec.split_once(':') {
+            Some(("max", metric)) => (Some(ObjectiveDirection::Maximize), metric),
+            Some(("min", metric)) => (Some(ObjectiveDirection::Minimize), metric),
+            Some((prefix, _)) => {
+                bail!("Invalid objective direction '{prefix}' in '{spec}', expected 'max' or 'min'")
+            }
+            None => (None, spec),
+        };
+        if !PARETO_METRICS.contains(&metric) {
+            bail!(
+                "Unknown objective '{metric}', expected one of: {}",
+                PARETO_METRICS.join(", ")
+            );
+        }
+        if objectives.iter().any(|o| o.metric == metric) {
+            bail!("Objective '{metric}' listed more than once");
+        }
+        let direction = direction.unwrap_or(match metric {
+            "max_drawdown" | "cvar_95" => ObjectiveDirection::Minimize,
+            _ => ObjectiveDirection::Maximize,
+        });
+        objectives.push(ParetoObjective {
+            metric: metric.to_string(),
+            direction,
+        });
+    }
+    if objectives.len() < 2 {
+        bail!(
+            "Pareto mode needs at least two objectives, got {}",
+            objectives.len()
+        );
+    }
+    Ok(objectives)
+}
+
+/// Raw value of a Pareto metric on a sweep result.
+pub fn metric_value(result: &SweepResult, metric: &str) -> f64 {
+    match metric {
+        "sortino" => result.sortino,
+        "calmar" => result.calmar,
+        "profit_factor" => result.profit_factor,
+        "cagr" => result.cagr,
+        "pnl" => result.pnl,
+        "win_rate" => result.win_rate,
+        "max_drawdown" => result.max_drawdown,
+        "cvar_95" => result.cvar_95,
+        _ => result.sharpe,
+    }
+}
+
+/// Objective vector oriented so that larger is always better. Non-finite
+/// values become `-inf` so they never dominate anything.
+fn oriented(result: &SweepResult, objectives: &[ParetoObjective]) -> Vec<f64> {
+    objectives
+        .iter()
+        .map(|o| {
+            let v = metric_value(result, &o.metric);
+            if !v.is_finite() {
+                f64::NEG_INFINITY
+            } else if o.direction == ObjectiveDirection::Minimize {
+                -v
+            } else {
+                v
+            }
+        })
+        .collect()
+}
+
+/// Whether `a` Pareto-dominates `b` (all objectives maximized).
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+/// Fast non-dominated sort: indices of `points` grouped into fronts, best
+/// front first.
+pub fn non_dominated_sort(points: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = points.len();
+    let mut dominated_by: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count = vec![0usize; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if dominates(&points[i], &points[j]) {
+                dominated_by[i].push(j);
+                domination_count[j] += 1;
+            } else if dominates(&points[j], &points[i]) {
+                dominated_by[j].push(i);
+                domination_count[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut current: Vec<usize> = (0..n).filter(|&i| domination_count[i] == 0).collect();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &i in &current {
+            for &j in &dominated_by[i] {
+                domination_count[j] -= 1;
+                if domination_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(current);
+        current = next;
+    }
+    fronts
+}
+
+/// Crowding distance of each member of `front` (aligned with `front`).
+/// Boundary members of any objective get `f64::INFINITY`.
+#[allow(clippy::needless_range_loop)]
+pub fn crowding_distance(points: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let n = front.len();
+    let mut distance = vec![0.0; n];
+    if n <= 2 {
+        return vec![f64::INFINITY; n];
+    }
+    let dims = points[front[0]].len();
+    let mut order: Vec<usize> = (0..n).collect();
+    for d in 0..dims {
+        order.sort_by(|&a, &b| points[front[a]][d].total_cmp(&points[front[b]][d]));
+        let lo = points[front[order[0]]][d];
+        let hi = points[front[order[n - 1]]][d];
+        distance[order[0]] = f64::INFINITY;
+        distance[order[n - 1]] = f64::INFINITY;
+        let span = hi - lo;
+        if !span.is_finite() || span <= 0.0 {
+            continue;
+        }
+        for k in 1..n - 1 {
+            let gap = points[front[order[k + 1]]][d] - points[front[order[k - 1]]][d];
+            distance[order[k]] += gap / span;
+        }
+    }
+    distance
+}
+
+/// Index (into `points`) of the knee of `front`: the member with the smallest
+/// Euclidean distance to the ideal point after min-max normalizing each
+/// objective over the front.
+pub fn knee_point(points: &[Vec<f64>], front: &[usize]) -> Option<usize> {
+    let first = *front.first()?;
+    let dims = points[first].len();
+    let bounds: Vec<(f64, f64)> = (0..dims)
+        .map(|d| {
+            front
+                .iter()
+                .map(|&i| points[i][d])
+                .filter(|v| v.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                    (lo.min(v), hi.max(v))
+                })
+        })
+        .collect();
+    let shortfall = |i: usize| -> f64 {
+        bounds
+            .iter()
+            .enumerate()
+            .map(|(d, &(lo, hi))| {
+                let v = points[i][d];
+                let gap = if !v.is_finite() {
+                    1.0
+                } else if hi > lo {
+                    (hi - v) / (hi - lo)
+                } else {
+                    0.0
+                };
+                gap * gap
+            })
+            .sum()
+    };
+    front
+        .iter()
+        .copied()
+        .min_by(|&a, &b| shortfall(a).total_cmp(&shortfall(b)))
+}
+
+/// Rank paired results by Pareto front, knee point first, then each front by
+/// descending crowding distance. Sets `rank` and `pareto_front` on every
+/// result and builds the front summary.
+pub fn rank_pareto(
+    paired: Vec<(SweepResult, ScriptBacktestResult)>,
+    objectives: &[ParetoObjective],
+    search: &str,
+    generations: usize,
+) -> (Vec<SweepResult>, Vec<ScriptBacktestResult>, ParetoSummary) {
+    let points: Vec<Vec<f64>> = paired
+        .iter()
+        .map(|(r, _)| oriented(r, objectives))
+        .collect();
+    let fronts = non_dominated_sort(&points);
+    let knee = fronts.first().and_then(|f| knee_point(&points, f));
+
+    let mut order: Vec<(usize, usize, f64)> = Vec::with_capacity(paired.len());
+    for (front_idx, front) in fronts.iter().enumerate() {
+        let distances = crowding_distance(&points, front);
+        let mut members: Vec<(usize, usize, f64)> = front
+            .iter()
+            .zip(distances)
+            .map(|(&i, d)| (i, front_idx + 1, d))
+            .collect();
+        members.sort_by(|a, b| {
+            let a_knee = Some(a.0) == knee;
+            let b_knee = Some(b.0) == knee;
+            b_knee.cmp(&a_knee).then(b.2.total_cmp(&a.2))
+        });
+        order.extend(members);
+    }
+
+    let mut slots: Vec<Option<(SweepResult, ScriptBacktestResult)>> =
+        paired.into_iter().map(Some).collect();
+    let mut results = Vec::with_capacity(order.len());
+    let mut full_results = Vec::with_capacity(order.len());
+    let mut front_points = Vec::new();
+    for (position, &(i, front, distance)) in order.iter().enumerate() {
+        let (mut result, full) = slots[i].take().expect("each index ranked once");
+        result.rank = position + 1;
+        result.pareto_front = Some(front);
+        if front == 1 {
+            front_points.push(ParetoPoint {
+                rank: result.rank,
+                params: result.params.clone(),
+                objectives: objectives
+                    .iter()
+                    .map(|o| (o.metric.clone(), metric_value(&result, &o.metric)))
+                    .collect(),
+                crowding_distance: distance.is_finite().then_some(distance),
+            });
+        }
+        results.push(result);
+        full_results.push(full);
+    }
+
+    let summary = ParetoSummary {
+        objectives: objectives.to_vec(),
+        search: search.to_string(),
+        generations,
+        knee: knee.and_then(|_| front_points.first().cloned()),
+        front: front_points,
+    };
+    (results, full_results, summary)
+}
+
+/// Run a multi-objective sweep: exhaustive when the grid fits in
+/// `max_evaluations`, NSGA-II otherwise.
+pub async fn run_pareto_sweep_with_results(
+    config: &ParetoConfig,
+    data_loader: Arc<dyn DataLoader>,
+    is_cancelled: &CancelCallback,
+    on_progress: impl Fn(usize, usize),
+    on_result: impl Fn(&SweepResult),
+) -> Result<SweepResponse> {
+    let start = Instant::now();
+    let total: usize = config.param_grid.values().map(Vec::len).product();
+
+    let (paired, failed, search, generations) = if total <= config.max_evaluations {
+        let grid = GridSweepConfig {
+            script_source: config.script_source.clone(),
+            base_params: config.base_params.clone(),
+            param_grid: config.param_grid.clone(),
+            objective: config.objective.clone(),
+        };
+        let response =
+            run_grid_sweep_with_results(&grid, data_loader, is_cancelled, on_progress, on_result)
+                .await?;
+        let paired = response
+            .ranked_results
+            .into_iter()
+            .zip(response.full_results)
+            .collect();
+        (paired, response.combinations_failed, "grid", 0)
+    } else {
+        let mut search = Nsga2::new(config);
+        search
+            .run(data_loader.as_ref(), is_cancelled, &on_progress, &on_result)
+            .await;
+        let generations = search.generations;
+        let failed = search.failed;
+        (search.into_paired(), failed, "nsga2", generations)
+    };
+
+    let (results, full_results, pareto) =
+        rank_pareto(paired, &config.objectives, search, generations);
+    let sensitivity = compute_sensitivity(&results, &config.param_grid, &config.objective);
+
+    Ok(SweepResponse {
+        mode: "pareto".to_string(),
+        objective: config.objective.clone(),
+        combinations_total: total,
+        combinations_run: results.len(),
+        combinations_failed: failed,
+        best_result: results.first().cloned(),
+        ranked_results: results,
+        dimension_sensitivity: sensitivity,
+        convergence_trace: None,
+        execution_time_ms: start.elapsed().as_millis() as u64,
+        multiple_comparisons: None,
+        reality_check: None,
+        pareto: Some(pareto),
+        full_results,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// NSGA-II search over grid indices
+// ---------------------------------------------------------------------------
+
+/// Genome: one index per grid dimension (dimensions sorted by name).
+type Genome = Vec<usize>;
+
+/// Max attempts per wanted offspring before the space counts as exhausted.
+const ATTEMPTS_PER_CHILD: usize = 20;
+
+struct Nsga2<'a> {
+    config: &'a ParetoConfig,
+    dims: Vec<(String, Vec<Value>)>,
+    pop_size: usize,
+    budget: usize,
+    /// Every evaluated genome, keyed by param cache key (`None` = failed).
+    archive: HashMap<String, Option<usize>>,
+    results: Vec<(SweepResult, ScriptBacktestResult)>,
+    genomes: Vec<Genome>,
+    precomputed: Option<PrecomputedOptionsData>,
+    failed: usize,
+    generations: usize,
+}
+
+impl<'a> Nsga2<'a> {
+    fn new(config: &'a ParetoConfig) -> Self {
+        let mut dims: Vec<(String, Vec<Value>)> = config
+            .param_grid
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        dims.sort_by(|a, b| a.0.cmp(&b.0));
+        Self {
+            config,
+            dims,
+            pop_size: (config.max_evaluations / 4).clamp(4, 32),
+            budget: config.max_evaluations,
+            archive: HashMap::new(),
+            results: Vec::new(),
+            genomes: Vec::new(),
+            precomputed: None,
+            failed: 0,
+            generations: 0,
+        }
+    }
+
+    fn decode(&self, genome: &Genome) -> HashMap<String, Value> {
+        self.dims
+            .iter()
+            .zip(genome)
+            .map(|((name, values), &i)| (name.clone(), values[i].clone()))
+            .collect()
+    }
+
+    fn random_genome(&self, rng: &mut impl Rng) -> Genome {
+        self.dims
+            .iter()
+            .map(|(_, values)| rng.random_range(0..values.len()))
+            .collect()
+    }
+
+    fn evaluations(&self) -> usize {
+        self.archive.len()
+    }
+
+    /// Evaluate a genome unless already archived; returns the result index of
+    /// a fresh successful evaluation.
+    async fn evaluate(
+        &mut self,
+        genome: Genome,
+        data_loader: &dyn DataLoader,
+        is_cancelled: &CancelCallback,
+        on_result: &impl Fn(&SweepResult),
+    ) -> Option<usize> {
+        let params = self.decode(&genome);
+        let key = cache_key(&params);
+        if self.archive.contains_key(&key) {
+            return None;
+        }
+        let outcome = evaluate(
+            &self.config.script_source,
+            &self.config.base_params,
+            params,
+            data_loader,
+            self.precomputed.as_ref(),
+            Some(is_cancelled),
+        )
+        .await;
+        let Ok((result, bt)) = outcome else {
+            self.failed += 1;
+            self.archive.insert(key, None);
+            return None;
+        };
+        if self.precomputed.is_none() {
+            self.precomputed.clone_from(&bt.precomputed_options);
+        }
+        on_result(&result);
+        let idx = self.results.len();
+        self.results.push((result, bt));
+        self.genomes.push(genome);
+        self.archive.insert(key, Some(idx));
+        Some(idx)
+    }
+
+    async fn run(
+        &mut self,
+        data_loader: &dyn DataLoader,
+        is_cancelled: &CancelCallback,
+        on_progress: &impl Fn(usize, usize),
+        on_result: &impl Fn(&SweepResult),
+    ) {
+        let mut rng = StdRng::seed_from_u64(self.config.seed);
+        on_progress(0, self.budget);
+
+        // Initial population: random unique genomes.
+        let mut population: Vec<usize> = Vec::new();
+        let mut attempts = 0;
+        while population.len() < self.pop_size
+            && self.evaluations() < self.budget
+            && attempts < self.pop_size * ATTEMPTS_PER_CHILD
+            && !is_cancelled()
+        {
+            attempts += 1;
+            let genome = self.random_genome(&mut rng);
+            if let Some(idx) = self
+                .evaluate(genome, data_loader, is_cancelled, on_result)
+                .await
+            {
+                population.push(idx);
+            }
+            on_progress(self.evaluations(), self.budget);
+        }
+
+        while self.evaluations() < self.budget && !is_cancelled() && population.len() >= 2 {
+            let points: Vec<Vec<f64>> = population
+                .iter()
+                .map(|&i| oriented(&self.results[i].0, &self.config.objectives))
+                .collect();
+            let (front_rank, crowding) = rank_and_crowding(&points);
+
+            let mut offspring: Vec<usize> = Vec::new();
+            let mut attempts = 0;
+            while offspring.len() < self.pop_size
+                && self.evaluations() < self.budget
+                && attempts < self.pop_size * ATTEMPTS_PER_CHILD
+                && !is_cancelled()
+            {
+                attempts += 1;
+                let a = tournament(&front_rank, &crowding, &mut rng);
+                let b = tournament(&front_rank, &crowding, &mut rng);
+                let child = self.mutate(
+                    crossover(
+                        &self.genomes[population[a]],
+                        &self.genomes[population[b]],
+                        &mut rng,
+                    ),
+                    &mut rng,
+                );
+                if let Some(idx) = self
+                    .evaluate(child, data_loader, is_cancelled, on_result)
+                    .await
+                {
+                    offspring.push(idx);
+                }
+                on_progress(self.evaluations(), self.budget);
+            }
+            if offspring.is_empty() {
+                // No unseen genome reachable: the space is exhausted.
+                break;
+            }
+            self.generations += 1;
+
+            population.extend(offspring);
+            let points: Vec<Vec<f64>> = population
+                .iter()
+                .map(|&i| oriented(&self.results[i].0, &self.config.objectives))
+                .collect();
+            population = select_survivors(&points, self.pop_size)
+                .into_iter()
+                .map(|k| population[k])
+                .collect();
+        }
+        on_progress(self.evaluations(), self.budget);
+    }
+
+    /// Per gene with probability 1/dims: step to a neighbouring grid value
+    /// (80%) or jump to a random one.
+    fn mutate(&self, mut genome: Genome, rng: &mut impl Rng) -> Genome {
+        let rate = 1.0 / self.dims.len().max(1) as f64;
+        for (gene, (_, values)) in genome.iter_mut().zip(&self.dims) {
+            let n = values.len();
+            if n < 2 || rng.random::<f64>() >= rate {
+                continue;
+            }
+            *gene = if rng.random::<f64>() < 0.8 {
+                if *gene == 0 || (*gene + 1 < n && rng.random::<bool>()) {
+                    *gene + 1
+                } else {
+                    *gene - 1
+                }
+            } else {
+                rng.random_range(0..n)
+            };
+        }
+        genome
+    }
+
+    /// All evaluated combos, deduplicated by construction.
+    fn into_paired(self) -> Vec<(SweepResult, ScriptBacktestResult)> {
+        self.results
+    }
+}
+
+/// Front rank (0 = best) and crowding distance for every point.
+fn rank_and_crowding(points: &[Vec<f64>]) -> (Vec<usize>, Vec<f64>) {
+    let mut rank = vec![0; points.len()];
+    let mut crowding = vec![0.0; points.len()];
+    for (r, front) in non_dominated_sort(points).iter().enumerate() {
+        for (&i, d) in front.iter().zip(crowding_distance(points, front)) {
+            rank[i] = r;
+            crowding[i] = d;
+        }
+    }
+    (rank, crowding)
+}
+
+/// Binary tournament on (front rank, crowding distance).
+fn tournament(rank: &[usize], crowding: &[f64], rng: &mut impl Rng) -> usize {
+    let a = rng.random_range(0..rank.len());
+    let b = rng.random_range(0..rank.len());
+    let a_wins = match rank[a].cmp(&rank[b]) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Greater => false,
+        std::cmp::Ordering::Equal => crowding[a] >= crowding[b],
+    };
+    if a_wins {
+        a
+    } else {
+        b
+    }
+}
+
+/// Uniform crossover.
+fn crossover(a: &Genome, b: &Genome, rng: &mut impl Rng) -> Genome {
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| if rng.random::<bool>() { x } else { y })
+        .collect()
+}
+
+/// Indices of the `size` points kept by NSGA-II elitist selection: whole
+/// fronts in order, with the last partial front cut by crowding distance.
+fn select_survivors(points: &[Vec<f64>], size: usize) -> Vec<usize> {
+    let mut survivors = Vec::with_capacity(size);
+    for front in non_dominated_sort(points) {
+        if survivors.len() + front.len() <= size {
+            survivors.extend(front);
+            continue;
+        }
+        let distances = crowding_distance(points, &front);
+        let mut members: Vec<(usize, f64)> = front.into_iter().zip(distances).collect();
+        members.sort_by(|a, b| b.1.total_cmp(&a.1));
+        survivors.extend(
+            members
+                .into_iter()
+                .map(|(i, _)| i)
+                .take(size - survivors.len()),
+        );
+        break;
+    }
+    survivors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parse_objectives_infers_directions() {
+        let objectives =
+            parse_objectives(&specs(&["cagr", "max_drawdown", "max:cvar_95"])).unwrap();
+        assert_eq!(objectives[0].direction, ObjectiveDirection::Maximize);
+        assert_eq!(objectives[1].direction, ObjectiveDirection::Minimize);
+        assert_eq!(objectives[2].direction, ObjectiveDirection::Maximize);
+    }
+
+    #[test]
+    fn parse_objectives_rejects_bad_specs() {
+        assert!(parse_objectives(&specs(&["cagr"])).is_err());
+        assert!(parse_objectives(&specs(&["cagr", "alpha"])).is_err());
+        assert!(parse_objectives(&specs(&["cagr", "up:sharpe"])).is_err());
+        assert!(parse_objectives(&specs(&["cagr", "min:cagr"])).is_err());
+    }
+
+    #[test]
+    fn non_dominated_sort_layers_fronts() {
+        let points = vec![
+            vec![1.0, 5.0], // front 1
+            vec![5.0, 1.0], // front 1
+            vec![3.0, 3.0], // front 1
+            vec![2.0, 2.0], // dominated by [3,3]
+            vec![1.0, 1.0], // dominated by everything
+        ];
+        let fronts = non_dominated_sort(&points);
+        assert_eq!(fronts, vec![vec![0, 1, 2], vec![3], vec![4]]);
+    }
+
+    #[test]
+    fn crowding_distance_marks_boundaries_infinite() {
+        let points = vec![
+            vec![0.0, 4.0],
+            vec![1.0, 3.0],
+            vec![3.0, 1.0],
+            vec![4.0, 0.0],
+        ];
+        let front = vec![0, 1, 2, 3];
+        let d = crowding_distance(&points, &front);
+        assert!(d[0].is_infinite() && d[3].is_infinite());
+        // Interior: (3-0)/4 + (4-1)/4 on each axis for both.
+        assert!((d[1] - 1.5).abs() < 1e-12);
+        assert!((d[2] - 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn knee_point_prefers_balanced_trade_off() {
+        let points = vec![vec![0.0, 10.0], vec![8.0, 8.0], vec![10.0, 0.0]];
+        assert_eq!(knee_point(&points, &[0, 1, 2]), Some(1));
+        assert_eq!(knee_point(&points, &[]), None);
+    }
+
+    #[test]
+    fn select_survivors_cuts_last_front_by_crowding() {
+        let points = vec![
+            vec![0.0, 4.0],
+            vec![1.0, 3.0],
+            vec![1.9, 2.1],
+            vec![2.0, 2.0],
+            vec![4.0, 0.0],
+            vec![0.0, 0.0],
+        ];
+        let survivors = select_survivors(&points, 4);
+        assert_eq!(survivors.len(), 4);
+        assert!(survivors.contains(&0) && survivors.contains(&4));
+        assert!(!survivors.contains(&5));
+    }
+
+    #[test]
+    fn genetic_operators_stay_on_grid() {
+        let config = ParetoConfig {
+            script_source: String::new(),
+            base_params: HashMap::new(),
+            param_grid: HashMap::from([
+                (
+                    "A".to_string(),
+                    (0..5).map(|i| serde_json::json!(i)).collect(),
+                ),
+                ("B".to_string(), vec![serde_json::json!(1)]),
+            ]),
+            objectives: Vec::new(),
+            max_evaluations: 10,
+            objective: "sharpe".to_string(),
+            seed: DEFAULT_PARETO_SEED,
+        };
+        let search = Nsga2::new(&config);
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let a = search.random_genome(&mut rng);
+            let b = search.random_genome(&mut rng);
+            let child = search.mutate(crossover(&a, &b, &mut rng), &mut rng);
+            assert!(child[0] < 5);
+            assert_eq!(child[1], 0);
+        }
+        let decoded = search.decode(&vec![3, 0]);
+        assert_eq!(decoded["A"], serde_json::json!(3));
+    }
+}
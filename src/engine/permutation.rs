@@ -563,6 +563,8 @@ mod tests {
             convergence_trace: None,
             execution_time_ms: 0,
             multiple_comparisons: None,
+            reality_check: None,
+            pareto: None,
             full_results: Vec::new(),
         }
     }
@@ -588,6 +590,8 @@ mod tests {
             profit_factor: 2.0,
             cagr: 0.1,
             calmar: 1.0,
+            cvar_95: 0.0,
+            pareto_front: None,
             p_value: None,
             significant: None,
         }]);
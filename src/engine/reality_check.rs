@@ -0,0 +1,341 @@
+//! White's Reality Check and Hansen's Superior Predictive Ability (SPA) test.
+//!
+//! Both test the family-wise null "no combo in the sweep beats the benchmark"
+//! on the per-period excess returns `d[k][t]` of every combo `k`. The joint
+//! null distribution is built with the Politis–Romano stationary bootstrap,
+//! resampling the same time indices for every combo so the correlation
+//! between combos is preserved — unlike per-combo permutation p-values, the
+//! result does not degrade as thousands of near-identical combos are added.
+//!
+//! The benchmark is cash, so excess returns are the combos' own period
+//! returns. The SPA statistic is studentized and recentred with Hansen's
+//! `-sqrt(2 log log n)` threshold, which keeps clearly inferior combos from
+//! inflating the p-value the way they do in the Reality Check.
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::P_VALUE_THRESHOLD;
+use crate::engine::cscv::aligned_returns;
+use crate::engine::types::EquityPoint;
+use crate::tools::response_types::sweep::SweepResponse;
+
+/// Maximum bootstrap replications to bound the `B × K × T` cost.
+const MAX_BOOTSTRAP: usize = 10_000;
+
+/// Minimum aligned return observations for a meaningful bootstrap.
+const MIN_OBSERVATIONS: usize = 20;
+
+/// Family-wise test of whether the best sweep combo beats the benchmark.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RealityCheckResult {
+    /// Benchmark the combos are measured against (`"cash"`).
+    pub benchmark: String,
+    /// Number of combos in the family.
+    pub n_strategies: usize,
+    /// Number of aligned return observations per combo.
+    pub n_observations: usize,
+    /// Number of stationary-bootstrap replications.
+    pub n_bootstrap: usize,
+    /// Expected block length of the stationary bootstrap.
+    pub mean_block_length: f64,
+    /// Rank (in `ranked_results`) of the combo with the highest mean excess return.
+    pub best_rank: usize,
+    /// Mean per-period excess return of that combo.
+    pub best_mean_excess: f64,
+    /// White's Reality Check p-value.
+    pub reality_check_p_value: f64,
+    /// Hansen's consistent SPA p-value (the one used for the decision).
+    pub spa_p_value: f64,
+    /// SPA p-value with lower recentring (liberal bound).
+    pub spa_lower_p_value: f64,
+    /// SPA p-value with upper recentring (conservative bound).
+    pub spa_upper_p_value: f64,
+    /// Significance level.
+    pub alpha: f64,
+    /// Whether the best combo beats the benchmark (`spa_p_value < alpha`).
+    pub significant: bool,
+}
+
+/// Apply the Reality Check / SPA gate to a completed sweep response.
+///
+/// Builds per-combo returns from the equity curves in `full_results` (parallel
+/// to `ranked_results`) and attaches the family-wise result as
+/// `reality_check`. Returns the response unchanged when `n_bootstrap == 0`,
+/// fewer than two combos have equity curves, or the aligned history is
+/// shorter than `MIN_OBSERVATIONS`.
+pub fn apply_reality_check_gate(
+    mut response: SweepResponse,
+    n_bootstrap: usize,
+    seed: Option<u64>,
+) -> SweepResponse {
+    if n_bootstrap == 0 || response.full_results.is_empty() {
+        return response;
+    }
+
+    let (indices, curves): (Vec<usize>, Vec<&[EquityPoint]>) = response
+        .full_results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (i, r.result.equity_curve.as_slice()))
+        .filter(|(_, c)| c.len() > 1)
+        .unzip();
+    let returns = aligned_returns(&curves);
+
+    if let Some(mut result) = reality_check(&returns, n_bootstrap, seed) {
+        // Map the winner back from the filtered set to its ranked position.
+        let combo = indices[result.best_rank];
+        result.best_rank = response
+            .ranked_results
+            .get(combo)
+            .map_or(combo + 1, |r| r.rank);
+        response.reality_check = Some(result);
+    }
+    response
+}
+
+/// Run the Reality Check and SPA tests on aligned excess-return series.
+///
+/// `best_rank` in the result is the index of the best series in `returns`.
+/// Returns `None` for fewer than two series or fewer than `MIN_OBSERVATIONS`
+/// observations.
+pub fn reality_check(
+    returns: &[Vec<f64>],
+    n_bootstrap: usize,
+    seed: Option<u64>,
+) -> Option<RealityCheckResult> {
+    let k = returns.len();
+    let n = returns.iter().map(Vec::len).min().unwrap_or(0);
+    if k < 2 || n < MIN_OBSERVATIONS || n_bootstrap == 0 {
+        return None;
+    }
+    let n_bootstrap = n_bootstrap.min(MAX_BOOTSTRAP);
+    let nf = n as f64;
+    let sqrt_n = nf.sqrt();
+
+    let block_length = nf.cbrt().max(1.0);
+    let q = 1.0 / block_length;
+
+    let means: Vec<f64> = returns
+        .iter()
+        .map(|r| r[..n].iter().sum::<f64>() / nf)
+        .collect();
+    let omegas: Vec<f64> = returns
+        .iter()
+        .zip(&means)
+        .map(|(r, &m)| stationary_bootstrap_std(&r[..n], m, q))
+        .collect();
+
+    let (best, best_mean) =
+        means
+            .iter()
+            .copied()
+            .enumerate()
+            .fold(
+                (0, f64::NEG_INFINITY),
+                |acc, (i, m)| {
+                    if m > acc.1 {
+                        (i, m)
+                    } else {
+                        acc
+                    }
+                },
+            );
+
+    // Observed statistics.
+    let rc_stat = sqrt_n * best_mean;
+    let spa_stat = means
+        .iter()
+        .zip(&omegas)
+        .map(|(&m, &w)| sqrt_n * m / w)
+        .fold(0.0_f64, f64::max);
+
+    // Shift added to the centred bootstrap mean for the lower and consistent
+    // SPA variants (`d̄ - g(d̄)`); the upper variant adds nothing. Clearly
+    // inferior combos are pushed down so they cannot set the bootstrap max.
+    let threshold = -(2.0 * nf.ln().ln().max(0.0)).sqrt();
+    let shift_lower: Vec<f64> = means.iter().map(|&m| m.min(0.0)).collect();
+    let shift_consistent: Vec<f64> = means
+        .iter()
+        .zip(&omegas)
+        .map(|(&m, &w)| if sqrt_n * m / w < threshold { m } else { 0.0 })
+        .collect();
+
+    // Each replicate returns hit flags for (RC, SPA-lower, SPA-consistent, SPA-upper).
+    let hits = (0..n_bootstrap)
+        .into_par_iter()
+        .map(|b| {
+            let mut rng = match seed {
+                Some(s) => StdRng::seed_from_u64(s.wrapping_add(b as u64)),
+                None => StdRng::from_os_rng(),
+            };
+            let idx = stationary_indices(&mut rng, n, q);
+
+            let mut rc_max = f64::NEG_INFINITY;
+            let mut spa_max = [0.0_f64; 3];
+            for s in 0..k {
+                let boot_mean = idx.iter().map(|&t| returns[s][t]).sum::<f64>() / nf;
+                let centred = boot_mean - means[s];
+                rc_max = rc_max.max(sqrt_n * centred);
+                for (v, shift) in [shift_lower[s], shift_consistent[s], 0.0]
+                    .iter()
+                    .enumerate()
+                {
+                    spa_max[v] = spa_max[v].max(sqrt_n * (centred + shift) / omegas[s]);
+                }
+            }
+            [
+                usize::from(rc_max >= rc_stat),
+                usize::from(spa_max[0] >= spa_stat),
+                usize::from(spa_max[1] >= spa_stat),
+                usize::from(spa_max[2] >= spa_stat),
+            ]
+        })
+        .reduce(
+            || [0; 4],
+            |a, b| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]],
+        );
+
+    // Conservative estimator, as in the permutation gate.
+    let p = |h: usize| (h as f64 + 1.0) / (n_bootstrap as f64 + 1.0);
+    let spa_p_value = p(hits[2]);
+
+    Some(RealityCheckResult {
+        benchmark: "cash".to_string(),
+        n_strategies: k,
+        n_observations: n,
+        n_bootstrap,
+        mean_block_length: block_length,
+        best_rank: best,
+        best_mean_excess: best_mean,
+        reality_check_p_value: p(hits[0]),
+        spa_p_value,
+        spa_lower_p_value: p(hits[1]),
+        spa_upper_p_value: p(hits[3]),
+        alpha: P_VALUE_THRESHOLD,
+        significant: spa_p_value < P_VALUE_THRESHOLD,
+    })
+}
+
+/// Draw one stationary-bootstrap index path of length `n`.
+///
+/// Each step continues the current block with probability `1 - q` and jumps
+/// to a uniformly random start with probability `q`, wrapping at the end.
+fn stationary_indices(rng: &mut StdRng, n: usize, q: f64) -> Vec<usize> {
+    let mut idx = Vec::with_capacity(n);
+    let mut t = rng.random_range(0..n);
+    for _ in 0..n {
+        idx.push(t);
+        t = if rng.random::<f64>() < q {
+            rng.random_range(0..n)
+        } else {
+            (t + 1) % n
+        };
+    }
+    idx
+}
+
+/// Standard deviation of `sqrt(n)·mean` under the stationary bootstrap.
+///
+/// Uses the Politis–Romano closed form
+/// `γ₀ + 2·Σ κᵢ·γᵢ` with `κᵢ = (1 - i/n)(1 - q)^i + (i/n)(1 - q)^(n-i)`,
+/// truncated once the weights become negligible. Floors at a tiny positive
+/// value so flat series do not divide by zero.
+fn stationary_bootstrap_std(series: &[f64], mean: f64, q: f64) -> f64 {
+    let n = series.len();
+    let nf = n as f64;
+    let autocov = |lag: usize| -> f64 {
+        series[lag..]
+            .iter()
+            .zip(series)
+            .map(|(a, b)| (a - mean) * (b - mean))
+            .sum::<f64>()
+            / nf
+    };
+
+    let mut variance = autocov(0);
+    for lag in 1..n {
+        let i = lag as f64;
+        let kappa = (1.0 - i / nf) * (1.0 - q).powf(i) + (i / nf) * (1.0 - q).powf(nf - i);
+        if kappa < 1e-10 {
+            break;
+        }
+        variance += 2.0 * kappa * autocov(lag);
+    }
+    variance.max(1e-24).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(seed: u64, n: usize, drift: f64) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| drift + rng.random_range(-0.01..0.01))
+            .collect()
+    }
+
+    #[test]
+    fn noise_family_is_not_significant() {
+        let returns: Vec<Vec<f64>> = (0..30).map(|s| noise(s, 500, 0.0)).collect();
+        let result = reality_check(&returns, 500, Some(7)).unwrap();
+        assert!(
+            result.reality_check_p_value > 0.05,
+            "RC p = {}",
+            result.reality_check_p_value
+        );
+        assert!(result.spa_p_value > 0.05, "SPA p = {}", result.spa_p_value);
+        assert!(!result.significant);
+    }
+
+    #[test]
+    fn real_edge_is_significant() {
+        let mut returns: Vec<Vec<f64>> = (0..10).map(|s| noise(s, 500, 0.0)).collect();
+        returns[4] = noise(42, 500, 0.003);
+        let result = reality_check(&returns, 500, Some(7)).unwrap();
+        assert_eq!(result.best_rank, 4);
+        assert!(result.spa_p_value < 0.01, "SPA p = {}", result.spa_p_value);
+        assert!(result.significant);
+    }
+
+    #[test]
+    fn spa_is_not_diluted_by_poor_combos() {
+        // One modest edge among many clearly losing combos: recentring drops
+        // the losers from the SPA null, so it is at least as powerful as RC.
+        let mut returns: Vec<Vec<f64>> = (0..40).map(|s| noise(s, 400, -0.004)).collect();
+        returns[0] = noise(99, 400, 0.0012);
+        let result = reality_check(&returns, 1000, Some(3)).unwrap();
+        assert!(result.spa_p_value <= result.reality_check_p_value + 1e-12);
+        assert!(result.spa_lower_p_value <= result.spa_p_value + 1e-12);
+        assert!(result.spa_p_value <= result.spa_upper_p_value + 1e-12);
+    }
+
+    #[test]
+    fn seeded_runs_are_deterministic() {
+        let returns: Vec<Vec<f64>> = (0..5).map(|s| noise(s, 100, 0.0005)).collect();
+        let a = reality_check(&returns, 200, Some(11)).unwrap();
+        let b = reality_check(&returns, 200, Some(11)).unwrap();
+        assert!((a.spa_p_value - b.spa_p_value).abs() < 1e-15);
+        assert!((a.reality_check_p_value - b.reality_check_p_value).abs() < 1e-15);
+    }
+
+    #[test]
+    fn rejects_insufficient_input() {
+        assert!(reality_check(&[noise(1, 100, 0.0)], 100, Some(1)).is_none());
+        let short: Vec<Vec<f64>> = (0..3).map(|s| noise(s, 10, 0.0)).collect();
+        assert!(reality_check(&short, 100, Some(1)).is_none());
+    }
+
+    #[test]
+    fn stationary_indices_stay_in_range() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let idx = stationary_indices(&mut rng, 50, 0.2);
+        assert_eq!(idx.len(), 50);
+        assert!(idx.iter().all(|&t| t < 50));
+    }
+}
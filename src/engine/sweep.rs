@@ -26,6 +26,10 @@ pub struct GridSweepConfig {
     pub objective: String,
 }
 
+/// Callback invoked with each combination's result as soon as it completes
+/// (unranked, so `rank` is 0).
+pub type SweepResultCallback = Box<dyn Fn(&SweepResult) + Send + Sync>;
+
 /// Max concurrent backtest tasks.  Kept moderate to avoid excessive memory use
 /// (each task holds its own Rhai engine + intermediate data frames).
 const MAX_CONCURRENT: usize = 8;
@@ -34,12 +38,24 @@ const MAX_CONCURRENT: usize = 8;
 ///
 /// Accepts `Arc<dyn DataLoader>` so that backtest tasks can be spawned onto the
 /// tokio runtime for true concurrency (the `CachingDataLoader` is `Send + Sync`).
-#[allow(clippy::too_many_lines)]
 pub async fn run_grid_sweep(
     config: &GridSweepConfig,
     data_loader: Arc<dyn DataLoader>,
     is_cancelled: &CancelCallback,
     on_progress: impl Fn(usize, usize),
+) -> Result<SweepResponse> {
+    run_grid_sweep_with_results(config, data_loader, is_cancelled, on_progress, |_| {}).await
+}
+
+/// Run a grid sweep, reporting each combination's result to `on_result` as
+/// soon as it completes.
+#[allow(clippy::too_many_lines)]
+pub async fn run_grid_sweep_with_results(
+    config: &GridSweepConfig,
+    data_loader: Arc<dyn DataLoader>,
+    is_cancelled: &CancelCallback,
+    on_progress: impl Fn(usize, usize),
+    on_result: impl Fn(&SweepResult),
 ) -> Result<SweepResponse> {
     let start = Instant::now();
     let combos = cartesian_product(&config.param_grid);
@@ -58,6 +74,8 @@ pub async fn run_grid_sweep(
             convergence_trace: None,
             execution_time_ms: start.elapsed().as_millis() as u64,
             multiple_comparisons: None,
+            reality_check: None,
+            pareto: None,
             full_results: Vec::new(),
         });
     }
@@ -90,12 +108,14 @@ pub async fn run_grid_sweep(
     {
         Ok(bt) => {
             precomputed.clone_from(&bt.precomputed_options);
-            results.push(SweepResult::from_metrics(
+            let result = SweepResult::from_metrics(
                 first_combo,
                 &bt.result.metrics,
                 bt.result.total_pnl,
                 bt.result.trade_count,
-            ));
+            );
+            on_result(&result);
+            results.push(result);
             full_results.push(bt);
         }
         Err(_) => {
@@ -171,12 +191,14 @@ pub async fn run_grid_sweep(
 
             match join_result {
                 Ok((_, combo, Ok(bt))) => {
-                    results.push(SweepResult::from_metrics(
+                    let result = SweepResult::from_metrics(
                         combo,
                         &bt.result.metrics,
                         bt.result.total_pnl,
                         bt.result.trade_count,
-                    ));
+                    );
+                    on_result(&result);
+                    results.push(result);
                     full_results.push(bt);
                 }
                 Ok((_, _, Err(_))) => {
@@ -212,6 +234,8 @@ pub async fn run_grid_sweep(
         convergence_trace: None,
         execution_time_ms: start.elapsed().as_millis() as u64,
         multiple_comparisons: None,
+        reality_check: None,
+        pareto: None,
         full_results,
     })
 }
@@ -297,6 +321,8 @@ mod tests {
             profit_factor,
             cagr: 0.0,
             calmar,
+            cvar_95: 0.0,
+            pareto_front: None,
             p_value: None,
             significant: None,
         }
@@ -319,6 +345,8 @@ mod tests {
             profit_factor: 0.0,
             cagr: 0.0,
             calmar: 0.0,
+            cvar_95: 0.0,
+            pareto_front: None,
             p_value: None,
             significant: None,
         }
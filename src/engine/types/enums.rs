@@ -160,6 +160,10 @@ pub enum ExitType {
     Assignment,
     /// Short call expired ITM — shares called away from the holder.
     CalledAway,
+    /// Closed by the engine to meet a margin call (`engine.margin`).
+    ForcedLiquidation,
+    /// Short leg assigned before expiration (`engine.early_assignment`).
+    EarlyAssignment,
 }
 
 /// Label indicating whether a cashflow is a credit (received) or debit (paid).
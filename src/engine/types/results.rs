@@ -1,5 +1,7 @@
 //! Result and output types produced by the backtesting engine.
 
+use std::collections::BTreeMap;
+
 use chrono::NaiveDateTime;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -37,6 +39,48 @@ pub struct BacktestResult {
     /// Diagnostic warnings surfaced to callers (e.g. entries skipped due to insufficient capital).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub warnings: Vec<String>,
+    /// Financing accruals credited to / debited from cash (script engine with
+    /// `engine.financing` only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub financing: Option<FinancingSummary>,
+    /// Fill statistics for working option-spread limit orders (script engine
+    /// only, when the script submitted any).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_orders: Option<WorkingOrderSummary>,
+}
+
+/// How working option-spread limit orders fared.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WorkingOrderSummary {
+    /// Limit orders submitted for options entries.
+    pub submitted: usize,
+    /// Orders that filled and opened a position.
+    pub filled: usize,
+    /// Orders whose `ttl` ran out before the spread traded at the limit.
+    pub expired: usize,
+    /// `filled / submitted`.
+    pub fill_rate: f64,
+    /// Mean bars between submission and fill.
+    pub avg_bars_to_fill: f64,
+    /// Mean per-share improvement of the fill over the natural quoted when the
+    /// order started working (positive = better than crossing the spread).
+    pub avg_price_improvement: f64,
+}
+
+/// Totals of each financing component accrued over a backtest, in dollars.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FinancingSummary {
+    /// Interest earned on positive cash balances at the risk-free rate.
+    pub cash_interest: f64,
+    /// Interest paid on negative cash (margin loan) balances.
+    pub margin_interest: f64,
+    /// Borrow fees paid on short stock.
+    pub borrow_fees: f64,
+    /// `borrow_fees` broken down by symbol.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub borrow_fees_by_symbol: BTreeMap<String, f64>,
+    /// `cash_interest - margin_interest - borrow_fees`.
+    pub net: f64,
 }
 
 /// Aggregate performance metrics derived from the equity curve and trade log.
@@ -159,6 +203,13 @@ pub struct TradeRecord {
     /// Group label from script `_group` variable for FE trade grouping.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub group: Option<String>,
+    /// Id of the linked order group (OCO/OTO) whose order opened the trade.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_group: Option<String>,
+    /// Cash dividends received (long) or paid (short) on the stock while the
+    /// trade was open; already included in `pnl`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dividend_pnl: Option<f64>,
 }
 
 impl TradeRecord {
@@ -204,6 +255,8 @@ impl TradeRecord {
             stock_exit_price: None,
             stock_pnl: None,
             group: None,
+            order_group: None,
+            dividend_pnl: None,
         }
     }
 }
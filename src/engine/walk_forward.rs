@@ -2,6 +2,8 @@
 //!
 //! Splits historical data into train/test windows, optimizes parameters
 //! on each training window, and validates on the out-of-sample test window.
+//! The `cpcv` mode instead runs combinatorial purged cross-validation (see
+//! [`crate::engine::cpcv`]) and reports a distribution of out-of-sample paths.
 
 use std::collections::HashMap;
 
@@ -14,6 +16,7 @@ use serde_json::Value;
 // BTreeMap used for stable JSON serialization of param maps
 use std::collections::BTreeMap;
 
+use crate::engine::cpcv::{CpcvConfig, CpcvSummary};
 use crate::engine::types::{BacktestResult, EquityPoint, PerformanceMetrics};
 use crate::scripting::engine::{run_script_backtest, CancelCallback, DataLoader};
 
@@ -23,6 +26,9 @@ use crate::scripting::engine::{run_script_backtest, CancelCallback, DataLoader};
 pub enum WfMode {
     Rolling,
     Anchored,
+    /// Combinatorial purged cross-validation: `n_windows` groups, every
+    /// `n_test_groups`-subset held out once.
+    Cpcv,
 }
 
 /// Objective metric to optimize.
@@ -121,6 +127,9 @@ pub fn compute_windows(
                 });
             }
         }
+        WfMode::Cpcv => {
+            bail!("CPCV splits are not contiguous windows; use engine::cpcv instead")
+        }
     }
 
     if windows.is_empty() {
@@ -184,6 +193,9 @@ pub struct WalkForwardParams {
     /// Base parameters (strategy-specific) to merge before each run.
     #[serde(default)]
     pub base_params: Option<HashMap<String, Value>>,
+    /// Split settings used when `mode` is `cpcv`.
+    #[serde(default)]
+    pub cpcv: CpcvConfig,
 }
 
 fn default_objective() -> WfObjective {
@@ -225,6 +237,9 @@ pub struct WalkForwardResponse {
     pub profitable_windows: usize,
     pub total_windows: usize,
     pub param_stability: String,
+    /// Out-of-sample path distribution (CPCV mode only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpcv: Option<CpcvSummary>,
 }
 
 /// Extract the target metric from a backtest result.
@@ -238,12 +253,24 @@ fn extract_metric(result: &BacktestResult, objective: &WfObjective) -> f64 {
 }
 
 /// Run walk-forward optimization.
-#[allow(clippy::too_many_lines)]
 pub async fn execute(
     params: WalkForwardParams,
     data_loader: &dyn DataLoader,
     is_cancelled: &CancelCallback,
     on_progress: impl Fn(usize, usize),
+) -> Result<WalkForwardResponse> {
+    execute_with_windows(params, data_loader, is_cancelled, on_progress, |_| {}).await
+}
+
+/// Run walk-forward optimization, reporting each window's result to
+/// `on_window` as soon as its out-of-sample run completes.
+#[allow(clippy::too_many_lines)]
+pub async fn execute_with_windows(
+    params: WalkForwardParams,
+    data_loader: &dyn DataLoader,
+    is_cancelled: &CancelCallback,
+    on_progress: impl Fn(usize, usize),
+    on_window: impl Fn(&WfWindowResult),
 ) -> Result<WalkForwardResponse> {
     let start = std::time::Instant::now();
 
@@ -309,8 +336,11 @@ pub async fn execute(
         }
     }
 
-    // Compute window boundaries
-    let windows = compute_windows(&dates, params.n_windows, params.train_pct, &params.mode)?;
+    // Compute window boundaries (CPCV builds its own splits below)
+    let windows = match params.mode {
+        WfMode::Cpcv => Vec::new(),
+        _ => compute_windows(&dates, params.n_windows, params.train_pct, &params.mode)?,
+    };
 
     // Generate param combos
     let combos = cartesian_product(&params.params_grid);
@@ -354,6 +384,32 @@ pub async fn execute(
     // because each window has different date bounds.
     let mut precomputed: Option<crate::scripting::engine::PrecomputedOptionsData>;
 
+    // CPCV: each split is reported as a window; the stitched equity is the
+    // median-Sharpe out-of-sample path.
+    let mut cpcv_summary = None;
+    if matches!(params.mode, WfMode::Cpcv) {
+        let run = crate::engine::cpcv::run(
+            &script_source,
+            &base_params,
+            &combos,
+            &dates,
+            params.capital,
+            &params.objective,
+            params.n_windows,
+            params.cpcv,
+            data_loader,
+            is_cancelled,
+            &on_progress,
+            &on_window,
+        )
+        .await?;
+        window_results = run.windows;
+        all_oos_equity = run.equity;
+        is_metrics_sum = run.is_metrics_sum;
+        oos_metrics_sum = run.oos_metrics_sum;
+        cpcv_summary = Some(run.summary);
+    }
+
     for (idx, window) in windows.iter().enumerate() {
         if is_cancelled() {
             break;
@@ -466,6 +522,9 @@ pub async fn execute(
             in_sample_metric: is_metric,
             out_of_sample_metric: oos_metric,
         });
+        if let Some(window) = window_results.last() {
+            on_window(window);
+        }
 
         on_progress(window_base + steps_per_window, total_steps);
     }
@@ -491,6 +550,7 @@ pub async fn execute(
     let mode_str = match params.mode {
         WfMode::Rolling => "rolling",
         WfMode::Anchored => "anchored",
+        WfMode::Cpcv => "cpcv",
     };
 
     // Compute new summary fields
@@ -538,6 +598,7 @@ pub async fn execute(
         profitable_windows,
         total_windows,
         param_stability,
+        cpcv: cpcv_summary,
     })
 }
 
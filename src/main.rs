@@ -59,6 +59,12 @@ async fn run_http(
     let prices_cache = Arc::clone(&cache);
     let app_state = services.build_app_state(Arc::clone(&cache));
     let task_manager = Arc::clone(&services.task_manager);
+
+    let resumed =
+        server::handlers::tasks::resume_tasks(&app_state, services.requeue_interrupted_tasks);
+    if resumed > 0 {
+        tracing::info!("Resumed {resumed} queued task(s) from the previous run");
+    }
     let services_for_mcp = services.clone();
 
     let service = StreamableHttpService::new(
@@ -68,10 +74,14 @@ async fn run_http(
     );
 
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
-        loop {
+        // Persist progress every 5s; evict finished tasks from memory every minute.
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        for tick in 0u64.. {
             interval.tick().await;
-            task_manager.cleanup(chrono::Duration::minutes(10));
+            task_manager.flush_progress();
+            if tick % 12 == 0 {
+                task_manager.cleanup(chrono::Duration::minutes(10));
+            }
         }
     });
 
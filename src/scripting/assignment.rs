@@ -0,0 +1,189 @@
+//! Early-assignment models for short American options.
+//!
+//! Without a model, short legs are only assigned at expiration (the
+//! `"assignment"` / `"called_away"` exits). With `engine.early_assignment` set,
+//! a short leg that is in the money before expiration may be assigned on any bar:
+//!
+//! - `Dividend`: deterministic. A short ITM call is assigned on the last bar
+//!   before an ex-dividend date when its extrinsic value is below the dividend,
+//!   since the holder gains more by exercising to capture the dividend than by
+//!   selling the option.
+//! - `Probabilistic`: seeded random. The dividend-rule calls, plus any short ITM
+//!   leg (call or put) whose extrinsic value has decayed to `min_extrinsic` or
+//!   less, are each assigned with `probability` per bar.
+//!
+//! An assigned leg settles at intrinsic value and closes on its own — any other
+//! legs of its position stay open — then follows the same stock transitions as
+//! an expiration assignment.
+
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::engine::types::{OptionType, Side};
+use crate::scripting::types::ScriptPositionLeg;
+
+/// Config keys accepted for `engine.early_assignment`.
+pub const EARLY_ASSIGNMENT_MODELS: [&str; 2] = ["dividend", "probabilistic"];
+
+/// Default per-bar assignment probability for candidate legs.
+pub const DEFAULT_ASSIGNMENT_PROBABILITY: f64 = 0.5;
+
+/// Default extrinsic value (per share) at or below which a deep ITM leg becomes
+/// a candidate in the probabilistic model.
+pub const DEFAULT_MIN_EXTRINSIC: f64 = 0.05;
+
+/// Early-assignment model selected via `config().engine.early_assignment`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EarlyAssignment {
+    Dividend,
+    Probabilistic {
+        seed: u64,
+        probability: f64,
+        min_extrinsic: f64,
+    },
+}
+
+impl EarlyAssignment {
+    /// Seed for the engine's assignment RNG (unused by the deterministic model).
+    pub fn seed(&self) -> u64 {
+        match self {
+            EarlyAssignment::Dividend => 0,
+            EarlyAssignment::Probabilistic { seed, .. } => *seed,
+        }
+    }
+
+    /// Index of the short leg assigned on this bar, if any.
+    ///
+    /// `dividend` is the cash dividend per share going ex before the next bar —
+    /// `None` when there is none (or this is the last bar).
+    pub fn assigned_leg(
+        &self,
+        legs: &[ScriptPositionLeg],
+        spot: f64,
+        dividend: Option<f64>,
+        rng: &mut StdRng,
+    ) -> Option<usize> {
+        let candidates = legs.iter().enumerate().filter(|(_, leg)| {
+            if leg.side != Side::Short || intrinsic(leg, spot) <= 0.0 {
+                return false;
+            }
+            let extrinsic = extrinsic(leg, spot);
+            let dividend_call = leg.option_type == OptionType::Call
+                && dividend.is_some_and(|amount| extrinsic < amount);
+            match self {
+                EarlyAssignment::Dividend => dividend_call,
+                EarlyAssignment::Probabilistic { min_extrinsic, .. } => {
+                    dividend_call || extrinsic <= *min_extrinsic
+                }
+            }
+        });
+
+        match self {
+            EarlyAssignment::Dividend => candidates.map(|(i, _)| i).next(),
+            EarlyAssignment::Probabilistic { probability, .. } => {
+                // Draw for every candidate so the RNG stream does not depend on
+                // which leg happened to be assigned first.
+                let draws: Vec<(usize, bool)> = candidates
+                    .map(|(i, _)| (i, rng.random_bool(*probability)))
+                    .collect();
+                draws.into_iter().find(|(_, hit)| *hit).map(|(i, _)| i)
+            }
+        }
+    }
+}
+
+/// Intrinsic value per share of `leg` at `spot`.
+pub fn intrinsic(leg: &ScriptPositionLeg, spot: f64) -> f64 {
+    match leg.option_type {
+        OptionType::Call => (spot - leg.strike).max(0.0),
+        OptionType::Put => (leg.strike - spot).max(0.0),
+    }
+}
+
+/// Time value per share left in `leg` at its last mark.
+fn extrinsic(leg: &ScriptPositionLeg, spot: f64) -> f64 {
+    (leg.current_price - intrinsic(leg, spot)).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rand::SeedableRng;
+
+    fn short_leg(option_type: OptionType, strike: f64, current_price: f64) -> ScriptPositionLeg {
+        ScriptPositionLeg {
+            strike,
+            option_type,
+            side: Side::Short,
+            expiration: NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+            entry_price: 2.0,
+            current_price,
+            delta: 0.0,
+            iv: 0.0,
+            gamma: 0.0,
+            theta: 0.0,
+            vega: 0.0,
+            qty: 1,
+        }
+    }
+
+    #[test]
+    fn dividend_model_assigns_call_with_little_time_value() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Intrinsic 5.00, extrinsic 0.20 < dividend 0.50 → assigned
+        let legs = [short_leg(OptionType::Call, 95.0, 5.20)];
+        let model = EarlyAssignment::Dividend;
+        assert_eq!(
+            model.assigned_leg(&legs, 100.0, Some(0.50), &mut rng),
+            Some(0)
+        );
+        // Extrinsic above the dividend → holder keeps the option
+        assert_eq!(model.assigned_leg(&legs, 100.0, Some(0.10), &mut rng), None);
+        // No dividend before the next bar → nothing to capture
+        assert_eq!(model.assigned_leg(&legs, 100.0, None, &mut rng), None);
+    }
+
+    #[test]
+    fn dividend_model_ignores_puts_and_otm_calls() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let legs = [
+            short_leg(OptionType::Put, 105.0, 5.0),
+            short_leg(OptionType::Call, 105.0, 0.10),
+        ];
+        let model = EarlyAssignment::Dividend;
+        assert_eq!(model.assigned_leg(&legs, 100.0, Some(1.0), &mut rng), None);
+    }
+
+    #[test]
+    fn probabilistic_model_is_reproducible_and_respects_extremes() {
+        // Deep ITM put with no time value left
+        let legs = [short_leg(OptionType::Put, 110.0, 10.02)];
+        let always = EarlyAssignment::Probabilistic {
+            seed: 7,
+            probability: 1.0,
+            min_extrinsic: DEFAULT_MIN_EXTRINSIC,
+        };
+        let never = EarlyAssignment::Probabilistic {
+            seed: 7,
+            probability: 0.0,
+            min_extrinsic: DEFAULT_MIN_EXTRINSIC,
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(always.assigned_leg(&legs, 100.0, None, &mut rng), Some(0));
+        assert_eq!(never.assigned_leg(&legs, 100.0, None, &mut rng), None);
+
+        let model = EarlyAssignment::Probabilistic {
+            seed: 7,
+            probability: DEFAULT_ASSIGNMENT_PROBABILITY,
+            min_extrinsic: DEFAULT_MIN_EXTRINSIC,
+        };
+        let run = || {
+            let mut rng = StdRng::seed_from_u64(model.seed());
+            (0..20)
+                .map(|_| model.assigned_leg(&legs, 100.0, None, &mut rng).is_some())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run(), "same seed must give the same assignments");
+    }
+}
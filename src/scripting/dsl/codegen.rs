@@ -47,6 +47,13 @@ const PRECOMPUTED_INDICATORS: &[&str] = &[
     "mfi",
     "rank",
     "iv_rank",
+    "iv_percentile",
+    "atm_iv",
+    "skew_25d",
+    "iv_term_slope",
+    "iv_rv_spread",
+    "put_call_volume",
+    "regime",
 ];
 
 /// Walk all statement blocks in the program and collect `"name:period"` indicator
@@ -103,18 +110,28 @@ fn collect_from_stmts(stmts: &[Stmt], specs: &mut Vec<String>, seen: &mut HashSe
             Stmt::Buy {
                 qty_expr,
                 order_type,
+                exit_modifiers,
                 ..
             } => {
                 scan_expr(qty_expr, specs, seen);
                 scan_order_modifier(order_type, specs, seen);
+                if let Some((ref target, ref stop)) = exit_modifiers.bracket {
+                    scan_expr(target, specs, seen);
+                    scan_expr(stop, specs, seen);
+                }
             }
             Stmt::Sell {
                 qty_expr,
                 order_type,
+                exit_modifiers,
                 ..
             } => {
                 scan_expr(qty_expr, specs, seen);
                 scan_order_modifier(order_type, specs, seen);
+                if let Some((ref target, ref stop)) = exit_modifiers.bracket {
+                    scan_expr(target, specs, seen);
+                    scan_expr(stop, specs, seen);
+                }
             }
             Stmt::OpenStrategy { call, .. } => scan_expr(call, specs, seen),
             Stmt::Plot { expr, .. } => scan_expr(expr, specs, seen),
@@ -250,11 +267,17 @@ pub fn generate(program: &DslProgram) -> String {
                 .params
                 .iter()
                 .map(|p| {
-                    if let Some(step) = p.step {
+                    let mut entry = if !p.choices.is_empty() {
+                        format!("{}=choices {}", p.name, p.choices.join("|"))
+                    } else if let Some(step) = p.step {
                         format!("{}={} to {} step {}", p.name, p.start, p.stop, step)
                     } else {
                         format!("{}={} to {}", p.name, p.start, p.stop)
+                    };
+                    if let Some(ref cond) = p.when {
+                        entry.push_str(&format!(" when {}={}", cond.parent, cond.values.join("|")));
                     }
+                    entry
                 })
                 .collect();
             out.push_str(&format!(
@@ -425,7 +448,11 @@ fn generate_config(out: &mut String, s: &StrategyBlock, program: &DslProgram) {
     out.push_str("        },\n");
 
     // engine block (only if any engine settings present)
-    let has_engine = s.slippage.is_some() || s.expiration_filter.is_some();
+    let has_engine = s.slippage.is_some()
+        || s.expiration_filter.is_some()
+        || s.margin.is_some()
+        || s.early_assignment.is_some()
+        || !s.financing.is_empty();
     if has_engine {
         out.push_str("        engine: #{\n");
         if let Some(ref slip) = s.slippage {
@@ -434,6 +461,22 @@ fn generate_config(out: &mut String, s: &StrategyBlock, program: &DslProgram) {
         if let Some(ref ef) = s.expiration_filter {
             out.push_str(&format!("            expiration_filter: \"{ef}\",\n"));
         }
+        if let Some(ref margin) = s.margin {
+            out.push_str(&format!("            margin: \"{margin}\",\n"));
+        }
+        if let Some(ref model) = s.early_assignment {
+            out.push_str(&format!("            early_assignment: \"{model}\",\n"));
+        }
+        if !s.financing.is_empty() {
+            out.push_str("            financing: #{\n");
+            for (key, value) in &s.financing {
+                out.push_str(&format!(
+                    "                {key}: {},\n",
+                    config_value(value)
+                ));
+            }
+            out.push_str("            },\n");
+        }
         out.push_str("        },\n");
     }
 
@@ -446,6 +489,15 @@ fn generate_config(out: &mut String, s: &StrategyBlock, program: &DslProgram) {
         out.push_str("        },\n");
     }
 
+    // limits block (engine-enforced book greeks)
+    if !s.limits.is_empty() {
+        out.push_str("        limits: #{\n");
+        for (key, value) in &s.limits {
+            out.push_str(&format!("            {key}: {},\n", config_value(value)));
+        }
+        out.push_str("        },\n");
+    }
+
     if s.procedural {
         out.push_str("        procedural: true,\n");
     }
@@ -564,6 +616,16 @@ fn emit_exit_modifiers(out: &mut String, mods: &ExitModifiers, indent: &str) {
             }
         }
     }
+    if let Some(ref group) = mods.oco {
+        out.push_str(&format!(
+            "{indent}__order.order_group = \"{group}\";\n{indent}__order.oco = true;\n"
+        ));
+    }
+    if let Some((ref target, ref stop)) = mods.bracket {
+        let t = rewrite_expr(target);
+        let s = rewrite_expr(stop);
+        out.push_str(&format!("{indent}__order = bracket(__order, {t}, {s});\n"));
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -677,9 +739,7 @@ fn generate_stmts(
                     }
                 };
 
-                let has_mods = exit_modifiers.stop_loss.is_some()
-                    || exit_modifiers.profit_target.is_some()
-                    || exit_modifiers.trailing_stop.is_some();
+                let has_mods = exit_modifiers.any();
 
                 if has_mods && kind == CallbackKind::ActionArray {
                     out.push_str(&format!("{indent}let __order = {call};\n"));
@@ -721,9 +781,7 @@ fn generate_stmts(
                     }
                 };
 
-                let has_mods = exit_modifiers.stop_loss.is_some()
-                    || exit_modifiers.profit_target.is_some()
-                    || exit_modifiers.trailing_stop.is_some();
+                let has_mods = exit_modifiers.any();
 
                 // Emit quantity validation guard
                 out.push_str(&format!("{indent}let __sell_qty = {qty};\n"));
@@ -1043,6 +1101,7 @@ const CTX_PROPERTIES: &[&str] = &[
     "volume",
     "cash",
     "equity",
+    "buying_power",
     "position_count",
     "unrealized_pnl",
     "realized_pnl",
@@ -1101,6 +1160,13 @@ const CTX_METHODS: &[&str] = &[
     "mfi",
     "rank",
     "iv_rank",
+    "iv_percentile",
+    "atm_iv",
+    "skew_25d",
+    "iv_term_slope",
+    "iv_rv_spread",
+    "put_call_volume",
+    "regime",
     // Generic
     "indicator",
     "indicator_with",
@@ -1473,11 +1539,7 @@ fn preprocess_crossovers(expr: &str) -> String {
 
     for keyword in &["crosses above", "crosses below"] {
         // Process all occurrences from left to right
-        loop {
-            let Some(kw_pos) = result.find(keyword) else {
-                break;
-            };
-
+        while let Some(kw_pos) = result.find(keyword) {
             let before = &result[..kw_pos];
             let after = &result[kw_pos + keyword.len()..];
 
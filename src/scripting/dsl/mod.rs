@@ -34,6 +34,7 @@
 pub mod codegen;
 pub mod error;
 pub mod parser;
+pub mod signal;
 pub mod syntax;
 #[cfg(test)]
 mod tests;
@@ -53,7 +54,26 @@ pub use syntax::register_dsl_syntax;
 /// Returns `DslError` with line numbers if the DSL source is malformed, or if
 /// intraday-only keywords are used with a non-intraday interval.
 pub fn transpile(source: &str) -> Result<String, DslError> {
-    let program = parser::parse(source)?;
+    transpile_with_signals(source, &|_| {
+        Err("no saved signal library is available".to_string())
+    })
+}
+
+/// Transpile DSL source, inlining `signal "name"` references first.
+///
+/// `resolve` maps each reference to a spec with every `Saved` node already
+/// expanded (see [`SqliteSignalStore::resolve`](crate::data::signal_store::SqliteSignalStore::resolve)).
+///
+/// # Errors
+///
+/// Returns `DslError` for malformed source and for references that cannot be
+/// resolved or translated.
+pub fn transpile_with_signals(
+    source: &str,
+    resolve: &dyn Fn(&str) -> Result<crate::engine::types::SignalSpec, String>,
+) -> Result<String, DslError> {
+    let source = signal::expand_saved_signals(source, resolve)?;
+    let program = parser::parse(&source)?;
     validate::check_interval_time_keywords(&program)?;
     validate::check_portfolio_access(&program)?;
     validate::check_quantifiers(&program)?;
@@ -61,6 +81,19 @@ pub fn transpile(source: &str) -> Result<String, DslError> {
     Ok(codegen::generate(&program))
 }
 
+/// Transpile DSL source for metadata and `extern` parameter extraction only.
+///
+/// Saved-signal references become `false`, so listing a strategy never
+/// depends on the signal library; the output must not be executed.
+///
+/// # Errors
+///
+/// Returns `DslError` if the DSL source is malformed.
+pub fn transpile_for_metadata(source: &str) -> Result<String, DslError> {
+    let source = signal::strip_saved_signals(source)?;
+    transpile(&source)
+}
+
 /// Check if a source string looks like Trading DSL (vs. plain Rhai).
 ///
 /// Checks whether the first non-comment, non-empty line starts with `strategy `.
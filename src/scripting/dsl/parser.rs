@@ -5,6 +5,10 @@
 //! the nearest preceding block header at a lower indent level.
 
 use super::error::DslError;
+use crate::scripting::assignment::EARLY_ASSIGNMENT_MODELS;
+use crate::scripting::financing::FINANCING_KEYS;
+use crate::scripting::margin::MARGIN_MODELS;
+use crate::scripting::types::GreekLimits;
 
 // ---------------------------------------------------------------------------
 // Raw line representation
@@ -48,7 +52,15 @@ pub struct StrategyBlock {
     pub indicators: Vec<String>,
     pub slippage: Option<String>,
     pub expiration_filter: Option<String>,
+    /// Margin model name (`cash`, `reg_t`, `portfolio`).
+    pub margin: Option<String>,
+    /// Early-assignment model name (`dividend`, `probabilistic`).
+    pub early_assignment: Option<String>,
+    /// Financing rates (`cash_rate`, `margin_rate`, `borrow_rate`) as `(key, value)` pairs.
+    pub financing: Vec<(String, String)>,
     pub max_positions: Option<i64>,
+    /// Engine-enforced greek limits as `(key, value)` pairs.
+    pub limits: Vec<(String, String)>,
     pub cross_symbols: Vec<String>,
     pub procedural: bool,
     pub category: Option<String>,
@@ -82,13 +94,24 @@ pub struct SweepProfileDecl {
     pub params: Vec<SweepParamRange>,
 }
 
-/// A single parameter range within a `sweep` block.
+/// A single parameter range (or set of choices) within a `sweep` block.
 #[derive(Debug)]
 pub struct SweepParamRange {
     pub name: String,
     pub start: f64,
     pub stop: f64,
     pub step: Option<f64>,
+    /// Choices for a categorical param; empty for numeric ranges.
+    pub choices: Vec<String>,
+    /// `when PARENT = v1, v2` — only sweep this param under those parent choices.
+    pub when: Option<SweepCondition>,
+}
+
+/// Parent condition on a sweep param.
+#[derive(Debug)]
+pub struct SweepCondition {
+    pub parent: String,
+    pub values: Vec<String>,
 }
 
 /// Order type modifier for buy/sell statements.
@@ -108,6 +131,21 @@ pub struct ExitModifiers {
     pub stop_loss: Option<OrderExitSpec>,
     pub profit_target: Option<OrderExitSpec>,
     pub trailing_stop: Option<OrderExitSpec>,
+    /// `oco "NAME"` — one-cancels-other group shared with sibling orders.
+    pub oco: Option<String>,
+    /// `bracket target EXPR stop EXPR` — exit orders armed once the entry fills.
+    pub bracket: Option<(String, String)>,
+}
+
+impl ExitModifiers {
+    /// Whether any modifier was attached (the order must be built in a temp).
+    pub fn any(&self) -> bool {
+        self.stop_loss.is_some()
+            || self.profit_target.is_some()
+            || self.trailing_stop.is_some()
+            || self.oco.is_some()
+            || self.bracket.is_some()
+    }
 }
 
 /// A single exit specification on an order.
@@ -294,7 +332,7 @@ pub fn parse(source: &str) -> Result<DslProgram, DslError> {
             program.states.push(parse_state(line)?);
             i += 1;
         } else if content.starts_with("sweep ") {
-            let (profile, next) = parse_sweep_profile(&lines, i)?;
+            let (profile, next) = parse_sweep_profile(&lines, i, &program.params)?;
             program.sweep_profiles.push(profile);
             i = next;
         } else if content == "on each bar" {
@@ -423,7 +461,11 @@ fn parse_strategy_block(lines: &[Line], start: usize) -> Result<(StrategyBlock,
         indicators: vec![],
         slippage: None,
         expiration_filter: None,
+        margin: None,
+        early_assignment: None,
+        financing: vec![],
         max_positions: None,
+        limits: vec![],
         cross_symbols: vec![],
         procedural,
         category: None,
@@ -452,12 +494,81 @@ fn parse_strategy_block(lines: &[Line], start: usize) -> Result<(StrategyBlock,
             block.slippage = Some(rest.trim().to_string());
         } else if let Some(rest) = content.strip_prefix("expiration_filter ") {
             block.expiration_filter = Some(rest.trim().to_string());
+        } else if let Some(rest) = content.strip_prefix("margin ") {
+            let model = rest.trim();
+            if !MARGIN_MODELS.contains(&model) {
+                return Err(DslError::new(
+                    line.num,
+                    format!(
+                        "unknown margin model '{model}' (expected one of: {})",
+                        MARGIN_MODELS.join(", ")
+                    ),
+                ));
+            }
+            block.margin = Some(model.to_string());
+        } else if let Some(rest) = content.strip_prefix("early_assignment ") {
+            let model = rest.trim();
+            if !EARLY_ASSIGNMENT_MODELS.contains(&model) {
+                return Err(DslError::new(
+                    line.num,
+                    format!(
+                        "unknown early assignment model '{model}' (expected one of: {})",
+                        EARLY_ASSIGNMENT_MODELS.join(", ")
+                    ),
+                ));
+            }
+            block.early_assignment = Some(model.to_string());
+        } else if let Some(rest) = content.strip_prefix("financing ") {
+            for item in rest.split(',') {
+                let mut parts = item.split_whitespace();
+                let (Some(key), Some(value), None) = (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(DslError::new(
+                        line.num,
+                        format!(
+                            "invalid financing rate '{}': expected `name value`",
+                            item.trim()
+                        ),
+                    ));
+                };
+                if !FINANCING_KEYS.contains(&key) {
+                    return Err(DslError::new(
+                        line.num,
+                        format!(
+                            "unknown financing rate '{key}' (expected one of: {})",
+                            FINANCING_KEYS.join(", ")
+                        ),
+                    ));
+                }
+                block.financing.push((key.to_string(), value.to_string()));
+            }
         } else if let Some(rest) = content.strip_prefix("max_positions ") {
             block.max_positions = Some(
                 rest.trim()
                     .parse::<i64>()
                     .map_err(|_| DslError::new(line.num, "max_positions must be an integer"))?,
             );
+        } else if let Some(rest) = content.strip_prefix("limits ") {
+            for item in rest.split(',') {
+                let mut parts = item.split_whitespace();
+                let (Some(key), Some(value), None) = (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(DslError::new(
+                        line.num,
+                        format!("invalid limit '{}': expected `name value`", item.trim()),
+                    ));
+                };
+                if !GreekLimits::KEYS.contains(&key) {
+                    return Err(DslError::new(
+                        line.num,
+                        format!(
+                            "unknown limit '{key}' (expected one of: {})",
+                            GreekLimits::KEYS.join(", ")
+                        ),
+                    ));
+                }
+                block.limits.push((key.to_string(), value.to_string()));
+            }
         } else if let Some(rest) = content.strip_prefix("cross_symbols ") {
             block.cross_symbols = rest.split(',').map(|s| s.trim().to_string()).collect();
         } else if let Some(rest) = content.strip_prefix("category ") {
@@ -552,6 +663,12 @@ fn parse_exit_modifiers(
                 _ => OrderExitSpec::Dollar(value),
             });
             consumed += 1;
+        } else if let Some(rest) = content.strip_prefix("oco ") {
+            mods.oco = Some(extract_quoted_value(rest, lines[j].num)?);
+            consumed += 1;
+        } else if let Some(rest) = content.strip_prefix("bracket ") {
+            mods.bracket = Some(parse_bracket(rest.trim(), lines[j].num)?);
+            consumed += 1;
         } else {
             break;
         }
@@ -560,6 +677,22 @@ fn parse_exit_modifiers(
     Ok((mods, consumed))
 }
 
+/// Parse `target EXPR stop EXPR` into its two price expressions.
+fn parse_bracket(s: &str, line_num: usize) -> Result<(String, String), DslError> {
+    let usage = "bracket requires 'target PRICE stop PRICE' (e.g., bracket target close * 1.05 stop close * 0.97)";
+    let rest = s
+        .strip_prefix("target ")
+        .ok_or_else(|| DslError::new(line_num, usage))?;
+    let (target, stop) = rest
+        .split_once(" stop ")
+        .ok_or_else(|| DslError::new(line_num, usage))?;
+    let (target, stop) = (target.trim(), stop.trim());
+    if target.is_empty() || stop.is_empty() {
+        return Err(DslError::new(line_num, usage));
+    }
+    Ok((target.to_string(), stop.to_string()))
+}
+
 // ---------------------------------------------------------------------------
 // Extern and state declarations
 // ---------------------------------------------------------------------------
@@ -711,10 +844,15 @@ fn parse_state(line: &Line) -> Result<StateDecl, DslError> {
 /// Parse a `sweep <name>` block with indented parameter range lines.
 ///
 /// Each line inside the block has the form:
-///   `PARAM_NAME <start> to <stop> [step <step>]`
+///   `PARAM_NAME <start> to <stop> [step <step>]` or
+///   `PARAM_NAME choices VAL1, VAL2`,
+/// optionally followed by `when PARENT = VAL1, VAL2`. A bare
+/// `PARAM_NAME choices` sweeps the choices of the param's `extern`, which
+/// must be declared earlier in `externs`.
 fn parse_sweep_profile(
     lines: &[Line],
     start: usize,
+    externs: &[ParamDecl],
 ) -> Result<(SweepProfileDecl, usize), DslError> {
     let header = &lines[start];
     let name = header
@@ -744,6 +882,65 @@ fn parse_sweep_profile(
             )
         })?;
 
+        let (rest, when) = match rest.split_once(" when ") {
+            Some((rest, cond)) => {
+                let (parent, values) = cond.split_once('=').ok_or_else(|| {
+                    DslError::new(
+                        line.num,
+                        format!("expected 'when PARENT = VALUE, ...', got 'when {cond}'"),
+                    )
+                })?;
+                let values: Vec<String> = split_list(values);
+                if parent.trim().is_empty() || values.is_empty() {
+                    return Err(DslError::new(
+                        line.num,
+                        format!("expected 'when PARENT = VALUE, ...', got 'when {cond}'"),
+                    ));
+                }
+                (
+                    rest,
+                    Some(SweepCondition {
+                        parent: parent.trim().to_string(),
+                        values,
+                    }),
+                )
+            }
+            None => (rest, None),
+        };
+
+        let choices_str = rest
+            .trim()
+            .strip_prefix("choices ")
+            .or((rest.trim() == "choices").then_some(""));
+        if let Some(choices_str) = choices_str {
+            let mut choices = split_list(choices_str);
+            if choices.is_empty() {
+                choices = externs
+                    .iter()
+                    .find(|p| p.name == param_name)
+                    .map(|p| p.choices.clone())
+                    .unwrap_or_default();
+            }
+            if choices.is_empty() {
+                return Err(DslError::new(
+                    line.num,
+                    format!(
+                        "'{param_name} choices' requires at least one value \
+                         (or an earlier 'extern {param_name} ... choices ...')"
+                    ),
+                ));
+            }
+            params.push(SweepParamRange {
+                name: param_name.to_string(),
+                start: 0.0,
+                stop: 0.0,
+                step: None,
+                choices,
+                when,
+            });
+            continue;
+        }
+
         // Parse: <start> to <stop> [step <step>]
         let parts: Vec<&str> = rest.split_whitespace().collect();
 
@@ -775,12 +972,23 @@ fn parse_sweep_profile(
             start: start_val,
             stop: stop_val,
             step: step_val,
+            choices: Vec::new(),
+            when,
         });
     }
 
     Ok((SweepProfileDecl { name, params }, next))
 }
 
+/// Split a comma-separated list, dropping empty entries.
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Indented body extraction
 // ---------------------------------------------------------------------------
@@ -0,0 +1,458 @@
+//! Translation of formula [`SignalSpec`]s into DSL conditions.
+//!
+//! `generate_hypotheses` describes its patterns with series-style formulas
+//! (`pct_change(close, 20) > 0.05`, `zscore(close, 20) < -2`). The DSL reads
+//! values bar by bar, so each supported function is rewritten onto its
+//! per-bar equivalent — lookbacks become `close[n]`, rolling statistics become
+//! precomputed indicators — and the indicators the condition depends on are
+//! collected so the generated script can `require` them.
+//!
+//! Options-structure features (`skew_25d`, `iv_term_slope`, `iv_rv_spread`,
+//! `put_call_volume`) map onto the options-chain indicators of the same name,
+//! and `rank(feature, n)` onto their trailing percentile.
+//!
+//! Anything without a bar-level equivalent (volume averages, other symbols'
+//! prices) is rejected with a reason rather than approximated.
+//!
+//! Scripts reference the saved signal library with `signal "name"` (or
+//! `signal "name@2"` to pin a version) anywhere an expression is allowed;
+//! [`expand_saved_signals`] inlines each reference as its guarded condition
+//! before parsing.
+
+use std::fmt::Write;
+
+use super::DslError;
+use crate::engine::types::SignalSpec;
+
+/// Keyword introducing a saved-signal reference in DSL expressions.
+const SIGNAL_KEYWORD: &str = "signal";
+
+/// OHLCV fields a formula may reference directly.
+const SERIES: &[&str] = &["close", "open", "high", "low", "volume"];
+
+/// Calendar functions that exist verbatim in the DSL.
+const CALENDAR_FUNCTIONS: &[&str] = &["day_of_week", "month", "day_of_month"];
+
+/// Options-structure series backed by options-chain indicators.
+const OPTIONS_STRUCTURE: &[&str] = &[
+    "skew_25d",
+    "iv_term_slope",
+    "iv_rv_spread",
+    "put_call_volume",
+];
+
+/// A formula rewritten as a DSL condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalExpr {
+    /// Condition usable in `when … then` / `skip when …`.
+    pub expr: String,
+    /// Indicator specs (`sma:20`) the condition reads, in first-use order.
+    pub indicators: Vec<String>,
+    /// Deepest price lookback (`close[n]`) the condition reads.
+    pub lookback: usize,
+    /// Whether the condition reads options-derived indicators (`iv_rank`,
+    /// `skew_25d`).
+    pub needs_options: bool,
+}
+
+impl SignalExpr {
+    /// The condition wrapped so it is `false` until every indicator it reads
+    /// is ready and enough bars exist for its deepest lookback.
+    pub fn guarded(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.indicators.is_empty() {
+            let specs: Vec<String> = self.indicators.iter().map(|s| format!("\"{s}\"")).collect();
+            parts.push(format!("indicators_ready([{}])", specs.join(", ")));
+        }
+        if self.lookback > 0 {
+            parts.push(format!("bar_idx >= {}", self.lookback));
+        }
+        parts.push(format!("({})", self.expr));
+        format!("({})", parts.join(" and "))
+    }
+}
+
+/// Inline every `signal "name"` reference in a DSL source.
+///
+/// `resolve` maps a reference (`name` or `name@version`) to a fully resolved
+/// spec — one without `Saved` nodes. Comment lines and string literals are
+/// left untouched, and line numbers are preserved.
+///
+/// # Errors
+///
+/// Returns a `DslError` on the referencing line when the signal cannot be
+/// resolved or translated.
+pub fn expand_saved_signals(
+    source: &str,
+    resolve: &dyn Fn(&str) -> Result<SignalSpec, String>,
+) -> Result<String, DslError> {
+    rewrite_references(source, Some(resolve))
+}
+
+/// Replace every `signal "name"` reference with `false`, for metadata and
+/// parameter extraction where the library may be unavailable.
+///
+/// # Errors
+///
+/// Returns a `DslError` for an unterminated signal name.
+pub fn strip_saved_signals(source: &str) -> Result<String, DslError> {
+    rewrite_references(source, None)
+}
+
+type Resolver<'a> = &'a dyn Fn(&str) -> Result<SignalSpec, String>;
+
+fn rewrite_references(source: &str, resolve: Option<Resolver<'_>>) -> Result<String, DslError> {
+    if !source.contains(SIGNAL_KEYWORD) {
+        return Ok(source.to_string());
+    }
+    let mut lines = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        if line.trim_start().starts_with('#') {
+            lines.push(line.to_string());
+        } else {
+            lines.push(
+                rewrite_line(line, resolve).map_err(|message| DslError::new(i + 1, message))?,
+            );
+        }
+    }
+    let mut out = lines.join("\n");
+    if source.ends_with('\n') {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn rewrite_line(line: &str, resolve: Option<Resolver<'_>>) -> Result<String, String> {
+    let chars: Vec<char> = line.chars().collect();
+    let keyword: Vec<char> = SIGNAL_KEYWORD.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' {
+            in_string = !in_string;
+        } else if !in_string
+            && chars[i..].starts_with(&keyword)
+            && (i == 0 || !is_ident_char(chars[i - 1]))
+            && !chars
+                .get(i + keyword.len())
+                .is_some_and(|&ch| is_ident_char(ch))
+        {
+            let open = skip_spaces(&chars, i + keyword.len());
+            if chars.get(open) == Some(&'"') {
+                let close = chars[open + 1..]
+                    .iter()
+                    .position(|&ch| ch == '"')
+                    .map(|p| open + 1 + p)
+                    .ok_or("unterminated signal name")?;
+                let reference: String = chars[open + 1..close].iter().collect();
+                out.push_str(&reference_condition(reference.trim(), resolve)?);
+                i = close + 1;
+                continue;
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+
+    Ok(out)
+}
+
+fn reference_condition(reference: &str, resolve: Option<Resolver<'_>>) -> Result<String, String> {
+    let Some(resolve) = resolve else {
+        return Ok("false".to_string());
+    };
+    let spec =
+        resolve(reference).map_err(|e| format!("cannot resolve signal \"{reference}\": {e}"))?;
+    let translated = signal_to_expr(&spec)
+        .map_err(|e| format!("signal \"{reference}\" cannot be used in the DSL: {e}"))?;
+    Ok(translated.guarded())
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Translate a signal spec into a DSL condition.
+///
+/// `And` / `Or` combine their translated operands; `Saved` signals must be
+/// resolved to formulas by the caller first.
+///
+/// # Errors
+///
+/// Returns a human-readable reason when the signal uses a function or series
+/// the DSL cannot evaluate per bar.
+pub fn signal_to_expr(spec: &SignalSpec) -> Result<SignalExpr, String> {
+    match spec {
+        SignalSpec::Formula { formula } => formula_to_expr(formula),
+        SignalSpec::Saved { name } => Err(format!(
+            "saved signal '{name}' must be resolved before translation"
+        )),
+        SignalSpec::And { left, right } => combine(left, right, "and"),
+        SignalSpec::Or { left, right } => combine(left, right, "or"),
+    }
+}
+
+fn combine(left: &SignalSpec, right: &SignalSpec, op: &str) -> Result<SignalExpr, String> {
+    let left = signal_to_expr(left)?;
+    let right = signal_to_expr(right)?;
+    let mut indicators = left.indicators;
+    for spec in right.indicators {
+        if !indicators.contains(&spec) {
+            indicators.push(spec);
+        }
+    }
+    Ok(SignalExpr {
+        expr: format!("({}) {op} ({})", left.expr, right.expr),
+        indicators,
+        lookback: left.lookback.max(right.lookback),
+        needs_options: left.needs_options || right.needs_options,
+    })
+}
+
+/// Translate a single formula into a DSL condition.
+///
+/// # Errors
+///
+/// Returns a human-readable reason when the formula uses a function or series
+/// the DSL cannot evaluate per bar.
+pub fn formula_to_expr(formula: &str) -> Result<SignalExpr, String> {
+    let mut translator = Translator::default();
+    let expr = translator.rewrite(formula.trim())?;
+    if expr.trim().is_empty() {
+        return Err("empty formula".to_string());
+    }
+    Ok(SignalExpr {
+        expr,
+        indicators: translator.indicators,
+        lookback: translator.lookback,
+        needs_options: translator.needs_options,
+    })
+}
+
+#[derive(Default)]
+struct Translator {
+    indicators: Vec<String>,
+    lookback: usize,
+    needs_options: bool,
+}
+
+impl Translator {
+    fn rewrite(&mut self, src: &str) -> Result<String, String> {
+        let chars: Vec<char> = src.chars().collect();
+        let mut out = String::with_capacity(src.len() + 16);
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            if !(c.is_ascii_alphabetic() || c == '_') {
+                if c.is_ascii_digit() || c == '.' {
+                    // Consume numeric literals whole so a digit never starts an
+                    // identifier.
+                    while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                        out.push(chars[i]);
+                        i += 1;
+                    }
+                } else {
+                    out.push(c);
+                    i += 1;
+                }
+                continue;
+            }
+
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let next = skip_spaces(&chars, i);
+
+            if chars.get(next) == Some(&'(') {
+                let (inner, end) = consume_parens(&chars, next)
+                    .ok_or_else(|| format!("unbalanced parentheses after '{word}'"))?;
+                let args = split_args(&inner);
+                out.push_str(&self.call(&word, &args)?);
+                i = end;
+            } else if matches!(word.as_str(), "and" | "or" | "not") {
+                out.push_str(&word);
+            } else if OPTIONS_STRUCTURE.contains(&word.as_str()) {
+                self.needs_options = true;
+                self.require(word.clone());
+                let _ = write!(out, "{word}()");
+            } else if SERIES.contains(&word.as_str()) {
+                out.push_str(&word);
+                if chars.get(next) == Some(&'[') {
+                    let close = chars[next..]
+                        .iter()
+                        .position(|&ch| ch == ']')
+                        .map(|p| next + p)
+                        .ok_or_else(|| format!("unterminated lookback after '{word}'"))?;
+                    let n = parse_lookback(&chars[next + 1..close].iter().collect::<String>())?;
+                    self.lookback = self.lookback.max(n);
+                    let _ = write!(out, "[{n}]");
+                    i = close + 1;
+                }
+            } else {
+                return Err(format!("unsupported series '{word}'"));
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Rewrite a function call onto its bar-level DSL equivalent.
+    fn call(&mut self, name: &str, args: &[String]) -> Result<String, String> {
+        match name {
+            _ if CALENDAR_FUNCTIONS.contains(&name) && args.is_empty() => Ok(format!("{name}()")),
+            "gap" if args.is_empty() => {
+                self.lookback = self.lookback.max(1);
+                Ok("(open / close[1] - 1)".to_string())
+            }
+            "pct_change" => {
+                let (field, n) = series_window(name, args)?;
+                self.lookback = self.lookback.max(n);
+                Ok(format!("({field} / {field}[{n}] - 1)"))
+            }
+            "change" => {
+                let (field, n) = series_window(name, args)?;
+                self.lookback = self.lookback.max(n);
+                Ok(format!("({field} - {field}[{n}])"))
+            }
+            "sma" | "ema" | "rsi" => {
+                let n = close_window(name, args)?;
+                self.require(format!("{name}:{n}"));
+                Ok(format!("{name}({n})"))
+            }
+            "std" => {
+                let n = close_window(name, args)?;
+                Ok(self.close_std(n))
+            }
+            "zscore" => {
+                let n = close_window(name, args)?;
+                let std = self.close_std(n);
+                Ok(format!("((close - bbands_mid({n})) / {std})"))
+            }
+            "iv_rank" | "iv_percentile" => {
+                if args.len() != 2 || args[0].trim() != "iv" {
+                    return Err(format!("{name}() expects (iv, lookback)"));
+                }
+                let n = parse_lookback(&args[1])?;
+                self.needs_options = true;
+                self.require(format!("{name}:{n}"));
+                Ok(format!("{name}({n})"))
+            }
+            "rank" => {
+                let feature = args.first().map(|a| a.trim()).unwrap_or_default();
+                if args.len() != 2 || !OPTIONS_STRUCTURE.contains(&feature) {
+                    return Err(format!(
+                        "rank() expects (series, lookback) over one of: {}",
+                        OPTIONS_STRUCTURE.join(", ")
+                    ));
+                }
+                let n = parse_lookback(&args[1])?;
+                self.needs_options = true;
+                self.require(format!("{feature}:{n}"));
+                Ok(format!("{feature}({n})"))
+            }
+            "abs" if args.len() == 1 => Ok(format!("abs({})", self.rewrite(args[0].trim())?)),
+            _ => Err(format!("unsupported function '{name}()'")),
+        }
+    }
+
+    /// Rolling standard deviation of close, recovered from the default
+    /// 2-sigma Bollinger Band width.
+    fn close_std(&mut self, n: usize) -> String {
+        self.require(format!("bbands_upper:{n}"));
+        self.require(format!("bbands_mid:{n}"));
+        format!("((bbands_upper({n}) - bbands_mid({n})) / 2)")
+    }
+
+    fn require(&mut self, spec: String) {
+        if !self.indicators.contains(&spec) {
+            self.indicators.push(spec);
+        }
+    }
+}
+
+/// Parse `(field, n)` arguments where `field` is a raw OHLCV series.
+fn series_window(name: &str, args: &[String]) -> Result<(String, usize), String> {
+    if args.len() != 2 {
+        return Err(format!("{name}() expects (series, window)"));
+    }
+    let field = args[0].trim();
+    if !SERIES.contains(&field) {
+        return Err(format!("{name}() over '{field}' is not supported"));
+    }
+    Ok((field.to_string(), parse_lookback(&args[1])?))
+}
+
+/// Parse `(close, n)` arguments for indicators the DSL only computes on close.
+fn close_window(name: &str, args: &[String]) -> Result<usize, String> {
+    let (field, n) = series_window(name, args)?;
+    if field != "close" {
+        return Err(format!(
+            "{name}() is only available on close, not '{field}'"
+        ));
+    }
+    Ok(n)
+}
+
+fn parse_lookback(raw: &str) -> Result<usize, String> {
+    match raw.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid window '{}'", raw.trim())),
+    }
+}
+
+fn skip_spaces(chars: &[char], mut pos: usize) -> usize {
+    while pos < chars.len() && chars[pos].is_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Return the text inside the parenthesis opening at `open` and the index
+/// just past its matching close.
+fn consume_parens(chars: &[char], open: usize) -> Option<(String, usize)> {
+    let mut depth = 0usize;
+    for (offset, &c) in chars[open..].iter().enumerate() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    let end = open + offset;
+                    return Some((chars[open + 1..end].iter().collect(), end + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split call arguments on top-level commas.
+fn split_args(inner: &str) -> Vec<String> {
+    if inner.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+    for c in inner.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    args.push(current);
+    args
+}
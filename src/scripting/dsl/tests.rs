@@ -2045,6 +2045,155 @@ on each bar
     );
 }
 
+#[test]
+fn test_greek_limits_and_portfolio_greeks() {
+    let dsl = r#"
+strategy "Premium Book"
+  interval daily
+  data ohlcv, options
+  limits max_abs_vega 500, min_theta -25, max_abs_delta MAX_DELTA
+
+asset symbol = "SPY"
+
+extern MAX_DELTA = 200 "Book delta cap"
+
+on each bar
+  skip when portfolio.net_vega < -400 or portfolio.net_theta < 0
+  open short_put(0.30, 45)
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(rhai.contains("limits: #{"), "Generated:\n{rhai}");
+    assert!(rhai.contains("max_abs_vega: 500,"));
+    assert!(rhai.contains("min_theta: -25,"));
+    assert!(rhai.contains("max_abs_delta: MAX_DELTA,"));
+    assert!(rhai.contains("ctx.portfolio.net_vega"));
+    assert!(rhai.contains("ctx.portfolio.net_theta"));
+}
+
+#[test]
+fn test_unknown_greek_limit_rejected() {
+    let dsl = r#"
+strategy "Bad Limits"
+  data ohlcv, options
+  limits max_vega 500
+
+asset symbol = "SPY"
+
+on each bar
+  open short_put(0.30, 45)
+"#;
+
+    let err = transpile(dsl).unwrap_err();
+    assert!(
+        err.to_string().contains("unknown limit 'max_vega'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_margin_model_and_buying_power() {
+    let dsl = r#"
+strategy "Short Strangle"
+  data ohlcv, options
+  margin reg_t
+
+asset symbol = "SPY"
+
+on each bar
+  skip when portfolio.buying_power < 10000
+  open short_strangle(0.16, 45)
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(rhai.contains("margin: \"reg_t\","), "Generated:\n{rhai}");
+    assert!(rhai.contains("ctx.portfolio.buying_power"));
+
+    let bad = dsl.replace("margin reg_t", "margin span");
+    let err = transpile(&bad).unwrap_err();
+    assert!(
+        err.to_string().contains("unknown margin model 'span'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_open_with_leg_selectors() {
+    let dsl = r#"
+strategy "Fixed Width Condor"
+  data ohlcv, options
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  open iron_condor(0.16, wing(5), moneyness(0.05), wing(5), 45)
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("ctx.iron_condor(0.16, wing(5), moneyness(0.05), wing(5), 45)"),
+        "Generated:\n{rhai}"
+    );
+}
+
+#[test]
+fn test_early_assignment_model() {
+    let dsl = r#"
+strategy "Covered Call"
+  data ohlcv, options
+  early_assignment dividend
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  open covered_call(0.30, 30)
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("early_assignment: \"dividend\","),
+        "Generated:\n{rhai}"
+    );
+
+    let bad = dsl.replace("early_assignment dividend", "early_assignment always");
+    let err = transpile(&bad).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unknown early assignment model 'always'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_financing_rates() {
+    let dsl = r#"
+strategy "Cash-Secured Put"
+  data ohlcv, options
+  financing cash_rate "^IRX", margin_rate 0.08
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  open short_put(0.30, 30)
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(rhai.contains("financing: #{"), "Generated:\n{rhai}");
+    assert!(rhai.contains("cash_rate: \"^IRX\","), "Generated:\n{rhai}");
+    assert!(rhai.contains("margin_rate: 0.08,"), "Generated:\n{rhai}");
+
+    let bad = dsl.replace("margin_rate 0.08", "repo_rate 0.05");
+    let err = transpile(&bad).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unknown financing rate 'repo_rate'"),
+        "{err}"
+    );
+}
+
 #[test]
 fn test_portfolio_in_when_then() {
     let dsl = r#"
@@ -2491,3 +2640,344 @@ on each bar
     assert_eq!(quick[0].step, Some(0.10));
     assert_eq!(quick[1].name, "DTE");
 }
+
+#[test]
+fn test_sweep_profile_categorical_and_conditional_roundtrip() {
+    let dsl = r#"
+strategy "Test Sweep"
+  interval daily
+
+asset symbol = "SPY"
+
+extern SLIPPAGE = "mid" "Slippage model" choices mid, spread
+extern WIDTH = 2 "Spread width"
+
+sweep slippage
+  SLIPPAGE choices mid, spread
+  WIDTH 1 to 5 step 1 when SLIPPAGE = spread
+
+on each bar
+  hold position
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("//! sweep.slippage: SLIPPAGE=choices mid|spread, WIDTH=1 to 5 step 1 when SLIPPAGE=spread"),
+        "Should contain categorical/conditional sweep header.\nGenerated:\n{rhai}"
+    );
+
+    let meta = crate::scripting::stdlib::parse_script_meta("test", &rhai);
+    let profile = &meta.sweep_profiles.expect("should have sweep_profiles")["slippage"];
+    assert_eq!(profile.len(), 2);
+    assert_eq!(profile[0].name, "SLIPPAGE");
+    assert_eq!(
+        profile[0].choices,
+        vec![serde_json::json!("mid"), serde_json::json!("spread")]
+    );
+    assert!(profile[0].when.is_none());
+    assert_eq!(profile[1].name, "WIDTH");
+    assert!((profile[1].stop - 5.0).abs() < f64::EPSILON);
+    let cond = profile[1]
+        .when
+        .as_ref()
+        .expect("WIDTH should be conditional");
+    assert_eq!(cond.parent, "SLIPPAGE");
+    assert_eq!(cond.values, vec![serde_json::json!("spread")]);
+}
+
+#[test]
+fn test_sweep_profile_bare_choices_use_extern_choices() {
+    let dsl = r#"
+strategy "Test Sweep"
+  interval daily
+
+asset symbol = "SPY"
+
+extern SLIPPAGE = "mid" "Slippage model" choices mid, spread
+
+sweep slippage
+  SLIPPAGE choices
+
+on each bar
+  hold position
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("//! sweep.slippage: SLIPPAGE=choices mid|spread"),
+        "Bare choices should expand to the extern's choices.\nGenerated:\n{rhai}"
+    );
+
+    // Without choices on the extern there is nothing to sweep
+    let err = transpile(&dsl.replace(" choices mid, spread", "")).unwrap_err();
+    assert!(err.to_string().contains("SLIPPAGE choices"), "{err}");
+}
+
+#[test]
+fn test_sweep_profile_rejects_malformed_condition() {
+    let dsl = r#"
+strategy "Test Sweep"
+  interval daily
+
+asset symbol = "SPY"
+
+sweep bad
+  WIDTH 1 to 5 when SLIPPAGE
+
+on each bar
+  hold position
+"#;
+
+    let err = transpile(dsl).unwrap_err();
+    assert!(err.to_string().contains("when PARENT"), "{err}");
+}
+
+#[test]
+fn test_transpile_open_spread_limit() {
+    let dsl = r#"
+strategy "Worked Entry"
+  interval daily
+  data ohlcv, options
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  open spread_limit(bull_put_spread(0.30, 0.15, 45), 0.05, 0.05, 5)
+"#;
+
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("spread_limit(ctx.bull_put_spread(0.30, 0.15, 45), 0.05, 0.05, 5)"),
+        "Generated:\n{rhai}"
+    );
+}
+
+#[test]
+fn test_transpile_bracket_order() {
+    let dsl = r#"
+strategy "Bracketed Breakout"
+  interval daily
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  Buy 100 shares of symbol next bar at market
+    bracket target close * 1.05 stop close * 0.97
+"#;
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("let __order = buy_stock(symbol, 100)"),
+        "Should use __order.\n{rhai}"
+    );
+    assert!(
+        rhai.contains("__order = bracket(__order, ctx.close * 1.05, ctx.close * 0.97);"),
+        "Missing bracket.\n{rhai}"
+    );
+}
+
+#[test]
+fn test_transpile_oco_orders() {
+    let dsl = r#"
+strategy "Straddle Breakout"
+  interval daily
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  Buy 100 shares of symbol next bar at high stop
+    oco "breakout"
+  Sell 100 shares of symbol next bar at low stop
+    oco "breakout"
+"#;
+    let rhai = transpile(dsl).unwrap();
+    assert_eq!(
+        rhai.matches("__order.order_group = \"breakout\";").count(),
+        2,
+        "Both orders should join the group.\n{rhai}"
+    );
+    assert!(rhai.contains("__order.oco = true;"), "Generated:\n{rhai}");
+}
+
+#[test]
+fn test_bracket_requires_target_and_stop() {
+    let dsl = r#"
+strategy "Bad Bracket"
+  interval daily
+
+asset symbol = "SPY"
+
+on each bar
+  Buy 100 shares of symbol next bar at market
+    bracket target close * 1.05
+"#;
+    let err = transpile(dsl).unwrap_err();
+    assert!(err.to_string().contains("bracket requires"), "got: {err}");
+}
+
+#[test]
+fn test_transpile_regime_gate() {
+    let dsl = r#"
+strategy "Regime-Gated Put Selling"
+  interval daily
+  data ohlcv, options
+
+asset symbol = "SPY"
+
+on each bar
+  skip when has positions
+  skip when regime(3) == "high_vol"
+  open short_put(0.30, 45)
+"#;
+    let rhai = transpile(dsl).unwrap();
+    assert!(
+        rhai.contains("ctx.regime(3) == \"high_vol\""),
+        "regime should be ctx-qualified.\n{rhai}"
+    );
+    assert!(
+        rhai.contains("\"regime:3\""),
+        "regime should be declared as an indicator.\n{rhai}"
+    );
+}
+
+#[test]
+fn test_signal_formula_translation() {
+    use crate::engine::types::SignalSpec;
+    use signal::{formula_to_expr, signal_to_expr};
+
+    let momentum = formula_to_expr("pct_change(close, 20) > 0.05").unwrap();
+    assert_eq!(momentum.expr, "(close / close[20] - 1) > 0.05");
+    assert_eq!(momentum.lookback, 20);
+    assert!(momentum.indicators.is_empty());
+
+    let streak =
+        formula_to_expr("change(close, 1) < 0 and close[1] < close[2] and close[2] < close[3]")
+            .unwrap();
+    assert_eq!(
+        streak.expr,
+        "(close - close[1]) < 0 and close[1] < close[2] and close[2] < close[3]"
+    );
+    assert_eq!(streak.lookback, 3);
+
+    let band = formula_to_expr("close < sma(close, 20) - 2 * std(close, 20)").unwrap();
+    assert_eq!(
+        band.indicators,
+        ["sma:20", "bbands_upper:20", "bbands_mid:20"]
+    );
+
+    let oversold = formula_to_expr("rsi(close, 14) < 30 and close > ema(close, 50)").unwrap();
+    assert_eq!(oversold.expr, "rsi(14) < 30 and close > ema(50)");
+    assert_eq!(oversold.indicators, ["rsi:14", "ema:50"]);
+    assert!(formula_to_expr("rsi(volume, 14) < 30").is_err());
+
+    let iv = formula_to_expr("iv_rank(iv, 252) > 80").unwrap();
+    assert_eq!(iv.expr, "iv_rank(252) > 80");
+    assert!(iv.needs_options);
+
+    let skew = formula_to_expr("rank(skew_25d, 252) > 80 and put_call_volume > 1").unwrap();
+    assert_eq!(skew.expr, "skew_25d(252) > 80 and put_call_volume() > 1");
+    assert_eq!(skew.indicators, ["skew_25d:252", "put_call_volume"]);
+    assert!(skew.needs_options);
+
+    let both = signal_to_expr(&SignalSpec::And {
+        left: Box::new(SignalSpec::Formula {
+            formula: "day_of_week() == 1".to_string(),
+        }),
+        right: Box::new(SignalSpec::Formula {
+            formula: "gap() < -0.01".to_string(),
+        }),
+    })
+    .unwrap();
+    assert_eq!(
+        both.expr,
+        "(day_of_week() == 1) and ((open / close[1] - 1) < -0.01)"
+    );
+    assert_eq!(both.lookback, 1);
+
+    for unsupported in [
+        "volume > 2 * sma(volume, 20)",
+        "rank(skew, 63) > 80",
+        "abs(pct_change(QQQ, 1)) > 0.02",
+        "(high - low) / low > 2 * sma((high - low) / low, 20)",
+    ] {
+        assert!(
+            formula_to_expr(unsupported).is_err(),
+            "{unsupported} should be unsupported"
+        );
+    }
+}
+
+#[test]
+fn test_translated_signals_transpile() {
+    for formula in [
+        "zscore(close, 20) < -2",
+        "day_of_month() >= 28 or day_of_month() <= 3",
+        "pct_change(close, 1) < -0.02",
+        "month() == 12",
+    ] {
+        let signal = signal::formula_to_expr(formula).unwrap();
+        let require = if signal.indicators.is_empty() {
+            String::new()
+        } else {
+            format!("  require {}\n", signal.indicators.join(", "))
+        };
+        let dsl = format!(
+            "strategy \"Signal\"\n  interval daily\n\nasset symbol = \"SPY\"\n\non each bar\n{require}  skip when has positions\n  when {} then\n    Buy 10 shares of symbol next bar at market\n",
+            signal.expr
+        );
+        if let Err(e) = transpile(&dsl) {
+            panic!("{formula}: {e}\n{dsl}");
+        }
+    }
+}
+
+#[test]
+fn test_saved_signal_reference_is_inlined() {
+    use crate::engine::types::SignalSpec;
+
+    let resolve = |reference: &str| -> Result<SignalSpec, String> {
+        match reference {
+            "risk_on" | "risk_on@1" => Ok(SignalSpec::And {
+                left: Box::new(SignalSpec::Formula {
+                    formula: "close > sma(close, 50)".to_string(),
+                }),
+                right: Box::new(SignalSpec::Formula {
+                    formula: "pct_change(close, 5) > 0".to_string(),
+                }),
+            }),
+            other => Err(format!("'{other}' not found")),
+        }
+    };
+    let dsl = "# signal \"ignored\" in a comment\nstrategy \"Uses signal\"\n  interval daily\n\nasset symbol = \"SPY\"\n\non each bar\n  skip when has positions\n  when signal \"risk_on\" and not signal \"risk_on@1\" then\n    Buy 10 shares of symbol next bar at market\n";
+
+    let expanded = signal::expand_saved_signals(dsl, &resolve).unwrap();
+    assert!(expanded.starts_with("# signal \"ignored\""));
+    assert!(expanded.contains("strategy \"Uses signal\""));
+    assert!(expanded.contains(
+        "(indicators_ready([\"sma:50\"]) and bar_idx >= 5 and ((close > sma(50)) and ((close / close[5] - 1) > 0)))"
+    ));
+    assert_eq!(expanded.lines().count(), dsl.lines().count());
+
+    let rhai = transpile_with_signals(dsl, &resolve).unwrap();
+    assert!(
+        rhai.contains("ctx.indicators_ready([\"sma:50\"])"),
+        "{rhai}"
+    );
+    assert!(rhai.contains("ctx.bar_idx >= 5"), "{rhai}");
+
+    let err = transpile_with_signals(&dsl.replace("risk_on@1", "missing"), &resolve).unwrap_err();
+    assert_eq!(err.line, 9);
+    assert!(err.message.contains("missing"), "{}", err.message);
+
+    let err = transpile(dsl).unwrap_err();
+    assert!(
+        err.message.contains("no saved signal library"),
+        "{}",
+        err.message
+    );
+
+    let meta = transpile_for_metadata(dsl).unwrap();
+    assert!(meta.contains("false"), "{meta}");
+}
@@ -81,8 +81,23 @@ fn visit_exprs_in_stmts(
                 check(call, *line)?;
                 visit_exprs_in_stmts(body, check)?;
             }
-            Stmt::Buy { qty_expr, line, .. } | Stmt::Sell { qty_expr, line, .. } => {
+            Stmt::Buy {
+                qty_expr,
+                exit_modifiers,
+                line,
+                ..
+            }
+            | Stmt::Sell {
+                qty_expr,
+                exit_modifiers,
+                line,
+                ..
+            } => {
                 check(qty_expr, *line)?;
+                if let Some((ref target, ref stop)) = exit_modifiers.bracket {
+                    check(target, *line)?;
+                    check(stop, *line)?;
+                }
             }
             Stmt::Plot { expr, line, .. } | Stmt::Return { expr, line, .. } => {
                 check(expr, *line)?;
@@ -177,16 +192,14 @@ fn check_reserved_names(program: &DslProgram) -> Result<(), DslError> {
 fn check_reserved_names_in_stmts(stmts: &[Stmt]) -> Result<(), DslError> {
     for stmt in stmts {
         match stmt {
-            Stmt::Set { name, line, .. } => {
-                if is_reserved_name(name) {
-                    return Err(DslError::new(
-                        *line,
-                        format!(
-                            "variable `{name}` conflicts with reserved day/month name. \
-                             Choose a different variable name."
-                        ),
-                    ));
-                }
+            Stmt::Set { name, line, .. } if is_reserved_name(name) => {
+                return Err(DslError::new(
+                    *line,
+                    format!(
+                        "variable `{name}` conflicts with reserved day/month name. \
+                         Choose a different variable name."
+                    ),
+                ));
             }
             Stmt::ForEach {
                 var, body, line, ..
@@ -472,6 +485,11 @@ const PORTFOLIO_PROPERTIES: &[&str] = &[
     "net_delta",
     "long_delta",
     "short_delta",
+    "net_gamma",
+    "net_theta",
+    "net_vega",
+    "margin_requirement",
+    "buying_power",
     "position_count",
     "long_count",
     "short_count",
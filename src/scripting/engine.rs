@@ -4,21 +4,34 @@
 //! Handles data loading, position management, and metrics calculation
 //! while scripts define trading logic via `config()`, `on_bar()`, etc.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use anyhow::{bail, Context, Result};
 use chrono::{NaiveDate, NaiveDateTime};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
 
+use crate::engine::greeks::{GreeksParams, OptionGreeks};
 use crate::engine::metrics::calculate_metrics;
 use crate::engine::types::{
     BacktestResult, Commission, EquityPoint, ExpirationFilter, Side, Slippage, TradeRecord,
-    TradeSelector,
+    TradeSelector, WorkingOrderSummary,
 };
 
+use super::assignment::{
+    intrinsic, EarlyAssignment, DEFAULT_ASSIGNMENT_PROBABILITY, DEFAULT_MIN_EXTRINSIC,
+    EARLY_ASSIGNMENT_MODELS,
+};
+use super::financing::{
+    FinancingConfig, FinancingLedger, RateSeries, RateSource, DEFAULT_BORROW_RATE,
+    DEFAULT_MARGIN_RATE,
+};
 use super::indicators::IndicatorStore;
+use super::margin::{MarginModel, DEFAULT_PRICE_SHOCK, DEFAULT_VOL_SHOCK, MARGIN_MODELS};
 use super::options_cache::DatePartitionedOptions;
+use super::portfolio::SleeveLink;
 use super::registration::build_engine;
 use super::types::*;
 
@@ -132,6 +145,13 @@ const KNOWN_INDICATORS: &[&str] = &[
     "roc",
     "rank",
     "iv_rank",
+    "iv_percentile",
+    "atm_iv",
+    "skew_25d",
+    "iv_term_slope",
+    "iv_rv_spread",
+    "put_call_volume",
+    "regime",
     "cmf",
     "change",
     "pct_change",
@@ -462,6 +482,52 @@ pub async fn run_script_backtest(
     progress: Option<ProgressCallback>,
     precomputed_options: Option<&PrecomputedOptionsData>,
     is_cancelled: Option<&CancelCallback>,
+) -> Result<ScriptBacktestResult> {
+    // The bar loop's state machine is large; box it so callers' futures stay small
+    Box::pin(run_backtest(
+        script_source,
+        params,
+        data_loader,
+        progress,
+        precomputed_options,
+        is_cancelled,
+        None,
+    ))
+    .await
+}
+
+/// Run a script as one sleeve of a portfolio backtest.
+///
+/// The sleeve starts with the capital the portfolio allocated to it, waits for
+/// the other sleeves before every bar, and has its entries gated by the shared
+/// capital pool.
+pub async fn run_sleeve_backtest(
+    script_source: &str,
+    params: &HashMap<String, serde_json::Value>,
+    data_loader: &dyn DataLoader,
+    sleeve: &SleeveLink,
+) -> Result<ScriptBacktestResult> {
+    Box::pin(run_backtest(
+        script_source,
+        params,
+        data_loader,
+        None,
+        None,
+        None,
+        Some(sleeve),
+    ))
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_backtest(
+    script_source: &str,
+    params: &HashMap<String, serde_json::Value>,
+    data_loader: &dyn DataLoader,
+    progress: Option<ProgressCallback>,
+    precomputed_options: Option<&PrecomputedOptionsData>,
+    is_cancelled: Option<&CancelCallback>,
+    sleeve: Option<&SleeveLink>,
 ) -> Result<ScriptBacktestResult> {
     let backtest_start = std::time::Instant::now();
 
@@ -553,6 +619,11 @@ pub async fn run_script_backtest(
         config.symbols = symbol_values;
     }
 
+    // 3a'. Portfolio sleeves start with the capital allocated to them
+    if let Some(link) = sleeve {
+        config.capital = link.initial_capital();
+    }
+
     // 3b. Override date bounds from params (used by walk-forward to set window dates)
     if let Some(s) = params.get("START_DATE").and_then(|v| v.as_str()) {
         if let Ok(d) = s.parse::<chrono::NaiveDate>() {
@@ -580,6 +651,7 @@ pub async fn run_script_backtest(
     let indicator_store: Arc<IndicatorStore>;
     let adjustment_timeline: Arc<crate::engine::adjustments::AdjustmentTimeline>;
     let split_timeline: Arc<crate::engine::adjustments::AdjustmentTimeline>;
+    let dividend_schedule: Arc<Vec<crate::data::adjustment_store::DividendRow>>;
     let options_by_date: Option<Arc<DatePartitionedOptions>>;
     let price_table: Option<Arc<crate::engine::sim_types::PriceTable>>;
     let date_index: Option<Arc<crate::engine::sim_types::DateIndex>>;
@@ -611,6 +683,7 @@ pub async fn run_script_backtest(
         indicator_store = Arc::clone(&first_data.indicator_store);
         split_timeline = Arc::clone(&first_data.split_timeline);
         adjustment_timeline = Arc::clone(&first_data.adjustment_timeline);
+        dividend_schedule = Arc::clone(&first_data.dividends);
 
         // Primary symbol's options data (used for single-symbol compat paths)
         options_by_date = first_data.options_by_date.as_ref().map(Arc::clone);
@@ -677,6 +750,7 @@ pub async fn run_script_backtest(
         adjustment_timeline = Arc::new(crate::engine::adjustments::AdjustmentTimeline::build(
             &splits, &dividends, &closes,
         ));
+        dividend_schedule = Arc::new(dividends);
 
         // OHLCV_ADJUST modes: "none" (default), "split" (raw→adjusted), "unadjust" (adjusted→raw)
         let ohlcv_mode = std::env::var("OHLCV_ADJUST").unwrap_or_else(|_| "none".to_string());
@@ -749,10 +823,7 @@ pub async fn run_script_backtest(
             )
         };
 
-        indicator_store = Arc::new(IndicatorStore::build(
-            &config.declared_indicators,
-            &indicator_bars,
-        )?);
+        let mut store = IndicatorStore::build(&config.declared_indicators, &indicator_bars)?;
 
         // Load options data if needed + build PriceTable for MTM
         if config.needs_options {
@@ -770,6 +841,7 @@ pub async fn run_script_backtest(
                 options_by_date = Some(Arc::new(DatePartitionedOptions::from_df(
                     &df,
                     &config.expiration_filter,
+                    &config.greeks_params,
                 )?));
             }
         } else {
@@ -777,6 +849,18 @@ pub async fn run_script_backtest(
             price_table = None;
             date_index = None;
         }
+
+        insert_implied_vol_indicators(
+            &mut store,
+            &config,
+            &config.symbol,
+            &indicator_bars,
+            options_by_date.as_deref(),
+            data_loader,
+            &mut early_warnings,
+        )
+        .await?;
+        indicator_store = Arc::new(store);
     }
 
     let config = Arc::new(config);
@@ -824,6 +908,32 @@ pub async fn run_script_backtest(
         Arc::new(cross_map)
     };
 
+    // Financing ledger; a rate-series symbol is loaded like any OHLCV symbol
+    let mut financing = match &config.financing {
+        Some(cfg) => {
+            let series = match &cfg.cash_rate {
+                RateSource::Series(symbol) => {
+                    let df = data_loader
+                        .load_ohlcv(symbol, config.start_date, config.end_date)
+                        .await
+                        .with_context(|| format!("Failed to load cash rate series '{symbol}'"))?;
+                    let series = RateSeries::from_percent(
+                        ohlcv_bars_from_df(&df)?
+                            .iter()
+                            .map(|b| (b.datetime.date(), b.close)),
+                    );
+                    if series.is_empty() {
+                        bail!("Cash rate series '{symbol}' has no data");
+                    }
+                    Some(series)
+                }
+                RateSource::Constant(_) => None,
+            };
+            Some(FinancingLedger::new(cfg.clone(), series))
+        }
+        None => None,
+    };
+
     let has_on_exit_check = has_fn(&ast, "on_exit_check", 2);
     let has_on_position_opened = has_fn(&ast, "on_position_opened", 2);
     let has_on_position_closed = has_fn(&ast, "on_position_closed", 3);
@@ -843,6 +953,8 @@ pub async fn run_script_backtest(
     let timeout = std::time::Duration::from_secs(config.timeout_secs);
     let mut pnl_history_arc = Arc::new(Vec::<f64>::new());
     let mut pnl_dirty = false;
+    let mut assignment_rng =
+        StdRng::seed_from_u64(config.early_assignment.map_or(0, |model| model.seed()));
 
     let custom_series = Arc::new(Mutex::new(CustomSeriesStore {
         series: HashMap::new(),
@@ -860,13 +972,19 @@ pub async fn run_script_backtest(
         custom_series: Arc::clone(&custom_series),
         adjustment_timeline: Arc::clone(&adjustment_timeline),
         split_timeline: Arc::clone(&split_timeline),
+        dividends: dividend_schedule,
     };
 
     // Pending order queue for next-bar execution model
     let mut pending_orders: Vec<PendingOrder> = Vec::new();
+    let mut working_stats = WorkingOrderStats::default();
+    // Counter for auto-named order groups
+    let mut next_order_group = 0usize;
     // Max profit/loss tracking per position id
     let mut max_profit_tracker: HashMap<usize, f64> = HashMap::new();
     let mut max_loss_tracker: HashMap<usize, f64> = HashMap::new();
+    // Dividend cash received (long) or paid (short) per stock position id
+    let mut dividend_tracker: HashMap<usize, f64> = HashMap::new();
 
     for (bar_idx, bar) in price_history.iter().enumerate() {
         if stop_requested {
@@ -893,6 +1011,12 @@ pub async fn run_script_backtest(
             }
         }
 
+        // Portfolio sleeves wait for the other sleeves to reach this bar and
+        // receive any rebalance cash before trading on it
+        if let Some(link) = sleeve {
+            realized_equity += link.sync(bar.datetime).await;
+        }
+
         if pnl_dirty {
             pnl_history_arc = Arc::new(pnl_history.clone());
             pnl_dirty = false;
@@ -906,17 +1030,48 @@ pub async fn run_script_backtest(
         // correct without runtime adjustment. The "100 shares at $100" in split-adjusted
         // terms is economically equivalent to "200 shares at $100" in unadjusted terms.
 
+        // --- Dividends: stock held through the previous close receives (or, when
+        // short, pays) every dividend going ex since then ---
+        if let Some(prev_bar) = bar_idx.checked_sub(1).and_then(|i| price_history.get(i)) {
+            let prev_date = prev_bar.datetime.date();
+            for pos in &positions {
+                if let ScriptPositionInner::Stock { side, qty, .. } = &pos.inner {
+                    let per_share = ctx_factory.dividends_between(&pos.symbol, prev_date, today);
+                    if per_share > 0.0 {
+                        let cash = per_share * f64::from(*qty) * side.multiplier();
+                        realized_equity += cash;
+                        *dividend_tracker.entry(pos.id).or_default() += cash;
+                    }
+                }
+            }
+        }
+
         // --- Phase A: Fill pending orders from previous bar ---
         // Orders submitted on bar N are filled on bar N+1.
         // Remove expired orders first, then attempt to fill remaining orders.
-        pending_orders.retain(|order| !order.is_expired(bar_idx));
+        pending_orders.retain(|order| {
+            let expired = order.is_expired(bar_idx);
+            if expired && matches!(order.order_type, OrderType::SpreadLimit { .. }) {
+                working_stats.expired += 1;
+            }
+            !expired
+        });
 
         let orders_to_process = std::mem::take(&mut pending_orders);
         // Snapshot the pending count before processing for accurate callback contexts
         let phase_a_pending_count = orders_to_process.len();
         let mut unfilled_orders: Vec<PendingOrder> = Vec::new();
         let mut auto_exit_orders: Vec<PendingOrder> = Vec::new();
-        for order in orders_to_process {
+        // OCO groups that already filled this bar
+        let mut cancelled_oco: HashSet<String> = HashSet::new();
+        for mut order in orders_to_process {
+            if order
+                .group
+                .as_ref()
+                .is_some_and(|g| g.oco && cancelled_oco.contains(&g.id))
+            {
+                continue;
+            }
             // Resolve the target symbol's bar for fill checks (multi-symbol support).
             // For close orders, derive the symbol from the referenced position so
             // auto stop-loss/take-profit orders fill against the correct market.
@@ -965,9 +1120,40 @@ pub async fn run_script_backtest(
             } else {
                 target_sym
             };
-            if let Some(fill_price) =
+            // Working spread limits are priced against their legs' quotes, not the bar
+            let mut spread_fill: Option<Vec<ResolvedLeg>> = None;
+            if let ScriptAction::OpenOptions { legs, .. } = &mut order.action {
+                if matches!(order.order_type, OrderType::SpreadLimit { .. }) {
+                    let spot = ctx_factory.symbol_close(target_sym, bar, bar_idx);
+                    let quoted = quote_working_legs(
+                        legs,
+                        &ctx_factory.options_chain(target_sym),
+                        today,
+                        spot,
+                        &config,
+                    );
+                    if let Some(resolved) = quoted {
+                        let (mid, natural) = spread_quote(&resolved);
+                        if order.order_type.work_spread_limit(mid, natural) {
+                            spread_fill = Some(resolved);
+                        }
+                    }
+                    if spread_fill.is_none() {
+                        unfilled_orders.push(order);
+                        continue;
+                    }
+                }
+            }
+            // Options entries ignore the bar fill price
+            let fill = if spread_fill.is_some() {
+                Some(fill_bar.open)
+            } else {
                 order.try_fill(fill_bar.open, fill_bar.high, fill_bar.low, fill_bar.close)
-            {
+            };
+            if let Some(fill_price) = fill {
+                // Set when the order executes; `opened` is the new position's id
+                let mut filled = false;
+                let mut opened: Option<usize> = None;
                 match &order.action {
                     ScriptAction::OpenStock { side, qty, .. } => {
                         // Stagger check
@@ -1000,7 +1186,44 @@ pub async fn run_script_backtest(
                             implicit: false,
                             group: read_group(&scope),
                             trailing_stop: order.trailing_stop.clone(),
+                            order_group: order.group.as_ref().map(|g| g.id.clone()),
                         };
+                        if let Some(reason) =
+                            greek_limit_breach(&config.greek_limits, &positions, &pos)
+                        {
+                            warnings.push(format!(
+                                "Stock entry on {today} blocked by config().limits: {reason}"
+                            ));
+                            continue;
+                        }
+                        if let Some(reason) = ctx_factory.margin_shortfall(
+                            &positions,
+                            &pos,
+                            realized_equity,
+                            bar,
+                            bar_idx,
+                        ) {
+                            warnings.push(format!(
+                                "Stock entry on {today} blocked by margin: {reason}"
+                            ));
+                            continue;
+                        }
+                        if let Some(reason) = sleeve.and_then(|link| {
+                            link.admission_shortfall(
+                                &positions,
+                                &pos,
+                                realized_equity
+                                    + positions.iter().map(|p| p.unrealized_pnl).sum::<f64>(),
+                                |sym| ctx_factory.symbol_close(sym, bar, bar_idx),
+                                today,
+                                &config.greeks_params,
+                            )
+                        }) {
+                            warnings.push(format!(
+                                "Stock entry on {today} blocked by portfolio: {reason}"
+                            ));
+                            continue;
+                        }
                         realized_equity -= compute_commission(&config.commission, &pos);
                         next_id += 1;
                         last_entry_date = Some(today);
@@ -1038,6 +1261,8 @@ pub async fn run_script_backtest(
                         // Dollar amounts are total P&L (e.g., $500 = exit when loss exceeds $500),
                         // converted to per-share offset: amt / qty
                         let pos_id = next_id - 1;
+                        filled = true;
+                        opened = Some(pos_id);
                         let is_long = *side == Side::Long;
                         let shares = *qty as f64;
                         if let Some(ref sl) = order.stop_loss {
@@ -1070,6 +1295,8 @@ pub async fn run_script_backtest(
                                 stop_loss: None,
                                 profit_target: None,
                                 trailing_stop: None,
+                                group: None,
+                                triggers: vec![],
                             });
                         }
                         if let Some(ref pt) = order.profit_target {
@@ -1102,6 +1329,8 @@ pub async fn run_script_backtest(
                                 stop_loss: None,
                                 profit_target: None,
                                 trailing_stop: None,
+                                group: None,
+                                triggers: vec![],
                             });
                         }
                     }
@@ -1155,13 +1384,15 @@ pub async fn run_script_backtest(
                                 );
                             }
 
-                            trade_log.push(build_script_trade_record(
+                            let record = build_script_trade_record(
                                 &closed_pos,
                                 bar.datetime,
                                 pnl,
                                 reason,
-                            ));
-                            pnl_history.push(pnl);
+                                dividend_tracker.remove(&closed_pos.id),
+                            );
+                            pnl_history.push(record.pnl);
+                            trade_log.push(record);
                             pnl_dirty = true;
                             max_profit_tracker.remove(pid);
                             max_loss_tracker.remove(pid);
@@ -1181,6 +1412,7 @@ pub async fn run_script_backtest(
                             };
                             unfilled_orders.retain(cancel_pid);
                             auto_exit_orders.retain(cancel_pid);
+                            filled = true;
                         } else {
                             // Silently skip — position already closed (e.g., sibling auto-exit)
                         }
@@ -1234,13 +1466,15 @@ pub async fn run_script_backtest(
                                 );
                             }
 
-                            trade_log.push(build_script_trade_record(
+                            let record = build_script_trade_record(
                                 &closed_pos,
                                 bar.datetime,
                                 pnl,
                                 reason,
-                            ));
-                            pnl_history.push(pnl);
+                                dividend_tracker.remove(&closed_pos.id),
+                            );
+                            pnl_history.push(record.pnl);
+                            trade_log.push(record);
                             pnl_dirty = true;
                             max_profit_tracker.remove(&closed_pos.id);
                             max_loss_tracker.remove(&closed_pos.id);
@@ -1260,6 +1494,7 @@ pub async fn run_script_backtest(
                             });
 
                             positions.swap_remove(idx);
+                            filled = true;
                         } else {
                             warnings.push(
                                 "Pending close order (no position_id): no open positions to close"
@@ -1268,14 +1503,17 @@ pub async fn run_script_backtest(
                         }
                     }
                     ScriptAction::OpenOptions { legs, qty, .. } => {
-                        // In multi-symbol mode, use the target symbol's options chain
-                        let target_obd = if let Some(psd) = &ctx_factory.per_symbol_data {
-                            psd.get(target_sym)
-                                .and_then(|d| d.options_by_date.as_ref().map(Arc::clone))
-                        } else {
-                            options_by_date.as_ref().map(Arc::clone)
-                        };
-                        let resolved = resolve_option_legs(legs, &target_obd, today, &config);
+                        let working = spread_fill.is_some();
+                        let resolved = spread_fill.take().unwrap_or_else(|| {
+                            let spot = ctx_factory.symbol_close(target_sym, bar, bar_idx);
+                            resolve_option_legs(
+                                legs,
+                                &ctx_factory.options_chain(target_sym),
+                                today,
+                                spot,
+                                &config,
+                            )
+                        });
                         if resolved.is_empty() {
                             warnings.push(format!(
                                 "OpenOptions pending order skipped on {today}: no option contracts \
@@ -1284,8 +1522,18 @@ pub async fn run_script_backtest(
                             continue;
                         }
                         let effective_qty = qty.unwrap_or(1);
-                        let (entry_cost, script_legs, expiration) =
-                            compute_options_entry(&resolved, &config, effective_qty);
+                        let (mut entry_cost, mut script_legs, expiration) =
+                            compute_options_entry(&resolved, &config.slippage, effective_qty);
+                        // A filled working limit trades at its limit price
+                        if let OrderType::SpreadLimit {
+                            limit: Some(limit), ..
+                        } = order.order_type
+                        {
+                            if working {
+                                reprice_entry_legs(&mut script_legs, &resolved, entry_cost, limit);
+                                entry_cost = limit;
+                            }
+                        }
                         let pos = ScriptPosition {
                             id: next_id,
                             symbol: target_sym.to_string(),
@@ -1307,7 +1555,44 @@ pub async fn run_script_backtest(
                             implicit: false,
                             group: read_group(&scope),
                             trailing_stop: None,
+                            order_group: order.group.as_ref().map(|g| g.id.clone()),
                         };
+                        if let Some(reason) =
+                            greek_limit_breach(&config.greek_limits, &positions, &pos)
+                        {
+                            warnings.push(format!(
+                                "Options entry on {today} blocked by config().limits: {reason}"
+                            ));
+                            continue;
+                        }
+                        if let Some(reason) = ctx_factory.margin_shortfall(
+                            &positions,
+                            &pos,
+                            realized_equity,
+                            bar,
+                            bar_idx,
+                        ) {
+                            warnings.push(format!(
+                                "Options entry on {today} blocked by margin: {reason}"
+                            ));
+                            continue;
+                        }
+                        if let Some(reason) = sleeve.and_then(|link| {
+                            link.admission_shortfall(
+                                &positions,
+                                &pos,
+                                realized_equity
+                                    + positions.iter().map(|p| p.unrealized_pnl).sum::<f64>(),
+                                |sym| ctx_factory.symbol_close(sym, bar, bar_idx),
+                                today,
+                                &config.greeks_params,
+                            )
+                        }) {
+                            warnings.push(format!(
+                                "Options entry on {today} blocked by portfolio: {reason}"
+                            ));
+                            continue;
+                        }
 
                         // Per-order exit modifiers are not yet supported for options entries
                         if order.stop_loss.is_some()
@@ -1351,10 +1636,43 @@ pub async fn run_script_backtest(
                             );
                         }
 
+                        if let OrderType::SpreadLimit { start_natural, .. } = order.order_type {
+                            working_stats.record_fill(
+                                bar_idx - order.submitted_bar,
+                                start_natural.unwrap_or(entry_cost) - entry_cost,
+                            );
+                        }
                         positions.push(pos);
+                        filled = true;
+                        opened = Some(next_id - 1);
                     }
                     _ => {} // Hold, Stop, CancelOrders handled elsewhere
                 }
+
+                if filled {
+                    // One-cancels-other: the rest of the group is canceled
+                    if let Some(group) = order.group.as_ref().filter(|g| g.oco) {
+                        unfilled_orders.retain(|o| !o.in_oco_group(&group.id));
+                        auto_exit_orders.retain(|o| !o.in_oco_group(&group.id));
+                        cancelled_oco.insert(group.id.clone());
+                    }
+                    // One-triggers-other: children work from the next bar
+                    for mut child in std::mem::take(&mut order.triggers) {
+                        if let ScriptAction::Close { position_id, .. } = &mut child.action {
+                            if position_id.is_none() {
+                                *position_id = opened;
+                            }
+                        }
+                        child.submitted_bar = bar_idx;
+                        if child.group.is_none() {
+                            child.group = order.group.as_ref().map(|g| OrderGroup {
+                                id: g.id.clone(),
+                                oco: false,
+                            });
+                        }
+                        auto_exit_orders.push(child);
+                    }
+                }
             } else {
                 unfilled_orders.push(order);
             }
@@ -1390,6 +1708,7 @@ pub async fn run_script_backtest(
             let pos = &positions[i];
             let mut should_close = false;
             let mut exit_reason = String::new();
+            let mut early_assigned_leg: Option<usize> = None;
 
             // Built-in: option expiration with ITM detection
             if let ScriptPositionInner::Options {
@@ -1410,6 +1729,26 @@ pub async fn run_script_backtest(
                 }
             }
 
+            // Built-in: early assignment of a short leg before expiration
+            if let (
+                Some(model),
+                ScriptPositionInner::Options {
+                    expiration, legs, ..
+                },
+            ) = (&config.early_assignment, &pos.inner)
+            {
+                if !should_close && today < *expiration {
+                    let spot = ctx_factory.symbol_close(&pos.symbol, bar, bar_idx);
+                    let dividend = ctx_factory.dividend_before_next_bar(&pos.symbol, bar_idx);
+                    early_assigned_leg =
+                        model.assigned_leg(legs, spot, dividend, &mut assignment_rng);
+                    if early_assigned_leg.is_some() {
+                        should_close = true;
+                        exit_reason = "early_assignment".to_string();
+                    }
+                }
+            }
+
             // Per-order trailing stop check
             if !should_close && pos.days_held > 0 {
                 if let Some(ref ts) = pos.trailing_stop {
@@ -1475,8 +1814,24 @@ pub async fn run_script_backtest(
             }
 
             if should_close {
+                // Early assignment of one leg of a multi-leg position closes only
+                // that leg; the rest of the position stays open
+                let assigned_alone =
+                    early_assigned_leg.and_then(|idx| split_assigned_leg(&mut positions[i], idx));
+                let partial = assigned_alone.is_some();
+                if partial {
+                    early_assigned_leg = Some(0);
+                }
                 // Clone before removal to reference position data after it's removed
-                let closed_pos = positions[i].clone();
+                let mut closed_pos = assigned_alone.unwrap_or_else(|| positions[i].clone());
+
+                // An early-assigned leg settles at intrinsic value, not its last mark
+                if let (Some(idx), ScriptPositionInner::Options { legs, .. }) =
+                    (early_assigned_leg, &mut closed_pos.inner)
+                {
+                    let spot = ctx_factory.symbol_close(&closed_pos.symbol, bar, bar_idx);
+                    legs[idx].current_price = intrinsic(&legs[idx], spot);
+                }
 
                 // Close position immediately (deduct exit commission)
                 // In multi-symbol mode, use position's symbol's close for stock P&L
@@ -1520,46 +1875,63 @@ pub async fn run_script_backtest(
                     );
                 }
 
-                trade_log.push(build_script_trade_record(
+                let record = build_script_trade_record(
                     &closed_pos,
                     bar.datetime,
                     pnl,
                     &exit_reason,
-                ));
-                pnl_history.push(pnl);
+                    dividend_tracker.remove(&closed_pos.id),
+                );
+                pnl_history.push(record.pnl);
+                trade_log.push(record);
                 pnl_dirty = true;
-
-                max_profit_tracker.remove(&closed_pos.id);
-                max_loss_tracker.remove(&closed_pos.id);
-                positions.swap_remove(i);
                 positions_dirty = true; // positions changed, Arc needs rebuild
 
-                // Cancel auto-generated stop/target orders for this position
-                let closed_id = closed_pos.id;
-                pending_orders.retain(|o| {
-                    if let ScriptAction::Close {
-                        position_id: Some(pid),
-                        ..
-                    } = &o.action
-                    {
-                        *pid != closed_id
-                    } else {
-                        true
-                    }
-                });
+                if !partial {
+                    max_profit_tracker.remove(&closed_pos.id);
+                    max_loss_tracker.remove(&closed_pos.id);
+                    positions.swap_remove(i);
+
+                    // Cancel auto-generated stop/target orders for this position
+                    let closed_id = closed_pos.id;
+                    pending_orders.retain(|o| {
+                        if let ScriptAction::Close {
+                            position_id: Some(pid),
+                            ..
+                        } = &o.action
+                        {
+                            *pid != closed_id
+                        } else {
+                            true
+                        }
+                    });
+                }
 
                 // Handle implicit stock transitions for wheel-like strategies.
                 // "assignment": short put expired ITM → open an implicit long stock at the strike.
                 // "called_away": short call expired ITM → close any implicit long stock at the strike.
-                match exit_reason.as_str() {
+                // An early assignment transitions like its leg's type, for that leg only.
+                let transition = match (early_assigned_leg, &closed_pos.inner) {
+                    (Some(idx), ScriptPositionInner::Options { legs, .. }) => {
+                        match legs[idx].option_type {
+                            crate::engine::types::OptionType::Put => "assignment",
+                            crate::engine::types::OptionType::Call => "called_away",
+                        }
+                    }
+                    _ => exit_reason.as_str(),
+                };
+                let transitions_leg =
+                    |leg_idx: usize| early_assigned_leg.is_none_or(|idx| idx == leg_idx);
+                match transition {
                     "assignment" => {
                         if let ScriptPositionInner::Options {
                             legs, multiplier, ..
                         } = &closed_pos.inner
                         {
-                            for leg in legs {
+                            for (leg_idx, leg) in legs.iter().enumerate() {
                                 if leg.side == Side::Short
                                     && leg.option_type == crate::engine::types::OptionType::Put
+                                    && transitions_leg(leg_idx)
                                 {
                                     // Use saturating_mul to avoid silent i32 overflow for
                                     // unusually large position sizes (e.g. qty=1, multiplier=100
@@ -1598,6 +1970,7 @@ pub async fn run_script_backtest(
                                         implicit: true,
                                         group: closed_pos.group.clone(),
                                         trailing_stop: None,
+                                        order_group: closed_pos.order_group.clone(),
                                     };
                                     next_id += 1;
                                     positions.push(implicit);
@@ -1607,9 +1980,10 @@ pub async fn run_script_backtest(
                     }
                     "called_away" => {
                         if let ScriptPositionInner::Options { legs, .. } = &closed_pos.inner {
-                            for leg in legs {
+                            for (leg_idx, leg) in legs.iter().enumerate() {
                                 if leg.side == Side::Short
                                     && leg.option_type == crate::engine::types::OptionType::Call
+                                    && transitions_leg(leg_idx)
                                 {
                                     let call_strike = leg.strike;
                                     // Close all implicit long stock positions (source="assignment").
@@ -1638,13 +2012,15 @@ pub async fn run_script_backtest(
                                                 &positions[j],
                                             );
                                             realized_equity += stock_pnl - stock_exit_comm;
-                                            trade_log.push(build_script_trade_record(
+                                            let record = build_script_trade_record(
                                                 &positions[j],
                                                 bar.datetime,
                                                 stock_pnl,
                                                 "called_away",
-                                            ));
-                                            pnl_history.push(stock_pnl);
+                                                dividend_tracker.remove(&positions[j].id),
+                                            );
+                                            pnl_history.push(record.pnl);
+                                            trade_log.push(record);
                                             pnl_dirty = true;
                                             positions.swap_remove(j);
                                             // Don't increment j
@@ -1658,7 +2034,11 @@ pub async fn run_script_backtest(
                     }
                     _ => {}
                 }
-                // Don't increment i — the next position is now at index i
+                // A partially assigned position keeps its slot; otherwise don't
+                // increment i — the next position is now at index i
+                if partial {
+                    i += 1;
+                }
             } else {
                 i += 1;
             }
@@ -1722,18 +2102,19 @@ pub async fn run_script_backtest(
                                 continue;
                             }
 
-                            pending_orders.push(PendingOrder {
-                                action: pa.action,
-                                symbol: pa.symbol,
-                                order_type: pa.order_type,
-                                is_buy: pa.is_buy,
-                                signal: pa.signal,
-                                submitted_bar: bar_idx,
-                                ttl: pa.ttl,
-                                stop_loss: pa.stop_loss,
-                                profit_target: pa.profit_target,
-                                trailing_stop: pa.trailing_stop,
-                            });
+                            if matches!(pa.order_type, OrderType::SpreadLimit { .. }) {
+                                working_stats.submitted += 1;
+                            }
+                            let mut order = pa.into_pending(bar_idx);
+                            // Name unnamed OTO parents so their trades can be told apart
+                            if order.group.is_none() && !order.triggers.is_empty() {
+                                next_order_group += 1;
+                                order.group = Some(OrderGroup {
+                                    id: format!("group-{next_order_group}"),
+                                    oco: false,
+                                });
+                            }
+                            pending_orders.push(order);
                         }
                     }
                 }
@@ -1801,6 +2182,12 @@ pub async fn run_script_backtest(
                         .as_ref()
                         .and_then(|psd| psd.get(&pos.symbol))
                         .map(|d| d.last_known.lock().unwrap_or_else(|e| e.into_inner()));
+                    let chain = match &ctx_factory.per_symbol_data {
+                        Some(psd) => psd
+                            .get(&pos.symbol)
+                            .and_then(|d| d.options_by_date.as_ref()),
+                        None => ctx_factory.options_by_date.as_ref(),
+                    };
 
                     let mut pos_pnl = 0.0;
                     for leg in legs.iter_mut() {
@@ -1840,6 +2227,12 @@ pub async fn run_script_backtest(
                                 * *multiplier as f64;
                             pos_pnl += leg_pnl;
                         }
+                        // Refresh greeks from today's chain (keeps entry values on data gaps)
+                        if let Some(g) = chain.and_then(|c| {
+                            c.contract_greeks(today, leg.option_type, leg.strike, leg.expiration)
+                        }) {
+                            leg.set_greeks(&g);
+                        }
                     }
                     pos.unrealized_pnl = pos_pnl;
                     unrealized += pos_pnl;
@@ -1858,6 +2251,97 @@ pub async fn run_script_backtest(
             }
         }
 
+        // Accrue cash interest, margin interest and borrow fees since the last close
+        if let Some(ledger) = financing.as_mut() {
+            realized_equity += ledger.settle(today, &positions, realized_equity, |sym| {
+                ctx_factory.symbol_close(sym, bar, bar_idx)
+            });
+        }
+
+        // Margin call: liquidate the positions that free the most margin until
+        // the requirement fits within mark-to-market equity again.
+        if config.margin.is_some() {
+            let equity = realized_equity + unrealized;
+            let mut required = ctx_factory.margin_requirement(&positions, bar, bar_idx);
+            if required > equity {
+                warnings.push(format!(
+                    "Margin call on {today}: requirement ${required:.2} exceeds equity ${equity:.2}"
+                ));
+            }
+            while !positions.is_empty() && required > realized_equity + unrealized {
+                // Score each candidate by taking it out of the book in place and
+                // putting it back, rather than copying the book per candidate
+                let mut best: Option<(usize, f64)> = None;
+                for i in 0..positions.len() {
+                    let candidate = positions.swap_remove(i);
+                    let without = ctx_factory.margin_requirement(&positions, bar, bar_idx);
+                    positions.push(candidate);
+                    let last = positions.len() - 1;
+                    positions.swap(i, last);
+                    if best.is_none_or(|(_, lowest)| without < lowest) {
+                        best = Some((i, without));
+                    }
+                }
+                let Some((idx, remaining)) = best else {
+                    break;
+                };
+                required = remaining;
+                let closed_pos = positions.swap_remove(idx);
+                // Positions were just marked to market, so unrealized P&L is the close P&L
+                let pnl = closed_pos.unrealized_pnl;
+                let exit_comm = compute_commission(&config.commission, &closed_pos);
+                realized_equity += pnl - exit_comm;
+                unrealized -= pnl;
+
+                if has_on_position_closed {
+                    let positions_arc = Arc::new(positions.clone());
+                    let ctx = ctx_factory.build(
+                        bar,
+                        bar_idx,
+                        &positions_arc,
+                        realized_equity,
+                        &pnl_history_arc,
+                        &awareness,
+                        peak_equity,
+                    );
+                    let _ = call_fn_persistent(
+                        &engine,
+                        &mut scope,
+                        &ast,
+                        "on_position_closed",
+                        (
+                            ctx,
+                            Dynamic::from(closed_pos.clone()),
+                            Dynamic::from("forced_liquidation".to_string()),
+                        ),
+                    );
+                }
+
+                let record = build_script_trade_record(
+                    &closed_pos,
+                    bar.datetime,
+                    pnl,
+                    "forced_liquidation",
+                    dividend_tracker.remove(&closed_pos.id),
+                );
+                pnl_history.push(record.pnl);
+                trade_log.push(record);
+                pnl_dirty = true;
+                max_profit_tracker.remove(&closed_pos.id);
+                max_loss_tracker.remove(&closed_pos.id);
+                let closed_id = closed_pos.id;
+                pending_orders.retain(|o| {
+                    !matches!(
+                        &o.action,
+                        ScriptAction::Close {
+                            position_id: Some(pid),
+                            ..
+                        } if *pid == closed_id
+                    )
+                });
+            }
+        }
+
         let current_equity = realized_equity + unrealized;
         peak_equity = peak_equity.max(current_equity);
         equity_curve.push(EquityPoint {
@@ -1865,6 +2349,15 @@ pub async fn run_script_backtest(
             equity: current_equity,
             unrealized: Some(unrealized),
         });
+        if let Some(link) = sleeve {
+            link.publish(
+                bar.datetime,
+                current_equity,
+                &positions,
+                |sym| ctx_factory.symbol_close(sym, bar, bar_idx),
+                &config.greeks_params,
+            );
+        }
     }
 
     // 7. End-of-simulation
@@ -1887,13 +2380,15 @@ pub async fn run_script_backtest(
                 } else {
                     compute_close_pnl(pos, last_bar)
                 };
-                trade_log.push(build_script_trade_record(
+                let record = build_script_trade_record(
                     pos,
                     last_bar.datetime,
                     pnl,
                     "end_of_data",
-                ));
-                pnl_history.push(pnl);
+                    dividend_tracker.remove(&pos.id),
+                );
+                pnl_history.push(record.pnl);
+                trade_log.push(record);
             }
         }
     }
@@ -1960,6 +2455,8 @@ pub async fn run_script_backtest(
             trade_log,
             quality: Default::default(),
             warnings,
+            financing: financing.as_ref().map(FinancingLedger::summary),
+            working_orders: working_stats.summary(),
         },
         metadata,
         execution_time_ms: backtest_start.elapsed().as_millis() as u64,
@@ -2145,6 +2642,12 @@ fn parse_config(map: Dynamic) -> Result<ScriptConfig> {
     let (slippage, commission, min_days_between, exp_filter, trade_selector) =
         parse_engine_section(&map)?;
 
+    let greeks_params = parse_greeks_params(&map);
+    let greek_limits = parse_limits_section(&map)?;
+    let margin = parse_margin_model(&map)?;
+    let early_assignment = parse_early_assignment(&map)?;
+    let financing = parse_financing(&map)?;
+
     // Script-readable defaults
     let defaults = parse_defaults_section(&map);
 
@@ -2168,6 +2671,11 @@ fn parse_config(map: Dynamic) -> Result<ScriptConfig> {
         min_days_between_entries: min_days_between,
         expiration_filter: exp_filter,
         trade_selector,
+        greeks_params,
+        greek_limits,
+        margin,
+        early_assignment,
+        financing,
         defaults,
         procedural,
     })
@@ -2284,46 +2792,294 @@ fn parse_engine_section(
     Ok((slippage, commission, min_days, exp_filter, trade_selector))
 }
 
-fn parse_slippage(value: &Dynamic) -> Result<Slippage> {
-    // String form: "mid", "spread"
-    if let Ok(s) = value.clone().into_immutable_string() {
-        return match s.as_str() {
-            "mid" => Ok(Slippage::Mid),
-            "spread" => Ok(Slippage::Spread),
-            other => bail!("Unknown slippage model: '{other}'"),
+/// Parse `engine.risk_free_rate` / `engine.dividend_yield` (annualized decimals)
+/// used when solving implied volatility and greeks. Both default to 0.
+fn parse_greeks_params(map: &rhai::Map) -> GreeksParams {
+    let engine_map = map
+        .get("engine")
+        .and_then(|d| d.clone().try_cast::<rhai::Map>())
+        .unwrap_or_default();
+    let rate = |key: &str| {
+        engine_map
+            .get(key)
+            .and_then(|v| {
+                v.as_float()
+                    .ok()
+                    .or_else(|| v.as_int().ok().map(|i| i as f64))
+            })
+            .unwrap_or(0.0)
+    };
+    GreeksParams {
+        risk_free_rate: rate("risk_free_rate"),
+        dividend_yield: rate("dividend_yield"),
+    }
+}
+
+/// Parse `config().limits` — engine-enforced book-level greek limits.
+fn parse_limits_section(map: &rhai::Map) -> Result<GreekLimits> {
+    let Some(section) = map.get("limits").filter(|v| !v.is_unit()) else {
+        return Ok(GreekLimits::default());
+    };
+    let section = section
+        .clone()
+        .try_cast::<rhai::Map>()
+        .ok_or_else(|| anyhow::anyhow!("config().limits must be a map"))?;
+
+    let mut limits = GreekLimits::default();
+    for (key, value) in &section {
+        if value.is_unit() {
+            continue;
+        }
+        let v = value
+            .as_float()
+            .ok()
+            .or_else(|| value.as_int().ok().map(|i| i as f64))
+            .ok_or_else(|| anyhow::anyhow!("config().limits.{key} must be a number"))?;
+        let slot = match key.as_str() {
+            "max_abs_delta" => &mut limits.max_abs_delta,
+            "max_abs_gamma" => &mut limits.max_abs_gamma,
+            "max_abs_vega" => &mut limits.max_abs_vega,
+            "min_theta" => &mut limits.min_theta,
+            other => bail!(
+                "Unknown limit '{other}' in config().limits (expected one of: {})",
+                GreekLimits::KEYS.join(", ")
+            ),
         };
+        *slot = Some(v);
     }
-    // Map form: #{ type: "per_leg", per_leg: 0.05 }
-    if let Some(m) = value.clone().try_cast::<rhai::Map>() {
-        let typ = m
+    Ok(limits)
+}
+
+/// Parse `engine.margin`: `"cash"`, `"reg_t"`, `"portfolio"`, or a map such as
+/// `#{ type: "portfolio", price_shock: 0.15, vol_shock: 0.25 }`.
+fn parse_margin_model(map: &rhai::Map) -> Result<Option<MarginModel>> {
+    let Some(value) = map
+        .get("engine")
+        .and_then(|d| d.clone().try_cast::<rhai::Map>())
+        .and_then(|m| m.get("margin").cloned())
+        .filter(|v| !v.is_unit())
+    else {
+        return Ok(None);
+    };
+    let (kind, options) = if let Some(m) = value.clone().try_cast::<rhai::Map>() {
+        let kind = m
             .get("type")
             .and_then(|v| v.clone().into_immutable_string().ok())
-            .unwrap_or_default();
-        return match typ.as_str() {
-            "per_leg" => {
-                let per_leg = m
-                    .get("per_leg")
-                    .and_then(|v| v.as_float().ok())
-                    .unwrap_or(0.0);
-                Ok(Slippage::PerLeg { per_leg })
+            .ok_or_else(|| anyhow::anyhow!("engine.margin map requires a string 'type'"))?;
+        (kind.to_string(), m)
+    } else if let Ok(s) = value.into_immutable_string() {
+        (s.to_string(), rhai::Map::new())
+    } else {
+        bail!("engine.margin must be a string or map");
+    };
+    let number = |key: &str, default: f64| {
+        options
+            .get(key)
+            .and_then(|v| {
+                v.as_float()
+                    .ok()
+                    .or_else(|| v.as_int().ok().map(|i| i as f64))
+            })
+            .unwrap_or(default)
+    };
+    let model = match kind.as_str() {
+        "cash" => MarginModel::Cash,
+        "reg_t" => MarginModel::RegT,
+        "portfolio" => {
+            let price_shock = number("price_shock", DEFAULT_PRICE_SHOCK);
+            let vol_shock = number("vol_shock", DEFAULT_VOL_SHOCK);
+            let valid = price_shock > 0.0 && price_shock < 1.0 && (0.0..1.0).contains(&vol_shock);
+            if !valid {
+                bail!("engine.margin shocks must be fractions in [0, 1) (price_shock > 0)");
             }
-            "liquidity" => {
-                let fill_ratio = m
-                    .get("fill_ratio")
-                    .and_then(|v| v.as_float().ok())
-                    .unwrap_or(0.5);
-                let ref_volume = m
-                    .get("ref_volume")
-                    .and_then(|v| v.as_int().ok())
-                    .unwrap_or(1000) as u64;
-                Ok(Slippage::Liquidity {
-                    fill_ratio,
-                    ref_volume,
-                })
+            MarginModel::Portfolio {
+                price_shock,
+                vol_shock,
             }
-            "bid_ask_travel" => {
-                let pct = m.get("pct").and_then(|v| v.as_float().ok()).unwrap_or(0.25);
-                Ok(Slippage::BidAskTravel { pct })
+        }
+        other => bail!(
+            "Unknown margin model '{other}' (expected one of: {})",
+            MARGIN_MODELS.join(", ")
+        ),
+    };
+    Ok(Some(model))
+}
+
+/// Parse `engine.early_assignment`: `"dividend"`, `"probabilistic"`, or a map
+/// `#{ type: "probabilistic", seed, probability, min_extrinsic }`.
+fn parse_early_assignment(map: &rhai::Map) -> Result<Option<EarlyAssignment>> {
+    let Some(value) = map
+        .get("engine")
+        .and_then(|d| d.clone().try_cast::<rhai::Map>())
+        .and_then(|m| m.get("early_assignment").cloned())
+        .filter(|v| !v.is_unit())
+    else {
+        return Ok(None);
+    };
+    let (kind, options) = if let Some(m) = value.clone().try_cast::<rhai::Map>() {
+        let kind = m
+            .get("type")
+            .and_then(|v| v.clone().into_immutable_string().ok())
+            .ok_or_else(|| {
+                anyhow::anyhow!("engine.early_assignment map requires a string 'type'")
+            })?;
+        (kind.to_string(), m)
+    } else if let Ok(s) = value.into_immutable_string() {
+        (s.to_string(), rhai::Map::new())
+    } else {
+        bail!("engine.early_assignment must be a string or map");
+    };
+    let number = |key: &str, default: f64| {
+        options
+            .get(key)
+            .and_then(|v| {
+                v.as_float()
+                    .ok()
+                    .or_else(|| v.as_int().ok().map(|i| i as f64))
+            })
+            .unwrap_or(default)
+    };
+    let model = match kind.as_str() {
+        "dividend" => EarlyAssignment::Dividend,
+        "probabilistic" => {
+            let probability = number("probability", DEFAULT_ASSIGNMENT_PROBABILITY);
+            let min_extrinsic = number("min_extrinsic", DEFAULT_MIN_EXTRINSIC);
+            if !(0.0..=1.0).contains(&probability) {
+                bail!("engine.early_assignment probability must be in [0, 1]");
+            }
+            if min_extrinsic < 0.0 {
+                bail!("engine.early_assignment min_extrinsic must be >= 0");
+            }
+            let seed = options
+                .get("seed")
+                .and_then(|v| v.as_int().ok())
+                .unwrap_or(0);
+            EarlyAssignment::Probabilistic {
+                seed: seed as u64,
+                probability,
+                min_extrinsic,
+            }
+        }
+        other => bail!(
+            "Unknown early assignment model '{other}' (expected one of: {})",
+            EARLY_ASSIGNMENT_MODELS.join(", ")
+        ),
+    };
+    Ok(Some(model))
+}
+
+/// Parse `engine.financing`: a cash rate (number, or OHLCV symbol string for a
+/// percent-quoted rate series) or a map
+/// `#{ cash_rate, margin_rate, borrow_rate, borrow_rates: #{ SYMBOL: rate } }`.
+fn parse_financing(map: &rhai::Map) -> Result<Option<FinancingConfig>> {
+    let Some(value) = map
+        .get("engine")
+        .and_then(|d| d.clone().try_cast::<rhai::Map>())
+        .and_then(|m| m.get("financing").cloned())
+        .filter(|v| !v.is_unit())
+    else {
+        return Ok(None);
+    };
+    let as_number = |v: &Dynamic| {
+        v.as_float()
+            .ok()
+            .or_else(|| v.as_int().ok().map(|i| i as f64))
+    };
+    let parse_rate_source = |v: &Dynamic| -> Result<RateSource> {
+        if let Some(rate) = as_number(v) {
+            if !rate.is_finite() {
+                bail!("engine.financing cash_rate must be finite");
+            }
+            Ok(RateSource::Constant(rate))
+        } else if let Ok(symbol) = v.clone().into_immutable_string() {
+            Ok(RateSource::Series(symbol.trim().to_uppercase()))
+        } else {
+            bail!("engine.financing cash_rate must be a number or rate-series symbol")
+        }
+    };
+    let Some(options) = value.clone().try_cast::<rhai::Map>() else {
+        return Ok(Some(FinancingConfig {
+            cash_rate: parse_rate_source(&value)?,
+            margin_rate: DEFAULT_MARGIN_RATE,
+            borrow_rate: DEFAULT_BORROW_RATE,
+            borrow_rates: HashMap::new(),
+        }));
+    };
+    let rate = |key: &str, default: f64| -> Result<f64> {
+        let Some(v) = options.get(key).filter(|v| !v.is_unit()) else {
+            return Ok(default);
+        };
+        match as_number(v) {
+            Some(r) if r.is_finite() && r >= 0.0 => Ok(r),
+            _ => bail!("engine.financing {key} must be a number >= 0"),
+        }
+    };
+    let cash_rate = match options.get("cash_rate").filter(|v| !v.is_unit()) {
+        Some(v) => parse_rate_source(v)?,
+        None => RateSource::Constant(0.0),
+    };
+    let mut borrow_rates = HashMap::new();
+    if let Some(v) = options.get("borrow_rates").filter(|v| !v.is_unit()) {
+        let Some(by_symbol) = v.clone().try_cast::<rhai::Map>() else {
+            bail!("engine.financing borrow_rates must be a map of symbol to rate");
+        };
+        for (symbol, v) in by_symbol {
+            match as_number(&v) {
+                Some(r) if r.is_finite() && r >= 0.0 => {
+                    borrow_rates.insert(symbol.to_uppercase(), r);
+                }
+                _ => bail!("engine.financing borrow rate for '{symbol}' must be a number >= 0"),
+            }
+        }
+    }
+    Ok(Some(FinancingConfig {
+        cash_rate,
+        margin_rate: rate("margin_rate", DEFAULT_MARGIN_RATE)?,
+        borrow_rate: rate("borrow_rate", DEFAULT_BORROW_RATE)?,
+        borrow_rates,
+    }))
+}
+
+fn parse_slippage(value: &Dynamic) -> Result<Slippage> {
+    // String form: "mid", "spread"
+    if let Ok(s) = value.clone().into_immutable_string() {
+        return match s.as_str() {
+            "mid" => Ok(Slippage::Mid),
+            "spread" => Ok(Slippage::Spread),
+            other => bail!("Unknown slippage model: '{other}'"),
+        };
+    }
+    // Map form: #{ type: "per_leg", per_leg: 0.05 }
+    if let Some(m) = value.clone().try_cast::<rhai::Map>() {
+        let typ = m
+            .get("type")
+            .and_then(|v| v.clone().into_immutable_string().ok())
+            .unwrap_or_default();
+        return match typ.as_str() {
+            "per_leg" => {
+                let per_leg = m
+                    .get("per_leg")
+                    .and_then(|v| v.as_float().ok())
+                    .unwrap_or(0.0);
+                Ok(Slippage::PerLeg { per_leg })
+            }
+            "liquidity" => {
+                let fill_ratio = m
+                    .get("fill_ratio")
+                    .and_then(|v| v.as_float().ok())
+                    .unwrap_or(0.5);
+                let ref_volume = m
+                    .get("ref_volume")
+                    .and_then(|v| v.as_int().ok())
+                    .unwrap_or(1000) as u64;
+                Ok(Slippage::Liquidity {
+                    fill_ratio,
+                    ref_volume,
+                })
+            }
+            "bid_ask_travel" => {
+                let pct = m.get("pct").and_then(|v| v.as_float().ok()).unwrap_or(0.25);
+                Ok(Slippage::BidAskTravel { pct })
             }
             other => bail!("Unknown slippage type: '{other}'"),
         };
@@ -2432,6 +3188,8 @@ struct BarContextFactory {
     custom_series: Arc<Mutex<CustomSeriesStore>>,
     adjustment_timeline: Arc<crate::engine::adjustments::AdjustmentTimeline>,
     split_timeline: Arc<crate::engine::adjustments::AdjustmentTimeline>,
+    /// Primary symbol's cash dividends (per-symbol data carries its own).
+    dividends: Arc<Vec<crate::data::adjustment_store::DividendRow>>,
 }
 
 /// Position awareness snapshot for the `BarContext`.
@@ -2494,6 +3252,24 @@ pub fn compute_position_awareness(
     }
 }
 
+/// Describe the `config().limits` breach `candidate` would cause, if any.
+fn greek_limit_breach(
+    limits: &GreekLimits,
+    positions: &[ScriptPosition],
+    candidate: &ScriptPosition,
+) -> Option<String> {
+    if limits.is_empty() {
+        return None;
+    }
+    let mut before = NetGreeks::default();
+    for pos in positions {
+        before += pos.net_greeks();
+    }
+    let mut after = before;
+    after += candidate.net_greeks();
+    limits.breach(&before, &after)
+}
+
 /// Compute portfolio-level aggregate state from current positions and equity.
 fn compute_portfolio_state(
     positions: &[ScriptPosition],
@@ -2506,6 +3282,9 @@ fn compute_portfolio_state(
     let mut net_delta = 0.0_f64;
     let mut long_delta = 0.0_f64;
     let mut short_delta = 0.0_f64;
+    let mut net_gamma = 0.0_f64;
+    let mut net_theta = 0.0_f64;
+    let mut net_vega = 0.0_f64;
     let mut long_count: i64 = 0;
     let mut short_count: i64 = 0;
     let mut position_count: i64 = 0;
@@ -2525,38 +3304,39 @@ fn compute_portfolio_state(
             min_position_pnl = pos.unrealized_pnl;
         }
 
+        let greeks = pos.net_greeks();
+        net_gamma += greeks.gamma;
+        net_theta += greeks.theta;
+        net_vega += greeks.vega;
+        net_delta += greeks.delta;
+
         match &pos.inner {
             ScriptPositionInner::Options {
                 legs, multiplier, ..
             } => {
-                let mut pos_delta = 0.0;
                 for leg in legs {
-                    let leg_delta = leg.delta * leg.qty as f64 * *multiplier as f64;
-                    pos_delta += leg_delta;
+                    let leg_delta = leg.delta
+                        * leg.side.multiplier()
+                        * f64::from(leg.qty)
+                        * f64::from(*multiplier);
                     if leg_delta > 0.0 {
                         long_delta += leg_delta;
                     } else {
                         short_delta += leg_delta;
                     }
                 }
-                if pos_delta > 0.0 {
+                if greeks.delta > 0.0 {
                     long_count += 1;
-                } else if pos_delta < 0.0 {
+                } else if greeks.delta < 0.0 {
                     short_count += 1;
                 }
-                net_delta += pos_delta;
             }
-            ScriptPositionInner::Stock { side, qty, .. } => {
-                let d = match side {
-                    Side::Long => *qty as f64,
-                    Side::Short => -(*qty as f64),
-                };
-                net_delta += d;
-                if d > 0.0 {
-                    long_delta += d;
+            ScriptPositionInner::Stock { .. } => {
+                if greeks.delta > 0.0 {
+                    long_delta += greeks.delta;
                     long_count += 1;
                 } else {
-                    short_delta += d;
+                    short_delta += greeks.delta;
                     short_count += 1;
                 }
             }
@@ -2593,6 +3373,12 @@ fn compute_portfolio_state(
         net_delta,
         long_delta,
         short_delta,
+        net_gamma,
+        net_theta,
+        net_vega,
+        // Margin needs market data; filled in by `BarContextFactory::build`
+        margin_requirement: 0.0,
+        buying_power: equity + unrealized_pnl,
         position_count,
         long_count,
         short_count,
@@ -2604,6 +3390,97 @@ fn compute_portfolio_state(
 }
 
 impl BarContextFactory {
+    /// Options chain for `symbol` (the primary chain in single-symbol mode).
+    fn options_chain(&self, symbol: &str) -> Option<Arc<DatePartitionedOptions>> {
+        match &self.per_symbol_data {
+            Some(psd) => psd
+                .get(symbol)
+                .and_then(|d| d.options_by_date.as_ref().map(Arc::clone)),
+            None => self.options_by_date.as_ref().map(Arc::clone),
+        }
+    }
+
+    /// Close of `symbol` on `bar_idx`, falling back to the primary bar.
+    fn symbol_close(&self, symbol: &str, bar: &OhlcvBar, bar_idx: usize) -> f64 {
+        self.per_symbol_data
+            .as_ref()
+            .and_then(|psd| psd.get(symbol))
+            .and_then(|d| d.bars.get(bar_idx).map(|b| b.close))
+            .unwrap_or(bar.close)
+    }
+
+    /// Cash dividend per share for `symbol` going ex after this bar and no later
+    /// than the next one — the last bar on which exercising captures it.
+    fn dividend_before_next_bar(&self, symbol: &str, bar_idx: usize) -> Option<f64> {
+        let bars = self
+            .per_symbol_data
+            .as_ref()
+            .and_then(|psd| psd.get(symbol))
+            .map_or(self.price_history.as_slice(), |d| d.bars.as_slice());
+        let today = bars.get(bar_idx)?.datetime.date();
+        let next = bars.get(bar_idx + 1)?.datetime.date();
+        let amount = self.dividends_between(symbol, today, next);
+        (amount > 0.0).then_some(amount)
+    }
+
+    /// Total cash dividend per share for `symbol` going ex in `(after, through]`.
+    fn dividends_between(&self, symbol: &str, after: NaiveDate, through: NaiveDate) -> f64 {
+        let dividends = self
+            .per_symbol_data
+            .as_ref()
+            .and_then(|psd| psd.get(symbol))
+            .map_or(self.dividends.as_slice(), |d| d.dividends.as_slice());
+        dividends
+            .iter()
+            .filter(|d| d.date > after && d.date <= through)
+            .map(|d| d.amount)
+            .sum()
+    }
+
+    /// Margin requirement of `positions` under `config().engine.margin`.
+    fn margin_requirement(
+        &self,
+        positions: &[ScriptPosition],
+        bar: &OhlcvBar,
+        bar_idx: usize,
+    ) -> f64 {
+        self.config.margin.map_or(0.0, |model| {
+            model.requirement(
+                positions,
+                |sym| self.symbol_close(sym, bar, bar_idx),
+                bar.datetime.date(),
+                &self.config.greeks_params,
+            )
+        })
+    }
+
+    /// Describe the buying-power shortfall `candidate` would cause, if any.
+    fn margin_shortfall(
+        &self,
+        positions: &[ScriptPosition],
+        candidate: &ScriptPosition,
+        realized_equity: f64,
+        bar: &OhlcvBar,
+        bar_idx: usize,
+    ) -> Option<String> {
+        self.config.margin?;
+        let equity = realized_equity + positions.iter().map(|p| p.unrealized_pnl).sum::<f64>();
+        let mut book = positions.to_vec();
+        book.push(candidate.clone());
+        let required = self.margin_requirement(&book, bar, bar_idx);
+        if required <= equity {
+            return None;
+        }
+        if required.is_infinite() {
+            return Some("position cannot be carried under this margin model".to_string());
+        }
+        let available = equity - self.margin_requirement(positions, bar, bar_idx);
+        Some(format!(
+            "requires ${:.2} of buying power, ${available:.2} available",
+            required - equity + available
+        ))
+    }
+
     fn build(
         &self,
         bar: &OhlcvBar,
@@ -2614,8 +3491,10 @@ impl BarContextFactory {
         awareness: &PositionAwareness,
         peak_equity: f64,
     ) -> BarContext {
-        let portfolio =
+        let mut portfolio =
             compute_portfolio_state(positions_arc, equity, self.config.capital, peak_equity);
+        portfolio.margin_requirement = self.margin_requirement(positions_arc, bar, bar_idx);
+        portfolio.buying_power = equity + portfolio.unrealized_pnl - portfolio.margin_requirement;
         let cash = portfolio.cash;
         BarContext {
             datetime: bar.datetime,
@@ -2670,6 +3549,7 @@ struct ResolvedLeg {
     bid: f64,
     ask: f64,
     delta: f64,
+    greeks: OptionGreeks,
 }
 
 /// Resolve unresolved option legs via the filter pipeline.
@@ -2678,9 +3558,9 @@ fn resolve_option_legs(
     legs: &[LegSpec],
     options_by_date: &Option<Arc<DatePartitionedOptions>>,
     today: NaiveDate,
+    spot: f64,
     _config: &ScriptConfig,
 ) -> Vec<ResolvedLeg> {
-    use crate::engine::filters;
     use polars::prelude::*;
 
     let today_df = match options_by_date {
@@ -2691,8 +3571,48 @@ fn resolve_option_legs(
         None => return vec![],
     };
 
+    // Unresolved legs are selected together so `same_strike_as` wings can
+    // reference any other leg. Pre-resolved legs stay fixed to their exact
+    // contract and only anchor wings that reference them.
+    let selected: Vec<Option<DataFrame>> = if legs
+        .iter()
+        .any(|leg| matches!(leg, LegSpec::Unresolved { .. }))
+    {
+        let (targets, pinned): (Vec<_>, Vec<_>) = legs
+            .iter()
+            .map(|leg| match leg {
+                LegSpec::Unresolved {
+                    option_type,
+                    selector,
+                    dte,
+                    ..
+                } => ((*option_type, *selector, *dte), None),
+                LegSpec::Resolved {
+                    option_type,
+                    strike,
+                    expiration,
+                    ..
+                } => (
+                    (
+                        *option_type,
+                        LegSelector::Strike(*strike),
+                        (*expiration - today).num_days() as i32,
+                    ),
+                    Some((*strike, *expiration)),
+                ),
+            })
+            .unzip();
+        match super::helpers::select_legs_around_pinned(today_df, &targets, &pinned, Some(spot)) {
+            Some(rows) => rows,
+            None => return vec![],
+        }
+    } else {
+        vec![None; legs.len()]
+    };
+
     legs.iter()
-        .filter_map(|leg| match leg {
+        .zip(selected)
+        .filter_map(|(leg, row)| match leg {
             LegSpec::Resolved {
                 side,
                 option_type,
@@ -2727,6 +3647,12 @@ fn resolve_option_legs(
                         }
                     })
                     .unwrap_or(0.0);
+                let greeks = options_by_date
+                    .as_ref()
+                    .and_then(|opts| {
+                        opts.contract_greeks(today, *option_type, *strike, *expiration)
+                    })
+                    .unwrap_or_default();
                 Some(ResolvedLeg {
                     side: *side,
                     option_type: *option_type,
@@ -2735,42 +3661,13 @@ fn resolve_option_legs(
                     bid: *bid,
                     ask: *ask,
                     delta,
+                    greeks,
                 })
             }
             LegSpec::Unresolved {
-                side,
-                option_type,
-                delta,
-                dte,
+                side, option_type, ..
             } => {
-                let opt_code = match option_type {
-                    crate::engine::types::OptionType::Call => "c",
-                    crate::engine::types::OptionType::Put => "p",
-                };
-                let target = crate::engine::types::TargetRange {
-                    target: *delta,
-                    min: (*delta - 0.10).max(0.01),
-                    max: (*delta + 0.10).min(1.0),
-                };
-                let dte_min = (*dte - 15).max(1);
-                let dte_max = *dte + 15;
-
-                // Already date-filtered — clone the daily slice and filter by type/DTE/quotes
-                let filtered = filters::filter_leg_candidates(
-                    today_df.clone(),
-                    opt_code,
-                    dte_max,
-                    dte_min,
-                    0.05,
-                )
-                .ok()?;
-                if filtered.height() == 0 {
-                    return None;
-                }
-                let selected = filters::select_closest_delta(filtered, &target).ok()?;
-                if selected.height() == 0 {
-                    return None;
-                }
+                let selected = row?;
 
                 let get_f64 = |col: &str| -> f64 {
                     selected
@@ -2791,6 +3688,13 @@ fn resolve_option_legs(
                 let bid = get_f64("bid");
                 let ask = get_f64("ask");
                 let found_delta = get_f64("delta");
+                let greeks = OptionGreeks {
+                    iv: get_f64("iv"),
+                    delta: found_delta,
+                    gamma: get_f64("gamma"),
+                    theta: get_f64("theta"),
+                    vega: get_f64("vega"),
+                };
                 let expiration = super::types::row_to_expiration_date(&selected, 0)?;
 
                 Some(ResolvedLeg {
@@ -2801,6 +3705,7 @@ fn resolve_option_legs(
                     bid,
                     ask,
                     delta: found_delta,
+                    greeks,
                 })
             }
         })
@@ -2811,7 +3716,7 @@ fn resolve_option_legs(
 /// Returns (net_entry_cost_per_contract, legs, primary_expiration).
 fn compute_options_entry(
     resolved: &[ResolvedLeg],
-    config: &ScriptConfig,
+    slippage: &Slippage,
     effective_qty: i32,
 ) -> (f64, Vec<ScriptPositionLeg>, NaiveDate) {
     use crate::engine::pricing::fill_price;
@@ -2821,7 +3726,7 @@ fn compute_options_entry(
     let mut primary_exp = NaiveDate::from_ymd_opt(2099, 1, 1).unwrap();
 
     for leg in resolved {
-        let entry_price = fill_price(leg.bid, leg.ask, leg.side, &config.slippage);
+        let entry_price = fill_price(leg.bid, leg.ask, leg.side, slippage);
         // Long side pays (debit), short side receives (credit)
         net_cost += entry_price * leg.side.multiplier();
 
@@ -2837,6 +3742,10 @@ fn compute_options_entry(
             entry_price,
             current_price: entry_price, // starts at entry
             delta: leg.delta,
+            iv: leg.greeks.iv,
+            gamma: leg.greeks.gamma,
+            theta: leg.greeks.theta,
+            vega: leg.greeks.vega,
             qty: effective_qty,
         });
     }
@@ -2844,6 +3753,170 @@ fn compute_options_entry(
     (net_cost, legs, primary_exp)
 }
 
+/// Re-price entry legs so their net debit per share moves from `current` to
+/// `net`, spreading the difference across the legs in proportion to their
+/// quoted width (evenly when every quote is locked).
+fn reprice_entry_legs(
+    legs: &mut [ScriptPositionLeg],
+    resolved: &[ResolvedLeg],
+    current: f64,
+    net: f64,
+) {
+    let diff = net - current;
+    let total_width: f64 = resolved.iter().map(|l| (l.ask - l.bid).max(0.0)).sum();
+    for (leg, quote) in legs.iter_mut().zip(resolved) {
+        let share = if total_width > f64::EPSILON {
+            (quote.ask - quote.bid).max(0.0) / total_width
+        } else {
+            1.0 / resolved.len() as f64
+        };
+        leg.entry_price += diff * share * leg.side.multiplier();
+        leg.current_price = leg.entry_price;
+    }
+}
+
+/// Quote a working order's legs on `today`, refreshing pre-resolved legs'
+/// bid/ask from today's chain. Selector legs are pinned to the contracts they
+/// resolve to, so every later bar re-quotes the same spread. Returns `None`
+/// when any leg has no quote today.
+fn quote_working_legs(
+    legs: &mut [LegSpec],
+    options_by_date: &Option<Arc<DatePartitionedOptions>>,
+    today: NaiveDate,
+    spot: f64,
+    config: &ScriptConfig,
+) -> Option<Vec<ResolvedLeg>> {
+    let chain = options_by_date.as_ref()?;
+    for spec in legs.iter_mut() {
+        if let LegSpec::Resolved {
+            option_type,
+            strike,
+            expiration,
+            bid,
+            ask,
+            ..
+        } = spec
+        {
+            (*bid, *ask) = chain.contract_quote(today, *option_type, *strike, *expiration)?;
+        }
+    }
+    let resolved = resolve_option_legs(legs, options_by_date, today, spot, config);
+    if resolved.len() != legs.len() {
+        return None;
+    }
+    for (spec, leg) in legs.iter_mut().zip(&resolved) {
+        *spec = LegSpec::Resolved {
+            side: leg.side,
+            option_type: leg.option_type,
+            strike: leg.strike,
+            expiration: leg.expiration,
+            bid: leg.bid,
+            ask: leg.ask,
+        };
+    }
+    Some(resolved)
+}
+
+/// Net `(mid, natural)` debit per share of a spread; the natural buys long legs
+/// at the ask and sells short legs at the bid.
+fn spread_quote(legs: &[ResolvedLeg]) -> (f64, f64) {
+    legs.iter().fold((0.0, 0.0), |(mid, natural), leg| {
+        let sign = leg.side.multiplier();
+        let cross = match leg.side {
+            Side::Long => leg.ask,
+            Side::Short => leg.bid,
+        };
+        (
+            mid + f64::midpoint(leg.bid, leg.ask) * sign,
+            natural + cross * sign,
+        )
+    })
+}
+
+/// Running fill statistics for working spread limit orders.
+#[derive(Default)]
+struct WorkingOrderStats {
+    submitted: usize,
+    filled: usize,
+    expired: usize,
+    bars_to_fill: usize,
+    improvement: f64,
+}
+
+impl WorkingOrderStats {
+    fn record_fill(&mut self, bars: usize, improvement: f64) {
+        self.filled += 1;
+        self.bars_to_fill += bars;
+        self.improvement += improvement;
+    }
+
+    fn summary(&self) -> Option<WorkingOrderSummary> {
+        if self.submitted == 0 {
+            return None;
+        }
+        let per_fill = |total: f64| {
+            if self.filled == 0 {
+                0.0
+            } else {
+                total / self.filled as f64
+            }
+        };
+        Some(WorkingOrderSummary {
+            submitted: self.submitted,
+            filled: self.filled,
+            expired: self.expired,
+            fill_rate: self.filled as f64 / self.submitted as f64,
+            avg_bars_to_fill: per_fill(self.bars_to_fill as f64),
+            avg_price_improvement: per_fill(self.improvement),
+        })
+    }
+}
+
+/// Detach leg `idx` of a multi-leg options position as its own single-leg
+/// position, moving its share of the entry cost and unrealized P&L with it.
+///
+/// Returns `None` (leaving `pos` untouched) for single-leg and stock positions,
+/// which close as a whole.
+fn split_assigned_leg(pos: &mut ScriptPosition, idx: usize) -> Option<ScriptPosition> {
+    let (leg, multiplier) = match &mut pos.inner {
+        ScriptPositionInner::Options {
+            legs,
+            expiration,
+            secondary_expiration,
+            multiplier,
+        } if legs.len() > 1 && idx < legs.len() => {
+            let leg = legs.remove(idx);
+            *expiration = legs
+                .iter()
+                .map(|l| l.expiration)
+                .min()
+                .unwrap_or(*expiration);
+            if secondary_expiration.is_some_and(|exp| legs.iter().all(|l| l.expiration != exp)) {
+                *secondary_expiration = None;
+            }
+            (leg, *multiplier)
+        }
+        _ => return None,
+    };
+    let scale = leg.side.multiplier() * f64::from(leg.qty) * f64::from(multiplier);
+    let entry_cost = leg.entry_price * scale;
+    let unrealized_pnl = (leg.current_price - leg.entry_price) * scale;
+    pos.entry_cost -= entry_cost;
+    pos.unrealized_pnl -= unrealized_pnl;
+
+    Some(ScriptPosition {
+        inner: ScriptPositionInner::Options {
+            expiration: leg.expiration,
+            secondary_expiration: None,
+            multiplier,
+            legs: vec![leg],
+        },
+        entry_cost,
+        unrealized_pnl,
+        ..pos.clone()
+    })
+}
+
 /// Compute P&L for closing a position at the current bar's prices.
 ///
 /// For stocks, uses the current bar's close price.
@@ -2957,16 +4030,16 @@ fn classify_expiration(legs: &[ScriptPositionLeg], underlying_close: f64) -> Str
     for leg in legs {
         match (leg.side, leg.option_type) {
             // Short put is ITM when strike >= close → assignment
-            (Side::Short, crate::engine::types::OptionType::Put) => {
-                if leg.strike >= underlying_close {
-                    return "assignment".to_string();
-                }
+            (Side::Short, crate::engine::types::OptionType::Put)
+                if leg.strike >= underlying_close =>
+            {
+                return "assignment".to_string();
             }
             // Short call is ITM when strike <= close → called away
-            (Side::Short, crate::engine::types::OptionType::Call) => {
-                if leg.strike <= underlying_close {
-                    return "called_away".to_string();
-                }
+            (Side::Short, crate::engine::types::OptionType::Call)
+                if leg.strike <= underlying_close =>
+            {
+                return "called_away".to_string();
             }
             _ => {}
         }
@@ -3010,11 +4083,15 @@ fn lookup_option_price(
 }
 
 /// Build a `TradeRecord` from a script position close.
+///
+/// `pnl` is the price P&L; `dividend_pnl` (stock dividends received or paid
+/// while open, already credited to cash) is added to the record's total.
 fn build_script_trade_record(
     pos: &ScriptPosition,
     exit_datetime: NaiveDateTime,
     pnl: f64,
     exit_reason: &str,
+    dividend_pnl: Option<f64>,
 ) -> TradeRecord {
     use crate::engine::types::{CashflowLabel, ExitType, LegDetail};
 
@@ -3033,6 +4110,8 @@ fn build_script_trade_record(
         "assignment" => ExitType::Assignment,
         "called_away" => ExitType::CalledAway,
         "delta_exit" => ExitType::DeltaExit,
+        "forced_liquidation" => ExitType::ForcedLiquidation,
+        "early_assignment" => ExitType::EarlyAssignment,
         "end_of_data" => ExitType::Expiration, // no dedicated variant; closest match
         _ => ExitType::Signal,                 // script-defined exit reasons
     };
@@ -3093,7 +4172,7 @@ fn build_script_trade_record(
         entry_label,
         exit_amount,
         exit_label,
-        pnl,
+        pnl: pnl + dividend_pnl.unwrap_or(0.0),
         days_held: pos.days_held,
         exit_type,
         legs,
@@ -3129,6 +4208,8 @@ fn build_script_trade_record(
             ScriptPositionInner::Options { .. } => None,
         },
         group: pos.group.clone(),
+        order_group: pos.order_group.clone(),
+        dividend_pnl,
     }
 }
 
@@ -3176,6 +4257,33 @@ struct ParsedAction {
     stop_loss: Option<ExitModifier>,
     profit_target: Option<ExitModifier>,
     trailing_stop: Option<ExitModifier>,
+    group: Option<OrderGroup>,
+    /// Linked child orders (one-triggers-other).
+    triggers: Vec<ParsedAction>,
+}
+
+impl ParsedAction {
+    /// Queue this action as an order submitted on `bar_idx`.
+    fn into_pending(self, bar_idx: usize) -> PendingOrder {
+        PendingOrder {
+            action: self.action,
+            symbol: self.symbol,
+            order_type: self.order_type,
+            is_buy: self.is_buy,
+            signal: self.signal,
+            submitted_bar: bar_idx,
+            ttl: self.ttl,
+            stop_loss: self.stop_loss,
+            profit_target: self.profit_target,
+            trailing_stop: self.trailing_stop,
+            group: self.group,
+            triggers: self
+                .triggers
+                .into_iter()
+                .map(|child| child.into_pending(bar_idx))
+                .collect(),
+        }
+    }
 }
 
 /// Parse the result of `on_bar` into a list of `ParsedAction`s.
@@ -3188,214 +4296,241 @@ fn parse_bar_actions(result: &Dynamic) -> Vec<ParsedAction> {
     };
 
     arr.into_iter()
-        .filter_map(|item| {
-            let map = item.try_cast::<rhai::Map>()?;
-            let action_str = map.get("action")?.clone().into_immutable_string().ok()?;
+        .filter_map(|item| parse_action_map(&item.try_cast::<rhai::Map>()?))
+        .collect()
+}
 
-            let (action, is_buy) = match action_str.as_str() {
-                "open_stock" => {
-                    let side_str = map.get("side")?.clone().into_immutable_string().ok()?;
+/// Parse one action map, including any linked child orders under `then`.
+fn parse_action_map(map: &rhai::Map) -> Option<ParsedAction> {
+    let action_str = map.get("action")?.clone().into_immutable_string().ok()?;
+
+    let (action, is_buy) = match action_str.as_str() {
+        "open_stock" => {
+            let side_str = map.get("side")?.clone().into_immutable_string().ok()?;
+            let side = match side_str.as_str() {
+                "long" => Side::Long,
+                "short" => Side::Short,
+                _ => return None,
+            };
+            let qty = map.get("qty")?.as_int().ok()? as i32;
+            let is_buy = side == Side::Long;
+            let symbol = map
+                .get("symbol")
+                .and_then(|v| v.clone().into_immutable_string().ok())
+                .and_then(|s| {
+                    let t = s.trim();
+                    (!t.is_empty()).then(|| t.to_uppercase())
+                });
+            (ScriptAction::OpenStock { side, qty, symbol }, is_buy)
+        }
+        "close" => {
+            let position_id = map
+                .get("position_id")
+                .and_then(|v| v.as_int().ok())
+                .map(|v| v as usize);
+            let reason = map
+                .get("reason")
+                .and_then(|v| v.clone().into_immutable_string().ok())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "script_close".to_string());
+            // Close a long position = sell (is_buy=false),
+            // close a short position = buy-to-cover (is_buy=true).
+            // Default to sell (most common: closing a long).
+            let side_str = map
+                .get("side")
+                .and_then(|v| v.clone().into_immutable_string().ok());
+            let is_buy = side_str.as_deref() == Some("short");
+            (
+                ScriptAction::Close {
+                    position_id,
+                    reason,
+                },
+                is_buy,
+            )
+        }
+        "stop" => {
+            let reason = map
+                .get("reason")
+                .and_then(|v| v.clone().into_immutable_string().ok())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "stop".to_string());
+            (ScriptAction::Stop { reason }, false)
+        }
+        "cancel_orders" => {
+            let signal = map
+                .get("signal")
+                .and_then(|v| v.clone().into_immutable_string().ok())
+                .map(|s| s.to_string());
+            (ScriptAction::CancelOrders { signal }, false)
+        }
+        "open_options" | "open_spread" => {
+            let legs_arr = if let Some(legs) = map.get("legs") {
+                legs.clone().try_cast::<rhai::Array>()?
+            } else if let Some(spread) = map.get("spread") {
+                let spread_map = spread.clone().try_cast::<rhai::Map>()?;
+                spread_map.get("legs")?.clone().try_cast::<rhai::Array>()?
+            } else {
+                return None;
+            };
+            let qty = map
+                .get("qty")
+                .and_then(|v| v.as_int().ok())
+                .map(|v| v as i32);
+
+            let legs: Vec<LegSpec> = legs_arr
+                .into_iter()
+                .filter_map(|leg_dyn| {
+                    let leg = leg_dyn.try_cast::<rhai::Map>()?;
+                    let side_str = leg.get("side")?.clone().into_immutable_string().ok()?;
                     let side = match side_str.as_str() {
                         "long" => Side::Long,
                         "short" => Side::Short,
                         _ => return None,
                     };
-                    let qty = map.get("qty")?.as_int().ok()? as i32;
-                    let is_buy = side == Side::Long;
-                    let symbol = map
-                        .get("symbol")
-                        .and_then(|v| v.clone().into_immutable_string().ok())
-                        .and_then(|s| {
-                            let t = s.trim();
-                            (!t.is_empty()).then(|| t.to_uppercase())
-                        });
-                    (ScriptAction::OpenStock { side, qty, symbol }, is_buy)
-                }
-                "close" => {
-                    let position_id = map
-                        .get("position_id")
-                        .and_then(|v| v.as_int().ok())
-                        .map(|v| v as usize);
-                    let reason = map
-                        .get("reason")
-                        .and_then(|v| v.clone().into_immutable_string().ok())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| "script_close".to_string());
-                    // Close a long position = sell (is_buy=false),
-                    // close a short position = buy-to-cover (is_buy=true).
-                    // Default to sell (most common: closing a long).
-                    let side_str = map
-                        .get("side")
-                        .and_then(|v| v.clone().into_immutable_string().ok());
-                    let is_buy = side_str.as_deref() == Some("short");
-                    (
-                        ScriptAction::Close {
-                            position_id,
-                            reason,
-                        },
-                        is_buy,
-                    )
-                }
-                "stop" => {
-                    let reason = map
-                        .get("reason")
-                        .and_then(|v| v.clone().into_immutable_string().ok())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| "stop".to_string());
-                    (ScriptAction::Stop { reason }, false)
-                }
-                "cancel_orders" => {
-                    let signal = map
-                        .get("signal")
-                        .and_then(|v| v.clone().into_immutable_string().ok())
-                        .map(|s| s.to_string());
-                    (ScriptAction::CancelOrders { signal }, false)
-                }
-                "open_options" | "open_spread" => {
-                    let legs_arr = if let Some(legs) = map.get("legs") {
-                        legs.clone().try_cast::<rhai::Array>()?
-                    } else if let Some(spread) = map.get("spread") {
-                        let spread_map = spread.clone().try_cast::<rhai::Map>()?;
-                        spread_map.get("legs")?.clone().try_cast::<rhai::Array>()?
-                    } else {
-                        return None;
+                    let opt_type_str = leg
+                        .get("option_type")
+                        .and_then(|v| v.clone().into_immutable_string().ok())?;
+                    let option_type = match opt_type_str.as_str() {
+                        "call" | "c" => crate::engine::types::OptionType::Call,
+                        "put" | "p" => crate::engine::types::OptionType::Put,
+                        _ => return None,
                     };
-                    let qty = map
-                        .get("qty")
-                        .and_then(|v| v.as_int().ok())
-                        .map(|v| v as i32);
-
-                    let legs: Vec<LegSpec> = legs_arr
-                        .into_iter()
-                        .filter_map(|leg_dyn| {
-                            let leg = leg_dyn.try_cast::<rhai::Map>()?;
-                            let side_str = leg.get("side")?.clone().into_immutable_string().ok()?;
-                            let side = match side_str.as_str() {
-                                "long" => Side::Long,
-                                "short" => Side::Short,
-                                _ => return None,
-                            };
-                            let opt_type_str = leg
-                                .get("option_type")
-                                .and_then(|v| v.clone().into_immutable_string().ok())?;
-                            let option_type = match opt_type_str.as_str() {
-                                "call" | "c" => crate::engine::types::OptionType::Call,
-                                "put" | "p" => crate::engine::types::OptionType::Put,
-                                _ => return None,
-                            };
 
-                            if let Some(strike_val) = leg.get("strike") {
-                                let strike = strike_val.as_float().ok()?;
-                                let exp_str = leg
-                                    .get("expiration")?
-                                    .clone()
-                                    .into_immutable_string()
-                                    .ok()?;
-                                let expiration =
-                                    NaiveDate::parse_from_str(&exp_str, "%Y-%m-%d").ok()?;
-                                let bid = leg
-                                    .get("bid")
-                                    .and_then(|v| v.as_float().ok())
-                                    .unwrap_or(0.0);
-                                let ask = leg
-                                    .get("ask")
-                                    .and_then(|v| v.as_float().ok())
-                                    .unwrap_or(0.0);
-                                Some(LegSpec::Resolved {
-                                    side,
-                                    option_type,
-                                    strike,
-                                    expiration,
-                                    bid,
-                                    ask,
-                                })
-                            } else {
-                                let delta = leg
-                                    .get("delta")
-                                    .and_then(|v| v.as_float().ok())
-                                    .unwrap_or(0.30);
-                                let dte = leg.get("dte").and_then(|v| v.as_int().ok()).unwrap_or(45)
-                                    as i32;
-                                Some(LegSpec::Unresolved {
-                                    side,
-                                    option_type,
-                                    delta,
-                                    dte,
-                                })
-                            }
+                    // A strike without an expiration is a strike selector
+                    if let Some(strike_val) =
+                        leg.get("strike").filter(|_| leg.contains_key("expiration"))
+                    {
+                        let strike = strike_val.as_float().ok()?;
+                        let exp_str = leg
+                            .get("expiration")?
+                            .clone()
+                            .into_immutable_string()
+                            .ok()?;
+                        let expiration = NaiveDate::parse_from_str(&exp_str, "%Y-%m-%d").ok()?;
+                        let bid = leg
+                            .get("bid")
+                            .and_then(|v| v.as_float().ok())
+                            .unwrap_or(0.0);
+                        let ask = leg
+                            .get("ask")
+                            .and_then(|v| v.as_float().ok())
+                            .unwrap_or(0.0);
+                        Some(LegSpec::Resolved {
+                            side,
+                            option_type,
+                            strike,
+                            expiration,
+                            bid,
+                            ask,
                         })
-                        .collect();
-
-                    if legs.is_empty() {
-                        return None;
-                    }
-                    // Options: buy = long first leg (net debit), sell = short first leg
-                    let is_buy =
-                        legs.first()
-                            .map(|l| match l {
-                                LegSpec::Unresolved { side, .. }
-                                | LegSpec::Resolved { side, .. } => *side == Side::Long,
-                            })
-                            .unwrap_or(true);
-                    // Symbol may be on the outer action map or nested inside the
-                    // "spread" sub-map (set by SymbolContext.build_strategy).
-                    let symbol = map
-                        .get("symbol")
-                        .cloned()
-                        .or_else(|| {
-                            map.get("spread")
-                                .and_then(|s| s.clone().try_cast::<rhai::Map>())
-                                .and_then(|m| m.get("symbol").cloned())
+                    } else {
+                        let selector = super::helpers::parse_leg_selector(&leg)?;
+                        let dte = leg.get("dte").and_then(|v| v.as_int().ok()).unwrap_or(45) as i32;
+                        Some(LegSpec::Unresolved {
+                            side,
+                            option_type,
+                            selector,
+                            dte,
                         })
-                        .and_then(|v| v.into_immutable_string().ok())
-                        .and_then(|s| {
-                            let t = s.trim();
-                            (!t.is_empty()).then(|| t.to_uppercase())
-                        });
-                    (ScriptAction::OpenOptions { legs, qty, symbol }, is_buy)
-                }
-                _ => return None,
-            };
+                    }
+                })
+                .collect();
 
-            // Extract order metadata from the same map (tied to this exact action)
-            let signal = map
-                .get("signal")
-                .and_then(|v| v.clone().into_immutable_string().ok())
-                .map(|s| s.to_string());
+            if legs.is_empty() {
+                return None;
+            }
+            // Options: buy = long first leg (net debit), sell = short first leg
+            let is_buy = legs
+                .first()
+                .map(|l| match l {
+                    LegSpec::Unresolved { side, .. } | LegSpec::Resolved { side, .. } => {
+                        *side == Side::Long
+                    }
+                })
+                .unwrap_or(true);
+            // Symbol may be on the outer action map or nested inside the
+            // "spread" sub-map (set by SymbolContext.build_strategy).
+            let symbol = map
+                .get("symbol")
+                .cloned()
+                .or_else(|| {
+                    map.get("spread")
+                        .and_then(|s| s.clone().try_cast::<rhai::Map>())
+                        .and_then(|m| m.get("symbol").cloned())
+                })
+                .and_then(|v| v.into_immutable_string().ok())
+                .and_then(|s| {
+                    let t = s.trim();
+                    (!t.is_empty()).then(|| t.to_uppercase())
+                });
+            (ScriptAction::OpenOptions { legs, qty, symbol }, is_buy)
+        }
+        _ => return None,
+    };
 
-            let ttl = map
-                .get("ttl")
-                .and_then(|v| v.as_int().ok())
-                .map(|v| v as usize);
+    // Extract order metadata from the same map (tied to this exact action)
+    let signal = map
+        .get("signal")
+        .and_then(|v| v.clone().into_immutable_string().ok())
+        .map(|s| s.to_string());
 
-            let order_type = extract_order_type(&map);
+    let ttl = map
+        .get("ttl")
+        .and_then(|v| v.as_int().ok())
+        .map(|v| v as usize);
 
-            let stop_loss = extract_exit_modifier(&map, "stop_loss_pct", "stop_loss_dollar");
-            let profit_target =
-                extract_exit_modifier(&map, "profit_target_pct", "profit_target_dollar");
-            let trailing_stop =
-                extract_exit_modifier(&map, "trailing_stop_pct", "trailing_stop_dollar");
+    let order_type = if matches!(action, ScriptAction::OpenOptions { .. }) {
+        extract_options_order_type(map)
+    } else {
+        extract_order_type(map)
+    };
 
-            // Extract symbol from the action (set by SymbolContext.build_strategy)
-            let symbol = match &action {
-                ScriptAction::OpenStock { symbol, .. }
-                | ScriptAction::OpenOptions { symbol, .. } => symbol.clone(),
-                _ => None,
-            };
+    let stop_loss = extract_exit_modifier(map, "stop_loss_pct", "stop_loss_dollar");
+    let profit_target = extract_exit_modifier(map, "profit_target_pct", "profit_target_dollar");
+    let trailing_stop = extract_exit_modifier(map, "trailing_stop_pct", "trailing_stop_dollar");
 
-            Some(ParsedAction {
-                action,
-                symbol,
-                order_type,
-                is_buy,
-                signal,
-                ttl,
-                stop_loss,
-                profit_target,
-                trailing_stop,
-            })
+    let group = map
+        .get("order_group")
+        .and_then(|v| v.clone().into_immutable_string().ok())
+        .map(|id| OrderGroup {
+            id: id.to_string(),
+            oco: map
+                .get("oco")
+                .and_then(|v| v.as_bool().ok())
+                .unwrap_or(false),
+        });
+    let triggers = map
+        .get("then")
+        .and_then(|v| v.clone().try_cast::<rhai::Array>())
+        .map(|children| {
+            children
+                .into_iter()
+                .filter_map(|child| parse_action_map(&child.try_cast::<rhai::Map>()?))
+                .collect()
         })
-        .collect()
+        .unwrap_or_default();
+
+    // Extract symbol from the action (set by SymbolContext.build_strategy)
+    let symbol = match &action {
+        ScriptAction::OpenStock { symbol, .. } | ScriptAction::OpenOptions { symbol, .. } => {
+            symbol.clone()
+        }
+        _ => None,
+    };
+
+    Some(ParsedAction {
+        action,
+        symbol,
+        order_type,
+        is_buy,
+        signal,
+        ttl,
+        stop_loss,
+        profit_target,
+        trailing_stop,
+        group,
+        triggers,
+    })
 }
 
 /// Extract `OrderType` from an action map. Falls back to `Market` with a warning
@@ -3439,6 +4574,26 @@ fn extract_order_type(map: &rhai::Map) -> OrderType {
     }
 }
 
+/// Extract the order type for an options entry. A `"limit"` order becomes a
+/// working spread limit (`limit_offset` below the mid, re-priced by
+/// `limit_step` each bar); other order types behave as for stock.
+fn extract_options_order_type(map: &rhai::Map) -> OrderType {
+    let order_type_str = map
+        .get("order_type")
+        .and_then(|v| v.clone().into_immutable_string().ok());
+    if order_type_str.as_deref() != Some("limit") {
+        return extract_order_type(map);
+    }
+    OrderType::SpreadLimit {
+        offset: super::helpers::map_number(map, "limit_offset").unwrap_or(0.0),
+        step: super::helpers::map_number(map, "limit_step")
+            .unwrap_or(0.0)
+            .max(0.0),
+        limit: None,
+        start_natural: None,
+    }
+}
+
 fn extract_exit_modifier(map: &rhai::Map, pct_key: &str, dollar_key: &str) -> Option<ExitModifier> {
     if let Some(v) = map.get(pct_key) {
         let pct = v
@@ -3952,10 +5107,8 @@ async fn load_multi_symbol_data(
                 .collect()
         };
 
-        let indicator_store = Arc::new(IndicatorStore::build(
-            &config.declared_indicators,
-            &indicator_bars,
-        )?);
+        let mut indicator_store =
+            IndicatorStore::build(&config.declared_indicators, &indicator_bars)?;
 
         // Only load options when the script needs them (avoids I/O and warnings
         // for stock-only multi-symbol scripts).
@@ -3966,7 +5119,11 @@ async fn load_multi_symbol_data(
             {
                 Ok(df) if df.height() > 0 => {
                     let (pt, _days, di) = crate::engine::price_table::build_price_table(&df)?;
-                    let obd = DatePartitionedOptions::from_df(&df, &config.expiration_filter)?;
+                    let obd = DatePartitionedOptions::from_df(
+                        &df,
+                        &config.expiration_filter,
+                        &config.greeks_params,
+                    )?;
                     (Some(Arc::new(obd)), Some(Arc::new(pt)), Some(Arc::new(di)))
                 }
                 Ok(_) => {
@@ -3983,13 +5140,25 @@ async fn load_multi_symbol_data(
             (None, None, None)
         };
 
+        insert_implied_vol_indicators(
+            &mut indicator_store,
+            config,
+            sym,
+            &indicator_bars,
+            options_by_date.as_deref(),
+            data_loader,
+            warnings,
+        )
+        .await?;
+
         per_symbol.insert(
             sym.clone(),
             PerSymbolData {
                 bars: Arc::new(bars),
-                indicator_store,
+                indicator_store: Arc::new(indicator_store),
                 split_timeline,
                 adjustment_timeline,
+                dividends: Arc::new(dividends),
                 options_by_date,
                 price_table,
                 date_index,
@@ -4001,6 +5170,70 @@ async fn load_multi_symbol_data(
     Ok((per_symbol, master_dates_vec))
 }
 
+/// Fill `atm_iv`, `iv_rank`, `iv_percentile`, and the options-structure
+/// indicators from the symbol's options chain.
+///
+/// Reuses the partitioned chain when the script already loads options;
+/// otherwise loads it just for these series. Without a chain the IV
+/// indicators stay absent (reading as `()`) and a warning is recorded.
+async fn insert_implied_vol_indicators(
+    store: &mut IndicatorStore,
+    config: &ScriptConfig,
+    symbol: &str,
+    bars: &[OhlcvBar],
+    options_by_date: Option<&DatePartitionedOptions>,
+    data_loader: &dyn DataLoader,
+    warnings: &mut Vec<String>,
+) -> Result<()> {
+    if !super::indicators::needs_implied_vol(&config.declared_indicators) {
+        return Ok(());
+    }
+
+    let loaded;
+    let chain = if let Some(obd) = options_by_date {
+        obd
+    } else {
+        match data_loader
+            .load_options(symbol, config.start_date, config.end_date)
+            .await
+        {
+            Ok(df) if df.height() > 0 => {
+                loaded = DatePartitionedOptions::from_df(
+                    &df,
+                    &ExpirationFilter::Any,
+                    &config.greeks_params,
+                )?;
+                &loaded
+            }
+            Ok(_) => {
+                warnings.push(format!(
+                    "{symbol}: options data empty — IV indicators will return ()"
+                ));
+                return Ok(());
+            }
+            Err(e) => {
+                warnings.push(format!(
+                    "{symbol}: no options data for IV indicators ({e:#}) — they will return ()"
+                ));
+                return Ok(());
+            }
+        }
+    };
+
+    let dates: Vec<NaiveDate> = bars.iter().map(|b| b.datetime.date()).collect();
+    let atm_iv = chain.atm_iv_series(dates.iter().copied());
+    store.insert_implied_vol(&config.declared_indicators, &atm_iv)?;
+    if super::indicators::needs_options_structure(&config.declared_indicators) {
+        let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+        store.insert_options_structure(
+            &config.declared_indicators,
+            &chain.structure_series(&dates),
+            &crate::engine::greeks::iv_rv_spread(&atm_iv, &closes),
+        )?;
+    }
+    Ok(())
+}
+
 /// Convert a Polars DataFrame (with OHLCV columns) to `Vec<OhlcvBar>`.
 ///
 /// Reuses `stock_sim::bars_from_df` for datetime handling, then converts
@@ -0,0 +1,324 @@
+//! Financing ledger for script backtests.
+//!
+//! Without `engine.financing`, idle cash earns nothing and short stock is free
+//! to carry. With it, the engine accrues three components into cash on every
+//! date change, each tracked separately in the result's `financing` summary:
+//!
+//! - Cash interest: a positive cash balance earns the risk-free rate, either a
+//!   constant or the last-known value of a rate series loaded like any other
+//!   OHLCV symbol (e.g. `^IRX`, closes quoted in percent).
+//! - Margin interest: a negative cash balance is a margin loan and pays
+//!   `margin_rate`.
+//! - Borrow fees: short stock pays its symbol's hard-to-borrow rate (or the
+//!   general `borrow_rate`) on the current market value of the shares.
+//!
+//! The cash balance is realized equity less the net premium and stock cost of
+//! open positions. Short-sale proceeds are held by the lender as collateral, so
+//! they are excluded. Interest accrues on the balance held at the previous
+//! close for the calendar days elapsed, using an actual/365 day count.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+
+use crate::engine::types::{FinancingSummary, Side};
+use crate::scripting::types::{ScriptPosition, ScriptPositionInner};
+
+/// Day-count basis for all financing accruals.
+pub const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Rate keys accepted in `engine.financing` (and the DSL `financing` line).
+pub const FINANCING_KEYS: [&str; 3] = ["cash_rate", "margin_rate", "borrow_rate"];
+
+/// Default annual rate on margin loans.
+pub const DEFAULT_MARGIN_RATE: f64 = 0.08;
+
+/// Default general-collateral borrow rate for short stock.
+pub const DEFAULT_BORROW_RATE: f64 = 0.0025;
+
+/// Where the risk-free rate credited on cash comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateSource {
+    /// Constant annual rate as a fraction (0.05 = 5%).
+    Constant(f64),
+    /// OHLCV symbol whose closes are annual yields in percent.
+    Series(String),
+}
+
+/// Financing settings from `config().engine.financing`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinancingConfig {
+    pub cash_rate: RateSource,
+    /// Annual rate charged on negative cash balances.
+    pub margin_rate: f64,
+    /// Annual general-collateral borrow rate for short stock.
+    pub borrow_rate: f64,
+    /// Per-symbol hard-to-borrow rates overriding `borrow_rate`.
+    pub borrow_rates: HashMap<String, f64>,
+}
+
+impl FinancingConfig {
+    /// Annual borrow rate for shorting `symbol`.
+    fn borrow_rate_for(&self, symbol: &str) -> f64 {
+        self.borrow_rates
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.borrow_rate)
+    }
+}
+
+/// Date-ordered annual rates (fractions), looked up by last-known value.
+#[derive(Debug, Clone, Default)]
+pub struct RateSeries {
+    points: Vec<(NaiveDate, f64)>,
+}
+
+impl RateSeries {
+    /// Build from `(date, annual yield in percent)` observations.
+    pub fn from_percent(observations: impl IntoIterator<Item = (NaiveDate, f64)>) -> Self {
+        let mut points: Vec<(NaiveDate, f64)> = observations
+            .into_iter()
+            .filter(|(_, pct)| pct.is_finite())
+            .map(|(date, pct)| (date, pct / 100.0))
+            .collect();
+        points.sort_by_key(|(date, _)| *date);
+        points.dedup_by_key(|(date, _)| *date);
+        Self { points }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Rate in effect on `date`: the last observation at or before it, or the
+    /// first observation for dates before the series starts.
+    pub fn rate_at(&self, date: NaiveDate) -> f64 {
+        let idx = self.points.partition_point(|(d, _)| *d <= date);
+        self.points
+            .get(idx.saturating_sub(1))
+            .map_or(0.0, |(_, rate)| *rate)
+    }
+}
+
+/// Balances captured at a close, accrued when the next date arrives.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    date: NaiveDate,
+    cash: f64,
+    /// Market value of short stock by symbol.
+    short_value: BTreeMap<String, f64>,
+}
+
+/// Running financing accruals for one backtest.
+#[derive(Debug, Clone)]
+pub struct FinancingLedger {
+    config: FinancingConfig,
+    /// Loaded series when `config.cash_rate` is `RateSource::Series`.
+    series: Option<RateSeries>,
+    last: Option<Snapshot>,
+    summary: FinancingSummary,
+}
+
+impl FinancingLedger {
+    pub fn new(config: FinancingConfig, series: Option<RateSeries>) -> Self {
+        Self {
+            config,
+            series,
+            last: None,
+            summary: FinancingSummary::default(),
+        }
+    }
+
+    /// Accrue financing since the previous close and snapshot today's balances.
+    ///
+    /// `realized_equity` is the equity before this accrual; the returned net
+    /// amount (credit positive) must be added to it by the caller. `spot`
+    /// returns the current close for a symbol.
+    pub fn settle(
+        &mut self,
+        today: NaiveDate,
+        positions: &[ScriptPosition],
+        realized_equity: f64,
+        spot: impl Fn(&str) -> f64,
+    ) -> f64 {
+        let accrued = match &self.last {
+            Some(prev) if today > prev.date => {
+                let years = (today - prev.date).num_days() as f64 / DAYS_PER_YEAR;
+                let cash_rate = match (&self.config.cash_rate, &self.series) {
+                    (RateSource::Constant(rate), _) => *rate,
+                    (RateSource::Series(_), Some(series)) => series.rate_at(prev.date),
+                    (RateSource::Series(_), None) => 0.0,
+                };
+                let (interest, margin) = if prev.cash >= 0.0 {
+                    (prev.cash * cash_rate * years, 0.0)
+                } else {
+                    (0.0, -prev.cash * self.config.margin_rate * years)
+                };
+                self.summary.cash_interest += interest;
+                self.summary.margin_interest += margin;
+                let mut fees = 0.0;
+                for (symbol, value) in &prev.short_value {
+                    let fee = value * self.config.borrow_rate_for(symbol) * years;
+                    *self
+                        .summary
+                        .borrow_fees_by_symbol
+                        .entry(symbol.clone())
+                        .or_default() += fee;
+                    fees += fee;
+                }
+                self.summary.borrow_fees += fees;
+                interest - margin - fees
+            }
+            _ => 0.0,
+        };
+        // Intraday bars share a date and only refresh the snapshot, so the next
+        // date change accrues on the balances held at the last bar of the day.
+        let cash = cash_balance(positions, realized_equity + accrued);
+        self.last = Some(Snapshot {
+            date: today,
+            cash,
+            short_value: short_stock_value(positions, spot),
+        });
+        accrued
+    }
+
+    /// Accrued totals, with `net` filled in.
+    pub fn summary(&self) -> FinancingSummary {
+        let mut summary = self.summary.clone();
+        summary.net = summary.cash_interest - summary.margin_interest - summary.borrow_fees;
+        summary
+    }
+}
+
+/// Realized equity less the net premium paid and stock bought for open
+/// positions. Short-stock proceeds are excluded (held as collateral).
+fn cash_balance(positions: &[ScriptPosition], realized_equity: f64) -> f64 {
+    let committed: f64 = positions
+        .iter()
+        .filter(|pos| {
+            !matches!(
+                pos.inner,
+                ScriptPositionInner::Stock {
+                    side: Side::Short,
+                    ..
+                }
+            )
+        })
+        .map(|pos| pos.entry_cost)
+        .sum();
+    realized_equity - committed
+}
+
+/// Market value of short stock per symbol.
+fn short_stock_value(
+    positions: &[ScriptPosition],
+    spot: impl Fn(&str) -> f64,
+) -> BTreeMap<String, f64> {
+    let mut values = BTreeMap::new();
+    for pos in positions {
+        if let ScriptPositionInner::Stock {
+            side: Side::Short,
+            qty,
+            ..
+        } = pos.inner
+        {
+            *values.entry(pos.symbol.clone()).or_default() += spot(&pos.symbol) * f64::from(qty);
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+    }
+
+    fn stock(symbol: &str, side: Side, qty: i32, entry_price: f64) -> ScriptPosition {
+        ScriptPosition {
+            id: 1,
+            symbol: symbol.to_string(),
+            entry_date: date(1),
+            inner: ScriptPositionInner::Stock {
+                side,
+                qty,
+                entry_price,
+            },
+            entry_cost: entry_price * f64::from(qty) * side.multiplier(),
+            unrealized_pnl: 0.0,
+            days_held: 0,
+            current_date: date(1),
+            entry_bar_idx: 0,
+            source: "script".to_string(),
+            implicit: false,
+            order_group: None,
+            group: None,
+            trailing_stop: None,
+        }
+    }
+
+    fn config(cash_rate: RateSource) -> FinancingConfig {
+        FinancingConfig {
+            cash_rate,
+            margin_rate: 0.10,
+            borrow_rate: 0.01,
+            borrow_rates: HashMap::from([("GME".to_string(), 0.50)]),
+        }
+    }
+
+    #[test]
+    fn idle_cash_earns_constant_rate() {
+        let mut ledger = FinancingLedger::new(config(RateSource::Constant(0.05)), None);
+        assert_eq!(ledger.settle(date(1), &[], 100_000.0, |_| 0.0), 0.0);
+        let accrued = ledger.settle(date(4), &[], 100_000.0, |_| 0.0);
+        let expected = 100_000.0 * 0.05 * 3.0 / DAYS_PER_YEAR;
+        assert!((accrued - expected).abs() < 1e-9);
+        assert!((ledger.summary().cash_interest - expected).abs() < 1e-9);
+        assert!((ledger.summary().net - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn negative_cash_pays_margin_interest() {
+        let mut ledger = FinancingLedger::new(config(RateSource::Constant(0.05)), None);
+        // $15,000 of stock on $10,000 of equity → $5,000 margin loan
+        let book = [stock("SPY", Side::Long, 100, 150.0)];
+        ledger.settle(date(1), &book, 10_000.0, |_| 150.0);
+        let accrued = ledger.settle(date(2), &book, 10_000.0, |_| 150.0);
+        let expected = 5_000.0 * 0.10 / DAYS_PER_YEAR;
+        assert!((accrued + expected).abs() < 1e-9);
+        let summary = ledger.summary();
+        assert_eq!(summary.cash_interest, 0.0);
+        assert!((summary.margin_interest - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn short_stock_pays_symbol_borrow_fee_on_market_value() {
+        let mut ledger = FinancingLedger::new(config(RateSource::Constant(0.0)), None);
+        let book = [
+            stock("GME", Side::Short, 100, 20.0),
+            stock("SPY", Side::Short, 10, 500.0),
+        ];
+        let spot = |s: &str| if s == "GME" { 30.0 } else { 500.0 };
+        ledger.settle(date(1), &book, 10_000.0, spot);
+        ledger.settle(date(2), &book, 10_000.0, spot);
+        let summary = ledger.summary();
+        let gme = 3_000.0 * 0.50 / DAYS_PER_YEAR;
+        let spy = 5_000.0 * 0.01 / DAYS_PER_YEAR;
+        assert!((summary.borrow_fees_by_symbol["GME"] - gme).abs() < 1e-9);
+        assert!((summary.borrow_fees_by_symbol["SPY"] - spy).abs() < 1e-9);
+        assert!((summary.borrow_fees - gme - spy).abs() < 1e-9);
+        // Short proceeds are collateral, so the cash balance stays at equity
+        assert_eq!(summary.margin_interest, 0.0);
+    }
+
+    #[test]
+    fn rate_series_uses_last_known_value() {
+        let series = RateSeries::from_percent([(date(5), 4.0), (date(2), 5.0)]);
+        assert!((series.rate_at(date(1)) - 0.05).abs() < 1e-12);
+        assert!((series.rate_at(date(2)) - 0.05).abs() < 1e-12);
+        assert!((series.rate_at(date(4)) - 0.05).abs() < 1e-12);
+        assert!((series.rate_at(date(9)) - 0.04).abs() < 1e-12);
+    }
+}
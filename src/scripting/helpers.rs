@@ -9,12 +9,15 @@
 
 use std::sync::Arc;
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
+use polars::prelude::*;
 use rhai::Dynamic;
 
 use super::indicators::IndicatorStore;
 use super::options_cache::DatePartitionedOptions;
-use super::types::BarContext;
+use super::types::{BarContext, LegSelector};
+use crate::engine::greeks::UNDERLYING_PRICE_COL;
+use crate::engine::types::OptionType;
 
 // ---------------------------------------------------------------------------
 // Shared indicator helpers — used by both BarContext and SymbolContext
@@ -58,6 +61,57 @@ pub(crate) fn indicator_lookup_multi(
     }
 }
 
+/// Look up the walk-forward HMM regime as `#{state, label, posterior}`.
+///
+/// Returns `()` during warmup, when `regime:<n_states>:<lookback>` was not
+/// pre-computed, or for any `method` other than `"hmm"`.
+pub(crate) fn regime_lookup(
+    store: &IndicatorStore,
+    bar_idx: usize,
+    method: &str,
+    n_states: i64,
+    lookback: i64,
+) -> Dynamic {
+    if !method.eq_ignore_ascii_case("hmm") {
+        return Dynamic::UNIT;
+    }
+    let state = indicator_lookup_multi(store, bar_idx, "regime", &[n_states, lookback]);
+    let posterior =
+        indicator_lookup_multi(store, bar_idx, "regime_posterior", &[n_states, lookback]);
+    let (Ok(state), Ok(posterior)) = (state.as_float(), posterior.as_float()) else {
+        return Dynamic::UNIT;
+    };
+    let state = state as usize;
+    let mut map = rhai::Map::new();
+    map.insert("state".into(), Dynamic::from(state as i64));
+    map.insert(
+        "label".into(),
+        Dynamic::from(crate::engine::hmm::volatility_regime_label(
+            state,
+            n_states as usize,
+        )),
+    );
+    map.insert("posterior".into(), Dynamic::from(posterior));
+    Dynamic::from_map(map)
+}
+
+/// Look up just the walk-forward HMM regime label (e.g. `"high_vol"`).
+pub(crate) fn regime_label_lookup(
+    store: &IndicatorStore,
+    bar_idx: usize,
+    n_states: i64,
+    lookback: i64,
+) -> Dynamic {
+    let state = indicator_lookup_multi(store, bar_idx, "regime", &[n_states, lookback]);
+    match state.as_float() {
+        Ok(state) => Dynamic::from(crate::engine::hmm::volatility_regime_label(
+            state as usize,
+            n_states as usize,
+        )),
+        Err(_) => Dynamic::UNIT,
+    }
+}
+
 /// Look up a multi-param indicator using a named parameter map (matching `BarContext::indicator_with`).
 ///
 /// Extracts known param keys in a fixed order, converting floats to scaled integers
@@ -138,51 +192,211 @@ pub(crate) fn indicators_all_ready(
 // Shared options leg resolution — used by both BarContext and SymbolContext
 // ---------------------------------------------------------------------------
 
-/// Resolve a single options leg to a specific contract via the filter pipeline.
-pub(super) fn resolve_option_leg(
-    option_type: &str,
-    target: &crate::engine::types::TargetRange,
-    dte_min: i32,
-    dte_max: i32,
-    options_by_date: &Option<Arc<DatePartitionedOptions>>,
-    datetime: NaiveDateTime,
-) -> Dynamic {
-    use crate::engine::filters;
+/// Leg-map keys that choose a strike. At most one may be set per leg.
+const LEG_SELECTOR_KEYS: &[&str] = &[
+    "delta",
+    "strike",
+    "moneyness",
+    "atm_offset",
+    "premium",
+    "same_strike_as",
+];
+
+/// Read a numeric map value, accepting both floats and integers.
+pub(crate) fn map_number(map: &rhai::Map, key: &str) -> Option<f64> {
+    let value = map.get(key)?;
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|v| v as f64))
+}
 
-    let today = datetime.date();
+/// Parse the strike selector of a leg map.
+///
+/// With no selector key the leg targets 0.30 delta. Returns `None` when more
+/// than one selector is set or the value has the wrong type.
+pub(crate) fn parse_leg_selector(leg: &rhai::Map) -> Option<LegSelector> {
+    let present: Vec<&str> = LEG_SELECTOR_KEYS
+        .iter()
+        .copied()
+        .filter(|key| leg.contains_key(*key))
+        .collect();
+    match present.as_slice() {
+        [] => Some(LegSelector::Delta(0.30)),
+        ["delta"] => map_number(leg, "delta").map(LegSelector::Delta),
+        ["strike"] => map_number(leg, "strike").map(LegSelector::Strike),
+        ["moneyness"] => map_number(leg, "moneyness").map(LegSelector::Moneyness),
+        ["atm_offset"] => leg
+            .get("atm_offset")?
+            .as_int()
+            .ok()
+            .map(LegSelector::AtmOffset),
+        ["premium"] => map_number(leg, "premium").map(LegSelector::Premium),
+        ["same_strike_as"] => {
+            let anchor = usize::try_from(leg.get("same_strike_as")?.as_int().ok()?).ok()?;
+            let width = if leg.contains_key("width") {
+                map_number(leg, "width")?
+            } else {
+                0.0
+            };
+            Some(LegSelector::SameStrikeAs { leg: anchor, width })
+        }
+        _ => None,
+    }
+}
 
-    let today_df = match options_by_date {
-        Some(opts) => match opts.get(today) {
-            Some(df) => df,
-            None => return Dynamic::UNIT,
-        },
-        None => return Dynamic::UNIT,
-    };
+/// Underlying price carried by the chain itself, when the data has one.
+fn chain_spot(df: &DataFrame) -> Option<f64> {
+    let spots = df
+        .column(UNDERLYING_PRICE_COL)
+        .ok()?
+        .cast(&DataType::Float64)
+        .ok()?;
+    let found = spots.f64().ok()?.into_iter().flatten().find(|s| *s > 0.0);
+    found
+}
 
-    let min_bid_ask = 0.05;
-    let opt_type_code = match option_type.to_lowercase().as_str() {
-        "call" | "c" => "c",
-        "put" | "p" => "p",
-        _ => return Dynamic::UNIT,
+/// Resolve legs against one day's chain, returning a single-row frame per leg.
+///
+/// Legs with absolute selectors resolve first; `SameStrikeAs` legs resolve once
+/// the leg they reference has, so a wing may reference a leg listed after it.
+/// Spot for moneyness/ATM selectors comes from the chain's `underlying_price`
+/// column when present, else `spot`. Returns `None` if any leg cannot be resolved.
+pub(crate) fn select_leg_contracts(
+    today_df: &DataFrame,
+    legs: &[(OptionType, LegSelector, i32)],
+    spot: Option<f64>,
+) -> Option<Vec<DataFrame>> {
+    let pinned = vec![None; legs.len()];
+    select_legs_around_pinned(today_df, legs, &pinned, spot)?
+        .into_iter()
+        .collect()
+}
+
+/// Like [`select_leg_contracts`], but legs with a `pinned` (strike, expiration)
+/// are already fixed to that exact contract: they are never re-selected (and
+/// come back as `None`), only serving as anchors for `SameStrikeAs` wings.
+pub(crate) fn select_legs_around_pinned(
+    today_df: &DataFrame,
+    legs: &[(OptionType, LegSelector, i32)],
+    pinned: &[Option<(f64, NaiveDate)>],
+    spot: Option<f64>,
+) -> Option<Vec<Option<DataFrame>>> {
+    let spot = chain_spot(today_df).or(spot).filter(|s| *s > 0.0);
+    let mut selected: Vec<Option<DataFrame>> = vec![None; legs.len()];
+    let pending = |selected: &[Option<DataFrame>], i: usize| {
+        selected[i].is_none() && pinned.get(i).is_none_or(Option::is_none)
     };
 
-    let filtered = match filters::filter_leg_candidates(
+    while (0..legs.len()).any(|i| pending(&selected, i)) {
+        let mut progressed = false;
+        for (i, &(option_type, selector, dte)) in legs.iter().enumerate() {
+            if !pending(&selected, i) {
+                continue;
+            }
+            let anchor = if let LegSelector::SameStrikeAs { leg, .. } = selector {
+                let (strike, expiration) = if let Some(&Some(fixed)) = pinned.get(leg) {
+                    (fixed.0, Some(fixed.1))
+                } else {
+                    let Some(anchor_df) = selected.get(leg)?.as_ref() else {
+                        continue;
+                    };
+                    let strike = anchor_df.column("strike").ok()?.f64().ok()?.get(0)?;
+                    (strike, super::types::row_to_expiration_date(anchor_df, 0))
+                };
+                Some((strike, expiration.filter(|_| legs[leg].2 == dte)))
+            } else {
+                None
+            };
+            selected[i] = Some(select_leg_contract(
+                today_df,
+                option_type,
+                selector,
+                dte,
+                spot,
+                anchor,
+            )?);
+            progressed = true;
+        }
+        if !progressed {
+            return None;
+        }
+    }
+    Some(selected)
+}
+
+/// Resolve one leg. `anchor` is the referenced leg's strike (and expiration,
+/// when it should be shared) for `SameStrikeAs` selectors.
+fn select_leg_contract(
+    today_df: &DataFrame,
+    option_type: OptionType,
+    selector: LegSelector,
+    dte: i32,
+    spot: Option<f64>,
+    anchor: Option<(f64, Option<NaiveDate>)>,
+) -> Option<DataFrame> {
+    use crate::engine::filters;
+
+    let candidates = filters::filter_leg_candidates(
         today_df.clone(),
-        opt_type_code,
-        dte_max,
-        dte_min,
-        min_bid_ask,
-    ) {
-        Ok(f) if f.height() > 0 => f,
-        _ => return Dynamic::UNIT,
-    };
+        option_type.as_str(),
+        dte + 15,
+        (dte - 15).max(1),
+        0.05,
+    )
+    .ok()?;
+    if candidates.height() == 0 {
+        return None;
+    }
 
-    let selected = match filters::select_closest_delta(filtered, target) {
-        Ok(s) if s.height() > 0 => s,
-        _ => return Dynamic::UNIT,
+    // Non-delta selectors choose a strike within a single expiration
+    let expiration_chain = |df: DataFrame| match anchor {
+        Some((_, Some(expiration))) => df
+            .lazy()
+            .filter(col("expiration").cast(DataType::Date).eq(lit(expiration)))
+            .collect()
+            .map_err(anyhow::Error::from),
+        _ => filters::select_nearest_expiration(df, dte),
+    };
+    // Moneyness and ATM offsets count positive toward out of the money
+    let otm_sign = match option_type {
+        OptionType::Call => 1.0,
+        OptionType::Put => -1.0,
     };
 
-    super::types::row_to_option_map(&selected, 0, today)
+    let selected = match selector {
+        LegSelector::Delta(delta) => {
+            let target = crate::engine::types::TargetRange {
+                target: delta,
+                min: (delta - 0.10).max(0.01),
+                max: (delta + 0.10).min(1.0),
+            };
+            filters::select_closest_delta(candidates, &target)
+        }
+        LegSelector::Strike(strike) => expiration_chain(candidates)
+            .and_then(|chain| filters::select_closest_strike(chain, strike)),
+        LegSelector::Moneyness(moneyness) => {
+            let target = spot? * (1.0 + otm_sign * moneyness);
+            expiration_chain(candidates)
+                .and_then(|chain| filters::select_closest_strike(chain, target))
+        }
+        LegSelector::AtmOffset(offset) => {
+            let spot = spot?;
+            let offset = if otm_sign > 0.0 { offset } else { -offset };
+            expiration_chain(candidates)
+                .and_then(|chain| filters::select_strike_offset(chain, spot, offset))
+        }
+        LegSelector::Premium(premium) => expiration_chain(candidates)
+            .and_then(|chain| filters::select_closest_premium(chain, premium)),
+        LegSelector::SameStrikeAs { width, .. } => {
+            let target = anchor?.0 + width;
+            expiration_chain(candidates)
+                .and_then(|chain| filters::select_closest_strike(chain, target))
+        }
+    }
+    .ok()?;
+
+    (selected.height() > 0).then(|| selected.head(Some(1)))
 }
 
 /// Build a multi-leg options strategy from a legs array, resolving each leg.
@@ -191,11 +405,16 @@ pub(crate) fn build_strategy_from_legs(
     legs: rhai::Array,
     options_by_date: &Option<Arc<DatePartitionedOptions>>,
     datetime: NaiveDateTime,
+    spot: f64,
     symbol: Option<&str>,
 ) -> Dynamic {
-    let mut resolved_legs = Vec::new();
-    let mut net_premium = 0.0;
+    let today = datetime.date();
+    let Some(today_df) = options_by_date.as_ref().and_then(|opts| opts.get(today)) else {
+        return Dynamic::UNIT;
+    };
 
+    let mut targets = Vec::with_capacity(legs.len());
+    let mut labels = Vec::with_capacity(legs.len());
     for leg_dyn in legs {
         let Some(leg) = leg_dyn.try_cast::<rhai::Map>() else {
             return Dynamic::UNIT;
@@ -206,10 +425,14 @@ pub(crate) fn build_strategy_from_legs(
             .and_then(|v| v.clone().into_immutable_string().ok())
             .unwrap_or_default()
             .to_string();
-        let delta = leg
-            .get("delta")
-            .and_then(|v| v.as_float().ok())
-            .unwrap_or(0.30);
+        let option_type = match opt_type.to_lowercase().as_str() {
+            "call" | "c" => OptionType::Call,
+            "put" | "p" => OptionType::Put,
+            _ => return Dynamic::UNIT,
+        };
+        let Some(selector) = parse_leg_selector(&leg) else {
+            return Dynamic::UNIT;
+        };
         let dte = leg.get("dte").and_then(|v| v.as_int().ok()).unwrap_or(45);
         let side = leg
             .get("side")
@@ -217,19 +440,18 @@ pub(crate) fn build_strategy_from_legs(
             .unwrap_or_default()
             .to_string();
 
-        let target = crate::engine::types::TargetRange {
-            target: delta,
-            min: (delta - 0.10).max(0.01),
-            max: (delta + 0.10).min(1.0),
-        };
-        let found = resolve_option_leg(
-            &opt_type,
-            &target,
-            (dte - 15).max(1) as i32,
-            (dte + 15) as i32,
-            options_by_date,
-            datetime,
-        );
+        targets.push((option_type, selector, dte as i32));
+        labels.push((side, opt_type));
+    }
+
+    let Some(rows) = select_leg_contracts(today_df, &targets, Some(spot)) else {
+        return Dynamic::UNIT;
+    };
+
+    let mut resolved_legs = Vec::new();
+    let mut net_premium = 0.0;
+    for (row, (side, opt_type)) in rows.iter().zip(labels) {
+        let found = super::types::row_to_option_map(row, 0, today);
         if found.is_unit() {
             return Dynamic::UNIT;
         }
@@ -279,6 +501,48 @@ pub(crate) fn leg(side: &str, option_type: &str, delta: f64, dte: i64) -> Dynami
     map.into()
 }
 
+/// Build a leg map for a named strategy helper.
+///
+/// `selector` is either a delta (number) or a selector map from `strike()`,
+/// `moneyness()`, `atm_offset()`, `premium()` or `wing()`. A `wing(width)`
+/// selector is placed `width` points from leg `anchor.0` of the same strategy,
+/// above it when `anchor.1` is positive and below it otherwise. Returns `()`
+/// for anything else, which makes `build_strategy` return `()`.
+pub(crate) fn strategy_leg(
+    side: &str,
+    option_type: &str,
+    selector: &Dynamic,
+    dte: i64,
+    anchor: Option<(usize, f64)>,
+) -> Dynamic {
+    if let Ok(delta) = selector.as_float() {
+        return leg(side, option_type, delta, dte);
+    }
+    if let Ok(delta) = selector.as_int() {
+        return leg(side, option_type, delta as f64, dte);
+    }
+    let Some(selector) = selector.clone().try_cast::<rhai::Map>() else {
+        return Dynamic::UNIT;
+    };
+
+    let mut map = rhai::Map::new();
+    map.insert("side".into(), side.into());
+    map.insert("option_type".into(), option_type.into());
+    map.insert("dte".into(), dte.into());
+    for (key, value) in selector {
+        if key == "wing" {
+            let (Some((anchor, direction)), Ok(width)) = (anchor, value.as_float()) else {
+                return Dynamic::UNIT;
+            };
+            map.insert("same_strike_as".into(), (anchor as i64).into());
+            map.insert("width".into(), (direction.signum() * width.abs()).into());
+        } else {
+            map.insert(key, value);
+        }
+    }
+    map.into()
+}
+
 /// Wrap a resolved spread (from `build_strategy`) into a ready action map.
 pub(crate) fn wrap_spread_action(spread: Dynamic) -> Dynamic {
     if spread.is_unit() {
@@ -424,6 +688,79 @@ pub fn sell_stop_limit(symbol: String, qty: i64, stop: f64, limit: f64) -> Dynam
     map.into()
 }
 
+/// `spread_limit(action, offset, step, max_bars)` → the options entry as a
+/// working limit: it starts at the spread's mid minus `offset`, steps `step`
+/// toward the natural each bar it goes unfilled, and expires after `max_bars`
+/// bars (`0` = good till canceled). A unit action (no spread found) passes through.
+pub fn spread_limit(action: Dynamic, offset: f64, step: f64, max_bars: i64) -> Dynamic {
+    let Some(mut map) = action.clone().try_cast::<rhai::Map>() else {
+        return action;
+    };
+    map.insert("order_type".into(), "limit".into());
+    map.insert("limit_offset".into(), offset.into());
+    map.insert("limit_step".into(), step.into());
+    if max_bars > 0 {
+        map.insert("ttl".into(), max_bars.into());
+    }
+    map.into()
+}
+
+/// `spread_limit(action, offset)` → a fixed working limit at mid minus `offset`.
+pub fn spread_limit_fixed(action: Dynamic, offset: f64) -> Dynamic {
+    spread_limit(action, offset, 0.0, 0)
+}
+
+/// `oco(group, orders)` → the orders tagged `order_group: group, oco: true`,
+/// so the first to fill cancels the rest. Unit entries are dropped.
+pub fn oco(group: String, orders: rhai::Array) -> rhai::Array {
+    orders
+        .into_iter()
+        .filter_map(|order| {
+            let mut map = order.try_cast::<rhai::Map>()?;
+            map.insert("order_group".into(), group.clone().into());
+            map.insert("oco".into(), true.into());
+            Some(map.into())
+        })
+        .collect()
+}
+
+/// `oto(order, children)` → `order` with `then: children`: the children are
+/// submitted once `order` fills. A child `close` without a position id closes
+/// the position the parent opened.
+pub fn oto(order: Dynamic, children: rhai::Array) -> Dynamic {
+    let Some(mut map) = order.clone().try_cast::<rhai::Map>() else {
+        return order;
+    };
+    map.insert("then".into(), children.into());
+    map.into()
+}
+
+/// `bracket(entry, target, stop)` → `entry` with a limit exit at `target` and a
+/// stop exit at `stop` attached; whichever exit fills first cancels the other.
+pub fn bracket(entry: Dynamic, target: f64, stop: f64) -> Dynamic {
+    let side = entry
+        .clone()
+        .try_cast::<rhai::Map>()
+        .and_then(|m| m.get("side")?.clone().into_immutable_string().ok())
+        .map_or_else(|| "long".to_string(), |s| s.to_string());
+    let exit = |reason: &str, order_type: &str, price_key: &str, price: f64| -> Dynamic {
+        let mut map = rhai::Map::new();
+        map.insert("action".into(), "close".into());
+        map.insert("side".into(), side.clone().into());
+        map.insert("reason".into(), reason.into());
+        map.insert("order_type".into(), order_type.into());
+        map.insert(price_key.into(), price.into());
+        map.into()
+    };
+    oto(
+        entry,
+        vec![
+            exit("take_profit", "limit", "limit_price", target),
+            exit("stop_loss", "stop", "stop_price", stop),
+        ],
+    )
+}
+
 /// `cancel_orders()` → `#{ action: "cancel_orders" }`
 pub fn cancel_orders() -> Dynamic {
     let mut map = rhai::Map::new();
@@ -439,6 +776,41 @@ pub fn cancel_orders_by_signal(signal: String) -> Dynamic {
     map.into()
 }
 
+// ---------------------------------------------------------------------------
+// Leg selectors (arguments to the named strategy helpers)
+// ---------------------------------------------------------------------------
+
+fn leg_selector(key: &str, value: Dynamic) -> Dynamic {
+    let mut map = rhai::Map::new();
+    map.insert(key.into(), value);
+    map.into()
+}
+
+/// `strike(450)` → `#{ strike: 450.0 }` — listed strike nearest to a price
+pub fn strike_selector(strike: f64) -> Dynamic {
+    leg_selector("strike", strike.into())
+}
+
+/// `moneyness(0.05)` → `#{ moneyness: 0.05 }` — 5% out of the money
+pub fn moneyness_selector(moneyness: f64) -> Dynamic {
+    leg_selector("moneyness", moneyness.into())
+}
+
+/// `atm_offset(2)` → `#{ atm_offset: 2 }` — two listed strikes out of the money
+pub fn atm_offset_selector(offset: i64) -> Dynamic {
+    leg_selector("atm_offset", offset.into())
+}
+
+/// `premium(1.50)` → `#{ premium: 1.5 }` — bid/ask mid closest to a price
+pub fn premium_selector(premium: f64) -> Dynamic {
+    leg_selector("premium", premium.into())
+}
+
+/// `wing(5)` → `#{ wing: 5.0 }` — 5 points from the strategy's paired leg
+pub fn wing_selector(width: f64) -> Dynamic {
+    leg_selector("wing", width.into())
+}
+
 // ---------------------------------------------------------------------------
 // Indicator utility (on BarContext)
 // ---------------------------------------------------------------------------
@@ -3,6 +3,19 @@
 //! Indicators are declared in `config().data.indicators` and/or auto-scanned
 //! from the compiled AST. All values are batch-computed before the simulation
 //! loop starts. Undeclared indicators return `()` at runtime.
+//!
+//! The implied-volatility indicators (`atm_iv`, `iv_rank`, `iv_percentile`)
+//! come from the options chain rather than OHLCV, so `build` skips them and
+//! the engine fills them via `insert_implied_vol` once options are loaded.
+//! The options-structure indicators (`skew_25d`, `iv_term_slope`,
+//! `iv_rv_spread`, `put_call_volume`) are filled the same way by
+//! `insert_options_structure`: undeclared periods read the raw daily value,
+//! `name:N` its percentile over the trailing `N` bars.
+//!
+//! The `regime` indicator is a walk-forward Gaussian HMM over close-to-close
+//! returns: it is refit on a trailing window and forward-filtered, storing the
+//! volatility-ranked state under `regime` and its filtered probability under
+//! `regime_posterior`.
 
 use std::collections::HashMap;
 
@@ -15,6 +28,8 @@ use rust_ti::trend_indicators::bulk as tti;
 use serde_json::Value;
 
 use super::types::OhlcvBar;
+use crate::engine::hypothesis::OptionsStructureSeries;
+use crate::engine::{greeks, hmm};
 
 /// Key identifying a specific pre-computed indicator series.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -69,8 +84,38 @@ const PRECOMPUTED_INDICATORS: &[&str] = &[
     "mfi",
     "rank",
     "iv_rank",
+    "iv_percentile",
+    "atm_iv",
+    "skew_25d",
+    "iv_term_slope",
+    "iv_rv_spread",
+    "put_call_volume",
+    "regime",
+];
+
+/// Indicators derived from the options chain's ATM implied volatility series.
+const IMPLIED_VOL_INDICATORS: &[&str] = &["atm_iv", "iv_rank", "iv_percentile"];
+
+/// Indicators derived from the options chain's daily structure features.
+const OPTIONS_STRUCTURE_INDICATORS: &[&str] = &[
+    "skew_25d",
+    "iv_term_slope",
+    "iv_rv_spread",
+    "put_call_volume",
 ];
 
+/// Default lookback (trading days) for `iv_rank` / `iv_percentile`.
+const DEFAULT_IV_LOOKBACK: usize = 252;
+
+/// Default trailing window (bars) the `regime` HMM is fit on.
+pub(crate) const DEFAULT_REGIME_LOOKBACK: usize = 252;
+
+/// Bars between `regime` HMM refits.
+const REGIME_REFIT_EVERY: usize = 21;
+
+/// Posterior a new `regime` state must exceed before the label switches.
+const REGIME_SWITCH_THRESHOLD: f64 = 0.6;
+
 impl IndicatorStore {
     /// Create a new empty store.
     #[must_use]
@@ -164,8 +209,24 @@ impl IndicatorStore {
                     .collect(),
             };
 
-            if store.contains(&key) {
-                continue; // already computed (e.g., from AST scan + config overlap)
+            if store.contains(&key)
+                || IMPLIED_VOL_INDICATORS.contains(&name.as_str())
+                || OPTIONS_STRUCTURE_INDICATORS.contains(&name.as_str())
+            {
+                continue; // already computed, or filled later from the options chain
+            }
+
+            if name == "regime" {
+                let (states, posteriors) = compute_regime(&params, &closes);
+                store.insert(
+                    IndicatorKey {
+                        name: "regime_posterior".to_string(),
+                        params: key.params.clone(),
+                    },
+                    posteriors,
+                );
+                store.insert(key, states);
+                continue;
             }
 
             let values = compute_indicator(&name, &params, &closes, &highs, &lows, &volumes)?;
@@ -174,6 +235,96 @@ impl IndicatorStore {
 
         Ok(store)
     }
+
+    /// Fill the declared implied-volatility indicators from an ATM IV series.
+    ///
+    /// `atm_iv` is the constant-maturity ATM implied volatility (decimal, e.g.
+    /// 0.18) aligned to the bar index, NaN where no chain was available.
+    pub fn insert_implied_vol(&mut self, declarations: &[String], atm_iv: &[f64]) -> Result<()> {
+        for decl in declarations {
+            let (name, params) = parse_indicator_declaration(decl)?;
+            let key = IndicatorKey {
+                name: name.clone(),
+                params: params
+                    .iter()
+                    .map(|p| IndicatorParam::Int(*p as i64))
+                    .collect(),
+            };
+            if self.contains(&key) {
+                continue;
+            }
+            let period = params.first().copied().unwrap_or(DEFAULT_IV_LOOKBACK);
+            let values = match name.as_str() {
+                "atm_iv" => atm_iv.to_vec(),
+                "iv_rank" => greeks::rolling_iv_rank(atm_iv, period),
+                "iv_percentile" => greeks::rolling_iv_percentile(atm_iv, period),
+                _ => continue,
+            };
+            self.insert(key, values);
+        }
+        Ok(())
+    }
+
+    /// Fill the declared options-structure indicators from their daily series.
+    ///
+    /// `structure` and `iv_rv_spread` are aligned to the bar index. A
+    /// declaration without a period stores the raw series; `name:N` stores its
+    /// trailing `N`-bar percentile (0-100).
+    pub fn insert_options_structure(
+        &mut self,
+        declarations: &[String],
+        structure: &OptionsStructureSeries,
+        iv_rv_spread: &[f64],
+    ) -> Result<()> {
+        for decl in declarations {
+            let (name, params) = parse_indicator_declaration(decl)?;
+            let key = IndicatorKey {
+                name: name.clone(),
+                params: params
+                    .iter()
+                    .map(|p| IndicatorParam::Int(*p as i64))
+                    .collect(),
+            };
+            if self.contains(&key) {
+                continue;
+            }
+            let series = match name.as_str() {
+                "skew_25d" => structure.skew.as_slice(),
+                "iv_term_slope" => structure.term_slope.as_slice(),
+                "iv_rv_spread" => iv_rv_spread,
+                "put_call_volume" => match &structure.put_call_volume {
+                    Some(ratio) => ratio.as_slice(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let values = match params.first() {
+                Some(&period) => greeks::rolling_iv_percentile(series, period),
+                None => series.to_vec(),
+            };
+            self.insert(key, values);
+        }
+        Ok(())
+    }
+}
+
+/// Whether any declaration needs the options-derived implied-volatility series.
+#[must_use]
+pub fn needs_implied_vol(declarations: &[String]) -> bool {
+    declarations.iter().any(|decl| {
+        let name = decl.split(':').next().unwrap_or(decl).to_lowercase();
+        IMPLIED_VOL_INDICATORS.contains(&name.as_str())
+            || OPTIONS_STRUCTURE_INDICATORS.contains(&name.as_str())
+    })
+}
+
+/// Whether any declaration needs the options-structure features.
+#[must_use]
+pub fn needs_options_structure(declarations: &[String]) -> bool {
+    declarations.iter().any(|decl| {
+        let name = decl.split(':').next().unwrap_or(decl).to_lowercase();
+        OPTIONS_STRUCTURE_INDICATORS.contains(&name.as_str())
+    })
 }
 
 impl Default for IndicatorStore {
@@ -251,10 +402,21 @@ fn indicator_spec_from_call(
     args: &str,
     params: &HashMap<String, Value>,
 ) -> Option<String> {
-    let resolved: Option<Vec<String>> = args
+    let mut args: Vec<&str> = args
         .split(',')
         .map(str::trim)
         .filter(|arg| !arg.is_empty())
+        .collect();
+    // `regime("hmm", n, lookback)` names its method first; the HMM is the only one
+    if name == "regime" && args.first().is_some_and(|arg| arg.starts_with('"')) {
+        if args[0] != "\"hmm\"" {
+            return None;
+        }
+        args.remove(0);
+    }
+
+    let resolved: Option<Vec<String>> = args
+        .into_iter()
         .map(|arg| resolve_indicator_arg(name, arg, params))
         .collect();
 
@@ -443,6 +605,16 @@ pub(super) fn parse_indicator_declaration(decl: &str) -> Result<(String, Vec<usi
                 params.push(20); // default mult*10=20
             }
         }
+        "iv_rank" | "iv_percentile" if params.is_empty() => {
+            params = vec![DEFAULT_IV_LOOKBACK];
+        }
+        "regime" | "regime_posterior" => {
+            if params.is_empty() {
+                params = vec![2, DEFAULT_REGIME_LOOKBACK];
+            } else if params.len() == 1 {
+                params.push(DEFAULT_REGIME_LOOKBACK);
+            }
+        }
         _ => {}
     }
 
@@ -463,6 +635,34 @@ fn pad_front(vals: &[f64], target_len: usize) -> Vec<f64> {
     result
 }
 
+/// Walk-forward HMM regime series for `params = [n_states, lookback]`.
+///
+/// Returns `(state, posterior)` series aligned to bars. The HMM sees the
+/// return ending at each bar, so bar `i` uses `closes[..=i]` only.
+fn compute_regime(params: &[usize], closes: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let n = closes.len();
+    let n_states = params.first().copied().unwrap_or(2);
+    let lookback = params.get(1).copied().unwrap_or(DEFAULT_REGIME_LOOKBACK);
+    let returns: Vec<f64> = closes.windows(2).map(|w| w[1] / w[0] - 1.0).collect();
+    let regimes = hmm::rolling_regimes(
+        &returns,
+        n_states,
+        lookback,
+        REGIME_REFIT_EVERY,
+        REGIME_SWITCH_THRESHOLD,
+    );
+
+    let mut states = vec![f64::NAN; n];
+    let mut posteriors = vec![f64::NAN; n];
+    for (i, regime) in regimes.into_iter().enumerate() {
+        if let Some((state, posterior)) = regime {
+            states[i + 1] = state as f64;
+            posteriors[i + 1] = posterior;
+        }
+    }
+    (states, posteriors)
+}
+
 /// Compute a full indicator series from OHLCV data.
 ///
 /// Returns a `Vec<f64>` with one value per bar. Values before the warmup
@@ -838,9 +1038,6 @@ fn compute_indicator(
         // ── Rank: Percentile rank (hand-rolled) ──────────────────────────
         "rank" => Ok(rolling_rank(closes, period)),
 
-        // ── IV Rank: Min-max normalization (hand-rolled) ─────────────────
-        "iv_rank" => Ok(rolling_iv_rank(closes, period)),
-
         // ── CMF: Chaikin Money Flow (hand-rolled) ────────────────────────
         "cmf" => Ok(rolling_cmf(highs, lows, closes, volumes, period)),
 
@@ -959,28 +1156,6 @@ fn rolling_rank(data: &[f64], period: usize) -> Vec<f64> {
     result
 }
 
-/// IV Rank: Min-max normalization within rolling window (0-100).
-fn rolling_iv_rank(data: &[f64], period: usize) -> Vec<f64> {
-    let n = data.len();
-    let mut result = vec![f64::NAN; n];
-    if period == 0 || n < period {
-        return result;
-    }
-    for i in (period - 1)..n {
-        let s = i + 1 - period;
-        let sl = &data[s..=i];
-        let mn = sl.iter().copied().fold(f64::INFINITY, f64::min);
-        let mx = sl.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-        let r = mx - mn;
-        result[i] = if r > f64::EPSILON {
-            (data[i] - mn) / r * 100.0
-        } else {
-            50.0
-        };
-    }
-    result
-}
-
 /// CMF: Chaikin Money Flow.
 fn rolling_cmf(
     highs: &[f64],
@@ -13,8 +13,11 @@
 /// Produces: `sma`, `ema`, `rsi`, `atr`, `macd_line`, `macd_signal`, `macd_hist`,
 /// `bbands_upper/mid/lower`, `stochastic`, `cci`, `obv`, `adx`, `plus_di`, `minus_di`,
 /// `keltner_upper/lower`, `psar`, `supertrend`, `donchian_upper/mid/lower`,
-/// `williams_r`, `mfi`, `rank`, `iv_rank`, `tr`, `indicator`, `indicator_with`,
-/// `indicators_ready`.
+/// `williams_r`, `mfi`, `rank`, `iv_rank`, `iv_percentile`, `atm_iv`, `tr`,
+/// `skew_25d`, `iv_term_slope`, `iv_rv_spread`, `put_call_volume` (raw value,
+/// or its percentile via the 1-arg overload),
+/// `regime` (HMM state map, or its label via the 1- and 2-arg overloads),
+/// `indicator`, `indicator_with`, `indicators_ready`.
 macro_rules! impl_indicators {
     ($ty:ty) => {
         impl $ty {
@@ -179,6 +182,14 @@ macro_rules! impl_indicators {
                     period,
                 )
             }
+            pub fn iv_percentile(&mut self, period: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "iv_percentile",
+                    period,
+                )
+            }
 
             // --- Multi-param indicators ---
             pub fn macd_line(&mut self) -> rhai::Dynamic {
@@ -245,6 +256,112 @@ macro_rules! impl_indicators {
                     &[],
                 )
             }
+            pub fn atm_iv(&mut self) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup_multi(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "atm_iv",
+                    &[],
+                )
+            }
+
+            // --- Options structure: 0 args → raw value, 1 arg → percentile ---
+            pub fn skew_25d(&mut self) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup_multi(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "skew_25d",
+                    &[],
+                )
+            }
+            pub fn skew_25d_percentile(&mut self, period: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "skew_25d",
+                    period,
+                )
+            }
+            pub fn iv_term_slope(&mut self) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup_multi(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "iv_term_slope",
+                    &[],
+                )
+            }
+            pub fn iv_term_slope_percentile(&mut self, period: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "iv_term_slope",
+                    period,
+                )
+            }
+            pub fn iv_rv_spread(&mut self) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup_multi(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "iv_rv_spread",
+                    &[],
+                )
+            }
+            pub fn iv_rv_spread_percentile(&mut self, period: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "iv_rv_spread",
+                    period,
+                )
+            }
+            pub fn put_call_volume(&mut self) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup_multi(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "put_call_volume",
+                    &[],
+                )
+            }
+            pub fn put_call_volume_percentile(&mut self, period: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::indicator_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    "put_call_volume",
+                    period,
+                )
+            }
+
+            // --- Walk-forward HMM regime ---
+            pub fn regime(
+                &mut self,
+                method: String,
+                n_states: i64,
+                lookback: i64,
+            ) -> rhai::Dynamic {
+                $crate::scripting::helpers::regime_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    &method,
+                    n_states,
+                    lookback,
+                )
+            }
+            pub fn regime_label(&mut self, n_states: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::regime_label_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    n_states,
+                    $crate::scripting::indicators::DEFAULT_REGIME_LOOKBACK as i64,
+                )
+            }
+            pub fn regime_label_with(&mut self, n_states: i64, lookback: i64) -> rhai::Dynamic {
+                $crate::scripting::helpers::regime_label_lookup(
+                    &self.indicator_store,
+                    self.bar_idx,
+                    n_states,
+                    lookback,
+                )
+            }
 
             // --- Generic accessors ---
             pub fn indicator(&mut self, name: String, period: i64) -> rhai::Dynamic {
@@ -298,6 +415,7 @@ macro_rules! register_indicators {
         $engine.register_fn("mfi", <$ty>::mfi);
         $engine.register_fn("rank", <$ty>::rank);
         $engine.register_fn("iv_rank", <$ty>::iv_rank);
+        $engine.register_fn("iv_percentile", <$ty>::iv_percentile);
 
         // Multi-param / no-param
         $engine.register_fn("macd_line", <$ty>::macd_line);
@@ -308,6 +426,20 @@ macro_rules! register_indicators {
         $engine.register_fn("obv", <$ty>::obv);
         $engine.register_fn("psar", <$ty>::psar);
         $engine.register_fn("tr", <$ty>::tr);
+        $engine.register_fn("atm_iv", <$ty>::atm_iv);
+        $engine.register_fn("skew_25d", <$ty>::skew_25d);
+        $engine.register_fn("skew_25d", <$ty>::skew_25d_percentile);
+        $engine.register_fn("iv_term_slope", <$ty>::iv_term_slope);
+        $engine.register_fn("iv_term_slope", <$ty>::iv_term_slope_percentile);
+        $engine.register_fn("iv_rv_spread", <$ty>::iv_rv_spread);
+        $engine.register_fn("iv_rv_spread", <$ty>::iv_rv_spread_percentile);
+        $engine.register_fn("put_call_volume", <$ty>::put_call_volume);
+        $engine.register_fn("put_call_volume", <$ty>::put_call_volume_percentile);
+
+        // Regime: 3 args → state map, 1–2 args → label
+        $engine.register_fn("regime", <$ty>::regime);
+        $engine.register_fn("regime", <$ty>::regime_label);
+        $engine.register_fn("regime", <$ty>::regime_label_with);
 
         // Generic + multi-param
         $engine.register_fn("indicator", <$ty>::indicator);
@@ -322,370 +454,873 @@ macro_rules! register_indicators {
 /// - `fn build_strategy(&mut self, legs: rhai::Array) -> rhai::Dynamic`
 /// - `fn wrap_strategy_action(spread: rhai::Dynamic) -> rhai::Dynamic` (associated fn)
 ///
-/// Produces all 23 named strategy builders (singles through calendar/diagonal).
+/// Produces all 31 named strategy builders (singles through calendar/diagonal).
+/// Each strike argument is a delta or a selector map (`strike()`, `moneyness()`,
+/// `atm_offset()`, `premium()`, `wing()`); `wing()` legs are placed relative to
+/// the structure's paired leg, e.g. the short strike for a spread's long wing.
 macro_rules! impl_options_strategies {
     ($ty:ty) => {
         impl $ty {
             // Singles
-            pub fn long_call(&mut self, delta: f64, dte: i64) -> rhai::Dynamic {
+            pub fn long_call(&mut self, delta: rhai::Dynamic, dte: i64) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "call", delta, dte),
+                    $crate::scripting::helpers::strategy_leg("long", "call", &delta, dte, None),
                 ]))
             }
-            pub fn short_call(&mut self, delta: f64, dte: i64) -> rhai::Dynamic {
+            pub fn short_call(&mut self, delta: rhai::Dynamic, dte: i64) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", delta, dte),
+                    $crate::scripting::helpers::strategy_leg("short", "call", &delta, dte, None),
                 ]))
             }
-            pub fn long_put(&mut self, delta: f64, dte: i64) -> rhai::Dynamic {
+            pub fn long_put(&mut self, delta: rhai::Dynamic, dte: i64) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", delta, dte),
+                    $crate::scripting::helpers::strategy_leg("long", "put", &delta, dte, None),
                 ]))
             }
-            pub fn short_put(&mut self, delta: f64, dte: i64) -> rhai::Dynamic {
+            pub fn short_put(&mut self, delta: rhai::Dynamic, dte: i64) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", delta, dte),
+                    $crate::scripting::helpers::strategy_leg("short", "put", &delta, dte, None),
                 ]))
             }
-            pub fn covered_call(&mut self, delta: f64, dte: i64) -> rhai::Dynamic {
+            pub fn covered_call(&mut self, delta: rhai::Dynamic, dte: i64) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", delta, dte),
+                    $crate::scripting::helpers::strategy_leg("short", "call", &delta, dte, None),
                 ]))
             }
 
             // Vertical spreads
             pub fn bull_call_spread(
                 &mut self,
-                long_d: f64,
-                short_d: f64,
+                long_d: rhai::Dynamic,
+                short_d: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "call", long_d, dte),
-                    $crate::scripting::helpers::leg("short", "call", short_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &long_d,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &short_d,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
                 ]))
             }
             pub fn bear_call_spread(
                 &mut self,
-                short_d: f64,
-                long_d: f64,
+                short_d: rhai::Dynamic,
+                long_d: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", short_d, dte),
-                    $crate::scripting::helpers::leg("long", "call", long_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &short_d,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &long_d,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
                 ]))
             }
             pub fn bull_put_spread(
                 &mut self,
-                short_d: f64,
-                long_d: f64,
+                short_d: rhai::Dynamic,
+                long_d: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", short_d, dte),
-                    $crate::scripting::helpers::leg("long", "put", long_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &short_d,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &long_d,
+                        dte,
+                        Some((0, -1.0)),
+                    ),
                 ]))
             }
             pub fn bear_put_spread(
                 &mut self,
-                long_d: f64,
-                short_d: f64,
+                long_d: rhai::Dynamic,
+                short_d: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", long_d, dte),
-                    $crate::scripting::helpers::leg("short", "put", short_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &long_d,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &short_d,
+                        dte,
+                        Some((0, -1.0)),
+                    ),
                 ]))
             }
 
             // Straddles & strangles
-            pub fn long_straddle(&mut self, call_d: f64, put_d: f64, dte: i64) -> rhai::Dynamic {
+            pub fn long_straddle(
+                &mut self,
+                call_d: rhai::Dynamic,
+                put_d: rhai::Dynamic,
+                dte: i64,
+            ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "call", call_d, dte),
-                    $crate::scripting::helpers::leg("long", "put", put_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &call_d,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &put_d,
+                        dte,
+                        Some((0, -1.0)),
+                    ),
                 ]))
             }
-            pub fn short_straddle(&mut self, call_d: f64, put_d: f64, dte: i64) -> rhai::Dynamic {
+            pub fn short_straddle(
+                &mut self,
+                call_d: rhai::Dynamic,
+                put_d: rhai::Dynamic,
+                dte: i64,
+            ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", call_d, dte),
-                    $crate::scripting::helpers::leg("short", "put", put_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &call_d,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &put_d,
+                        dte,
+                        Some((0, -1.0)),
+                    ),
                 ]))
             }
-            pub fn long_strangle(&mut self, put_d: f64, call_d: f64, dte: i64) -> rhai::Dynamic {
+            pub fn long_strangle(
+                &mut self,
+                put_d: rhai::Dynamic,
+                call_d: rhai::Dynamic,
+                dte: i64,
+            ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", put_d, dte),
-                    $crate::scripting::helpers::leg("long", "call", call_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &put_d,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &call_d,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
                 ]))
             }
-            pub fn short_strangle(&mut self, put_d: f64, call_d: f64, dte: i64) -> rhai::Dynamic {
+            pub fn short_strangle(
+                &mut self,
+                put_d: rhai::Dynamic,
+                call_d: rhai::Dynamic,
+                dte: i64,
+            ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", put_d, dte),
-                    $crate::scripting::helpers::leg("short", "call", call_d, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &put_d,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &call_d,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
                 ]))
             }
 
             // Butterflies
             pub fn long_call_butterfly(
                 &mut self,
-                lower: f64,
-                center: f64,
-                upper: f64,
+                lower: rhai::Dynamic,
+                center: rhai::Dynamic,
+                upper: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "call", lower, dte),
-                    $crate::scripting::helpers::leg("short", "call", center, dte),
-                    $crate::scripting::helpers::leg("short", "call", center, dte),
-                    $crate::scripting::helpers::leg("long", "call", upper, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &lower,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &upper,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
                 ]))
             }
             pub fn short_call_butterfly(
                 &mut self,
-                lower: f64,
-                center: f64,
-                upper: f64,
+                lower: rhai::Dynamic,
+                center: rhai::Dynamic,
+                upper: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", lower, dte),
-                    $crate::scripting::helpers::leg("long", "call", center, dte),
-                    $crate::scripting::helpers::leg("long", "call", center, dte),
-                    $crate::scripting::helpers::leg("short", "call", upper, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &lower,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &upper,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
                 ]))
             }
             pub fn long_put_butterfly(
                 &mut self,
-                lower: f64,
-                center: f64,
-                upper: f64,
+                lower: rhai::Dynamic,
+                center: rhai::Dynamic,
+                upper: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", lower, dte),
-                    $crate::scripting::helpers::leg("short", "put", center, dte),
-                    $crate::scripting::helpers::leg("short", "put", center, dte),
-                    $crate::scripting::helpers::leg("long", "put", upper, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &lower,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &upper,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
                 ]))
             }
             pub fn short_put_butterfly(
                 &mut self,
-                lower: f64,
-                center: f64,
-                upper: f64,
+                lower: rhai::Dynamic,
+                center: rhai::Dynamic,
+                upper: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", lower, dte),
-                    $crate::scripting::helpers::leg("long", "put", center, dte),
-                    $crate::scripting::helpers::leg("long", "put", center, dte),
-                    $crate::scripting::helpers::leg("short", "put", upper, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &lower,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &center,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &upper,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
                 ]))
             }
 
             // Condors (all same option type)
             pub fn long_call_condor(
                 &mut self,
-                ol: f64,
-                il: f64,
-                iu: f64,
-                ou: f64,
+                ol: rhai::Dynamic,
+                il: rhai::Dynamic,
+                iu: rhai::Dynamic,
+                ou: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "call", ol, dte),
-                    $crate::scripting::helpers::leg("short", "call", il, dte),
-                    $crate::scripting::helpers::leg("short", "call", iu, dte),
-                    $crate::scripting::helpers::leg("long", "call", ou, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &ol,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &il,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &iu,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &ou,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
             pub fn short_call_condor(
                 &mut self,
-                ol: f64,
-                il: f64,
-                iu: f64,
-                ou: f64,
+                ol: rhai::Dynamic,
+                il: rhai::Dynamic,
+                iu: rhai::Dynamic,
+                ou: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", ol, dte),
-                    $crate::scripting::helpers::leg("long", "call", il, dte),
-                    $crate::scripting::helpers::leg("long", "call", iu, dte),
-                    $crate::scripting::helpers::leg("short", "call", ou, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &ol,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &il,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &iu,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &ou,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
             pub fn long_put_condor(
                 &mut self,
-                ol: f64,
-                il: f64,
-                iu: f64,
-                ou: f64,
+                ol: rhai::Dynamic,
+                il: rhai::Dynamic,
+                iu: rhai::Dynamic,
+                ou: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", ol, dte),
-                    $crate::scripting::helpers::leg("short", "put", il, dte),
-                    $crate::scripting::helpers::leg("short", "put", iu, dte),
-                    $crate::scripting::helpers::leg("long", "put", ou, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &ol,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &il,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &iu,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &ou,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
             pub fn short_put_condor(
                 &mut self,
-                ol: f64,
-                il: f64,
-                iu: f64,
-                ou: f64,
+                ol: rhai::Dynamic,
+                il: rhai::Dynamic,
+                iu: rhai::Dynamic,
+                ou: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", ol, dte),
-                    $crate::scripting::helpers::leg("long", "put", il, dte),
-                    $crate::scripting::helpers::leg("long", "put", iu, dte),
-                    $crate::scripting::helpers::leg("short", "put", ou, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &ol,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &il,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &iu,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &ou,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
 
             // Iron strategies
             pub fn iron_condor(
                 &mut self,
-                sp: f64,
-                lp: f64,
-                sc: f64,
-                lc: f64,
+                sp: rhai::Dynamic,
+                lp: rhai::Dynamic,
+                sc: rhai::Dynamic,
+                lc: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", lp, dte),
-                    $crate::scripting::helpers::leg("short", "put", sp, dte),
-                    $crate::scripting::helpers::leg("short", "call", sc, dte),
-                    $crate::scripting::helpers::leg("long", "call", lc, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &lp,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &sp,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &sc,
+                        dte,
+                        Some((3, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &lc,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
             pub fn reverse_iron_condor(
                 &mut self,
-                lp: f64,
-                sp: f64,
-                lc: f64,
-                sc: f64,
+                lp: rhai::Dynamic,
+                sp: rhai::Dynamic,
+                lc: rhai::Dynamic,
+                sc: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", sp, dte),
-                    $crate::scripting::helpers::leg("long", "put", lp, dte),
-                    $crate::scripting::helpers::leg("long", "call", lc, dte),
-                    $crate::scripting::helpers::leg("short", "call", sc, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &sp,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &lp,
+                        dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &lc,
+                        dte,
+                        Some((3, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &sc,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
             pub fn iron_butterfly(
                 &mut self,
-                sp: f64,
-                lp: f64,
-                sc: f64,
-                lc: f64,
+                sp: rhai::Dynamic,
+                lp: rhai::Dynamic,
+                sc: rhai::Dynamic,
+                lc: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("long", "put", lp, dte),
-                    $crate::scripting::helpers::leg("short", "put", sp, dte),
-                    $crate::scripting::helpers::leg("short", "call", sc, dte),
-                    $crate::scripting::helpers::leg("long", "call", lc, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &lp,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &sp,
+                        dte,
+                        Some((2, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &sc,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &lc,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
             pub fn reverse_iron_butterfly(
                 &mut self,
-                lp: f64,
-                sp: f64,
-                lc: f64,
-                sc: f64,
+                lp: rhai::Dynamic,
+                sp: rhai::Dynamic,
+                lc: rhai::Dynamic,
+                sc: rhai::Dynamic,
                 dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", sp, dte),
-                    $crate::scripting::helpers::leg("long", "put", lp, dte),
-                    $crate::scripting::helpers::leg("long", "call", lc, dte),
-                    $crate::scripting::helpers::leg("short", "call", sc, dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &sp,
+                        dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &lp,
+                        dte,
+                        Some((2, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &lc,
+                        dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &sc,
+                        dte,
+                        Some((2, 1.0)),
+                    ),
                 ]))
             }
 
             // Calendar & diagonal
             pub fn call_calendar(
                 &mut self,
-                near_d: f64,
-                far_d: f64,
+                near_d: rhai::Dynamic,
+                far_d: rhai::Dynamic,
                 near_dte: i64,
                 far_dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", near_d, near_dte),
-                    $crate::scripting::helpers::leg("long", "call", far_d, far_dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &near_d,
+                        near_dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &far_d,
+                        far_dte,
+                        Some((0, -1.0)),
+                    ),
                 ]))
             }
             pub fn put_calendar(
                 &mut self,
-                near_d: f64,
-                far_d: f64,
+                near_d: rhai::Dynamic,
+                far_d: rhai::Dynamic,
                 near_dte: i64,
                 far_dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", near_d, near_dte),
-                    $crate::scripting::helpers::leg("long", "put", far_d, far_dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &near_d,
+                        near_dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &far_d,
+                        far_dte,
+                        Some((0, 1.0)),
+                    ),
                 ]))
             }
             pub fn call_diagonal(
                 &mut self,
-                short_d: f64,
-                long_d: f64,
+                short_d: rhai::Dynamic,
+                long_d: rhai::Dynamic,
                 near_dte: i64,
                 far_dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "call", short_d, near_dte),
-                    $crate::scripting::helpers::leg("long", "call", long_d, far_dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &short_d,
+                        near_dte,
+                        Some((1, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &long_d,
+                        far_dte,
+                        Some((0, -1.0)),
+                    ),
                 ]))
             }
             pub fn put_diagonal(
                 &mut self,
-                short_d: f64,
-                long_d: f64,
+                short_d: rhai::Dynamic,
+                long_d: rhai::Dynamic,
                 near_dte: i64,
                 far_dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", short_d, near_dte),
-                    $crate::scripting::helpers::leg("long", "put", long_d, far_dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &short_d,
+                        near_dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &long_d,
+                        far_dte,
+                        Some((0, 1.0)),
+                    ),
                 ]))
             }
             pub fn double_calendar(
                 &mut self,
-                np: f64,
-                fp: f64,
-                nc: f64,
-                fc: f64,
+                np: rhai::Dynamic,
+                fp: rhai::Dynamic,
+                nc: rhai::Dynamic,
+                fc: rhai::Dynamic,
                 near_dte: i64,
                 far_dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", np, near_dte),
-                    $crate::scripting::helpers::leg("long", "put", fp, far_dte),
-                    $crate::scripting::helpers::leg("short", "call", nc, near_dte),
-                    $crate::scripting::helpers::leg("long", "call", fc, far_dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &np,
+                        near_dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &fp,
+                        far_dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &nc,
+                        near_dte,
+                        Some((3, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &fc,
+                        far_dte,
+                        Some((2, -1.0)),
+                    ),
                 ]))
             }
             pub fn double_diagonal(
                 &mut self,
-                sp: f64,
-                lp: f64,
-                sc: f64,
-                lc: f64,
+                sp: rhai::Dynamic,
+                lp: rhai::Dynamic,
+                sc: rhai::Dynamic,
+                lc: rhai::Dynamic,
                 near_dte: i64,
                 far_dte: i64,
             ) -> rhai::Dynamic {
                 <$ty>::wrap_strategy_action(self.build_strategy(vec![
-                    $crate::scripting::helpers::leg("short", "put", sp, near_dte),
-                    $crate::scripting::helpers::leg("long", "put", lp, far_dte),
-                    $crate::scripting::helpers::leg("short", "call", sc, near_dte),
-                    $crate::scripting::helpers::leg("long", "call", lc, far_dte),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "put",
+                        &sp,
+                        near_dte,
+                        Some((1, -1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "put",
+                        &lp,
+                        far_dte,
+                        Some((0, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "short",
+                        "call",
+                        &sc,
+                        near_dte,
+                        Some((3, 1.0)),
+                    ),
+                    $crate::scripting::helpers::strategy_leg(
+                        "long",
+                        "call",
+                        &lc,
+                        far_dte,
+                        Some((2, -1.0)),
+                    ),
                 ]))
             }
         }
@@ -0,0 +1,437 @@
+//! Broker margin models for script backtests.
+//!
+//! A margin model turns the open book into a dollar requirement: the part of
+//! account equity that must be set aside to carry the positions. Buying power is
+//! `equity - requirement`; entries that would drive it negative are rejected and
+//! a negative balance after mark-to-market triggers a margin call.
+//!
+//! Requirements are computed per underlying so that offsetting positions (a
+//! covered call and its stock, or the two legs of a vertical opened as separate
+//! positions) are margined together.
+//!
+//! Three models are supported:
+//! - `Cash`: every position must be fully funded — long premium and stock in
+//!   full, short options secured by their worst-case assignment loss. Positions
+//!   with unbounded loss (naked calls, short stock) cannot be carried.
+//! - `RegT`: strategy-based rules — 50% on stock, long options in full, naked
+//!   shorts at `max(20% of underlying − OTM amount, 10% of underlying/strike)`
+//!   (only the larger side of a strangle), defined-risk structures at their max
+//!   loss, whichever is lower.
+//! - `Portfolio`: simplified TIMS-style portfolio margin — the worst loss over a
+//!   grid of underlying price moves (±`price_shock`) and implied-volatility
+//!   moves (±`vol_shock`, relative), floored at $0.375 × multiplier per short
+//!   contract.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::engine::greeks::{bs_price, year_fraction, GreeksParams};
+use crate::engine::types::{OptionType, Side};
+use crate::scripting::types::{ScriptPosition, ScriptPositionInner, ScriptPositionLeg};
+
+/// Config keys accepted for `engine.margin`.
+pub const MARGIN_MODELS: [&str; 3] = ["cash", "reg_t", "portfolio"];
+
+/// Default TIMS equity price shock (±15%).
+pub const DEFAULT_PRICE_SHOCK: f64 = 0.15;
+
+/// Default relative implied-volatility shock for portfolio margin (±25%).
+pub const DEFAULT_VOL_SHOCK: f64 = 0.25;
+
+/// Number of price scenarios on each side of the current underlying price.
+const PRICE_STEPS: i32 = 10;
+
+/// TIMS minimum per short contract, as a fraction of the contract multiplier.
+const MIN_PER_SHORT_CONTRACT: f64 = 0.375;
+
+/// Margin model selected via `config().engine.margin`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarginModel {
+    Cash,
+    RegT,
+    Portfolio { price_shock: f64, vol_shock: f64 },
+}
+
+impl MarginModel {
+    /// Total requirement for `positions`, in dollars.
+    ///
+    /// `spot` returns the current underlying price for a symbol. Returns
+    /// `f64::INFINITY` when the book cannot be carried under this model.
+    pub fn requirement(
+        &self,
+        positions: &[ScriptPosition],
+        spot: impl Fn(&str) -> f64,
+        today: NaiveDate,
+        params: &GreeksParams,
+    ) -> f64 {
+        group_by_symbol(positions)
+            .into_iter()
+            .map(|(symbol, book)| {
+                let s = spot(symbol);
+                match self {
+                    MarginModel::Cash => cash_requirement(&book, s),
+                    MarginModel::RegT => reg_t_requirement(&book, s),
+                    MarginModel::Portfolio {
+                        price_shock,
+                        vol_shock,
+                    } => portfolio_requirement(&book, s, *price_shock, *vol_shock, today, params),
+                }
+            })
+            .sum()
+    }
+}
+
+/// All exposure to one underlying: net stock shares plus every option leg.
+#[derive(Default)]
+struct SymbolBook<'a> {
+    /// Signed share count (positive = long).
+    shares: f64,
+    /// Option legs with their contract multiplier.
+    legs: Vec<(&'a ScriptPositionLeg, f64)>,
+}
+
+impl SymbolBook<'_> {
+    /// Signed mark-to-market value of the book at `spot`.
+    fn market_value(&self, spot: f64) -> f64 {
+        self.shares * spot
+            + self
+                .legs
+                .iter()
+                .map(|(leg, m)| leg.side.multiplier() * f64::from(leg.qty) * m * leg.current_price)
+                .sum::<f64>()
+    }
+
+    /// Value of the option legs (and optionally the stock) if the underlying
+    /// settles at `s`.
+    fn terminal_value(&self, s: f64, with_stock: bool) -> f64 {
+        let stock = if with_stock { self.shares * s } else { 0.0 };
+        stock
+            + self
+                .legs
+                .iter()
+                .map(|(leg, m)| leg.side.multiplier() * f64::from(leg.qty) * m * intrinsic(leg, s))
+                .sum::<f64>()
+    }
+
+    /// Lowest terminal value over all underlying prices `s >= 0`, or
+    /// `NEG_INFINITY` when the payoff falls without bound as `s` rises.
+    ///
+    /// The payoff is piecewise linear with kinks at the strikes, so it is enough
+    /// to check zero, every strike, and the slope beyond the highest strike.
+    fn terminal_floor(&self, with_stock: bool) -> f64 {
+        let upside_slope = if with_stock { self.shares } else { 0.0 }
+            + self
+                .legs
+                .iter()
+                .filter(|(leg, _)| leg.option_type == OptionType::Call)
+                .map(|(leg, m)| leg.side.multiplier() * f64::from(leg.qty) * m)
+                .sum::<f64>();
+        if upside_slope < 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        self.legs
+            .iter()
+            .map(|(leg, _)| leg.strike)
+            .chain(std::iter::once(0.0))
+            .map(|s| self.terminal_value(s, with_stock))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+fn group_by_symbol(positions: &[ScriptPosition]) -> BTreeMap<&str, SymbolBook<'_>> {
+    let mut books: BTreeMap<&str, SymbolBook<'_>> = BTreeMap::new();
+    for pos in positions {
+        let book = books.entry(pos.symbol.as_str()).or_default();
+        match &pos.inner {
+            ScriptPositionInner::Stock { side, qty, .. } => {
+                book.shares += f64::from(*qty) * side.multiplier();
+            }
+            ScriptPositionInner::Options {
+                legs, multiplier, ..
+            } => {
+                let m = f64::from(*multiplier);
+                book.legs.extend(legs.iter().map(|leg| (leg, m)));
+            }
+        }
+    }
+    books
+}
+
+fn intrinsic(leg: &ScriptPositionLeg, s: f64) -> f64 {
+    match leg.option_type {
+        OptionType::Call => (s - leg.strike).max(0.0),
+        OptionType::Put => (leg.strike - s).max(0.0),
+    }
+}
+
+/// Fully funded: current value of the book plus its worst-case terminal loss.
+fn cash_requirement(book: &SymbolBook<'_>, spot: f64) -> f64 {
+    let floor = book.terminal_floor(true);
+    if floor.is_infinite() {
+        return f64::INFINITY;
+    }
+    (book.market_value(spot) - floor.min(0.0)).max(0.0)
+}
+
+/// Reg-T strategy-based margin.
+///
+/// Stock carries 50% of its market value. Short calls covered by long shares
+/// (and short puts covered by short shares) add nothing beyond the stock
+/// requirement. The remaining options are charged the lower of the naked-leg
+/// rules and the max loss of the combined option structure.
+fn reg_t_requirement(book: &SymbolBook<'_>, spot: f64) -> f64 {
+    let stock = 0.5 * book.shares.abs() * spot;
+
+    let mut covering_shares = book.shares.abs();
+    let mut long_premium = 0.0;
+    let (mut naked_calls, mut naked_puts) = (0.0, 0.0);
+    for (leg, m) in &book.legs {
+        let contracts = f64::from(leg.qty);
+        match leg.side {
+            Side::Long => long_premium += contracts * m * leg.current_price,
+            Side::Short => {
+                let covers = match leg.option_type {
+                    OptionType::Call => book.shares > 0.0,
+                    OptionType::Put => book.shares < 0.0,
+                };
+                let covered = if covers {
+                    (covering_shares / m).floor().min(contracts)
+                } else {
+                    0.0
+                };
+                covering_shares -= covered * m;
+                let charge = (contracts - covered) * m * naked_short_requirement(leg, spot);
+                match leg.option_type {
+                    OptionType::Call => naked_calls += charge,
+                    OptionType::Put => naked_puts += charge,
+                }
+            }
+        }
+    }
+    // Short calls and puts cannot both finish in the money, so only the
+    // larger side is charged (the Reg-T straddle/strangle rule).
+    let naked = long_premium + f64::max(naked_calls, naked_puts);
+
+    let options_only = SymbolBook {
+        shares: 0.0,
+        legs: book.legs.clone(),
+    };
+    let floor = options_only.terminal_floor(false);
+    let defined = if floor.is_infinite() {
+        f64::INFINITY
+    } else {
+        (options_only.market_value(spot) - floor.min(0.0)).max(0.0)
+    };
+
+    stock + naked.min(defined)
+}
+
+/// Per-share Reg-T requirement for an uncovered short option, excluding the
+/// premium (which is already reflected in equity).
+fn naked_short_requirement(leg: &ScriptPositionLeg, spot: f64) -> f64 {
+    let (otm, floor_base) = match leg.option_type {
+        OptionType::Call => ((leg.strike - spot).max(0.0), spot),
+        OptionType::Put => ((spot - leg.strike).max(0.0), leg.strike),
+    };
+    (0.20 * spot - otm).max(0.10 * floor_base)
+}
+
+/// Worst scenario loss over the price × volatility shock grid.
+fn portfolio_requirement(
+    book: &SymbolBook<'_>,
+    spot: f64,
+    price_shock: f64,
+    vol_shock: f64,
+    today: NaiveDate,
+    params: &GreeksParams,
+) -> f64 {
+    let mut worst = 0.0_f64;
+    for step in -PRICE_STEPS..=PRICE_STEPS {
+        let shocked = spot * (1.0 + price_shock * f64::from(step) / f64::from(PRICE_STEPS));
+        for vol_factor in [1.0 - vol_shock, 1.0, 1.0 + vol_shock] {
+            let pnl = book.shares * (shocked - spot)
+                + book
+                    .legs
+                    .iter()
+                    .map(|(leg, m)| {
+                        let value = scenario_price(leg, spot, shocked, vol_factor, today, params);
+                        leg.side.multiplier() * f64::from(leg.qty) * m * (value - leg.current_price)
+                    })
+                    .sum::<f64>();
+            worst = worst.min(pnl);
+        }
+    }
+
+    let short_minimum: f64 = book
+        .legs
+        .iter()
+        .filter(|(leg, _)| leg.side == Side::Short)
+        .map(|(leg, m)| MIN_PER_SHORT_CONTRACT * m * f64::from(leg.qty))
+        .sum();
+    (-worst).max(short_minimum)
+}
+
+/// Reprice a leg at a shocked underlying and volatility.
+///
+/// Uses Black-Scholes when the leg has a usable implied volatility, otherwise a
+/// delta-gamma approximation from the current mark. Never below intrinsic.
+fn scenario_price(
+    leg: &ScriptPositionLeg,
+    spot: f64,
+    shocked: f64,
+    vol_factor: f64,
+    today: NaiveDate,
+    params: &GreeksParams,
+) -> f64 {
+    let dte = (leg.expiration - today).num_days();
+    let price = if dte <= 0 {
+        0.0
+    } else if leg.iv.is_finite() && leg.iv > 0.0 {
+        let t = year_fraction(i32::try_from(dte).unwrap_or(i32::MAX));
+        bs_price(
+            leg.option_type,
+            shocked,
+            leg.strike,
+            t,
+            params,
+            leg.iv * vol_factor,
+        )
+    } else {
+        let ds = shocked - spot;
+        leg.current_price + leg.delta * ds + 0.5 * leg.gamma * ds * ds
+    };
+    price.max(intrinsic(leg, shocked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+    }
+
+    fn leg(option_type: OptionType, side: Side, strike: f64, price: f64) -> ScriptPositionLeg {
+        ScriptPositionLeg {
+            strike,
+            option_type,
+            side,
+            expiration: today() + chrono::Duration::days(30),
+            entry_price: price,
+            current_price: price,
+            delta: 0.0,
+            iv: 0.25,
+            gamma: 0.0,
+            theta: 0.0,
+            vega: 0.0,
+            qty: 1,
+        }
+    }
+
+    fn position(inner: ScriptPositionInner) -> ScriptPosition {
+        ScriptPosition {
+            id: 1,
+            symbol: "SPY".into(),
+            entry_date: today(),
+            inner,
+            entry_cost: 0.0,
+            unrealized_pnl: 0.0,
+            days_held: 0,
+            current_date: today(),
+            entry_bar_idx: 0,
+            source: "script".into(),
+            implicit: false,
+            order_group: None,
+            group: None,
+            trailing_stop: None,
+        }
+    }
+
+    fn options(legs: Vec<ScriptPositionLeg>) -> ScriptPosition {
+        position(ScriptPositionInner::Options {
+            legs,
+            expiration: today() + chrono::Duration::days(30),
+            secondary_expiration: None,
+            multiplier: 100,
+        })
+    }
+
+    fn stock(side: Side, qty: i32) -> ScriptPosition {
+        position(ScriptPositionInner::Stock {
+            side,
+            qty,
+            entry_price: 100.0,
+        })
+    }
+
+    fn req(model: MarginModel, positions: &[ScriptPosition]) -> f64 {
+        model.requirement(positions, |_| 100.0, today(), &GreeksParams::default())
+    }
+
+    #[test]
+    fn cash_secured_put_requires_strike_less_premium() {
+        let csp = options(vec![leg(OptionType::Put, Side::Short, 95.0, 2.0)]);
+        assert!((req(MarginModel::Cash, &[csp]) - (9500.0 - 200.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cash_rejects_unbounded_risk_but_allows_covered_call() {
+        let naked_call = options(vec![leg(OptionType::Call, Side::Short, 105.0, 1.5)]);
+        assert!(req(MarginModel::Cash, std::slice::from_ref(&naked_call)).is_infinite());
+        assert!(req(MarginModel::Cash, &[stock(Side::Short, 100)]).is_infinite());
+
+        // Stock worth $10,000 less the $150 call premium
+        let covered = [stock(Side::Long, 100), naked_call];
+        assert!((req(MarginModel::Cash, &covered) - 9850.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reg_t_naked_put_uses_twenty_percent_rule() {
+        let put = options(vec![leg(OptionType::Put, Side::Short, 95.0, 2.0)]);
+        // max(20% × 100 − 5 OTM, 10% × 95) = 15 per share
+        assert!((req(MarginModel::RegT, &[put]) - 1500.0).abs() < 1e-9);
+
+        let far_otm = options(vec![leg(OptionType::Put, Side::Short, 70.0, 0.1)]);
+        // 20% rule goes negative, so the 10%-of-strike floor applies
+        assert!((req(MarginModel::RegT, &[far_otm]) - 700.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reg_t_spread_charges_max_loss_and_covered_call_only_stock() {
+        let spread = options(vec![
+            leg(OptionType::Put, Side::Short, 95.0, 2.0),
+            leg(OptionType::Put, Side::Long, 90.0, 0.8),
+        ]);
+        // Width $500 less $120 net credit
+        assert!((req(MarginModel::RegT, &[spread]) - 380.0).abs() < 1e-9);
+
+        let covered = [
+            stock(Side::Long, 100),
+            options(vec![leg(OptionType::Call, Side::Short, 105.0, 1.5)]),
+        ];
+        assert!((req(MarginModel::RegT, &covered) - 5000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn portfolio_margin_is_below_reg_t_for_strangle() {
+        let strangle = [options(vec![
+            leg(OptionType::Put, Side::Short, 90.0, 1.0),
+            leg(OptionType::Call, Side::Short, 110.0, 1.0),
+        ])];
+        let pm = MarginModel::Portfolio {
+            price_shock: DEFAULT_PRICE_SHOCK,
+            vol_shock: DEFAULT_VOL_SHOCK,
+        };
+        let pm_req = req(pm, &strangle);
+        let reg_t = req(MarginModel::RegT, &strangle);
+        assert!(pm_req > 75.0, "at least the per-contract minimum: {pm_req}");
+        // Reg-T charges only the larger side: max(20 − 10, 9) vs max(20 − 10, 10)
+        assert!((reg_t - 1000.0).abs() < 1e-9);
+        assert!(pm_req < reg_t, "PM {pm_req} should be below Reg-T {reg_t}");
+
+        // A long stock position loses 15% × $10,000 in the down-shock
+        let long_stock = [stock(Side::Long, 100)];
+        assert!((req(pm, &long_stock) - 1500.0).abs() < 1e-6);
+    }
+}
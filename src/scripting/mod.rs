@@ -7,11 +7,15 @@
 
 #[macro_use]
 pub mod macros;
+pub mod assignment;
 pub mod dsl;
 pub mod engine;
+pub mod financing;
 pub mod helpers;
 pub mod indicators;
+pub mod margin;
 pub mod options_cache;
+pub mod portfolio;
 pub mod registration;
 pub mod stdlib;
 #[cfg(test)]
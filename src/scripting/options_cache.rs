@@ -3,30 +3,54 @@
 //! Pre-splits the full options `DataFrame` by date at load time so each bar
 //! does O(1) lookup + small-DF filter instead of scanning millions of rows.
 //! Optionally pre-computes a `dte` column and pre-filters by expiration type
-//! at partition time to avoid redundant work in the per-bar hot path.
+//! at partition time to avoid redundant work in the per-bar hot path. Implied
+//! volatility and model greeks (`iv`, `gamma`, `theta`, `vega`) are solved once
+//! per row here as well, so leg resolution never re-prices the chain, along
+//! with the per-date constant-maturity ATM implied volatility.
 
 use std::collections::HashMap;
 
 use anyhow::Result;
 use chrono::NaiveDate;
 use polars::prelude::*;
+use rayon::prelude::*;
 
 use crate::data::parquet::DATETIME_COL;
 use crate::engine::filters;
-use crate::engine::types::{timestamp_to_naive_datetime, ExpirationFilter};
+use crate::engine::greeks::{self, GreeksParams, OptionGreeks};
+use crate::engine::hypothesis::OptionsStructureSeries;
+use crate::engine::types::{timestamp_to_naive_datetime, ExpirationFilter, OptionType};
+
+/// Contract identity within one date partition: `(dte, strike in
+/// thousandths, option type)`.
+type ContractKey = (i32, i64, OptionType);
+
+fn contract_key(dte: i32, strike: f64, option_type: OptionType) -> ContractKey {
+    (dte, (strike * 1000.0).round() as i64, option_type)
+}
 
 /// Options data pre-partitioned by quote date for O(1) per-bar access.
 pub struct DatePartitionedOptions {
     pub by_date: HashMap<NaiveDate, DataFrame>,
+    /// 30-day constant-maturity ATM implied volatility per quote date.
+    pub atm_iv: HashMap<NaiveDate, f64>,
+    /// Row of each contract within its date partition, so per-leg greeks and
+    /// quote lookups in the mark-to-market path never scan the chain.
+    contract_rows: HashMap<NaiveDate, HashMap<ContractKey, usize>>,
 }
 
 impl DatePartitionedOptions {
     /// Build from a full options DataFrame by grouping on the date portion of `datetime`.
     ///
     /// Computes the `dte` column once on the full DataFrame, applies the
-    /// `expiration_filter`, then partitions by date — avoiding per-slice
-    /// `lazy().collect()` overhead (previously thousands of collects).
-    pub fn from_df(df: &DataFrame, expiration_filter: &ExpirationFilter) -> Result<Self> {
+    /// `expiration_filter`, solves IV/greeks with `greeks_params`, then
+    /// partitions by date — avoiding per-slice `lazy().collect()` overhead
+    /// (previously thousands of collects).
+    pub fn from_df(
+        df: &DataFrame,
+        expiration_filter: &ExpirationFilter,
+        greeks_params: &GreeksParams,
+    ) -> Result<Self> {
         let ms_per_day = 86_400_000i64;
 
         // 1. Compute DTE once on the full DataFrame
@@ -46,7 +70,10 @@ impl DatePartitionedOptions {
         // 2. Apply expiration filter once on the full DataFrame
         let df_filtered = filters::filter_expiration_type(df_with_dte, expiration_filter)?;
 
-        // 3. Partition by date using index gather (no per-slice lazy/collect)
+        // 3. Solve implied volatility and greeks on the surviving rows
+        let df_filtered = greeks::append_greeks_columns(&df_filtered, greeks_params)?;
+
+        // 4. Partition by date using index gather (no per-slice lazy/collect)
         let dt_col = df_filtered.column(DATETIME_COL)?;
         let dt_ca = dt_col.datetime()?;
         let tu = dt_ca.time_unit();
@@ -70,11 +97,175 @@ impl DatePartitionedOptions {
             }
         }
 
-        Ok(Self { by_date })
+        // 5. Constant-maturity ATM implied volatility per quote date
+        let atm_iv = by_date
+            .par_iter()
+            .filter_map(|(date, chain)| {
+                greeks::constant_maturity_atm_iv(chain, greeks::ATM_IV_TARGET_DTE)
+                    .map(|iv| (*date, iv))
+            })
+            .collect();
+
+        // 6. Contract → row index per date partition
+        let contract_rows = by_date
+            .par_iter()
+            .map(|(date, chain)| (*date, index_contracts(chain)))
+            .collect();
+
+        Ok(Self {
+            by_date,
+            atm_iv,
+            contract_rows,
+        })
     }
 
     /// Get the options slice for a given date (typically ~5K-10K rows).
     pub fn get(&self, date: NaiveDate) -> Option<&DataFrame> {
         self.by_date.get(&date)
     }
+
+    /// ATM implied volatility aligned to `dates`, forward-filling quote dates
+    /// with no solvable chain. Dates before the first solved value are NaN.
+    pub fn atm_iv_series(&self, dates: impl IntoIterator<Item = NaiveDate>) -> Vec<f64> {
+        let mut last = f64::NAN;
+        dates
+            .into_iter()
+            .map(|date| {
+                if let Some(&iv) = self.atm_iv.get(&date) {
+                    last = iv;
+                }
+                last
+            })
+            .collect()
+    }
+
+    /// Options-structure features (delta skew, ATM term slope, put/call
+    /// volume) aligned to `dates`, forward-filled the same way as
+    /// [`Self::atm_iv_series`].
+    pub fn structure_series(&self, dates: &[NaiveDate]) -> OptionsStructureSeries {
+        let per_date: HashMap<NaiveDate, [Option<f64>; 3]> = dates
+            .par_iter()
+            .filter_map(|date| {
+                let chain = self.by_date.get(date)?;
+                Some((
+                    *date,
+                    [
+                        greeks::delta_skew(chain, greeks::ATM_IV_TARGET_DTE, greeks::SKEW_DELTA),
+                        greeks::atm_term_slope(
+                            chain,
+                            greeks::ATM_IV_TARGET_DTE,
+                            greeks::TERM_SLOPE_FAR_DTE,
+                        ),
+                        greeks::put_call_volume_ratio(chain),
+                    ],
+                ))
+            })
+            .collect();
+
+        let mut last = [f64::NAN; 3];
+        let mut columns: [Vec<f64>; 3] = Default::default();
+        for date in dates {
+            if let Some(values) = per_date.get(date) {
+                for (slot, v) in last.iter_mut().zip(values) {
+                    if let Some(v) = v {
+                        *slot = *v;
+                    }
+                }
+            }
+            for (column, v) in columns.iter_mut().zip(last) {
+                column.push(v);
+            }
+        }
+        let [skew, term_slope, put_call_volume] = columns;
+        OptionsStructureSeries {
+            skew,
+            term_slope,
+            put_call_volume: put_call_volume
+                .iter()
+                .any(|v| v.is_finite())
+                .then_some(put_call_volume),
+        }
+    }
+
+    /// Look up the solved greeks for a single contract quoted on `date`.
+    ///
+    /// Matches on `dte` instead of parsing `expiration`, since within one date
+    /// partition the two identify the same contract. Unsolved greeks read as 0.
+    pub fn contract_greeks(
+        &self,
+        date: NaiveDate,
+        option_type: OptionType,
+        strike: f64,
+        expiration: NaiveDate,
+    ) -> Option<OptionGreeks> {
+        let (df, row) = self.contract_row(date, option_type, strike, expiration)?;
+        let get = |name: &str| {
+            df.column(name)
+                .ok()
+                .and_then(|c| c.f64().ok()?.get(row))
+                .unwrap_or(0.0)
+        };
+        Some(OptionGreeks {
+            iv: get("iv"),
+            delta: get("delta"),
+            gamma: get("gamma"),
+            theta: get("theta"),
+            vega: get("vega"),
+        })
+    }
+
+    /// Look up the `(bid, ask)` quote for a single contract on `date`.
+    pub fn contract_quote(
+        &self,
+        date: NaiveDate,
+        option_type: OptionType,
+        strike: f64,
+        expiration: NaiveDate,
+    ) -> Option<(f64, f64)> {
+        let (df, row) = self.contract_row(date, option_type, strike, expiration)?;
+        let get = |name: &str| df.column(name).ok()?.f64().ok()?.get(row);
+        Some((get("bid")?, get("ask")?))
+    }
+
+    /// Find a contract's row within the `date` partition.
+    fn contract_row(
+        &self,
+        date: NaiveDate,
+        option_type: OptionType,
+        strike: f64,
+        expiration: NaiveDate,
+    ) -> Option<(&DataFrame, usize)> {
+        let df = self.get(date)?;
+        let dte = i32::try_from((expiration - date).num_days()).ok()?;
+        let row = *self
+            .contract_rows
+            .get(&date)?
+            .get(&contract_key(dte, strike, option_type))?;
+        Some((df, row))
+    }
+}
+
+/// Map each contract in one date partition to its row. The first row wins
+/// when a contract is quoted more than once.
+fn index_contracts(chain: &DataFrame) -> HashMap<ContractKey, usize> {
+    let mut rows = HashMap::with_capacity(chain.height());
+    let (Ok(dtes), Ok(strikes), Ok(types)) = (
+        chain.column("dte").and_then(|c| c.i32().cloned()),
+        chain.column("strike").and_then(|c| c.f64().cloned()),
+        chain.column("option_type").and_then(|c| c.str().cloned()),
+    ) else {
+        return rows;
+    };
+    for i in 0..chain.height() {
+        let option_type = match types.get(i) {
+            Some("c") => OptionType::Call,
+            Some("p") => OptionType::Put,
+            _ => continue,
+        };
+        if let (Some(dte), Some(strike)) = (dtes.get(i), strikes.get(i)) {
+            rows.entry(contract_key(dte, strike, option_type))
+                .or_insert(i);
+        }
+    }
+    rows
 }
@@ -0,0 +1,950 @@
+//! Multi-strategy portfolio backtests with a shared capital pool.
+//!
+//! Each sleeve is an ordinary script backtest running on its own timeline, but
+//! all sleeves advance bar-synchronously: a sleeve may only process a bar once
+//! every other sleeve has finished all earlier bars (ties broken by sleeve
+//! order). Between bars, sleeves publish their equity, margin requirement and
+//! open positions to a shared [`PortfolioBook`], which
+//!
+//! - gates every entry against the pool's buying power and the cross-strategy
+//!   position limits, so sleeves compete for the same cash;
+//! - moves cash between sleeves at rebalance dates (fixed weights or inverse-
+//!   volatility risk budgets), never withdrawing capital that backs open
+//!   positions.
+//!
+//! The consolidated result sums sleeve P&L (net of transfers) onto the
+//! portfolio's starting capital, so rebalancing never shows up as a gain.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::constants::TRADING_DAYS_PER_YEAR;
+use crate::engine::equity::collapse_daily;
+use crate::engine::greeks::GreeksParams;
+use crate::engine::metrics::calculate_metrics;
+use crate::engine::types::{BacktestResult, EquityPoint, PerformanceMetrics, TradeRecord};
+
+use super::engine::{run_sleeve_backtest, DataLoader, ScriptBacktestResult};
+use super::margin::{MarginModel, DEFAULT_PRICE_SHOCK, DEFAULT_VOL_SHOCK};
+use super::types::ScriptPosition;
+
+/// Minimum daily returns before a sleeve's volatility is trusted for risk budgeting.
+const MIN_RISK_OBSERVATIONS: usize = 5;
+
+/// How sleeve target weights are derived.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum AllocationRule {
+    /// Targets are the sleeves' fixed `weight`s (normalized)
+    #[default]
+    #[serde(rename = "fixed_weights")]
+    FixedWeights,
+    /// Targets are `risk_budget / trailing volatility` (normalized), so each
+    /// sleeve contributes risk in proportion to its budget
+    #[serde(rename = "risk_budget")]
+    RiskBudget,
+}
+
+impl AllocationRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FixedWeights => "fixed_weights",
+            Self::RiskBudget => "risk_budget",
+        }
+    }
+}
+
+/// How often sleeve capital is reset to its target weight.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum RebalanceFrequency {
+    /// Allocate once at the start and let sleeves drift
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "weekly")]
+    Weekly,
+    #[serde(rename = "monthly")]
+    Monthly,
+    #[serde(rename = "quarterly")]
+    Quarterly,
+}
+
+impl RebalanceFrequency {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+            Self::Quarterly => "quarterly",
+        }
+    }
+
+    /// Calendar period `date` falls in; a rebalance fires when it changes.
+    fn period(self, date: NaiveDate) -> Option<(i32, u32)> {
+        match self {
+            Self::None => None,
+            Self::Weekly => {
+                let week = date.iso_week();
+                Some((week.year(), week.week()))
+            }
+            Self::Monthly => Some((date.year(), date.month())),
+            Self::Quarterly => Some((date.year(), (date.month() - 1) / 3)),
+        }
+    }
+}
+
+/// Margin model used to measure each sleeve's draw on the shared pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum PoolMargin {
+    #[serde(rename = "cash")]
+    Cash,
+    #[default]
+    #[serde(rename = "reg_t")]
+    RegT,
+    #[serde(rename = "portfolio")]
+    Portfolio,
+}
+
+impl PoolMargin {
+    fn model(self) -> MarginModel {
+        match self {
+            Self::Cash => MarginModel::Cash,
+            Self::RegT => MarginModel::RegT,
+            Self::Portfolio => MarginModel::Portfolio {
+                price_shock: DEFAULT_PRICE_SHOCK,
+                vol_shock: DEFAULT_VOL_SHOCK,
+            },
+        }
+    }
+}
+
+/// Cross-strategy limits enforced on every entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PortfolioLimits {
+    /// Maximum open positions across all sleeves.
+    pub max_positions: Option<usize>,
+    /// Maximum open positions on any one symbol across all sleeves.
+    pub max_positions_per_symbol: Option<usize>,
+    /// Margin a sleeve may commit, as a multiple of its own equity. `None`
+    /// lets a sleeve draw on idle capital anywhere in the pool.
+    pub max_sleeve_utilization: Option<f64>,
+}
+
+/// Portfolio-level settings shared by all sleeves.
+#[derive(Debug, Clone, Copy)]
+pub struct PortfolioConfig {
+    pub capital: f64,
+    pub allocation: AllocationRule,
+    pub rebalance: RebalanceFrequency,
+    /// Trailing daily returns used to estimate sleeve volatility for risk budgets.
+    pub risk_lookback: usize,
+    pub margin: PoolMargin,
+    pub limits: PortfolioLimits,
+}
+
+/// One strategy script in the portfolio.
+#[derive(Debug, Clone)]
+pub struct SleeveScript {
+    pub label: String,
+    pub source: String,
+    pub params: HashMap<String, serde_json::Value>,
+    /// Relative target weight (used by `fixed_weights`).
+    pub weight: f64,
+    /// Relative risk budget (used by `risk_budget`).
+    pub risk_budget: f64,
+}
+
+/// Per-sleeve contribution to a portfolio backtest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SleeveAttribution {
+    pub label: String,
+    /// Symbol the sleeve's script traded (primary symbol for multi-symbol scripts)
+    pub symbol: Option<String>,
+    pub initial_weight: f64,
+    /// Share of portfolio equity held by the sleeve at the end
+    pub final_weight: f64,
+    pub initial_capital: f64,
+    /// Net cash moved into (+) or out of (−) the sleeve by rebalancing
+    pub net_transfers: f64,
+    /// Sleeve P&L excluding transfers, including open positions at the end
+    pub pnl: f64,
+    /// `pnl` as a percentage of portfolio starting capital
+    pub contribution_pct: f64,
+    pub trade_count: usize,
+    /// Entries rejected by the shared pool or cross-strategy limits
+    pub blocked_entries: usize,
+    /// Metrics of the sleeve's own P&L on its starting capital
+    pub metrics: PerformanceMetrics,
+}
+
+/// Consolidated portfolio result plus per-sleeve attribution.
+#[derive(Debug, Clone)]
+pub struct PortfolioBacktestResult {
+    pub result: BacktestResult,
+    pub sleeves: Vec<SleeveAttribution>,
+    pub rebalances: usize,
+}
+
+/// Run every sleeve against one shared capital pool.
+pub async fn run_portfolio_backtest(
+    sleeves: Vec<SleeveScript>,
+    config: &PortfolioConfig,
+    data_loader: &dyn DataLoader,
+) -> Result<PortfolioBacktestResult> {
+    if sleeves.is_empty() {
+        anyhow::bail!("portfolio backtest needs at least one sleeve");
+    }
+    let book = PortfolioBook::new(&sleeves, config);
+    let initial_weights = book.lock().target_weights();
+
+    let runs = sleeves.iter().enumerate().map(|(index, sleeve)| {
+        let link = SleeveLink {
+            book: Arc::clone(&book.0),
+            index,
+        };
+        let mut params = sleeve.params.clone();
+        params.insert(
+            "CAPITAL".to_string(),
+            serde_json::Value::from(link.initial_capital()),
+        );
+        async move {
+            // `link` is dropped when the sleeve finishes (or fails), releasing
+            // the sleeves waiting on its clock.
+            run_sleeve_backtest(&sleeve.source, &params, data_loader, &link).await
+        }
+    });
+    let results = futures::future::join_all(runs).await;
+
+    let mut finished = Vec::with_capacity(results.len());
+    for (sleeve, result) in sleeves.iter().zip(results) {
+        finished.push(result.with_context(|| format!("Sleeve '{}' failed", sleeve.label))?);
+    }
+    let state = book.lock();
+    consolidate(&sleeves, &initial_weights, &finished, &state, config)
+}
+
+// ── Shared book ───────────────────────────────────────────────────────────
+
+/// Where a sleeve is on the shared timeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Clock {
+    /// Still loading data; blocks every other sleeve.
+    Pending,
+    /// Waiting for, or processing, the bar at this timestamp.
+    At(NaiveDateTime),
+    Done,
+}
+
+#[derive(Debug)]
+struct SleeveState {
+    clock: Clock,
+    weight: f64,
+    risk_budget: f64,
+    initial_capital: f64,
+    equity: f64,
+    requirement: f64,
+    open_by_symbol: HashMap<String, usize>,
+    /// Cash assigned by a rebalance, delivered at the sleeve's next bar.
+    pending_transfer: f64,
+    /// Transfers actually applied, with the bar they were applied on.
+    transfers: Vec<(NaiveDateTime, f64)>,
+    /// End-of-day equity net of transfers, for volatility estimates.
+    daily_pnl_equity: Vec<(NaiveDate, f64)>,
+    blocked_entries: usize,
+}
+
+impl SleeveState {
+    fn open_positions(&self) -> usize {
+        self.open_by_symbol.values().sum()
+    }
+
+    fn applied_transfers(&self) -> f64 {
+        self.transfers.iter().map(|(_, t)| t).sum()
+    }
+
+    /// Standard deviation of the trailing daily returns (not annualized).
+    fn trailing_volatility(&self, lookback: usize) -> Option<f64> {
+        let start = self.daily_pnl_equity.len().saturating_sub(lookback + 1);
+        let returns: Vec<f64> = self.daily_pnl_equity[start..]
+            .windows(2)
+            .filter(|w| w[0].1.abs() > f64::EPSILON)
+            .map(|w| w[1].1 / w[0].1 - 1.0)
+            .collect();
+        if returns.len() < MIN_RISK_OBSERVATIONS {
+            return None;
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let vol = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        (vol > f64::EPSILON).then_some(vol)
+    }
+}
+
+#[derive(Debug)]
+struct BookState {
+    config: PortfolioConfig,
+    sleeves: Vec<SleeveState>,
+    last_period: Option<(i32, u32)>,
+    rebalances: usize,
+}
+
+impl BookState {
+    /// Whether sleeve `index` may process the bar at `at`.
+    fn is_turn(&self, index: usize, at: NaiveDateTime) -> bool {
+        self.sleeves
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != index)
+            .all(|(j, s)| match s.clock {
+                Clock::Pending => false,
+                Clock::Done => true,
+                Clock::At(t) => (t, j) > (at, index),
+            })
+    }
+
+    /// Normalized target weights for the active sleeves (zero for finished ones).
+    fn target_weights(&self) -> Vec<f64> {
+        let active = |s: &SleeveState| s.clock != Clock::Done;
+        let raw: Vec<f64> = match self.config.allocation {
+            AllocationRule::FixedWeights => self
+                .sleeves
+                .iter()
+                .map(|s| if active(s) { s.weight } else { 0.0 })
+                .collect(),
+            AllocationRule::RiskBudget => {
+                let lookback = self.config.risk_lookback;
+                let vols: Vec<Option<f64>> = self
+                    .sleeves
+                    .iter()
+                    .map(|s| s.trailing_volatility(lookback))
+                    .collect();
+                // Until every active sleeve has a usable history, allocate by budget alone
+                let all_known = self
+                    .sleeves
+                    .iter()
+                    .zip(&vols)
+                    .all(|(s, v)| !active(s) || v.is_some());
+                self.sleeves
+                    .iter()
+                    .zip(&vols)
+                    .map(|(s, v)| match (active(s), v) {
+                        (false, _) => 0.0,
+                        (true, Some(vol)) if all_known => s.risk_budget / vol,
+                        (true, _) => s.risk_budget,
+                    })
+                    .collect()
+            }
+        };
+        let total: f64 = raw.iter().sum();
+        if total > 0.0 {
+            raw.iter().map(|w| w / total).collect()
+        } else {
+            vec![0.0; raw.len()]
+        }
+    }
+
+    /// Rebalance on the first bar of each new calendar period.
+    fn maybe_rebalance(&mut self, at: NaiveDateTime) {
+        let Some(period) = self.config.rebalance.period(at.date()) else {
+            return;
+        };
+        let previous = self.last_period.replace(period);
+        if previous.is_none_or(|p| p == period) {
+            return;
+        }
+
+        let weights = self.target_weights();
+        let total: f64 = self
+            .sleeves
+            .iter()
+            .filter(|s| s.clock != Clock::Done)
+            .map(|s| s.equity)
+            .sum();
+        let desired: Vec<f64> = self
+            .sleeves
+            .iter()
+            .zip(&weights)
+            .map(|(s, w)| {
+                if s.clock == Clock::Done {
+                    0.0
+                } else {
+                    w * total - s.equity
+                }
+            })
+            .collect();
+
+        // Only free cash can leave a sleeve; scale deposits to what was raised.
+        let withdrawals: Vec<f64> = self
+            .sleeves
+            .iter()
+            .zip(&desired)
+            .map(|(s, d)| {
+                if *d < 0.0 {
+                    (-d).min((s.equity - s.requirement).max(0.0))
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let raised: f64 = withdrawals.iter().sum();
+        let wanted: f64 = desired.iter().filter(|d| **d > 0.0).sum();
+        if raised <= 0.0 || wanted <= 0.0 {
+            return;
+        }
+        let deposit_scale = (raised / wanted).min(1.0);
+        let withdraw_scale = raised.min(wanted) / raised;
+
+        for ((sleeve, d), out) in self.sleeves.iter_mut().zip(&desired).zip(&withdrawals) {
+            let transfer = if *d > 0.0 {
+                d * deposit_scale
+            } else {
+                -out * withdraw_scale
+            };
+            sleeve.pending_transfer += transfer;
+            sleeve.equity += transfer;
+        }
+        self.rebalances += 1;
+    }
+
+    /// Total margin and equity of every sleeve except `index`.
+    fn others(&self, index: usize) -> (f64, f64) {
+        self.sleeves
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != index)
+            .fold((0.0, 0.0), |(req, eq), (_, s)| {
+                (req + s.requirement, eq + s.equity)
+            })
+    }
+}
+
+/// Shared state behind every sleeve link.
+#[derive(Debug)]
+struct BookInner {
+    state: Mutex<BookState>,
+    notify: Notify,
+}
+
+struct PortfolioBook(Arc<BookInner>);
+
+impl PortfolioBook {
+    fn new(sleeves: &[SleeveScript], config: &PortfolioConfig) -> Self {
+        let mut state = BookState {
+            config: *config,
+            sleeves: sleeves
+                .iter()
+                .map(|s| SleeveState {
+                    clock: Clock::Pending,
+                    weight: s.weight.max(0.0),
+                    risk_budget: s.risk_budget.max(0.0),
+                    initial_capital: 0.0,
+                    equity: 0.0,
+                    requirement: 0.0,
+                    open_by_symbol: HashMap::new(),
+                    pending_transfer: 0.0,
+                    transfers: Vec::new(),
+                    daily_pnl_equity: Vec::new(),
+                    blocked_entries: 0,
+                })
+                .collect(),
+            last_period: None,
+            rebalances: 0,
+        };
+        let weights = state.target_weights();
+        for (sleeve, w) in state.sleeves.iter_mut().zip(weights) {
+            sleeve.initial_capital = config.capital * w;
+            sleeve.equity = sleeve.initial_capital;
+        }
+        Self(Arc::new(BookInner {
+            state: Mutex::new(state),
+            notify: Notify::new(),
+        }))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BookState> {
+        self.0.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A sleeve's handle on the shared book, passed into the script engine.
+///
+/// Dropping the link marks the sleeve finished.
+pub struct SleeveLink {
+    book: Arc<BookInner>,
+    index: usize,
+}
+
+impl SleeveLink {
+    fn lock(&self) -> MutexGuard<'_, BookState> {
+        self.book.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Capital allocated to this sleeve at the start.
+    pub fn initial_capital(&self) -> f64 {
+        self.lock().sleeves[self.index].initial_capital
+    }
+
+    /// Wait until every other sleeve has processed all bars before `at`, then
+    /// return the rebalance cash to credit to this sleeve before the bar runs.
+    pub async fn sync(&self, at: NaiveDateTime) -> f64 {
+        self.lock().sleeves[self.index].clock = Clock::At(at);
+        self.book.notify.notify_waiters();
+        loop {
+            // Register before checking so a wake-up between the two is not lost
+            let notified = self.book.notify.notified();
+            {
+                let mut state = self.lock();
+                if state.is_turn(self.index, at) {
+                    state.maybe_rebalance(at);
+                    let sleeve = &mut state.sleeves[self.index];
+                    let transfer = std::mem::take(&mut sleeve.pending_transfer);
+                    if transfer != 0.0 {
+                        sleeve.transfers.push((at, transfer));
+                    }
+                    return transfer;
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// Reason `candidate` may not be opened under the shared pool, if any.
+    ///
+    /// `equity` is the sleeve's current mark-to-market equity and `positions`
+    /// its open book before the entry.
+    pub fn admission_shortfall(
+        &self,
+        positions: &[ScriptPosition],
+        candidate: &ScriptPosition,
+        equity: f64,
+        spot: impl Fn(&str) -> f64,
+        today: NaiveDate,
+        greeks: &GreeksParams,
+    ) -> Option<String> {
+        let mut state = self.lock();
+        let reason = Self::shortfall(
+            &state, self.index, positions, candidate, equity, spot, today, greeks,
+        );
+        if reason.is_some() {
+            state.sleeves[self.index].blocked_entries += 1;
+        }
+        reason
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn shortfall(
+        state: &BookState,
+        index: usize,
+        positions: &[ScriptPosition],
+        candidate: &ScriptPosition,
+        equity: f64,
+        spot: impl Fn(&str) -> f64,
+        today: NaiveDate,
+        greeks: &GreeksParams,
+    ) -> Option<String> {
+        let limits = state.config.limits;
+        let others = || {
+            state
+                .sleeves
+                .iter()
+                .enumerate()
+                .filter(move |(j, _)| *j != index)
+                .map(|(_, s)| s)
+        };
+
+        if let Some(max) = limits.max_positions {
+            let open = others().map(SleeveState::open_positions).sum::<usize>() + positions.len();
+            if open >= max {
+                return Some(format!("portfolio max_positions ({max}) reached"));
+            }
+        }
+        if let Some(max) = limits.max_positions_per_symbol {
+            let symbol = &candidate.symbol;
+            let open = others()
+                .map(|s| s.open_by_symbol.get(symbol).copied().unwrap_or(0))
+                .sum::<usize>()
+                + positions.iter().filter(|p| &p.symbol == symbol).count();
+            if open >= max {
+                return Some(format!(
+                    "portfolio max_positions_per_symbol ({max}) reached for {symbol}"
+                ));
+            }
+        }
+
+        let model = state.config.margin.model();
+        let mut book = positions.to_vec();
+        book.push(candidate.clone());
+        let required = model.requirement(&book, &spot, today, greeks);
+        if required.is_infinite() {
+            return Some("position cannot be carried under the portfolio margin model".to_string());
+        }
+        if let Some(max) = limits.max_sleeve_utilization {
+            if required > max * equity {
+                return Some(format!(
+                    "sleeve would commit ${required:.2}, cap is ${:.2}",
+                    max * equity
+                ));
+            }
+        }
+        let (others_required, others_equity) = state.others(index);
+        let pool_equity = others_equity + equity;
+        if others_required + required > pool_equity {
+            let available =
+                pool_equity - others_required - model.requirement(positions, &spot, today, greeks);
+            return Some(format!(
+                "requires ${:.2} of shared buying power, ${:.2} available",
+                required - model.requirement(positions, &spot, today, greeks),
+                available.max(0.0)
+            ));
+        }
+        None
+    }
+
+    /// Record the sleeve's state after a bar.
+    pub fn publish(
+        &self,
+        at: NaiveDateTime,
+        equity: f64,
+        positions: &[ScriptPosition],
+        spot: impl Fn(&str) -> f64,
+        greeks: &GreeksParams,
+    ) {
+        let mut state = self.lock();
+        let model = state.config.margin.model();
+        let requirement = model.requirement(positions, spot, at.date(), greeks);
+        let sleeve = &mut state.sleeves[self.index];
+        sleeve.equity = equity;
+        sleeve.requirement = if requirement.is_finite() {
+            requirement
+        } else {
+            equity.max(0.0)
+        };
+        sleeve.open_by_symbol.clear();
+        for pos in positions {
+            *sleeve.open_by_symbol.entry(pos.symbol.clone()).or_default() += 1;
+        }
+        let net = equity - sleeve.applied_transfers();
+        match sleeve.daily_pnl_equity.last_mut() {
+            Some((date, value)) if *date == at.date() => *value = net,
+            _ => sleeve.daily_pnl_equity.push((at.date(), net)),
+        }
+    }
+}
+
+impl Drop for SleeveLink {
+    fn drop(&mut self) {
+        self.lock().sleeves[self.index].clock = Clock::Done;
+        self.book.notify.notify_waiters();
+    }
+}
+
+// ── Consolidation ─────────────────────────────────────────────────────────
+
+/// Sleeve P&L (net of transfers) at the end of each day.
+fn daily_pnl(
+    curve: &[EquityPoint],
+    initial: f64,
+    transfers: &[(NaiveDateTime, f64)],
+) -> Vec<(NaiveDate, f64, f64)> {
+    let mut applied = 0.0;
+    let mut next = 0;
+    collapse_daily(curve, |point| {
+        while next < transfers.len() && transfers[next].0 <= point.datetime {
+            applied += transfers[next].1;
+            next += 1;
+        }
+        (
+            point.equity - initial - applied,
+            point.unrealized.unwrap_or(0.0),
+        )
+    })
+    .into_iter()
+    .map(|(date, (pnl, unrealized))| (date, pnl, unrealized))
+    .collect()
+}
+
+/// Sum sleeve P&L over the union of their dates, carrying each sleeve's last
+/// value forward (zero before its first bar).
+fn combine_pnl(series: &[Vec<(NaiveDate, f64, f64)>], capital: f64) -> Vec<EquityPoint> {
+    let mut dates: Vec<NaiveDate> = series.iter().flatten().map(|(d, _, _)| *d).collect();
+    dates.sort_unstable();
+    dates.dedup();
+    let mut cursors = vec![0usize; series.len()];
+    let mut last = vec![(0.0, 0.0); series.len()];
+
+    dates
+        .into_iter()
+        .map(|date| {
+            for (i, s) in series.iter().enumerate() {
+                while cursors[i] < s.len() && s[cursors[i]].0 <= date {
+                    last[i] = (s[cursors[i]].1, s[cursors[i]].2);
+                    cursors[i] += 1;
+                }
+            }
+            EquityPoint {
+                datetime: date.and_time(NaiveTime::MIN),
+                equity: capital + last.iter().map(|(p, _)| p).sum::<f64>(),
+                unrealized: Some(last.iter().map(|(_, u)| u).sum()),
+            }
+        })
+        .collect()
+}
+
+fn metrics_for(
+    curve: &[EquityPoint],
+    trades: &[TradeRecord],
+    capital: f64,
+) -> Result<PerformanceMetrics> {
+    if trades.is_empty() || curve.is_empty() {
+        return calculate_metrics(
+            &[EquityPoint {
+                datetime: NaiveDateTime::default(),
+                equity: capital,
+                unrealized: None,
+            }],
+            &[],
+            capital,
+            TRADING_DAYS_PER_YEAR,
+        );
+    }
+    calculate_metrics(curve, trades, capital, TRADING_DAYS_PER_YEAR)
+}
+
+fn consolidate(
+    sleeves: &[SleeveScript],
+    initial_weights: &[f64],
+    finished: &[ScriptBacktestResult],
+    state: &BookState,
+    config: &PortfolioConfig,
+) -> Result<PortfolioBacktestResult> {
+    let capital = config.capital;
+    let mut pnl_series = Vec::with_capacity(finished.len());
+    let mut trade_log: Vec<TradeRecord> = Vec::new();
+    let mut warnings = Vec::new();
+    let mut attribution = Vec::with_capacity(finished.len());
+
+    let final_total: f64 = finished
+        .iter()
+        .map(|r| r.result.equity_curve.last().map_or(0.0, |p| p.equity))
+        .sum();
+
+    for (((sleeve, run), sleeve_state), weight) in sleeves
+        .iter()
+        .zip(finished)
+        .zip(&state.sleeves)
+        .zip(initial_weights)
+    {
+        let result = &run.result;
+        let initial = sleeve_state.initial_capital;
+        let series = daily_pnl(&result.equity_curve, initial, &sleeve_state.transfers);
+        let pnl = series.last().map_or(0.0, |(_, p, _)| *p);
+        let own_curve: Vec<EquityPoint> = series
+            .iter()
+            .map(|(d, p, u)| EquityPoint {
+                datetime: d.and_time(NaiveTime::MIN),
+                equity: initial + p,
+                unrealized: Some(*u),
+            })
+            .collect();
+        let final_equity = result.equity_curve.last().map_or(initial, |p| p.equity);
+
+        attribution.push(SleeveAttribution {
+            label: sleeve.label.clone(),
+            symbol: result.symbol.clone(),
+            initial_weight: *weight,
+            final_weight: if final_total > 0.0 {
+                final_equity / final_total
+            } else {
+                0.0
+            },
+            initial_capital: initial,
+            net_transfers: sleeve_state.applied_transfers(),
+            pnl,
+            contribution_pct: pnl / capital * 100.0,
+            trade_count: result.trade_count,
+            blocked_entries: sleeve_state.blocked_entries,
+            metrics: metrics_for(&own_curve, &result.trade_log, initial)?,
+        });
+        pnl_series.push(series);
+        trade_log.extend(result.trade_log.iter().cloned());
+        warnings.extend(
+            result
+                .warnings
+                .iter()
+                .map(|w| format!("[{}] {w}", sleeve.label)),
+        );
+    }
+
+    trade_log.sort_by_key(|t| t.entry_datetime);
+    for (i, trade) in trade_log.iter_mut().enumerate() {
+        trade.trade_id = i + 1;
+    }
+    let equity_curve = combine_pnl(&pnl_series, capital);
+    let metrics = metrics_for(&equity_curve, &trade_log, capital)?;
+
+    Ok(PortfolioBacktestResult {
+        result: BacktestResult {
+            symbol: None,
+            trade_count: trade_log.len(),
+            total_pnl: trade_log.iter().map(|t| t.pnl).sum(),
+            metrics,
+            equity_curve,
+            trade_log,
+            quality: Default::default(),
+            warnings,
+            financing: None,
+            working_orders: None,
+        },
+        sleeves: attribution,
+        rebalances: state.rebalances,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(16, 0, 0)
+            .unwrap()
+    }
+
+    fn config(rebalance: RebalanceFrequency) -> PortfolioConfig {
+        PortfolioConfig {
+            capital: 100_000.0,
+            allocation: AllocationRule::FixedWeights,
+            rebalance,
+            risk_lookback: 63,
+            margin: PoolMargin::RegT,
+            limits: PortfolioLimits::default(),
+        }
+    }
+
+    fn sleeve(weight: f64) -> SleeveScript {
+        SleeveScript {
+            label: format!("w{weight}"),
+            source: String::new(),
+            params: HashMap::new(),
+            weight,
+            risk_budget: 1.0,
+        }
+    }
+
+    #[test]
+    fn initial_capital_follows_normalized_weights() {
+        let book = PortfolioBook::new(
+            &[sleeve(3.0), sleeve(1.0)],
+            &config(RebalanceFrequency::None),
+        );
+        let state = book.lock();
+        assert!((state.sleeves[0].initial_capital - 75_000.0).abs() < 1e-9);
+        assert!((state.sleeves[1].initial_capital - 25_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn turn_order_is_by_time_then_sleeve() {
+        let book = PortfolioBook::new(
+            &[sleeve(1.0), sleeve(1.0)],
+            &config(RebalanceFrequency::None),
+        );
+        let mut state = book.lock();
+        // A pending sleeve blocks everyone
+        state.sleeves[1].clock = Clock::Pending;
+        assert!(!state.is_turn(0, dt(2)));
+        state.sleeves[1].clock = Clock::At(dt(2));
+        assert!(state.is_turn(0, dt(2)));
+        assert!(!state.is_turn(1, dt(3)));
+        state.sleeves[0].clock = Clock::At(dt(3));
+        assert!(state.is_turn(1, dt(2)));
+        state.sleeves[0].clock = Clock::Done;
+        assert!(state.is_turn(1, dt(9)));
+    }
+
+    #[test]
+    fn rebalance_moves_only_free_cash_and_conserves_equity() {
+        let book = PortfolioBook::new(
+            &[sleeve(1.0), sleeve(1.0)],
+            &config(RebalanceFrequency::Monthly),
+        );
+        let mut state = book.lock();
+        for s in &mut state.sleeves {
+            s.clock = Clock::At(dt(31));
+        }
+        // Sleeve 0 grew to 70k with 60k committed; sleeve 1 shrank to 40k
+        state.sleeves[0].equity = 70_000.0;
+        state.sleeves[0].requirement = 60_000.0;
+        state.sleeves[1].equity = 40_000.0;
+
+        state.maybe_rebalance(dt(31));
+        assert_eq!(state.rebalances, 0, "first bar only sets the period");
+        let feb = NaiveDate::from_ymd_opt(2024, 2, 1)
+            .unwrap()
+            .and_hms_opt(16, 0, 0)
+            .unwrap();
+        state.maybe_rebalance(feb);
+        assert_eq!(state.rebalances, 1);
+
+        // Target is 55k each, but only 10k of sleeve 0 is free
+        assert!((state.sleeves[0].pending_transfer + 10_000.0).abs() < 1e-9);
+        assert!((state.sleeves[1].pending_transfer - 10_000.0).abs() < 1e-9);
+        let total: f64 = state.sleeves.iter().map(|s| s.equity).sum();
+        assert!((total - 110_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn risk_budget_weights_scale_with_inverse_volatility() {
+        let mut cfg = config(RebalanceFrequency::None);
+        cfg.allocation = AllocationRule::RiskBudget;
+        let book = PortfolioBook::new(&[sleeve(1.0), sleeve(1.0)], &cfg);
+        let mut state = book.lock();
+        let day = |i: u32| {
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(i64::from(i))
+        };
+        // Sleeve 1 swings twice as much as sleeve 0
+        for i in 0..20 {
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            state.sleeves[0]
+                .daily_pnl_equity
+                .push((day(i), 100.0 + sign));
+            state.sleeves[1]
+                .daily_pnl_equity
+                .push((day(i), 100.0 + 2.0 * sign));
+        }
+        let w = state.target_weights();
+        assert!((w[0] + w[1] - 1.0).abs() < 1e-9);
+        assert!(w[0] > 1.9 * w[1] && w[0] < 2.1 * w[1], "w={w:?}");
+    }
+
+    #[test]
+    fn combined_pnl_ignores_transfers() {
+        let transfers = vec![(dt(3), 5_000.0)];
+        let curve = vec![
+            EquityPoint {
+                datetime: dt(2),
+                equity: 50_100.0,
+                unrealized: None,
+            },
+            EquityPoint {
+                datetime: dt(3),
+                equity: 55_200.0,
+                unrealized: None,
+            },
+        ];
+        let series = daily_pnl(&curve, 50_000.0, &transfers);
+        assert_eq!(series.len(), 2);
+        assert!((series[0].1 - 100.0).abs() < 1e-9);
+        assert!((series[1].1 - 200.0).abs() < 1e-9);
+
+        let other = vec![(dt(3).date(), -50.0, 0.0)];
+        let combined = combine_pnl(&[series, other], 100_000.0);
+        assert!((combined[0].equity - 100_100.0).abs() < 1e-9);
+        assert!((combined[1].equity - 100_150.0).abs() < 1e-9);
+    }
+}
@@ -75,6 +75,7 @@ fn register_bar_context(engine: &mut Engine) {
     // Portfolio getters
     engine.register_get("cash", BarContext::get_cash);
     engine.register_get("equity", BarContext::get_equity);
+    engine.register_get("buying_power", BarContext::get_buying_power);
 
     // Position awareness (next-bar execution model)
     engine.register_get("market_position", BarContext::get_market_position);
@@ -236,6 +237,11 @@ fn register_script_position(engine: &mut Engine) {
     engine.register_get("is_options", ScriptPosition::get_is_options);
     engine.register_get("is_stock", ScriptPosition::get_is_stock);
     engine.register_get("source", ScriptPosition::get_source);
+    engine.register_get("iv", ScriptPosition::get_iv);
+    engine.register_get("delta", ScriptPosition::get_delta);
+    engine.register_get("gamma", ScriptPosition::get_gamma);
+    engine.register_get("theta", ScriptPosition::get_theta);
+    engine.register_get("vega", ScriptPosition::get_vega);
 }
 
 /// Register `PortfolioState` as a Rhai custom type with property getters.
@@ -249,6 +255,11 @@ fn register_portfolio_state(engine: &mut Engine) {
     engine.register_get("net_delta", PortfolioState::get_net_delta);
     engine.register_get("long_delta", PortfolioState::get_long_delta);
     engine.register_get("short_delta", PortfolioState::get_short_delta);
+    engine.register_get("net_gamma", PortfolioState::get_net_gamma);
+    engine.register_get("net_theta", PortfolioState::get_net_theta);
+    engine.register_get("net_vega", PortfolioState::get_net_vega);
+    engine.register_get("margin_requirement", PortfolioState::get_margin_requirement);
+    engine.register_get("buying_power", PortfolioState::get_buying_power);
     engine.register_get("position_count", PortfolioState::get_position_count);
     engine.register_get("long_count", PortfolioState::get_long_count);
     engine.register_get("short_count", PortfolioState::get_short_count);
@@ -287,8 +298,23 @@ fn register_action_helpers(engine: &mut Engine) {
     engine.register_fn("sell_limit", helpers::sell_limit);
     engine.register_fn("sell_stop", helpers::sell_stop);
     engine.register_fn("sell_stop_limit", helpers::sell_stop_limit);
+    engine.register_fn("oco", helpers::oco);
+    engine.register_fn("oto", helpers::oto);
+    engine.register_fn("bracket", helpers::bracket);
+    engine.register_fn("spread_limit", helpers::spread_limit);
+    engine.register_fn("spread_limit", helpers::spread_limit_fixed);
     engine.register_fn("cancel_orders", helpers::cancel_orders as fn() -> Dynamic);
     engine.register_fn("cancel_orders", helpers::cancel_orders_by_signal);
+
+    // Leg selectors for the named strategy helpers (ints accepted for prices)
+    engine.register_fn("strike", helpers::strike_selector);
+    engine.register_fn("strike", |k: i64| helpers::strike_selector(k as f64));
+    engine.register_fn("moneyness", helpers::moneyness_selector);
+    engine.register_fn("atm_offset", helpers::atm_offset_selector);
+    engine.register_fn("premium", helpers::premium_selector);
+    engine.register_fn("premium", |p: i64| helpers::premium_selector(p as f64));
+    engine.register_fn("wing", helpers::wing_selector);
+    engine.register_fn("wing", |w: i64| helpers::wing_selector(w as f64));
 }
 
 /// Register strategy helper methods on `BarContext`.
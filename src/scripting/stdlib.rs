@@ -140,14 +140,22 @@ pub struct ScriptMeta {
     pub sweep_profiles: Option<HashMap<String, Vec<SweepParamMeta>>>,
 }
 
-/// A sweep parameter range from a script's sweep profile.
+/// A sweep parameter range (or set of choices) from a script's sweep profile.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct SweepParamMeta {
     pub name: String,
+    #[serde(default)]
     pub start: f64,
+    #[serde(default)]
     pub stop: f64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub step: Option<f64>,
+    /// Choices for a categorical param (empty for numeric ranges).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<serde_json::Value>,
+    /// Parent condition for a conditional param.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<crate::engine::bayesian::ParamCondition>,
 }
 
 /// Parse `//!` doc-comment header from script source for metadata fields.
@@ -158,7 +166,8 @@ pub struct SweepParamMeta {
 /// Lines must start with `//!` followed by `key: value`.
 /// `tags` and `regime` accept comma-separated values.
 /// `profile.<name>:` accepts comma-separated `key=value` pairs.
-/// `sweep.<name>:` accepts comma-separated `NAME=start to stop [step S]` entries.
+/// `sweep.<name>:` accepts comma-separated `NAME=start to stop [step S]` or
+/// `NAME=choices a|b` entries, each optionally suffixed with `when PARENT=v1|v2`.
 pub fn parse_script_meta(id: &str, source: &str) -> ScriptMeta {
     let mut name = None;
     let mut description = None;
@@ -270,12 +279,53 @@ pub fn parse_script_meta(id: &str, source: &str) -> ScriptMeta {
     }
 }
 
-/// Parse a single sweep param entry like `PUT_DELTA=0.20 to 0.40 step 0.10`.
+/// Parse a single sweep param entry like `PUT_DELTA=0.20 to 0.40 step 0.10`,
+/// `SLIPPAGE=choices mid|spread`, `SLIPPAGE=choices` (every option the extern
+/// declares) or `WIDTH=1 to 5 when SLIPPAGE=spread`.
 fn parse_sweep_param_entry(s: &str) -> Option<SweepParamMeta> {
     let (name, rest) = s.split_once('=')?;
     let name = name.trim();
+    let (rest, when) = match rest.split_once(" when ") {
+        Some((rest, cond)) => {
+            let (parent, values) = cond.split_once('=')?;
+            let values: Vec<serde_json::Value> = values
+                .split('|')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(parse_profile_value)
+                .collect();
+            (
+                rest,
+                Some(crate::engine::bayesian::ParamCondition {
+                    parent: parent.trim().to_string(),
+                    values,
+                }),
+            )
+        }
+        None => (rest, None),
+    };
     let rest = rest.trim();
 
+    // A bare `choices` defers to the options declared on the param's `extern`
+    if let Some(choices) = rest
+        .strip_prefix("choices ")
+        .or((rest == "choices").then_some(""))
+    {
+        return Some(SweepParamMeta {
+            name: name.to_string(),
+            start: 0.0,
+            stop: 0.0,
+            step: None,
+            choices: choices
+                .split('|')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(parse_profile_value)
+                .collect(),
+            when,
+        });
+    }
+
     // Parse: start to stop [step S]
     let parts: Vec<&str> = rest.split_whitespace().collect();
     if parts.len() < 3 || parts[1] != "to" {
@@ -293,6 +343,8 @@ fn parse_sweep_param_entry(s: &str) -> Option<SweepParamMeta> {
         start,
         stop,
         step,
+        choices: Vec::new(),
+        when,
     })
 }
 
@@ -532,7 +584,7 @@ pub fn list_scripts() -> Vec<ScriptMeta> {
 
             // For .trading files, transpile to Rhai for metadata and param extraction
             let rhai_source = if filename.ends_with(".trading") {
-                crate::scripting::dsl::transpile(&source).ok()?
+                crate::scripting::dsl::transpile_for_metadata(&source).ok()?
             } else {
                 source
             };
@@ -355,6 +355,126 @@ mod tests {
         assert!(store.get_at(&key, 1, 5).is_none());
     }
 
+    #[test]
+    fn test_indicator_store_implied_vol() {
+        let bars = make_bars(&[10.0, 11.0, 12.0, 13.0, 14.0]);
+        let decls = vec![
+            "atm_iv".to_string(),
+            "iv_rank:3".to_string(),
+            "iv_percentile:3".to_string(),
+        ];
+        // IV indicators are not derived from closes
+        let mut store = IndicatorStore::build(&decls, &bars).unwrap();
+        let rank_key = IndicatorKey {
+            name: "iv_rank".to_string(),
+            params: vec![IndicatorParam::Int(3)],
+        };
+        assert!(!store.contains(&rank_key));
+
+        store
+            .insert_implied_vol(&decls, &[f64::NAN, 0.2, 0.3, 0.1, 0.25])
+            .unwrap();
+        let atm_key = IndicatorKey {
+            name: "atm_iv".to_string(),
+            params: vec![],
+        };
+        assert!((store.get(&atm_key, 4).unwrap() - 0.25).abs() < 1e-10);
+        assert!(store.get(&rank_key, 2).unwrap().is_nan());
+        assert!((store.get(&rank_key, 4).unwrap() - 75.0).abs() < 1e-10);
+        let pct_key = IndicatorKey {
+            name: "iv_percentile".to_string(),
+            params: vec![IndicatorParam::Int(3)],
+        };
+        assert!((store.get(&pct_key, 4).unwrap() - 50.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_indicator_store_options_structure() {
+        use crate::engine::hypothesis::OptionsStructureSeries;
+
+        let bars = make_bars(&[10.0, 11.0, 12.0, 13.0, 14.0]);
+        let decls = vec![
+            "skew_25d".to_string(),
+            "skew_25d:3".to_string(),
+            "put_call_volume".to_string(),
+        ];
+        let mut store = IndicatorStore::build(&decls, &bars).unwrap();
+        assert!(crate::scripting::indicators::needs_implied_vol(&decls));
+        let structure = OptionsStructureSeries {
+            skew: vec![f64::NAN, 0.02, 0.04, 0.01, 0.03],
+            term_slope: vec![0.0; 5],
+            put_call_volume: None,
+        };
+        store
+            .insert_options_structure(&decls, &structure, &[f64::NAN; 5])
+            .unwrap();
+        let raw_key = IndicatorKey {
+            name: "skew_25d".to_string(),
+            params: vec![],
+        };
+        assert!((store.get(&raw_key, 4).unwrap() - 0.03).abs() < 1e-10);
+        let pct_key = IndicatorKey {
+            name: "skew_25d".to_string(),
+            params: vec![IndicatorParam::Int(3)],
+        };
+        assert!((store.get(&pct_key, 4).unwrap() - 50.0).abs() < 1e-10);
+        // No volume in the chain → the indicator stays absent
+        assert!(!store.contains(&IndicatorKey {
+            name: "put_call_volume".to_string(),
+            params: vec![],
+        }));
+    }
+
+    #[test]
+    fn test_indicator_store_regime_is_causal() {
+        // Alternating calm / volatile 40-bar blocks
+        let mut price = 100.0;
+        let prices: Vec<f64> = (0..320)
+            .map(|i: usize| {
+                let amp = if (i / 40).is_multiple_of(2) {
+                    0.002
+                } else {
+                    0.03
+                };
+                let sign = if (i * 7 + i / 3).is_multiple_of(2) {
+                    1.0
+                } else {
+                    -1.0
+                };
+                price *= 1.0 + sign * amp * (1.0 + (i % 5) as f64 / 5.0);
+                price
+            })
+            .collect();
+        let decls = vec!["regime:2:120".to_string()];
+        let full = IndicatorStore::build(&decls, &make_bars(&prices)).unwrap();
+        let key = IndicatorKey {
+            name: "regime".to_string(),
+            params: vec![IndicatorParam::Int(2), IndicatorParam::Int(120)],
+        };
+        assert!(full.get(&key, 120).unwrap().is_nan(), "still warming up");
+        assert!(!full.get(&key, 121).unwrap().is_nan());
+
+        // Dropping future bars must not change any earlier label
+        let truncated = IndicatorStore::build(&decls, &make_bars(&prices[..230])).unwrap();
+        for bar in 0..230 {
+            let (a, b) = (
+                full.get(&key, bar).unwrap(),
+                truncated.get(&key, bar).unwrap(),
+            );
+            assert!(a.to_bits() == b.to_bits(), "bar {bar}: {a} vs {b}");
+        }
+
+        let regime = crate::scripting::helpers::regime_lookup(&full, 300, "hmm", 2, 120);
+        let map = regime.cast::<rhai::Map>();
+        let state = map["state"].as_int().unwrap();
+        let label = map["label"].clone().into_string().unwrap();
+        assert_eq!(label, if state == 0 { "low_vol" } else { "high_vol" });
+        let posterior = map["posterior"].as_float().unwrap();
+        assert!((0.0..=1.0).contains(&posterior));
+        assert!(crate::scripting::helpers::regime_lookup(&full, 300, "kmeans", 2, 120).is_unit());
+        assert!(crate::scripting::helpers::regime_lookup(&full, 50, "hmm", 2, 120).is_unit());
+    }
+
     // -----------------------------------------------------------------------
     // Stdlib injection tests
     // -----------------------------------------------------------------------
@@ -635,6 +755,11 @@ mod tests {
             min_days_between_entries: None,
             expiration_filter: ExpirationFilter::Any,
             trade_selector: TradeSelector::Nearest,
+            greeks_params: Default::default(),
+            greek_limits: Default::default(),
+            margin: None,
+            early_assignment: None,
+            financing: None,
             defaults: HashMap::new(),
             procedural: false,
         });
@@ -829,6 +954,7 @@ mod tests {
                 entry_bar_idx: 0,
                 source: String::new(),
                 implicit: false,
+                order_group: None,
                 group: None,
                 trailing_stop: None,
             },
@@ -848,6 +974,7 @@ mod tests {
                 entry_bar_idx: 0,
                 source: String::new(),
                 implicit: false,
+                order_group: None,
                 group: None,
                 trailing_stop: None,
             },
@@ -856,6 +983,98 @@ mod tests {
         assert!((ctx.get_unrealized_pnl() - 300.0).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_position_net_greeks_signed_by_side() {
+        use crate::engine::types::{OptionType, Side};
+        use crate::scripting::types::ScriptPositionLeg;
+        let bars = make_bars(&[100.0]);
+        let date = bars[0].datetime.date();
+        let leg = |option_type, side, strike, delta| ScriptPositionLeg {
+            strike,
+            option_type,
+            side,
+            expiration: date + chrono::Duration::days(30),
+            entry_price: 1.0,
+            current_price: 1.0,
+            delta,
+            iv: 0.2,
+            gamma: 0.02,
+            theta: -0.05,
+            vega: 0.1,
+            qty: 2,
+        };
+        // Short 95 put / long 90 put credit spread
+        let pos = ScriptPosition {
+            symbol: "TEST".into(),
+            id: 1,
+            entry_date: date,
+            inner: ScriptPositionInner::Options {
+                legs: vec![
+                    leg(OptionType::Put, Side::Short, 95.0, -0.30),
+                    leg(OptionType::Put, Side::Long, 90.0, -0.10),
+                ],
+                expiration: date + chrono::Duration::days(30),
+                secondary_expiration: None,
+                multiplier: 100,
+            },
+            entry_cost: -200.0,
+            unrealized_pnl: 0.0,
+            days_held: 0,
+            current_date: date,
+            entry_bar_idx: 0,
+            source: String::new(),
+            implicit: false,
+            order_group: None,
+            group: None,
+            trailing_stop: None,
+        };
+        let g = pos.net_greeks();
+        // (+0.30 - 0.10) × 2 × 100
+        assert!((g.delta - 40.0).abs() < 1e-10);
+        // Equal per-leg gamma/theta/vega net to zero across the spread
+        assert!(g.gamma.abs() < 1e-10 && g.theta.abs() < 1e-10 && g.vega.abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_greek_limits_block_only_worsening_entries() {
+        use crate::scripting::types::{GreekLimits, NetGreeks};
+        let limits = GreekLimits {
+            max_abs_vega: Some(500.0),
+            min_theta: Some(-20.0),
+            ..GreekLimits::default()
+        };
+        let book = NetGreeks {
+            vega: -450.0,
+            theta: 30.0,
+            ..NetGreeks::default()
+        };
+        let more_short_vega = NetGreeks {
+            vega: -600.0,
+            ..book
+        };
+        let reason = limits.breach(&book, &more_short_vega).unwrap();
+        assert!(reason.starts_with("max_abs_vega"), "{reason}");
+
+        // Already over the limit: a hedge that reduces |vega| is allowed
+        let over = NetGreeks {
+            vega: -700.0,
+            ..book
+        };
+        assert!(limits.breach(&over, &more_short_vega).is_none());
+
+        let long_premium = NetGreeks {
+            theta: -25.0,
+            ..book
+        };
+        assert!(limits
+            .breach(&book, &long_premium)
+            .unwrap()
+            .starts_with("min_theta"));
+        assert!(GreekLimits::default()
+            .breach(&book, &long_premium)
+            .is_none());
+    }
+
     #[test]
     fn test_realized_pnl() {
         let bars = make_bars(&[100.0]);
@@ -1436,6 +1655,7 @@ mod tests {
                 entry_bar_idx: 0,
                 source: String::new(),
                 implicit: false,
+                order_group: None,
                 group: None,
                 trailing_stop: None,
             },
@@ -1455,6 +1675,7 @@ mod tests {
                 entry_bar_idx: 0,
                 source: String::new(),
                 implicit: false,
+                order_group: None,
                 group: None,
                 trailing_stop: None,
             },
@@ -1464,6 +1685,294 @@ mod tests {
         assert!((ctx.get_total_exposure() - 14500.0).abs() < 1e-10);
     }
 
+    // -----------------------------------------------------------------------
+    // Leg selector tests
+    // -----------------------------------------------------------------------
+
+    /// One quote date with calls and puts at strikes 90..=110 for a 32-DTE and a
+    /// 60-DTE expiration. Mids fall off linearly with distance OTM; spot is 100.
+    fn make_selector_chain() -> polars::prelude::DataFrame {
+        use crate::data::parquet::DATETIME_COL;
+        use chrono::NaiveDate;
+        use polars::prelude::*;
+
+        let quote = NaiveDate::from_ymd_opt(2024, 1, 15)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let near = NaiveDate::from_ymd_opt(2024, 2, 16).unwrap();
+        let far = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+
+        let (mut dates, mut types, mut strikes, mut bids, mut asks, mut deltas) =
+            (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let (mut expirations, mut dtes, mut spots) = (vec![], vec![], vec![]);
+        for (expiration, dte) in [(near, 32), (far, 60)] {
+            for option_type in ["c", "p"] {
+                for strike in [90.0, 95.0, 100.0, 105.0, 110.0] {
+                    let otm: f64 = if option_type == "c" {
+                        strike - 100.0
+                    } else {
+                        100.0 - strike
+                    };
+                    let mid = (3.0 - otm * 0.2).max(0.5);
+                    let delta = (0.5 - otm * 0.04).clamp(0.05, 0.95);
+                    dates.push(quote);
+                    types.push(option_type);
+                    strikes.push(strike);
+                    bids.push(mid - 0.1);
+                    asks.push(mid + 0.1);
+                    deltas.push(if option_type == "c" { delta } else { -delta });
+                    expirations.push(expiration);
+                    dtes.push(dte);
+                    spots.push(100.0);
+                }
+            }
+        }
+        let mut df = df! {
+            DATETIME_COL => &dates,
+            "option_type" => &types,
+            "strike" => &strikes,
+            "bid" => &bids,
+            "ask" => &asks,
+            "delta" => &deltas,
+            "dte" => &dtes,
+            "underlying_price" => &spots,
+        }
+        .unwrap();
+        df.with_column(
+            DateChunked::from_naive_date(PlSmallStr::from("expiration"), expirations).into_column(),
+        )
+        .unwrap();
+        df
+    }
+
+    fn selected_strike(df: &polars::prelude::DataFrame) -> f64 {
+        df.column("strike").unwrap().f64().unwrap().get(0).unwrap()
+    }
+
+    fn selected_dte(df: &polars::prelude::DataFrame) -> i32 {
+        df.column("dte").unwrap().i32().unwrap().get(0).unwrap()
+    }
+
+    #[test]
+    fn test_partitioned_options_index_contracts_by_dte_strike_and_type() {
+        use crate::engine::greeks::GreeksParams;
+        use crate::engine::types::{ExpirationFilter, OptionType};
+        use crate::scripting::options_cache::DatePartitionedOptions;
+        use chrono::NaiveDate;
+
+        let opts = DatePartitionedOptions::from_df(
+            &make_selector_chain(),
+            &ExpirationFilter::Any,
+            &GreeksParams::default(),
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let far = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+
+        // 95 put is 5 points OTM: mid 2.0
+        let (bid, ask) = opts
+            .contract_quote(today, OptionType::Put, 95.0, far)
+            .expect("indexed contract");
+        assert!((bid - 1.9).abs() < 1e-10 && (ask - 2.1).abs() < 1e-10);
+        let greeks = opts
+            .contract_greeks(today, OptionType::Put, 95.0, far)
+            .expect("indexed contract");
+        assert!(greeks.delta < 0.0);
+
+        assert!(opts
+            .contract_quote(today, OptionType::Put, 97.5, far)
+            .is_none());
+        assert!(opts
+            .contract_quote(today.succ_opt().unwrap(), OptionType::Put, 95.0, far)
+            .is_none());
+    }
+
+    #[test]
+    fn test_select_legs_by_strike_moneyness_offset_and_premium() {
+        use crate::engine::types::OptionType;
+        use crate::scripting::helpers::select_leg_contracts;
+        use crate::scripting::types::LegSelector;
+
+        let chain = make_selector_chain();
+        let rows = select_leg_contracts(
+            &chain,
+            &[
+                (OptionType::Call, LegSelector::Strike(103.0), 30),
+                (OptionType::Put, LegSelector::Moneyness(0.05), 30),
+                (OptionType::Call, LegSelector::AtmOffset(2), 55),
+                (OptionType::Put, LegSelector::AtmOffset(1), 30),
+                (OptionType::Put, LegSelector::Premium(2.0), 30),
+            ],
+            None,
+        )
+        .unwrap();
+
+        assert!((selected_strike(&rows[0]) - 105.0).abs() < 1e-10);
+        assert_eq!(selected_dte(&rows[0]), 32);
+        // 5% OTM put from spot 100 → 95
+        assert!((selected_strike(&rows[1]) - 95.0).abs() < 1e-10);
+        // Two strikes above ATM in the expiration nearest 55 DTE
+        assert!((selected_strike(&rows[2]) - 110.0).abs() < 1e-10);
+        assert_eq!(selected_dte(&rows[2]), 60);
+        // Positive offsets move puts down the chain
+        assert!((selected_strike(&rows[3]) - 95.0).abs() < 1e-10);
+        // Put mid 2.0 sits 5 points OTM
+        assert!((selected_strike(&rows[4]) - 95.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_select_legs_same_strike_as_resolves_forward_references() {
+        use crate::engine::types::OptionType;
+        use crate::scripting::helpers::select_leg_contracts;
+        use crate::scripting::types::LegSelector;
+
+        let chain = make_selector_chain();
+        // Iron condor with 5-point wings listed before the short strikes
+        let rows = select_leg_contracts(
+            &chain,
+            &[
+                (
+                    OptionType::Put,
+                    LegSelector::SameStrikeAs {
+                        leg: 1,
+                        width: -5.0,
+                    },
+                    45,
+                ),
+                (OptionType::Put, LegSelector::Strike(95.0), 45),
+                (OptionType::Call, LegSelector::Strike(105.0), 45),
+                (
+                    OptionType::Call,
+                    LegSelector::SameStrikeAs { leg: 2, width: 5.0 },
+                    45,
+                ),
+            ],
+            None,
+        )
+        .unwrap();
+        let strikes: Vec<f64> = rows.iter().map(selected_strike).collect();
+        assert_eq!(strikes, vec![90.0, 95.0, 105.0, 110.0]);
+        assert!(rows.iter().all(|r| selected_dte(r) == 32));
+
+        // Legs that only reference each other can never resolve
+        let cyclic = select_leg_contracts(
+            &chain,
+            &[
+                (
+                    OptionType::Put,
+                    LegSelector::SameStrikeAs { leg: 1, width: 0.0 },
+                    45,
+                ),
+                (
+                    OptionType::Call,
+                    LegSelector::SameStrikeAs { leg: 0, width: 0.0 },
+                    45,
+                ),
+            ],
+            None,
+        );
+        assert!(cyclic.is_none());
+    }
+
+    #[test]
+    fn test_select_legs_keeps_pinned_legs_exact() {
+        use crate::engine::types::OptionType;
+        use crate::scripting::helpers::select_legs_around_pinned;
+        use crate::scripting::types::LegSelector;
+        use chrono::NaiveDate;
+
+        let chain = make_selector_chain();
+        let far = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let near = NaiveDate::from_ymd_opt(2024, 2, 16).unwrap();
+        // A put spread whose short leg was pinned by find_option at 102.5 (not
+        // listed today), a wing anchored to it, and a pinned 110 call far
+        // outside any DTE window the selector would search
+        let rows = select_legs_around_pinned(
+            &chain,
+            &[
+                (OptionType::Put, LegSelector::Strike(102.5), 60),
+                (
+                    OptionType::Put,
+                    LegSelector::SameStrikeAs {
+                        leg: 0,
+                        width: -7.5,
+                    },
+                    60,
+                ),
+                (OptionType::Call, LegSelector::Strike(110.0), 200),
+                (OptionType::Call, LegSelector::Delta(0.30), 30),
+            ],
+            &[Some((102.5, far)), None, Some((110.0, near)), None],
+            None,
+        )
+        .expect("pinned legs are never re-selected");
+
+        assert!(rows[0].is_none() && rows[2].is_none());
+        let wing = rows[1].as_ref().unwrap();
+        assert!((selected_strike(wing) - 95.0).abs() < 1e-10);
+        // The wing shares the pinned leg's expiration
+        assert_eq!(selected_dte(wing), 60);
+        let call = rows[3].as_ref().unwrap();
+        assert!((selected_strike(call) - 105.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_strategy_leg_maps_wing_to_anchor() {
+        use crate::scripting::helpers::{parse_leg_selector, strategy_leg, wing_selector};
+        use crate::scripting::types::LegSelector;
+
+        let wing = strategy_leg("long", "put", &wing_selector(5.0), 45, Some((1, -1.0)));
+        let map = wing.cast::<rhai::Map>();
+        assert_eq!(get_i64(&map, "same_strike_as"), 1);
+        assert!((get_f64(&map, "width") + 5.0).abs() < 1e-10);
+        assert_eq!(
+            parse_leg_selector(&map),
+            Some(LegSelector::SameStrikeAs {
+                leg: 1,
+                width: -5.0
+            })
+        );
+
+        // Numbers stay delta targets; single legs have nothing to anchor a wing to
+        let delta = strategy_leg("short", "put", &Dynamic::from(0.30), 45, None);
+        let map = delta.cast::<rhai::Map>();
+        assert_eq!(parse_leg_selector(&map), Some(LegSelector::Delta(0.30)));
+        assert!(strategy_leg("short", "put", &wing_selector(5.0), 45, None).is_unit());
+
+        // Conflicting selectors are rejected
+        let mut both = rhai::Map::new();
+        both.insert("delta".into(), Dynamic::from(0.30));
+        both.insert("premium".into(), Dynamic::from(1.0));
+        assert_eq!(parse_leg_selector(&both), None);
+    }
+
+    #[test]
+    fn test_named_helpers_accept_selectors() {
+        let engine = build_engine();
+        let ast = engine
+            .compile(
+                r"
+            fn on_bar(ctx) {
+                [
+                    ctx.iron_condor(0.16, wing(5), 0.16, wing(5.0), 45),
+                    ctx.bull_put_spread(moneyness(0.05), wing(5), 45),
+                    ctx.short_strangle(atm_offset(2), premium(1.5), 45),
+                    ctx.short_put(strike(450), 30),
+                ]
+            }
+            ",
+            )
+            .unwrap();
+        // No options data on this context, so every builder resolves to `()`
+        let ctx = make_ctx(&make_bars(&[100.0]), 0);
+        let result: rhai::Array = engine
+            .call_fn(&mut Scope::new(), &ast, "on_bar", (ctx,))
+            .unwrap();
+        assert_eq!(result.len(), 4);
+        assert!(result.iter().all(Dynamic::is_unit));
+    }
+
     // -----------------------------------------------------------------------
     // Custom series plotting tests
     // -----------------------------------------------------------------------
@@ -1592,6 +2101,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         // Market orders always fill at the open
@@ -1619,6 +2130,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         // Low reaches limit → fills at limit price
@@ -1654,6 +2167,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         // High reaches stop → fills at stop price
@@ -1689,6 +2204,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         // Low reaches stop → fills at stop price
@@ -1720,6 +2237,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         assert!(!order.is_expired(6));
@@ -1727,6 +2246,40 @@ mod tests {
         assert!(order.is_expired(9)); // 4 bars = expired
     }
 
+    #[test]
+    fn test_pending_order_oco_membership() {
+        use crate::engine::types::Side;
+        use crate::scripting::types::{OrderGroup, OrderType, PendingOrder, ScriptAction};
+
+        let mut order = PendingOrder {
+            symbol: None,
+            action: ScriptAction::OpenStock {
+                symbol: None,
+                side: Side::Long,
+                qty: 100,
+            },
+            order_type: OrderType::Stop { price: 105.0 },
+            is_buy: true,
+            signal: None,
+            submitted_bar: 0,
+            ttl: None,
+            stop_loss: None,
+            profit_target: None,
+            trailing_stop: None,
+            group: Some(OrderGroup {
+                id: "breakout".into(),
+                oco: true,
+            }),
+            triggers: vec![],
+        };
+        assert!(order.in_oco_group("breakout"));
+        assert!(!order.in_oco_group("other"));
+
+        // Children of a bracket share the group id but are not OCO members
+        order.group.as_mut().unwrap().oco = false;
+        assert!(!order.in_oco_group("breakout"));
+    }
+
     #[test]
     fn test_pending_order_gtc_never_expires() {
         use crate::engine::types::Side;
@@ -1747,6 +2300,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         assert!(!order.is_expired(1000));
@@ -1775,6 +2330,8 @@ mod tests {
             stop_loss: None,
             profit_target: None,
             trailing_stop: None,
+            group: None,
+            triggers: vec![],
         };
 
         // Both conditions met: high >= stop AND low <= limit
@@ -1786,6 +2343,99 @@ mod tests {
         assert!(fill.is_none());
     }
 
+    #[test]
+    fn test_spread_limit_ladder_steps_toward_natural() {
+        use crate::scripting::types::OrderType;
+
+        // Debit spread quoted 1.00 mid / 1.20 natural: start at 0.95, step 0.10
+        let mut order = OrderType::SpreadLimit {
+            offset: 0.05,
+            step: 0.10,
+            limit: None,
+            start_natural: None,
+        };
+        assert!(!order.work_spread_limit(1.00, 1.20));
+        assert!(!order.work_spread_limit(1.00, 1.20));
+        // Third bar: the limit has climbed to 1.15 and the natural dips to 1.12
+        assert!(order.work_spread_limit(1.00, 1.12));
+        let OrderType::SpreadLimit {
+            limit,
+            start_natural,
+            ..
+        } = order
+        else {
+            unreachable!()
+        };
+        assert!((limit.unwrap() - 1.15).abs() < 1e-9);
+        assert_eq!(start_natural, Some(1.20));
+    }
+
+    #[test]
+    fn test_spread_limit_credit_caps_at_natural() {
+        use crate::scripting::types::OrderType;
+
+        // Credit spread: receive 0.80 mid, 0.70 natural (debits -0.80 / -0.70)
+        let mut order = OrderType::SpreadLimit {
+            offset: 0.0,
+            step: 0.25,
+            limit: None,
+            start_natural: None,
+        };
+        assert!(!order.work_spread_limit(-0.80, -0.70));
+        // The step would overshoot, so the limit stops at the natural and fills
+        assert!(order.work_spread_limit(-0.80, -0.70));
+
+        // Zero step never chases: the order only fills if the market comes to it
+        let mut fixed = OrderType::SpreadLimit {
+            offset: 0.0,
+            step: 0.0,
+            limit: None,
+            start_natural: None,
+        };
+        for _ in 0..5 {
+            assert!(!fixed.work_spread_limit(-0.80, -0.70));
+        }
+        assert!(fixed.work_spread_limit(-0.85, -0.80));
+    }
+
+    #[test]
+    fn test_spread_limit_never_fills_against_bar() {
+        use crate::engine::types::{OptionType, Side};
+        use crate::scripting::types::{
+            LegSelector, LegSpec, OrderType, PendingOrder, ScriptAction,
+        };
+
+        let order = PendingOrder {
+            symbol: None,
+            action: ScriptAction::OpenOptions {
+                legs: vec![LegSpec::Unresolved {
+                    side: Side::Long,
+                    option_type: OptionType::Call,
+                    selector: LegSelector::Delta(0.5),
+                    dte: 30,
+                }],
+                qty: Some(1),
+                symbol: None,
+            },
+            order_type: OrderType::SpreadLimit {
+                offset: 0.0,
+                step: 0.0,
+                limit: None,
+                start_natural: None,
+            },
+            is_buy: true,
+            signal: None,
+            submitted_bar: 0,
+            ttl: Some(3),
+            stop_loss: None,
+            profit_target: None,
+            trailing_stop: None,
+            group: None,
+            triggers: vec![],
+        };
+        assert!(order.try_fill(150.0, 155.0, 148.0, 152.0).is_none());
+    }
+
     #[test]
     fn test_position_awareness_flat() {
         use crate::scripting::engine::compute_position_awareness;
@@ -1819,6 +2469,7 @@ mod tests {
             entry_bar_idx: 5,
             source: "script".to_string(),
             implicit: false,
+            order_group: None,
             group: None,
             trailing_stop: None,
         };
@@ -1983,4 +2634,26 @@ fn on_bar(ctx) {
 
         assert!(declarations.contains(&"bbands_upper:20:20".to_string()));
     }
+
+    #[test]
+    fn test_runtime_param_indicator_expansion_for_regime() {
+        let source = r#"
+fn on_bar(ctx) {
+    let r = ctx.regime("hmm", 3, LOOKBACK);
+    let label = ctx.regime(2);
+    []
+}
+"#;
+        let params =
+            std::collections::HashMap::from([("LOOKBACK".to_string(), serde_json::json!(126))]);
+
+        let declarations = crate::scripting::indicators::augment_declarations_from_runtime_params(
+            &[],
+            source,
+            &params,
+        );
+
+        assert!(declarations.contains(&"regime:3:126".to_string()));
+        assert!(declarations.contains(&"regime:2".to_string()));
+    }
 }
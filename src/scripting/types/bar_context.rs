@@ -123,6 +123,14 @@ pub struct PortfolioState {
     pub net_delta: f64,
     pub long_delta: f64,
     pub short_delta: f64,
+    /// Dollar-weighted book greeks (see `NetGreeks` for units).
+    pub net_gamma: f64,
+    pub net_theta: f64,
+    pub net_vega: f64,
+    /// Requirement under `config().engine.margin` (0 when no model is set).
+    pub margin_requirement: f64,
+    /// Mark-to-market equity less `margin_requirement`.
+    pub buying_power: f64,
     pub position_count: i64,
     pub long_count: i64,
     pub short_count: i64,
@@ -160,6 +168,21 @@ impl PortfolioState {
     pub fn get_short_delta(&mut self) -> f64 {
         self.short_delta
     }
+    pub fn get_net_gamma(&mut self) -> f64 {
+        self.net_gamma
+    }
+    pub fn get_net_theta(&mut self) -> f64 {
+        self.net_theta
+    }
+    pub fn get_net_vega(&mut self) -> f64 {
+        self.net_vega
+    }
+    pub fn get_margin_requirement(&mut self) -> f64 {
+        self.margin_requirement
+    }
+    pub fn get_buying_power(&mut self) -> f64 {
+        self.buying_power
+    }
     pub fn get_position_count(&mut self) -> i64 {
         self.position_count
     }
@@ -224,6 +247,10 @@ impl BarContext {
     pub fn get_equity(&mut self) -> f64 {
         self.equity
     }
+    /// Mark-to-market equity less the margin requirement of open positions.
+    pub fn get_buying_power(&mut self) -> f64 {
+        self.portfolio.buying_power
+    }
 
     // --- Position awareness getters ---
     pub fn get_market_position(&mut self) -> i64 {
@@ -547,7 +574,9 @@ impl BarContext {
     // --- Options chain ---
 
     /// Build an options strategy from an array of leg specifications.
-    /// Each leg: `#{ side: "short", option_type: "put", delta: 0.30, dte: 45 }`
+    /// Each leg: `#{ side: "short", option_type: "put", delta: 0.30, dte: 45 }`.
+    /// Instead of `delta` a leg may set `strike`, `moneyness`, `atm_offset`,
+    /// `premium`, or `same_strike_as` (leg index, with optional `width` points).
     /// Returns `#{ legs: [...], net_premium }` or `()` if any leg can't be filled.
     ///
     /// Works for any structure — single legs, spreads, condors, butterflies, etc.
@@ -556,6 +585,7 @@ impl BarContext {
             legs,
             &self.options_by_date,
             self.datetime,
+            self.close,
             None, // no symbol tag for single-symbol BarContext
         )
     }
@@ -861,7 +891,8 @@ fn parse_indicator_ref(s: &str) -> (String, i64) {
 // ---------------------------------------------------------------------------
 
 /// Convert a DataFrame row to a Rhai Map for find_option results.
-/// Returns `#{ strike, bid, ask, delta, expiration, dte }` or `()`.
+/// Returns `#{ strike, bid, ask, delta, iv, gamma, theta, vega, expiration, dte }`
+/// or `()`. Greeks that could not be solved for the row read as 0.
 pub(in crate::scripting) fn row_to_option_map(
     df: &polars::prelude::DataFrame,
     row: usize,
@@ -878,6 +909,10 @@ pub(in crate::scripting) fn row_to_option_map(
     let bid = get_f64("bid").unwrap_or(0.0);
     let ask = get_f64("ask").unwrap_or(0.0);
     let delta = get_f64("delta").unwrap_or(0.0);
+    let iv = get_f64("iv").unwrap_or(0.0);
+    let gamma = get_f64("gamma").unwrap_or(0.0);
+    let theta = get_f64("theta").unwrap_or(0.0);
+    let vega = get_f64("vega").unwrap_or(0.0);
 
     // Get expiration date — handle both Date and Datetime column types
     let expiration: Option<NaiveDate> = df.column("expiration").ok().and_then(|c| {
@@ -918,6 +953,10 @@ pub(in crate::scripting) fn row_to_option_map(
     map.insert("bid".into(), Dynamic::from(bid));
     map.insert("ask".into(), Dynamic::from(ask));
     map.insert("delta".into(), Dynamic::from(delta));
+    map.insert("iv".into(), Dynamic::from(iv));
+    map.insert("gamma".into(), Dynamic::from(gamma));
+    map.insert("theta".into(), Dynamic::from(theta));
+    map.insert("vega".into(), Dynamic::from(vega));
     map.insert("expiration".into(), Dynamic::from(expiration.to_string()));
     map.insert("dte".into(), Dynamic::from(dte));
     Dynamic::from(map)
@@ -1001,6 +1040,11 @@ mod tests {
             min_days_between_entries: None,
             expiration_filter: Default::default(),
             trade_selector: Default::default(),
+            greeks_params: Default::default(),
+            greek_limits: Default::default(),
+            margin: None,
+            early_assignment: None,
+            financing: None,
             defaults: HashMap::new(),
             procedural: false,
         };
@@ -8,12 +8,19 @@ use chrono::NaiveDate;
 use std::sync::Arc;
 
 use crate::constants::TRADING_DAYS_PER_YEAR;
+use crate::data::adjustment_store::DividendRow;
 use crate::engine::adjustments::AdjustmentTimeline;
+use crate::engine::greeks::GreeksParams;
 use crate::engine::sim_types::{DateIndex, LastKnown, PriceTable};
 use crate::engine::types::{Commission, ExpirationFilter, Slippage, TradeSelector};
+use crate::scripting::assignment::EarlyAssignment;
+use crate::scripting::financing::FinancingConfig;
 use crate::scripting::indicators::IndicatorStore;
+use crate::scripting::margin::MarginModel;
 use crate::scripting::options_cache::DatePartitionedOptions;
 
+use super::position::NetGreeks;
+
 // ---------------------------------------------------------------------------
 // ScriptConfig — parsed from the Rhai config() callback return value
 // ---------------------------------------------------------------------------
@@ -47,6 +54,16 @@ pub struct ScriptConfig {
     pub min_days_between_entries: Option<i32>,
     pub expiration_filter: ExpirationFilter,
     pub trade_selector: TradeSelector,
+    /// Rate and dividend-yield inputs for implied volatility / greeks.
+    pub greeks_params: GreeksParams,
+    /// Book-level greek limits that block entries (`config().limits`).
+    pub greek_limits: GreekLimits,
+    /// Broker margin model (`engine.margin`); `None` leaves entries unconstrained.
+    pub margin: Option<MarginModel>,
+    /// Early-assignment model (`engine.early_assignment`); `None` assigns only at expiration.
+    pub early_assignment: Option<EarlyAssignment>,
+    /// Cash interest, margin interest and borrow fees (`engine.financing`); `None` accrues nothing.
+    pub financing: Option<FinancingConfig>,
 
     // Script-readable defaults (NOT engine-enforced)
     pub defaults: HashMap<String, ScriptValue>,
@@ -55,6 +72,72 @@ pub struct ScriptConfig {
     pub procedural: bool,
 }
 
+/// Engine-enforced book-level greek limits declared in `config().limits`.
+///
+/// Limits apply to the portfolio's dollar-weighted net greeks (see
+/// `NetGreeks`). An entry is rejected when the book after the fill would breach
+/// a limit *and* the entry moves that greek further the wrong way, so
+/// risk-reducing trades are always allowed. Unset limits are not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GreekLimits {
+    pub max_abs_delta: Option<f64>,
+    pub max_abs_gamma: Option<f64>,
+    pub max_abs_vega: Option<f64>,
+    pub min_theta: Option<f64>,
+}
+
+impl GreekLimits {
+    /// Config keys accepted in `config().limits`.
+    pub const KEYS: [&'static str; 4] = [
+        "max_abs_delta",
+        "max_abs_gamma",
+        "max_abs_vega",
+        "min_theta",
+    ];
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Describe the first limit breached by moving the book from `before` to
+    /// `after`, or `None` if the move is allowed.
+    #[must_use]
+    pub fn breach(&self, before: &NetGreeks, after: &NetGreeks) -> Option<String> {
+        let abs_checks = [
+            (
+                "max_abs_delta",
+                self.max_abs_delta,
+                before.delta,
+                after.delta,
+            ),
+            (
+                "max_abs_gamma",
+                self.max_abs_gamma,
+                before.gamma,
+                after.gamma,
+            ),
+            ("max_abs_vega", self.max_abs_vega, before.vega, after.vega),
+        ];
+        for (name, limit, before, after) in abs_checks {
+            if let Some(limit) = limit {
+                if after.abs() > limit && after.abs() > before.abs() {
+                    return Some(format!("{name} {limit} (book would be {after:.2})"));
+                }
+            }
+        }
+        if let Some(limit) = self.min_theta {
+            if after.theta < limit && after.theta < before.theta {
+                return Some(format!(
+                    "min_theta {limit} (book would be {:.2})",
+                    after.theta
+                ));
+            }
+        }
+        None
+    }
+}
+
 /// Interval for bar iteration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Interval {
@@ -184,6 +267,8 @@ pub struct PerSymbolData {
     pub split_timeline: Arc<AdjustmentTimeline>,
     /// Full adjustment timeline (splits + dividends, for `adjusted_close`).
     pub adjustment_timeline: Arc<AdjustmentTimeline>,
+    /// Cash dividends (ex-date, amount per share) from the adjustment store.
+    pub dividends: Arc<Vec<DividendRow>>,
     /// Options chain partitioned by date. `None` if symbol has no options data.
     pub options_by_date: Option<Arc<DatePartitionedOptions>>,
     /// O(1) options quote lookup table. `None` if no options data.
@@ -224,6 +309,48 @@ pub enum OrderType {
     /// same bar. Invalid stop/limit relationships (buy: limit < stop, sell:
     /// limit > stop) are rejected as unfilled.
     StopLimit { stop: f64, limit: f64 },
+    /// Working limit for an options entry, priced against the combined legs'
+    /// quotes instead of the underlying's bar. Prices are net debit per share
+    /// (negative = credit), so a lower limit is always the better price.
+    ///
+    /// The limit starts at the spread's mid minus `offset` on the first bar the
+    /// order works, and after each bar it fails to fill it steps `step` toward
+    /// the natural (long legs at the ask, short legs at the bid), never past it.
+    /// The order fills at the limit once the natural is at or inside it.
+    SpreadLimit {
+        offset: f64,
+        step: f64,
+        /// Current limit; `None` until the order first sees quotes.
+        limit: Option<f64>,
+        /// Natural price when the order started working, for fill statistics.
+        start_natural: Option<f64>,
+    },
+}
+
+impl OrderType {
+    /// Work a `SpreadLimit` order against one bar's quotes for its legs
+    /// (`mid` and `natural` as net debit per share). Returns `true` when the
+    /// natural has come to the limit and the order fills at the limit;
+    /// otherwise re-prices the limit one step toward the natural for the next bar.
+    pub fn work_spread_limit(&mut self, mid: f64, natural: f64) -> bool {
+        let Self::SpreadLimit {
+            offset,
+            step,
+            limit,
+            start_natural,
+        } = self
+        else {
+            return false;
+        };
+        start_natural.get_or_insert(natural);
+        let current = *limit.get_or_insert(mid - *offset);
+        // Tolerate float noise from summing leg prices
+        if natural <= current + 1e-9 {
+            return true;
+        }
+        *limit = Some((current + *step).min(natural));
+        false
+    }
 }
 
 /// Per-order exit modifier, attached to individual orders at submission time.
@@ -233,6 +360,16 @@ pub enum ExitModifier {
     Dollar(f64),
 }
 
+/// Membership of an order in a linked order group.
+#[derive(Debug, Clone)]
+pub struct OrderGroup {
+    /// Group id, recorded on the trades the group's orders open.
+    pub id: String,
+    /// One-cancels-other: the first order in the group to fill cancels the
+    /// rest (in submission order when several trigger on the same bar).
+    pub oco: bool,
+}
+
 /// A pending order in the order queue, waiting to be filled on a future bar.
 #[derive(Debug, Clone)]
 pub struct PendingOrder {
@@ -259,9 +396,19 @@ pub struct PendingOrder {
     pub profit_target: Option<ExitModifier>,
     /// Per-order trailing stop, stored on position at fill time.
     pub trailing_stop: Option<ExitModifier>,
+    /// Linked order group, if any.
+    pub group: Option<OrderGroup>,
+    /// Orders submitted once this one fills (one-triggers-other). A child
+    /// `Close` without a position id targets the position this order opened.
+    pub triggers: Vec<PendingOrder>,
 }
 
 impl PendingOrder {
+    /// Whether this order belongs to the one-cancels-other group `id`.
+    pub fn in_oco_group(&self, id: &str) -> bool {
+        self.group.as_ref().is_some_and(|g| g.oco && g.id == id)
+    }
+
     /// Check whether this order has expired given the current bar index.
     pub fn is_expired(&self, current_bar: usize) -> bool {
         if let Some(ttl) = self.ttl {
@@ -273,9 +420,11 @@ impl PendingOrder {
 
     /// Attempt to fill this order given the current bar's OHLCV data.
     /// Returns `Some(fill_price)` if the order should be filled, `None` otherwise.
+    /// `SpreadLimit` orders never fill against the bar — see [`OrderType::work_spread_limit`].
     pub fn try_fill(&self, open: f64, high: f64, low: f64, _close: f64) -> Option<f64> {
         match &self.order_type {
             OrderType::Market => Some(open),
+            OrderType::SpreadLimit { .. } => None,
             OrderType::Limit { price } => {
                 if self.is_buy {
                     // Buy limit: fill if low ≤ limit price
@@ -373,15 +522,37 @@ pub enum ScriptAction {
     Stop { reason: String },
 }
 
+/// How an unresolved leg picks its strike.
+///
+/// `Delta` searches every expiration within ±15 days of the target DTE; the
+/// other selectors first pick the single expiration closest to the target DTE
+/// and then choose a strike within it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LegSelector {
+    /// Closest absolute delta (searched within ±0.10 of the target).
+    Delta(f64),
+    /// Listed strike nearest to an absolute price.
+    Strike(f64),
+    /// Fraction out of the money relative to spot (`0.05` = 5% OTM, negative = ITM).
+    Moneyness(f64),
+    /// Number of listed strikes away from the at-the-money strike (positive = OTM).
+    AtmOffset(i64),
+    /// Closest bid/ask mid to a target premium.
+    Premium(f64),
+    /// Strike of another leg (by index) shifted by `width` points; positive is a
+    /// higher strike. Shares that leg's expiration when both target the same DTE.
+    SameStrikeAs { leg: usize, width: f64 },
+}
+
 /// A leg specification in an `open_options` action.
-/// Can be "unresolved" (delta/DTE targets) or "resolved" (specific contract).
+/// Can be "unresolved" (selector/DTE targets) or "resolved" (specific contract).
 #[derive(Debug, Clone)]
 pub enum LegSpec {
     /// Engine resolves to a specific contract via `filters.rs`.
     Unresolved {
         side: Side,
         option_type: OptionType,
-        delta: f64,
+        selector: LegSelector,
         dte: i32,
     },
     /// Pre-resolved contract from `find_option`.
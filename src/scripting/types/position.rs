@@ -4,6 +4,7 @@ use chrono::NaiveDate;
 use rhai::Dynamic;
 
 use super::config::ExitModifier;
+use crate::engine::greeks::OptionGreeks;
 use crate::engine::types::{OptionType, Side};
 
 // ---------------------------------------------------------------------------
@@ -38,6 +39,8 @@ pub struct ScriptPosition {
     pub group: Option<String>,
     /// Per-order trailing stop (percent or dollar), checked each bar in exit phase.
     pub trailing_stop: Option<ExitModifier>,
+    /// Id of the linked order group whose order opened this position.
+    pub order_group: Option<String>,
 }
 
 /// The inner variant: options (multi-leg) or stock (single holding).
@@ -57,6 +60,9 @@ pub enum ScriptPositionInner {
 }
 
 /// A single leg of an options position, exposed to scripts.
+///
+/// Greeks are per-share contract values (not signed by side), set at entry
+/// and refreshed from the options chain during mark-to-market.
 #[derive(Debug, Clone)]
 pub struct ScriptPositionLeg {
     pub strike: f64,
@@ -66,9 +72,45 @@ pub struct ScriptPositionLeg {
     pub entry_price: f64,
     pub current_price: f64,
     pub delta: f64,
+    pub iv: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
     pub qty: i32,
 }
 
+impl ScriptPositionLeg {
+    /// Overwrite this leg's greeks with a fresh snapshot from the chain.
+    pub fn set_greeks(&mut self, greeks: &OptionGreeks) {
+        self.delta = greeks.delta;
+        self.iv = greeks.iv;
+        self.gamma = greeks.gamma;
+        self.theta = greeks.theta;
+        self.vega = greeks.vega;
+    }
+}
+
+/// Dollar-weighted greeks summed over one or more positions.
+///
+/// Delta is in share equivalents, gamma in share equivalents per $1 move,
+/// theta in dollars per calendar day, and vega in dollars per vol point.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetGreeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
+}
+
+impl std::ops::AddAssign for NetGreeks {
+    fn add_assign(&mut self, rhs: Self) {
+        self.delta += rhs.delta;
+        self.gamma += rhs.gamma;
+        self.theta += rhs.theta;
+        self.vega += rhs.vega;
+    }
+}
+
 impl ScriptPosition {
     /// Days to expiration for options positions; `None` for stock.
     #[must_use]
@@ -92,6 +134,41 @@ impl ScriptPosition {
         }
     }
 
+    /// Position-level greek in dollars: Σ side × qty × multiplier × leg greek.
+    /// Returns 0 for stock positions.
+    #[must_use]
+    pub fn net_leg_greek(&self, greek: impl Fn(&ScriptPositionLeg) -> f64) -> f64 {
+        match &self.inner {
+            ScriptPositionInner::Options {
+                legs, multiplier, ..
+            } => legs
+                .iter()
+                .map(|leg| {
+                    greek(leg) * leg.side.multiplier() * f64::from(leg.qty) * f64::from(*multiplier)
+                })
+                .sum(),
+            ScriptPositionInner::Stock { .. } => 0.0,
+        }
+    }
+
+    /// Dollar-weighted greeks of the whole position. Stock contributes delta
+    /// only (±1 per share); options use `net_leg_greek` per greek.
+    #[must_use]
+    pub fn net_greeks(&self) -> NetGreeks {
+        match &self.inner {
+            ScriptPositionInner::Options { .. } => NetGreeks {
+                delta: self.net_leg_greek(|l| l.delta),
+                gamma: self.net_leg_greek(|l| l.gamma),
+                theta: self.net_leg_greek(|l| l.theta),
+                vega: self.net_leg_greek(|l| l.vega),
+            },
+            ScriptPositionInner::Stock { side, qty, .. } => NetGreeks {
+                delta: f64::from(*qty) * side.multiplier(),
+                ..NetGreeks::default()
+            },
+        }
+    }
+
     #[must_use]
     pub fn is_options(&self) -> bool {
         matches!(self.inner, ScriptPositionInner::Options { .. })
@@ -169,6 +246,10 @@ impl ScriptPosition {
                         map.insert("entry_price".into(), Dynamic::from(leg.entry_price));
                         map.insert("current_price".into(), Dynamic::from(leg.current_price));
                         map.insert("delta".into(), Dynamic::from(leg.delta));
+                        map.insert("iv".into(), Dynamic::from(leg.iv));
+                        map.insert("gamma".into(), Dynamic::from(leg.gamma));
+                        map.insert("theta".into(), Dynamic::from(leg.theta));
+                        map.insert("vega".into(), Dynamic::from(leg.vega));
                         map.insert("qty".into(), Dynamic::from(leg.qty as i64));
                         Dynamic::from(map)
                     })
@@ -196,4 +277,28 @@ impl ScriptPosition {
     pub fn get_source(&mut self) -> String {
         self.source.clone()
     }
+    /// Average implied volatility across legs with a solved IV; `()` if none.
+    pub fn get_iv(&mut self) -> Dynamic {
+        let ScriptPositionInner::Options { legs, .. } = &self.inner else {
+            return Dynamic::UNIT;
+        };
+        let solved: Vec<f64> = legs.iter().map(|l| l.iv).filter(|v| *v > 0.0).collect();
+        if solved.is_empty() {
+            Dynamic::UNIT
+        } else {
+            Dynamic::from(solved.iter().sum::<f64>() / solved.len() as f64)
+        }
+    }
+    pub fn get_delta(&mut self) -> f64 {
+        self.net_greeks().delta
+    }
+    pub fn get_gamma(&mut self) -> f64 {
+        self.net_leg_greek(|l| l.gamma)
+    }
+    pub fn get_theta(&mut self) -> f64 {
+        self.net_leg_greek(|l| l.theta)
+    }
+    pub fn get_vega(&mut self) -> f64 {
+        self.net_leg_greek(|l| l.vega)
+    }
 }
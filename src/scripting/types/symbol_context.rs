@@ -118,6 +118,7 @@ impl SymbolContext {
             legs,
             &self.options_by_date,
             self.datetime,
+            self.close,
             Some(&self.symbol),
         )
     }
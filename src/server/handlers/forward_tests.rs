@@ -73,6 +73,7 @@ pub async fn create_forward_test(
     let result = forward_test::start(&forward_test::StartParams {
         store: &fwd_store,
         strategy_store: strategy_store.as_deref(),
+        signal_store: state.server.signal_store.as_deref(),
         strategy: &body.strategy,
         symbol: &body.symbol,
         capital: body.capital,
@@ -136,6 +137,7 @@ pub async fn step_forward_test(
     let result = forward_test::step(
         &fwd_store,
         strategy_store.as_deref(),
+        state.server.signal_store.as_deref(),
         &cache,
         adjustment_store,
         &id,
@@ -8,6 +8,7 @@ pub mod pipeline;
 pub mod profiles;
 pub mod run_script;
 pub mod runs;
+pub mod signals;
 pub mod strategies;
 pub mod sweeps;
 pub mod tasks;
@@ -1,7 +1,8 @@
 //! REST API handler for the backtest pipeline.
 //!
 //! Runs the full analysis pipeline
-//! (`sweep` -> `significance_gate` -> `walk-forward` -> `oos_data_gate` -> `monte carlo`)
+//! (`sweep` -> `significance_gate` -> `overfitting_gate` -> `walk-forward` ->
+//! `oos_data_gate` -> `monte carlo`)
 //! and returns a `PipelineResponse` with stage statuses.
 //! Monte Carlo may be skipped when earlier gates do not pass.
 
@@ -32,6 +33,12 @@ pub struct CreateBaselineValidationRequest {
     pub mode: String,
     #[serde(default = "crate::application::sweeps::default_objective")]
     pub objective: String,
+    /// Objectives for `mode = "pareto"`, e.g. `["cagr", "max_drawdown", "cvar_95"]`.
+    #[serde(default)]
+    pub objectives: Vec<String>,
+    /// Seed for the NSGA-II search in `mode = "pareto"`. Default 42.
+    #[serde(default)]
+    pub seed: Option<u64>,
     pub params: HashMap<String, Value>,
     #[serde(default)]
     pub sweep_params: Vec<SweepParamDef>,
@@ -56,6 +63,12 @@ pub struct CreateWorkflowRequest {
     pub mode: String,
     #[serde(default = "crate::application::sweeps::default_objective")]
     pub objective: String,
+    /// Objectives for `mode = "pareto"`, e.g. `["cagr", "max_drawdown", "cvar_95"]`.
+    #[serde(default)]
+    pub objectives: Vec<String>,
+    /// Seed for the NSGA-II search in `mode = "pareto"`. Default 42.
+    #[serde(default)]
+    pub seed: Option<u64>,
     pub params: HashMap<String, Value>,
     #[serde(default)]
     pub sweep_params: Vec<SweepParamDef>,
@@ -87,8 +100,12 @@ fn resolve_sweep_params_from_script(
         .as_ref()
         .ok_or((StatusCode::BAD_REQUEST, "No strategy store".to_string()))?;
 
-    let (_id, source) = sweeps::resolve_strategy_source_from_store(store.as_ref(), strategy)
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let (_id, source) = sweeps::resolve_strategy_source_from_store(
+        store.as_ref(),
+        state.server.signal_store.as_deref(),
+        strategy,
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
 
     let mut meta = crate::scripting::stdlib::parse_script_meta(strategy, &source);
     meta.params = crate::scripting::stdlib::extract_extern_params(&source);
@@ -110,21 +127,29 @@ fn resolve_sweep_params_from_script(
         )
     })?;
 
-    // Look up param_type from extern declarations in the script metadata
+    // Look up param_type from extern declarations in the script metadata; a
+    // profile entry without choices sweeps the extern's declared options
     Ok(params
         .iter()
         .map(|p| {
-            let param_type = meta
-                .params
-                .iter()
-                .find(|ep| ep.name == p.name)
-                .map_or_else(|| "float".to_string(), |ep| ep.param_type.clone());
+            let declared = meta.params.iter().find(|ep| ep.name == p.name);
+            let choices = match declared {
+                Some(ep) if p.choices.is_empty() => ep.options.clone(),
+                _ => p.choices.clone(),
+            };
+            let param_type = if choices.is_empty() {
+                declared.map_or_else(|| "float".to_string(), |ep| ep.param_type.clone())
+            } else {
+                "categorical".to_string()
+            };
             SweepParamDef {
                 name: p.name.clone(),
                 param_type,
                 start: p.start,
                 stop: p.stop,
                 step: p.step,
+                choices,
+                when: p.when.clone(),
             }
         })
         .collect())
@@ -157,16 +182,21 @@ pub(crate) fn build_pipeline_params(
         ));
     };
 
-    if !matches!(req.mode.as_str(), "grid" | "bayesian") {
+    if !matches!(req.mode.as_str(), "grid" | "bayesian" | "pareto") {
         return Err((
             StatusCode::BAD_REQUEST,
             format!(
-                "Validation error: mode must be one of ['grid', 'bayesian'], got '{}'",
+                "Validation error: mode must be one of ['grid', 'bayesian', 'pareto'], got '{}'",
                 req.mode
             ),
         ));
     }
 
+    if req.mode == "pareto" {
+        crate::engine::pareto::parse_objectives(&req.objectives)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Validation error: {e}")))?;
+    }
+
     if req.num_permutations > 100_000 {
         return Err((
             StatusCode::BAD_REQUEST,
@@ -178,6 +208,8 @@ pub(crate) fn build_pipeline_params(
         strategy: req.strategy,
         mode: req.mode,
         objective: req.objective,
+        objectives: req.objectives,
+        seed: req.seed,
         params: req.params,
         sweep_params,
         max_evaluations: req.max_evaluations,
@@ -196,6 +228,8 @@ pub(super) fn build_workflow_params(
             strategy: req.strategy,
             mode: req.mode,
             objective: req.objective,
+            objectives: req.objectives,
+            seed: req.seed,
             params: req.params,
             sweep_params: req.sweep_params,
             sweep_profile: req.sweep_profile,
@@ -0,0 +1,232 @@
+//! REST API handlers for the saved signal library.
+//!
+//! Endpoints:
+//! - `GET    /signals`                 — list the latest version of every signal
+//! - `POST   /signals`                 — save a new signal (version 1)
+//! - `GET    /signals/{name}`          — get the latest (or `?version=N`) definition
+//! - `PUT    /signals/{name}`          — save a new version of an existing signal
+//! - `DELETE /signals/{name}`          — delete a signal and all its versions
+//! - `GET    /signals/{name}/versions` — list every version, oldest first
+//!
+//! Every save is validated: the name must be well-formed, `Saved` references
+//! must resolve with the new definition in place (so no reference chain leads
+//! back to the signal itself), and the resolved spec must translate into a
+//! DSL condition.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::data::signal_store::{
+    parse_signal_ref, validate_signal_name, SignalRow, SqliteSignalStore,
+};
+use crate::engine::types::SignalSpec;
+use crate::scripting::dsl::signal::signal_to_expr;
+use crate::server::state::AppState;
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Request types
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// Request body for `POST /signals`.
+#[derive(Debug, Deserialize)]
+pub struct CreateSignalRequest {
+    pub name: String,
+    pub spec: SignalSpec,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Request body for `PUT /signals/{name}`.
+#[derive(Debug, Deserialize)]
+pub struct UpdateSignalRequest {
+    pub spec: SignalSpec,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Query params for `GET /signals/{name}`.
+#[derive(Debug, Deserialize, Default)]
+pub struct GetSignalQuery {
+    pub version: Option<i64>,
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Helpers
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// Helper to clone the signal store `Arc` or return 503.
+fn clone_store(state: &AppState) -> Result<Arc<SqliteSignalStore>, (StatusCode, String)> {
+    state.server.signal_store.clone().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Signal store not configured".to_string(),
+        )
+    })
+}
+
+/// Whether `spec` contains a `Saved` reference to `name` (any version).
+fn references(spec: &SignalSpec, name: &str) -> bool {
+    match spec {
+        SignalSpec::Formula { .. } => false,
+        SignalSpec::Saved { name: reference } => {
+            parse_signal_ref(reference).is_ok_and(|(base, _)| base.eq_ignore_ascii_case(name))
+        }
+        SignalSpec::And { left, right } | SignalSpec::Or { left, right } => {
+            references(left, name) || references(right, name)
+        }
+    }
+}
+
+/// Validate `spec` as the next definition of `name`, then save it.
+fn validate_and_save(
+    store: &SqliteSignalStore,
+    name: &str,
+    spec: &SignalSpec,
+    description: Option<&str>,
+) -> Result<SignalRow, (StatusCode, String)> {
+    if references(spec, name) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Signal '{name}' cannot reference itself"),
+        ));
+    }
+    let resolved = store
+        .resolve_as(name, spec)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    signal_to_expr(&resolved).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid signal '{name}': {e}"),
+        )
+    })?;
+    store
+        .save(name, spec, description)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Handlers
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// `GET /signals` — List the latest version of every saved signal.
+pub async fn list_signals(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SignalRow>>, (StatusCode, String)> {
+    let store = clone_store(&state)?;
+    let rows = tokio::task::spawn_blocking(move || store.list())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(rows))
+}
+
+/// `POST /signals` — Save a new signal as version 1.
+pub async fn create_signal(
+    State(state): State<AppState>,
+    Json(req): Json<CreateSignalRequest>,
+) -> Result<(StatusCode, Json<SignalRow>), (StatusCode, String)> {
+    validate_signal_name(&req.name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let store = clone_store(&state)?;
+    let row = tokio::task::spawn_blocking(move || {
+        if store
+            .get(&req.name, None)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .is_some()
+        {
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Signal '{}' already exists — use PUT /signals/{} to save a new version",
+                    req.name, req.name
+                ),
+            ));
+        }
+        validate_and_save(&store, &req.name, &req.spec, req.description.as_deref())
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))??;
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+/// `GET /signals/{name}` — Return the latest version, or `?version=N`.
+pub async fn get_signal(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<GetSignalQuery>,
+) -> Result<Json<SignalRow>, (StatusCode, String)> {
+    validate_signal_name(&name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let store = clone_store(&state)?;
+    let row = tokio::task::spawn_blocking(move || store.get(&name, query.version))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Signal not found".to_string()))?;
+    Ok(Json(row))
+}
+
+/// `PUT /signals/{name}` — Save a new version of an existing signal.
+///
+/// Strategies referencing the signal without a pinned version pick up the
+/// new definition the next time they are transpiled.
+pub async fn update_signal(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<UpdateSignalRequest>,
+) -> Result<Json<SignalRow>, (StatusCode, String)> {
+    validate_signal_name(&name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let store = clone_store(&state)?;
+    let row = tokio::task::spawn_blocking(move || {
+        if store
+            .get(&name, None)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .is_none()
+        {
+            return Err((StatusCode::NOT_FOUND, "Signal not found".to_string()));
+        }
+        validate_and_save(&store, &name, &req.spec, req.description.as_deref())
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))??;
+    Ok(Json(row))
+}
+
+/// `DELETE /signals/{name}` — Delete a signal and all its versions.
+pub async fn delete_signal(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    validate_signal_name(&name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let store = clone_store(&state)?;
+    let deleted = tokio::task::spawn_blocking(move || store.delete(&name))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, "Signal not found".to_string()))
+    }
+}
+
+/// `GET /signals/{name}/versions` — List every version of a signal, oldest first.
+pub async fn list_signal_versions(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<SignalRow>>, (StatusCode, String)> {
+    validate_signal_name(&name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let store = clone_store(&state)?;
+    let rows = tokio::task::spawn_blocking(move || store.versions(&name))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if rows.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Signal not found".to_string()));
+    }
+    Ok(Json(rows))
+}
@@ -81,6 +81,7 @@ pub async fn create_sweep(
             None,
             Some(progress),
             Some(&is_cancelled),
+            None,
         )
         .await;
 
@@ -1,11 +1,12 @@
 //! REST API handlers for task-based backtest and sweep submission.
 //!
 //! Tasks are queued, executed with concurrency limits, and their progress
-//! streamed via reconnectable SSE.
+//! streamed via reconnectable SSE. Each task is stored with its submit
+//! request so the queue can be resumed after a restart.
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::sse::{Event, KeepAlive, Sse},
     Json,
 };
@@ -15,13 +16,17 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 use crate::application::error::{ApplicationError, ApplicationErrorKind};
 use crate::application::{backtests, pipeline, sweeps, tasks as app_tasks, workflows};
+use crate::data::task_store::TaskRow;
+use crate::engine::cpcv::CpcvConfig;
 use crate::engine::walk_forward::{WalkForwardParams, WfMode, WfObjective};
 use crate::scripting::engine::CachingDataLoader;
 use crate::server::state::AppState;
-use crate::server::task_manager::{TaskInfo, TaskKind, TaskStatus};
+use crate::server::task_events::{Subscription, TaskEvent};
+use crate::server::task_manager::{TaskInfo, TaskKind, TaskManager, TaskStatus};
 use crate::tools::response_types::workflow::{WorkflowKind, WorkflowResponse};
 use crate::tools::run_script::RunScriptParams;
 
@@ -29,7 +34,7 @@ use crate::tools::run_script::RunScriptParams;
 // Request / response types
 // ──────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitBacktestRequest {
     pub strategy: String,
     pub params: HashMap<String, Value>,
@@ -39,12 +44,18 @@ pub struct SubmitBacktestRequest {
     pub thread_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitSweepRequest {
     pub strategy: String,
     pub mode: String,
     #[serde(default = "sweeps::default_objective")]
     pub objective: String,
+    /// Objectives for `mode = "pareto"`, e.g. `["cagr", "max_drawdown", "cvar_95"]`.
+    #[serde(default)]
+    pub objectives: Vec<String>,
+    /// Seed for the NSGA-II search in `mode = "pareto"`. Default 42.
+    #[serde(default)]
+    pub seed: Option<u64>,
     pub params: HashMap<String, Value>,
     pub sweep_params: Vec<sweeps::SweepParamDef>,
     #[serde(default = "sweeps::default_max_evaluations")]
@@ -56,7 +67,7 @@ pub struct SubmitSweepRequest {
     pub thread_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitWalkForwardRequest {
     pub strategy: String,
     pub sweep_id: String,
@@ -70,6 +81,12 @@ pub struct SubmitWalkForwardRequest {
     pub mode: String,
     #[serde(default = "sweeps::default_objective")]
     pub objective: String,
+    /// CPCV only: groups held out per split (default: 2).
+    #[serde(default)]
+    pub n_test_groups: Option<usize>,
+    /// CPCV only: bars embargoed after each test group (default: 5).
+    #[serde(default)]
+    pub embargo_bars: Option<usize>,
     #[serde(default)]
     pub thread_id: Option<String>,
 }
@@ -84,6 +101,86 @@ fn default_wf_mode() -> String {
     "rolling".to_string()
 }
 
+/// Submit request persisted with a task so it can be replayed after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskRequest {
+    Backtest(SubmitBacktestRequest),
+    Sweep(SubmitSweepRequest),
+    /// Full strategy evaluation (`POST /tasks/pipeline`).
+    Pipeline(SubmitSweepRequest),
+    WalkForward(SubmitWalkForwardRequest),
+    /// Baseline validation with its sweep grid already resolved.
+    BaselineValidation(SubmitSweepRequest),
+    /// Named workflow with its sweep grid already resolved.
+    Workflow {
+        workflow: WorkflowKind,
+        request: SubmitSweepRequest,
+    },
+}
+
+impl TaskRequest {
+    fn strategy_params_thread(&self) -> (&str, &HashMap<String, Value>, Option<&String>) {
+        match self {
+            Self::Backtest(req) => (&req.strategy, &req.params, req.thread_id.as_ref()),
+            Self::WalkForward(req) => (&req.strategy, &req.params, req.thread_id.as_ref()),
+            Self::Sweep(req)
+            | Self::Pipeline(req)
+            | Self::BaselineValidation(req)
+            | Self::Workflow { request: req, .. } => {
+                (&req.strategy, &req.params, req.thread_id.as_ref())
+            }
+        }
+    }
+}
+
+impl From<pipeline::PipelineRequest> for SubmitSweepRequest {
+    fn from(req: pipeline::PipelineRequest) -> Self {
+        Self {
+            strategy: req.strategy,
+            mode: req.mode,
+            objective: req.objective,
+            objectives: req.objectives,
+            seed: req.seed,
+            params: req.params,
+            sweep_params: req.sweep_params,
+            max_evaluations: req.max_evaluations,
+            num_permutations: req.num_permutations,
+            thread_id: req.thread_id,
+        }
+    }
+}
+
+impl From<SubmitSweepRequest> for pipeline::PipelineRequest {
+    fn from(req: SubmitSweepRequest) -> Self {
+        Self {
+            strategy: req.strategy,
+            mode: req.mode,
+            objective: req.objective,
+            objectives: req.objectives,
+            seed: req.seed,
+            params: req.params,
+            sweep_params: req.sweep_params,
+            max_evaluations: req.max_evaluations,
+            num_permutations: req.num_permutations,
+            thread_id: req.thread_id,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTasksQuery {
+    /// Include finished tasks from the database, across restarts.
+    #[serde(default)]
+    pub history: bool,
+    #[serde(default = "default_history_limit")]
+    pub limit: usize,
+}
+
+fn default_history_limit() -> usize {
+    100
+}
+
 #[derive(Serialize)]
 pub struct SubmitResponse {
     pub task_id: String,
@@ -144,37 +241,422 @@ fn snapshot(task: &TaskInfo, queue_pos: Option<usize>) -> TaskSnapshot {
     }
 }
 
+/// Snapshot of a persisted task that is no longer tracked in memory.
+fn snapshot_from_row(row: TaskRow) -> Option<TaskSnapshot> {
+    Some(TaskSnapshot {
+        id: row.id,
+        kind: TaskKind::parse(&row.kind)?,
+        strategy: row.strategy,
+        symbol: row.symbol,
+        status: TaskStatus::parse(&row.status)?,
+        progress_current: usize::try_from(row.progress_current).unwrap_or(0),
+        progress_total: usize::try_from(row.progress_total).unwrap_or(0),
+        stage_label: row.stage_label,
+        queue_position: None,
+        created_at: row.created_at,
+        started_at: row.started_at,
+        completed_at: row.completed_at,
+        result: row.result,
+        result_id: row.result_id,
+        error: row.error,
+    })
+}
+
 // ──────────────────────────────────────────────────────────────────────────────
 // Handlers
 // ──────────────────────────────────────────────────────────────────────────────
 
 /// `POST /tasks/backtest` — Submit a single backtest task.
-#[allow(clippy::unused_async, clippy::too_many_lines)]
+#[allow(clippy::unused_async)]
 pub async fn submit_backtest(
     State(state): State<AppState>,
     Json(req): Json<SubmitBacktestRequest>,
 ) -> Json<SubmitResponse> {
+    let task_id = enqueue(&state, TaskKind::Single, TaskRequest::Backtest(req));
+    Json(SubmitResponse { task_id })
+}
+
+/// `POST /tasks/sweep` — Submit a sweep task.
+#[allow(clippy::unused_async)]
+pub async fn submit_sweep(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitSweepRequest>,
+) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
+    let task_id = enqueue(&state, TaskKind::Sweep, TaskRequest::Sweep(req));
+    Ok(Json(SubmitResponse { task_id }))
+}
+
+/// `POST /tasks/pipeline` — Submit a full strategy evaluation task
+/// (sweep + gates + WF + MC + robustness checks + verdict).
+#[allow(clippy::unused_async)]
+pub async fn submit_pipeline(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitSweepRequest>,
+) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
+    let task_id = enqueue(&state, TaskKind::Sweep, TaskRequest::Pipeline(req));
+    Ok(Json(SubmitResponse { task_id }))
+}
+
+/// `POST /tasks/walk-forward` — Submit a walk-forward validation task.
+#[allow(clippy::unused_async)]
+pub async fn submit_walk_forward(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitWalkForwardRequest>,
+) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
+    let task_id = enqueue(&state, TaskKind::WalkForward, TaskRequest::WalkForward(req));
+    Ok(Json(SubmitResponse { task_id }))
+}
+
+/// `GET /tasks` — List active (queued + running) tasks.
+///
+/// With `?history=true`, list the most recent `limit` tasks from the
+/// database instead, including finished and interrupted tasks from previous
+/// server runs. Tasks still in memory report live progress.
+#[allow(clippy::unused_async)]
+pub async fn list_tasks(
+    State(state): State<AppState>,
+    Query(query): Query<ListTasksQuery>,
+) -> Json<Vec<TaskSnapshot>> {
+    if query.history {
+        let snapshots = state
+            .task_manager
+            .history(query.limit)
+            .into_iter()
+            .filter_map(|row| match state.task_manager.get(&row.id) {
+                Some(task) => Some(snapshot(&task, state.task_manager.queue_position(&row.id))),
+                None => snapshot_from_row(row),
+            })
+            .collect();
+        return Json(snapshots);
+    }
+
+    let active = state.task_manager.list_active();
+    let snapshots: Vec<TaskSnapshot> = active
+        .iter()
+        .map(|t| {
+            let pos = state.task_manager.queue_position(&t.id);
+            snapshot(t, pos)
+        })
+        .collect();
+    Json(snapshots)
+}
+
+/// `GET /tasks/{id}` — Get a single task's status.
+///
+/// Falls back to the database for tasks evicted from memory.
+#[allow(clippy::unused_async)]
+pub async fn get_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<TaskSnapshot>, StatusCode> {
+    if let Some(task) = state.task_manager.get(&id) {
+        let pos = state.task_manager.queue_position(&id);
+        return Ok(Json(snapshot(&task, pos)));
+    }
+    state
+        .task_manager
+        .get_persisted(&id)
+        .and_then(snapshot_from_row)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// `DELETE /tasks/{id}` — Cancel a task.
+#[allow(clippy::unused_async)]
+pub async fn cancel_task(State(state): State<AppState>, Path(id): Path<String>) -> StatusCode {
+    if state.task_manager.cancel(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// `GET /tasks/{id}/stream` — SSE event stream for one task (reconnectable).
+///
+/// Events are pushed from the task event bus as they happen. Every event
+/// carries an `id`; reconnecting with `Last-Event-ID` replays the events
+/// missed in between, otherwise the task's current state is sent first.
+/// The stream closes after the `done` event.
+#[allow(clippy::unused_async)]
+pub async fn stream_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, StatusCode> {
+    let task = state.task_manager.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let tm = Arc::clone(&state.task_manager);
+    let subscription = tm.events().subscribe(last_event_id(&headers), Some(&id));
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Event>(64);
+    tokio::spawn(async move {
+        let snapshot = |head: u64| snapshot_events(&tm, &task, head);
+        forward_events(&tx, &tm, subscription, Some(&id), snapshot).await;
+    });
+
+    Ok(
+        Sse::new(tokio_stream::wrappers::ReceiverStream::new(rx).map(Ok))
+            .keep_alive(KeepAlive::default()),
+    )
+}
+
+/// `GET /tasks/stream` — SSE firehose of events for all tasks.
+///
+/// Each event's data is `{"task_id": ..., "data": ...}`. Supports
+/// `Last-Event-ID` like the per-task stream; without it, the current state
+/// of every active task is sent first. The stream stays open until the
+/// client disconnects.
+#[allow(clippy::unused_async)]
+pub async fn stream_all_tasks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let tm = Arc::clone(&state.task_manager);
+    let subscription = tm.events().subscribe(last_event_id(&headers), None);
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Event>(256);
+    tokio::spawn(async move {
+        let snapshot = |head: u64| {
+            tm.list_active()
+                .iter()
+                .flat_map(|task| snapshot_events(&tm, task, head))
+                .collect()
+        };
+        forward_events(&tx, &tm, subscription, None, snapshot).await;
+    });
+
+    Sse::new(tokio_stream::wrappers::ReceiverStream::new(rx).map(Ok))
+        .keep_alive(KeepAlive::default())
+}
+
+fn last_event_id(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// The events that describe a task's current state, stamped with `seq`.
+fn snapshot_events(tm: &TaskManager, task: &TaskInfo, seq: u64) -> Vec<TaskEvent> {
+    let event = |name: &str, data: Value| TaskEvent {
+        seq,
+        task_id: task.id.clone(),
+        event: name.to_string(),
+        data,
+    };
+    let (result, error) = {
+        let m = task.mutable.lock().unwrap();
+        (m.result.clone(), m.error.clone())
+    };
+    match task.status() {
+        TaskStatus::Queued => {
+            let pos = tm.queue_position(&task.id).unwrap_or(0);
+            vec![event("queued", serde_json::json!({ "position": pos }))]
+        }
+        TaskStatus::Running => {
+            let label = task.stage_label.lock().unwrap().clone();
+            vec![event(
+                "progress",
+                serde_json::json!({
+                    "current": task.progress_current.load(Ordering::Relaxed),
+                    "total": task.progress_total.load(Ordering::Relaxed),
+                    "stage_label": label,
+                }),
+            )]
+        }
+        TaskStatus::Completed => {
+            let mut events: Vec<TaskEvent> =
+                result.map(|r| event("result", r)).into_iter().collect();
+            events.push(event("done", Value::Null));
+            events
+        }
+        TaskStatus::Failed | TaskStatus::Interrupted => vec![
+            event("error", Value::String(error.unwrap_or_default())),
+            event("done", Value::Null),
+        ],
+        TaskStatus::Cancelled => vec![event("cancelled", Value::Null), event("done", Value::Null)],
+    }
+}
+
+/// Pump bus events into an SSE channel until the client disconnects (or, for
+/// a single-task stream, the task is done).
+///
+/// Starts with the subscription's replay, or with `snapshot` when there is
+/// none. A lagging subscriber catches up from the replay buffer, falling back
+/// to a fresh snapshot if the buffer has moved on.
+async fn forward_events(
+    tx: &tokio::sync::mpsc::Sender<Event>,
+    tm: &TaskManager,
+    subscription: Subscription,
+    task_id: Option<&str>,
+    snapshot: impl Fn(u64) -> Vec<TaskEvent>,
+) {
+    let Subscription {
+        mut receiver,
+        head,
+        replay,
+    } = subscription;
+    let firehose = task_id.is_none();
+
+    // Returns false once the stream should end.
+    let send = |event: TaskEvent| async move {
+        let data = if firehose {
+            event.firehose_payload()
+        } else {
+            event.payload()
+        };
+        let sse = Event::default()
+            .id(event.seq.to_string())
+            .event(&event.event)
+            .data(data);
+        tx.send(sse).await.is_ok() && (firehose || !event.is_done())
+    };
+
+    let mut last_sent = head;
+    let mut pending = replay.unwrap_or_else(|| snapshot(head));
+    loop {
+        for event in pending.drain(..) {
+            last_sent = last_sent.max(event.seq);
+            if !send(event).await {
+                return;
+            }
+        }
+
+        let received = tokio::select! {
+            received = receiver.recv() => received,
+            () = tx.closed() => return,
+        };
+        match received {
+            Ok(event) => {
+                if event.seq > last_sent && task_id.is_none_or(|id| event.task_id == id) {
+                    pending.push(event);
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                pending = tm
+                    .events()
+                    .replay_since(last_sent, task_id)
+                    .unwrap_or_else(|| snapshot(last_sent));
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Pipeline task
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// `POST /tasks/baseline-validation` — submit the baseline validation workflow as a background task.
+pub async fn submit_baseline_validation(
+    State(state): State<AppState>,
+    Json(req): Json<super::pipeline::CreateBaselineValidationRequest>,
+) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
+    let pipeline = super::pipeline::build_pipeline_params(req, Some(&state))?;
+    let task_id = enqueue(
+        &state,
+        TaskKind::Pipeline,
+        TaskRequest::BaselineValidation(pipeline.into()),
+    );
+    Ok(Json(SubmitResponse { task_id }))
+}
+
+/// `POST /tasks/workflows` — submit a named workflow as a background task.
+pub async fn submit_workflow(
+    State(state): State<AppState>,
+    Json(req): Json<super::pipeline::CreateWorkflowRequest>,
+) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
+    let workflow = super::pipeline::build_workflow_params(req, Some(&state))?;
+    let task_id = enqueue(
+        &state,
+        TaskKind::Workflow,
+        TaskRequest::Workflow {
+            workflow: workflow.kind,
+            request: workflow.pipeline.into(),
+        },
+    );
+    Ok(Json(SubmitResponse { task_id }))
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Dispatch
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// Register a task with its replayable request and start its queued execution.
+fn enqueue(state: &AppState, kind: TaskKind, request: TaskRequest) -> String {
+    let (strategy, params, thread_id) = request.strategy_params_thread();
+
     // Symbol from params is a pre-execution hint; actual symbol is resolved
-    // from the engine result after the backtest completes.
-    let symbol = req
-        .params
+    // from the engine result or script source once the task runs.
+    let symbol = params
         .get("symbol")
         .and_then(Value::as_str)
         .unwrap_or("pending")
         .to_owned();
 
     let params_json =
-        serde_json::to_value(&req.params).unwrap_or(Value::Object(serde_json::Map::default()));
+        serde_json::to_value(params).unwrap_or(Value::Object(serde_json::Map::default()));
 
-    let task = state.task_manager.register(
-        TaskKind::Single,
-        &req.strategy,
-        &symbol,
-        req.thread_id.clone(),
+    let task = state.task_manager.register_resumable(
+        kind,
+        strategy,
+        symbol,
+        thread_id.cloned(),
         params_json,
+        serde_json::to_value(&request).unwrap_or(Value::Null),
     );
     let task_id = task.id.clone();
+    spawn_task(state, task, request);
+    task_id
+}
 
+/// Resume the task queue left by a previous process.
+///
+/// Tasks that were running are marked interrupted, or re-queued when
+/// `requeue_running` is set; queued tasks are dispatched again in their
+/// original order. Returns the number of resumed tasks.
+pub fn resume_tasks(state: &AppState, requeue_running: bool) -> usize {
+    let mut resumed = 0;
+    for (task, request) in state.task_manager.recover(requeue_running) {
+        match serde_json::from_value::<TaskRequest>(request) {
+            Ok(request) => {
+                spawn_task(state, task, request);
+                resumed += 1;
+            }
+            Err(e) => state
+                .task_manager
+                .mark_failed(&task.id, format!("Cannot resume task: {e}")),
+        }
+    }
+    resumed
+}
+
+/// Spawn the queued execution of a registered task.
+fn spawn_task(state: &AppState, task: Arc<TaskInfo>, request: TaskRequest) {
+    match request {
+        TaskRequest::Backtest(req) => spawn_backtest(state, task, req),
+        TaskRequest::Sweep(req) => spawn_sweep(state, task, req),
+        TaskRequest::Pipeline(req) => spawn_pipeline(state, task, req),
+        TaskRequest::WalkForward(req) => spawn_walk_forward(state, task, req),
+        TaskRequest::BaselineValidation(req) => {
+            let result_id = req.thread_id.clone();
+            let workflow = workflows::WorkflowRequest {
+                kind: WorkflowKind::BaselineValidation,
+                pipeline: req.into(),
+            };
+            spawn_workflow(state, task, workflow, result_id);
+        }
+        TaskRequest::Workflow { workflow, request } => {
+            let workflow = workflows::WorkflowRequest {
+                kind: workflow,
+                pipeline: request.into(),
+            };
+            spawn_workflow(state, task, workflow, None);
+        }
+    }
+}
+
+/// Run a backtest task: execute the script and persist the run.
+fn spawn_backtest(state: &AppState, task: Arc<TaskInfo>, req: SubmitBacktestRequest) {
     let tm = Arc::clone(&state.task_manager);
     let server = state.server.clone();
     let run_store = Arc::clone(&state.run_store);
@@ -228,36 +710,10 @@ pub async fn submit_backtest(
         })
         .await;
     });
-
-    Json(SubmitResponse { task_id })
 }
 
-/// `POST /tasks/sweep` — Submit a sweep task.
-#[allow(clippy::unused_async, clippy::too_many_lines)]
-pub async fn submit_sweep(
-    State(state): State<AppState>,
-    Json(req): Json<SubmitSweepRequest>,
-) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
-    // Symbol from params is a pre-execution hint; resolved from script later.
-    let symbol = req
-        .params
-        .get("symbol")
-        .and_then(Value::as_str)
-        .unwrap_or("pending")
-        .to_owned();
-
-    let params_json =
-        serde_json::to_value(&req.params).unwrap_or(Value::Object(serde_json::Map::default()));
-
-    let task = state.task_manager.register(
-        TaskKind::Sweep,
-        &req.strategy,
-        &symbol,
-        req.thread_id.clone(),
-        params_json,
-    );
-    let task_id = task.id.clone();
-
+/// Run a sweep task and persist the sweep.
+fn spawn_sweep(state: &AppState, task: Arc<TaskInfo>, req: SubmitSweepRequest) {
     let tm = Arc::clone(&state.task_manager);
     let server = state.server.clone();
     let run_store = Arc::clone(&state.run_store);
@@ -270,6 +726,8 @@ pub async fn submit_sweep(
                 strategy: req.strategy.clone(),
                 mode: req.mode.clone(),
                 objective: req.objective.clone(),
+                objectives: req.objectives.clone(),
+                seed: req.seed,
                 params: req.params.clone(),
                 sweep_params: req.sweep_params.clone(),
                 max_evaluations: req.max_evaluations,
@@ -284,6 +742,7 @@ pub async fn submit_sweep(
                 req.thread_id.as_deref(),
                 Some(progress),
                 Some(&is_cancelled),
+                Some(app_tasks::sweep_result_callback(&task)),
             )
             .await
             .map_err(|e| e.to_string())?;
@@ -302,36 +761,10 @@ pub async fn submit_sweep(
         })
         .await;
     });
-
-    Ok(Json(SubmitResponse { task_id }))
 }
 
-/// `POST /tasks/pipeline` — Submit a full strategy evaluation task
-/// (sweep + gates + WF + MC + robustness checks + verdict).
-#[allow(clippy::unused_async)]
-pub async fn submit_pipeline(
-    State(state): State<AppState>,
-    Json(req): Json<SubmitSweepRequest>,
-) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
-    let symbol = req
-        .params
-        .get("symbol")
-        .and_then(Value::as_str)
-        .unwrap_or("pending")
-        .to_owned();
-
-    let params_json =
-        serde_json::to_value(&req.params).unwrap_or(Value::Object(serde_json::Map::default()));
-
-    let task = state.task_manager.register(
-        TaskKind::Sweep,
-        &req.strategy,
-        &symbol,
-        req.thread_id.clone(),
-        params_json,
-    );
-    let task_id = task.id.clone();
-
+/// Run a full strategy evaluation task, reporting each stage.
+fn spawn_pipeline(state: &AppState, task: Arc<TaskInfo>, req: SubmitSweepRequest) {
     let tm = Arc::clone(&state.task_manager);
     let server = state.server.clone();
     let run_store = Arc::clone(&state.run_store);
@@ -346,6 +779,8 @@ pub async fn submit_pipeline(
                         strategy: req.strategy,
                         mode: req.mode,
                         objective: req.objective,
+                        objectives: req.objectives,
+                        seed: req.seed,
                         params: req.params,
                         sweep_params: req.sweep_params,
                         max_evaluations: req.max_evaluations,
@@ -359,11 +794,7 @@ pub async fn submit_pipeline(
                 let task_ref = Arc::clone(&task);
                 let on_stage: crate::tools::pipeline::StageCallback =
                     Some(Box::new(move |label: &str| {
-                        *task_ref.stage_label.lock().unwrap() = label.to_string();
-                        if label != "Sweep" {
-                            task_ref.progress_current.store(0, Ordering::Relaxed);
-                            task_ref.progress_total.store(0, Ordering::Relaxed);
-                        }
+                        task_ref.set_stage(label, label != "Sweep");
                     }));
 
                 // Progress + cancel callbacks for sweep bar-level progress
@@ -377,6 +808,7 @@ pub async fn submit_pipeline(
                     &on_stage,
                     Some(progress),
                     Some(&is_cancelled),
+                    Some(app_tasks::sweep_result_callback(&task)),
                 )
                 .await
                 .map_err(|e| e.to_string())?;
@@ -399,6 +831,10 @@ pub async fn submit_pipeline(
                         pipeline.sweep_id.clone(),
                         serde_json::to_value(&result).unwrap_or(Value::Null),
                     ),
+                    WorkflowResponse::OverfittingCheck(check) => (
+                        check.sweep_id.clone(),
+                        serde_json::to_value(&result).unwrap_or(Value::Null),
+                    ),
                 };
 
                 Ok(app_tasks::TaskCompletion {
@@ -409,38 +845,14 @@ pub async fn submit_pipeline(
         ))
         .await;
     });
-
-    Ok(Json(SubmitResponse { task_id }))
 }
 
-/// `POST /tasks/walk-forward` — Submit a walk-forward validation task.
-#[allow(clippy::unused_async, clippy::too_many_lines)]
-pub async fn submit_walk_forward(
-    State(state): State<AppState>,
-    Json(req): Json<SubmitWalkForwardRequest>,
-) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
-    // Symbol from params; resolved from script source later if not provided
-    let symbol = req
-        .params
-        .get("symbol")
-        .and_then(Value::as_str)
-        .unwrap_or("pending")
-        .to_owned();
-
+/// Run a walk-forward task and persist the validation.
+#[allow(clippy::too_many_lines)]
+fn spawn_walk_forward(state: &AppState, task: Arc<TaskInfo>, req: SubmitWalkForwardRequest) {
     // Will be overwritten with resolved symbol after script is loaded
     let symbol_from_params = req.params.contains_key("symbol");
-
-    let params_json =
-        serde_json::to_value(&req.params).unwrap_or(Value::Object(serde_json::Map::default()));
-
-    let task = state.task_manager.register(
-        TaskKind::WalkForward,
-        &req.strategy,
-        &symbol,
-        req.thread_id.clone(),
-        params_json,
-    );
-    let task_id = task.id.clone();
+    let symbol = task.symbol.clone();
 
     let tm = Arc::clone(&state.task_manager);
     let server = state.server.clone();
@@ -448,9 +860,12 @@ pub async fn submit_walk_forward(
     tokio::spawn(async move {
         app_tasks::execute_queued_task(tm, Arc::clone(&task), async move {
             let strategy_store = server.require_strategy_store().map_err(|e| e.to_string())?;
-            let (strategy_key, script_source) =
-                sweeps::resolve_strategy_source_from_store(strategy_store.as_ref(), &req.strategy)
-                    .map_err(|e| e.to_string())?;
+            let (strategy_key, script_source) = sweeps::resolve_strategy_source_from_store(
+                strategy_store.as_ref(),
+                server.signal_store.as_deref(),
+                &req.strategy,
+            )
+            .map_err(|e| e.to_string())?;
 
             let symbol = if symbol_from_params {
                 symbol
@@ -472,6 +887,7 @@ pub async fn submit_walk_forward(
             let wf_mode = match req.mode.as_str() {
                 "rolling" => WfMode::Rolling,
                 "anchored" => WfMode::Anchored,
+                "cpcv" => WfMode::Cpcv,
                 other => return Err(format!("Invalid walk-forward mode '{other}'")),
             };
 
@@ -491,7 +907,10 @@ pub async fn submit_walk_forward(
                     .get("CAPITAL")
                     .and_then(Value::as_f64)
                     .unwrap_or(100_000.0),
-                params_grid: sweeps::build_grid(&req.sweep_params)?,
+                params_grid: sweeps::build_grid(
+                    &req.sweep_params,
+                    &crate::scripting::stdlib::extract_extern_params(&script_source),
+                )?,
                 objective: wf_objective,
                 mode: wf_mode,
                 n_windows: req.n_windows,
@@ -513,13 +932,29 @@ pub async fn submit_walk_forward(
                     .map(String::from),
                 script_source: Some(script_source),
                 base_params: Some(req.params.clone()),
+                cpcv: CpcvConfig {
+                    n_test_groups: req
+                        .n_test_groups
+                        .unwrap_or(CpcvConfig::default().n_test_groups),
+                    embargo_bars: req
+                        .embargo_bars
+                        .unwrap_or(CpcvConfig::default().embargo_bars),
+                },
+            };
+
+            let on_window = |window: &crate::engine::walk_forward::WfWindowResult| {
+                task.emit(
+                    "wf_window",
+                    serde_json::to_value(window).unwrap_or(Value::Null),
+                );
             };
 
-            let wf_response = match crate::engine::walk_forward::execute(
+            let wf_response = match crate::engine::walk_forward::execute_with_windows(
                 wf_params,
                 &loader,
                 &is_cancelled,
                 &on_progress,
+                on_window,
             )
             .await
             {
@@ -587,275 +1022,15 @@ pub async fn submit_walk_forward(
         })
         .await;
     });
-
-    Ok(Json(SubmitResponse { task_id }))
-}
-
-/// `GET /tasks` — List active (queued + running) tasks.
-#[allow(clippy::unused_async)]
-pub async fn list_tasks(State(state): State<AppState>) -> Json<Vec<TaskSnapshot>> {
-    let active = state.task_manager.list_active();
-    let snapshots: Vec<TaskSnapshot> = active
-        .iter()
-        .map(|t| {
-            let pos = state.task_manager.queue_position(&t.id);
-            snapshot(t, pos)
-        })
-        .collect();
-    Json(snapshots)
 }
 
-/// `GET /tasks/{id}` — Get a single task's status.
-#[allow(clippy::unused_async)]
-pub async fn get_task(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<TaskSnapshot>, StatusCode> {
-    let task = state.task_manager.get(&id).ok_or(StatusCode::NOT_FOUND)?;
-    let pos = state.task_manager.queue_position(&id);
-    Ok(Json(snapshot(&task, pos)))
-}
-
-/// `DELETE /tasks/{id}` — Cancel a task.
-#[allow(clippy::unused_async)]
-pub async fn cancel_task(State(state): State<AppState>, Path(id): Path<String>) -> StatusCode {
-    if state.task_manager.cancel(&id) {
-        StatusCode::NO_CONTENT
-    } else {
-        StatusCode::NOT_FOUND
-    }
-}
-
-/// `GET /tasks/{id}/stream` — SSE progress stream (reconnectable).
-///
-/// Sends the current state immediately on connect, then polls every 200ms
-/// until the task reaches a terminal state.
-#[allow(clippy::unused_async, clippy::too_many_lines)]
-pub async fn stream_task(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, StatusCode> {
-    let task = state.task_manager.get(&id).ok_or(StatusCode::NOT_FOUND)?;
-    let tm = Arc::clone(&state.task_manager);
-
-    let (tx, rx) = tokio::sync::mpsc::channel::<Event>(32);
-
-    tokio::spawn(async move {
-        /// Helper: send an SSE event; returns false if the client disconnected.
-        async fn emit(tx: &tokio::sync::mpsc::Sender<Event>, event: &str, data: String) -> bool {
-            tx.send(Event::default().event(event).data(data))
-                .await
-                .is_ok()
-        }
-
-        let status = task.status();
-
-        // ── Send initial state based on current status ──
-        match status {
-            TaskStatus::Queued => {
-                let pos = tm.queue_position(&task.id).unwrap_or(0);
-                emit(&tx, "queued", format!(r#"{{"position":{pos}}}"#)).await;
-            }
-            TaskStatus::Running => {
-                let cur = task.progress_current.load(Ordering::Relaxed);
-                let tot = task.progress_total.load(Ordering::Relaxed);
-                let label = task.stage_label.lock().unwrap().clone();
-                emit(
-                    &tx,
-                    "progress",
-                    format!(
-                        r#"{{"current":{cur},"total":{tot},"stage_label":{}}}"#,
-                        serde_json::to_string(&label).unwrap_or_else(|_| "\"\"".to_string())
-                    ),
-                )
-                .await;
-            }
-            TaskStatus::Completed => {
-                let result_str = {
-                    let m = task.mutable.lock().unwrap();
-                    m.result.as_ref().map(std::string::ToString::to_string)
-                };
-                if let Some(s) = result_str {
-                    emit(&tx, "result", s).await;
-                }
-                emit(&tx, "done", String::new()).await;
-                return;
-            }
-            TaskStatus::Failed => {
-                let msg = {
-                    let m = task.mutable.lock().unwrap();
-                    m.error.clone().unwrap_or_default()
-                };
-                emit(&tx, "error", msg).await;
-                emit(&tx, "done", String::new()).await;
-                return;
-            }
-            TaskStatus::Cancelled => {
-                emit(&tx, "cancelled", String::new()).await;
-                emit(&tx, "done", String::new()).await;
-                return;
-            }
-        }
-
-        // ── Track status transitions for "started" event ──
-        let mut prev_status = status;
-
-        // ── Poll loop ──
-        let mut interval = tokio::time::interval(std::time::Duration::from_millis(200));
-        loop {
-            interval.tick().await;
-
-            let status = task.status();
-            let current = task.progress_current.load(Ordering::Relaxed);
-            let total = task.progress_total.load(Ordering::Relaxed);
-
-            // Detect transition from Queued → Running and send "started"
-            if prev_status == TaskStatus::Queued && status == TaskStatus::Running {
-                emit(&tx, "started", String::new()).await;
-            }
-            prev_status = status;
-
-            match status {
-                TaskStatus::Completed => {
-                    let result_str = {
-                        let m = task.mutable.lock().unwrap();
-                        m.result.as_ref().map(std::string::ToString::to_string)
-                    };
-                    if let Some(s) = result_str {
-                        emit(&tx, "result", s).await;
-                    }
-                    emit(&tx, "done", String::new()).await;
-                    break;
-                }
-                TaskStatus::Failed => {
-                    let msg = {
-                        let m = task.mutable.lock().unwrap();
-                        m.error.clone().unwrap_or_default()
-                    };
-                    emit(&tx, "error", msg).await;
-                    emit(&tx, "done", String::new()).await;
-                    break;
-                }
-                TaskStatus::Cancelled => {
-                    emit(&tx, "cancelled", String::new()).await;
-                    emit(&tx, "done", String::new()).await;
-                    break;
-                }
-                TaskStatus::Running => {
-                    let label = task.stage_label.lock().unwrap().clone();
-                    let label_json =
-                        serde_json::to_string(&label).unwrap_or_else(|_| "\"\"".to_string());
-                    let data = format!(
-                        r#"{{"current":{current},"total":{total},"stage_label":{label_json}}}"#
-                    );
-                    if !emit(&tx, "progress", data).await {
-                        break;
-                    }
-                }
-                TaskStatus::Queued => {
-                    let pos = tm.queue_position(&task.id).unwrap_or(0);
-                    if !emit(&tx, "queued", format!(r#"{{"position":{pos}}}"#)).await {
-                        break;
-                    }
-                }
-            }
-        }
-    });
-
-    Ok(
-        Sse::new(tokio_stream::wrappers::ReceiverStream::new(rx).map(Ok))
-            .keep_alive(KeepAlive::default()),
-    )
-}
-
-// ──────────────────────────────────────────────────────────────────────────────
-// Pipeline task
-// ──────────────────────────────────────────────────────────────────────────────
-
-/// `POST /tasks/baseline-validation` — submit the baseline validation workflow as a background task.
-pub async fn submit_baseline_validation(
-    State(state): State<AppState>,
-    Json(req): Json<super::pipeline::CreateBaselineValidationRequest>,
-) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
-    let workflow = workflows::WorkflowRequest {
-        kind: crate::tools::response_types::workflow::WorkflowKind::BaselineValidation,
-        pipeline: super::pipeline::build_pipeline_params(req, Some(&state))?,
-    };
-
-    let symbol = workflow
-        .pipeline
-        .params
-        .get("symbol")
-        .and_then(Value::as_str)
-        .unwrap_or("pending")
-        .to_owned();
-
-    let params_json = serde_json::to_value(&workflow.pipeline.params)
-        .unwrap_or(Value::Object(serde_json::Map::default()));
-
-    let task = state.task_manager.register(
-        TaskKind::Pipeline,
-        &workflow.pipeline.strategy,
-        &symbol,
-        workflow.pipeline.thread_id.clone(),
-        params_json,
-    );
-    let task_id = task.id.clone();
-
-    let tm = Arc::clone(&state.task_manager);
-    let server = state.server.clone();
-    tokio::spawn(async move {
-        Box::pin(app_tasks::execute_queued_task(
-            tm,
-            Arc::clone(&task),
-            async move {
-                let response = workflows::execute(&server, &workflow, "manual")
-                    .await
-                    .map_err(|e| e.to_string())?;
-                let result_json = serde_json::to_value(&response).unwrap_or(Value::Null);
-                Ok(app_tasks::TaskCompletion {
-                    result_json,
-                    result_id: workflow
-                        .pipeline
-                        .thread_id
-                        .clone()
-                        .unwrap_or_else(|| task.id.clone()),
-                })
-            },
-        ))
-        .await;
-    });
-
-    Ok(Json(SubmitResponse { task_id }))
-}
-
-/// `POST /tasks/workflows` — submit a named workflow as a background task.
-pub async fn submit_workflow(
-    State(state): State<AppState>,
-    Json(req): Json<super::pipeline::CreateWorkflowRequest>,
-) -> Result<Json<SubmitResponse>, (StatusCode, String)> {
-    let workflow = super::pipeline::build_workflow_params(req, Some(&state))?;
-
-    let symbol = workflow
-        .pipeline
-        .params
-        .get("symbol")
-        .and_then(Value::as_str)
-        .unwrap_or("pending")
-        .to_owned();
-
-    let params_json = serde_json::to_value(&workflow.pipeline.params)
-        .unwrap_or(Value::Object(serde_json::Map::default()));
-
-    let task = state.task_manager.register(
-        TaskKind::Workflow,
-        &workflow.pipeline.strategy,
-        &symbol,
-        workflow.pipeline.thread_id.clone(),
-        params_json,
-    );
-    let task_id = task.id.clone();
-
+/// Run a workflow task. The result id is `result_id`, or the task id.
+fn spawn_workflow(
+    state: &AppState,
+    task: Arc<TaskInfo>,
+    workflow: workflows::WorkflowRequest,
+    result_id: Option<String>,
+) {
     let tm = Arc::clone(&state.task_manager);
     let server = state.server.clone();
     tokio::spawn(async move {
@@ -869,12 +1044,10 @@ pub async fn submit_workflow(
                 let result_json = serde_json::to_value(&response).unwrap_or(Value::Null);
                 Ok(app_tasks::TaskCompletion {
                     result_json,
-                    result_id: task.id.clone(),
+                    result_id: result_id.unwrap_or_else(|| task.id.clone()),
                 })
             },
         ))
         .await;
     });
-
-    Ok(Json(SubmitResponse { task_id }))
 }
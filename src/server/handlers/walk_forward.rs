@@ -22,6 +22,7 @@ pub async fn run_walk_forward(
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Validation error: {e}")))?;
 
     let cache = Arc::clone(&state.server.cache);
+    let cpcv = params.cpcv_config();
     let response = wf_tool::execute(
         &cache,
         state.server.adjustment_store_handle(),
@@ -38,6 +39,7 @@ pub async fn run_walk_forward(
         params.profile,
         None,
         None,
+        Some(cpcv),
     )
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
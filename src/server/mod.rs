@@ -8,9 +8,13 @@ mod params;
 pub mod router;
 pub(crate) mod sanitize;
 pub mod state;
+pub mod task_events;
 pub mod task_manager;
 
-pub use params::{AggMetric, CorrelateMode, FactorProxies, GroupBy, RegimeMethod, RollingMetric};
+pub use params::{
+    AggMetric, CorrelateMode, FactorProxies, GroupBy, MonteCarloMethod, PortfolioSleeveSpec,
+    PortfolioStrategySpec, RegimeMethod, RollingMetric,
+};
 
 use garde::Validate;
 
@@ -29,13 +33,15 @@ use crate::tools;
 use crate::tools::response_types::{
     AggregatePricesResponse, BenchmarkAnalysisResponse, CointegrationResponse, CorrelateResponse,
     DistributionResponse, DrawdownAnalysisResponse, FactorAttributionResponse, HypothesisParams,
-    HypothesisResponse, MonteCarloResponse, PortfolioOptimizeResponse, RegimeDetectResponse,
+    HypothesisResponse, HypothesisValidationParams, HypothesisValidationResponse,
+    MonteCarloResponse, PortfolioBacktestResponse, PortfolioOptimizeResponse, RegimeDetectResponse,
     RollingMetricResponse,
 };
 use params::{
     tool_err, validation_err, AggregatePricesParams, BenchmarkAnalysisParams, CointegrationParams,
     CorrelateParams, DistributionParams, DrawdownAnalysisParams, FactorAttributionParams,
-    MonteCarloParams, PortfolioOptimizeParams, RegimeDetectParams, RollingMetricParams,
+    MonteCarloParams, PortfolioBacktestParams, PortfolioOptimizeParams, RegimeDetectParams,
+    RollingMetricParams,
 };
 use sanitize::SanitizedResult;
 
@@ -57,6 +63,8 @@ pub struct OptopsyServer {
     pub adjustment_store: Option<Arc<crate::data::adjustment_store::SqliteAdjustmentStore>>,
     /// Forward test session store for paper trading persistence.
     pub forward_test_store: Option<Arc<crate::data::forward_test_store::SqliteForwardTestStore>>,
+    /// Saved signal library resolved into strategies at transpile time.
+    pub signal_store: Option<Arc<crate::data::signal_store::SqliteSignalStore>>,
     tool_router: ToolRouter<Self>,
 }
 
@@ -70,6 +78,7 @@ impl OptopsyServer {
             run_store: None,
             adjustment_store: None,
             forward_test_store: None,
+            signal_store: None,
             tool_router: Self::tool_router(),
         }
     }
@@ -86,6 +95,7 @@ impl OptopsyServer {
             run_store: None,
             adjustment_store: None,
             forward_test_store: None,
+            signal_store: None,
             tool_router: Self::tool_router(),
         }
     }
@@ -103,6 +113,7 @@ impl OptopsyServer {
             run_store: Some(run_store),
             adjustment_store: None,
             forward_test_store: None,
+            signal_store: None,
             tool_router: Self::tool_router(),
         }
     }
@@ -121,6 +132,7 @@ impl OptopsyServer {
             run_store: Some(run_store),
             adjustment_store: Some(adjustment_store),
             forward_test_store: None,
+            signal_store: None,
             tool_router: Self::tool_router(),
         }
     }
@@ -135,6 +147,16 @@ impl OptopsyServer {
         self
     }
 
+    /// Attach a saved signal library to this server instance.
+    #[must_use]
+    pub fn with_signal_store(
+        mut self,
+        store: Arc<crate::data::signal_store::SqliteSignalStore>,
+    ) -> Self {
+        self.signal_store = Some(store);
+        self
+    }
+
     /// Ensure OHLCV price data exists for a symbol.
     /// Returns the parquet file path.
     ///
@@ -308,7 +330,10 @@ impl OptopsyServer {
     /// Returns per-regime statistics, a transition probability matrix, and a time series of regime labels.
     ///
     /// Methods: `volatility_cluster` (quantile-based vol regimes), `trend_state` (SMA crossover),
-    /// or `hmm` (Gaussian HMM with Baum-Welch EM — learns regime parameters from data).
+    /// `hmm` (Gaussian HMM with Baum-Welch EM — learns regime parameters from data), or
+    /// `multivariate_hmm` (full-covariance HMM on returns, realized vol, VIX level and VIX3M/VIX
+    /// slope jointly, with the state count chosen by BIC up to `n_regimes`), or `markov_switching`
+    /// (Hamilton Markov-switching AR(1) regression of returns, BIC-selected the same way).
     #[tool(name = "regime_detect", annotations(read_only_hint = true))]
     async fn regime_detect(
         &self,
@@ -349,7 +374,9 @@ impl OptopsyServer {
     /// **Dimensions scanned**: seasonality (day-of-week, month, turn-of-month),
     /// price action (momentum, consecutive moves), mean reversion (Bollinger, z-score),
     /// volume (spikes, low volume), volatility regime, cross-asset lead/lag,
-    /// microstructure (gaps, intraday range), autocorrelation.
+    /// microstructure (gaps, intraday range), autocorrelation, and — when the symbol
+    /// has cached options data — options structure (put/call skew, IV term slope,
+    /// IV-vs-realized spread, put/call volume imbalance).
     ///
     /// **Output**: Ranked patterns with deployable signal specs that can be passed directly
     /// to `run_stock_backtest` or `run_options_backtest` for validation.
@@ -389,6 +416,48 @@ impl OptopsyServer {
             .await,
         )
     }
+
+    /// Turn the top hypotheses from `generate_hypotheses` into runnable strategies
+    /// and validate each one through the full backtest pipeline.
+    ///
+    /// For each of the `top_n` hypotheses a `.trading` strategy is synthesized —
+    /// enter when the signal fires (long or short in the direction of the effect,
+    /// or `options_expression` such as `short_put(0.30, 45)`), exit after the
+    /// forward horizon — and saved to the strategy store. Each strategy then runs
+    /// sweep (holding period) -> significance gate (permutation test) ->
+    /// overfitting gate -> walk-forward -> `oos_data_gate` -> monte carlo.
+    ///
+    /// Options-structure ranks run on the matching options-chain indicators; signals
+    /// without a bar-level DSL equivalent (volume averages, cross-asset moves) are
+    /// reported as `unsupported` instead of run.
+    ///
+    /// **Output**: A ranked table — validated hypotheses first, then by gates passed
+    /// and out-of-sample Sharpe — with the stored strategy ID for each.
+    ///
+    /// **Time to run**: roughly one pipeline run per hypothesis (tens of seconds each).
+    #[tool(name = "validate_hypotheses", annotations(read_only_hint = false))]
+    async fn validate_hypotheses(
+        &self,
+        Parameters(params): Parameters<HypothesisValidationParams>,
+    ) -> SanitizedResult<HypothesisValidationResponse, String> {
+        SanitizedResult(
+            async {
+                params
+                    .validate()
+                    .map_err(|e| validation_err("validate_hypotheses", e))?;
+                for sym in &params.scan.symbols {
+                    let upper = sym.to_uppercase();
+                    validate_path_segment(&upper)
+                        .map_err(|e| format!("Invalid symbol \"{sym}\": {e}"))?;
+                    self.ensure_ohlcv(&upper)?;
+                }
+                tools::hypothesis_validation::execute(self, &params)
+                    .await
+                    .map_err(tool_err)
+            }
+            .await,
+        )
+    }
     /// Analyze the full drawdown distribution of a symbol's price history.
     ///
     /// Decomposes the equity curve into individual drawdown episodes and computes
@@ -460,12 +529,18 @@ impl OptopsyServer {
     /// equity paths via bootstrapped block resampling. Produces confidence intervals on
     /// terminal wealth, max drawdown distributions, and ruin probabilities.
     ///
+    /// **Sources** (exactly one): `symbol` bootstraps the symbol's price returns;
+    /// `run_id` (a stored run) or `result` (an inline backtest result) resamples the
+    /// strategy's own daily returns (`block_bootstrap`) or trade P&Ls (`trade_shuffle`,
+    /// `trade_bootstrap`), scaled by `position_scale`.
+    ///
     /// **When to use**: After backtesting, to estimate the range of possible outcomes
     /// going forward. Complements the permutation test (which tests *past* significance)
     /// with *forward-looking* risk quantification.
     ///
     /// **Output**: Percentile paths (5th/25th/50th/75th/95th), ruin probabilities,
-    /// drawdown distribution, and terminal wealth histogram.
+    /// drawdown distribution, and terminal wealth histogram. Strategy sources add
+    /// path-level risk of ruin and time-to-recover.
     #[tool(name = "monte_carlo", annotations(read_only_hint = true))]
     async fn monte_carlo(
         &self,
@@ -476,16 +551,49 @@ impl OptopsyServer {
                 params
                     .validate()
                     .map_err(|e| validation_err("monte_carlo", e))?;
-                tools::monte_carlo::execute(
-                    &self.cache,
-                    &params.symbol,
+                let sample = match (&params.symbol, &params.run_id, &params.result) {
+                    (Some(symbol), None, None) => {
+                        return tools::monte_carlo::execute(
+                            &self.cache,
+                            symbol,
+                            params.n_simulations,
+                            params.horizon_days,
+                            params.initial_capital,
+                            params.years,
+                            params.seed,
+                        )
+                        .await
+                        .map_err(tool_err);
+                    }
+                    (None, Some(run_id), None) => {
+                        let run_store = self.require_run_store().map_err(tool_err)?;
+                        let run = run_store
+                            .get_run(run_id)
+                            .map_err(tool_err)?
+                            .ok_or_else(|| tool_err(format!("Run '{run_id}' not found")))?;
+                        tools::monte_carlo::StrategySample::from_run(&run).map_err(tool_err)?
+                    }
+                    (None, None, Some(result)) => tools::monte_carlo::StrategySample::from_result(
+                        result,
+                        params.initial_capital,
+                    ),
+                    _ => {
+                        return Err(validation_err(
+                            "monte_carlo",
+                            "provide exactly one of symbol, run_id, or result",
+                        ))
+                    }
+                };
+                tools::monte_carlo::execute_on_strategy(
+                    &sample,
+                    params.method,
                     params.n_simulations,
                     params.horizon_days,
                     params.initial_capital,
-                    params.years,
+                    params.position_scale,
+                    params.ruin_threshold,
                     params.seed,
                 )
-                .await
                 .map_err(tool_err)
             }
             .await,
@@ -527,18 +635,25 @@ impl OptopsyServer {
         )
     }
 
-    /// Optimize portfolio weights using risk parity, minimum variance, and/or maximum Sharpe.
+    /// Optimize portfolio weights across symbols or strategies.
     ///
-    /// Takes 2-20 symbols and computes optimal allocations using three methods:
+    /// Takes 2-20 `symbols`, stored `run_ids`, or `strategies` (strategy + params
+    /// sets) and computes optimal allocations using five methods:
     /// - **`risk_parity`**: Equal risk contribution from each asset
     /// - **`min_variance`**: Minimize total portfolio volatility
     /// - **`max_sharpe`**: Maximize risk-adjusted return (tangency portfolio)
+    /// - **`max_diversification`**: Maximize the diversification ratio
+    /// - **`hrp`**: Hierarchical risk parity on correlation clusters
+    ///
+    /// Strategies are compared on the daily returns of their equity curves,
+    /// aligned on common trading days. Set `rerun_method` to re-run every
+    /// strategy with `capital × weight` and get a combined backtest.
     ///
     /// **When to use**: After identifying a set of assets/strategies, to determine
     /// optimal allocation weights rather than using equal weighting.
     ///
     /// **Output**: Optimal weights per method, expected portfolio metrics,
-    /// correlation matrix, and per-asset statistics.
+    /// correlation matrix, per-asset statistics, and the optional combined backtest.
     #[tool(name = "portfolio_optimize", annotations(read_only_hint = true))]
     async fn portfolio_optimize(
         &self,
@@ -549,15 +664,81 @@ impl OptopsyServer {
                 params
                     .validate()
                     .map_err(|e| validation_err("portfolio_optimize", e))?;
-                tools::portfolio_optimize::execute(
-                    &self.cache,
-                    &params.symbols,
-                    params.methods.as_deref(),
-                    params.years,
-                    params.risk_free_rate,
-                )
-                .await
-                .map_err(tool_err)
+                let sources = [
+                    params.symbols.len(),
+                    params.run_ids.len(),
+                    params.strategies.len(),
+                ];
+                if sources.iter().filter(|&&n| n > 0).count() != 1
+                    || sources.contains(&1)
+                {
+                    return Err(validation_err(
+                        "portfolio_optimize",
+                        "provide at least 2 entries in exactly one of symbols, run_ids, or strategies",
+                    ));
+                }
+                if params.symbols.is_empty() {
+                    tools::portfolio_optimize::execute_strategies(
+                        self,
+                        &params.run_ids,
+                        &params.strategies,
+                        params.methods.as_deref(),
+                        params.risk_free_rate,
+                        params.rerun_method.as_deref(),
+                        params.capital,
+                    )
+                    .await
+                    .map_err(tool_err)
+                } else if params.rerun_method.is_some() {
+                    Err(validation_err(
+                        "portfolio_optimize",
+                        "rerun_method requires run_ids or strategies",
+                    ))
+                } else {
+                    tools::portfolio_optimize::execute(
+                        &self.cache,
+                        &params.symbols,
+                        params.methods.as_deref(),
+                        params.years,
+                        params.risk_free_rate,
+                    )
+                    .await
+                    .map_err(tool_err)
+                }
+            }
+            .await,
+        )
+    }
+
+    /// Backtest several saved strategies together on one shared capital pool.
+    ///
+    /// Sleeves (strategy + params) trade bar-synchronously: every entry is checked
+    /// against the pool's buying power and the cross-strategy position limits, so
+    /// sleeves compete for the same cash instead of each assuming its own account.
+    /// - **Allocation**: `fixed_weights` (per-sleeve `weight`) or `risk_budget`
+    ///   (per-sleeve `risk_budget` divided by trailing volatility)
+    /// - **Rebalancing**: none, weekly, monthly, or quarterly; only free cash moves
+    /// - **Limits**: `max_positions`, `max_positions_per_symbol`,
+    ///   `max_sleeve_utilization`
+    ///
+    /// **When to use**: After validating strategies individually, to see how they
+    /// trade together with realistic capital contention.
+    ///
+    /// **Output**: Consolidated backtest result (equity curve, trade log, metrics)
+    /// and per-sleeve attribution (P&L, weight drift, blocked entries).
+    #[tool(name = "portfolio_backtest", annotations(read_only_hint = true))]
+    async fn portfolio_backtest(
+        &self,
+        Parameters(params): Parameters<PortfolioBacktestParams>,
+    ) -> SanitizedResult<PortfolioBacktestResponse, String> {
+        SanitizedResult(
+            async {
+                params
+                    .validate()
+                    .map_err(|e| validation_err("portfolio_backtest", e))?;
+                tools::portfolio_backtest::execute(self, &params.sleeves, params.portfolio_config())
+                    .await
+                    .map_err(tool_err)
             }
             .await,
         )
@@ -611,9 +792,21 @@ impl OptopsyServer {
     ///
     /// Omit `sweep_params` for a single backtest (returns full equity curve, trade log, metrics).
     /// Provide `sweep_params` for a grid/bayesian sweep. By default, sweeps run the
-    /// full analysis pipeline: sweep -> significance gate -> walk-forward ->
-    /// `oos_data_gate` -> monte carlo. Set `pipeline=false` to return sweep-only results.
-    /// Results are persisted to the runs database.
+    /// full analysis pipeline: sweep -> significance gate -> overfitting gate ->
+    /// walk-forward -> `oos_data_gate` -> monte carlo. Set `pipeline=false` to return
+    /// sweep-only results. Results are persisted to the runs database.
+    ///
+    /// The overfitting gate runs CSCV on the per-combo equity curves and stops the
+    /// pipeline when the probability of backtest overfitting (`cscv.pbo`) exceeds 0.5.
+    ///
+    /// Use `mode="pareto"` with `objectives` (e.g. `["cagr", "max_drawdown", "cvar_95"]`)
+    /// to trade several metrics off at once: the response's `pareto` field lists the
+    /// non-dominated combos with crowding distances, and `best_result` is the knee point.
+    ///
+    /// A sweep param with `"param_type": "categorical"` sweeps its `choices` (e.g. a
+    /// `SLIPPAGE` extern). In bayesian mode a param with
+    /// `"when": { "parent": "SLIPPAGE", "values": ["spread"] }` is only swept while that
+    /// parent choice is active.
     ///
     /// **Example (single backtest)**:
     /// ```json
@@ -623,6 +816,20 @@ impl OptopsyServer {
     /// }
     /// ```
     ///
+    /// **Example (multi-objective sweep)**:
+    /// ```json
+    /// {
+    ///   "strategy": "short_put",
+    ///   "mode": "pareto",
+    ///   "objectives": ["cagr", "max_drawdown", "cvar_95"],
+    ///   "params": { "symbol": "SPY" },
+    ///   "sweep_params": [
+    ///     { "name": "DELTA_TARGET", "start": 0.10, "stop": 0.40, "step": 0.05 }
+    ///   ],
+    ///   "pipeline": false
+    /// }
+    /// ```
+    ///
     /// **Example (parameter sweep with full pipeline)**:
     /// ```json
     /// {
@@ -675,7 +882,8 @@ impl ServerHandler for OptopsyServer {
                 \n\
                 \n### 1. Run a Backtest\
                 \n  - **backtest** — Run a backtest or parameter sweep. Pass a saved strategy by display name.\
-                \n    Omit `sweep_params` for a single backtest, or provide ranges for a grid/bayesian sweep.\
+                \n    Omit `sweep_params` for a single backtest, or provide ranges for a grid/bayesian sweep\
+                \n    (or mode=\"pareto\" with `objectives` for a multi-objective Pareto front).\
                 \n    Results are persisted to the runs database.\
                 \n  - OHLCV and options data is loaded from cache automatically.\
                 \n  - To compare parameters, use backtest with sweep_params.\
@@ -683,6 +891,7 @@ impl ServerHandler for OptopsyServer {
                 \n### 2. Discover Patterns (optional)\
                 \n  - generate_hypotheses({ symbols: [\"SPY\"] }) — scan for statistically significant patterns\
                 \n  - Results are HYPOTHESES — validate with a backtest before trusting\
+                \n  - validate_hypotheses({ symbols: [\"SPY\"], top_n: 3 }) — turn the top hypotheses into stored strategies and run each through the validation pipeline\
                 \n\
                 \n### 3. Analyze Results\
                 \n  After a backtest, use analytical tools to evaluate:\
@@ -699,7 +908,8 @@ impl ServerHandler for OptopsyServer {
                 \n  - rolling_metric — rolling Sharpe, volatility, beta, etc.\
                 \n  - regime_detect — market regime identification (HMM, volatility, trend)\
                 \n  - cointegration_test — pairs trading validation\
-                \n  - portfolio_optimize — optimal weight allocation (risk parity, min variance, max Sharpe)\
+                \n  - portfolio_backtest — run several strategies together on shared capital\
+                \n  - portfolio_optimize — optimal weight allocation across symbols or strategies (risk parity, min variance, max Sharpe, max diversification, HRP)\
                 \n\
                 \n## RULES\
                 \n- Each tool response includes suggested_next_steps — follow them"
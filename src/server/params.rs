@@ -11,7 +11,11 @@ use serde::Deserialize;
 
 use std::collections::HashMap;
 
-use crate::engine::types::Interval;
+use crate::engine::cpcv::CpcvConfig;
+use crate::engine::types::{BacktestResult, Interval};
+use crate::scripting::portfolio::{
+    AllocationRule, PoolMargin, PortfolioConfig, PortfolioLimits, RebalanceFrequency,
+};
 
 /// Format a garde validation error with the originating tool name for easier debugging.
 pub(crate) fn validation_err(tool: &str, e: impl std::fmt::Display) -> String {
@@ -55,9 +59,9 @@ fn validate_wf_objective(value: &Option<String>, _ctx: &()) -> garde::Result {
 fn validate_wf_mode(value: &Option<String>, _ctx: &()) -> garde::Result {
     if let Some(v) = value {
         match v.as_str() {
-            "rolling" | "anchored" => Ok(()),
+            "rolling" | "anchored" | "cpcv" => Ok(()),
             _ => Err(garde::Error::new(format!(
-                "invalid mode '{v}', expected: rolling, anchored"
+                "invalid mode '{v}', expected: rolling, anchored, cpcv"
             ))),
         }
     } else {
@@ -223,6 +227,14 @@ pub enum RegimeMethod {
     /// Gaussian Hidden Markov Model
     #[serde(rename = "hmm")]
     Hmm,
+    /// Multivariate Gaussian HMM on returns, realized vol, VIX level and term
+    /// slope, with the state count chosen by BIC
+    #[serde(rename = "multivariate_hmm")]
+    MultivariateHmm,
+    /// Markov-switching AR(1) regression of returns (switching intercept, AR
+    /// coefficient and variance), with the state count chosen by BIC
+    #[serde(rename = "markov_switching")]
+    MarkovSwitching,
 }
 
 impl RegimeMethod {
@@ -231,6 +243,33 @@ impl RegimeMethod {
             Self::VolatilityCluster => "volatility_cluster",
             Self::TrendState => "trend_state",
             Self::Hmm => "hmm",
+            Self::MultivariateHmm => "multivariate_hmm",
+            Self::MarkovSwitching => "markov_switching",
+        }
+    }
+}
+
+/// Resampling method for `monte_carlo` on a backtest's own results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+pub enum MonteCarloMethod {
+    /// Block bootstrap of the strategy's daily returns (default)
+    #[default]
+    #[serde(rename = "block_bootstrap")]
+    BlockBootstrap,
+    /// Replay the same trades in a random order
+    #[serde(rename = "trade_shuffle")]
+    TradeShuffle,
+    /// Draw trades with replacement
+    #[serde(rename = "trade_bootstrap")]
+    TradeBootstrap,
+}
+
+impl MonteCarloMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BlockBootstrap => "block_bootstrap",
+            Self::TradeShuffle => "trade_shuffle",
+            Self::TradeBootstrap => "trade_bootstrap",
         }
     }
 }
@@ -363,11 +402,14 @@ pub struct RegimeDetectParams {
     /// Ticker symbol
     #[garde(length(min = 1, max = 10), pattern(r"^[A-Za-z0-9._-]+$"))]
     pub symbol: String,
-    /// Detection method: `"volatility_cluster"` (default), `"trend_state"`, or `"hmm"` (Gaussian HMM)
+    /// Detection method: `"volatility_cluster"` (default), `"trend_state"`, `"hmm"` (Gaussian HMM),
+    /// `"multivariate_hmm"` (joint returns / realized vol / VIX / term slope, BIC-selected state count),
+    /// or `"markov_switching"` (Markov-switching AR(1) regression of returns, BIC-selected state count)
     #[serde(default)]
     #[garde(skip)]
     pub method: RegimeMethod,
-    /// Number of regimes to detect (default: 3, range: 2-4)
+    /// Number of regimes to detect (default: 3, range: 2-4). For `"multivariate_hmm"` and
+    /// `"markov_switching"` this is the largest state count tried; BIC picks from 2 up to it
     #[serde(default = "default_n_regimes")]
     #[garde(range(min = 2, max = 4))]
     pub n_regimes: usize,
@@ -433,18 +475,53 @@ pub struct CointegrationParams {
     pub years: u32,
 }
 
+fn default_position_scale() -> f64 {
+    1.0
+}
+
+fn default_ruin_threshold() -> f64 {
+    0.5
+}
+
 /// Parameters for the `monte_carlo` tool.
+///
+/// Exactly one source is required: `symbol` (bootstrap the symbol's price
+/// returns), `run_id` (a stored backtest run), or `result` (an inline backtest
+/// result).
 #[derive(Debug, Deserialize, JsonSchema, Validate)]
 #[garde(context(()))]
 pub struct MonteCarloParams {
     /// Ticker symbol to base simulations on
-    #[garde(length(min = 1, max = 10), pattern(r"^[A-Za-z0-9._-]+$"))]
-    pub symbol: String,
+    #[serde(default)]
+    #[garde(inner(length(min = 1, max = 10), pattern(r"^[A-Za-z0-9._-]+$")))]
+    pub symbol: Option<String>,
+    /// Stored backtest run id — simulate the strategy's own trades/returns
+    #[serde(default)]
+    #[garde(inner(length(min = 1)))]
+    pub run_id: Option<String>,
+    /// Inline backtest result (as returned by `backtest`) to simulate
+    #[serde(default)]
+    #[garde(skip)]
+    pub result: Option<BacktestResult>,
+    /// Resampling method for `run_id`/`result`: `block_bootstrap` (default),
+    /// `trade_shuffle`, or `trade_bootstrap`
+    #[serde(default)]
+    #[garde(skip)]
+    pub method: MonteCarloMethod,
+    /// Position-size multiplier applied to strategy returns or trade P&L (default: 1.0)
+    #[serde(default = "default_position_scale")]
+    #[garde(range(min = 0.01, max = 10.0))]
+    pub position_scale: f64,
+    /// Drawdown from starting capital that counts as ruin, as a fraction (default: 0.5)
+    #[serde(default = "default_ruin_threshold")]
+    #[garde(range(min = 0.01, max = 1.0))]
+    pub ruin_threshold: f64,
     /// Number of simulations (default: 10000)
     #[serde(default = "default_n_simulations")]
     #[garde(range(min = 100, max = 100_000))]
     pub n_simulations: usize,
-    /// Forecast horizon in trading days (default: 252)
+    /// Forecast horizon in trading days (default: 252). Trade-based methods
+    /// simulate as many trades as the backtest produced instead.
     #[serde(default = "default_horizon_days")]
     #[garde(range(min = 5, max = 2520))]
     pub horizon_days: usize,
@@ -452,7 +529,7 @@ pub struct MonteCarloParams {
     #[serde(default = "default_monte_carlo_capital")]
     #[garde(range(min = 1.0))]
     pub initial_capital: f64,
-    /// Years of historical data to fit from (default: 5)
+    /// Years of historical data to fit from (default: 5; `symbol` only)
     #[serde(default = "default_analysis_years")]
     #[garde(range(min = 1, max = 50))]
     pub years: u32,
@@ -506,20 +583,56 @@ pub struct FactorProxies {
     pub momentum: Option<String>,
 }
 
+/// A strategy backtest to include in a strategy-level `portfolio_optimize`.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct PortfolioStrategySpec {
+    /// Saved strategy name or ID
+    pub strategy: String,
+    /// Parameters injected into the script (same as `backtest`)
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
+    /// Display label for this sleeve (default: strategy name)
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
 /// Parameters for the `portfolio_optimize` tool.
+///
+/// Provide exactly one source with at least two entries: `symbols`,
+/// `run_ids`, or `strategies`.
 #[derive(Debug, Deserialize, JsonSchema, Validate)]
 #[garde(context(()))]
 pub struct PortfolioOptimizeParams {
     /// Symbols to include in portfolio (2-20)
+    #[serde(default)]
     #[garde(
-        length(min = 2, max = 20),
+        length(max = 20),
         inner(length(min = 1, max = 10), pattern(r"^[A-Za-z0-9._-]+$"))
     )]
     pub symbols: Vec<String>,
-    /// Optimization methods to run (default: all three)
+    /// Stored backtest run IDs to allocate across (2-20); their daily
+    /// equity-curve returns are aligned on common trading days
+    #[serde(default)]
+    #[garde(length(max = 20), inner(length(min = 1, max = 64)))]
+    pub run_ids: Vec<String>,
+    /// Strategy + params sets to backtest and allocate across (2-20)
+    #[serde(default)]
+    #[garde(length(max = 20))]
+    pub strategies: Vec<PortfolioStrategySpec>,
+    /// Optimization methods to run: `risk_parity`, `min_variance`, `max_sharpe`,
+    /// `max_diversification`, `hrp` (default: all)
     #[serde(default)]
     #[garde(skip)]
     pub methods: Option<Vec<String>>,
+    /// Strategy sources only: re-run every strategy with `capital × weight` from
+    /// this method and return the combined backtest
+    #[serde(default)]
+    #[garde(skip)]
+    pub rerun_method: Option<String>,
+    /// Total capital for the combined backtest (default: 100000)
+    #[serde(default = "default_portfolio_capital")]
+    #[garde(range(min = 1000.0, max = 1_000_000_000.0))]
+    pub capital: f64,
     /// Years of history (default: 5)
     #[serde(default = "default_analysis_years")]
     #[garde(range(min = 1, max = 50))]
@@ -534,6 +647,94 @@ fn default_risk_free() -> f64 {
     0.05
 }
 
+fn default_portfolio_capital() -> f64 {
+    100_000.0
+}
+
+/// One strategy sleeve of a `portfolio_backtest`.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct PortfolioSleeveSpec {
+    /// Saved strategy name or ID
+    pub strategy: String,
+    /// Parameters injected into the script (symbols, thresholds, …)
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
+    /// Display label (default: strategy name)
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Relative capital weight for `fixed_weights` allocation (default: 1)
+    #[serde(default)]
+    pub weight: Option<f64>,
+    /// Relative risk budget for `risk_budget` allocation (default: 1)
+    #[serde(default)]
+    pub risk_budget: Option<f64>,
+}
+
+/// Parameters for the `portfolio_backtest` tool.
+#[derive(Debug, Deserialize, JsonSchema, Validate)]
+#[garde(context(()))]
+pub struct PortfolioBacktestParams {
+    /// Strategy sleeves sharing one capital pool (2-20)
+    #[garde(length(min = 2, max = 20))]
+    pub sleeves: Vec<PortfolioSleeveSpec>,
+    /// Total starting capital shared by all sleeves (default: 100000)
+    #[serde(default = "default_portfolio_capital")]
+    #[garde(range(min = 1000.0, max = 1_000_000_000.0))]
+    pub capital: f64,
+    /// How sleeve target weights are set: `fixed_weights` (default) or `risk_budget`
+    #[serde(default)]
+    #[garde(skip)]
+    pub allocation: AllocationRule,
+    /// Reset sleeves to their target weights: "none" (default), "weekly", "monthly", "quarterly"
+    #[serde(default)]
+    #[garde(skip)]
+    pub rebalance: RebalanceFrequency,
+    /// Trailing daily returns used to estimate sleeve volatility for risk budgets (default: 63)
+    #[serde(default = "default_risk_lookback_days")]
+    #[garde(range(min = 5, max = 504))]
+    pub risk_lookback_days: usize,
+    /// Margin model measuring each sleeve's draw on the shared pool:
+    /// "cash", `reg_t` (default), "portfolio"
+    #[serde(default)]
+    #[garde(skip)]
+    pub margin: PoolMargin,
+    /// Maximum open positions across all sleeves
+    #[serde(default)]
+    #[garde(inner(range(min = 1, max = 10_000)))]
+    pub max_positions: Option<usize>,
+    /// Maximum open positions on any one symbol across all sleeves
+    #[serde(default)]
+    #[garde(inner(range(min = 1, max = 10_000)))]
+    pub max_positions_per_symbol: Option<usize>,
+    /// Margin a sleeve may commit as a multiple of its own equity
+    /// (default: unlimited — sleeves draw on idle capital anywhere in the pool)
+    #[serde(default)]
+    #[garde(inner(range(min = 0.01, max = 10.0)))]
+    pub max_sleeve_utilization: Option<f64>,
+}
+
+fn default_risk_lookback_days() -> usize {
+    63
+}
+
+impl PortfolioBacktestParams {
+    /// Portfolio-level settings for the shared capital pool.
+    pub fn portfolio_config(&self) -> PortfolioConfig {
+        PortfolioConfig {
+            capital: self.capital,
+            allocation: self.allocation,
+            rebalance: self.rebalance,
+            risk_lookback: self.risk_lookback_days,
+            margin: self.margin,
+            limits: PortfolioLimits {
+                max_positions: self.max_positions,
+                max_positions_per_symbol: self.max_positions_per_symbol,
+                max_sleeve_utilization: self.max_sleeve_utilization,
+            },
+        }
+    }
+}
+
 /// Parameters for the `benchmark_analysis` tool.
 #[derive(Debug, Deserialize, JsonSchema, Validate)]
 #[garde(context(()))]
@@ -592,12 +793,14 @@ pub struct WalkForwardToolParams {
     #[garde(custom(validate_wf_objective))]
     pub objective: Option<String>,
 
-    /// Number of walk-forward windows (default: 5).
+    /// Number of walk-forward windows (default: 5). In `cpcv` mode, the
+    /// number of contiguous groups the date range is split into.
     #[serde(default = "default_wf_n_windows")]
     #[garde(range(min = 1, max = 50))]
     pub n_windows: usize,
 
-    /// Walk-forward mode: `rolling` (default) or `anchored`.
+    /// Walk-forward mode: `rolling` (default), `anchored`, or `cpcv`
+    /// (combinatorial purged cross-validation).
     #[serde(default)]
     #[garde(custom(validate_wf_mode))]
     pub mode: Option<String>,
@@ -621,6 +824,27 @@ pub struct WalkForwardToolParams {
     #[serde(default)]
     #[garde(skip)]
     pub profile: Option<String>,
+
+    /// CPCV only: groups held out as the test set in each split (default: 2).
+    #[serde(default)]
+    #[garde(inner(range(min = 1, max = 49)))]
+    pub n_test_groups: Option<usize>,
+
+    /// CPCV only: bars dropped from training after each test group (default: 5).
+    #[serde(default)]
+    #[garde(inner(range(max = 252)))]
+    pub embargo_bars: Option<usize>,
+}
+
+impl WalkForwardToolParams {
+    /// CPCV split settings, falling back to defaults for omitted fields.
+    pub fn cpcv_config(&self) -> CpcvConfig {
+        let defaults = CpcvConfig::default();
+        CpcvConfig {
+            n_test_groups: self.n_test_groups.unwrap_or(defaults.n_test_groups),
+            embargo_bars: self.embargo_bars.unwrap_or(defaults.embargo_bars),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -695,6 +919,57 @@ mod tests {
         }
     }
 
+    // ─── MonteCarloParams ────────────────────────────────────────────────
+
+    #[test]
+    fn monte_carlo_params_run_source_defaults() {
+        let json = serde_json::json!({ "run_id": "abc", "method": "trade_shuffle" });
+        let p: MonteCarloParams = serde_json::from_value(json).unwrap();
+        p.validate().unwrap();
+        assert!(p.symbol.is_none());
+        assert_eq!(p.method, MonteCarloMethod::TradeShuffle);
+        assert!((p.position_scale - 1.0).abs() < f64::EPSILON);
+        assert!((p.ruin_threshold - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn monte_carlo_params_rejects_bad_scale() {
+        let json = serde_json::json!({ "symbol": "SPY", "position_scale": 0.0 });
+        let p: MonteCarloParams = serde_json::from_value(json).unwrap();
+        assert!(p.validate().is_err());
+    }
+
+    // ─── PortfolioBacktestParams ─────────────────────────────────────────
+
+    #[test]
+    fn portfolio_backtest_params_parse_rules() {
+        let json = serde_json::json!({
+            "sleeves": [
+                { "strategy": "short_put", "weight": 2.0 },
+                { "strategy": "momentum", "params": { "symbol": "QQQ" } }
+            ],
+            "allocation": "risk_budget",
+            "rebalance": "monthly",
+            "max_positions_per_symbol": 2
+        });
+        let p: PortfolioBacktestParams = serde_json::from_value(json).unwrap();
+        p.validate().unwrap();
+        let config = p.portfolio_config();
+        assert_eq!(config.allocation, AllocationRule::RiskBudget);
+        assert_eq!(config.rebalance, RebalanceFrequency::Monthly);
+        assert_eq!(config.margin, PoolMargin::RegT);
+        assert_eq!(config.risk_lookback, 63);
+        assert_eq!(config.limits.max_positions_per_symbol, Some(2));
+        assert!(config.limits.max_sleeve_utilization.is_none());
+    }
+
+    #[test]
+    fn portfolio_backtest_params_need_two_sleeves() {
+        let json = serde_json::json!({ "sleeves": [{ "strategy": "short_put" }] });
+        let p: PortfolioBacktestParams = serde_json::from_value(json).unwrap();
+        assert!(p.validate().is_err());
+    }
+
     // ─── WalkForwardToolParams validation ────────────────────────────────
 
     #[test]
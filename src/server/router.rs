@@ -8,14 +8,14 @@ use axum::Router;
 use tower_http::cors::CorsLayer;
 
 use crate::server::handlers::{
-    backtests, chat as chat_handlers, forward_tests, hypotheses, pipeline, profiles, runs,
+    backtests, chat as chat_handlers, forward_tests, hypotheses, pipeline, profiles, runs, signals,
     strategies, sweeps, tasks,
 };
 use crate::server::state::AppState;
 
 /// Build the full REST API router from `state`.
 ///
-/// Includes all route groups (strategy, chat, run, task, misc) merged together
+/// Includes all route groups (strategy, chat, run, task, signal, misc) merged together
 /// with a permissive CORS layer.
 ///
 /// **Not included** (handled by the caller in `main.rs`):
@@ -139,6 +139,7 @@ pub fn build_api_router(state: AppState) -> Router {
 
     let task_routes = Router::new()
         .route("/tasks", axum::routing::get(tasks::list_tasks))
+        .route("/tasks/stream", axum::routing::get(tasks::stream_all_tasks))
         .route(
             "/tasks/backtest",
             axum::routing::post(tasks::submit_backtest),
@@ -183,6 +184,23 @@ pub fn build_api_router(state: AppState) -> Router {
             "/forward-tests/{id}/step",
             axum::routing::post(forward_tests::step_forward_test),
         )
+        .with_state(state.clone());
+
+    let signal_routes = Router::new()
+        .route(
+            "/signals",
+            axum::routing::get(signals::list_signals).post(signals::create_signal),
+        )
+        .route(
+            "/signals/{name}",
+            axum::routing::get(signals::get_signal)
+                .put(signals::update_signal)
+                .delete(signals::delete_signal),
+        )
+        .route(
+            "/signals/{name}/versions",
+            axum::routing::get(signals::list_signal_versions),
+        )
         .with_state(state);
 
     Router::new()
@@ -191,6 +209,7 @@ pub fn build_api_router(state: AppState) -> Router {
         .merge(run_routes)
         .merge(task_routes)
         .merge(forward_test_routes)
+        .merge(signal_routes)
         .merge(analysis_routes)
         .merge(misc_routes)
         .layer(CorsLayer::permissive())
@@ -61,6 +61,7 @@ pub fn trade_row_from_record(t: &TradeRecord) -> TradeRow {
         stock_exit_price: t.stock_exit_price.map(sanitize),
         stock_pnl: t.stock_pnl.map(sanitize),
         group: t.group.clone(),
+        dividend_pnl: t.dividend_pnl.map(sanitize),
     }
 }
 
@@ -0,0 +1,208 @@
+//! Push-based event bus for task streams.
+//!
+//! Every task lifecycle transition, throttled progress update, pipeline stage
+//! change and intermediate result (per-combo sweep results, per-window
+//! walk-forward results) is published here as a [`TaskEvent`]. Events carry a
+//! sequence number that is monotonic across all tasks and is sent as the SSE
+//! `id`, so a reconnecting client can pass `Last-Event-ID` and have missed
+//! events replayed from a bounded in-memory buffer.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Number of recent events kept for `Last-Event-ID` replay.
+pub const REPLAY_CAPACITY: usize = 4096;
+
+/// A single event published for a task.
+#[derive(Debug, Clone)]
+pub struct TaskEvent {
+    /// Sequence number, monotonic across all tasks; the SSE event `id`.
+    pub seq: u64,
+    pub task_id: String,
+    /// SSE event name (`queued`, `started`, `progress`, `sweep_result`,
+    /// `wf_window`, `result`, `error`, `cancelled`, `done`).
+    pub event: String,
+    pub data: Value,
+}
+
+impl TaskEvent {
+    /// SSE `data` for a single-task stream: strings are sent raw, `null` as
+    /// an empty payload, and everything else as JSON.
+    pub fn payload(&self) -> String {
+        match &self.data {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// SSE `data` for the all-tasks firehose, tagged with the task id.
+    pub fn firehose_payload(&self) -> String {
+        serde_json::json!({ "task_id": self.task_id, "data": self.data }).to_string()
+    }
+
+    /// Whether this is the last event a task will publish.
+    pub fn is_done(&self) -> bool {
+        self.event == "done"
+    }
+}
+
+/// Subscription handle plus the buffered events a client missed.
+pub struct Subscription {
+    pub receiver: broadcast::Receiver<TaskEvent>,
+    /// Sequence number of the newest event published before subscribing.
+    pub head: u64,
+    /// Buffered events after the requested id, or `None` when no id was given
+    /// or the buffer no longer reaches back that far.
+    pub replay: Option<Vec<TaskEvent>>,
+}
+
+struct Buffer {
+    events: VecDeque<TaskEvent>,
+    last_seq: u64,
+}
+
+/// Broadcast channel plus replay buffer shared by all tasks.
+pub struct TaskEventBus {
+    sender: broadcast::Sender<TaskEvent>,
+    buffer: Mutex<Buffer>,
+    capacity: usize,
+}
+
+impl Default for TaskEventBus {
+    fn default() -> Self {
+        Self::new(REPLAY_CAPACITY)
+    }
+}
+
+impl TaskEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            buffer: Mutex::new(Buffer {
+                events: VecDeque::with_capacity(capacity),
+                last_seq: 0,
+            }),
+            capacity,
+        }
+    }
+
+    /// Publish an event and return its sequence number.
+    pub fn publish(&self, task_id: &str, event: &str, data: Value) -> u64 {
+        let mut buffer = self.buffer.lock().expect("mutex poisoned");
+        buffer.last_seq += 1;
+        let event = TaskEvent {
+            seq: buffer.last_seq,
+            task_id: task_id.to_string(),
+            event: event.to_string(),
+            data,
+        };
+        if buffer.events.len() >= self.capacity {
+            buffer.events.pop_front();
+        }
+        buffer.events.push_back(event.clone());
+        // Sent under the buffer lock so receivers observe sequence order.
+        let _ = self.sender.send(event);
+        buffer.last_seq
+    }
+
+    /// Subscribe to live events, atomically collecting the buffered events
+    /// after `last_event_id` (for one task, or all tasks when `task_id` is
+    /// `None`).
+    pub fn subscribe(&self, last_event_id: Option<u64>, task_id: Option<&str>) -> Subscription {
+        let buffer = self.buffer.lock().expect("mutex poisoned");
+        let receiver = self.sender.subscribe();
+        let replay = last_event_id.and_then(|after| collect_after(&buffer, after, task_id));
+        Subscription {
+            receiver,
+            head: buffer.last_seq,
+            replay,
+        }
+    }
+
+    /// Buffered events after `after`, or `None` if some have been evicted.
+    pub fn replay_since(&self, after: u64, task_id: Option<&str>) -> Option<Vec<TaskEvent>> {
+        let buffer = self.buffer.lock().expect("mutex poisoned");
+        collect_after(&buffer, after, task_id)
+    }
+}
+
+fn collect_after(buffer: &Buffer, after: u64, task_id: Option<&str>) -> Option<Vec<TaskEvent>> {
+    if after > buffer.last_seq {
+        return None;
+    }
+    // A gap means events the client never saw were evicted.
+    let oldest = buffer.events.front().map_or(buffer.last_seq + 1, |e| e.seq);
+    if oldest > after + 1 {
+        return None;
+    }
+    Some(
+        buffer
+            .events
+            .iter()
+            .filter(|e| e.seq > after && task_id.is_none_or(|id| e.task_id == id))
+            .cloned()
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_assigns_increasing_sequence() {
+        let bus = TaskEventBus::new(8);
+        assert_eq!(bus.publish("a", "queued", Value::Null), 1);
+        assert_eq!(bus.publish("b", "queued", Value::Null), 2);
+        let sub = bus.subscribe(None, None);
+        assert_eq!(sub.head, 2);
+        assert!(sub.replay.is_none());
+    }
+
+    #[test]
+    fn test_replay_filters_by_task_and_id() {
+        let bus = TaskEventBus::new(8);
+        bus.publish("a", "queued", Value::Null);
+        bus.publish("b", "queued", Value::Null);
+        bus.publish("a", "started", Value::Null);
+        bus.publish("a", "done", Value::Null);
+
+        let events = bus.replay_since(1, Some("a")).unwrap();
+        let names: Vec<&str> = events.iter().map(|e| e.event.as_str()).collect();
+        assert_eq!(names, vec!["started", "done"]);
+        assert_eq!(bus.replay_since(0, None).unwrap().len(), 4);
+        assert!(bus.replay_since(4, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replay_reports_gap_after_eviction() {
+        let bus = TaskEventBus::new(2);
+        for _ in 0..4 {
+            bus.publish("a", "progress", Value::Null);
+        }
+        // Events 1 and 2 were evicted; resuming after 2 is still gap-free.
+        assert!(bus.replay_since(1, None).is_none());
+        assert_eq!(bus.replay_since(2, None).unwrap().len(), 2);
+        // An id from the future (e.g. a previous process) cannot be resumed.
+        assert!(bus.replay_since(99, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_receives_live_events() {
+        let bus = TaskEventBus::new(8);
+        let mut sub = bus.subscribe(Some(0), Some("a"));
+        assert!(sub.replay.unwrap().is_empty());
+        bus.publish("a", "result", serde_json::json!({"ok": true}));
+        let event = sub.receiver.recv().await.unwrap();
+        assert_eq!(event.seq, 1);
+        assert_eq!(event.payload(), r#"{"ok":true}"#);
+        let tagged: Value = serde_json::from_str(&event.firehose_payload()).unwrap();
+        assert_eq!(tagged["task_id"], "a");
+        assert_eq!(tagged["data"]["ok"], true);
+    }
+}
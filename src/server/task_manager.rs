@@ -2,15 +2,30 @@
 //!
 //! Manages task lifecycle: Queued → Running → Completed/Failed/Cancelled.
 //! Uses a semaphore to limit concurrent executions and `DashMap` for concurrent access.
+//!
+//! With a [`SqliteTaskStore`] attached, every transition is mirrored to the
+//! database so queued tasks can be resumed after a restart, tasks that were
+//! running are marked Interrupted (or re-queued), and history outlives the
+//! in-memory cleanup window.
+//!
+//! Transitions, throttled progress and intermediate results are also
+//! published on a [`TaskEventBus`] that backs the SSE task streams.
 
 use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use dashmap::DashMap;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_util::sync::CancellationToken;
 
+use crate::data::task_store::{SqliteTaskStore, TaskRow};
+use crate::server::task_events::TaskEventBus;
+
+/// Minimum spacing between `progress` events for one task.
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
 // ── Enums ────────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
@@ -23,6 +38,29 @@ pub enum TaskKind {
     Workflow,
 }
 
+impl TaskKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Sweep => "sweep",
+            Self::WalkForward => "walk_forward",
+            Self::Pipeline => "pipeline",
+            Self::Workflow => "workflow",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "single" => Some(Self::Single),
+            "sweep" => Some(Self::Sweep),
+            "walk_forward" => Some(Self::WalkForward),
+            "pipeline" => Some(Self::Pipeline),
+            "workflow" => Some(Self::Workflow),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
@@ -31,6 +69,8 @@ pub enum TaskStatus {
     Completed,
     Failed,
     Cancelled,
+    /// Was running when the server stopped and was not re-queued.
+    Interrupted,
 }
 
 impl TaskStatus {
@@ -40,12 +80,39 @@ impl TaskStatus {
             1 => Self::Running,
             2 => Self::Completed,
             4 => Self::Cancelled,
+            5 => Self::Interrupted,
             _ => Self::Failed, // covers 3 (Failed) and any other invalid values
         }
     }
 
     pub fn is_terminal(self) -> bool {
-        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+        matches!(
+            self,
+            Self::Completed | Self::Failed | Self::Cancelled | Self::Interrupted
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(Self::Queued),
+            "running" => Some(Self::Running),
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            "cancelled" => Some(Self::Cancelled),
+            "interrupted" => Some(Self::Interrupted),
+            _ => None,
+        }
     }
 }
 
@@ -74,9 +141,47 @@ pub struct TaskInfo {
     pub cancellation_token: CancellationToken,
     status: AtomicU8,
     pub mutable: Mutex<TaskMutable>,
+    events: Arc<TaskEventBus>,
+    last_progress_event: Mutex<Option<Instant>>,
 }
 
 impl TaskInfo {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        id: String,
+        kind: TaskKind,
+        strategy: String,
+        symbol: String,
+        thread_id: Option<String>,
+        params: serde_json::Value,
+        created_at: chrono::DateTime<Utc>,
+        events: Arc<TaskEventBus>,
+    ) -> Self {
+        Self {
+            id,
+            kind,
+            strategy,
+            symbol,
+            thread_id,
+            params,
+            created_at,
+            progress_current: AtomicUsize::new(0),
+            progress_total: AtomicUsize::new(0),
+            stage_label: Mutex::new(String::new()),
+            cancellation_token: CancellationToken::new(),
+            status: AtomicU8::new(TaskStatus::Queued as u8),
+            mutable: Mutex::new(TaskMutable {
+                started_at: None,
+                completed_at: None,
+                result: None,
+                error: None,
+                result_id: None,
+            }),
+            events,
+            last_progress_event: Mutex::new(None),
+        }
+    }
+
     pub fn status(&self) -> TaskStatus {
         TaskStatus::from_u8(self.status.load(Ordering::Acquire))
     }
@@ -84,6 +189,60 @@ impl TaskInfo {
     fn set_status(&self, s: TaskStatus) {
         self.status.store(s as u8, Ordering::Release);
     }
+
+    /// Publish an event for this task on the shared bus.
+    pub fn emit(&self, event: &str, data: serde_json::Value) {
+        self.events.publish(&self.id, event, data);
+    }
+
+    /// Update the progress counters, publishing a `progress` event at most
+    /// every [`PROGRESS_EVENT_INTERVAL`] (and always on the final step).
+    pub fn report_progress(&self, current: usize, total: usize) {
+        self.progress_current.store(current, Ordering::Relaxed);
+        self.progress_total.store(total, Ordering::Relaxed);
+        let due = {
+            let Ok(mut last) = self.last_progress_event.lock() else {
+                return;
+            };
+            let due =
+                current >= total || last.is_none_or(|at| at.elapsed() >= PROGRESS_EVENT_INTERVAL);
+            if due {
+                *last = Some(Instant::now());
+            }
+            due
+        };
+        if due {
+            self.emit_progress();
+        }
+    }
+
+    /// Enter a pipeline stage, optionally resetting the bar-level progress.
+    pub fn set_stage(&self, label: &str, reset_progress: bool) {
+        if let Ok(mut stage) = self.stage_label.lock() {
+            *stage = label.to_string();
+        }
+        if reset_progress {
+            self.progress_current.store(0, Ordering::Relaxed);
+            self.progress_total.store(0, Ordering::Relaxed);
+        }
+        self.emit_progress();
+    }
+
+    fn emit_progress(&self) {
+        let label = self
+            .stage_label
+            .lock()
+            .map(|l| l.clone())
+            .unwrap_or_default();
+        self.emit(
+            "progress",
+            serde_json::json!({
+                "current": self.progress_current.load(Ordering::Relaxed),
+                "total": self.progress_total.load(Ordering::Relaxed),
+                "stage_label": label,
+            }),
+        );
+    }
 }
 
 // ── TaskManager ───────────────────────────────────────────────────────────────
@@ -92,6 +251,9 @@ pub struct TaskManager {
     tasks: DashMap<String, Arc<TaskInfo>>,
     semaphore: Arc<Semaphore>,
     max_concurrent: usize,
+    /// Optional persistence; `None` keeps tasks in memory only.
+    store: Option<SqliteTaskStore>,
+    events: Arc<TaskEventBus>,
 }
 
 impl TaskManager {
@@ -101,9 +263,18 @@ impl TaskManager {
             tasks: DashMap::new(),
             semaphore: Arc::new(Semaphore::new(max_concurrent)),
             max_concurrent,
+            store: None,
+            events: Arc::new(TaskEventBus::default()),
         }
     }
 
+    /// Mirror task state to `store` so it survives restarts.
+    #[must_use]
+    pub fn with_store(mut self, store: SqliteTaskStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
     /// Register a new task in the Queued state and return its `Arc<TaskInfo>`.
     pub fn register(
         &self,
@@ -112,30 +283,69 @@ impl TaskManager {
         symbol: impl Into<String>,
         thread_id: Option<String>,
         params: serde_json::Value,
+    ) -> Arc<TaskInfo> {
+        self.insert(kind, strategy, symbol, thread_id, params, None)
+    }
+
+    /// Register a task together with the submit request needed to replay it,
+    /// so a queued (or re-queued) task can be resumed after a restart.
+    pub fn register_resumable(
+        &self,
+        kind: TaskKind,
+        strategy: impl Into<String>,
+        symbol: impl Into<String>,
+        thread_id: Option<String>,
+        params: serde_json::Value,
+        request: serde_json::Value,
+    ) -> Arc<TaskInfo> {
+        self.insert(kind, strategy, symbol, thread_id, params, Some(request))
+    }
+
+    fn insert(
+        &self,
+        kind: TaskKind,
+        strategy: impl Into<String>,
+        symbol: impl Into<String>,
+        thread_id: Option<String>,
+        params: serde_json::Value,
+        request: Option<serde_json::Value>,
     ) -> Arc<TaskInfo> {
         let id = uuid::Uuid::new_v4().to_string();
-        let task = Arc::new(TaskInfo {
-            id: id.clone(),
+        let task = Arc::new(TaskInfo::new(
+            id.clone(),
             kind,
-            strategy: strategy.into(),
-            symbol: symbol.into(),
+            strategy.into(),
+            symbol.into(),
             thread_id,
             params,
-            created_at: Utc::now(),
-            progress_current: AtomicUsize::new(0),
-            progress_total: AtomicUsize::new(0),
-            stage_label: Mutex::new(String::new()),
-            cancellation_token: CancellationToken::new(),
-            status: AtomicU8::new(TaskStatus::Queued as u8),
-            mutable: Mutex::new(TaskMutable {
+            Utc::now(),
+            Arc::clone(&self.events),
+        ));
+        if let Some(store) = &self.store {
+            let row = TaskRow {
+                id: task.id.clone(),
+                kind: kind.as_str().to_string(),
+                strategy: task.strategy.clone(),
+                symbol: task.symbol.clone(),
+                thread_id: task.thread_id.clone(),
+                params: task.params.clone(),
+                request,
+                status: TaskStatus::Queued.as_str().to_string(),
+                progress_current: 0,
+                progress_total: 0,
+                stage_label: String::new(),
+                created_at: task.created_at.to_rfc3339(),
                 started_at: None,
                 completed_at: None,
                 result: None,
-                error: None,
                 result_id: None,
-            }),
-        });
+                error: None,
+            };
+            log_store_error(&task.id, store.insert_task(&row));
+        }
         self.tasks.insert(id, Arc::clone(&task));
+        let position = self.queue_position(&task.id);
+        task.emit("queued", serde_json::json!({ "position": position }));
         task
     }
 
@@ -151,10 +361,16 @@ impl TaskManager {
     pub fn mark_running(&self, task_id: &str) {
         if let Some(task) = self.tasks.get(task_id) {
             task.set_status(TaskStatus::Running);
+            let now = Utc::now();
             if let Ok(mut m) = task.mutable.lock() {
-                m.started_at = Some(Utc::now());
+                m.started_at = Some(now);
             }
+            if let Some(store) = &self.store {
+                log_store_error(task_id, store.mark_started(task_id, &now.to_rfc3339()));
+            }
+            task.emit("started", serde_json::Value::Null);
         }
+        self.publish_queue_positions();
     }
 
     /// Transition a task to Completed, storing the result and `result_id`.
@@ -162,10 +378,13 @@ impl TaskManager {
         if let Some(task) = self.tasks.get(task_id) {
             task.set_status(TaskStatus::Completed);
             if let Ok(mut m) = task.mutable.lock() {
-                m.result = Some(result);
+                m.result = Some(result.clone());
                 m.result_id = Some(result_id);
                 m.completed_at = Some(Utc::now());
             }
+            self.persist_terminal(&task);
+            task.emit("result", result);
+            task.emit("done", serde_json::Value::Null);
         }
     }
 
@@ -174,9 +393,12 @@ impl TaskManager {
         if let Some(task) = self.tasks.get(task_id) {
             task.set_status(TaskStatus::Failed);
             if let Ok(mut m) = task.mutable.lock() {
-                m.error = Some(error);
+                m.error = Some(error.clone());
                 m.completed_at = Some(Utc::now());
             }
+            self.persist_terminal(&task);
+            task.emit("error", serde_json::Value::String(error));
+            task.emit("done", serde_json::Value::Null);
         }
     }
 
@@ -188,6 +410,8 @@ impl TaskManager {
                 if let Ok(mut m) = task.mutable.lock() {
                     m.completed_at = Some(Utc::now());
                 }
+                self.persist_terminal(&task);
+                emit_cancelled(&task);
             }
         }
     }
@@ -205,12 +429,21 @@ impl TaskManager {
             if let Ok(mut m) = task.mutable.lock() {
                 m.completed_at = Some(Utc::now());
             }
+            self.persist_terminal(&task);
+            emit_cancelled(&task);
+            drop(task);
+            self.publish_queue_positions();
             true
         } else {
             false
         }
     }
 
+    /// The event bus carrying every task's stream events.
+    pub fn events(&self) -> &Arc<TaskEventBus> {
+        &self.events
+    }
+
     /// Retrieve a task by ID.
     pub fn get(&self, task_id: &str) -> Option<Arc<TaskInfo>> {
         self.tasks.get(task_id).map(|e| Arc::clone(e.value()))
@@ -249,6 +482,8 @@ impl TaskManager {
     }
 
     /// Remove terminal tasks whose `completed_at` is older than `max_age`.
+    ///
+    /// Only the in-memory entry is evicted; persisted history is kept.
     pub fn cleanup(&self, max_age: chrono::Duration) {
         let cutoff = Utc::now() - max_age;
         self.tasks.retain(|_, task| {
@@ -268,10 +503,158 @@ impl TaskManager {
         });
     }
 
+    /// Write the progress counters and stage label of running tasks to the store.
+    pub fn flush_progress(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        for entry in &self.tasks {
+            let task = entry.value();
+            if task.status() != TaskStatus::Running {
+                continue;
+            }
+            let (current, total) = progress(task);
+            let label = task
+                .stage_label
+                .lock()
+                .map(|l| l.clone())
+                .unwrap_or_default();
+            log_store_error(
+                &task.id,
+                store.update_progress(&task.id, current, total, &label),
+            );
+        }
+    }
+
+    /// Reconcile the store after a restart and reload the queue.
+    ///
+    /// Tasks that were running are marked Interrupted, or re-queued when
+    /// `requeue_running` is set. Returns every queued task that has a stored
+    /// request, in submission order, paired with that request so the caller
+    /// can re-dispatch it.
+    pub fn recover(&self, requeue_running: bool) -> Vec<(Arc<TaskInfo>, serde_json::Value)> {
+        let Some(store) = &self.store else {
+            return Vec::new();
+        };
+        match store.recover_after_restart(requeue_running, &Utc::now().to_rfc3339()) {
+            Ok(0) => {}
+            Ok(n) => tracing::warn!("Marked {n} task(s) interrupted by the previous shutdown"),
+            Err(e) => tracing::warn!("Failed to recover interrupted tasks: {e}"),
+        }
+        let rows = match store.list_by_status(TaskStatus::Queued.as_str()) {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!("Failed to load queued tasks: {e}");
+                return Vec::new();
+            }
+        };
+        let mut resumed = Vec::new();
+        for row in rows {
+            let (Some(kind), Some(request)) = (TaskKind::parse(&row.kind), row.request) else {
+                continue;
+            };
+            let created_at = chrono::DateTime::parse_from_rfc3339(&row.created_at)
+                .map_or_else(|_| Utc::now(), |t| t.with_timezone(&Utc));
+            let task = Arc::new(TaskInfo::new(
+                row.id.clone(),
+                kind,
+                row.strategy,
+                row.symbol,
+                row.thread_id,
+                row.params,
+                created_at,
+                Arc::clone(&self.events),
+            ));
+            self.tasks.insert(row.id, Arc::clone(&task));
+            resumed.push((task, request));
+        }
+        resumed
+    }
+
+    /// Look up a persisted task, including ones evicted from memory.
+    pub fn get_persisted(&self, task_id: &str) -> Option<TaskRow> {
+        let store = self.store.as_ref()?;
+        store.get_task(task_id).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load task {task_id}: {e}");
+            None
+        })
+    }
+
+    /// List the most recent persisted tasks, newest first. Empty without a store.
+    pub fn history(&self, limit: usize) -> Vec<TaskRow> {
+        let Some(store) = &self.store else {
+            return Vec::new();
+        };
+        store.list_tasks(limit).unwrap_or_else(|e| {
+            tracing::warn!("Failed to list task history: {e}");
+            Vec::new()
+        })
+    }
+
     /// Return the configured concurrency limit.
     pub fn max_concurrent(&self) -> usize {
         self.max_concurrent
     }
+
+    /// Publish the current position of every queued task.
+    fn publish_queue_positions(&self) {
+        let mut queued: Vec<Arc<TaskInfo>> = self
+            .tasks
+            .iter()
+            .filter(|e| e.value().status() == TaskStatus::Queued)
+            .map(|e| Arc::clone(e.value()))
+            .collect();
+        queued.sort_by_key(|t| t.created_at);
+        for (idx, task) in queued.iter().enumerate() {
+            task.emit("queued", serde_json::json!({ "position": idx + 1 }));
+        }
+    }
+
+    fn persist_terminal(&self, task: &TaskInfo) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let (current, total) = progress(task);
+        let Ok(m) = task.mutable.lock() else {
+            return;
+        };
+        let completed_at = m.completed_at.unwrap_or_else(Utc::now).to_rfc3339();
+        log_store_error(
+            &task.id,
+            store.finish_task(
+                &task.id,
+                task.status().as_str(),
+                &completed_at,
+                current,
+                total,
+                m.result.as_ref(),
+                m.result_id.as_deref(),
+                m.error.as_deref(),
+            ),
+        );
+    }
+}
+
+fn emit_cancelled(task: &TaskInfo) {
+    task.emit("cancelled", serde_json::Value::Null);
+    task.emit("done", serde_json::Value::Null);
+}
+
+fn progress(task: &TaskInfo) -> (i64, i64) {
+    let current = task.progress_current.load(Ordering::Relaxed);
+    let total = task.progress_total.load(Ordering::Relaxed);
+    (
+        i64::try_from(current).unwrap_or(i64::MAX),
+        i64::try_from(total).unwrap_or(i64::MAX),
+    )
+}
+
+/// Persistence is best-effort: a failed write is logged and the in-memory
+/// task keeps running.
+fn log_store_error(task_id: &str, result: anyhow::Result<()>) {
+    if let Err(e) = result {
+        tracing::warn!("Failed to persist task {task_id}: {e}");
+    }
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -441,4 +824,93 @@ mod tests {
         mgr.mark_cancelled(&task.id); // should not panic
         assert_eq!(task.status(), TaskStatus::Cancelled);
     }
+
+    fn persistent_manager(db: &crate::data::database::Database) -> TaskManager {
+        TaskManager::new(1).with_store(db.tasks())
+    }
+
+    // 11. persisted tasks outlive in-memory cleanup
+    #[test]
+    fn test_history_survives_cleanup() {
+        let db = crate::data::database::Database::open_in_memory().unwrap();
+        let mgr = persistent_manager(&db);
+        let task = register_task(&mgr);
+        mgr.mark_running(&task.id);
+        task.progress_current.store(3, Ordering::Relaxed);
+        task.progress_total.store(4, Ordering::Relaxed);
+        mgr.flush_progress();
+        assert_eq!(mgr.get_persisted(&task.id).unwrap().progress_current, 3);
+
+        mgr.mark_completed(&task.id, serde_json::json!({"ok": true}), "rid".to_string());
+        mgr.cleanup(chrono::Duration::zero());
+
+        assert!(mgr.get(&task.id).is_none());
+        let row = mgr.get_persisted(&task.id).expect("persisted");
+        assert_eq!(row.status, "completed");
+        assert_eq!(row.result_id.as_deref(), Some("rid"));
+        assert_eq!(mgr.history(10).len(), 1);
+    }
+
+    // 12. restart resumes queued tasks and interrupts running ones
+    #[test]
+    fn test_recover_after_restart() {
+        let db = crate::data::database::Database::open_in_memory().unwrap();
+        let before = persistent_manager(&db);
+        let running = before.register_resumable(
+            TaskKind::Sweep,
+            "s",
+            "SPY",
+            None,
+            serde_json::json!({}),
+            serde_json::json!({"type": "sweep"}),
+        );
+        before.mark_running(&running.id);
+        std::thread::sleep(Duration::from_millis(2));
+        let queued = before.register_resumable(
+            TaskKind::Single,
+            "s",
+            "SPY",
+            None,
+            serde_json::json!({}),
+            serde_json::json!({"type": "backtest"}),
+        );
+        // Tasks without a stored request cannot be replayed.
+        let bare = register_task(&before);
+        drop(before);
+
+        let after = persistent_manager(&db);
+        let resumed = after.recover(false);
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].0.id, queued.id);
+        assert_eq!(resumed[0].1["type"], "backtest");
+        assert_eq!(after.queue_position(&queued.id), Some(1));
+        assert_eq!(
+            after.get_persisted(&running.id).unwrap().status,
+            "interrupted"
+        );
+        assert_eq!(after.get_persisted(&bare.id).unwrap().status, "interrupted");
+    }
+
+    // 13. re-queue flag replays tasks that were running
+    #[test]
+    fn test_recover_requeues_running_tasks() {
+        let db = crate::data::database::Database::open_in_memory().unwrap();
+        let before = persistent_manager(&db);
+        let running = before.register_resumable(
+            TaskKind::Sweep,
+            "s",
+            "SPY",
+            None,
+            serde_json::json!({}),
+            serde_json::json!({"type": "sweep"}),
+        );
+        before.mark_running(&running.id);
+        drop(before);
+
+        let after = persistent_manager(&db);
+        let resumed = after.recover(true);
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].0.id, running.id);
+        assert_eq!(resumed[0].0.status(), TaskStatus::Queued);
+    }
 }
@@ -47,7 +47,7 @@ pub fn format_aggregate_prices(
         }
     } else {
         // Non-return metrics (volume, range): omit significance language
-        format!("Aggregated {metric} for {upper} by {group_by} across {total_bars} bars.",)
+        format!("Aggregated {metric} for {upper} by {group_by} across {total_bars} bars.")
     };
 
     let mut key_findings = Vec::new();
@@ -511,6 +511,8 @@ pub fn format_regime_detect(
         regimes,
         transition_matrix,
         regime_series,
+        features: vec![],
+        model_selection: vec![],
         key_findings,
         suggested_next_steps,
     }
@@ -43,7 +43,9 @@ pub struct BacktestToolParams {
     #[garde(length(min = 1))]
     pub strategy: String,
 
-    /// Sweep mode: `"grid"` (exhaustive) or `"bayesian"` (adaptive). Default `"grid"`.
+    /// Sweep mode: `"grid"` (exhaustive), `"bayesian"` (adaptive) or `"pareto"`
+    /// (multi-objective; exhaustive when the grid fits in `max_evaluations`,
+    /// NSGA-II search otherwise). Default `"grid"`.
     #[serde(default = "default_mode")]
     #[garde(skip)]
     pub mode: String,
@@ -53,23 +55,41 @@ pub struct BacktestToolParams {
     #[garde(skip)]
     pub objective: String,
 
+    /// Objectives for `mode = "pareto"`: metric names, optionally prefixed with
+    /// `max:` or `min:` (e.g. `["cagr", "max_drawdown", "cvar_95"]`).
+    /// `max_drawdown` and `cvar_95` are minimized by default, others maximized.
+    /// `objective` is still used for sensitivity and downstream pipeline stages.
+    #[serde(default)]
+    #[garde(skip)]
+    pub objectives: Vec<String>,
+
+    /// Seed for the NSGA-II search in `mode = "pareto"`, so reruns explore the
+    /// same combos. Default 42.
+    #[serde(default)]
+    #[garde(skip)]
+    pub seed: Option<u64>,
+
     /// Base parameters injected into the script (e.g. `SYMBOL`, `CAPITAL`).
     #[serde(default)]
     #[garde(skip)]
     pub params: HashMap<String, Value>,
 
-    /// Parameter ranges to sweep. Omit for a single backtest.
+    /// Parameter ranges to sweep. Omit for a single backtest. Categorical params
+    /// list `choices` instead of a range; `when` makes a param conditional on a
+    /// parent choice (bayesian mode).
     #[serde(default)]
     #[garde(skip)]
     pub sweep_params: Vec<sweeps::SweepParamDef>,
 
-    /// Maximum evaluations for bayesian mode. Default 50.
+    /// Maximum evaluations for bayesian and pareto modes. Default 50.
     #[serde(default = "default_max_evaluations")]
     #[garde(skip)]
     pub max_evaluations: usize,
 
     /// Number of permutations for statistical significance testing. Default 0 (off).
-    /// When > 0, runs sign-flip permutation test and applies BH-FDR correction.
+    /// When > 0, runs sign-flip permutation test and applies BH-FDR correction,
+    /// plus a stationary-bootstrap Reality Check / SPA test (same number of
+    /// replications, capped at 10,000) of whether the best combo beats cash.
     /// Values above 100,000 are rejected by input validation.
     #[serde(default)]
     #[garde(range(max = 100_000))]
@@ -180,6 +200,8 @@ fn build_pipeline_request(params: BacktestToolParams) -> pipeline::PipelineReque
         strategy,
         mode,
         objective,
+        objectives,
+        seed,
         params,
         sweep_params,
         max_evaluations,
@@ -192,6 +214,8 @@ fn build_pipeline_request(params: BacktestToolParams) -> pipeline::PipelineReque
         strategy,
         mode,
         objective,
+        objectives,
+        seed,
         params,
         sweep_params,
         max_evaluations,
@@ -275,6 +299,8 @@ async fn execute_sweep_raw(
         strategy: params.strategy.clone(),
         mode: params.mode.clone(),
         objective: params.objective.clone(),
+        objectives: params.objectives.clone(),
+        seed: params.seed,
         params: params.params.clone(),
         sweep_params: params.sweep_params.clone(),
         max_evaluations: params.max_evaluations,
@@ -289,6 +315,7 @@ async fn execute_sweep_raw(
         params.thread_id.as_deref(),
         None,
         None,
+        None,
     )
     .await?;
 
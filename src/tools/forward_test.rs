@@ -30,6 +30,7 @@ use crate::tools::response_types::forward_test::{
 pub struct StartParams<'a> {
     pub store: &'a SqliteForwardTestStore,
     pub strategy_store: Option<&'a dyn crate::data::traits::StrategyStore>,
+    pub signal_store: Option<&'a crate::data::signal_store::SqliteSignalStore>,
     pub strategy: &'a str,
     pub symbol: &'a str,
     pub capital: f64,
@@ -54,7 +55,7 @@ pub fn start(p: &StartParams<'_>) -> Result<StartForwardTestResponse> {
         params: p.params.clone(),
         profile: None,
     };
-    crate::tools::run_script::resolve_script_source(&run_params, p.strategy_store)?;
+    crate::tools::run_script::resolve_script_source(&run_params, p.strategy_store, p.signal_store)?;
 
     let now = Utc::now().to_rfc3339();
     let session_id = uuid::Uuid::new_v4().to_string();
@@ -142,6 +143,7 @@ pub fn start(p: &StartParams<'_>) -> Result<StartForwardTestResponse> {
 pub async fn step(
     store: &SqliteForwardTestStore,
     strategy_store: Option<&dyn crate::data::traits::StrategyStore>,
+    signal_store: Option<&crate::data::signal_store::SqliteSignalStore>,
     cache: &Arc<CachedStore>,
     adjustment_store: Option<Arc<crate::data::adjustment_store::SqliteAdjustmentStore>>,
     session_id: &str,
@@ -168,7 +170,7 @@ pub async fn step(
         profile: None,
     };
     let (_resolved_id, source) =
-        crate::tools::run_script::resolve_script_source(&run_params, strategy_store)?;
+        crate::tools::run_script::resolve_script_source(&run_params, strategy_store, signal_store)?;
 
     let loader = CachingDataLoader::new(Arc::clone(cache), adjustment_store);
     let no_cancel: CancelCallback = Box::new(|| false);
@@ -7,8 +7,11 @@ use std::sync::Arc;
 
 use crate::constants::TRADING_DAYS_PER_YEAR;
 use crate::data::cache::CachedStore;
-use crate::engine::hypothesis::{generate_hypotheses, HypothesisConfig};
-use crate::engine::types::HypothesisDimension;
+use crate::data::DataStore;
+use crate::engine::greeks::GreeksParams;
+use crate::engine::hypothesis::{generate_hypotheses, HypothesisConfig, OptionsStructureSeries};
+use crate::engine::types::{ExpirationFilter, HypothesisDimension};
+use crate::scripting::options_cache::DatePartitionedOptions;
 use crate::tools::ai_format;
 use crate::tools::ai_helpers;
 use crate::tools::response_types::{HypothesisParams, HypothesisResponse, PriceBar};
@@ -30,8 +33,9 @@ pub async fn execute(
 
     // Determine which dimensions to scan.
     // When dimensions is None, default to OHLCV-only dimensions plus CrossAsset
-    // when multiple symbols are provided.
-    let dimensions: Vec<HypothesisDimension> = params.dimensions.clone().unwrap_or_else(|| {
+    // when multiple symbols are provided (OptionsStructure joins below once an
+    // options chain is found).
+    let mut dimensions: Vec<HypothesisDimension> = params.dimensions.clone().unwrap_or_else(|| {
         let mut dims = HypothesisDimension::ohlcv_dimensions().to_vec();
         if params.symbols.len() > 1 {
             dims.push(HypothesisDimension::CrossAsset);
@@ -93,12 +97,30 @@ pub async fn execute(
         None
     };
 
+    // ATM implied volatility and options-structure features from the options
+    // chain, when one is cached
+    let wants_structure =
+        params.dimensions.is_none() || dimensions.contains(&HypothesisDimension::OptionsStructure);
+    let options_features =
+        if wants_structure || dimensions.contains(&HypothesisDimension::VolatilityRegime) {
+            load_options_features(cache, &primary_symbol, primary_prices, wants_structure).await
+        } else {
+            None
+        };
+    let (atm_iv, options_structure) = options_features.unzip();
+    let options_structure = options_structure.flatten();
+    if params.dimensions.is_none() && options_structure.is_some() {
+        dimensions.push(HypothesisDimension::OptionsStructure);
+    }
+
     // Run the hypothesis engine
     let (total_trials, patterns_tested, patterns_significant, hypotheses) = generate_hypotheses(
         primary_prices,
         &config,
         &dimensions,
         regime_labels.as_deref(),
+        atm_iv.as_deref(),
+        options_structure.as_ref(),
         &cross_asset_prices,
     );
 
@@ -112,6 +134,40 @@ pub async fn execute(
     ))
 }
 
+/// Load the symbol's options chain and build the 30-day ATM IV series aligned
+/// to `prices`, plus the options-structure features when `with_structure` is
+/// set. Returns `None` when no chain is available.
+async fn load_options_features(
+    cache: &CachedStore,
+    symbol: &str,
+    prices: &[PriceBar],
+    with_structure: bool,
+) -> Option<(Vec<f64>, Option<OptionsStructureSeries>)> {
+    let dates: Vec<chrono::NaiveDate> = prices
+        .iter()
+        .filter_map(|p| chrono::DateTime::from_timestamp(p.date, 0).map(|dt| dt.date_naive()))
+        .collect();
+    if dates.len() != prices.len() {
+        return None;
+    }
+    let df = match cache
+        .load_options(symbol, dates.first().copied(), dates.last().copied())
+        .await
+    {
+        Ok(df) if df.height() > 0 => df,
+        Ok(_) => return None,
+        Err(e) => {
+            tracing::info!(symbol, error = %e, "No options data — skipping options hypotheses");
+            return None;
+        }
+    };
+    let chain =
+        DatePartitionedOptions::from_df(&df, &ExpirationFilter::Any, &GreeksParams::default())
+            .ok()?;
+    let structure = with_structure.then(|| chain.structure_series(&dates));
+    Some((chain.atm_iv_series(dates), structure))
+}
+
 /// Compute simple volatility-based regime labels for stability scoring.
 fn compute_regime_labels(returns: &[f64]) -> (Vec<usize>, Vec<String>, usize) {
     let n_regimes = 2;
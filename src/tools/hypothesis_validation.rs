@@ -0,0 +1,623 @@
+//! Turn the top hypotheses from `generate_hypotheses` into `.trading`
+//! strategies, store them, and run each through the baseline-validation
+//! pipeline with permutation gating.
+//!
+//! Each strategy enters when the hypothesis signal fires (long or short in the
+//! direction of its effect, or an options position when one is given) and
+//! exits after the hypothesis' forward horizon. The holding period is the
+//! only swept parameter, so the pipeline's gates judge the signal rather than
+//! a tuned parameter set.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::application::pipeline::{self, PipelineRequest};
+use crate::application::sweeps::{self, SweepParamDef};
+use crate::data::strategy_store::StrategyRow;
+use crate::scripting::dsl::signal::{signal_to_expr, SignalExpr};
+use crate::server::OptopsyServer;
+use crate::tools::response_types::pipeline::{PipelineResponse, StageInfo, StageStatus};
+use crate::tools::response_types::{
+    DiscoveredPattern, HypothesisValidationParams, HypothesisValidationResponse,
+    HypothesisValidationRow, HypothesisVerdict,
+};
+
+/// A hypothesis rendered as a stored, sweepable strategy.
+#[derive(Debug, Clone)]
+pub struct SynthesizedStrategy {
+    pub id: String,
+    pub name: String,
+    /// `"long"`, `"short"` or `"options"`.
+    pub direction: &'static str,
+    /// `.trading` DSL source.
+    pub source: String,
+    /// Holding-period sweep over the exit extern.
+    pub hold_sweep: SweepParamDef,
+    pub row: StrategyRow,
+}
+
+/// Execute the `validate_hypotheses` tool.
+#[allow(clippy::too_many_lines)]
+pub async fn execute(
+    server: &OptopsyServer,
+    params: &HypothesisValidationParams,
+) -> Result<HypothesisValidationResponse> {
+    let started_at = Instant::now();
+    let store = server.require_strategy_store()?.clone();
+    server.require_run_store()?;
+
+    let scan = crate::tools::hypothesis::execute(&server.cache, &params.scan).await?;
+    let symbol = params.scan.symbols[0].to_uppercase();
+
+    let mut rows = Vec::new();
+    for pattern in scan.hypotheses.iter().take(params.top_n) {
+        let strategy =
+            match synthesize_strategy(&symbol, pattern, params.options_expression.as_deref()) {
+                Ok(strategy) => strategy,
+                Err(reason) => {
+                    rows.push(base_row(
+                        pattern,
+                        HypothesisVerdict::Unsupported,
+                        Some(reason),
+                    ));
+                    continue;
+                }
+            };
+
+        let upsert_store = store.clone();
+        let strategy_row = strategy.row.clone();
+        tokio::task::spawn_blocking(move || upsert_store.upsert(&strategy_row)).await??;
+
+        let request = PipelineRequest {
+            strategy: strategy.id.clone(),
+            mode: "grid".to_string(),
+            objective: sweeps::default_objective(),
+            objectives: Vec::new(),
+            seed: None,
+            params: HashMap::from([
+                ("symbol".to_string(), Value::from(symbol.clone())),
+                ("CAPITAL".to_string(), Value::from(params.capital)),
+            ]),
+            sweep_params: vec![strategy.hold_sweep.clone()],
+            max_evaluations: sweeps::default_max_evaluations(),
+            num_permutations: params.num_permutations,
+            thread_id: None,
+        };
+
+        let mut row = base_row(pattern, HypothesisVerdict::Error, None);
+        row.strategy_id = Some(strategy.id.clone());
+        row.strategy_name = Some(strategy.name.clone());
+        row.direction = Some(strategy.direction.to_string());
+        match pipeline::execute(server, &request, "hypothesis").await {
+            Ok(response) => apply_pipeline(&mut row, &response),
+            Err(e) => {
+                tracing::warn!(strategy = %strategy.id, error = %e, "Hypothesis validation failed");
+                row.reason = Some(e.to_string());
+            }
+        }
+        rows.push(row);
+    }
+    rank_rows(&mut rows);
+
+    let validated = rows
+        .iter()
+        .filter(|r| r.verdict == HypothesisVerdict::Validated)
+        .count();
+    let summary = if scan.hypotheses.is_empty() {
+        format!(
+            "No hypotheses for {symbol} survived BH-FDR correction at α={} — nothing to validate.",
+            params.scan.significance
+        )
+    } else {
+        format!(
+            "Synthesized and validated the top {} of {} hypotheses for {symbol}: \
+             {validated} passed every gate with a positive out-of-sample Sharpe.",
+            rows.len(),
+            scan.hypotheses.len(),
+        )
+    };
+
+    let mut key_findings: Vec<String> = rows
+        .iter()
+        .map(|r| match r.verdict {
+            HypothesisVerdict::Unsupported => format!(
+                "#{} {}: not synthesized ({})",
+                r.rank,
+                r.description,
+                r.reason.as_deref().unwrap_or("unsupported signal")
+            ),
+            _ => format!(
+                "#{} {} [{}]: {}/{} gates passed, best Sharpe={}, OOS Sharpe={} → {:?}",
+                r.rank,
+                r.description,
+                r.direction.as_deref().unwrap_or("-"),
+                r.gates_passed,
+                r.gates_evaluated,
+                r.best_sharpe
+                    .map_or_else(|| "n/a".to_string(), |s| format!("{s:.3}")),
+                r.oos_sharpe
+                    .map_or_else(|| "n/a".to_string(), |s| format!("{s:.3}")),
+                r.verdict,
+            ),
+        })
+        .collect();
+    if validated == 0 && !rows.is_empty() {
+        key_findings.push(
+            "No hypothesis survived as a tradable strategy — the scan's statistical edge did not \
+             carry over to next-bar execution with costs"
+                .to_string(),
+        );
+    }
+
+    let suggested_next_steps = match rows
+        .iter()
+        .find(|r| r.verdict == HypothesisVerdict::Validated)
+    {
+        Some(best) => vec![
+            format!(
+                "[NEXT] Call backtest(strategy=\"{}\", params={{\"symbol\": \"{symbol}\"}}) to inspect the trade log of the top validated hypothesis",
+                best.strategy_id.as_deref().unwrap_or_default()
+            ),
+            "[THEN] Run the strategy_evaluation workflow on it for anchored vs rolling walk-forward robustness".to_string(),
+            "[TIP] The synthesized scripts are plain .trading strategies — add stops or regime filters and re-validate".to_string(),
+        ],
+        None => vec![
+            "[NEXT] Call generate_hypotheses with more years or additional forward_horizons to find sturdier patterns".to_string(),
+            "[TIP] Pass options_expression (e.g. \"short_put(0.30, 45)\") to express a volatility hypothesis through options instead of shares".to_string(),
+        ],
+    };
+
+    Ok(HypothesisValidationResponse {
+        summary,
+        symbols: scan.symbols,
+        hypotheses_found: scan.hypotheses.len(),
+        results: rows,
+        key_findings,
+        suggested_next_steps,
+        total_duration_ms: started_at.elapsed().as_millis() as u64,
+    })
+}
+
+/// Render a hypothesis as a `.trading` strategy for `symbol`.
+///
+/// The strategy enters on the signal — buying shares when the hypothesis'
+/// mean forward return is positive, shorting when negative, or opening
+/// `options_expression` when given — and exits once the forward horizon has
+/// elapsed.
+///
+/// # Errors
+///
+/// Returns a reason when the signal has no DSL equivalent or the generated
+/// script fails to transpile.
+pub fn synthesize_strategy(
+    symbol: &str,
+    pattern: &DiscoveredPattern,
+    options_expression: Option<&str>,
+) -> Result<SynthesizedStrategy, String> {
+    let signal = signal_to_expr(&pattern.signal_spec)?;
+    let direction = match options_expression {
+        Some(_) => "options",
+        None if pattern.effect_size < 0.0 => "short",
+        None => "long",
+    };
+    // Options positions only expose calendar days held, so convert the
+    // trading-day horizon.
+    let (hold_param, hold_default) = if options_expression.is_some() {
+        (
+            "HOLD_DAYS",
+            pattern.forward_horizon.saturating_mul(7).div_ceil(5),
+        )
+    } else {
+        ("HOLD_BARS", pattern.forward_horizon)
+    };
+    let hold_sweep = hold_sweep(hold_param, hold_default);
+
+    let name = quote_safe(&format!("{symbol} hypothesis: {}", pattern.description));
+    let description = quote_safe(&pattern.description);
+    let explanation = quote_safe(&pattern.structural_explanation);
+    let needs_options = signal.needs_options || options_expression.is_some();
+    let source = render_source(&StrategyTemplate {
+        name: &name,
+        description: &description,
+        explanation: &explanation,
+        dimension: &pattern.dimension,
+        symbol,
+        signal: &signal,
+        needs_options,
+        direction,
+        options_expression,
+        hold_param,
+        hold_default,
+        hold_sweep: &hold_sweep,
+    });
+    crate::scripting::dsl::transpile(&source)
+        .map_err(|e| format!("synthesized script failed to transpile: {e}"))?;
+
+    let id = strategy_id(symbol, &pattern.description);
+    let row = StrategyRow {
+        id: id.clone(),
+        name: name.clone(),
+        description: Some(description),
+        category: Some(if needs_options { "options" } else { "stock" }.to_string()),
+        hypothesis: Some(explanation),
+        tags: Some(vec!["hypothesis".to_string(), pattern.dimension.clone()]),
+        regime: None,
+        source: source.clone(),
+        created_at: String::new(),
+        updated_at: String::new(),
+    };
+    Ok(SynthesizedStrategy {
+        id,
+        name,
+        direction,
+        source,
+        hold_sweep,
+        row,
+    })
+}
+
+struct StrategyTemplate<'a> {
+    name: &'a str,
+    description: &'a str,
+    explanation: &'a str,
+    dimension: &'a str,
+    symbol: &'a str,
+    signal: &'a SignalExpr,
+    needs_options: bool,
+    direction: &'static str,
+    options_expression: Option<&'a str>,
+    hold_param: &'a str,
+    hold_default: usize,
+    hold_sweep: &'a SweepParamDef,
+}
+
+#[allow(clippy::format_push_string)]
+fn render_source(t: &StrategyTemplate<'_>) -> String {
+    let mut out = format!(
+        "# Synthesized by validate_hypotheses; enters when {formula}\n\n\
+         strategy \"{name}\"\n  capital CAPITAL\n  interval daily\n",
+        formula = t.signal.expr,
+        name = t.name,
+    );
+    if t.needs_options {
+        out.push_str("  data ohlcv, options\n");
+    }
+    out.push_str(&format!(
+        "  category {}\n  description \"{}\"\n  hypothesis \"{}\"\n  tags hypothesis, {}\n\n",
+        if t.needs_options { "options" } else { "stock" },
+        t.description,
+        t.explanation,
+        t.dimension,
+    ));
+    out.push_str(&format!("asset symbol = \"{}\"\n\n", t.symbol));
+    out.push_str(&format!(
+        "extern {} = {} \"Holding period after the signal fires\"\n\n",
+        t.hold_param, t.hold_default
+    ));
+    out.push_str(&format!(
+        "sweep quick\n  {} {} to {} step {}\n\n",
+        t.hold_param,
+        t.hold_sweep.start,
+        t.hold_sweep.stop,
+        t.hold_sweep.step.unwrap_or(1.0)
+    ));
+
+    out.push_str("on each bar\n");
+    if !t.signal.indicators.is_empty() {
+        out.push_str(&format!("  require {}\n", t.signal.indicators.join(", ")));
+    }
+    out.push_str("  skip when has positions\n");
+    if t.signal.lookback > 0 {
+        out.push_str(&format!("  skip when bar_idx < {}\n", t.signal.lookback));
+    }
+    out.push_str(&format!("\n  when {} then\n", t.signal.expr));
+    let order = match (t.direction, t.options_expression) {
+        (_, Some(expr)) => format!("open {expr}"),
+        ("short", None) => {
+            "Sell size_by_equity(1.0) shares of symbol next bar at market".to_string()
+        }
+        _ => "Buy size_by_equity(1.0) shares of symbol next bar at market".to_string(),
+    };
+    out.push_str(&format!("    {order}\n"));
+
+    let held = if t.options_expression.is_some() {
+        "pos.days_held"
+    } else {
+        "bars_since_entry"
+    };
+    out.push_str(&format!(
+        "\non exit check\n  when {held} >= {} then\n    close position \"horizon\"\n  otherwise\n    hold position\n",
+        t.hold_param
+    ));
+    out
+}
+
+/// Integer sweep of five holding periods centred on `center`.
+fn hold_sweep(name: &str, center: usize) -> SweepParamDef {
+    let step = (center / 4).max(1);
+    let start = center.saturating_sub(2 * step).max(1);
+    SweepParamDef {
+        name: name.to_string(),
+        param_type: "int".to_string(),
+        start: start as f64,
+        stop: (center + 2 * step) as f64,
+        step: Some(step as f64),
+        choices: Vec::new(),
+        when: None,
+    }
+}
+
+/// Deterministic strategy ID so re-validating a hypothesis updates its row.
+fn strategy_id(symbol: &str, description: &str) -> String {
+    let mut slug = String::new();
+    for c in description.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug: String = slug.trim_end_matches('_').chars().take(60).collect();
+    format!(
+        "hypothesis_{}_{}",
+        symbol.to_ascii_lowercase(),
+        slug.trim_end_matches('_')
+    )
+}
+
+fn quote_safe(text: &str) -> String {
+    text.replace('"', "'").replace('\n', " ")
+}
+
+fn base_row(
+    pattern: &DiscoveredPattern,
+    verdict: HypothesisVerdict,
+    reason: Option<String>,
+) -> HypothesisValidationRow {
+    HypothesisValidationRow {
+        rank: 0,
+        dimension: pattern.dimension.clone(),
+        description: pattern.description.clone(),
+        signal_spec: pattern.signal_spec.clone(),
+        forward_horizon: pattern.forward_horizon,
+        adjusted_p_value: pattern.adjusted_p_value,
+        effect_size: pattern.effect_size,
+        strategy_id: None,
+        strategy_name: None,
+        direction: None,
+        sweep_id: None,
+        best_sharpe: None,
+        permutation_p_value: None,
+        oos_sharpe: None,
+        gates_passed: 0,
+        gates_evaluated: 0,
+        verdict,
+        reason,
+    }
+}
+
+fn apply_pipeline(row: &mut HypothesisValidationRow, response: &PipelineResponse) {
+    let best = response.sweep.best_result.as_ref();
+    row.sweep_id = Some(response.sweep_id.clone());
+    row.best_sharpe = best.map(|b| b.sharpe);
+    row.permutation_p_value = best.and_then(|b| b.p_value);
+    row.oos_sharpe = response
+        .walk_forward
+        .as_ref()
+        .map(|wf| wf.stitched_metrics.sharpe);
+    let (passed, evaluated, verdict, reason) = assess_stages(&response.stages, row.oos_sharpe);
+    row.gates_passed = passed;
+    row.gates_evaluated = evaluated;
+    row.verdict = verdict;
+    row.reason = reason;
+}
+
+/// Count gate outcomes and derive a verdict from pipeline stages.
+///
+/// Returns `(gates_passed, gates_evaluated, verdict, reason)`. A hypothesis is
+/// validated only when no stage failed and walk-forward produced a positive
+/// out-of-sample Sharpe.
+pub fn assess_stages(
+    stages: &[StageInfo],
+    oos_sharpe: Option<f64>,
+) -> (usize, usize, HypothesisVerdict, Option<String>) {
+    let gates = stages.iter().filter(|s| s.name.ends_with("_gate"));
+    let passed = gates
+        .clone()
+        .filter(|s| matches!(s.status, StageStatus::Completed))
+        .count();
+    let evaluated = gates
+        .filter(|s| !matches!(s.status, StageStatus::Skipped))
+        .count();
+
+    if let Some(failed) = stages
+        .iter()
+        .find(|s| matches!(s.status, StageStatus::Failed))
+    {
+        let reason = match &failed.reason {
+            Some(reason) => format!("{}: {reason}", failed.name),
+            None => format!("{} failed", failed.name),
+        };
+        return (passed, evaluated, HypothesisVerdict::Rejected, Some(reason));
+    }
+    match oos_sharpe {
+        Some(sharpe) if sharpe > 0.0 => (passed, evaluated, HypothesisVerdict::Validated, None),
+        Some(sharpe) => (
+            passed,
+            evaluated,
+            HypothesisVerdict::Rejected,
+            Some(format!("out-of-sample Sharpe {sharpe:.3} is not positive")),
+        ),
+        None => (
+            passed,
+            evaluated,
+            HypothesisVerdict::Rejected,
+            Some("walk-forward did not run".to_string()),
+        ),
+    }
+}
+
+/// Order rows by verdict, then gates passed, then OOS and in-sample Sharpe,
+/// and number them from 1.
+pub fn rank_rows(rows: &mut [HypothesisValidationRow]) {
+    rows.sort_by(|a, b| {
+        a.verdict
+            .cmp(&b.verdict)
+            .then(b.gates_passed.cmp(&a.gates_passed))
+            .then(
+                b.oos_sharpe
+                    .unwrap_or(f64::NEG_INFINITY)
+                    .total_cmp(&a.oos_sharpe.unwrap_or(f64::NEG_INFINITY)),
+            )
+            .then(
+                b.best_sharpe
+                    .unwrap_or(f64::NEG_INFINITY)
+                    .total_cmp(&a.best_sharpe.unwrap_or(f64::NEG_INFINITY)),
+            )
+    });
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.rank = i + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::types::SignalSpec;
+
+    fn pattern(formula: &str, effect_size: f64, horizon: usize) -> DiscoveredPattern {
+        DiscoveredPattern {
+            dimension: "momentum".to_string(),
+            description: format!("Signal \"{formula}\" → {horizon}-day forward return"),
+            structural_basis: "empirical_only".to_string(),
+            structural_explanation: "Test pattern".to_string(),
+            signal_spec: SignalSpec::Formula {
+                formula: formula.to_string(),
+            },
+            forward_horizon: horizon,
+            p_value: 0.001,
+            adjusted_p_value: 0.01,
+            effect_size,
+            occurrence_count: 40,
+            sharpe: 1.2,
+            dsr: 0.9,
+            regime_stability: None,
+            cluster_id: 0,
+            sample_dates: Vec::new(),
+        }
+    }
+
+    fn stage(name: &str, status: StageStatus) -> StageInfo {
+        StageInfo {
+            name: name.to_string(),
+            status,
+            reason: None,
+            duration_ms: 0,
+            details: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn synthesizes_long_strategy_that_transpiles() {
+        let p = pattern("pct_change(close, 20) > 0.05", 0.01, 10);
+        let s = synthesize_strategy("SPY", &p, None).unwrap();
+        assert_eq!(s.direction, "long");
+        assert!(s
+            .source
+            .contains("when (close / close[20] - 1) > 0.05 then"));
+        assert!(s.source.contains("skip when bar_idx < 20"));
+        assert!(s.source.contains("Buy size_by_equity(1.0) shares"));
+        assert!(s.source.contains("when bars_since_entry >= HOLD_BARS then"));
+        assert!(s
+            .id
+            .starts_with("hypothesis_spy_signal_pct_change_close_20"));
+        assert_eq!(s.row.category.as_deref(), Some("stock"));
+        assert!(!s.name.contains('"'));
+        // 10-bar horizon sweeps 6..14 in steps of 2
+        assert!((s.hold_sweep.start - 6.0).abs() < f64::EPSILON);
+        assert!((s.hold_sweep.stop - 14.0).abs() < f64::EPSILON);
+        assert_eq!(s.hold_sweep.step, Some(2.0));
+    }
+
+    #[test]
+    fn synthesizes_short_and_options_strategies() {
+        let p = pattern("zscore(close, 20) < -2", -0.004, 5);
+        let short = synthesize_strategy("SPY", &p, None).unwrap();
+        assert_eq!(short.direction, "short");
+        assert!(short.source.contains("Sell size_by_equity(1.0) shares"));
+        assert!(short
+            .source
+            .contains("require bbands_upper:20, bbands_mid:20"));
+
+        let options = synthesize_strategy("SPY", &p, Some("short_put(0.30, 45)")).unwrap();
+        assert_eq!(options.direction, "options");
+        assert!(options.source.contains("data ohlcv, options"));
+        assert!(options.source.contains("open short_put(0.30, 45)"));
+        assert!(options
+            .source
+            .contains("when pos.days_held >= HOLD_DAYS then"));
+        // 5 trading days ≈ 7 calendar days
+        assert!(options.source.contains("extern HOLD_DAYS = 7"));
+    }
+
+    #[test]
+    fn unsupported_signal_is_rejected_with_reason() {
+        let p = pattern("volume > 2 * sma(volume, 20)", 0.01, 5);
+        let err = synthesize_strategy("SPY", &p, None).unwrap_err();
+        assert!(err.contains("volume"), "{err}");
+    }
+
+    #[test]
+    fn assess_stages_derives_verdicts() {
+        let passing = vec![
+            stage("sweep", StageStatus::Completed),
+            stage("significance_gate", StageStatus::Completed),
+            stage("overfitting_gate", StageStatus::Skipped),
+            stage("walk_forward", StageStatus::Completed),
+            stage("oos_data_gate", StageStatus::Completed),
+        ];
+        let (passed, evaluated, verdict, reason) = assess_stages(&passing, Some(0.8));
+        assert_eq!((passed, evaluated), (2, 2));
+        assert_eq!(verdict, HypothesisVerdict::Validated);
+        assert!(reason.is_none());
+
+        let (_, _, verdict, reason) = assess_stages(&passing, Some(-0.2));
+        assert_eq!(verdict, HypothesisVerdict::Rejected);
+        assert!(reason.unwrap().contains("not positive"));
+
+        let mut failing = passing.clone();
+        failing[1].status = StageStatus::Failed;
+        failing[1].reason = Some("p=0.40".to_string());
+        let (passed, _, verdict, reason) = assess_stages(&failing, None);
+        assert_eq!(passed, 1);
+        assert_eq!(verdict, HypothesisVerdict::Rejected);
+        assert_eq!(reason.as_deref(), Some("significance_gate: p=0.40"));
+    }
+
+    #[test]
+    fn rank_rows_orders_by_verdict_then_gates_then_sharpe() {
+        let p = pattern("month() == 1", 0.01, 5);
+        let mut unsupported = base_row(&p, HypothesisVerdict::Unsupported, None);
+        unsupported.description = "unsupported".to_string();
+        let mut weak = base_row(&p, HypothesisVerdict::Validated, None);
+        weak.description = "weak".to_string();
+        weak.gates_passed = 2;
+        weak.oos_sharpe = Some(0.3);
+        let mut strong = base_row(&p, HypothesisVerdict::Validated, None);
+        strong.description = "strong".to_string();
+        strong.gates_passed = 2;
+        strong.oos_sharpe = Some(1.1);
+        let mut rejected = base_row(&p, HypothesisVerdict::Rejected, None);
+        rejected.description = "rejected".to_string();
+        rejected.gates_passed = 3;
+
+        let mut rows = vec![unsupported, weak, rejected, strong];
+        rank_rows(&mut rows);
+        let order: Vec<&str> = rows.iter().map(|r| r.description.as_str()).collect();
+        assert_eq!(order, ["strong", "weak", "rejected", "unsupported"]);
+        assert_eq!(rows[0].rank, 1);
+        assert_eq!(rows[3].rank, 4);
+    }
+}
@@ -12,9 +12,11 @@ pub mod drawdown_analysis;
 pub mod factor_attribution;
 pub mod forward_test;
 pub mod hypothesis;
+pub mod hypothesis_validation;
 pub mod list_symbols;
 pub mod monte_carlo;
 pub mod pipeline;
+pub mod portfolio_backtest;
 pub mod portfolio_optimize;
 pub mod raw_prices;
 pub mod regime_detect;
@@ -3,23 +3,92 @@
 //! Generates thousands of synthetic equity paths using block-bootstrapped
 //! historical returns to produce confidence intervals on terminal wealth,
 //! max drawdown distributions, and ruin probabilities.
+//!
+//! Paths are bootstrapped either from a symbol's price returns or from a
+//! backtest's own results (a stored run or an inline `BacktestResult`): its
+//! daily strategy returns (block bootstrap) or its trade P&Ls (order shuffle
+//! or resampling with replacement), optionally scaled by a position-size
+//! multiplier. Strategy simulations also report path-level risk of ruin and
+//! time-to-recover.
 
 use anyhow::Result;
 use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
 use std::sync::Arc;
 
 use crate::constants::MIN_RETURNS_FOR_BOOTSTRAP;
 use crate::data::cache::CachedStore;
+use crate::data::traits::RunDetail;
+use crate::engine::equity::{daily_returns, stored_equity_curve};
+use crate::engine::types::BacktestResult;
+use crate::server::MonteCarloMethod;
 use crate::stats;
 use crate::tools::ai_helpers;
 use crate::tools::response_types::{
-    DrawdownDistribution, HistogramBin, MonteCarloPercentilePath, MonteCarloResponse, RuinAnalysis,
+    DrawdownDistribution, HistogramBin, MonteCarloPercentilePath, MonteCarloResponse,
+    RecoveryDistribution, RuinAnalysis, StrategyRiskAnalysis,
 };
 
 /// Block size for bootstrap resampling (trading days).
 const BLOCK_SIZE: usize = 21;
 
+/// Minimum closed trades for trade-level resampling.
+const MIN_TRADES_FOR_RESAMPLING: usize = 10;
+
+/// A backtest's own results, ready for resampling.
+#[derive(Debug, Clone)]
+pub struct StrategySample {
+    /// Label used in the summary (strategy name and symbol).
+    pub label: String,
+    /// `run:<id>` or `inline`.
+    pub source: String,
+    /// Capital the backtest ran with; trade P&L is rescaled from this.
+    pub capital: f64,
+    /// Daily strategy returns (last equity point per calendar day).
+    pub daily_returns: Vec<f64>,
+    /// Realized P&L per closed trade, in entry order.
+    pub trade_pnls: Vec<f64>,
+}
+
+impl StrategySample {
+    /// Build a sample from an inline backtest result.
+    pub fn from_result(result: &BacktestResult, fallback_capital: f64) -> Self {
+        let capital = result
+            .equity_curve
+            .first()
+            .map_or(fallback_capital, |p| p.equity);
+        let mut trades: Vec<_> = result.trade_log.iter().collect();
+        trades.sort_by_key(|t| t.entry_datetime);
+        Self {
+            label: result
+                .symbol
+                .clone()
+                .unwrap_or_else(|| "backtest".to_string()),
+            source: "inline".to_string(),
+            capital,
+            daily_returns: daily_returns(&result.equity_curve),
+            trade_pnls: trades.iter().map(|t| t.pnl).collect(),
+        }
+    }
+
+    /// Build a sample from a stored run (trades table plus the equity curve in
+    /// the stored result JSON).
+    pub fn from_run(run: &RunDetail) -> Result<Self> {
+        let equity_curve = stored_equity_curve(run)?;
+        let mut trades: Vec<_> = run.trades.iter().collect();
+        trades.sort_by_key(|t| t.entry_datetime);
+        let name = run.strategy_name.as_deref().unwrap_or("run");
+        Ok(Self {
+            label: format!("{name} on {}", run.symbol.to_uppercase()),
+            source: format!("run:{}", run.id),
+            capital: run.capital,
+            daily_returns: daily_returns(&equity_curve),
+            trade_pnls: trades.iter().map(|t| t.pnl).collect(),
+        })
+    }
+}
+
 /// Execute Monte Carlo simulation from a symbol's cached price data.
 #[allow(clippy::too_many_lines, clippy::similar_names)]
 pub async fn execute(
@@ -62,10 +131,7 @@ pub fn execute_from_returns(
     }
 
     // Run simulations
-    let mut rng = match seed {
-        Some(s) => StdRng::seed_from_u64(s),
-        None => StdRng::from_os_rng(),
-    };
+    let mut rng = rng_from_seed(seed);
 
     let mut terminal_values = Vec::with_capacity(n_simulations);
     let mut max_drawdowns = Vec::with_capacity(n_simulations);
@@ -75,6 +141,194 @@ pub fn execute_from_returns(
         max_drawdowns.push(max_dd);
     }
 
+    Ok(build_response(
+        label,
+        n_simulations,
+        horizon_days,
+        initial_capital,
+        terminal_values,
+        max_drawdowns,
+    ))
+}
+
+/// Execute Monte Carlo simulation on a backtest's own returns or trades.
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+pub fn execute_on_strategy(
+    sample: &StrategySample,
+    method: MonteCarloMethod,
+    n_simulations: usize,
+    horizon_days: usize,
+    initial_capital: f64,
+    position_scale: f64,
+    ruin_threshold: f64,
+    seed: Option<u64>,
+) -> Result<MonteCarloResponse> {
+    let ruin_level = initial_capital * (1.0 - ruin_threshold);
+    let mut rng = rng_from_seed(seed);
+
+    let (steps, step_unit) = match method {
+        MonteCarloMethod::BlockBootstrap => {
+            if sample.daily_returns.len() < MIN_RETURNS_FOR_BOOTSTRAP {
+                anyhow::bail!(
+                    "Insufficient daily strategy returns: {} (need {MIN_RETURNS_FOR_BOOTSTRAP})",
+                    sample.daily_returns.len()
+                );
+            }
+            (horizon_days, "days")
+        }
+        MonteCarloMethod::TradeShuffle | MonteCarloMethod::TradeBootstrap => {
+            if sample.trade_pnls.len() < MIN_TRADES_FOR_RESAMPLING {
+                anyhow::bail!(
+                    "Insufficient closed trades: {} (need {MIN_TRADES_FOR_RESAMPLING})",
+                    sample.trade_pnls.len()
+                );
+            }
+            (sample.trade_pnls.len(), "trades")
+        }
+    };
+
+    let scaled_returns: Vec<f64> = sample
+        .daily_returns
+        .iter()
+        .map(|r| r * position_scale)
+        .collect();
+    // Trade P&L is in dollars of the original backtest; rescale to the
+    // simulated capital before applying the position-size multiplier.
+    let capital_ratio = if sample.capital > 0.0 {
+        initial_capital / sample.capital
+    } else {
+        1.0
+    };
+    let mut scaled_pnls: Vec<f64> = sample
+        .trade_pnls
+        .iter()
+        .map(|p| p * capital_ratio * position_scale)
+        .collect();
+
+    let mut paths = Vec::with_capacity(n_simulations);
+    for _ in 0..n_simulations {
+        let path = match method {
+            MonteCarloMethod::BlockBootstrap => simulate_block_path(
+                &scaled_returns,
+                steps,
+                initial_capital,
+                ruin_level,
+                &mut rng,
+            ),
+            MonteCarloMethod::TradeShuffle => {
+                scaled_pnls.shuffle(&mut rng);
+                simulate_trade_path(scaled_pnls.iter().copied(), initial_capital, ruin_level)
+            }
+            MonteCarloMethod::TradeBootstrap => {
+                let n = scaled_pnls.len();
+                let draws: Vec<f64> = (0..n)
+                    .map(|_| scaled_pnls[rng.random_range(0..n)])
+                    .collect();
+                simulate_trade_path(draws, initial_capital, ruin_level)
+            }
+        };
+        paths.push(path);
+    }
+
+    let n = n_simulations as f64;
+    let risk_of_ruin = paths.iter().filter(|p| p.ruined).count() as f64 / n;
+    let prob_unrecovered = paths.iter().filter(|p| p.ends_underwater).count() as f64 / n;
+    let mut underwater: Vec<f64> = paths.iter().map(|p| p.longest_underwater as f64).collect();
+    underwater.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let time_to_recover = RecoveryDistribution {
+        median: stats::median(&underwater),
+        percentile_95: stats::percentile(&underwater, 95.0),
+        worst: underwater.last().copied().unwrap_or(0.0),
+        prob_unrecovered,
+    };
+
+    let terminal_values = paths.iter().map(|p| p.terminal).collect();
+    let max_drawdowns = paths.iter().map(|p| p.max_drawdown).collect();
+    let mut response = build_response(
+        &sample.label,
+        n_simulations,
+        steps,
+        initial_capital,
+        terminal_values,
+        max_drawdowns,
+    );
+
+    let method_str = method.as_str();
+    response.summary = format!(
+        "Monte Carlo ({method_str}) on {} [{}]: {n_simulations} paths over {steps} {step_unit} \
+         at {position_scale:.2}x size. Risk of ruin (-{:.0}%)={:.1}%, \
+         P(loss)={:.1}%, median max drawdown={:.1}%.",
+        sample.label,
+        sample.source,
+        ruin_threshold * 100.0,
+        risk_of_ruin * 100.0,
+        response.ruin_analysis.prob_negative_return * 100.0,
+        response.drawdown_distribution.median,
+    );
+    // Replace the price-bootstrap methodology note with strategy-specific findings.
+    response.key_findings.pop();
+    response.key_findings.push(format!(
+        "Risk of ruin: {:.1}% of paths lose {:.0}% of starting capital at some point",
+        risk_of_ruin * 100.0,
+        ruin_threshold * 100.0,
+    ));
+    response.key_findings.push(format!(
+        "Time to recover: longest underwater stretch median={:.0} {step_unit}, \
+         95th percentile={:.0}, worst={:.0}; {:.1}% of paths end below their peak",
+        time_to_recover.median,
+        time_to_recover.percentile_95,
+        time_to_recover.worst,
+        prob_unrecovered * 100.0,
+    ));
+    response.key_findings.push(match method {
+        MonteCarloMethod::BlockBootstrap => format!(
+            "Block bootstrap of {} daily strategy returns with {BLOCK_SIZE}-day blocks",
+            sample.daily_returns.len()
+        ),
+        MonteCarloMethod::TradeShuffle => format!(
+            "Same {} trades replayed in random order — isolates sequence risk",
+            sample.trade_pnls.len()
+        ),
+        MonteCarloMethod::TradeBootstrap => format!(
+            "{} trades drawn with replacement from the backtest's trade log",
+            sample.trade_pnls.len()
+        ),
+    });
+    response.suggested_next_steps = vec![
+        if risk_of_ruin > 0.05 {
+            "[NEXT] Risk of ruin is above 5% — rerun with a smaller position_scale to find a survivable size".to_string()
+        } else {
+            "[NEXT] Rerun with a larger position_scale to see how much size the strategy can carry".to_string()
+        },
+        "[THEN] Compare method=\"trade_shuffle\" with \"block_bootstrap\" — a large gap means results depend on trade sequencing".to_string(),
+        "[TIP] Use the 95th-percentile drawdown, not the backtest's single drawdown, when setting risk limits".to_string(),
+    ];
+    response.strategy_risk = Some(StrategyRiskAnalysis {
+        source: sample.source.clone(),
+        method: method_str.to_string(),
+        step_unit: step_unit.to_string(),
+        steps,
+        n_trades: sample.trade_pnls.len(),
+        n_daily_returns: sample.daily_returns.len(),
+        position_scale,
+        ruin_threshold,
+        risk_of_ruin,
+        time_to_recover,
+    });
+
+    Ok(response)
+}
+
+/// Summarize simulated terminal values and max drawdowns into a response.
+#[allow(clippy::too_many_lines, clippy::similar_names)]
+fn build_response(
+    label: &str,
+    n_simulations: usize,
+    horizon_days: usize,
+    initial_capital: f64,
+    mut terminal_values: Vec<f64>,
+    mut max_drawdowns: Vec<f64>,
+) -> MonteCarloResponse {
     // Sort for percentile extraction
     terminal_values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
     max_drawdowns.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
@@ -202,7 +456,7 @@ pub fn execute_from_returns(
             .to_string(),
     ];
 
-    Ok(MonteCarloResponse {
+    MonteCarloResponse {
         summary,
         symbol: label.to_string(),
         n_simulations,
@@ -212,9 +466,84 @@ pub fn execute_from_returns(
         ruin_analysis,
         drawdown_distribution,
         terminal_histogram,
+        strategy_risk: None,
         key_findings,
         suggested_next_steps,
-    })
+    }
+}
+
+fn rng_from_seed(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_os_rng(),
+    }
+}
+
+/// Summary of one simulated equity path.
+#[derive(Debug, Clone, Copy)]
+struct PathStats {
+    terminal: f64,
+    /// Max drawdown fraction (capped at 1.0).
+    max_drawdown: f64,
+    /// Equity touched the ruin level; the path stops there.
+    ruined: bool,
+    /// Longest consecutive run of steps below the running peak.
+    longest_underwater: usize,
+    ends_underwater: bool,
+}
+
+/// Accumulates drawdown, ruin, and underwater statistics step by step.
+struct PathTracker {
+    equity: f64,
+    peak: f64,
+    max_dd: f64,
+    ruin_level: f64,
+    ruined: bool,
+    underwater: usize,
+    longest_underwater: usize,
+}
+
+impl PathTracker {
+    fn new(initial_capital: f64, ruin_level: f64) -> Self {
+        Self {
+            equity: initial_capital,
+            peak: initial_capital,
+            max_dd: 0.0,
+            ruin_level,
+            ruined: false,
+            underwater: 0,
+            longest_underwater: 0,
+        }
+    }
+
+    /// Record the next equity value. Returns `false` once the path is ruined.
+    fn step(&mut self, equity: f64) -> bool {
+        self.equity = equity;
+        if equity >= self.peak {
+            self.peak = equity;
+            self.underwater = 0;
+        } else {
+            self.underwater += 1;
+            self.longest_underwater = self.longest_underwater.max(self.underwater);
+        }
+        if self.peak > 0.0 {
+            self.max_dd = self.max_dd.max(((self.peak - equity) / self.peak).min(1.0));
+        }
+        if equity <= self.ruin_level {
+            self.ruined = true;
+        }
+        !self.ruined
+    }
+
+    fn finish(self) -> PathStats {
+        PathStats {
+            terminal: self.equity,
+            max_drawdown: self.max_dd,
+            ruined: self.ruined,
+            longest_underwater: self.longest_underwater,
+            ends_underwater: self.equity < self.peak,
+        }
+    }
 }
 
 /// Simulate one equity path using block bootstrap resampling.
@@ -225,38 +554,55 @@ fn simulate_path(
     initial_capital: f64,
     rng: &mut StdRng,
 ) -> (f64, f64) {
+    let path = simulate_block_path(returns, horizon, initial_capital, f64::NEG_INFINITY, rng);
+    (path.terminal, path.max_drawdown)
+}
+
+/// Block-bootstrap `horizon` compounded returns, stopping at ruin.
+fn simulate_block_path(
+    returns: &[f64],
+    horizon: usize,
+    initial_capital: f64,
+    ruin_level: f64,
+    rng: &mut StdRng,
+) -> PathStats {
     let n = returns.len();
+    let mut tracker = PathTracker::new(initial_capital, ruin_level);
     let mut equity = initial_capital;
-    let mut peak = initial_capital;
-    let mut max_dd = 0.0_f64;
     let mut days_simulated = 0;
 
-    while days_simulated < horizon {
+    'outer: while days_simulated < horizon {
         // Pick a random block start
         let block_start = rng.random_range(0..n.saturating_sub(BLOCK_SIZE).max(1));
         let block_end = (block_start + BLOCK_SIZE).min(n);
 
         for &ret in &returns[block_start..block_end] {
             equity *= 1.0 + ret;
-            if equity > peak {
-                peak = equity;
-            }
-            let dd = if peak > 0.0 {
-                (peak - equity) / peak
-            } else {
-                0.0
-            };
-            if dd > max_dd {
-                max_dd = dd;
-            }
             days_simulated += 1;
-            if days_simulated >= horizon {
-                break;
+            if !tracker.step(equity) || days_simulated >= horizon {
+                break 'outer;
             }
         }
     }
 
-    (equity, max_dd)
+    tracker.finish()
+}
+
+/// Apply dollar trade P&Ls in sequence, stopping at ruin.
+fn simulate_trade_path(
+    pnls: impl IntoIterator<Item = f64>,
+    initial_capital: f64,
+    ruin_level: f64,
+) -> PathStats {
+    let mut tracker = PathTracker::new(initial_capital, ruin_level);
+    let mut equity = initial_capital;
+    for pnl in pnls {
+        equity += pnl;
+        if !tracker.step(equity) {
+            break;
+        }
+    }
+    tracker.finish()
 }
 
 #[cfg(test)]
@@ -351,4 +697,99 @@ mod tests {
             "different seeds should produce different paths: t1={t1}, t2={t2}"
         );
     }
+
+    fn sample(trade_pnls: Vec<f64>, daily_returns: Vec<f64>) -> StrategySample {
+        StrategySample {
+            label: "test".to_string(),
+            source: "inline".to_string(),
+            capital: 10_000.0,
+            daily_returns,
+            trade_pnls,
+        }
+    }
+
+    #[test]
+    fn trade_shuffle_preserves_terminal_wealth() {
+        // Reordering the same trades changes the path, never the end point.
+        let pnls: Vec<f64> = (0..40)
+            .map(|i| if i % 3 == 0 { -150.0 } else { 100.0 })
+            .collect();
+        let expected = 10_000.0 + pnls.iter().sum::<f64>();
+        let response = execute_on_strategy(
+            &sample(pnls, vec![]),
+            MonteCarloMethod::TradeShuffle,
+            200,
+            252,
+            10_000.0,
+            1.0,
+            0.5,
+            Some(7),
+        )
+        .unwrap();
+        for path in &response.percentile_paths {
+            assert!(
+                (path.terminal_value - expected).abs() < 1e-6,
+                "terminal={}, expected={expected}",
+                path.terminal_value
+            );
+        }
+        let risk = response.strategy_risk.expect("strategy risk present");
+        assert_eq!(risk.step_unit, "trades");
+        assert_eq!(risk.steps, 40);
+        assert!(risk.risk_of_ruin.abs() < 1e-12);
+    }
+
+    #[test]
+    fn position_scale_drives_risk_of_ruin() {
+        let pnls: Vec<f64> = (0..30)
+            .map(|i| if i % 2 == 0 { -400.0 } else { 420.0 })
+            .collect();
+        let run = |scale: f64| {
+            execute_on_strategy(
+                &sample(pnls.clone(), vec![]),
+                MonteCarloMethod::TradeBootstrap,
+                500,
+                252,
+                10_000.0,
+                scale,
+                0.5,
+                Some(3),
+            )
+            .unwrap()
+            .strategy_risk
+            .unwrap()
+            .risk_of_ruin
+        };
+        assert!(run(0.5).abs() < 1e-12, "half size should never hit -50%");
+        assert!(run(5.0) > 0.1, "5x size should often hit -50%");
+    }
+
+    #[test]
+    fn trade_path_tracks_ruin_and_recovery() {
+        let path = simulate_trade_path([-100.0, -100.0, 300.0, -50.0], 1_000.0, 500.0);
+        assert!(!path.ruined);
+        assert_eq!(path.longest_underwater, 2);
+        assert!(path.ends_underwater);
+        assert!((path.max_drawdown - 0.2).abs() < 1e-12);
+
+        let ruined = simulate_trade_path([-300.0, -300.0, 1_000.0], 1_000.0, 500.0);
+        assert!(ruined.ruined);
+        assert!(
+            (ruined.terminal - 400.0).abs() < 1e-12,
+            "path stops at ruin"
+        );
+    }
+
+    #[test]
+    fn strategy_simulation_rejects_thin_samples() {
+        let thin = sample(vec![10.0; 5], vec![0.001; 10]);
+        for method in [
+            MonteCarloMethod::BlockBootstrap,
+            MonteCarloMethod::TradeShuffle,
+        ] {
+            assert!(
+                execute_on_strategy(&thin, method, 100, 20, 10_000.0, 1.0, 0.5, Some(1)).is_err()
+            );
+        }
+    }
 }
@@ -1,7 +1,8 @@
 //! Backtest pipeline orchestrator.
 //!
 //! When `pipeline=true` on a sweep, this module chains:
-//! sweep -> significance gate -> walk-forward -> OOS data gate -> monte carlo.
+//! sweep -> significance gate -> overfitting gate (CSCV) -> walk-forward ->
+//! OOS data gate -> monte carlo.
 //! Each stage is fail-tolerant and reports status for frontend rendering.
 
 use std::collections::HashMap;
@@ -10,7 +11,9 @@ use anyhow::Result;
 use serde_json::Value;
 
 use crate::constants::{MIN_RETURNS_FOR_BOOTSTRAP, P_VALUE_THRESHOLD};
+use crate::engine::cscv::{run_cscv, sweep_returns, DEFAULT_CSCV_BLOCKS};
 use crate::server::OptopsyServer;
+use crate::tools::response_types::overfitting::CscvResponse;
 use crate::tools::response_types::pipeline::{PipelineResponse, StageInfo, StageStatus};
 use crate::tools::response_types::sweep::{SweepResponse, SweepResult};
 
@@ -71,6 +74,13 @@ pub async fn run_pipeline(
         ));
     }
 
+    if let Some(rc) = &sweep_response.reality_check {
+        key_findings.push(format!(
+            "Family-wise test across {} combos: SPA p={:.3}, Reality Check p={:.3} (best combo vs cash)",
+            rc.n_strategies, rc.spa_p_value, rc.reality_check_p_value,
+        ));
+    }
+
     // Gate 1: Significance — decide which combos to validate
     notify("Significance Gate");
     let top_combos = select_top_combos(&sweep_response);
@@ -81,14 +91,27 @@ pub async fn run_pipeline(
         stages.push(StageInfo {
             name: "significance_gate".to_string(),
             status: StageStatus::Failed,
-            reason: Some(format!(
-                "No parameter combos passed significance gate (all p > {P_VALUE_THRESHOLD:.2} or insufficient trades)"
-            )),
+            reason: Some(match &sweep_response.reality_check {
+                Some(rc) if !rc.significant => format!(
+                    "Best combo does not beat cash after accounting for the whole sweep (SPA p = {:.3} > {P_VALUE_THRESHOLD:.2})",
+                    rc.spa_p_value
+                ),
+                _ => format!(
+                    "No parameter combos passed significance gate (all p > {P_VALUE_THRESHOLD:.2} or insufficient trades)"
+                ),
+            }),
             duration_ms: 0,
             details: sig_details,
         });
 
         // Skip remaining stages
+        stages.push(StageInfo {
+            name: "overfitting_gate".to_string(),
+            status: StageStatus::Skipped,
+            reason: Some("Skipped: significance gate failed".to_string()),
+            duration_ms: 0,
+            details: HashMap::new(),
+        });
         stages.push(StageInfo {
             name: "walk_forward".to_string(),
             status: StageStatus::Skipped,
@@ -121,6 +144,7 @@ pub async fn run_pipeline(
             sweep_response,
             None,
             None,
+            None,
             key_findings,
             pipeline_start,
             symbol,
@@ -135,6 +159,77 @@ pub async fn run_pipeline(
         details: sig_details,
     });
 
+    // Gate 2: Overfitting — CSCV probability of backtest overfitting
+    notify("Overfitting Gate");
+    let cscv_start = std::time::Instant::now();
+    let cscv = match compute_cscv(&sweep_response).await {
+        Ok(cscv) => {
+            let details = cscv_details(&cscv);
+            key_findings.push(format!(
+                "CSCV: PBO={:.1}%, OOS loss probability={:.1}%, degradation slope={:.2} ({} splits)",
+                cscv.pbo * 100.0,
+                cscv.prob_oos_loss * 100.0,
+                cscv.degradation_slope,
+                cscv.n_splits,
+            ));
+            if cscv.rejected {
+                stages.push(StageInfo {
+                    name: "overfitting_gate".to_string(),
+                    status: StageStatus::Failed,
+                    reason: Some(format!(
+                        "Probability of backtest overfitting {:.2} > {:.2} threshold",
+                        cscv.pbo, cscv.pbo_threshold
+                    )),
+                    duration_ms: cscv_start.elapsed().as_millis() as u64,
+                    details,
+                });
+                for name in ["walk_forward", "oos_data_gate", "monte_carlo"] {
+                    stages.push(StageInfo {
+                        name: name.to_string(),
+                        status: StageStatus::Skipped,
+                        reason: Some("Skipped: overfitting gate failed".to_string()),
+                        duration_ms: 0,
+                        details: HashMap::new(),
+                    });
+                }
+                key_findings
+                    .push("Pipeline stopped: the sweep winner is likely an overfit".to_string());
+
+                return Ok(build_response(
+                    stages,
+                    sweep_id,
+                    run_ids,
+                    sweep_response,
+                    Some(cscv),
+                    None,
+                    None,
+                    key_findings,
+                    pipeline_start,
+                    symbol,
+                ));
+            }
+            stages.push(StageInfo {
+                name: "overfitting_gate".to_string(),
+                status: StageStatus::Completed,
+                reason: None,
+                duration_ms: cscv_start.elapsed().as_millis() as u64,
+                details,
+            });
+            Some(cscv)
+        }
+        Err(e) => {
+            // Not enough combos or history to split — informational, not blocking.
+            stages.push(StageInfo {
+                name: "overfitting_gate".to_string(),
+                status: StageStatus::Skipped,
+                reason: Some(format!("Skipped: {e}")),
+                duration_ms: 0,
+                details: HashMap::new(),
+            });
+            None
+        }
+    };
+
     // Stage 2: Walk-forward validation
     notify("Walk-Forward");
     let params_grid = build_wf_params_grid(&top_combos);
@@ -151,7 +246,10 @@ pub async fn run_pipeline(
         })
     });
     let script_source = match raw_source {
-        Some(raw) => Some(crate::tools::run_script::maybe_transpile(raw)?),
+        Some(raw) => Some(crate::tools::run_script::maybe_transpile(
+            raw,
+            server.signal_store.as_deref(),
+        )?),
         None => None,
     };
 
@@ -178,6 +276,7 @@ pub async fn run_pipeline(
         None, // profile
         script_source,
         wf_base_params,
+        None, // cpcv (unused in rolling mode)
     )
     .await;
 
@@ -234,6 +333,7 @@ pub async fn run_pipeline(
                 sweep_id,
                 run_ids,
                 sweep_response,
+                cscv,
                 None,
                 None,
                 key_findings,
@@ -288,6 +388,7 @@ pub async fn run_pipeline(
             sweep_id,
             run_ids,
             sweep_response,
+            cscv,
             wf_response,
             None,
             key_findings,
@@ -378,6 +479,7 @@ pub async fn run_pipeline(
         sweep_id,
         run_ids,
         sweep_response,
+        cscv,
         wf_response,
         mc_response,
         key_findings,
@@ -392,9 +494,19 @@ pub async fn run_pipeline(
 
 /// Select top parameter combos for walk-forward validation.
 ///
-/// If a permutation test was run, returns combos where `significant == true`.
+/// If a permutation test was run, returns combos where `significant == true`,
+/// or none at all when the family-wise Reality Check / SPA test finds that
+/// even the best combo does not beat cash.
 /// Otherwise, returns the top N combos by objective (already ranked).
 pub(crate) fn select_top_combos(sweep: &SweepResponse) -> Vec<&HashMap<String, Value>> {
+    if sweep
+        .reality_check
+        .as_ref()
+        .is_some_and(|rc| !rc.significant)
+    {
+        return Vec::new();
+    }
+
     let has_permutation = sweep.multiple_comparisons.is_some()
         || sweep.ranked_results.iter().any(|r| r.p_value.is_some());
 
@@ -433,7 +545,7 @@ fn build_significance_details(
         .and_then(|r| r.p_value)
         .unwrap_or(f64::NAN);
 
-    HashMap::from([
+    let mut details = HashMap::from([
         ("combos_tested".to_string(), Value::from(combos_tested)),
         ("combos_passed".to_string(), Value::from(combos_passed)),
         (
@@ -441,6 +553,39 @@ fn build_significance_details(
             Value::from(P_VALUE_THRESHOLD),
         ),
         ("top_combo_p_value".to_string(), Value::from(top_p_value)),
+    ]);
+    if let Some(rc) = &sweep.reality_check {
+        details.insert("spa_p_value".to_string(), Value::from(rc.spa_p_value));
+        details.insert(
+            "reality_check_p_value".to_string(),
+            Value::from(rc.reality_check_p_value),
+        );
+    }
+    details
+}
+
+/// Run CSCV on the sweep's per-combo equity curves off the async runtime.
+///
+/// Fails when fewer than two combos have equity curves or the aligned
+/// history is too short to split.
+pub async fn compute_cscv(sweep: &SweepResponse) -> Result<CscvResponse> {
+    let returns = sweep_returns(sweep);
+    tokio::task::spawn_blocking(move || run_cscv(&returns, DEFAULT_CSCV_BLOCKS)).await?
+}
+
+/// Build gate decision details for the overfitting gate.
+fn cscv_details(cscv: &CscvResponse) -> HashMap<String, Value> {
+    HashMap::from([
+        ("pbo".to_string(), Value::from(cscv.pbo)),
+        ("pbo_threshold".to_string(), Value::from(cscv.pbo_threshold)),
+        ("prob_oos_loss".to_string(), Value::from(cscv.prob_oos_loss)),
+        (
+            "degradation_slope".to_string(),
+            Value::from(cscv.degradation_slope),
+        ),
+        ("n_blocks".to_string(), Value::from(cscv.n_blocks)),
+        ("n_splits".to_string(), Value::from(cscv.n_splits)),
+        ("n_strategies".to_string(), Value::from(cscv.n_strategies)),
     ])
 }
 
@@ -489,6 +634,7 @@ fn build_response(
     sweep_id: String,
     run_ids: Vec<String>,
     sweep: SweepResponse,
+    cscv: Option<CscvResponse>,
     walk_forward: Option<crate::tools::response_types::walk_forward::WalkForwardResponse>,
     monte_carlo: Option<crate::tools::response_types::risk::MonteCarloResponse>,
     key_findings: Vec<String>,
@@ -521,6 +667,7 @@ fn build_response(
         sweep_id,
         run_ids,
         sweep,
+        cscv,
         walk_forward,
         monte_carlo,
         key_findings,
@@ -564,6 +711,9 @@ fn build_suggested_next_steps(stages: &[StageInfo], symbol: &str) -> Vec<String>
     let sig_failed = stages
         .iter()
         .any(|s| s.name == "significance_gate" && matches!(s.status, StageStatus::Failed));
+    let overfit = stages
+        .iter()
+        .any(|s| s.name == "overfitting_gate" && matches!(s.status, StageStatus::Failed));
     let wf_failed = stages
         .iter()
         .any(|s| s.name == "walk_forward" && matches!(s.status, StageStatus::Failed));
@@ -576,6 +726,11 @@ fn build_suggested_next_steps(stages: &[StageInfo], symbol: &str) -> Vec<String>
             "[NEXT] Re-run the sweep with wider parameter ranges or a different strategy".to_string(),
             "[TIP] Consider running with num_permutations=0 to skip significance testing and force walk-forward validation".to_string(),
         ]
+    } else if overfit {
+        vec![
+            "[NEXT] Narrow the sweep to fewer, economically motivated parameter values and re-run".to_string(),
+            "[TIP] A high PBO means the best in-sample combo tends to rank below median out-of-sample — do not paper trade it".to_string(),
+        ]
     } else if wf_failed {
         vec![
             "[NEXT] Check that the strategy and symbol have sufficient data for walk-forward windows".to_string(),
@@ -667,6 +822,8 @@ mod tests {
             profit_factor: 1.8,
             cagr: 0.15,
             calmar: 1.5,
+            cvar_95: 0.0,
+            pareto_front: None,
             p_value: None,
             significant: None,
         };
@@ -694,6 +851,8 @@ mod tests {
             profit_factor: 1.8,
             cagr: 0.15,
             calmar: 1.5,
+            cvar_95: 0.0,
+            pareto_front: None,
             p_value: None,
             significant: None,
         };
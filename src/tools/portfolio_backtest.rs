@@ -0,0 +1,133 @@
+//! Multi-strategy portfolio backtest: several saved strategies trading
+//! bar-synchronously against one shared capital pool.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+
+use crate::scripting::engine::CachingDataLoader;
+use crate::scripting::portfolio::{self, PortfolioConfig, SleeveScript};
+use crate::server::{OptopsyServer, PortfolioSleeveSpec};
+use crate::tools::portfolio_optimize::dedupe_labels;
+use crate::tools::response_types::PortfolioBacktestResponse;
+use crate::tools::run_script::{resolve_script_source, RunScriptParams};
+
+/// Execute a portfolio backtest across `sleeves`.
+#[allow(clippy::too_many_lines)]
+pub async fn execute(
+    server: &OptopsyServer,
+    sleeves: &[PortfolioSleeveSpec],
+    config: PortfolioConfig,
+) -> Result<PortfolioBacktestResponse> {
+    let mut scripts = Vec::with_capacity(sleeves.len());
+    for spec in sleeves {
+        let weight = spec.weight.unwrap_or(1.0);
+        let risk_budget = spec.risk_budget.unwrap_or(1.0);
+        if !(weight > 0.0 && risk_budget > 0.0) {
+            anyhow::bail!(
+                "Sleeve '{}': weight and risk_budget must be positive",
+                spec.strategy
+            );
+        }
+        let (_, source) = resolve_script_source(
+            &RunScriptParams {
+                strategy: Some(spec.strategy.clone()),
+                script: None,
+                params: spec.params.clone(),
+                profile: None,
+            },
+            server.strategy_store.as_deref(),
+            server.signal_store.as_deref(),
+        )
+        .with_context(|| format!("Failed to load strategy '{}'", spec.strategy))?;
+        scripts.push(SleeveScript {
+            label: spec.label.clone().unwrap_or_else(|| spec.strategy.clone()),
+            source,
+            params: spec.params.clone(),
+            weight,
+            risk_budget,
+        });
+    }
+    dedupe_labels(scripts.iter_mut().map(|s| &mut s.label));
+
+    let loader =
+        CachingDataLoader::new(Arc::clone(&server.cache), server.adjustment_store_handle());
+    let outcome = portfolio::run_portfolio_backtest(scripts, &config, &loader).await?;
+    let result = outcome.result;
+    let metrics = &result.metrics;
+
+    let summary = format!(
+        "Portfolio of {} sleeves on ${:.0} shared capital ({}, rebalance {}): \
+         return={:.2}%, Sharpe={:.3}, max drawdown={:.2}%, {} trades.",
+        outcome.sleeves.len(),
+        config.capital,
+        config.allocation.as_str(),
+        config.rebalance.as_str(),
+        metrics.total_return_pct,
+        metrics.sharpe,
+        metrics.max_drawdown * 100.0,
+        result.trade_count,
+    );
+
+    let mut key_findings: Vec<String> = outcome
+        .sleeves
+        .iter()
+        .map(|s| {
+            format!(
+                "{}: P&L ${:.2} ({:+.2}% of capital), weight {:.1}% → {:.1}%, Sharpe={:.3}, {} trades",
+                s.label,
+                s.pnl,
+                s.contribution_pct,
+                s.initial_weight * 100.0,
+                s.final_weight * 100.0,
+                s.metrics.sharpe,
+                s.trade_count,
+            )
+        })
+        .collect();
+    if let Some(best) = outcome.sleeves.iter().max_by(|a, b| {
+        a.metrics
+            .sharpe
+            .partial_cmp(&b.metrics.sharpe)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }) {
+        key_findings.push(format!(
+            "Portfolio Sharpe {:.3} vs best sleeve ({}) {:.3}",
+            metrics.sharpe, best.label, best.metrics.sharpe
+        ));
+    }
+    let blocked: usize = outcome.sleeves.iter().map(|s| s.blocked_entries).sum();
+    if blocked > 0 {
+        key_findings.push(format!(
+            "{blocked} entries were blocked by the shared capital pool or cross-strategy limits — \
+             sleeves are competing for capital"
+        ));
+    }
+    if outcome.rebalances > 0 {
+        key_findings.push(format!(
+            "{} rebalances moved free cash between sleeves",
+            outcome.rebalances
+        ));
+    }
+
+    let suggested_next_steps = vec![
+        "[NEXT] Call monte_carlo(result=<this result>) to stress the consolidated book's drawdowns"
+            .to_string(),
+        if blocked > 0 {
+            "[THEN] Raise capital or lower sleeve sizing — blocked entries mean the standalone backtests overstate what the book can trade".to_string()
+        } else {
+            "[THEN] Call portfolio_optimize(strategies=...) to derive weights, then rerun with them".to_string()
+        },
+        "[TIP] Compare allocation=\"risk_budget\" with monthly rebalancing against fixed weights to see how much volatility targeting helps".to_string(),
+    ];
+
+    Ok(PortfolioBacktestResponse {
+        summary,
+        allocation: config.allocation.as_str().to_string(),
+        rebalance: config.rebalance.as_str().to_string(),
+        rebalances: outcome.rebalances,
+        sleeves: outcome.sleeves,
+        result,
+        key_findings,
+        suggested_next_steps,
+    })
+}
@@ -1,23 +1,48 @@
-//! Portfolio optimization tool: risk parity, minimum variance, and maximum Sharpe.
+//! Portfolio optimization tool: risk parity, minimum variance, maximum Sharpe,
+//! maximum diversification, and hierarchical risk parity (HRP).
 //!
-//! Takes multiple symbols, computes the covariance matrix from historical returns,
-//! and finds optimal portfolio weights under three different objective functions.
-
-use anyhow::Result;
+//! Allocates either across symbols (daily returns from cached prices) or across
+//! strategies (daily returns of stored runs or fresh backtests). Strategy
+//! allocations can be re-run as a combined backtest with the optimal capital
+//! weights.
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveTime};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
+use crate::application::backtests;
 use crate::constants::TRADING_DAYS_PER_YEAR;
 use crate::data::cache::CachedStore;
+use crate::data::traits::RunDetail;
+use crate::engine::equity::{self, daily_equity, stored_equity_curve};
+use crate::engine::metrics::calculate_metrics;
+use crate::engine::types::{EquityPoint, TradeRecord};
+use crate::server::{OptopsyServer, PortfolioStrategySpec};
 use crate::tools::ai_helpers;
 use crate::tools::response_types::{
-    AssetStats, CorrelationEntry, OptimalWeight, OptimizationResult, PortfolioOptimizeResponse,
+    AssetStats, CombinedBacktest, CorrelationEntry, OptimalWeight, OptimizationResult,
+    PortfolioOptimizeResponse, SleeveResult,
 };
+use crate::tools::run_script::RunScriptParams;
 
 /// Default methods to run if none specified.
-const DEFAULT_METHODS: &[&str] = &["risk_parity", "min_variance", "max_sharpe"];
+const DEFAULT_METHODS: &[&str] = &[
+    "risk_parity",
+    "min_variance",
+    "max_sharpe",
+    "max_diversification",
+    "hrp",
+];
 
-/// Execute portfolio optimization.
-#[allow(clippy::too_many_lines)]
+/// Minimum aligned daily observations required to estimate the covariance matrix.
+const MIN_OBSERVATIONS: usize = 30;
+
+/// Weights below this are treated as unfunded when re-running a combined backtest.
+const MIN_SLEEVE_WEIGHT: f64 = 1e-4;
+
+/// Execute portfolio optimization across symbols.
 pub async fn execute(
     cache: &Arc<CachedStore>,
     symbols: &[String],
@@ -26,9 +51,109 @@ pub async fn execute(
     risk_free_rate: f64,
 ) -> Result<PortfolioOptimizeResponse> {
     let (upper_symbols, aligned) =
-        ai_helpers::load_aligned_returns(cache, symbols, years, 30).await?;
+        ai_helpers::load_aligned_returns(cache, symbols, years, MIN_OBSERVATIONS).await?;
+
+    let mut response = optimize(upper_symbols, &aligned, methods, risk_free_rate, "assets")?;
+
+    let sym_list = response.symbols.join(", ");
+    response.suggested_next_steps = vec![
+        format!(
+            "[NEXT] Call portfolio_backtest with the optimal weights to validate out-of-sample"
+        ),
+        format!(
+            "[THEN] Call correlate to investigate pairwise relationships between {sym_list}"
+        ),
+        "[TIP] Risk parity is more robust out-of-sample; max Sharpe can be sensitive to estimation error"
+            .to_string(),
+    ];
+    Ok(response)
+}
+
+/// Execute portfolio optimization across strategies.
+///
+/// Sleeves come from stored `run_ids` or, if empty, from fresh backtests of
+/// `strategies`. When `rerun_method` is set, each sleeve is re-run with
+/// `capital × weight` from that method and the results are summed into a
+/// combined backtest.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_strategies(
+    server: &OptopsyServer,
+    run_ids: &[String],
+    strategies: &[PortfolioStrategySpec],
+    methods: Option<&[String]>,
+    risk_free_rate: f64,
+    rerun_method: Option<&str>,
+    capital: f64,
+) -> Result<PortfolioOptimizeResponse> {
+    if let Some(method) = rerun_method {
+        let requested = methods.map_or_else(
+            || DEFAULT_METHODS.contains(&method),
+            |m| m.iter().any(|x| x == method),
+        );
+        if !requested {
+            anyhow::bail!("rerun_method '{method}' must be one of the requested methods");
+        }
+    }
+
+    let sleeves = if run_ids.is_empty() {
+        backtest_sleeves(server, strategies).await?
+    } else {
+        stored_sleeves(server, run_ids)?
+    };
+
+    let daily: Vec<Vec<(NaiveDate, f64)>> = sleeves
+        .iter()
+        .map(|s| daily_equity(&s.equity_curve))
+        .collect();
+    let (dates, aligned) = align_daily_returns(&daily)?;
+    let labels = sleeves.iter().map(|s| s.label.clone()).collect();
 
-    let n_assets = upper_symbols.len();
+    let mut response = optimize(labels, &aligned, methods, risk_free_rate, "strategies")?;
+    if let (Some(first), Some(last)) = (dates.first(), dates.last()) {
+        response.key_findings.push(format!(
+            "Strategies aligned on common trading days {first} to {last}"
+        ));
+    }
+
+    if let Some(method) = rerun_method {
+        let optimization = response
+            .optimizations
+            .iter()
+            .find(|o| o.method == method)
+            .context("rerun method missing from optimizations")?;
+        let combined = run_combined(server, &sleeves, optimization, capital).await?;
+        response.key_findings.push(format!(
+            "Combined backtest ({method} weights, ${capital:.0}): return={:.2}%, Sharpe={:.3}, max drawdown={:.2}%",
+            combined.metrics.total_return_pct,
+            combined.metrics.sharpe,
+            combined.metrics.max_drawdown * 100.0,
+        ));
+        response.combined_backtest = Some(combined);
+    }
+
+    response.suggested_next_steps = vec![
+        if rerun_method.is_some() {
+            "[NEXT] Call monte_carlo(result=combined_backtest) to stress the combined book's drawdowns".to_string()
+        } else {
+            "[NEXT] Re-call portfolio_optimize with rerun_method=\"hrp\" (or another method) to backtest the allocation".to_string()
+        },
+        "[THEN] Compare combined Sharpe against the best single strategy — diversification should lift it".to_string(),
+        "[TIP] HRP and risk parity rely only on the covariance matrix and tend to hold up better out-of-sample than max Sharpe".to_string(),
+    ];
+    Ok(response)
+}
+
+/// Compute statistics, correlations, and the requested allocations for aligned
+/// daily return series. `unit` names the allocated items in the summary.
+#[allow(clippy::too_many_lines)]
+fn optimize(
+    labels: Vec<String>,
+    aligned: &[Vec<f64>],
+    methods: Option<&[String]>,
+    risk_free_rate: f64,
+    unit: &str,
+) -> Result<PortfolioOptimizeResponse> {
+    let n_assets = labels.len();
     let min_len = aligned[0].len();
 
     // Compute mean returns and annualized stats
@@ -40,7 +165,7 @@ pub async fn execute(
         .collect();
 
     // Covariance matrix (annualized)
-    let cov_matrix = ai_helpers::covariance_matrix(&aligned, &means, TRADING_DAYS_PER_YEAR);
+    let cov_matrix = ai_helpers::covariance_matrix(aligned, &means, TRADING_DAYS_PER_YEAR);
 
     // Correlation matrix
     let mut correlation_entries = Vec::new();
@@ -52,8 +177,8 @@ pub async fn execute(
                 0.0
             };
             correlation_entries.push(CorrelationEntry {
-                strategy_a: upper_symbols[i].clone(),
-                strategy_b: upper_symbols[j].clone(),
+                strategy_a: labels[i].clone(),
+                strategy_b: labels[j].clone(),
                 correlation: corr,
             });
         }
@@ -68,7 +193,7 @@ pub async fn execute(
                 0.0
             };
             AssetStats {
-                symbol: upper_symbols[i].clone(),
+                symbol: labels[i].clone(),
                 annualized_return: annualized_returns[i],
                 annualized_volatility: annualized_vols[i],
                 sharpe,
@@ -89,9 +214,12 @@ pub async fn execute(
             "risk_parity" => risk_parity_weights(&cov_matrix),
             "min_variance" => min_variance_weights(&cov_matrix),
             "max_sharpe" => max_sharpe_weights(&annualized_returns, &cov_matrix, risk_free_rate),
+            "max_diversification" => max_diversification_weights(&cov_matrix),
+            "hrp" => hrp_weights(&cov_matrix),
             other => {
                 anyhow::bail!(
-                    "Unknown optimization method: '{other}'. Valid methods: risk_parity, min_variance, max_sharpe"
+                    "Unknown optimization method: '{other}'. Valid methods: {}",
+                    DEFAULT_METHODS.join(", ")
                 );
             }
         };
@@ -113,7 +241,7 @@ pub async fn execute(
             .iter()
             .enumerate()
             .map(|(i, &w)| OptimalWeight {
-                symbol: upper_symbols[i].clone(),
+                symbol: labels[i].clone(),
                 weight: w,
                 weight_pct: w * 100.0,
             })
@@ -136,7 +264,7 @@ pub async fn execute(
     });
 
     let summary = format!(
-        "Portfolio optimization for {} assets over {} observations. \
+        "Portfolio optimization for {} {unit} over {} observations. \
          Best Sharpe: {:.3} ({} method).",
         n_assets,
         min_len,
@@ -166,30 +294,215 @@ pub async fn execute(
         ));
     }
 
-    let sym_list = upper_symbols.join(", ");
-    let suggested_next_steps = vec![
-        format!(
-            "[NEXT] Call portfolio_backtest with the optimal weights to validate out-of-sample"
-        ),
-        format!(
-            "[THEN] Call correlate to investigate pairwise relationships between {sym_list}"
-        ),
-        "[TIP] Risk parity is more robust out-of-sample; max Sharpe can be sensitive to estimation error"
-            .to_string(),
-    ];
-
     Ok(PortfolioOptimizeResponse {
         summary,
-        symbols: upper_symbols,
+        symbols: labels,
         n_observations: min_len,
         correlation_matrix: correlation_entries,
         asset_stats,
         optimizations,
+        combined_backtest: None,
         key_findings,
-        suggested_next_steps,
+        suggested_next_steps: Vec::new(),
+    })
+}
+
+// ── Strategy sleeves ─────────────────────────────────────────────────────
+
+/// A strategy taking part in the allocation.
+struct Sleeve {
+    label: String,
+    /// Strategy ID or name used to re-run the sleeve; `None` if unknown.
+    strategy: Option<String>,
+    params: HashMap<String, Value>,
+    equity_curve: Vec<EquityPoint>,
+}
+
+/// Load sleeves from stored runs.
+fn stored_sleeves(server: &OptopsyServer, run_ids: &[String]) -> Result<Vec<Sleeve>> {
+    let run_store = server.require_run_store()?;
+    let mut sleeves = Vec::with_capacity(run_ids.len());
+    for run_id in run_ids {
+        let run = run_store
+            .get_run(run_id)?
+            .with_context(|| format!("Run '{run_id}' not found"))?;
+        sleeves.push(sleeve_from_run(&run)?);
+    }
+    dedupe_labels(sleeves.iter_mut().map(|s| &mut s.label));
+    Ok(sleeves)
+}
+
+fn sleeve_from_run(run: &RunDetail) -> Result<Sleeve> {
+    let equity_curve = stored_equity_curve(run)?;
+    let params = match &run.params {
+        Value::Object(map) => map.clone().into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    let name = run.strategy_name.as_deref().unwrap_or("run");
+    Ok(Sleeve {
+        label: format!("{name} on {}", run.symbol.to_uppercase()),
+        strategy: run
+            .strategy_id
+            .clone()
+            .or_else(|| run.strategy_name.clone()),
+        params,
+        equity_curve,
+    })
+}
+
+/// Run each strategy spec once to obtain its equity curve.
+async fn backtest_sleeves(
+    server: &OptopsyServer,
+    strategies: &[PortfolioStrategySpec],
+) -> Result<Vec<Sleeve>> {
+    let mut sleeves = Vec::with_capacity(strategies.len());
+    for spec in strategies {
+        let exec = backtests::execute_script(
+            server,
+            RunScriptParams {
+                strategy: Some(spec.strategy.clone()),
+                script: None,
+                params: spec.params.clone(),
+                profile: None,
+            },
+        )
+        .await
+        .with_context(|| format!("Backtest of '{}' failed", spec.strategy))?;
+        sleeves.push(Sleeve {
+            label: spec.label.clone().unwrap_or_else(|| spec.strategy.clone()),
+            strategy: exec
+                .resolved_strategy_id
+                .or_else(|| Some(spec.strategy.clone())),
+            params: spec.params.clone(),
+            equity_curve: exec.response.result.equity_curve,
+        });
+    }
+    dedupe_labels(sleeves.iter_mut().map(|s| &mut s.label));
+    Ok(sleeves)
+}
+
+/// Suffix repeated labels with `#2`, `#3`, … so weights stay distinguishable.
+pub(crate) fn dedupe_labels<'a>(labels: impl IntoIterator<Item = &'a mut String>) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for label in labels {
+        let count = seen.entry(label.clone()).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            *label = format!("{label} #{count}");
+        }
+    }
+}
+
+/// Re-run every funded sleeve with its share of `capital` and sum the books.
+async fn run_combined(
+    server: &OptopsyServer,
+    sleeves: &[Sleeve],
+    optimization: &OptimizationResult,
+    capital: f64,
+) -> Result<CombinedBacktest> {
+    let mut sleeve_results = Vec::new();
+    let mut curves = Vec::new();
+    let mut trade_log: Vec<TradeRecord> = Vec::new();
+    let mut funded = 0.0;
+
+    for (sleeve, weight) in sleeves.iter().zip(&optimization.weights) {
+        if weight.weight < MIN_SLEEVE_WEIGHT {
+            continue;
+        }
+        let strategy = sleeve
+            .strategy
+            .clone()
+            .with_context(|| format!("'{}' has no linked strategy to re-run", sleeve.label))?;
+        let sleeve_capital = capital * weight.weight;
+        let mut params = sleeve.params.clone();
+        params.insert("CAPITAL".to_string(), Value::from(sleeve_capital));
+
+        let result = backtests::execute_script(
+            server,
+            RunScriptParams {
+                strategy: Some(strategy),
+                script: None,
+                params,
+                profile: None,
+            },
+        )
+        .await
+        .with_context(|| format!("Re-running '{}' failed", sleeve.label))?
+        .response
+        .result;
+
+        sleeve_results.push(SleeveResult {
+            label: sleeve.label.clone(),
+            weight: weight.weight,
+            capital: sleeve_capital,
+            total_return_pct: result.metrics.total_return_pct,
+            sharpe: result.metrics.sharpe,
+            max_drawdown: result.metrics.max_drawdown,
+            trade_count: result.trade_count,
+        });
+        curves.push((sleeve_capital, daily_equity(&result.equity_curve)));
+        trade_log.extend(result.trade_log);
+        funded += sleeve_capital;
+    }
+
+    let equity_curve = combine_equity(&curves, capital - funded);
+    trade_log.sort_by_key(|t| t.entry_datetime);
+    let metrics = calculate_metrics(&equity_curve, &trade_log, capital, TRADING_DAYS_PER_YEAR)?;
+
+    Ok(CombinedBacktest {
+        method: optimization.method.clone(),
+        capital,
+        sleeves: sleeve_results,
+        metrics,
+        equity_curve,
     })
 }
 
+/// Daily returns of each series on the dates common to all of them, requiring
+/// more than `MIN_OBSERVATIONS` shared days.
+fn align_daily_returns(
+    series: &[Vec<(NaiveDate, f64)>],
+) -> Result<(Vec<NaiveDate>, Vec<Vec<f64>>)> {
+    let (dates, aligned) = equity::align_daily_returns(series);
+    if dates.len() <= MIN_OBSERVATIONS {
+        anyhow::bail!(
+            "Strategies share only {} trading days (need more than {MIN_OBSERVATIONS})",
+            dates.len()
+        );
+    }
+    Ok((dates, aligned))
+}
+
+/// Sum sleeve equity over the union of their dates, carrying each sleeve's
+/// last value forward (or its starting capital before its first point).
+fn combine_equity(curves: &[(f64, Vec<(NaiveDate, f64)>)], idle_cash: f64) -> Vec<EquityPoint> {
+    let dates: BTreeSet<NaiveDate> = curves
+        .iter()
+        .flat_map(|(_, c)| c.iter().map(|(d, _)| *d))
+        .collect();
+    let mut cursors = vec![0usize; curves.len()];
+    let mut last: Vec<f64> = curves.iter().map(|(start, _)| *start).collect();
+
+    dates
+        .into_iter()
+        .map(|date| {
+            let mut equity = idle_cash;
+            for (i, (_, curve)) in curves.iter().enumerate() {
+                while cursors[i] < curve.len() && curve[cursors[i]].0 <= date {
+                    last[i] = curve[cursors[i]].1;
+                    cursors[i] += 1;
+                }
+                equity += last[i];
+            }
+            EquityPoint {
+                datetime: date.and_time(NaiveTime::MIN),
+                equity,
+                unrealized: None,
+            }
+        })
+        .collect()
+}
+
 /// Compute portfolio volatility given weights and covariance matrix.
 fn portfolio_volatility(weights: &[f64], cov: &[Vec<f64>]) -> f64 {
     let n = weights.len();
@@ -283,6 +596,125 @@ fn max_sharpe_weights(expected_returns: &[f64], cov: &[Vec<f64>], risk_free: f64
     }
 }
 
+/// Maximum diversification portfolio: maximizes `w'σ / sqrt(w'Σw)`.
+///
+/// The unconstrained solution is `w* ∝ Σ^{-1} σ`; negative weights are clamped
+/// to zero (long-only) and the rest renormalized.
+fn max_diversification_weights(cov: &[Vec<f64>]) -> Vec<f64> {
+    let n = cov.len();
+    let vols: Vec<f64> = (0..n).map(|i| cov[i][i].max(0.0).sqrt()).collect();
+    let vol_vec = nalgebra::DVector::from_column_slice(&vols);
+    let cov_mat = nalgebra::DMatrix::from_fn(n, n, |i, j| cov[i][j]);
+
+    match cov_mat.try_inverse() {
+        Some(inv) => {
+            let w = &inv * &vol_vec;
+            let raw: Vec<f64> = w.iter().map(|&x| x.max(0.0)).collect();
+            let total: f64 = raw.iter().sum();
+            if total > 1e-15 {
+                raw.iter().map(|x| x / total).collect()
+            } else {
+                vec![1.0 / n as f64; n]
+            }
+        }
+        None => vec![1.0 / n as f64; n],
+    }
+}
+
+/// Hierarchical risk parity (López de Prado, 2016).
+///
+/// 1. Cluster assets by correlation distance `sqrt(0.5 · (1 − ρ))` with single
+///    linkage.
+/// 2. Walk the cluster tree top-down, splitting each node's weight between its
+///    two children in inverse proportion to their inverse-variance cluster
+///    variance.
+///
+/// Bisecting along the tree (rather than halving the quasi-diagonal order)
+/// keeps redundant strategies inside one branch so they share a budget. Needs
+/// no matrix inversion, so it stays stable for near-singular covariances.
+fn hrp_weights(cov: &[Vec<f64>]) -> Vec<f64> {
+    let mut weights = vec![1.0; cov.len()];
+    for (left, right) in hrp_merges(cov) {
+        let var_left = cluster_variance(cov, &left);
+        let var_right = cluster_variance(cov, &right);
+        let alpha = if var_left + var_right > 0.0 {
+            1.0 - var_left / (var_left + var_right)
+        } else {
+            0.5
+        };
+        for i in left {
+            weights[i] *= alpha;
+        }
+        for i in right {
+            weights[i] *= 1.0 - alpha;
+        }
+    }
+    weights
+}
+
+/// Single-linkage merges on correlation distance, as `(left, right)` member
+/// lists in merge order. Every internal node of the cluster tree appears once.
+fn hrp_merges(cov: &[Vec<f64>]) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let n = cov.len();
+    let dist = |i: usize, j: usize| {
+        let denom = (cov[i][i] * cov[j][j]).max(0.0).sqrt();
+        let corr = if denom > 0.0 {
+            (cov[i][j] / denom).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        (0.5 * (1.0 - corr)).max(0.0).sqrt()
+    };
+
+    let mut clusters: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+    let mut merges = Vec::with_capacity(n.saturating_sub(1));
+    while clusters.len() > 1 {
+        let mut best = (0, 1, f64::INFINITY);
+        for a in 0..clusters.len() {
+            for b in (a + 1)..clusters.len() {
+                let d = clusters[a]
+                    .iter()
+                    .flat_map(|&i| clusters[b].iter().map(move |&j| (i, j)))
+                    .map(|(i, j)| dist(i, j))
+                    .fold(f64::INFINITY, f64::min);
+                if d < best.2 {
+                    best = (a, b, d);
+                }
+            }
+        }
+        let right = clusters.remove(best.1);
+        merges.push((clusters[best.0].clone(), right.clone()));
+        clusters[best.0].extend(right);
+    }
+    merges
+}
+
+/// Variance of the inverse-variance-weighted portfolio of `members`.
+fn cluster_variance(cov: &[Vec<f64>], members: &[usize]) -> f64 {
+    let inv_var: Vec<f64> = members
+        .iter()
+        .map(|&i| {
+            if cov[i][i] > 1e-15 {
+                1.0 / cov[i][i]
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let total: f64 = inv_var.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let w: Vec<f64> = inv_var.iter().map(|v| v / total).collect();
+    let mut variance = 0.0;
+    for (a, &i) in members.iter().enumerate() {
+        for (b, &j) in members.iter().enumerate() {
+            variance += w[a] * w[b] * cov[i][j];
+        }
+    }
+    variance.max(0.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -428,4 +860,121 @@ mod tests {
         let w = max_sharpe_weights(&returns, &cov, 0.02);
         assert!(w[0] > w[1], "w0={} should be > w1={}", w[0], w[1]);
     }
+
+    // ─── max_diversification_weights ─────────────────────────────────
+
+    #[test]
+    fn max_diversification_uncorrelated_equal_risk_contribution() {
+        // Uncorrelated: w ∝ σ / σ² = 1/σ, same as inverse volatility
+        let cov = vec![vec![0.04, 0.0], vec![0.0, 0.16]];
+        let w = max_diversification_weights(&cov);
+        assert!((w[0] - 2.0 / 3.0).abs() < 1e-6, "w0={}", w[0]);
+        assert!((w[1] - 1.0 / 3.0).abs() < 1e-6, "w1={}", w[1]);
+    }
+
+    #[test]
+    fn max_diversification_long_only_sums_to_one() {
+        let cov = vec![
+            vec![0.04, 0.035, 0.005],
+            vec![0.035, 0.04, 0.02],
+            vec![0.005, 0.02, 0.16],
+        ];
+        let w = max_diversification_weights(&cov);
+        let sum: f64 = w.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-6, "sum={sum}");
+        assert!(w.iter().all(|&x| x >= 0.0), "w={w:?}");
+    }
+
+    // ─── hrp_weights ─────────────────────────────────────────────────
+
+    #[test]
+    fn hrp_merges_correlated_assets_first() {
+        // Assets 0 and 2 are highly correlated; 1 is independent
+        let cov = vec![
+            vec![0.04, 0.0, 0.036],
+            vec![0.0, 0.04, 0.0],
+            vec![0.036, 0.0, 0.04],
+        ];
+        let merges = hrp_merges(&cov);
+        assert_eq!(merges.len(), 2);
+        assert_eq!(merges[0], (vec![0], vec![2]));
+    }
+
+    #[test]
+    fn hrp_uncorrelated_matches_inverse_variance_pairs() {
+        // Two uncorrelated assets: bisection gives inverse-variance weights
+        let cov = vec![vec![0.04, 0.0], vec![0.0, 0.16]];
+        let w = hrp_weights(&cov);
+        assert!((w[0] - 0.8).abs() < 1e-10, "w0={}", w[0]);
+        assert!((w[1] - 0.2).abs() < 1e-10, "w1={}", w[1]);
+    }
+
+    #[test]
+    fn hrp_underweights_redundant_cluster() {
+        // Two near-duplicate strategies and one diversifier, all equal vol:
+        // the diversifier should receive more than either duplicate.
+        let cov = vec![
+            vec![0.04, 0.039, 0.0],
+            vec![0.039, 0.04, 0.0],
+            vec![0.0, 0.0, 0.04],
+        ];
+        let w = hrp_weights(&cov);
+        let sum: f64 = w.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-10, "sum={sum}");
+        assert!(w[2] > w[0] && w[2] > w[1], "w={w:?}");
+    }
+
+    // ─── strategy alignment ──────────────────────────────────────────
+
+    fn daily(start: NaiveDate, equity: &[f64]) -> Vec<(NaiveDate, f64)> {
+        equity
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| (start + chrono::Duration::days(i as i64), e))
+            .collect()
+    }
+
+    #[test]
+    fn align_uses_common_dates_only() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let a: Vec<f64> = (0..40).map(|i| 100.0 + f64::from(i)).collect();
+        let b: Vec<f64> = (0..40).map(|i| 200.0 - f64::from(i)).collect();
+        // b starts five days later, so only 35 dates overlap
+        let series = vec![
+            daily(start, &a),
+            daily(start + chrono::Duration::days(5), &b),
+        ];
+        let (dates, aligned) = align_daily_returns(&series).unwrap();
+        assert_eq!(dates.len(), 35);
+        assert_eq!(aligned[0].len(), 34);
+        assert_eq!(aligned[1].len(), 34);
+        assert!((aligned[0][0] - (106.0 / 105.0 - 1.0)).abs() < 1e-12);
+        assert!((aligned[1][0] - (199.0 / 200.0 - 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn align_rejects_short_overlap() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let flat = vec![100.0; 40];
+        let series = vec![
+            daily(start, &flat),
+            daily(start + chrono::Duration::days(30), &flat),
+        ];
+        assert!(align_daily_returns(&series).is_err());
+    }
+
+    #[test]
+    fn combine_equity_forward_fills_and_adds_idle_cash() {
+        let d = |day: u32| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let curves = vec![
+            (600.0, vec![(d(1), 610.0), (d(3), 620.0)]),
+            (400.0, vec![(d(2), 390.0)]),
+        ];
+        let combined = combine_equity(&curves, 10.0);
+        let equity: Vec<f64> = combined.iter().map(|p| p.equity).collect();
+        assert_eq!(equity.len(), 3);
+        assert!((equity[0] - 1020.0).abs() < 1e-10);
+        assert!((equity[1] - 1010.0).abs() < 1e-10);
+        assert!((equity[2] - 1020.0).abs() < 1e-10);
+    }
 }
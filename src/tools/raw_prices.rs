@@ -302,7 +302,7 @@ pub async fn load_and_execute(
         "get_raw_prices execute complete"
     );
 
-    let json_size = serde_json::to_string(&resp).map(|s| s.len()).unwrap_or(0);
+    let json_size = serde_json::to_string(&resp).map_or(0, |s| s.len());
     tracing::debug!(
         elapsed_ms = t0.elapsed().as_millis(),
         response_kb = json_size / 1024,
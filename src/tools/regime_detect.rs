@@ -1,6 +1,10 @@
-//! Market regime detection via volatility clustering or trend state analysis.
+//! Market regime detection via volatility clustering, trend state analysis,
+//! Gaussian HMMs (univariate on returns, or multivariate on returns,
+//! realized vol, VIX level and VIX term-structure slope), or a Markov-switching
+//! AR(1) regression of returns.
 
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::data::cache::CachedStore;
@@ -10,7 +14,18 @@ use crate::tools::ai_format;
 use crate::tools::ai_helpers::{
     compute_returns, compute_years_cutoff, epoch_to_timestamp_string, subsample_to_max,
 };
-use crate::tools::response_types::{RegimeDetectResponse, RegimeInfo, RegimeSeriesPoint};
+use crate::tools::response_types::{
+    RegimeDetectResponse, RegimeInfo, RegimeModelCandidate, RegimeSeriesPoint,
+};
+
+/// Symbol whose close is used as the VIX-level feature.
+const VIX_SYMBOL: &str = "VIX";
+
+/// Symbol whose close, relative to VIX, gives the term-structure slope feature.
+const VIX3M_SYMBOL: &str = "VIX3M";
+
+/// Minimum share of bars a reference feature must cover to be included.
+const MIN_FEATURE_COVERAGE: f64 = 0.5;
 
 /// Execute the `regime_detect` analysis.
 #[allow(clippy::too_many_lines)]
@@ -18,7 +33,7 @@ pub async fn execute(
     cache: &Arc<CachedStore>,
     symbol: &str,
     method: RegimeMethod,
-    n_regimes: usize,
+    mut n_regimes: usize,
     years: u32,
     lookback_window: usize,
     interval: crate::engine::types::Interval,
@@ -45,6 +60,8 @@ pub async fn execute(
     let min_bars = match method {
         RegimeMethod::TrendState => lookback_window * 3 + 2,
         RegimeMethod::Hmm => 50, // HMM needs enough observations for EM convergence
+        RegimeMethod::MultivariateHmm => lookback_window + 100,
+        RegimeMethod::MarkovSwitching => 60,
         RegimeMethod::VolatilityCluster => lookback_window + 2,
     };
     if prices.len() < min_bars {
@@ -58,7 +75,10 @@ pub async fn execute(
     // with SMA arrays (critical for classify_by_trend).
     let (returns, dates) = compute_returns(prices);
 
-    let (regime_labels, regime_names, hmm_params) = match method {
+    let mut model_selection = Vec::new();
+    let mut features = Vec::new();
+    let mut method_findings = Vec::new();
+    let (regime_labels, regime_names, emissions) = match method {
         RegimeMethod::VolatilityCluster => {
             let (l, n) = classify_by_volatility(&returns, lookback_window, n_regimes, interval);
             (l, n, None)
@@ -67,7 +87,62 @@ pub async fn execute(
             let (l, n) = classify_by_trend(prices, lookback_window, n_regimes);
             (l, n, None)
         }
-        RegimeMethod::Hmm => classify_by_hmm(&returns, n_regimes),
+        RegimeMethod::Hmm => {
+            let (l, n, hmm) = classify_by_hmm(&returns, n_regimes);
+            let emissions = hmm.map(|hmm| {
+                hmm.means
+                    .iter()
+                    .zip(&hmm.variances)
+                    .map(|(&m, &v)| (m, v.sqrt()))
+                    .collect()
+            });
+            (l, n, emissions)
+        }
+        RegimeMethod::MultivariateHmm => {
+            let vix = load_reference_closes(cache, VIX_SYMBOL, &cutoff_str, interval).await;
+            let vix3m = load_reference_closes(cache, VIX3M_SYMBOL, &cutoff_str, interval).await;
+            let regime_features = build_features(
+                &returns,
+                &dates,
+                lookback_window,
+                interval,
+                vix.as_ref(),
+                vix3m.as_ref(),
+            );
+            let fit =
+                classify_by_multivariate_hmm(&regime_features, n_regimes).with_context(|| {
+                    format!(
+                        "Not enough complete feature rows for {upper} to fit a multivariate HMM"
+                    )
+                })?;
+            n_regimes = fit.names.len();
+            model_selection = fit.candidates;
+            features = regime_features.names;
+            (fit.labels, fit.names, Some(fit.emissions))
+        }
+        RegimeMethod::MarkovSwitching => {
+            let (fit, coefficients) = classify_by_markov_switching(&returns, n_regimes)
+                .with_context(|| {
+                    format!("Not enough returns for {upper} to fit a Markov-switching regression")
+                })?;
+            n_regimes = fit.names.len();
+            model_selection = fit.candidates;
+            method_findings = fit
+                .names
+                .iter()
+                .zip(&coefficients)
+                .zip(&fit.emissions)
+                .map(|((name, beta), (_, sd))| {
+                    format!(
+                        "{name}: r(t) = {:.4}% + {:.3}·r(t-1), residual σ {:.3}%",
+                        beta[0] * 100.0,
+                        beta[1],
+                        sd * 100.0
+                    )
+                })
+                .collect();
+            (fit.labels, fit.names, Some(fit.emissions))
+        }
     };
 
     // Build regime series (skip leading NaN window)
@@ -139,11 +214,9 @@ pub async fn execute(
             .collect();
         let mean_vol = stats::mean(&regime_vols);
 
-        let (em, es) = hmm_params.as_ref().map_or((None, None), |hmm| {
-            (
-                Some(hmm.means[regime_idx] * 100.0),
-                Some(hmm.variances[regime_idx].sqrt() * 100.0),
-            )
+        let (em, es) = emissions.as_ref().map_or((None, None), |emissions| {
+            let (mean, std) = emissions[regime_idx];
+            (Some(mean * 100.0), Some(std * 100.0))
         });
 
         regimes.push(RegimeInfo {
@@ -165,7 +238,7 @@ pub async fn execute(
     // Transition matrix
     let transition_matrix = compute_transition_matrix(&regime_labels, n_regimes);
 
-    Ok(ai_format::format_regime_detect(
+    let mut response = ai_format::format_regime_detect(
         &upper,
         method_str,
         n_regimes,
@@ -174,7 +247,250 @@ pub async fn execute(
         regimes,
         transition_matrix,
         regime_series,
-    ))
+    );
+    if !features.is_empty() {
+        response
+            .key_findings
+            .push(format!("Multivariate features: {}", features.join(", ")));
+        if !features.iter().any(|f| f == "vix") {
+            response.key_findings.push(format!(
+                "{VIX_SYMBOL} data unavailable for these dates — fit on returns and realized vol only"
+            ));
+        }
+    }
+    if let Some(best) = model_selection
+        .iter()
+        .min_by(|a, b| a.bic.total_cmp(&b.bic))
+    {
+        response.key_findings.push(format!(
+            "BIC selected {} states ({})",
+            best.n_states,
+            model_selection
+                .iter()
+                .map(|c| format!("k={}: {:.1}", c.n_states, c.bic))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    response.key_findings.extend(method_findings);
+    response.features = features;
+    response.model_selection = model_selection;
+    Ok(response)
+}
+
+/// Load a reference series (e.g. VIX) as date → close, or `None` if unavailable.
+async fn load_reference_closes(
+    cache: &Arc<CachedStore>,
+    symbol: &str,
+    cutoff: &str,
+    interval: crate::engine::types::Interval,
+) -> Option<HashMap<i64, f64>> {
+    let resp = crate::tools::raw_prices::load_and_execute(
+        cache,
+        symbol,
+        Some(cutoff),
+        None,
+        None,
+        interval,
+        None,
+    )
+    .await
+    .ok()?;
+    let closes: HashMap<i64, f64> = resp
+        .prices
+        .iter()
+        .filter(|p| p.close > 0.0)
+        .map(|p| (p.date, p.close))
+        .collect();
+    (!closes.is_empty()).then_some(closes)
+}
+
+/// Feature matrix for `multivariate_hmm`, aligned to the return series.
+struct RegimeFeatures {
+    names: Vec<String>,
+    /// One row per return; `None` where any feature is missing.
+    rows: Vec<Option<Vec<f64>>>,
+}
+
+/// Assemble return, realized vol and (when they cover enough bars) VIX level
+/// and VIX3M/VIX term-structure slope features.
+fn build_features(
+    returns: &[f64],
+    dates: &[i64],
+    lookback: usize,
+    interval: crate::engine::types::Interval,
+    vix: Option<&HashMap<i64, f64>>,
+    vix3m: Option<&HashMap<i64, f64>>,
+) -> RegimeFeatures {
+    let annualization = interval.bars_per_year().sqrt();
+    let mut names = vec!["return".to_string(), "realized_vol".to_string()];
+    let mut columns = vec![
+        returns.to_vec(),
+        stats::rolling_apply(returns, lookback, |w| stats::std_dev(w) * annualization),
+    ];
+
+    let covered = |column: &[f64]| {
+        let finite = column.iter().filter(|v| v.is_finite()).count();
+        !column.is_empty() && finite as f64 / column.len() as f64 >= MIN_FEATURE_COVERAGE
+    };
+    if let Some(vix) = vix {
+        let level: Vec<f64> = dates
+            .iter()
+            .map(|d| vix.get(d).copied().unwrap_or(f64::NAN))
+            .collect();
+        if covered(&level) {
+            if let Some(vix3m) = vix3m {
+                let slope: Vec<f64> = dates
+                    .iter()
+                    .zip(&level)
+                    .map(|(d, &v)| vix3m.get(d).map_or(f64::NAN, |&far| far / v - 1.0))
+                    .collect();
+                if covered(&slope) {
+                    names.push("term_slope".to_string());
+                    columns.push(slope);
+                }
+            }
+            names.insert(2, "vix".to_string());
+            columns.insert(2, level);
+        }
+    }
+
+    let rows = (0..returns.len())
+        .map(|i| {
+            let row: Vec<f64> = columns.iter().map(|c| c[i]).collect();
+            row.iter().all(|v| v.is_finite()).then_some(row)
+        })
+        .collect();
+    RegimeFeatures { names, rows }
+}
+
+/// Outcome of a BIC-selected regime model fit.
+struct SelectedFit {
+    labels: Vec<usize>,
+    names: Vec<String>,
+    /// Per-state return emission `(mean, std)` in return units.
+    emissions: Vec<(f64, f64)>,
+    candidates: Vec<RegimeModelCandidate>,
+}
+
+/// Standardize the features, fit multivariate HMMs for 2..=`max_regimes`
+/// states, keep the lowest-BIC model and Viterbi-decode every complete row.
+///
+/// Unclassified rows get the sentinel label `names.len()`.
+fn classify_by_multivariate_hmm(
+    features: &RegimeFeatures,
+    max_regimes: usize,
+) -> Option<SelectedFit> {
+    let complete: Vec<(usize, &Vec<f64>)> = features
+        .rows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, row)| row.as_ref().map(|r| (i, r)))
+        .collect();
+    let d = features.names.len();
+    let (centers, scales): (Vec<f64>, Vec<f64>) = (0..d)
+        .map(|f| {
+            let column: Vec<f64> = complete.iter().map(|(_, r)| r[f]).collect();
+            let sd = stats::std_dev(&column);
+            (stats::mean(&column), if sd > 0.0 { sd } else { 1.0 })
+        })
+        .unzip();
+    let standardized: Vec<Vec<f64>> = complete
+        .iter()
+        .map(|(_, r)| {
+            r.iter()
+                .zip(centers.iter().zip(&scales))
+                .map(|(v, (c, s))| (v - c) / s)
+                .collect()
+        })
+        .collect();
+
+    let (hmm, candidates) =
+        crate::engine::hmm::select_by_bic(&standardized, 2..=max_regimes.max(2))?;
+    let decoded = crate::engine::hmm::viterbi_multivariate(&hmm, &standardized);
+    let mut labels = vec![hmm.n_states; features.rows.len()];
+    for ((original_idx, _), state) in complete.iter().zip(decoded) {
+        labels[*original_idx] = state;
+    }
+
+    // Realized vol (feature 1) in z-units drives the volatility half of each name
+    let vol_levels: Vec<f64> = hmm.means.iter().map(|m| m[1]).collect();
+    let emissions = hmm
+        .means
+        .iter()
+        .zip(&hmm.covariances)
+        .map(|(m, cov)| {
+            (
+                centers[0] + scales[0] * m[0],
+                scales[0] * cov[(0, 0)].max(0.0).sqrt(),
+            )
+        })
+        .collect();
+    Some(SelectedFit {
+        labels,
+        names: regime_names(&vol_levels),
+        emissions,
+        candidates: candidates
+            .into_iter()
+            .map(|c| RegimeModelCandidate {
+                n_states: c.n_states,
+                log_likelihood: c.log_likelihood,
+                bic: c.bic,
+            })
+            .collect(),
+    })
+}
+
+/// Fit Markov-switching AR(1) regressions `r(t) = c + φ·r(t-1)` for
+/// 2..=`max_regimes` states, keep the lowest-BIC model and Viterbi-decode
+/// every bar with a finite return and previous return.
+///
+/// Also returns each state's `[c, φ]`. Emissions are the state's long-run mean
+/// `c / (1 − φ)` (or `c` when the state is not stationary) and residual std.
+/// Unclassified bars get the sentinel label `names.len()`.
+fn classify_by_markov_switching(
+    returns: &[f64],
+    max_regimes: usize,
+) -> Option<(SelectedFit, Vec<Vec<f64>>)> {
+    let valid: Vec<usize> = (1..returns.len())
+        .filter(|&i| returns[i].is_finite() && returns[i - 1].is_finite())
+        .collect();
+    let y: Vec<f64> = valid.iter().map(|&i| returns[i]).collect();
+    let x: Vec<Vec<f64>> = valid.iter().map(|&i| vec![1.0, returns[i - 1]]).collect();
+
+    let (model, candidates) =
+        crate::engine::hmm::select_markov_switching_by_bic(&y, &x, 2..=max_regimes.max(2))?;
+    let decoded = crate::engine::hmm::viterbi_markov_switching(&model, &y, &x);
+    let mut labels = vec![model.n_states; returns.len()];
+    for (&original_idx, state) in valid.iter().zip(decoded) {
+        labels[original_idx] = state;
+    }
+
+    let stds: Vec<f64> = model.variances.iter().map(|v| v.sqrt()).collect();
+    let emissions = model
+        .coefficients
+        .iter()
+        .zip(&stds)
+        .map(|(beta, &sd)| {
+            let (c, phi) = (beta[0], beta[1]);
+            let mean = if phi.abs() < 1.0 { c / (1.0 - phi) } else { c };
+            (mean, sd)
+        })
+        .collect();
+    let fit = SelectedFit {
+        labels,
+        names: regime_names(&stds),
+        emissions,
+        candidates: candidates
+            .into_iter()
+            .map(|c| RegimeModelCandidate {
+                n_states: c.n_states,
+                log_likelihood: c.log_likelihood,
+                bic: c.bic,
+            })
+            .collect(),
+    };
+    Some((fit, model.coefficients))
 }
 
 /// Classify each bar using a Gaussian Hidden Markov Model.
@@ -226,9 +542,17 @@ fn classify_by_hmm(
 /// Names reflect both the mean (return direction) and volatility (emission std dev)
 /// derived from the actual fitted model, rather than hardcoded assumptions.
 fn derive_hmm_names(hmm: &crate::engine::hmm::GaussianHmm) -> Vec<String> {
-    let k = hmm.n_states;
-    // States are already sorted by ascending mean
     let stds: Vec<f64> = hmm.variances.iter().map(|v| v.sqrt()).collect();
+    regime_names(&stds)
+}
+
+/// Name states sorted by ascending mean return from their relative volatility.
+///
+/// `vol_levels[i]` is any volatility measure for state `i` (emission std dev,
+/// mean realized vol, ...); only its ordering across states matters.
+fn regime_names(vol_levels: &[f64]) -> Vec<String> {
+    let k = vol_levels.len();
+    let stds = vol_levels;
 
     (0..k)
         .map(|i| {
@@ -811,4 +1135,123 @@ mod tests {
             assert!(l <= 2, "label {l} out of range");
         }
     }
+
+    /// Calm-rally / volatile-selloff blocks with a VIX level that tracks the regime.
+    fn regime_feature_inputs(n: usize) -> (Vec<f64>, Vec<i64>, HashMap<i64, f64>) {
+        let base_epoch: i64 = 1_577_836_800;
+        let mut seed: u64 = 3;
+        let mut noise = || -> f64 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let mut returns = Vec::with_capacity(n);
+        let mut dates = Vec::with_capacity(n);
+        let mut vix = HashMap::new();
+        for i in 0..n {
+            let volatile = (i / 80) % 2 == 1;
+            let date = base_epoch + i as i64 * 86400;
+            let (drift, scale, level) = if volatile {
+                (-0.003, 0.05, 32.0)
+            } else {
+                (0.001, 0.01, 13.0)
+            };
+            returns.push(drift + noise() * scale);
+            dates.push(date);
+            vix.insert(date, level + noise() * 2.0);
+        }
+        (returns, dates, vix)
+    }
+
+    #[test]
+    fn test_build_features_includes_covered_reference_series() {
+        let (returns, dates, vix) = regime_feature_inputs(200);
+        let vix3m: HashMap<i64, f64> = vix.iter().map(|(&d, &v)| (d, v * 1.05)).collect();
+        let daily = crate::engine::types::Interval::Daily;
+
+        let features = build_features(&returns, &dates, 10, daily, Some(&vix), Some(&vix3m));
+        assert_eq!(
+            features.names,
+            vec!["return", "realized_vol", "vix", "term_slope"]
+        );
+        // Rolling vol warmup leaves the first rows incomplete
+        assert!(features.rows[..9].iter().all(Option::is_none));
+        let row = features.rows[50].as_ref().unwrap();
+        assert!((row[3] - 0.05).abs() < 1e-9);
+
+        // A reference series covering too few bars is dropped
+        let sparse: HashMap<i64, f64> = vix
+            .iter()
+            .filter(|(&d, _)| d < dates[40])
+            .map(|(&d, &v)| (d, v))
+            .collect();
+        let features = build_features(&returns, &dates, 10, daily, Some(&sparse), None);
+        assert_eq!(features.names, vec!["return", "realized_vol"]);
+    }
+
+    #[test]
+    fn test_multivariate_hmm_selects_state_count_by_bic() {
+        let (returns, dates, vix) = regime_feature_inputs(640);
+        let features = build_features(
+            &returns,
+            &dates,
+            10,
+            crate::engine::types::Interval::Daily,
+            Some(&vix),
+            None,
+        );
+        let fit = classify_by_multivariate_hmm(&features, 3).expect("fit");
+        assert_eq!(fit.candidates.len(), 2);
+        let k = fit.names.len();
+        let best = fit
+            .candidates
+            .iter()
+            .min_by(|a, b| a.bic.total_cmp(&b.bic))
+            .unwrap();
+        assert_eq!(best.n_states, k);
+        assert_eq!(fit.emissions.len(), k);
+        assert_eq!(fit.labels.len(), returns.len());
+        assert!(
+            fit.labels[..9].iter().all(|&l| l == k),
+            "warmup must be unclassified"
+        );
+
+        // The lowest-return state is the volatile one
+        let volatile_share = |state: usize| {
+            let members: Vec<usize> = (0..returns.len())
+                .filter(|&i| fit.labels[i] == state)
+                .collect();
+            members.iter().filter(|&&i| (i / 80) % 2 == 1).count() as f64 / members.len() as f64
+        };
+        assert!(
+            volatile_share(0) > 0.9,
+            "state 0 should be the selloff regime"
+        );
+        assert!(fit.names[0].ends_with("High Vol"), "names: {:?}", fit.names);
+        assert!(fit.emissions[0].1 > fit.emissions[k - 1].1);
+    }
+
+    #[test]
+    fn test_markov_switching_separates_selloff_regime() {
+        let (mut returns, _, _) = regime_feature_inputs(640);
+        returns[100] = f64::NAN;
+        let (fit, coefficients) = classify_by_markov_switching(&returns, 3).expect("fit");
+        let k = fit.names.len();
+        assert_eq!(fit.candidates.len(), 2);
+        assert_eq!(coefficients.len(), k);
+        assert!(coefficients.iter().all(|beta| beta.len() == 2));
+        assert_eq!(fit.labels.len(), returns.len());
+        // No previous return for the first bar, and none around the gap
+        assert_eq!(fit.labels[0], k);
+        assert_eq!(fit.labels[100], k);
+        assert_eq!(fit.labels[101], k);
+
+        // The lowest-drift state is the volatile selloff
+        let members: Vec<usize> = (0..returns.len()).filter(|&i| fit.labels[i] == 0).collect();
+        let volatile = members.iter().filter(|&&i| (i / 80) % 2 == 1).count();
+        assert!(
+            volatile as f64 / members.len() as f64 > 0.9,
+            "state 0 should be the selloff regime"
+        );
+        assert!(fit.names[0].ends_with("High Vol"), "names: {:?}", fit.names);
+    }
 }
@@ -102,3 +102,125 @@ pub struct HypothesisResponse {
     pub key_findings: Vec<String>,
     pub suggested_next_steps: Vec<String>,
 }
+
+/// Default number of top hypotheses carried into validation.
+fn default_validation_top_n() -> usize {
+    3
+}
+
+/// Default starting capital for synthesized strategies.
+fn default_validation_capital() -> f64 {
+    100_000.0
+}
+
+/// Default permutation count for the significance gate.
+fn default_validation_permutations() -> usize {
+    200
+}
+
+/// Parameters for `validate_hypotheses`.
+#[derive(Debug, Clone, Deserialize, JsonSchema, Validate)]
+#[garde(context(()))]
+pub struct HypothesisValidationParams {
+    /// Hypothesis scan settings, as for `generate_hypotheses`
+    #[serde(flatten)]
+    #[garde(dive)]
+    pub scan: HypothesisParams,
+    /// Number of top-ranked hypotheses to turn into strategies (1-10, default: 3)
+    #[serde(default = "default_validation_top_n")]
+    #[garde(range(min = 1, max = 10))]
+    pub top_n: usize,
+    /// Starting capital for each synthesized strategy (default: 100000)
+    #[serde(default = "default_validation_capital")]
+    #[garde(range(min = 1000.0))]
+    pub capital: f64,
+    /// Permutations for the pipeline's significance gate (1-100000, default: 200)
+    #[serde(default = "default_validation_permutations")]
+    #[garde(range(min = 1, max = 100_000))]
+    pub num_permutations: usize,
+    /// Options position opened on each signal instead of trading shares,
+    /// e.g. `short_put(0.30, 45)`. Omit to trade the underlying in the
+    /// direction of the hypothesis' effect.
+    #[serde(default)]
+    #[garde(pattern(r"^[a-z_]+\([^\n]*\)$"))]
+    pub options_expression: Option<String>,
+}
+
+/// Outcome of validating one hypothesis.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum HypothesisVerdict {
+    /// Every gate passed and the walk-forward Sharpe is positive.
+    Validated,
+    /// A gate failed, or the out-of-sample Sharpe is not positive.
+    Rejected,
+    /// The strategy was synthesized but the pipeline errored.
+    Error,
+    /// The signal formula has no DSL equivalent, so no strategy was built.
+    Unsupported,
+}
+
+/// One hypothesis carried through strategy synthesis and baseline validation.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HypothesisValidationRow {
+    /// Position in the ranked table (1 = strongest)
+    pub rank: usize,
+    /// Scanning dimension that produced the hypothesis
+    pub dimension: String,
+    /// Human-readable description of the hypothesis
+    pub description: String,
+    /// Signal the strategy enters on
+    pub signal_spec: SignalSpec,
+    /// Forward return horizon (trading days) the hypothesis targets
+    pub forward_horizon: usize,
+    /// BH-FDR adjusted p-value from the hypothesis scan
+    pub adjusted_p_value: f64,
+    /// Mean forward return from the hypothesis scan
+    pub effect_size: f64,
+    /// Stored strategy ID (absent when the signal is unsupported)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_id: Option<String>,
+    /// Stored strategy display name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_name: Option<String>,
+    /// How the strategy trades the signal: `"long"`, `"short"` or `"options"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<String>,
+    /// Persisted sweep ID of the pipeline run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sweep_id: Option<String>,
+    /// Sharpe of the best holding period in the sweep
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_sharpe: Option<f64>,
+    /// Permutation-test p-value of the best holding period
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permutation_p_value: Option<f64>,
+    /// Walk-forward (out-of-sample) Sharpe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oos_sharpe: Option<f64>,
+    /// Pipeline gates that passed
+    pub gates_passed: usize,
+    /// Pipeline gates that were evaluated
+    pub gates_evaluated: usize,
+    pub verdict: HypothesisVerdict,
+    /// Why the hypothesis was rejected, errored or unsupported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// AI-enriched response for `validate_hypotheses`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HypothesisValidationResponse {
+    pub summary: String,
+    pub symbols: Vec<String>,
+    /// Hypotheses that survived the scan (before `top_n` was applied)
+    pub hypotheses_found: usize,
+    /// Ranked validation table (validated first, then by gates passed and OOS Sharpe)
+    pub results: Vec<HypothesisValidationRow>,
+    pub key_findings: Vec<String>,
+    pub suggested_next_steps: Vec<String>,
+    /// Total wall-clock time in milliseconds
+    pub total_duration_ms: u64,
+}
@@ -5,6 +5,7 @@ pub mod data;
 pub mod forward_test;
 pub mod hypothesis;
 pub mod inputs;
+pub mod overfitting;
 pub mod pipeline;
 pub mod risk;
 pub mod stats;
@@ -17,6 +18,7 @@ pub use data::*;
 pub use forward_test::*;
 pub use hypothesis::*;
 pub use inputs::*;
+pub use overfitting::*;
 pub use pipeline::*;
 pub use risk::*;
 pub use stats::*;
@@ -0,0 +1,52 @@
+//! Response types for backtest overfitting diagnostics.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::sweep::SweepResponse;
+
+/// Probability of Backtest Overfitting estimated by combinatorially symmetric
+/// cross-validation (CSCV) over a sweep's per-combo return series.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CscvResponse {
+    /// Number of contiguous time blocks the returns were split into.
+    pub n_blocks: usize,
+    /// Number of train/test block combinations evaluated.
+    pub n_splits: usize,
+    /// Number of parameter combos compared.
+    pub n_strategies: usize,
+    /// Number of aligned return observations per combo.
+    pub n_observations: usize,
+    /// Probability of Backtest Overfitting: share of splits in which the
+    /// in-sample best combo ranks at or below the out-of-sample median.
+    pub pbo: f64,
+    /// Slope of out-of-sample on in-sample Sharpe of the in-sample best combo
+    /// across splits (below 1 means performance degrades out of sample).
+    pub degradation_slope: f64,
+    /// Intercept of the degradation regression.
+    pub degradation_intercept: f64,
+    /// R² of the degradation regression.
+    pub degradation_r2: f64,
+    /// Share of splits in which the in-sample best combo loses money out of sample.
+    pub prob_oos_loss: f64,
+    /// Median logit of the in-sample best combo's relative out-of-sample rank
+    /// (negative means it typically underperforms the median combo).
+    pub median_logit: f64,
+    /// PBO above which the sweep is rejected.
+    pub pbo_threshold: f64,
+    /// Whether `pbo` exceeds `pbo_threshold`.
+    pub rejected: bool,
+}
+
+/// Result of the `overfitting_check` workflow: a persisted sweep plus its CSCV
+/// diagnostics, without the walk-forward and Monte Carlo stages.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct OverfittingCheckResponse {
+    pub summary: String,
+    pub sweep_id: String,
+    pub sweep: SweepResponse,
+    pub cscv: CscvResponse,
+    pub key_findings: Vec<String>,
+    pub suggested_next_steps: Vec<String>,
+    pub total_duration_ms: u64,
+}
@@ -6,6 +6,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::overfitting::CscvResponse;
 use super::risk::MonteCarloResponse;
 use super::sweep::SweepResponse;
 use super::walk_forward::WalkForwardResponse;
@@ -29,8 +30,8 @@ pub enum StageStatus {
 /// green (completed/passed), yellow (skipped), red (failed/error or gate not passed).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StageInfo {
-    /// Stage identifier: `"sweep"`, `"significance_gate"`, `"walk_forward"`,
-    /// `"oos_data_gate"`, `"monte_carlo"`.
+    /// Stage identifier: `"sweep"`, `"significance_gate"`, `"overfitting_gate"`,
+    /// `"walk_forward"`, `"oos_data_gate"`, `"monte_carlo"`.
     pub name: String,
     /// Current status of this stage or gate.
     pub status: StageStatus,
@@ -64,6 +65,10 @@ pub struct PipelineResponse {
     pub sweep: SweepResponse,
 
     // -- Pipeline stages (present only if gates passed) --
+    /// CSCV overfitting analysis of the sweep. Present when the significance
+    /// gate passes and the sweep has enough combos and history to split.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cscv: Option<CscvResponse>,
     /// Walk-forward validation result. Present when the significance gate passes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub walk_forward: Option<WalkForwardResponse>,
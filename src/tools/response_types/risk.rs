@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 
 use super::common::CorrelationEntry;
 use super::stats::HistogramBin;
+use crate::engine::types::{BacktestResult, EquityPoint, PerformanceMetrics};
+use crate::scripting::portfolio::SleeveAttribution;
 
 // ── Drawdown analysis types ──────────────────────────────────────────────
 
@@ -175,6 +177,38 @@ pub struct DrawdownDistribution {
     pub worst: f64,
 }
 
+/// Longest underwater stretch (peak to recovery) distribution, in simulation
+/// steps (days or trades).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecoveryDistribution {
+    pub median: f64,
+    pub percentile_95: f64,
+    pub worst: f64,
+    /// Probability a path ends below its running peak
+    pub prob_unrecovered: f64,
+}
+
+/// Path-level risk from resampling a strategy's own trades or returns.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StrategyRiskAnalysis {
+    /// Source of the simulated series: `run:<id>` or `inline`
+    pub source: String,
+    /// Resampling method: `block_bootstrap`, `trade_shuffle`, or `trade_bootstrap`
+    pub method: String,
+    /// Unit of one simulation step: "days" or "trades"
+    pub step_unit: String,
+    /// Steps simulated per path
+    pub steps: usize,
+    pub n_trades: usize,
+    pub n_daily_returns: usize,
+    pub position_scale: f64,
+    /// Loss from starting capital treated as ruin, as a fraction
+    pub ruin_threshold: f64,
+    /// Probability equity touches the ruin level at any point on the path
+    pub risk_of_ruin: f64,
+    pub time_to_recover: RecoveryDistribution,
+}
+
 /// AI-enriched response for `monte_carlo`.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MonteCarloResponse {
@@ -188,6 +222,9 @@ pub struct MonteCarloResponse {
     pub drawdown_distribution: DrawdownDistribution,
     /// Terminal equity distribution histogram
     pub terminal_histogram: Vec<HistogramBin>,
+    /// Present when simulating a backtest run or inline result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy_risk: Option<StrategyRiskAnalysis>,
     pub key_findings: Vec<String>,
     pub suggested_next_steps: Vec<String>,
 }
@@ -249,7 +286,8 @@ pub struct OptimalWeight {
 /// Portfolio optimization result for one method.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OptimizationResult {
-    /// Method name (e.g., "`risk_parity`", "`min_variance`", "`max_sharpe`")
+    /// Method name (e.g., "`risk_parity`", "`min_variance`", "`max_sharpe`",
+    /// "`max_diversification`", "`hrp`")
     pub method: String,
     pub weights: Vec<OptimalWeight>,
     /// Expected annualized return
@@ -272,6 +310,9 @@ pub struct PortfolioOptimizeResponse {
     pub asset_stats: Vec<AssetStats>,
     /// Optimization results for each method
     pub optimizations: Vec<OptimizationResult>,
+    /// Combined backtest re-run with one method's weights (strategy mode only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combined_backtest: Option<CombinedBacktest>,
     pub key_findings: Vec<String>,
     pub suggested_next_steps: Vec<String>,
 }
@@ -285,6 +326,51 @@ pub struct AssetStats {
     pub sharpe: f64,
 }
 
+/// One strategy sleeve of a combined backtest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SleeveResult {
+    pub label: String,
+    pub weight: f64,
+    /// Capital the sleeve was re-run with (`capital × weight`)
+    pub capital: f64,
+    pub total_return_pct: f64,
+    pub sharpe: f64,
+    pub max_drawdown: f64,
+    pub trade_count: usize,
+}
+
+/// Strategies re-run with optimized capital weights and summed into one book.
+///
+/// Each sleeve runs with its own slice of capital; sleeves do not compete for
+/// cash intra-period.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CombinedBacktest {
+    /// Optimization method whose weights were used
+    pub method: String,
+    pub capital: f64,
+    pub sleeves: Vec<SleeveResult>,
+    pub metrics: PerformanceMetrics,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// AI-enriched response for `portfolio_backtest`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PortfolioBacktestResponse {
+    pub summary: String,
+    /// Allocation rule used for target weights
+    pub allocation: String,
+    /// Rebalance frequency
+    pub rebalance: String,
+    /// Number of rebalances performed
+    pub rebalances: usize,
+    /// Per-sleeve P&L attribution
+    pub sleeves: Vec<SleeveAttribution>,
+    /// Consolidated result across all sleeves
+    pub result: BacktestResult,
+    pub key_findings: Vec<String>,
+    pub suggested_next_steps: Vec<String>,
+}
+
 // ── Benchmark-relative metrics types ──────────────────────────────────────
 
 /// AI-enriched response for `benchmark_analysis`.
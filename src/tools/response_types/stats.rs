@@ -206,10 +206,12 @@ pub struct RegimeInfo {
     pub mean_return: f64,
     pub std_dev: f64,
     pub mean_vol: f64,
-    /// HMM emission mean (only for method="hmm").
+    /// HMM return emission mean (only for `method="hmm"` or `"multivariate_hmm"`;
+    /// the long-run mean of the state's regression for `"markov_switching"`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub emission_mean: Option<f64>,
-    /// HMM emission std dev (only for method="hmm").
+    /// HMM return emission std dev (only for `method="hmm"` or `"multivariate_hmm"`;
+    /// the residual std dev for `"markov_switching"`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub emission_std: Option<f64>,
 }
@@ -221,6 +223,15 @@ pub struct RegimeSeriesPoint {
     pub regime: String,
 }
 
+/// Fit quality of one HMM state count tried during BIC model selection.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RegimeModelCandidate {
+    pub n_states: usize,
+    pub log_likelihood: f64,
+    /// Bayesian information criterion (lower is better).
+    pub bic: f64,
+}
+
 /// Response for `regime_detect`
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RegimeDetectResponse {
@@ -233,6 +244,13 @@ pub struct RegimeDetectResponse {
     pub regimes: Vec<RegimeInfo>,
     pub transition_matrix: Vec<Vec<f64>>,
     pub regime_series: Vec<RegimeSeriesPoint>,
+    /// Features the HMM was fit on (only for `method="multivariate_hmm"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// BIC per candidate state count (only for `method="multivariate_hmm"` or
+    /// `"markov_switching"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub model_selection: Vec<RegimeModelCandidate>,
     pub key_findings: Vec<String>,
     pub suggested_next_steps: Vec<String>,
 }
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::engine::multiple_comparisons::MultipleComparisonsResult;
+use crate::engine::reality_check::RealityCheckResult;
 use crate::engine::types::PerformanceMetrics;
 
 /// A single parameter combination result.
@@ -21,6 +22,13 @@ pub struct SweepResult {
     pub profit_factor: f64,
     pub cagr: f64,
     pub calmar: f64,
+    /// Conditional Value at Risk at 95% (positive = loss).
+    #[serde(default)]
+    pub cvar_95: f64,
+    /// Pareto front this combo belongs to (1 = non-dominated). Multi-objective
+    /// sweeps only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pareto_front: Option<usize>,
     /// Raw (unadjusted) p-value from permutation test. `None` if permutation test was not run.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub p_value: Option<f64>,
@@ -50,6 +58,8 @@ impl SweepResult {
             profit_factor: metrics.profit_factor,
             cagr: metrics.cagr,
             calmar: metrics.calmar,
+            cvar_95: metrics.cvar_95,
+            pareto_front: None,
             p_value: None,
             significant: None,
         }
@@ -84,9 +94,63 @@ pub struct SweepResponse {
     /// `None` if permutation test was not run.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub multiple_comparisons: Option<Vec<MultipleComparisonsResult>>,
+    /// White's Reality Check / Hansen SPA family-wise test of whether the best
+    /// combo beats cash. `None` if the permutation test was not run or the
+    /// sweep has too little aligned history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reality_check: Option<RealityCheckResult>,
+    /// Multi-objective sweeps only: the Pareto front and knee point.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pareto: Option<ParetoSummary>,
     /// Full backtest responses per combo — parallel to `ranked_results`.
     /// Skipped from MCP serialization (too large); used by REST handler for DB storage.
     #[serde(skip)]
     #[schemars(skip)]
     pub full_results: Vec<crate::scripting::engine::ScriptBacktestResult>,
 }
+
+/// Whether a multi-objective sweep wants an objective high or low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectiveDirection {
+    Maximize,
+    Minimize,
+}
+
+/// One objective of a multi-objective sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParetoObjective {
+    /// `SweepResult` metric name (e.g. `cagr`, `max_drawdown`, `cvar_95`).
+    pub metric: String,
+    pub direction: ObjectiveDirection,
+}
+
+/// A non-dominated combination on the Pareto front.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParetoPoint {
+    /// Position in `ranked_results` (1-based).
+    pub rank: usize,
+    pub params: HashMap<String, serde_json::Value>,
+    /// Objective values keyed by metric name.
+    pub objectives: HashMap<String, f64>,
+    /// NSGA-II crowding distance within the front; `None` for boundary points
+    /// (infinite distance).
+    pub crowding_distance: Option<f64>,
+}
+
+/// Pareto-front output of a multi-objective sweep.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParetoSummary {
+    pub objectives: Vec<ParetoObjective>,
+    /// `"grid"` when every combination was run, `"nsga2"` when the grid was
+    /// larger than `max_evaluations` and searched evolutionarily.
+    pub search: String,
+    /// NSGA-II generations run (0 for an exhaustive grid).
+    pub generations: usize,
+    /// Non-dominated combinations, ordered as in `ranked_results`.
+    pub front: Vec<ParetoPoint>,
+    /// Recommended trade-off: the front member closest to the ideal point
+    /// once each objective is normalized to the front's range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knee: Option<ParetoPoint>,
+}
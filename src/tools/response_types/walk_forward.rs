@@ -4,6 +4,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::engine::cpcv::CpcvSummary;
 use crate::engine::types::{EquityPoint, PerformanceMetrics};
 
 /// A single walk-forward window result.
@@ -32,6 +33,10 @@ pub struct WalkForwardResponse {
     pub windows: Vec<WalkForwardWindowResult>,
     pub stitched_equity: Vec<EquityPoint>,
     pub stitched_metrics: PerformanceMetrics,
+    /// Out-of-sample Sharpe distribution across CPCV paths (`cpcv` mode only).
+    /// `stitched_equity` is then the median-Sharpe path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpcv: Option<CpcvSummary>,
     pub execution_time_ms: u64,
     pub key_findings: Vec<String>,
     pub suggested_next_steps: Vec<String>,
@@ -3,6 +3,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::overfitting::OverfittingCheckResponse;
 use super::pipeline::PipelineResponse;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -10,6 +11,8 @@ use super::pipeline::PipelineResponse;
 pub enum WorkflowKind {
     BaselineValidation,
     StrategyEvaluation,
+    /// Sweep followed by a CSCV probability-of-backtest-overfitting check.
+    OverfittingCheck,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -40,4 +43,5 @@ pub struct StrategyEvaluationResponse {
 pub enum WorkflowResponse {
     BaselineValidation(PipelineResponse),
     StrategyEvaluation(StrategyEvaluationResponse),
+    OverfittingCheck(OverfittingCheckResponse),
 }
@@ -8,8 +8,9 @@ use garde::Validate;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::data::signal_store::SqliteSignalStore;
 use crate::data::traits::StrategyStore;
-use crate::engine::types::BacktestResult;
+use crate::engine::types::{BacktestResult, SignalSpec};
 use crate::scripting::stdlib::ScriptMeta;
 use crate::scripting::types::CustomSeriesStore;
 
@@ -164,6 +165,12 @@ fn indicator_display_name(decl: &str) -> String {
         "mfi" => "MFI",
         "rank" => "Rank",
         "iv_rank" => "IV Rank",
+        "iv_percentile" => "IV Percentile",
+        "atm_iv" => "ATM IV",
+        "skew_25d" => "25Δ Skew",
+        "iv_term_slope" => "IV Term Slope",
+        "iv_rv_spread" => "IV − RV Spread",
+        "put_call_volume" => "Put/Call Volume",
         "tr" => "True Range",
         "ppo" => "PPO",
         "cmo" => "CMO",
@@ -196,7 +203,7 @@ fn indicator_thresholds(decl: &str) -> Vec<f64> {
     let name = decl.split(':').next().unwrap_or(decl);
     match name {
         "rsi" => vec![30.0, 70.0],
-        "stochastic" | "mfi" => vec![20.0, 80.0],
+        "stochastic" | "mfi" | "iv_rank" | "iv_percentile" => vec![20.0, 80.0],
         "williams_r" => vec![-80.0, -20.0],
         "cci" => vec![-100.0, 100.0],
         _ => vec![],
@@ -210,9 +217,20 @@ fn indicator_thresholds(decl: &str) -> Vec<f64> {
 /// Transpile `.trading` DSL to Rhai if the source looks like DSL, otherwise
 /// return it unchanged.  This is the **single** place where DSL→Rhai
 /// conversion is applied before execution.
-pub fn maybe_transpile(source: String) -> Result<String> {
+///
+/// `signal "name"` references are resolved against `signal_store`, the saved
+/// signal library; scripts using them fail to transpile without one.
+pub fn maybe_transpile(source: String, signal_store: Option<&SqliteSignalStore>) -> Result<String> {
     if crate::scripting::dsl::is_trading_dsl(&source) {
-        crate::scripting::dsl::transpile(&source)
+        let resolve = |reference: &str| -> Result<SignalSpec, String> {
+            let store = signal_store.ok_or("no saved signal library is configured")?;
+            store
+                .resolve(&SignalSpec::Saved {
+                    name: reference.to_string(),
+                })
+                .map_err(|e| e.to_string())
+        };
+        crate::scripting::dsl::transpile_with_signals(&source, &resolve)
             .map_err(|e| anyhow::Error::new(e).context("DSL transpilation failed"))
     } else {
         Ok(source)
@@ -226,6 +244,7 @@ pub fn maybe_transpile(source: String) -> Result<String> {
 pub fn resolve_script_source(
     params: &RunScriptParams,
     strategy_store: Option<&dyn StrategyStore>,
+    signal_store: Option<&SqliteSignalStore>,
 ) -> Result<(Option<String>, String)> {
     let (id, source) = match (&params.strategy, &params.script) {
         (Some(name_or_id), _) => {
@@ -239,7 +258,7 @@ pub fn resolve_script_source(
             )
         }
     };
-    Ok((id, maybe_transpile(source)?))
+    Ok((id, maybe_transpile(source, signal_store)?))
 }
 
 /// Load a strategy by ID or display name from the database, falling back to
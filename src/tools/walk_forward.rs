@@ -12,6 +12,7 @@ use serde_json::Value;
 
 use crate::data::adjustment_store::SqliteAdjustmentStore;
 use crate::data::cache::CachedStore;
+use crate::engine::cpcv::CpcvConfig;
 use crate::engine::walk_forward::{self as wf_engine, WalkForwardParams, WfMode, WfObjective};
 use crate::scripting::engine::{CachingDataLoader, CancelCallback};
 use crate::tools::response_types::walk_forward::{WalkForwardResponse, WalkForwardWindowResult};
@@ -38,6 +39,7 @@ pub async fn execute(
     profile: Option<String>,
     script_source: Option<String>,
     base_params: Option<HashMap<String, Value>>,
+    cpcv: Option<CpcvConfig>,
 ) -> Result<WalkForwardResponse> {
     let wf_objective = match objective.as_deref() {
         Some("sortino") => WfObjective::Sortino,
@@ -50,9 +52,10 @@ pub async fn execute(
     };
     let wf_mode = match mode.as_deref() {
         Some("anchored") => WfMode::Anchored,
+        Some("cpcv") => WfMode::Cpcv,
         None | Some("rolling") => WfMode::Rolling,
         Some(other) => {
-            anyhow::bail!("Invalid mode '{other}', expected: rolling, anchored")
+            anyhow::bail!("Invalid mode '{other}', expected: rolling, anchored, cpcv")
         }
     };
 
@@ -70,6 +73,7 @@ pub async fn execute(
         profile,
         script_source,
         base_params,
+        cpcv: cpcv.unwrap_or_default(),
     };
 
     let obj_str = engine_params.objective.clone();
@@ -108,6 +112,7 @@ pub async fn execute(
     let mode_label = match mode_str {
         WfMode::Rolling => "rolling",
         WfMode::Anchored => "anchored",
+        WfMode::Cpcv => "cpcv",
     }
     .to_string();
     let er = result.efficiency_ratio;
@@ -169,6 +174,31 @@ pub async fn execute(
         }
     }
 
+    if let Some(ref cpcv) = result.cpcv {
+        key_findings.push(format!(
+            "CPCV: {} splits ({} groups, {} held out, {}-bar embargo) → {} OOS paths; \
+             path Sharpe mean={:.2} ± {:.2}, median={:.2}, 5th–95th pct [{:.2}, {:.2}]",
+            cpcv.n_splits,
+            cpcv.n_groups,
+            cpcv.n_test_groups,
+            cpcv.embargo_bars,
+            cpcv.n_paths,
+            cpcv.oos_sharpe_mean,
+            cpcv.oos_sharpe_std,
+            cpcv.oos_sharpe_median,
+            cpcv.oos_sharpe_p5,
+            cpcv.oos_sharpe_p95,
+        ));
+        key_findings.push(if cpcv.prob_negative_sharpe > 0.0 {
+            format!(
+                "{:.0}% of CPCV paths have a negative OOS Sharpe — the edge is not robust to which periods were held out",
+                cpcv.prob_negative_sharpe * 100.0,
+            )
+        } else {
+            "Every CPCV path has a positive OOS Sharpe".to_string()
+        });
+    }
+
     key_findings.push(format!(
         "Stitched OOS metrics: Sharpe={:.2}, Sortino={:.2}, CAGR={:.1}%, max DD={:.1}%",
         result.stitched_metrics.sharpe,
@@ -202,6 +232,7 @@ pub async fn execute(
         windows,
         stitched_equity: result.stitched_equity,
         stitched_metrics: result.stitched_metrics,
+        cpcv: result.cpcv,
         execution_time_ms: result.execution_time_ms,
         key_findings,
         suggested_next_steps,
@@ -31,6 +31,7 @@ fn sample_trades() -> Vec<TradeRow> {
             stock_exit_price: None,
             stock_pnl: None,
             group: Some("Cycle 1".to_string()),
+            dividend_pnl: None,
         },
         TradeRow {
             trade_id: 2,
@@ -52,6 +53,7 @@ fn sample_trades() -> Vec<TradeRow> {
             stock_exit_price: None,
             stock_pnl: None,
             group: None,
+            dividend_pnl: None,
         },
         TradeRow {
             trade_id: 3,
@@ -73,6 +75,7 @@ fn sample_trades() -> Vec<TradeRow> {
             stock_exit_price: None,
             stock_pnl: None,
             group: None,
+            dividend_pnl: None,
         },
     ]
 }
@@ -61,6 +61,8 @@ async fn bayesian_wheel_call_dte_benchmark() {
         script_source,
         base_params: wheel_base_params(),
         continuous_params,
+        categorical_params: Vec::new(),
+        conditions: HashMap::new(),
         max_evaluations,
         initial_samples,
         objective: "sharpe".to_string(),
@@ -1,11 +1,98 @@
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+
 use chrono::NaiveDate;
+use optopsy_mcp::data::adjustment_store::{DividendRow, SplitRow};
 use optopsy_mcp::data::parquet::DATETIME_COL;
 use optopsy_mcp::engine::types::{BacktestParams, DteRange, Slippage, TargetRange, TradeSelector};
+use optopsy_mcp::scripting::engine::DataLoader;
+use optopsy_mcp::scripting::types::OhlcvBar;
 use polars::prelude::*;
 use tempfile::TempDir;
 
+/// In-memory `DataLoader` for script backtests.
+///
+/// Serves `ohlcv` for every symbol unless `ohlcv_by_symbol` is populated, in
+/// which case unknown symbols are an error. Options and dividends are shared
+/// across symbols; there are never any splits.
+#[derive(Default)]
+pub struct MemoryLoader {
+    pub ohlcv: DataFrame,
+    pub ohlcv_by_symbol: HashMap<String, DataFrame>,
+    pub options: DataFrame,
+    pub dividends: Vec<DividendRow>,
+}
+
+impl MemoryLoader {
+    /// Loader serving `ohlcv` for every symbol, with no options or dividends.
+    pub fn new(ohlcv: DataFrame) -> Self {
+        Self {
+            ohlcv,
+            ..Self::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DataLoader for MemoryLoader {
+    async fn load_ohlcv(
+        &self,
+        symbol: &str,
+        _start: Option<NaiveDate>,
+        _end: Option<NaiveDate>,
+    ) -> anyhow::Result<DataFrame> {
+        if self.ohlcv_by_symbol.is_empty() {
+            return Ok(self.ohlcv.clone());
+        }
+        self.ohlcv_by_symbol
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no data for {symbol}"))
+    }
+
+    async fn load_options(
+        &self,
+        _symbol: &str,
+        _start: Option<NaiveDate>,
+        _end: Option<NaiveDate>,
+    ) -> anyhow::Result<DataFrame> {
+        Ok(self.options.clone())
+    }
+
+    fn load_splits(&self, _symbol: &str) -> anyhow::Result<Vec<SplitRow>> {
+        Ok(Vec::new())
+    }
+
+    fn load_dividends(&self, _symbol: &str) -> anyhow::Result<Vec<DividendRow>> {
+        Ok(self.dividends.clone())
+    }
+}
+
+/// OHLCV `DataFrame` in the layout `load_ohlcv` returns.
+pub fn bars_to_df(bars: &[OhlcvBar]) -> DataFrame {
+    let datetimes: Vec<chrono::NaiveDateTime> = bars.iter().map(|b| b.datetime).collect();
+    let opens: Vec<f64> = bars.iter().map(|b| b.open).collect();
+    let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+    let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let volumes: Vec<f64> = bars.iter().map(|b| b.volume).collect();
+
+    df! {
+        "datetime" => DatetimeChunked::from_naive_datetime(
+            PlSmallStr::from("datetime"),
+            datetimes,
+            TimeUnit::Microseconds,
+        ).into_column().take_materialized_series(),
+        "open" => &opens,
+        "high" => &highs,
+        "low" => &lows,
+        "close" => &closes,
+        "volume" => &volumes,
+    }
+    .unwrap()
+}
+
 /// Build a rich synthetic options `DataFrame` with calls+puts at 4 strikes across 3 dates,
 /// with two expirations: near-term (Feb 16, DTE=32) and far-term (Mar 15, DTE=60).
 ///
@@ -183,7 +270,8 @@ pub fn test_app_state() -> (AppState, TempDir) {
     let forward_test_store = Arc::new(db.forward_tests());
     let server =
         OptopsyServer::with_all_stores(cache, strategy_store, run_store.clone(), adjustment_store)
-            .with_forward_test_store(forward_test_store.clone());
+            .with_forward_test_store(forward_test_store.clone())
+            .with_signal_store(Arc::new(db.signals()));
     let task_manager = Arc::new(TaskManager::new(1));
     let state = AppState {
         server,
@@ -0,0 +1,216 @@
+//! Integration tests for early assignment of short options (`engine.early_assignment`).
+//!
+//! Verifies that assigning one short leg of a spread closes only that leg —
+//! the rest of the spread stays open — under both the deterministic
+//! ex-dividend rule and the seeded probabilistic model.
+
+mod common;
+
+use chrono::NaiveDate;
+use polars::prelude::*;
+
+use optopsy_mcp::data::adjustment_store::DividendRow;
+use optopsy_mcp::data::parquet::DATETIME_COL;
+use optopsy_mcp::engine::types::{ExitType, TradeRecord};
+use optopsy_mcp::scripting::engine::{run_script_backtest, ScriptBacktestResult};
+
+use common::MemoryLoader;
+
+fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, day).unwrap()
+}
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    d(y, m, day).and_hms_opt(0, 0, 0).unwrap()
+}
+
+const DAYS: std::ops::RangeInclusive<u32> = 2..=9;
+
+/// Flat $100 underlying from Jan 2 through Jan 9.
+fn bars_df() -> DataFrame {
+    let datetimes: Vec<chrono::NaiveDateTime> = DAYS.map(|day| dt(2024, 1, day)).collect();
+    let n = datetimes.len();
+    let price = vec![100.0; n];
+    df! {
+        "datetime" => DatetimeChunked::from_naive_datetime(
+            PlSmallStr::from("datetime"),
+            datetimes,
+            TimeUnit::Microseconds,
+        ).into_column().take_materialized_series(),
+        "open" => &price,
+        "high" => &price,
+        "low" => &price,
+        "close" => &price,
+        "volume" => &vec![1e6; n],
+    }
+    .unwrap()
+}
+
+/// Constant quotes for March expiry contracts with the underlying at 100: an
+/// ITM 95 call with 0.20 of time value, a deep ITM 110 put with 0.01 left,
+/// and OTM 110 call / 90 put wings.
+fn options_df() -> DataFrame {
+    let contracts = [
+        ("c", 95.0, 5.10, 5.30, 0.90),
+        ("c", 110.0, 0.40, 0.60, 0.15),
+        ("p", 110.0, 9.99, 10.03, -0.95),
+        ("p", 90.0, 0.20, 0.30, -0.05),
+    ];
+    let (mut dates, mut types, mut strikes, mut bids, mut asks, mut deltas) =
+        (vec![], vec![], vec![], vec![], vec![], vec![]);
+    for today in DAYS {
+        for (option_type, strike, bid, ask, delta) in contracts {
+            dates.push(dt(2024, 1, today));
+            types.push(option_type);
+            strikes.push(strike);
+            bids.push(bid);
+            asks.push(ask);
+            deltas.push(delta);
+        }
+    }
+    let expirations = vec![d(2024, 3, 15); dates.len()];
+    let mut df = df! {
+        DATETIME_COL => &dates,
+        "option_type" => &types,
+        "strike" => &strikes,
+        "bid" => &bids,
+        "ask" => &asks,
+        "delta" => &deltas,
+    }
+    .unwrap();
+    let exp_col =
+        DateChunked::from_naive_date(PlSmallStr::from("expiration"), expirations).into_column();
+    df.with_column(exp_col).unwrap();
+    df
+}
+
+/// Open one spread (`short` strike/type against a `long` wing) on the first bar
+/// under the given `early_assignment` config, holding to the end of the data.
+fn script(early_assignment: &str, option_type: &str, short: f64, long: f64) -> String {
+    format!(
+        r#"
+        fn config() {{
+            #{{
+                symbol: "TEST",
+                capital: 100000.0,
+                interval: "daily",
+                data: #{{ ohlcv: true, options: true }},
+                engine: #{{ early_assignment: {early_assignment} }},
+                auto_close_on_end: true,
+            }}
+        }}
+
+        fn on_bar(ctx) {{
+            if ctx.bar_idx == 0 {{
+                return [#{{
+                    action: "open_spread",
+                    spread: ctx.build_strategy([
+                        #{{ side: "short", option_type: "{option_type}", strike: {short}, dte: 70 }},
+                        #{{ side: "long", option_type: "{option_type}", strike: {long}, dte: 70 }},
+                    ]),
+                    qty: 1,
+                }}];
+            }}
+            []
+        }}
+    "#
+    )
+}
+
+async fn run(script: &str, dividends: Vec<DividendRow>) -> ScriptBacktestResult {
+    let loader = MemoryLoader {
+        ohlcv: bars_df(),
+        options: options_df(),
+        dividends,
+        ..MemoryLoader::default()
+    };
+    let params = std::collections::HashMap::new();
+    run_script_backtest(script, &params, &loader, None, None, None)
+        .await
+        .unwrap()
+}
+
+fn early_assignments(trades: &[TradeRecord]) -> Vec<&TradeRecord> {
+    trades
+        .iter()
+        .filter(|t| t.exit_type == ExitType::EarlyAssignment)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dividend_rule_assigns_only_the_short_call() {
+    // 0.50 goes ex on Jan 6: on Jan 5 the short 95 call's 0.20 of time value
+    // is worth less than the dividend, so the holder exercises
+    let dividends = vec![DividendRow {
+        symbol: "TEST".to_string(),
+        date: d(2024, 1, 6),
+        amount: 0.50,
+    }];
+    let out = run(&script("\"dividend\"", "call", 95.0, 110.0), dividends).await;
+    let trades = &out.result.trade_log;
+
+    let assigned = early_assignments(trades);
+    assert_eq!(assigned.len(), 1, "trades: {trades:?}");
+    let assigned = assigned[0];
+    assert_eq!(assigned.exit_datetime, dt(2024, 1, 5));
+    assert_eq!(assigned.legs.len(), 1);
+    assert!((assigned.legs[0].strike - 95.0).abs() < 1e-10);
+    // Sold at the 5.20 mid, settled at 5.00 intrinsic
+    assert!((assigned.pnl - 20.0).abs() < 1e-6, "pnl {}", assigned.pnl);
+
+    // The long wing stays open until the end of the data
+    assert_eq!(trades.len(), 2, "trades: {trades:?}");
+    let wing = trades
+        .iter()
+        .find(|t| t.exit_type != ExitType::EarlyAssignment)
+        .unwrap();
+    assert_eq!(wing.exit_datetime, dt(2024, 1, 9));
+    assert_eq!(wing.legs.len(), 1);
+    assert!((wing.legs[0].strike - 110.0).abs() < 1e-10);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn probabilistic_model_assigns_only_the_short_put() {
+    let config = "#{ type: \"probabilistic\", seed: 11, probability: 1.0 }";
+    let out = run(&script(config, "put", 110.0, 90.0), Vec::new()).await;
+    let trades = &out.result.trade_log;
+
+    // The deep ITM short put (0.01 of time value) is assigned on its fill bar
+    let assigned = early_assignments(trades);
+    assert_eq!(assigned.len(), 1, "trades: {trades:?}");
+    assert_eq!(assigned[0].exit_datetime, dt(2024, 1, 3));
+    assert_eq!(assigned[0].legs.len(), 1);
+    assert!((assigned[0].legs[0].strike - 110.0).abs() < 1e-10);
+
+    // Assignment delivers stock at the strike; the long 90 put wing stays open
+    assert_eq!(trades.len(), 3, "trades: {trades:?}");
+    let stock = trades
+        .iter()
+        .find(|t| t.stock_entry_price.is_some())
+        .expect("assigned stock");
+    assert!((stock.stock_entry_price.unwrap() - 110.0).abs() < 1e-10);
+    let wing = trades
+        .iter()
+        .find(|t| t.exit_type != ExitType::EarlyAssignment && t.stock_entry_price.is_none())
+        .unwrap();
+    assert_eq!(wing.exit_datetime, dt(2024, 1, 9));
+    assert!((wing.legs[0].strike - 90.0).abs() < 1e-10);
+
+    // A coin-flip model is reproducible for a given seed
+    let coin_flip = script(
+        "#{ type: \"probabilistic\", seed: 11, probability: 0.5 }",
+        "put",
+        110.0,
+        90.0,
+    );
+    let exits = |out: &ScriptBacktestResult| -> Vec<_> {
+        early_assignments(&out.result.trade_log)
+            .iter()
+            .map(|t| t.exit_datetime)
+            .collect()
+    };
+    let first = run(&coin_flip, Vec::new()).await;
+    let second = run(&coin_flip, Vec::new()).await;
+    assert_eq!(exits(&first), exits(&second));
+    assert_eq!(exits(&first).len(), 1);
+}
@@ -0,0 +1,130 @@
+//! Integration tests for the financing ledger (`engine.financing`).
+//!
+//! Verifies that idle cash earns the configured rate, that short stock pays its
+//! symbol's borrow fee, and that each accrual is reported in the result's
+//! `financing` summary and flows into the equity curve.
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use optopsy_mcp::scripting::engine::run_script_backtest;
+use optopsy_mcp::scripting::types::OhlcvBar;
+
+use common::{bars_to_df, MemoryLoader};
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+fn default_params() -> HashMap<String, serde_json::Value> {
+    let mut params = HashMap::new();
+    params.insert("symbol".to_string(), serde_json::json!("TEST"));
+    params.insert("CAPITAL".to_string(), serde_json::json!(100_000.0));
+    params
+}
+
+/// Flat 100.0 bars on Jan 2, 3, 4 and 8 (a weekend gap before the last bar).
+fn flat_bars() -> Vec<OhlcvBar> {
+    [2, 3, 4, 8]
+        .into_iter()
+        .map(|day| OhlcvBar {
+            datetime: dt(2024, 1, day),
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            close: 100.0,
+            volume: 1e6,
+        })
+        .collect()
+}
+
+/// No trades: cash earns 5% for the 6 calendar days from Jan 2 to Jan 8,
+/// compounding at each bar (1 + 1 + 4 days).
+#[tokio::test(flavor = "multi_thread")]
+async fn idle_cash_earns_interest() {
+    let loader = MemoryLoader::new(bars_to_df(&flat_bars()));
+
+    let script = r#"
+        fn config() {
+            #{
+                symbol: params.symbol,
+                capital: params.CAPITAL,
+                interval: "daily",
+                data: #{ ohlcv: true, options: false },
+                engine: #{ financing: #{ cash_rate: 0.05 } },
+            }
+        }
+
+        fn on_bar(ctx) { [] }
+    "#;
+
+    let result = run_script_backtest(script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    let financing = result.result.financing.expect("financing summary");
+    let growth: f64 = [1.0, 1.0, 4.0]
+        .iter()
+        .map(|days| 1.0 + 0.05 * days / 365.0)
+        .product();
+    let expected = 100_000.0 * (growth - 1.0);
+    assert!(
+        (financing.cash_interest - expected).abs() < 1e-6,
+        "Expected {expected:.4} interest, got {:.4}",
+        financing.cash_interest
+    );
+    assert!(financing.margin_interest.abs() < f64::EPSILON);
+    assert!(financing.borrow_fees.abs() < f64::EPSILON);
+
+    let final_equity = result.result.equity_curve.last().unwrap().equity;
+    assert!((final_equity - (100_000.0 + expected)).abs() < 1e-6);
+}
+
+/// Short 100 shares filled on Jan 3: the $10,000 short is charged its
+/// hard-to-borrow rate from Jan 3 to Jan 8, and the proceeds earn nothing.
+#[tokio::test(flavor = "multi_thread")]
+async fn short_stock_pays_borrow_fee() {
+    let loader = MemoryLoader::new(bars_to_df(&flat_bars()));
+
+    let script = r#"
+        fn config() {
+            #{
+                symbol: params.symbol,
+                capital: params.CAPITAL,
+                interval: "daily",
+                data: #{ ohlcv: true, options: false },
+                engine: #{
+                    financing: #{ cash_rate: 0.0, borrow_rates: #{ TEST: 0.30 } },
+                },
+            }
+        }
+
+        fn on_bar(ctx) {
+            if ctx.bar_idx == 0 {
+                return [sell_stock("TEST", 100)];
+            }
+            []
+        }
+    "#;
+
+    let result = run_script_backtest(script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    let financing = result.result.financing.expect("financing summary");
+    let expected = 10_000.0 * 0.30 * 5.0 / 365.0;
+    assert!(
+        (financing.borrow_fees - expected).abs() < 1e-6,
+        "Expected {expected:.4} borrow fees, got {:.4}",
+        financing.borrow_fees
+    );
+    assert!((financing.borrow_fees_by_symbol["TEST"] - expected).abs() < 1e-6);
+    assert!(financing.margin_interest.abs() < f64::EPSILON);
+    assert!((financing.net + expected).abs() < 1e-6);
+}
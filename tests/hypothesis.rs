@@ -448,3 +448,113 @@ async fn generate_hypotheses_multi_symbol_cross_asset() {
     // Cross-asset scan may find 0 patterns if no Granger causality detected
     let _ = result.total_trials;
 }
+
+/// Write a synthetic options chain for `symbol` alongside the OHLCV cache:
+/// 30- and 90-DTE expirations with 90/100/110 strikes, priced off a surface
+/// whose ATM level, term slope, and put skew cycle over time.
+fn write_options_chain(dir: &std::path::Path, symbol: &str, n_days: usize) {
+    use optopsy_mcp::engine::greeks::{bs_price, GreeksParams};
+    use optopsy_mcp::engine::types::OptionType;
+
+    let params = GreeksParams::default();
+    let base_date = NaiveDate::from_ymd_opt(2019, 1, 2).unwrap();
+    let mut dates = Vec::new();
+    let mut expirations = Vec::new();
+    let mut types = Vec::new();
+    let mut strikes = Vec::new();
+    let mut bids = Vec::new();
+    let mut asks = Vec::new();
+    let mut deltas = Vec::new();
+    let mut volumes = Vec::new();
+
+    for i in 0..n_days {
+        let date = base_date + chrono::Duration::days(i as i64);
+        if date.weekday() == chrono::Weekday::Sat || date.weekday() == chrono::Weekday::Sun {
+            continue;
+        }
+        let x = i as f64;
+        let atm = 0.20 + 0.05 * (x / 30.0).sin();
+        let put_wing = atm + 0.04 + 0.03 * (x / 45.0).sin();
+        for (dte, level) in [(30i64, atm), (90, 0.22)] {
+            let t = dte as f64 / 365.0;
+            for (strike, sigma, call_delta) in [
+                (90.0, put_wing, 0.85),
+                (100.0, level, 0.50),
+                (110.0, level - 0.02, 0.25),
+            ] {
+                for (option_type, code, delta) in [
+                    (OptionType::Call, "c", call_delta),
+                    (OptionType::Put, "p", call_delta - 1.0),
+                ] {
+                    let mid = bs_price(option_type, 100.0, strike, t, &params, sigma);
+                    dates.push(date);
+                    expirations.push(date + chrono::Duration::days(dte));
+                    types.push(code);
+                    strikes.push(strike);
+                    bids.push(mid - 0.01);
+                    asks.push(mid + 0.01);
+                    deltas.push(delta);
+                    volumes.push(if code == "p" { 150.0 } else { 100.0 } + x % 7.0);
+                }
+            }
+        }
+    }
+
+    let mut df = df! {
+        "option_type" => &types,
+        "strike" => &strikes,
+        "bid" => &bids,
+        "ask" => &asks,
+        "delta" => &deltas,
+        "volume" => &volumes,
+        "underlying_price" => vec![100.0; types.len()],
+    }
+    .unwrap();
+    df.with_column(DateChunked::from_naive_date(PlSmallStr::from("date"), dates).into_column())
+        .unwrap();
+    df.with_column(
+        DateChunked::from_naive_date(PlSmallStr::from("expiration"), expirations).into_column(),
+    )
+    .unwrap();
+
+    let options_dir = dir.join("options");
+    std::fs::create_dir_all(&options_dir).unwrap();
+    let file = std::fs::File::create(options_dir.join(format!("{symbol}.parquet"))).unwrap();
+    ParquetWriter::new(file).finish(&mut df).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn generate_hypotheses_scans_options_structure() {
+    let (dir, cache) = setup_cache_with_ohlcv("SPY", 2000);
+    write_options_chain(dir.path(), "SPY", 2000);
+
+    let params = HypothesisParams {
+        symbols: vec!["SPY".to_string()],
+        dimensions: Some(vec![
+            optopsy_mcp::engine::types::HypothesisDimension::OptionsStructure,
+        ]),
+        significance: 0.20,
+        forward_horizons: vec![5],
+        years: 5,
+        dedup_threshold: 0.9,
+    };
+
+    let result = optopsy_mcp::tools::hypothesis::execute(&cache, &params)
+        .await
+        .expect("options structure scan should succeed");
+
+    // Skew, term slope, IV-vs-realized, and put/call volume each yield high/low tests
+    assert!(
+        result.total_trials >= 4,
+        "expected options-structure trials, got {}",
+        result.total_trials
+    );
+    for h in &result.hypotheses {
+        assert_eq!(h.dimension, "options_structure");
+        assert!(
+            h.structural_basis == "hedging_demand" || h.structural_basis == "variance_risk_premium",
+            "unexpected basis {}",
+            h.structural_basis
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Integration tests for margin enforcement (`engine.margin`).
+//!
+//! Verifies that the engine refuses entries the account cannot carry under the
+//! configured model, and that a margin call forcibly liquidates positions until
+//! the remaining book fits within equity.
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use optopsy_mcp::engine::types::ExitType;
+use optopsy_mcp::scripting::engine::run_script_backtest;
+use optopsy_mcp::scripting::types::OhlcvBar;
+
+use common::{bars_to_df, MemoryLoader};
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+fn default_params() -> HashMap<String, serde_json::Value> {
+    let mut params = HashMap::new();
+    params.insert("symbol".to_string(), serde_json::json!("TEST"));
+    params.insert("CAPITAL".to_string(), serde_json::json!(100_000.0));
+    params
+}
+
+/// Daily bars on consecutive January 2024 days, opening and closing at each price.
+fn bars(prices: &[f64]) -> Vec<OhlcvBar> {
+    prices
+        .iter()
+        .zip(2u32..)
+        .map(|(&price, day)| OhlcvBar {
+            datetime: dt(2024, 1, day),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 1e6,
+        })
+        .collect()
+}
+
+/// Under the cash model a $150k stock purchase needs $150k of a $100k account:
+/// the order is refused with a warning and nothing trades.
+#[tokio::test(flavor = "multi_thread")]
+async fn over_margined_entry_is_rejected() {
+    let loader = MemoryLoader::new(bars_to_df(&bars(&[100.0, 100.0, 100.0])));
+
+    let script = r#"
+        fn config() {
+            #{
+                symbol: params.symbol,
+                capital: params.CAPITAL,
+                interval: "daily",
+                data: #{ ohlcv: true, options: false },
+                engine: #{ margin: "cash" },
+            }
+        }
+
+        fn on_bar(ctx) {
+            if ctx.bar_idx == 0 { return [buy_stock("TEST", 1500)]; }
+            []
+        }
+    "#;
+
+    let result = run_script_backtest(script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    assert!(result.result.trade_log.is_empty());
+    assert!(
+        result
+            .result
+            .warnings
+            .iter()
+            .any(|w| w.contains("blocked by margin")),
+        "warnings: {:?}",
+        result.result.warnings
+    );
+}
+
+/// Two Reg-T stock positions (1000 and 900 shares at 100) need $95k of a $100k
+/// account. A drop to 70 leaves $43k of equity against $66.5k required: the
+/// engine liquidates the 1000-share lot, after which the 900-share lot's $31.5k
+/// requirement fits and it stays open until the end of data.
+#[tokio::test(flavor = "multi_thread")]
+async fn margin_call_forces_liquidation() {
+    let loader = MemoryLoader::new(bars_to_df(&bars(&[100.0, 100.0, 70.0, 70.0])));
+
+    let script = r#"
+        fn config() {
+            #{
+                symbol: params.symbol,
+                capital: params.CAPITAL,
+                interval: "daily",
+                data: #{ ohlcv: true, options: false },
+                engine: #{ margin: "reg_t" },
+                auto_close_on_end: true,
+            }
+        }
+
+        fn on_bar(ctx) {
+            if ctx.bar_idx == 0 {
+                return [buy_stock("TEST", 1000), buy_stock("TEST", 900)];
+            }
+            []
+        }
+    "#;
+
+    let result = run_script_backtest(script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    assert!(
+        result
+            .result
+            .warnings
+            .iter()
+            .any(|w| w.contains("Margin call on 2024-01-04")),
+        "warnings: {:?}",
+        result.result.warnings
+    );
+
+    let trades = &result.result.trade_log;
+    assert_eq!(trades.len(), 2, "trades: {trades:?}");
+    let forced: Vec<_> = trades
+        .iter()
+        .filter(|t| t.exit_type == ExitType::ForcedLiquidation)
+        .collect();
+    assert_eq!(forced.len(), 1, "trades: {trades:?}");
+    assert_eq!(forced[0].exit_datetime, dt(2024, 1, 4));
+    assert!((forced[0].pnl + 30_000.0).abs() < 1e-6, "{:?}", forced[0]);
+
+    let survivor = trades
+        .iter()
+        .find(|t| t.exit_type != ExitType::ForcedLiquidation)
+        .unwrap();
+    assert_eq!(survivor.exit_datetime, dt(2024, 1, 5));
+    assert!((survivor.pnl + 27_000.0).abs() < 1e-6, "{survivor:?}");
+}
@@ -65,7 +65,7 @@ async fn tool_router_lists_all_tools() {
     let tools = client.list_all_tools().await.unwrap();
     let tool_names: Vec<String> = tools.iter().map(|t| t.name.to_string()).collect();
 
-    assert_eq!(tools.len(), 14, "Expected 14 tools, got: {tool_names:?}");
+    assert_eq!(tools.len(), 16, "Expected 16 tools, got: {tool_names:?}");
     for expected in [
         "backtest",
         "scripting_guide",
@@ -75,11 +75,13 @@ async fn tool_router_lists_all_tools() {
         "rolling_metric",
         "regime_detect",
         "generate_hypotheses",
+        "validate_hypotheses",
         "drawdown_analysis",
         "cointegration_test",
         "monte_carlo",
         "factor_attribution",
         "portfolio_optimize",
+        "portfolio_backtest",
         "benchmark_analysis",
     ] {
         assert!(
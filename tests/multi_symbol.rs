@@ -1236,6 +1236,7 @@ impl DataLoader for DateFilteringLoader {
 /// so that `extern_symbol("symbol", ...)` resolves correctly.
 #[tokio::test(flavor = "multi_thread")]
 async fn walk_forward_with_symbol() {
+    use optopsy_mcp::engine::cpcv::CpcvConfig;
     use optopsy_mcp::engine::walk_forward::{self as wf, WalkForwardParams, WfMode, WfObjective};
 
     let bars: Vec<OhlcvBar> = (0..20)
@@ -1306,6 +1307,7 @@ fn on_exit_check(ctx, pos) {
         profile: None,
         script_source: Some(script.to_string()),
         base_params: None,
+        cpcv: CpcvConfig::default(),
     };
 
     let no_cancel: Box<dyn Fn() -> bool + Send + Sync> = Box::new(|| false);
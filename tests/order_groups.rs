@@ -0,0 +1,202 @@
+//! Integration tests for linked stock orders: one-cancels-other groups and
+//! bracket (one-triggers-other) entries.
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use optopsy_mcp::scripting::engine::run_script_backtest;
+use optopsy_mcp::scripting::types::OhlcvBar;
+
+use common::{bars_to_df, MemoryLoader};
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+fn default_params() -> HashMap<String, serde_json::Value> {
+    let mut params = HashMap::new();
+    params.insert("symbol".to_string(), serde_json::json!("TEST"));
+    params.insert("CAPITAL".to_string(), serde_json::json!(100_000.0));
+    params
+}
+
+/// Build daily bars from `(open, high, low, close)` tuples starting 2024-01-02.
+fn bars(ohlc: &[(f64, f64, f64, f64)]) -> Vec<OhlcvBar> {
+    ohlc.iter()
+        .enumerate()
+        .map(|(i, &(open, high, low, close))| OhlcvBar {
+            datetime: dt(2024, 1, 2) + chrono::Duration::days(i as i64),
+            open,
+            high,
+            low,
+            close,
+            volume: 1e6,
+        })
+        .collect()
+}
+
+const CONFIG: &str = r#"
+    fn config() {
+        #{
+            symbol: params.symbol,
+            capital: params.CAPITAL,
+            interval: "daily",
+            data: #{ ohlcv: true, options: false },
+        }
+    }
+"#;
+
+// ---------------------------------------------------------------------------
+// OCO: the first fill cancels the rest of the group
+// ---------------------------------------------------------------------------
+
+/// Bar 0: queue a buy stop at 105 and a sell stop at 95 in one OCO group
+/// Bar 1: high=106 → the buy stop fills at 105, the sell stop is cancelled
+/// Bar 2: low=90 would have triggered the sell stop — it must not fire
+/// The long is closed after 2 days at 92 → P&L = (92 - 105) * 100 = -1300
+#[tokio::test(flavor = "multi_thread")]
+async fn oco_fill_cancels_sibling() {
+    let loader = MemoryLoader::new(bars_to_df(&bars(&[
+        (100.0, 101.0, 99.0, 100.0),
+        (101.0, 106.0, 100.0, 105.0),
+        (104.0, 105.0, 90.0, 92.0),
+        (92.0, 93.0, 91.0, 92.0),
+        (92.0, 93.0, 91.0, 92.0),
+        (92.0, 93.0, 91.0, 92.0),
+    ])));
+
+    let script = format!(
+        r#"{CONFIG}
+        fn on_bar(ctx) {{
+            if ctx.bar_idx == 0 {{
+                return oco("breakout", [
+                    buy_stop("SPY", 100, 105.0),
+                    sell_stop("SPY", 100, 95.0),
+                ]);
+            }}
+            []
+        }}
+
+        fn on_exit_check(ctx, pos) {{
+            if pos.days_held >= 2 {{
+                return close_position("time");
+            }}
+            hold_position()
+        }}
+    "#
+    );
+
+    let result = run_script_backtest(&script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.result.trade_count, 1,
+        "Only one side of the OCO group should fill. Warnings: {:?}",
+        result.result.warnings
+    );
+    let trade = &result.result.trade_log[0];
+    assert_eq!(trade.order_group.as_deref(), Some("breakout"));
+    assert!(
+        (trade.pnl + 1300.0).abs() < 1.0,
+        "Expected ~-1300 (long from 105, out at 92), got {:.2}",
+        trade.pnl
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Bracket: entry arms a target and a stop; the first exit cancels the other
+// ---------------------------------------------------------------------------
+
+/// Bar 0: queue a market buy bracketed by a 110 target and a 95 stop
+/// Bar 1: entry fills at the open (100); exits start working next bar
+/// Bar 2: high=111 → target fills at 110, the stop is cancelled
+/// Bar 3: low=90 would have hit the stop — no second exit, no re-entry
+#[tokio::test(flavor = "multi_thread")]
+async fn bracket_target_exit_cancels_stop() {
+    let loader = MemoryLoader::new(bars_to_df(&bars(&[
+        (99.0, 100.0, 98.0, 99.0),
+        (100.0, 102.0, 99.0, 101.0),
+        (101.0, 111.0, 100.0, 108.0),
+        (100.0, 101.0, 90.0, 91.0),
+        (91.0, 92.0, 90.0, 91.0),
+    ])));
+
+    let script = format!(
+        r#"{CONFIG}
+        fn on_bar(ctx) {{
+            if ctx.bar_idx == 0 {{
+                return [bracket(buy_stock("SPY", 100), 110.0, 95.0)];
+            }}
+            []
+        }}
+    "#
+    );
+
+    let result = run_script_backtest(&script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.result.trade_count, 1,
+        "Warnings: {:?}",
+        result.result.warnings
+    );
+    let trade = &result.result.trade_log[0];
+    // Unnamed brackets are auto-grouped so their legs can be told apart
+    assert_eq!(trade.order_group.as_deref(), Some("group-1"));
+    assert!(
+        (trade.pnl - 1000.0).abs() < 1.0,
+        "Expected ~1000 (100 → 110 target), got {:.2}",
+        trade.pnl
+    );
+}
+
+/// A short bracket inverts its exits: the stop sits above the entry.
+/// Bar 1: short fills at 100. Bar 2: high=106 → buy stop at 105 covers.
+#[tokio::test(flavor = "multi_thread")]
+async fn bracket_stop_exit_on_short() {
+    let loader = MemoryLoader::new(bars_to_df(&bars(&[
+        (99.0, 100.0, 98.0, 99.0),
+        (100.0, 101.0, 99.0, 100.0),
+        (101.0, 106.0, 100.0, 104.0),
+        (100.0, 101.0, 80.0, 81.0),
+        (81.0, 82.0, 80.0, 81.0),
+    ])));
+
+    let script = format!(
+        r#"{CONFIG}
+        fn on_bar(ctx) {{
+            if ctx.bar_idx == 0 {{
+                let entry = sell_stock("SPY", 100);
+                entry.order_group = "fade";
+                return [bracket(entry, 90.0, 105.0)];
+            }}
+            []
+        }}
+    "#
+    );
+
+    let result = run_script_backtest(&script, &default_params(), &loader, None, None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.result.trade_count, 1,
+        "Warnings: {:?}",
+        result.result.warnings
+    );
+    let trade = &result.result.trade_log[0];
+    assert_eq!(trade.order_group.as_deref(), Some("fade"));
+    assert!(
+        (trade.pnl + 500.0).abs() < 1.0,
+        "Expected ~-500 (short 100, stopped at 105), got {:.2}",
+        trade.pnl
+    );
+}
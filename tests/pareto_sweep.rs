@@ -0,0 +1,183 @@
+//! Integration tests for multi-objective (Pareto) sweeps.
+//!
+//! Runs the `bb_mean_reversion` strategy over synthetic OHLCV data with
+//! repeated lower-band dips, checking both the exhaustive path and the
+//! NSGA-II path taken when the grid exceeds `max_evaluations`.
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use serde_json::json;
+
+use optopsy_mcp::engine::pareto::{
+    parse_objectives, run_pareto_sweep_with_results, ParetoConfig, DEFAULT_PARETO_SEED,
+};
+use optopsy_mcp::scripting::dsl;
+use optopsy_mcp::scripting::engine::{CancelCallback, DataLoader};
+use optopsy_mcp::scripting::types::OhlcvBar;
+use optopsy_mcp::tools::response_types::sweep::SweepResponse;
+
+use common::{bars_to_df, MemoryLoader};
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Stable stretches around a drifting base, each followed by an oversold dip
+/// and a recovery above the mean.
+fn make_bars() -> Vec<OhlcvBar> {
+    let mut bars = Vec::new();
+    let start = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+    let mut push = |day: &mut i64, open: f64, high: f64, low: f64, close: f64| {
+        bars.push(OhlcvBar {
+            datetime: (start + chrono::Duration::days(*day))
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            open,
+            high,
+            low,
+            close,
+            volume: 1_000_000.0,
+        });
+        *day += 1;
+    };
+
+    let mut day = 0i64;
+    for cycle in 0..6 {
+        let base = 100.0 + f64::from(cycle) * 2.0;
+        for i in 0..25 {
+            let close = base + (f64::from(i) * 0.05).sin() * 0.3;
+            push(&mut day, close - 0.1, close + 0.3, close - 0.3, close);
+        }
+        push(&mut day, base, base + 0.2, base - 6.0, base - 5.0);
+        for i in 1..=5 {
+            let close = base - 3.5 + f64::from(i) * 0.7;
+            push(&mut day, close - 0.1, close + 0.3, close - 0.3, close);
+        }
+        push(&mut day, base + 0.2, base + 1.8, base - 0.2, base + 1.2);
+    }
+    bars
+}
+
+fn config(max_evaluations: usize) -> ParetoConfig {
+    let trading_source = std::fs::read_to_string("scripts/strategies/bb_mean_reversion.trading")
+        .expect("bb_mean_reversion.trading should exist");
+    let script_source =
+        dsl::transpile(&trading_source).expect("bb_mean_reversion.trading should transpile");
+
+    let mut base_params = HashMap::new();
+    base_params.insert("symbol".to_string(), json!("SPY"));
+    base_params.insert("CAPITAL".to_string(), json!(100_000));
+
+    let mut param_grid = HashMap::new();
+    param_grid.insert(
+        "BB_PERIOD".to_string(),
+        (10..=20).map(|v| json!(v)).collect(),
+    );
+    param_grid.insert("MAX_HOLD".to_string(), vec![json!(3), json!(5), json!(10)]);
+
+    ParetoConfig {
+        script_source,
+        base_params,
+        param_grid,
+        objectives: parse_objectives(&["cagr".to_string(), "max_drawdown".to_string()]).unwrap(),
+        max_evaluations,
+        objective: "sharpe".to_string(),
+        seed: DEFAULT_PARETO_SEED,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pareto_grid_ranks_front_first_with_knee_as_best() {
+    let loader: Arc<dyn DataLoader> = Arc::new(MemoryLoader::new(bars_to_df(&make_bars())));
+    let no_cancel: CancelCallback = Box::new(|| false);
+
+    let response =
+        run_pareto_sweep_with_results(&config(100), loader, &no_cancel, |_, _| {}, |_| {})
+            .await
+            .expect("pareto sweep should succeed");
+
+    assert_eq!(response.mode, "pareto");
+    assert_eq!(response.combinations_total, 33);
+    let pareto = response.pareto.as_ref().expect("pareto summary");
+    assert_eq!(pareto.search, "grid");
+    assert!(!pareto.front.is_empty());
+
+    // Front members come first, the knee leads, and fronts never go backwards.
+    let knee = pareto.knee.as_ref().expect("knee point");
+    let best = response.best_result.as_ref().unwrap();
+    assert_eq!(best.rank, 1);
+    assert_eq!(best.params, knee.params);
+    let fronts: Vec<usize> = response
+        .ranked_results
+        .iter()
+        .map(|r| r.pareto_front.expect("front set"))
+        .collect();
+    assert!(fronts.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(
+        fronts.iter().filter(|&&f| f == 1).count(),
+        pareto.front.len()
+    );
+
+    // No front member is dominated by any evaluated combo.
+    for point in &pareto.front {
+        let (cagr, dd) = (point.objectives["cagr"], point.objectives["max_drawdown"]);
+        assert!(!response.ranked_results.iter().any(|r| {
+            r.cagr >= cagr && r.max_drawdown <= dd && (r.cagr > cagr || r.max_drawdown < dd)
+        }));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pareto_uses_nsga2_within_budget_for_large_grids() {
+    let loader: Arc<dyn DataLoader> = Arc::new(MemoryLoader::new(bars_to_df(&make_bars())));
+    let no_cancel: CancelCallback = Box::new(|| false);
+    let streamed = std::sync::atomic::AtomicUsize::new(0);
+
+    let response = run_pareto_sweep_with_results(
+        &config(12),
+        loader,
+        &no_cancel,
+        |_, _| {},
+        |_| {
+            streamed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        },
+    )
+    .await
+    .expect("pareto sweep should succeed");
+
+    let pareto = response.pareto.as_ref().expect("pareto summary");
+    assert_eq!(pareto.search, "nsga2");
+    assert!(pareto.generations >= 1);
+    assert!(response.combinations_run + response.combinations_failed <= 12);
+    assert_eq!(
+        streamed.load(std::sync::atomic::Ordering::Relaxed),
+        response.combinations_run
+    );
+    assert!(pareto.knee.is_some());
+
+    // The seeded search evaluates the same combos on a rerun
+    let rerun = run_pareto_sweep_with_results(
+        &config(12),
+        Arc::new(MemoryLoader::new(bars_to_df(&make_bars()))),
+        &no_cancel,
+        |_, _| {},
+        |_| {},
+    )
+    .await
+    .expect("pareto sweep should succeed");
+    let combos = |r: &SweepResponse| -> Vec<BTreeMap<String, serde_json::Value>> {
+        r.ranked_results
+            .iter()
+            .map(|res| res.params.clone().into_iter().collect())
+            .collect()
+    };
+    assert_eq!(combos(&response), combos(&rerun));
+}
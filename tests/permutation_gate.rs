@@ -73,6 +73,8 @@ fn trade(id: usize, pnl: f64) -> TradeRecord {
         stock_exit_price: None,
         stock_pnl: None,
         group: None,
+        dividend_pnl: None,
+        order_group: None,
     }
 }
 
@@ -93,6 +95,8 @@ fn backtest_from_pnls(pnls: &[f64]) -> ScriptBacktestResult {
             trade_log,
             quality: BacktestQualityStats::default(),
             warnings: vec![],
+            financing: None,
+            working_orders: None,
         },
         metadata: None,
         execution_time_ms: 10,
@@ -120,6 +124,8 @@ fn sweep_result(rank: usize) -> SweepResult {
         profit_factor: 0.0,
         cagr: 0.0,
         calmar: 0.0,
+        cvar_95: 0.0,
+        pareto_front: None,
         p_value: None,
         significant: None,
     }
@@ -140,6 +146,8 @@ fn build_response(combo_pnls: &[Vec<f64>]) -> SweepResponse {
         convergence_trace: None,
         execution_time_ms: 100,
         multiple_comparisons: None,
+        reality_check: None,
+        pareto: None,
         full_results: combo_pnls.iter().map(|p| backtest_from_pnls(p)).collect(),
     }
 }
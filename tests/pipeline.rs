@@ -3,6 +3,7 @@
 //! Tests cover:
 //! - Significance gate failing (all combos non-significant) → downstream skipped
 //! - Significance gate passing (no permutation test) → top combos forwarded
+//! - Significance gate failing on the family-wise SPA test despite significant combos
 //! - OOS data gate failing (too few equity points) → monte carlo skipped
 //! - Full pipeline end-to-end with NVDA fixture data
 
@@ -10,6 +11,7 @@ mod common;
 
 use std::collections::HashMap;
 
+use optopsy_mcp::engine::reality_check::RealityCheckResult;
 use optopsy_mcp::tools::response_types::pipeline::{PipelineResponse, StageStatus};
 use optopsy_mcp::tools::response_types::sweep::{SweepResponse, SweepResult};
 
@@ -33,6 +35,8 @@ fn make_sweep(ranked: Vec<SweepResult>) -> SweepResponse {
         convergence_trace: None,
         execution_time_ms: 100,
         multiple_comparisons: None,
+        reality_check: None,
+        pareto: None,
         full_results: vec![],
     }
 }
@@ -57,6 +61,8 @@ fn make_result(
         profit_factor: 1.5,
         cagr: 0.12,
         calmar: 1.2,
+        cvar_95: 0.0,
+        pareto_front: None,
         p_value,
         significant,
     }
@@ -118,14 +124,16 @@ async fn significance_gate_fails_skips_downstream() {
     let response = result.expect("Pipeline should not error even when gate fails");
 
     // Verify stages
-    assert_eq!(response.stages.len(), 5, "Should have 5 stages total");
+    assert_eq!(response.stages.len(), 6, "Should have 6 stages total");
     assert_stage(&response, "sweep", &StageStatus::Completed);
     assert_stage(&response, "significance_gate", &StageStatus::Failed);
+    assert_stage(&response, "overfitting_gate", &StageStatus::Skipped);
     assert_stage(&response, "walk_forward", &StageStatus::Skipped);
     assert_stage(&response, "oos_data_gate", &StageStatus::Skipped);
     assert_stage(&response, "monte_carlo", &StageStatus::Skipped);
 
-    // No walk-forward or monte carlo results
+    // No CSCV, walk-forward or monte carlo results
+    assert!(response.cscv.is_none());
     assert!(response.walk_forward.is_none());
     assert!(response.monte_carlo.is_none());
 
@@ -140,6 +148,60 @@ async fn significance_gate_fails_skips_downstream() {
     );
 }
 
+// ──────────────────────────────────────────────────────────────────────────────
+// Test: significant combos but family-wise SPA test fails → gate fails
+// ──────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reality_check_failure_fails_significance_gate() {
+    let (state, _tmp) = common::test_app_state();
+
+    let mut params1 = HashMap::new();
+    params1.insert("DELTA".to_string(), serde_json::json!(0.3));
+
+    let mut sweep = make_sweep(vec![make_result(1, params1, 1.1, Some(true), Some(0.01))]);
+    sweep.reality_check = Some(RealityCheckResult {
+        benchmark: "cash".to_string(),
+        n_strategies: 500,
+        n_observations: 750,
+        n_bootstrap: 1000,
+        mean_block_length: 9.1,
+        best_rank: 1,
+        best_mean_excess: 0.0002,
+        reality_check_p_value: 0.41,
+        spa_p_value: 0.27,
+        spa_lower_p_value: 0.22,
+        spa_upper_p_value: 0.41,
+        alpha: 0.05,
+        significant: false,
+    });
+
+    let response = optopsy_mcp::tools::pipeline::run_pipeline(
+        &state.server,
+        "test_strategy",
+        "SPY",
+        100_000.0,
+        "sharpe",
+        "sweep-004".to_string(),
+        vec!["run-1".to_string()],
+        sweep,
+        HashMap::new(),
+        &None,
+    )
+    .await
+    .expect("Pipeline should not error when gate fails");
+
+    assert_stage(&response, "significance_gate", &StageStatus::Failed);
+    assert_stage(&response, "walk_forward", &StageStatus::Skipped);
+    let gate = response
+        .stages
+        .iter()
+        .find(|s| s.name == "significance_gate")
+        .unwrap();
+    assert!(gate.reason.as_deref().unwrap_or_default().contains("SPA"));
+    assert_eq!(gate.details["spa_p_value"], serde_json::json!(0.27));
+}
+
 // ──────────────────────────────────────────────────────────────────────────────
 // Test: no permutation test → top combos pass significance gate
 // ──────────────────────────────────────────────────────────────────────────────
@@ -175,6 +237,10 @@ async fn no_permutation_passes_significance_gate() {
     // Significance gate should pass (no permutation test → top combos accepted)
     assert_stage(&response, "significance_gate", &StageStatus::Completed);
 
+    // A single combo with no equity curves cannot be split — gate is skipped, not blocking
+    assert_stage(&response, "overfitting_gate", &StageStatus::Skipped);
+    assert!(response.cscv.is_none());
+
     // Walk-forward will fail because "nonexistent_strategy" doesn't exist in cache
     // That's fine — the point is the significance gate passed
     assert_stage(&response, "walk_forward", &StageStatus::Failed);
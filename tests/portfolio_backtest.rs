@@ -0,0 +1,199 @@
+//! Integration tests for multi-strategy portfolio backtests.
+//!
+//! Verifies that sleeves share one capital pool (an entry that fits a sleeve's
+//! standalone book is blocked when the pool is exhausted), that cross-strategy
+//! limits apply across sleeves, and that rebalancing moves cash without
+//! distorting the consolidated P&L.
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use optopsy_mcp::scripting::portfolio::{
+    run_portfolio_backtest, AllocationRule, PoolMargin, PortfolioConfig, PortfolioLimits,
+    RebalanceFrequency, SleeveScript,
+};
+use optopsy_mcp::scripting::types::OhlcvBar;
+
+use common::{bars_to_df, MemoryLoader};
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+/// Daily bars through January and February 2024 priced by `price(i)`.
+fn bars(price: impl Fn(usize) -> f64) -> Vec<OhlcvBar> {
+    (1..=28)
+        .map(|d| dt(2024, 1, d))
+        .chain((1..=28).map(|d| dt(2024, 2, d)))
+        .enumerate()
+        .map(|(i, datetime)| {
+            let p = price(i);
+            OhlcvBar {
+                datetime,
+                open: p,
+                high: p,
+                low: p,
+                close: p,
+                volume: 1e6,
+            }
+        })
+        .collect()
+}
+
+fn loader(symbols: &[(&str, Vec<OhlcvBar>)]) -> MemoryLoader {
+    MemoryLoader {
+        ohlcv_by_symbol: symbols
+            .iter()
+            .map(|(s, b)| ((*s).to_string(), bars_to_df(b)))
+            .collect(),
+        ..MemoryLoader::default()
+    }
+}
+
+/// Buys `qty` shares of `params.symbol` on the first bar and holds for
+/// `params.hold_days` days.
+const BUY_AND_HOLD: &str = r#"
+    fn config() {
+        #{
+            symbol: params.symbol,
+            capital: params.CAPITAL,
+            interval: "daily",
+            data: #{ ohlcv: true, options: false },
+        }
+    }
+
+    fn on_bar(ctx) {
+        if ctx.bar_idx == 0 {
+            return [buy_stock(params.symbol, params.qty)];
+        }
+        []
+    }
+
+    fn on_exit_check(ctx, pos) {
+        if pos.days_held >= params.hold_days {
+            return close_position("done");
+        }
+        hold_position()
+    }
+"#;
+
+fn sleeve(label: &str, symbol: &str, qty: i64) -> SleeveScript {
+    let mut params = HashMap::new();
+    params.insert("hold_days".to_string(), serde_json::json!(10));
+    params.insert("symbol".to_string(), serde_json::json!(symbol));
+    params.insert("qty".to_string(), serde_json::json!(qty));
+    SleeveScript {
+        label: label.to_string(),
+        source: BUY_AND_HOLD.to_string(),
+        params,
+        weight: 1.0,
+        risk_budget: 1.0,
+    }
+}
+
+fn config(margin: PoolMargin, limits: PortfolioLimits) -> PortfolioConfig {
+    PortfolioConfig {
+        capital: 100_000.0,
+        allocation: AllocationRule::FixedWeights,
+        rebalance: RebalanceFrequency::None,
+        risk_lookback: 20,
+        margin,
+        limits,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shared_pool_blocks_entry_that_fits_standalone() {
+    let loader = loader(&[("AAA", bars(|_| 100.0)), ("BBB", bars(|_| 100.0))]);
+    // Each sleeve starts with $50k; a Reg-T buy of 800 shares fits on its own,
+    // but two $80k cash positions cannot both fit in a $100k pool.
+    let out = run_portfolio_backtest(
+        vec![sleeve("a", "AAA", 800), sleeve("b", "BBB", 800)],
+        &config(PoolMargin::Cash, PortfolioLimits::default()),
+        &loader,
+    )
+    .await
+    .unwrap();
+
+    let a = &out.sleeves[0];
+    let b = &out.sleeves[1];
+    assert!((a.initial_capital - 50_000.0).abs() < 1e-6);
+    assert_eq!(a.blocked_entries, 0, "first sleeve should fill");
+    assert_eq!(b.blocked_entries, 1, "second sleeve should be blocked");
+    assert_eq!(a.trade_count, 1);
+    assert_eq!(b.trade_count, 0);
+    assert!(out
+        .result
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("[b]") && w.contains("blocked by portfolio")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn per_symbol_limit_applies_across_sleeves() {
+    let loader = loader(&[("AAA", bars(|_| 100.0))]);
+    let limits = PortfolioLimits {
+        max_positions_per_symbol: Some(1),
+        ..PortfolioLimits::default()
+    };
+    let out = run_portfolio_backtest(
+        vec![sleeve("first", "AAA", 10), sleeve("second", "AAA", 10)],
+        &config(PoolMargin::RegT, limits),
+        &loader,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(out.sleeves[0].blocked_entries, 0);
+    assert_eq!(out.sleeves[1].blocked_entries, 1);
+    assert_eq!(out.result.trade_count, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::cast_precision_loss)]
+async fn monthly_rebalance_moves_cash_without_changing_pnl() {
+    // AAA rallies through January so the "winner" sleeve outgrows its target
+    // weight; the idle sleeve holds cash.
+    let loader = loader(&[
+        ("AAA", bars(|i| 100.0 + i as f64)),
+        ("BBB", bars(|_| 100.0)),
+    ]);
+    let mut cfg = config(PoolMargin::RegT, PortfolioLimits::default());
+    cfg.rebalance = RebalanceFrequency::Monthly;
+    let out = run_portfolio_backtest(
+        vec![sleeve("winner", "AAA", 100), sleeve("idle", "BBB", 0)],
+        &cfg,
+        &loader,
+    )
+    .await
+    .unwrap();
+
+    assert!(
+        out.rebalances >= 1,
+        "expected a rebalance at the month turn"
+    );
+    let winner = &out.sleeves[0];
+    let idle = &out.sleeves[1];
+    assert!(
+        winner.net_transfers < 0.0,
+        "winner should fund the idle sleeve"
+    );
+    assert!((winner.net_transfers + idle.net_transfers).abs() < 1e-6);
+
+    // Transfers are internal: consolidated equity reflects sleeve P&L only.
+    let final_equity = out.result.equity_curve.last().unwrap().equity;
+    let total_pnl: f64 = out.sleeves.iter().map(|s| s.pnl).sum();
+    assert!(
+        (final_equity - (100_000.0 + total_pnl)).abs() < 1e-3,
+        "final equity {final_equity} vs capital + P&L {}",
+        100_000.0 + total_pnl
+    );
+    assert!(winner.pnl > 0.0);
+    assert!(idle.pnl.abs() < 1e-6);
+}
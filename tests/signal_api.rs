@@ -0,0 +1,211 @@
+//! Integration tests for the `/signals/*` REST endpoints.
+//!
+//! Uses `tower::ServiceExt::oneshot()` to drive the router in-process.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use optopsy_mcp::server::router::build_api_router;
+use optopsy_mcp::server::state::AppState;
+use tower::ServiceExt;
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Local helpers
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// Send a request through a fresh router and return status + parsed JSON body.
+async fn send(
+    state: &AppState,
+    method: &str,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder().method(method).uri(path);
+    let body = match body {
+        Some(json) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(json.to_string())
+        }
+        None => Body::empty(),
+    };
+    let resp = build_api_router(state.clone())
+        .oneshot(builder.body(body).expect("build request"))
+        .await
+        .expect("oneshot failed");
+    let status = resp.status();
+    let bytes = resp
+        .into_body()
+        .collect()
+        .await
+        .expect("collect body")
+        .to_bytes();
+    let json = serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(&bytes).into()));
+    (status, json)
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Tests
+// ──────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn signal_crud_and_versioning() {
+    let (state, _tmp) = common::test_app_state();
+
+    let (status, body) = send(
+        &state,
+        "POST",
+        "/signals",
+        Some(serde_json::json!({
+            "name": "risk_on",
+            "spec": "close > sma(close, 200)",
+            "description": "Above the 200-day average"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "body: {body}");
+    assert_eq!(body["version"], 1);
+    assert_eq!(body["spec"]["formula"], "close > sma(close, 200)");
+
+    // Creating the same name again conflicts — new versions go through PUT
+    let (status, _) = send(
+        &state,
+        "POST",
+        "/signals",
+        Some(serde_json::json!({"name": "risk_on", "spec": "close > open"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = send(
+        &state,
+        "PUT",
+        "/signals/risk_on",
+        Some(serde_json::json!({"spec": "close > sma(close, 100)"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["version"], 2);
+
+    let (_, latest) = send(&state, "GET", "/signals/risk_on", None).await;
+    assert_eq!(latest["version"], 2);
+    let (_, pinned) = send(&state, "GET", "/signals/risk_on?version=1", None).await;
+    assert_eq!(pinned["spec"]["formula"], "close > sma(close, 200)");
+    let (_, versions) = send(&state, "GET", "/signals/risk_on/versions", None).await;
+    assert_eq!(versions.as_array().map(Vec::len), Some(2));
+    let (_, list) = send(&state, "GET", "/signals", None).await;
+    assert_eq!(list.as_array().map(Vec::len), Some(1));
+
+    let (status, _) = send(&state, "DELETE", "/signals/risk_on", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&state, "GET", "/signals/risk_on", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(
+        &state,
+        "PUT",
+        "/signals/risk_on",
+        Some(serde_json::json!({"spec": "close > open"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn signal_save_is_validated() {
+    let (state, _tmp) = common::test_app_state();
+
+    let cases = [
+        serde_json::json!({"name": "bad name", "spec": "close > open"}),
+        serde_json::json!({"name": "vol", "spec": "volume > sma(volume, 20)"}),
+        serde_json::json!({"name": "dangling", "spec": {"type": "Saved", "name": "missing"}}),
+        serde_json::json!({"name": "loop", "spec": {
+            "type": "Or",
+            "left": "close > open",
+            "right": {"type": "Saved", "name": "loop"}
+        }}),
+    ];
+    for case in cases {
+        let (status, body) = send(&state, "POST", "/signals", Some(case.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{case} -> {body}");
+    }
+    let (_, list) = send(&state, "GET", "/signals", None).await;
+    assert_eq!(list.as_array().map(Vec::len), Some(0));
+}
+
+#[tokio::test]
+async fn signal_save_rejects_indirect_cycles() {
+    let (state, _tmp) = common::test_app_state();
+
+    for (name, spec) in [
+        ("b", serde_json::json!("close > open")),
+        ("a", serde_json::json!({"type": "Saved", "name": "b"})),
+    ] {
+        let (status, body) = send(
+            &state,
+            "POST",
+            "/signals",
+            Some(serde_json::json!({"name": name, "spec": spec})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{name}: {body}");
+    }
+
+    // `b -> a -> b` would make both signals unresolvable
+    let (status, body) = send(
+        &state,
+        "PUT",
+        "/signals/b",
+        Some(serde_json::json!({"spec": {"type": "Saved", "name": "a"}})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "body: {body}");
+    assert!(body.to_string().contains("Circular"), "body: {body}");
+
+    let (_, latest) = send(&state, "GET", "/signals/b", None).await;
+    assert_eq!(latest["version"], 1);
+}
+
+#[tokio::test]
+async fn saved_signals_resolve_into_strategies() {
+    let (state, _tmp) = common::test_app_state();
+
+    for (name, spec) in [
+        ("trend", serde_json::json!("close > sma(close, 50)")),
+        ("momentum", serde_json::json!("pct_change(close, 20) > 0")),
+        (
+            "risk_on",
+            serde_json::json!({
+                "type": "And",
+                "left": {"type": "Saved", "name": "trend"},
+                "right": {"type": "Saved", "name": "momentum"}
+            }),
+        ),
+    ] {
+        let (status, body) = send(
+            &state,
+            "POST",
+            "/signals",
+            Some(serde_json::json!({"name": name, "spec": spec})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{name}: {body}");
+    }
+
+    let source = "strategy \"Risk On\"\n  interval daily\n\nasset symbol = \"SPY\"\n\non each bar\n  skip when has positions\n  when signal \"risk_on\" then\n    Buy 10 shares of symbol next bar at market\n";
+    let rhai = optopsy_mcp::tools::run_script::maybe_transpile(
+        source.to_string(),
+        state.server.signal_store.as_deref(),
+    )
+    .expect("saved signal should resolve");
+    assert!(
+        rhai.contains("ctx.indicators_ready([\"sma:50\"])"),
+        "{rhai}"
+    );
+    assert!(rhai.contains("ctx.bar_idx >= 20"), "{rhai}");
+
+    let err = optopsy_mcp::tools::run_script::maybe_transpile(source.to_string(), None)
+        .expect_err("no library configured");
+    assert!(format!("{err:#}").contains("risk_on"), "{err:#}");
+}
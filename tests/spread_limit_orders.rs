@@ -0,0 +1,191 @@
+//! Integration tests for working limit orders on option spreads.
+//!
+//! A `spread_limit` entry rests below the spread's mid, steps toward the
+//! natural each bar, and fills only once the combined legs' natural is at or
+//! inside the limit — so fills depend on how the quotes move, not the bar.
+
+mod common;
+
+use chrono::NaiveDate;
+use polars::prelude::*;
+
+use optopsy_mcp::data::parquet::DATETIME_COL;
+use optopsy_mcp::scripting::engine::{run_script_backtest, ScriptBacktestResult};
+
+use common::MemoryLoader;
+
+fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, day).unwrap()
+}
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    d(y, m, day).and_hms_opt(0, 0, 0).unwrap()
+}
+
+const EXPIRATION: (i32, u32, u32) = (2024, 3, 15);
+const DAYS: std::ops::RangeInclusive<u32> = 2..=12;
+
+/// Flat $100 underlying from Jan 2 through Jan 12.
+fn bars_df() -> DataFrame {
+    let datetimes: Vec<chrono::NaiveDateTime> = DAYS.map(|day| dt(2024, 1, day)).collect();
+    let n = datetimes.len();
+    let price = vec![100.0; n];
+    df! {
+        "datetime" => DatetimeChunked::from_naive_datetime(
+            PlSmallStr::from("datetime"),
+            datetimes,
+            TimeUnit::Microseconds,
+        ).into_column().take_materialized_series(),
+        "open" => &price,
+        "high" => &price,
+        "low" => &price,
+        "close" => &price,
+        "volume" => &vec![1e6; n],
+    }
+    .unwrap()
+}
+
+/// Quotes for the 100/105 call spread each day: `quote(day)` returns
+/// `(long_bid, long_ask, short_bid, short_ask)`.
+fn options_df(quote: impl Fn(u32) -> (f64, f64, f64, f64)) -> DataFrame {
+    let (y, m, day) = EXPIRATION;
+    let mut dates = Vec::new();
+    let mut types = Vec::new();
+    let mut strikes = Vec::new();
+    let mut bids = Vec::new();
+    let mut asks = Vec::new();
+    let mut deltas = Vec::new();
+    for today in DAYS {
+        let (lb, la, sb, sa) = quote(today);
+        for (strike, bid, ask, delta) in [(100.0, lb, la, 0.50), (105.0, sb, sa, 0.30)] {
+            dates.push(dt(2024, 1, today));
+            types.push("c");
+            strikes.push(strike);
+            bids.push(bid);
+            asks.push(ask);
+            deltas.push(delta);
+        }
+    }
+    let expirations = vec![d(y, m, day); dates.len()];
+    let mut df = df! {
+        DATETIME_COL => &dates,
+        "option_type" => &types,
+        "strike" => &strikes,
+        "bid" => &bids,
+        "ask" => &asks,
+        "delta" => &deltas,
+    }
+    .unwrap();
+    let exp_col =
+        DateChunked::from_naive_date(PlSmallStr::from("expiration"), expirations).into_column();
+    df.with_column(exp_col).unwrap();
+    df
+}
+
+/// Submit one 100/105 call debit spread on the first bar as `order`, then
+/// close it two days after it fills.
+fn script(order: &str) -> String {
+    format!(
+        r#"
+        fn config() {{
+            #{{
+                symbol: "TEST",
+                capital: 100000.0,
+                interval: "daily",
+                data: #{{ ohlcv: true, options: true }},
+            }}
+        }}
+
+        fn on_bar(ctx) {{
+            if ctx.bar_idx == 0 {{
+                let spread = #{{
+                    action: "open_spread",
+                    spread: ctx.build_strategy([
+                        #{{ side: "long", option_type: "call", strike: 100.0, dte: 70 }},
+                        #{{ side: "short", option_type: "call", strike: 105.0, dte: 70 }},
+                    ]),
+                    qty: 1,
+                }};
+                return [{order}];
+            }}
+            []
+        }}
+
+        fn on_exit_check(ctx, pos) {{
+            if pos.days_held >= 2 {{
+                return close_position("done");
+            }}
+            hold_position()
+        }}
+    "#
+    )
+}
+
+async fn run(order: &str, quote: impl Fn(u32) -> (f64, f64, f64, f64)) -> ScriptBacktestResult {
+    let loader = MemoryLoader {
+        ohlcv: bars_df(),
+        options: options_df(quote),
+        ..MemoryLoader::default()
+    };
+    let params = std::collections::HashMap::new();
+    run_script_backtest(&script(order), &params, &loader, None, None, None)
+        .await
+        .unwrap()
+}
+
+/// Mid 2.10, natural 2.40 every day.
+fn steady(_day: u32) -> (f64, f64, f64, f64) {
+    (3.00, 3.40, 1.00, 1.20)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn market_order_fills_at_slippage_model_price() {
+    // The default script slippage assumes a fill at the mid, however wide the market
+    let out = run("spread", steady).await;
+    assert_eq!(out.result.trade_count, 1);
+    let entry = out.result.trade_log[0].entry_cost;
+    assert!((entry - 210.0).abs() < 1e-6, "entry cost {entry}");
+    assert!(out.result.working_orders.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn working_limit_expires_when_quotes_never_come_in() {
+    // Starts at 2.05 and steps 0.05 a bar: still 0.10 short of the natural
+    // when the five-bar ttl runs out.
+    let out = run("spread_limit(spread, 0.05, 0.05, 5)", steady).await;
+    assert_eq!(out.result.trade_count, 0, "{:?}", out.result.warnings);
+    let stats = out.result.working_orders.expect("working order stats");
+    assert_eq!(stats.submitted, 1);
+    assert_eq!(stats.filled, 0);
+    assert_eq!(stats.expired, 1);
+    assert!(stats.fill_rate.abs() < 1e-12);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn working_limit_fills_when_natural_crosses() {
+    // From Jan 5 (the third working bar, limit 2.15) the natural tightens to
+    // 2.10, inside the limit; the order fills at its limit, not the natural.
+    let out = run("spread_limit(spread, 0.05, 0.05, 5)", |day| {
+        if day >= 5 {
+            (3.00, 3.20, 1.10, 1.20)
+        } else {
+            steady(day)
+        }
+    })
+    .await;
+    assert_eq!(out.result.trade_count, 1, "{:?}", out.result.warnings);
+    let trade = &out.result.trade_log[0];
+    assert_eq!(trade.entry_datetime, dt(2024, 1, 5));
+    assert!(
+        (trade.entry_cost - 215.0).abs() < 1e-6,
+        "entry cost {}",
+        trade.entry_cost
+    );
+
+    let stats = out.result.working_orders.expect("working order stats");
+    assert_eq!((stats.submitted, stats.filled, stats.expired), (1, 1, 0));
+    assert!((stats.fill_rate - 1.0).abs() < 1e-12);
+    assert!((stats.avg_bars_to_fill - 3.0).abs() < 1e-12);
+    // Filled at 2.15 against 2.40 when the order started working
+    assert!((stats.avg_price_improvement - 0.25).abs() < 1e-9);
+}
@@ -0,0 +1,135 @@
+//! Integration tests for stock dividend cash flows.
+//!
+//! Verifies that stock held through the close before an ex-date receives the
+//! dividend in cash when long and pays it when short, and that the amount is
+//! reported in the trade's `dividend_pnl` and included in its P&L.
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use optopsy_mcp::data::adjustment_store::DividendRow;
+use optopsy_mcp::scripting::engine::run_script_backtest;
+use optopsy_mcp::scripting::types::OhlcvBar;
+
+use common::{bars_to_df, MemoryLoader};
+
+fn dt(y: i32, m: u32, day: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+fn default_params() -> HashMap<String, serde_json::Value> {
+    let mut params = HashMap::new();
+    params.insert("symbol".to_string(), serde_json::json!("TEST"));
+    params.insert("CAPITAL".to_string(), serde_json::json!(100_000.0));
+    params
+}
+
+/// Flat 100.0 bars on Jan 2, 3, 4 and 8, so every trade's price P&L is zero.
+fn flat_bars() -> Vec<OhlcvBar> {
+    [2, 3, 4, 8]
+        .into_iter()
+        .map(|day| OhlcvBar {
+            datetime: dt(2024, 1, day),
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            close: 100.0,
+            volume: 1e6,
+        })
+        .collect()
+}
+
+/// $1.00 per share going ex on Jan 4, plus one before the backtest starts.
+fn loader() -> MemoryLoader {
+    MemoryLoader {
+        ohlcv: bars_to_df(&flat_bars()),
+        dividends: vec![
+            DividendRow {
+                symbol: "TEST".to_string(),
+                date: NaiveDate::from_ymd_opt(2023, 12, 15).unwrap(),
+                amount: 0.90,
+            },
+            DividendRow {
+                symbol: "TEST".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 1, 4).unwrap(),
+                amount: 1.00,
+            },
+        ],
+        ..MemoryLoader::default()
+    }
+}
+
+fn stock_script(order: &str) -> String {
+    format!(
+        r#"
+        fn config() {{
+            #{{
+                symbol: params.symbol,
+                capital: params.CAPITAL,
+                interval: "daily",
+                auto_close_on_end: true,
+                data: #{{ ohlcv: true, options: false }},
+            }}
+        }}
+
+        fn on_bar(ctx) {{
+            if ctx.bar_idx == 0 {{
+                return [{order}("TEST", 100)];
+            }}
+            []
+        }}
+    "#
+    )
+}
+
+/// Bought on Jan 3 and held through the Jan 4 ex-date: +$100 of dividends.
+#[tokio::test(flavor = "multi_thread")]
+async fn long_stock_receives_dividend() {
+    let result = run_script_backtest(
+        &stock_script("buy_stock"),
+        &default_params(),
+        &loader(),
+        None,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.result.trade_count, 1);
+    let trade = &result.result.trade_log[0];
+    assert_eq!(trade.dividend_pnl, Some(100.0));
+    assert_eq!(trade.stock_pnl, Some(0.0));
+    assert!((trade.pnl - 100.0).abs() < 1e-9, "pnl = {}", trade.pnl);
+
+    let final_equity = result.result.equity_curve.last().unwrap().equity;
+    assert!((final_equity - 100_100.0).abs() < 1e-9);
+}
+
+/// Shorted on Jan 3 and held through the Jan 4 ex-date: −$100 owed to the lender.
+#[tokio::test(flavor = "multi_thread")]
+async fn short_stock_pays_dividend() {
+    let result = run_script_backtest(
+        &stock_script("sell_stock"),
+        &default_params(),
+        &loader(),
+        None,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let trade = &result.result.trade_log[0];
+    assert_eq!(trade.dividend_pnl, Some(-100.0));
+    assert!((trade.pnl + 100.0).abs() < 1e-9, "pnl = {}", trade.pnl);
+
+    let final_equity = result.result.equity_curve.last().unwrap().equity;
+    assert!((final_equity - 99_900.0).abs() < 1e-9);
+}
@@ -408,3 +408,104 @@ async fn sse_stream_delivers_events() {
         "SSE body should contain 'done' event, got: {sse_text}"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sse_stream_replays_from_last_event_id() {
+    let (state, _tmp, strategy_id) = common::test_app_state_with_strategy();
+    let app = build_api_router(state);
+
+    let body = serde_json::json!({
+        "strategy": strategy_id,
+        "params": {
+            "symbol": "SPY"
+        }
+    })
+    .to_string();
+    let (status, resp_body) = send(app.clone(), post_json("/tasks/backtest", &body)).await;
+    assert_eq!(status, StatusCode::OK);
+    let submit_json: serde_json::Value = serde_json::from_str(&resp_body).unwrap();
+    let task_id = submit_json["task_id"].as_str().unwrap().to_string();
+
+    // Wait for the task to finish via a normal stream
+    let stream = |last_event_id: Option<&str>| {
+        let mut builder = Request::builder()
+            .method("GET")
+            .uri(format!("/tasks/{task_id}/stream"));
+        if let Some(id) = last_event_id {
+            builder = builder.header("last-event-id", id);
+        }
+        builder.body(Body::empty()).unwrap()
+    };
+    let (_, first) = tokio::time::timeout(
+        std::time::Duration::from_secs(30),
+        send(app.clone(), stream(None)),
+    )
+    .await
+    .expect("SSE stream timed out after 30s");
+    assert!(first.contains("event: done"), "got: {first}");
+
+    // Reconnecting from the start replays the full lifecycle with ids
+    let (_, replayed) = tokio::time::timeout(
+        std::time::Duration::from_secs(30),
+        send(app, stream(Some("0"))),
+    )
+    .await
+    .expect("SSE replay timed out after 30s");
+    for event in ["queued", "started", "done"] {
+        assert!(
+            replayed.contains(&format!("event: {event}")),
+            "replay should contain '{event}', got: {replayed}"
+        );
+    }
+    assert!(replayed.contains("id: 1\n"), "got: {replayed}");
+    let queued = replayed.find("event: queued").unwrap();
+    let done = replayed.find("event: done").unwrap();
+    assert!(queued < done);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn firehose_stream_tags_events_with_task_id() {
+    let (state, _tmp, strategy_id) = common::test_app_state_with_strategy();
+    let app = build_api_router(state);
+
+    let body = serde_json::json!({
+        "strategy": strategy_id,
+        "params": {
+            "symbol": "SPY"
+        }
+    })
+    .to_string();
+    let (status, resp_body) = send(app.clone(), post_json("/tasks/backtest", &body)).await;
+    assert_eq!(status, StatusCode::OK);
+    let submit_json: serde_json::Value = serde_json::from_str(&resp_body).unwrap();
+    let task_id = submit_json["task_id"].as_str().unwrap().to_string();
+
+    let req = Request::builder()
+        .method("GET")
+        .uri("/tasks/stream")
+        .header("last-event-id", "0")
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.oneshot(req).await.expect("oneshot failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // The firehose never closes; read frames until this task's "done"
+    let mut body = resp.into_body();
+    let mut text = String::new();
+    tokio::time::timeout(std::time::Duration::from_secs(30), async {
+        while !text.contains("event: done") {
+            let frame = body.frame().await.expect("stream ended").expect("frame");
+            if let Some(data) = frame.data_ref() {
+                text.push_str(&String::from_utf8_lossy(data));
+            }
+        }
+    })
+    .await
+    .expect("firehose timed out after 30s");
+
+    assert!(text.contains("event: queued"), "got: {text}");
+    assert!(
+        text.contains(&format!(r#""task_id":"{task_id}""#)),
+        "firehose data should be tagged with the task id, got: {text}"
+    );
+}
@@ -10,6 +10,7 @@ use anyhow::Result;
 use chrono::NaiveDate;
 use polars::prelude::*;
 
+use optopsy_mcp::engine::cpcv::CpcvConfig;
 use optopsy_mcp::engine::walk_forward::{
     self as wf_engine, WalkForwardParams, WfMode, WfObjective,
 };
@@ -221,6 +222,7 @@ async fn walk_forward_rolling_end_to_end() {
         profile: None,
         script_source: None,
         base_params: None,
+        cpcv: CpcvConfig::default(),
     };
 
     let no_cancel: Box<dyn Fn() -> bool + Send + Sync> = Box::new(|| false);
@@ -316,6 +318,7 @@ async fn walk_forward_anchored_end_to_end() {
         profile: None,
         script_source: None,
         base_params: None,
+        cpcv: CpcvConfig::default(),
     };
 
     let no_cancel: Box<dyn Fn() -> bool + Send + Sync> = Box::new(|| false);
@@ -339,6 +342,65 @@ async fn walk_forward_anchored_end_to_end() {
     }
 }
 
+/// End-to-end walk-forward: combinatorial purged cross-validation mode.
+#[tokio::test(flavor = "multi_thread")]
+async fn walk_forward_cpcv_end_to_end() {
+    let bars = make_walk_forward_bars();
+    let loader = DateFilteringLoader {
+        ohlcv_df: bars_to_df(&bars),
+    };
+
+    let mut params_grid = HashMap::new();
+    params_grid.insert(
+        "BB_PERIOD".to_string(),
+        vec![serde_json::json!(15), serde_json::json!(20)],
+    );
+
+    let params = WalkForwardParams {
+        strategy: "bb_mean_reversion".to_string(),
+        symbol: "TEST".to_string(),
+        capital: 100_000.0,
+        params_grid,
+        objective: WfObjective::Sharpe,
+        n_windows: 5,
+        mode: WfMode::Cpcv,
+        train_pct: 0.70,
+        start_date: None,
+        end_date: None,
+        profile: None,
+        script_source: None,
+        base_params: None,
+        cpcv: CpcvConfig {
+            n_test_groups: 2,
+            embargo_bars: 3,
+        },
+    };
+
+    let no_cancel: Box<dyn Fn() -> bool + Send + Sync> = Box::new(|| false);
+    let result = wf_engine::execute(params, &loader, &no_cancel, |_, _| {}).await;
+    assert!(
+        result.is_ok(),
+        "CPCV walk-forward should succeed: {:?}",
+        result.err()
+    );
+
+    let result = result.unwrap();
+    assert_eq!(result.mode, "cpcv");
+    // C(5, 2) = 10 splits, C(4, 1) = 4 paths
+    assert_eq!(result.windows.len(), 10);
+    let cpcv = result.cpcv.expect("CPCV summary should be present");
+    assert_eq!(cpcv.n_splits, 10);
+    assert_eq!(cpcv.n_paths, 4);
+    assert_eq!(cpcv.paths.len(), 4);
+    assert!(cpcv.oos_sharpe_p5 <= cpcv.oos_sharpe_median);
+    assert!(cpcv.oos_sharpe_median <= cpcv.oos_sharpe_p95);
+    assert!((0.0..=1.0).contains(&cpcv.prob_negative_sharpe));
+    assert!(
+        !result.stitched_equity.is_empty(),
+        "Representative path equity should not be empty"
+    );
+}
+
 /// Walk-forward with empty `params_grid` still runs (cartesian product of nothing = 1 combo).
 #[tokio::test(flavor = "multi_thread")]
 async fn walk_forward_empty_grid_runs_with_base_params() {
@@ -361,6 +423,7 @@ async fn walk_forward_empty_grid_runs_with_base_params() {
         profile: None,
         script_source: None,
         base_params: None,
+        cpcv: CpcvConfig::default(),
     };
 
     // Empty grid produces 1 combo (base params only) — should succeed